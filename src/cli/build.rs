@@ -1,56 +1,185 @@
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 use tracing::info;
 
-use crate::mount::handler::{flush_mount_to_disk, on_project_discovery};
-use crate::mount::{MountTable, MountedEvent};
+use crate::cli::progress::{ProgressReporter, Verbosity};
+use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::mount::handler::{
+    IndexedFile, drop_unmounted_projects, flush_mount_to_disk, index_changed_since,
+    on_project_discovery,
+};
+use crate::mount::{DEFAULT_MAX_FILE_BYTES, MountMode, MountTable, MountedEvent};
+use crate::parser::filter::FilterPolicy;
+use crate::parser::languages::detect_language_for_path;
+use crate::parser::treesitter::parse_file_checked;
 use crate::server::db::SearchDb;
 
+/// File name of the persisted search database inside the root `.codeindex/`.
+pub const PERSISTED_DB_FILE: &str = "index.sqlite";
+
 /// Result type for build_index_to_db: (MountTable, SearchDb)
 pub type BuildResult = (Arc<Mutex<MountTable>>, Arc<Mutex<SearchDb>>);
 
+/// How [`build_index_to_db`] indexes the workspace and where it keeps the result.
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// Create FTS5 tables for search (serve mode). Off in build mode to save
+    /// memory on large repos.
+    pub enable_fts: bool,
+    /// Try loading each project from its .codeindex/ first (serve) instead of
+    /// always re-indexing (build).
+    pub load_from_cache: bool,
+    /// Keep the database in `.codeindex/index.sqlite` under the first root (FTS
+    /// always enabled) and reuse it on the next run, re-parsing only files whose
+    /// hash changed. Projects it holds that discovery no longer mounts are dropped.
+    /// When another process holds the database, an in-memory one is used instead.
+    pub persist: bool,
+    /// Unless `persist`, tokenize FTS5 by trigrams so terms match inside words
+    /// (see [`SearchDb::new_trigram`]).
+    pub substring: bool,
+    /// Also index files excluded by `.gitignore` (`--no-gitignore`); built-in
+    /// exclusions and `.codeixignore` still apply.
+    pub index_ignored: bool,
+    /// Walk into symlinked directories, each real directory once (`--follow-symlinks`).
+    pub follow_symlinks: bool,
+    /// Files larger than this are recorded without symbols, texts or references
+    /// (`--max-file-bytes`).
+    pub max_file_bytes: u64,
+    /// Threads parsing the files of each project walk (`--jobs`, 0 = one per CPU).
+    pub jobs: usize,
+    /// Read and write indexes (and their lock files) under this directory instead
+    /// of each project's `.codeindex/` (`--index-dir`, see [`MountTable::index_dir`]).
+    pub index_dir: Option<PathBuf>,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            enable_fts: false,
+            load_from_cache: false,
+            persist: false,
+            substring: false,
+            index_ignored: false,
+            follow_symlinks: false,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            jobs: 1,
+            index_dir: None,
+        }
+    }
+}
+
 /// Build the index into a database without flushing to disk.
 /// Returns MountTable + SearchDb for both build (flush to disk) and serve (keep in memory).
 ///
 /// Uses `on_project_discovery` which:
-/// 1. If load_from_cache=true: loads from .codeindex/ if it exists
+/// 1. If `options.load_from_cache`: loads from .codeindex/ if it exists
 /// 2. Otherwise indexes files (stopping at subproject boundaries)
 /// 3. Recursively handles discovered subprojects
 ///
-/// Parameters:
-/// - `enable_fts`: If true, creates FTS5 tables for search (serve mode).
-///   If false, skips FTS to reduce memory on large repos (build mode).
-/// - `load_from_cache`: If true (serve), try loading from .codeindex/ first.
-///   If false (build), always re-index.
+/// Each of `paths` is a workspace root; with several, project strings are
+/// prefixed by the root's directory name (see [`MountTable::with_roots`]).
+///
 /// - `tx`: If provided, initializes notify watchers during walk so directories
 ///   are watched immediately (single walk strategy for serve --watch).
+/// - `progress`: If provided, receives an `IndexedFile` for each file walked
+///   (`build` renders them with a [`ProgressReporter`]).
 pub fn build_index_to_db(
-    path: &Path,
-    enable_fts: bool,
-    load_from_cache: bool,
+    paths: &[PathBuf],
+    options: &BuildOptions,
     tx: Option<Sender<MountedEvent>>,
+    progress: Option<&Sender<IndexedFile>>,
 ) -> Result<BuildResult> {
-    let root = path
-        .canonicalize()
-        .with_context(|| format!("cannot resolve path: {}", path.display()))?;
+    let BuildOptions {
+        enable_fts,
+        load_from_cache,
+        persist,
+        substring,
+        index_ignored,
+        follow_symlinks,
+        max_file_bytes,
+        jobs,
+        ref index_dir,
+    } = *options;
+
+    let roots = paths
+        .iter()
+        .map(|path| {
+            path.canonicalize()
+                .with_context(|| format!("cannot resolve path: {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let root = roots.first().context("no root directory given")?.clone();
 
-    info!("building index at {}", root.display());
+    for root in &roots {
+        info!("building index at {}", root.display());
+    }
 
     // Create mount table and database
-    let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
-    let db = Arc::new(Mutex::new(if enable_fts {
+    let mut mount_table = MountTable::with_roots(roots.clone())?;
+    mount_table.set_index_ignored(index_ignored);
+    mount_table.set_follow_symlinks(follow_symlinks);
+    mount_table.set_max_file_bytes(max_file_bytes);
+    mount_table.set_jobs(jobs);
+    if let Some(index_dir) = index_dir {
+        std::fs::create_dir_all(index_dir)
+            .with_context(|| format!("failed to create {}", index_dir.display()))?;
+        let index_dir = index_dir
+            .canonicalize()
+            .with_context(|| format!("cannot resolve path: {}", index_dir.display()))?;
+        mount_table.set_index_dir(Some(index_dir));
+    }
+    let root_index_dir = mount_table.index_dir(&root);
+    let mount_table = Arc::new(Mutex::new(mount_table));
+    let persisted = if persist {
+        std::fs::create_dir_all(&root_index_dir)
+            .with_context(|| format!("failed to create {}", root_index_dir.display()))?;
+        let db_path = root_index_dir.join(PERSISTED_DB_FILE);
+        let db =
+            SearchDb::open_file(&db_path).context("failed to open persisted search database")?;
+        if db.is_none() {
+            tracing::warn!(
+                "{} is in use by another process; using an in-memory index",
+                db_path.display()
+            );
+        }
+        db
+    } else {
+        None
+    };
+    let persist = persisted.is_some();
+    let db = Arc::new(Mutex::new(if let Some(db) = persisted {
+        db
+    } else if enable_fts && substring {
+        SearchDb::new_trigram().context("failed to create search database")?
+    } else if enable_fts {
         SearchDb::new().context("failed to create search database")?
     } else {
         SearchDb::new_no_fts().context("failed to create search database")?
     }));
 
-    // Process root project (will recursively discover and handle subprojects)
+    // Process each root project (will recursively discover and handle subprojects)
     // Pass load_from_cache and tx (for notify watchers during walk, if provided)
-    on_project_discovery(&root, &mount_table, &db, load_from_cache, tx)
-        .context("failed to process root project")?;
+    for root in &roots {
+        on_project_discovery(
+            root,
+            &mount_table,
+            &db,
+            load_from_cache,
+            tx.clone(),
+            progress,
+        )
+        .with_context(|| format!("failed to process root project {}", root.display()))?;
+    }
+
+    // A persisted database may hold projects deleted since the last run
+    if persist {
+        drop_unmounted_projects(&mount_table, &db)?;
+    }
 
     Ok((mount_table, db))
 }
@@ -60,11 +189,54 @@ pub fn build_index_to_db(
 ///
 /// Discovers .git/ boundaries and creates separate .codeindex/ for each
 /// project found. Root is always treated as a project (with or without .git/).
-pub fn build_index(path: &Path) -> Result<()> {
+///
+/// `options` are used as given (see [`BuildOptions`]), except that FTS is
+/// always off and `load_from_cache` follows `since`. With `options.persist`,
+/// only files whose hash changed since the last run are re-parsed.
+///
+/// With `since` (a git revision), each project's existing `.codeindex/` is loaded
+/// and only the files `git diff <since>..HEAD` lists are re-indexed; projects
+/// without an index are indexed in full, and those where the diff fails are walked.
+/// Projects whose lock is held by another process are left to that process.
+///
+/// Unless `verbosity` is quiet, progress and a summary are printed on stderr.
+pub fn build_index(
+    path: &Path,
+    options: &BuildOptions,
+    since: Option<&str>,
+    verbosity: Verbosity,
+) -> Result<()> {
+    let reporter = (verbosity != Verbosity::Quiet).then(|| ProgressReporter::start(verbosity));
+    let progress = reporter.as_ref().map(ProgressReporter::sender);
+
     // Build mode: disable FTS to reduce memory on large repos
-    // load_from_cache=false: always re-index (ignore .codeindex/)
+    // load_from_cache=false: always re-index (ignore .codeindex/), unless
+    // building incrementally from the last index
     // tx=None: no watcher
-    let (mount_table, db) = build_index_to_db(path, false, false, None)?;
+    let options = BuildOptions {
+        enable_fts: false,
+        load_from_cache: since.is_some(),
+        ..options.clone()
+    };
+    let (mount_table, db) = build_index_to_db(&[path.to_path_buf()], &options, None, progress)?;
+
+    if let Some(since) = since {
+        let roots: Vec<PathBuf> = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?
+            .iter()
+            .filter(|(_, mount)| mount.mode == MountMode::ReadWrite)
+            .map(|(root, _)| root.clone())
+            .collect();
+        for root in &roots {
+            index_changed_since(root, &mount_table, &db, since, progress)
+                .with_context(|| format!("failed to update {} since {since}", root.display()))?;
+        }
+    }
+
+    if let Some(reporter) = reporter {
+        reporter.finish();
+    }
 
     // Flush each dirty mount to disk
     let mt = mount_table
@@ -117,6 +289,218 @@ pub fn build_index(path: &Path) -> Result<()> {
 
 /// Run the `build` subcommand: scan the directory tree, parse files with
 /// tree-sitter, and write the `.codeindex/` output.
-pub fn run(path: &Path) -> Result<()> {
-    build_index(path)
+pub fn run(
+    path: &Path,
+    options: &BuildOptions,
+    since: Option<&str>,
+    verbosity: Verbosity,
+) -> Result<()> {
+    build_index(path, options, since, verbosity)
+}
+
+/// One extraction result printed by `build --stdin`, tagged like search results.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ParsedEntry {
+    Symbol(SymbolEntry),
+    Text(TextEntry),
+    Reference(ReferenceEntry),
+    /// The source has syntax errors from this line on (symbols may be incomplete)
+    #[serde(rename = "syntax_error")]
+    SyntaxError {
+        line: u32,
+    },
+}
+
+/// Parse one file's source read from `input` and write its symbols, texts and
+/// references to `output` as JSONL (one `{"type": ..., ...}` object per line),
+/// followed by a `{"type": "syntax_error", "line": N}` line if the tree has errors.
+///
+/// `path` names the file in the output and, without `lang`, picks the language
+/// from its name, extension or shebang. No mount, database or `.codeindex/` is involved.
+pub fn parse_to_jsonl(
+    path: &str,
+    lang: Option<&str>,
+    mut input: impl Read,
+    mut output: impl Write,
+) -> Result<()> {
+    let mut source = Vec::new();
+    input
+        .read_to_end(&mut source)
+        .context("failed to read source")?;
+
+    let lang = match lang {
+        Some(lang) => lang,
+        None => detect_language_for_path(Path::new(path), &source)
+            .with_context(|| format!("cannot detect the language of {path}; pass --lang"))?,
+    };
+    let parsed = parse_file_checked(&source, lang, path, &[], &FilterPolicy::default())?;
+
+    let entries = parsed
+        .symbols
+        .into_iter()
+        .map(ParsedEntry::Symbol)
+        .chain(parsed.texts.into_iter().map(ParsedEntry::Text))
+        .chain(parsed.references.into_iter().map(ParsedEntry::Reference))
+        .chain(
+            parsed
+                .error_line
+                .map(|line| ParsedEntry::SyntaxError { line }),
+        );
+    for entry in entries {
+        serde_json::to_writer(&mut output, &entry)?;
+        output.write_all(b"\n")?;
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// Run `build --stdin`: parse source piped on stdin as the file `path` and
+/// print the extraction results as JSONL on stdout.
+pub fn run_stdin(path: &str, lang: Option<&str>) -> Result<()> {
+    parse_to_jsonl(
+        path,
+        lang,
+        std::io::stdin().lock(),
+        std::io::stdout().lock(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::db::SearchQuery;
+
+    #[test]
+    fn test_persisted_db_drops_vanished_projects() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        let sub = root.join("libs/sub");
+        std::fs::create_dir_all(sub.join(".git")).unwrap();
+        std::fs::write(sub.join("lib.rs"), "pub fn sub_fn() {}\n").unwrap();
+
+        let projects = || {
+            let (mount_table, db) = build_index_to_db(
+                std::slice::from_ref(&root),
+                &BuildOptions {
+                    enable_fts: true,
+                    persist: true,
+                    ..BuildOptions::default()
+                },
+                None,
+                None,
+            )
+            .unwrap();
+            let projects = db.lock().unwrap().list_projects().unwrap();
+            // Release the locks before the next run
+            drop(mount_table);
+            projects
+        };
+
+        assert_eq!(projects(), vec!["", "libs/sub"]);
+        std::fs::remove_dir_all(&sub).unwrap();
+        assert_eq!(projects(), vec![""]);
+    }
+
+    #[test]
+    fn test_second_persisted_build_leaves_the_first_db_alone() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("main.rs"), "fn main() {}\n").unwrap();
+        let sub = root.join("libs/sub");
+        std::fs::create_dir_all(sub.join(".git")).unwrap();
+        std::fs::write(sub.join("lib.rs"), "pub fn sub_fn() {}\n").unwrap();
+
+        let build = || {
+            build_index_to_db(
+                std::slice::from_ref(&root),
+                &BuildOptions {
+                    enable_fts: true,
+                    load_from_cache: true,
+                    persist: true,
+                    ..BuildOptions::default()
+                },
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        let (_first_mounts, first_db) = build();
+        let projects = first_db.lock().unwrap().list_projects().unwrap();
+        let files = first_db.lock().unwrap().file_paths("").unwrap();
+
+        // The root is read-only for the second build, which finds the database
+        // locked and so never drops the root project from the shared file
+        let (_second_mounts, second_db) = build();
+        assert!(
+            second_db
+                .lock()
+                .unwrap()
+                .list_projects()
+                .unwrap()
+                .is_empty()
+        );
+
+        let first_db = first_db.lock().unwrap();
+        assert_eq!(first_db.list_projects().unwrap(), projects);
+        assert_eq!(first_db.file_paths("").unwrap(), files);
+        assert_eq!(first_db.search(&SearchQuery::new("main")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_to_jsonl() {
+        let source = "/// Entry point\nfn main() {\n    run();\n}\n";
+        let mut out = Vec::new();
+        parse_to_jsonl("src/main.rs", None, source.as_bytes(), &mut out).unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let of_type = |ty: &str| lines.iter().filter(|l| l["type"] == ty).collect::<Vec<_>>();
+
+        let symbols = of_type("symbol");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0]["name"], "main");
+        assert_eq!(symbols[0]["file"], "src/main.rs");
+        assert!(of_type("text").iter().any(|t| t["kind"] == "docstring"));
+        assert!(of_type("reference").iter().any(|r| r["name"] == "run"));
+    }
+
+    #[test]
+    fn test_parse_to_jsonl_language() {
+        // --lang wins over the extension
+        let mut out = Vec::new();
+        parse_to_jsonl(
+            "buffer.txt",
+            Some("python"),
+            "def f(): pass\n".as_bytes(),
+            &mut out,
+        )
+        .unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("\"name\":\"f\""));
+
+        let err = parse_to_jsonl("notes.unknown", None, "".as_bytes(), Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("pass --lang"));
+
+        let err = parse_to_jsonl("x", Some("cobol"), "".as_bytes(), Vec::new()).unwrap_err();
+        assert!(err.to_string().contains("unsupported language: cobol"));
+    }
+
+    #[test]
+    fn test_parse_to_jsonl_reports_syntax_errors() {
+        let source = "fn ok() {}\n\nfn broken( {\n";
+        let mut out = Vec::new();
+        parse_to_jsonl("src/lib.rs", None, source.as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let last: serde_json::Value = serde_json::from_str(out.lines().last().unwrap()).unwrap();
+        assert_eq!(last["type"], "syntax_error");
+        assert_eq!(last["line"], 3);
+        assert!(out.contains("\"name\":\"ok\""));
+    }
 }
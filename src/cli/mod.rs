@@ -1,3 +1,5 @@
 pub mod build;
+pub mod progress;
 pub mod query;
+pub mod remote;
 pub mod serve;
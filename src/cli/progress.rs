@@ -0,0 +1,168 @@
+//! Indexing progress of `codeix build`, rendered on stderr.
+//!
+//! The indexer only sends [`IndexedFile`] notifications down a channel; this
+//! module counts them on a background thread and draws the progress line, so
+//! nothing in `mount::handler` knows about terminals.
+
+use std::io::{IsTerminal, Write};
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::mount::handler::{IndexStatus, IndexedFile};
+
+/// Shortest interval between two redraws of the in-place progress line.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How much `codeix build` reports on stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Nothing but errors
+    Quiet,
+    /// A progress line while indexing (on a terminal), then a one-line summary
+    #[default]
+    Normal,
+    /// Also one line per file parsed or failed
+    Verbose,
+}
+
+/// Running totals of the files a build handled.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProgressCounts {
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    /// Symbols of the indexed files
+    pub symbols: usize,
+}
+
+impl ProgressCounts {
+    pub fn record(&mut self, file: &IndexedFile) {
+        match file.status {
+            IndexStatus::Indexed => self.indexed += 1,
+            IndexStatus::Unchanged => self.unchanged += 1,
+            IndexStatus::Failed => self.failed += 1,
+        }
+        self.symbols += file.symbol_count;
+    }
+
+    /// `"12 indexed, 3 unchanged, 1 failed, 340 symbols"`
+    pub fn summary(&self) -> String {
+        format!(
+            "{} indexed, {} unchanged, {} failed, {} symbols",
+            self.indexed, self.unchanged, self.failed, self.symbols
+        )
+    }
+}
+
+/// Counts and renders the notifications sent to [`ProgressReporter::sender`].
+pub struct ProgressReporter {
+    tx: Sender<IndexedFile>,
+    handle: JoinHandle<ProgressCounts>,
+    started: Instant,
+}
+
+impl ProgressReporter {
+    /// Start the rendering thread. The progress line is only drawn when stderr
+    /// is a terminal; per-file lines (`Verbose`) are printed either way.
+    pub fn start(verbosity: Verbosity) -> Self {
+        let (tx, rx) = mpsc::channel::<IndexedFile>();
+        let live = std::io::stderr().is_terminal();
+        let handle = std::thread::spawn(move || {
+            let mut counts = ProgressCounts::default();
+            let mut last_draw: Option<Instant> = None;
+            for file in rx {
+                counts.record(&file);
+                let mut stderr = std::io::stderr().lock();
+                if verbosity == Verbosity::Verbose && file.status != IndexStatus::Unchanged {
+                    let path = if file.project.is_empty() {
+                        file.path.clone()
+                    } else {
+                        format!("{}/{}", file.project, file.path)
+                    };
+                    let status = match file.status {
+                        IndexStatus::Failed => "failed ",
+                        _ => "indexed",
+                    };
+                    if live {
+                        let _ = write!(stderr, "\r\x1b[K");
+                    }
+                    let _ = writeln!(
+                        stderr,
+                        "{} {} ({} symbols)",
+                        status, path, file.symbol_count
+                    );
+                    last_draw = None;
+                }
+                if live && last_draw.is_none_or(|t| t.elapsed() >= REDRAW_INTERVAL) {
+                    let _ = write!(stderr, "\r\x1b[K{}", counts.summary());
+                    let _ = stderr.flush();
+                    last_draw = Some(Instant::now());
+                }
+            }
+            if live && last_draw.is_some() {
+                let _ = write!(std::io::stderr(), "\r\x1b[K");
+            }
+            counts
+        });
+        Self {
+            tx,
+            handle,
+            started: Instant::now(),
+        }
+    }
+
+    /// Channel to pass as the `progress` of an indexing call.
+    pub fn sender(&self) -> &Sender<IndexedFile> {
+        &self.tx
+    }
+
+    /// Wait for the notifications still queued, clear the progress line and
+    /// print the summary. Every clone of the sender must be dropped first.
+    pub fn finish(self) -> ProgressCounts {
+        drop(self.tx);
+        let counts = self.handle.join().unwrap_or_default();
+        eprintln!(
+            "{} in {:.1}s",
+            counts.summary(),
+            self.started.elapsed().as_secs_f64()
+        );
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(status: IndexStatus, symbol_count: usize) -> IndexedFile {
+        IndexedFile {
+            path: "src/lib.rs".to_string(),
+            project: String::new(),
+            symbol_count,
+            status,
+        }
+    }
+
+    #[test]
+    fn test_progress_counts() {
+        let mut counts = ProgressCounts::default();
+        counts.record(&file(IndexStatus::Indexed, 3));
+        counts.record(&file(IndexStatus::Indexed, 2));
+        counts.record(&file(IndexStatus::Unchanged, 0));
+        counts.record(&file(IndexStatus::Failed, 0));
+        assert_eq!(
+            counts.summary(),
+            "2 indexed, 1 unchanged, 1 failed, 5 symbols"
+        );
+
+        let reporter = ProgressReporter::start(Verbosity::Normal);
+        reporter
+            .sender()
+            .send(file(IndexStatus::Indexed, 4))
+            .unwrap();
+        let counts = reporter.finish();
+        assert_eq!(counts.indexed, 1);
+        assert_eq!(counts.symbols, 4);
+    }
+}
@@ -2,51 +2,332 @@
 //!
 //! Provides the same API as the MCP server but through an interactive command line.
 
+use std::cell::Cell;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::{Arc, mpsc};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use clap_repl::{ClapEditor, ReadCommandOutput};
+use rmcp::ErrorData as McpError;
 use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::CallToolResult;
+use serde::{Deserialize, Serialize};
 
-use crate::cli::build::build_index_to_db;
+use crate::cli::build::{BuildOptions, build_index_to_db};
+use crate::cli::remote;
 use crate::mount::MountedEvent;
-use crate::mount::handler::{flush_mount_to_disk, run_event_loop};
+use crate::mount::handler::{WatchOptions, flush_mount_to_disk, run_event_loop};
 use crate::server::mcp::{
-    CodeIndexServer, ExploreParams, GetCalleesParams, GetCallersParams, GetChildrenParams,
-    GetFileSymbolsParams, SearchParams, extract_result_text,
+    ApiDiffParams, AtParams, CallTreeParams, CodeIndexServer, CountParams, DefinitionParams,
+    DiffParams, ExploreParams, FindImportCyclesParams, FindUndocumentedParams, GetCalleesParams,
+    GetCallersParams, GetChildrenParams, GetFileSymbolsParams, GetSnippetParams, GrepParams,
+    ListSymbolsParams, ManifestParams, MembersParams, OutlineParams, ProjectsParams, RecentParams,
+    ReindexParams, ResolveParams, SearchParams, StatsParams, SymbolDiffParams, TreeParams,
+    UnreferencedParams, UnusedImportsParams, extract_result_text,
 };
+use crate::utils::format::OutputFormat;
 
 /// REPL commands matching the MCP tools.
 /// NOTE: When adding/removing/renaming tools, also update src/server/mcp.rs (tool implementations)
-#[derive(Debug, Parser)]
+///
+/// Serializes as `{"command": "get_callers", "params": {...}}`, the form
+/// `query --connect` sends to a running server (see [`crate::cli::remote`]).
+#[derive(Debug, Parser, Serialize, Deserialize)]
 #[command(name = "")]
+#[serde(tag = "command", content = "params", rename_all = "snake_case")]
 pub enum QueryCommand {
     /// Unified search across symbols, files, and texts
     Search(#[command(flatten)] SearchParams),
+    /// Search docstrings and comments only (search --preset docs)
+    Docs(#[command(flatten)] SearchParams),
+    /// Search file names and paths only (search --scope file)
+    Files(#[command(flatten)] SearchParams),
+    /// Count search matches without fetching them
+    Count(#[command(flatten)] CountParams),
+    /// Find a literal substring in comments, strings, and docstrings
+    Grep(#[command(flatten)] GrepParams),
     /// Get all symbols in a file
     GetFileSymbols(#[command(flatten)] GetFileSymbolsParams),
+    /// Print a range of lines of a file
+    GetSnippet(#[command(flatten)] GetSnippetParams),
+    /// Find the innermost symbol enclosing a line of a file
+    At(#[command(flatten)] AtParams),
+    /// Show the symbols of a file as an indented tree
+    Outline(#[command(flatten)] OutlineParams),
     /// Get children of a symbol
     GetChildren(#[command(flatten)] GetChildrenParams),
+    /// Get children of a parent symbol across all files of a project
+    Members(#[command(flatten)] MembersParams),
+    /// List all symbols of a project (optionally of one kind)
+    ListSymbols(#[command(flatten)] ListSymbolsParams),
     /// Explore project structure (files grouped by directory)
     Explore(#[command(flatten)] ExploreParams),
+    /// Show the directory tree with file counts per language
+    Tree(#[command(flatten)] TreeParams),
     /// Find callers of a symbol
     GetCallers(#[command(flatten)] GetCallersParams),
     /// Find what a symbol calls
     GetCallees(#[command(flatten)] GetCalleesParams),
+    /// Find transitive callers of a symbol (impact analysis)
+    CallTree(#[command(flatten)] CallTreeParams),
+    /// Find symbols without a docstring
+    FindUndocumented(#[command(flatten)] FindUndocumentedParams),
+    /// Find symbols that nothing references (dead-code candidates)
+    Unreferenced(#[command(flatten)] UnreferencedParams),
+    /// Resolve a reference name to candidate definitions
+    Resolve(#[command(flatten)] ResolveParams),
+    /// Show the source and docs of the best-ranked definition of a name
+    Definition(#[command(flatten)] DefinitionParams),
+    /// Find circular import dependencies between files
+    FindImportCycles(#[command(flatten)] FindImportCyclesParams),
+    /// List the imports of a file that the file never uses
+    UnusedImports(#[command(flatten)] UnusedImportsParams),
+    /// Compare a project's public API against another project or a saved index
+    ApiDiff(#[command(flatten)] ApiDiffParams),
+    /// List symbols added, removed, or moved relative to a saved index
+    SymbolDiff(#[command(flatten)] SymbolDiffParams),
+    /// Show index statistics per project (files, symbols by kind, languages)
+    Stats(#[command(flatten)] StatsParams),
+    /// Show a project's manifest (name, index format version, languages)
+    Manifest(#[command(flatten)] ManifestParams),
+    /// List mounted projects with their roots, modes, and watch state
+    Projects(#[command(flatten)] ProjectsParams),
+    /// List files by when they were last (re)indexed, most recent first
+    Recent(#[command(flatten)] RecentParams),
+    /// Force a re-parse of a file, a directory, or the whole project
+    Reindex(#[command(flatten)] ReindexParams),
+    /// Show what flushing would change in .codeindex/ (added/removed/modified files)
+    Diff(#[command(flatten)] DiffParams),
     /// Flush index to disk
     FlushIndex,
+    /// Switch output between "text" and "json" (JSON lines on stdout)
+    Format {
+        /// Output format: "json" or "text"
+        format: OutputFormat,
+    },
     /// Exit the REPL
     #[command(alias = "quit")]
     Exit,
 }
 
+impl QueryCommand {
+    /// Command name carried by each JSON line (the MCP tool name).
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            QueryCommand::Search(_) | QueryCommand::Docs(_) | QueryCommand::Files(_) => "search",
+            QueryCommand::Count(_) => "count",
+            QueryCommand::Grep(_) => "grep",
+            QueryCommand::GetFileSymbols(_) => "get_file_symbols",
+            QueryCommand::GetSnippet(_) => "get_snippet",
+            QueryCommand::At(_) => "at",
+            QueryCommand::Outline(_) => "outline",
+            QueryCommand::GetChildren(_) => "get_children",
+            QueryCommand::Members(_) => "members",
+            QueryCommand::ListSymbols(_) => "list_symbols",
+            QueryCommand::Explore(_) => "explore",
+            QueryCommand::Tree(_) => "tree",
+            QueryCommand::GetCallers(_) => "get_callers",
+            QueryCommand::GetCallees(_) => "get_callees",
+            QueryCommand::CallTree(_) => "call_tree",
+            QueryCommand::FindUndocumented(_) => "find_undocumented",
+            QueryCommand::Unreferenced(_) => "unreferenced",
+            QueryCommand::Resolve(_) => "resolve",
+            QueryCommand::Definition(_) => "definition",
+            QueryCommand::FindImportCycles(_) => "find_import_cycles",
+            QueryCommand::UnusedImports(_) => "unused_imports",
+            QueryCommand::ApiDiff(_) => "api_diff",
+            QueryCommand::SymbolDiff(_) => "symbol_diff",
+            QueryCommand::Stats(_) => "stats",
+            QueryCommand::Manifest(_) => "manifest",
+            QueryCommand::Projects(_) => "projects",
+            QueryCommand::Recent(_) => "recent",
+            QueryCommand::Reindex(_) => "reindex",
+            QueryCommand::Diff(_) => "diff",
+            QueryCommand::FlushIndex => "flush_index",
+            QueryCommand::Format { .. } => "format",
+            QueryCommand::Exit => "exit",
+        }
+    }
+
+    /// Make the command produce JSON, whatever `--format` it was given.
+    fn force_json(&mut self) {
+        let format = match self {
+            QueryCommand::Search(p) | QueryCommand::Docs(p) | QueryCommand::Files(p) => {
+                &mut p.format
+            }
+            QueryCommand::Grep(p) => &mut p.format,
+            QueryCommand::GetFileSymbols(p) => &mut p.format,
+            QueryCommand::GetSnippet(p) => &mut p.format,
+            QueryCommand::At(p) => &mut p.format,
+            QueryCommand::Outline(p) => &mut p.format,
+            QueryCommand::GetChildren(p) => &mut p.format,
+            QueryCommand::Members(p) => &mut p.format,
+            QueryCommand::ListSymbols(p) => &mut p.format,
+            QueryCommand::Explore(p) => &mut p.format,
+            QueryCommand::Tree(p) => &mut p.format,
+            QueryCommand::GetCallers(p) => &mut p.format,
+            QueryCommand::GetCallees(p) => &mut p.format,
+            QueryCommand::CallTree(p) => &mut p.format,
+            QueryCommand::FindUndocumented(p) => &mut p.format,
+            QueryCommand::Unreferenced(p) => &mut p.format,
+            QueryCommand::Resolve(p) => &mut p.format,
+            QueryCommand::Definition(p) => &mut p.format,
+            QueryCommand::FindImportCycles(p) => &mut p.format,
+            QueryCommand::UnusedImports(p) => &mut p.format,
+            QueryCommand::ApiDiff(p) => &mut p.format,
+            QueryCommand::SymbolDiff(p) => &mut p.format,
+            QueryCommand::Stats(p) => &mut p.format,
+            QueryCommand::Manifest(p) => &mut p.format,
+            QueryCommand::Projects(p) => &mut p.format,
+            QueryCommand::Recent(p) => &mut p.format,
+            QueryCommand::Diff(p) => &mut p.format,
+            // Plain values (a count, a message): wrapped as-is
+            QueryCommand::Count(_)
+            | QueryCommand::Reindex(_)
+            | QueryCommand::FlushIndex
+            | QueryCommand::Format { .. }
+            | QueryCommand::Exit => return,
+        };
+        *format = OutputFormat::Json;
+    }
+}
+
+/// Run a command through the tool it stands for.
+///
+/// `format` and `exit` only make sense in the REPL and are rejected.
+pub(crate) async fn call_tool(
+    server: &CodeIndexServer,
+    cmd: QueryCommand,
+) -> Result<CallToolResult, McpError> {
+    match cmd {
+        QueryCommand::Search(params) => server.search(Parameters(params)).await,
+        QueryCommand::Docs(mut params) => {
+            params.preset.get_or_insert_with(|| "docs".to_string());
+            server.search(Parameters(params)).await
+        }
+        QueryCommand::Files(mut params) => {
+            params.scope.get_or_insert_with(|| vec!["file".to_string()]);
+            server.search(Parameters(params)).await
+        }
+        QueryCommand::Count(params) => server.count(Parameters(params)).await,
+        QueryCommand::Grep(params) => server.grep(Parameters(params)).await,
+        QueryCommand::GetFileSymbols(params) => server.get_file_symbols(Parameters(params)).await,
+        QueryCommand::GetSnippet(params) => server.get_snippet(Parameters(params)).await,
+        QueryCommand::At(params) => server.at(Parameters(params)).await,
+        QueryCommand::Outline(params) => server.outline(Parameters(params)).await,
+        QueryCommand::GetChildren(params) => server.get_children(Parameters(params)).await,
+        QueryCommand::Members(params) => server.members(Parameters(params)).await,
+        QueryCommand::ListSymbols(params) => server.list_symbols(Parameters(params)).await,
+        QueryCommand::Explore(params) => server.explore(Parameters(params)).await,
+        QueryCommand::Tree(params) => server.tree(Parameters(params)).await,
+        QueryCommand::GetCallers(params) => server.get_callers(Parameters(params)).await,
+        QueryCommand::GetCallees(params) => server.get_callees(Parameters(params)).await,
+        QueryCommand::CallTree(params) => server.call_tree(Parameters(params)).await,
+        QueryCommand::FindUndocumented(params) => {
+            server.find_undocumented(Parameters(params)).await
+        }
+        QueryCommand::Unreferenced(params) => server.unreferenced(Parameters(params)).await,
+        QueryCommand::Resolve(params) => server.resolve(Parameters(params)).await,
+        QueryCommand::Definition(params) => server.definition(Parameters(params)).await,
+        QueryCommand::FindImportCycles(params) => {
+            server.find_import_cycles(Parameters(params)).await
+        }
+        QueryCommand::UnusedImports(params) => server.unused_imports(Parameters(params)).await,
+        QueryCommand::ApiDiff(params) => server.api_diff(Parameters(params)).await,
+        QueryCommand::SymbolDiff(params) => server.symbol_diff(Parameters(params)).await,
+        QueryCommand::Stats(params) => server.stats(Parameters(params)).await,
+        QueryCommand::Manifest(params) => server.manifest(Parameters(params)).await,
+        QueryCommand::Projects(params) => server.projects(Parameters(params)).await,
+        QueryCommand::Recent(params) => server.recent(Parameters(params)).await,
+        QueryCommand::Reindex(params) => server.reindex(Parameters(params)).await,
+        QueryCommand::Diff(params) => server.diff(Parameters(params)).await,
+        QueryCommand::FlushIndex => server.flush_index().await,
+        QueryCommand::Format { .. } | QueryCommand::Exit => Err(McpError::invalid_params(
+            format!("'{}' is not a tool", cmd.name()),
+            None,
+        )),
+    }
+}
+
+/// Render one command's output as a JSON line: `{"command": ..., "result": ...}`,
+/// or `{"command": ..., "error": ...}` when it failed. Output that is not JSON
+/// (a count, a status message) becomes a JSON number or string.
+fn json_line(command: &str, output: Result<String, String>) -> String {
+    let line = match output {
+        Ok(text) => {
+            let result = serde_json::from_str(&text)
+                .unwrap_or_else(|_| serde_json::Value::String(text.trim_end().to_string()));
+            serde_json::json!({ "command": command, "result": result })
+        }
+        Err(error) => serde_json::json!({ "command": command, "error": error }),
+    };
+    line.to_string()
+}
+
+/// Split a query script into commands: one per line, words split like a shell
+/// (quotes group words), blank lines and lines starting with `#` skipped.
+/// Each command comes with its 1-based line number.
+fn parse_script(text: &str) -> Result<Vec<(usize, Vec<String>)>> {
+    let mut commands = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words = shlex::split(line)
+            .with_context(|| format!("line {}: invalid input (check quotes)", i + 1))?;
+        commands.push((i + 1, words));
+    }
+    Ok(commands)
+}
+
 /// Run the interactive query REPL or execute a single command.
 ///
+/// With `script`, runs the commands of that file (stdin for "-", see
+/// `parse_script`) in order against the same index, without a TTY. In text
+/// mode each output is preceded by a `--- <command>` delimiter line; JSON lines
+/// need none. Fails if any command of the script failed.
 /// If `command` is empty, starts the interactive REPL.
 /// Otherwise, executes the command and exits.
-pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
+/// With `json`, each result is printed to stdout as one JSON line (see `json_line`);
+/// the REPL banner and prompt go to stderr, so stdout stays pure JSONL.
+/// `options` are used as given (see [`BuildOptions`]), except that FTS is always
+/// on and `.codeindex/` is loaded when present.
+/// With `connect`, commands go to the server answering on that address
+/// (`serve --http`) and nothing is indexed locally; when no server answers
+/// there, the local index is queried as usual.
+pub fn run(
+    root: &Path,
+    connect: Option<SocketAddr>,
+    watch: bool,
+    json: bool,
+    options: &BuildOptions,
+    script: Option<&Path>,
+    command: Vec<String>,
+) -> Result<()> {
+    // Read the script before indexing, so a bad path fails fast
+    let script = script
+        .map(|path| {
+            let text = if path == Path::new("-") {
+                std::io::read_to_string(std::io::stdin())
+                    .context("failed to read script from stdin")
+            } else {
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read script {}", path.display()))
+            }?;
+            parse_script(&text)
+        })
+        .transpose()?;
+
+    if let Some(addr) = connect {
+        if remote::is_reachable(addr) {
+            return run_commands(json, script, command, |cmd| remote::call(addr, &cmd));
+        }
+        eprintln!("no codeix server answering on {addr}, querying the local index");
+    }
+
     // If watch mode: create channel BEFORE building
     // This way directories are watched during the single walk (no second walk needed)
     let (tx, rx): (
@@ -60,8 +341,13 @@ pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
     };
 
     // Build index with FTS enabled (loads from .codeindex/ if exists, otherwise parses files)
-    let (mount_table, db) =
-        build_index_to_db(root, true, true, tx.clone()).context("failed to build/load index")?;
+    let options = BuildOptions {
+        enable_fts: true,
+        load_from_cache: true,
+        ..options.clone()
+    };
+    let (mount_table, db) = build_index_to_db(&[root.to_path_buf()], &options, tx.clone(), None)
+        .context("failed to build/load index")?;
 
     // Flush any dirty mounts to disk
     {
@@ -82,7 +368,9 @@ pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
         let db_clone = Arc::clone(&db);
 
         std::thread::spawn(move || {
-            if let Err(e) = run_event_loop(rx, tx, mount_table_clone, db_clone) {
+            if let Err(e) =
+                run_event_loop(rx, tx, mount_table_clone, db_clone, WatchOptions::default())
+            {
                 tracing::error!("event loop error: {}", e);
             }
         });
@@ -94,29 +382,81 @@ pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
     // Create the MCP server (reusing its tool implementations)
     let server = CodeIndexServer::new(Arc::clone(&db), Arc::clone(&mount_table));
 
-    // Helper to execute a command
-    let execute_command = |cmd: QueryCommand| {
+    run_commands(json, script, command, |cmd| {
         rt.block_on(async {
-            let result = match cmd {
-                QueryCommand::Search(params) => server.search(Parameters(params)).await,
-                QueryCommand::GetFileSymbols(params) => {
-                    server.get_file_symbols(Parameters(params)).await
-                }
-                QueryCommand::GetChildren(params) => server.get_children(Parameters(params)).await,
-                QueryCommand::Explore(params) => server.explore(Parameters(params)).await,
-                QueryCommand::GetCallers(params) => server.get_callers(Parameters(params)).await,
-                QueryCommand::GetCallees(params) => server.get_callees(Parameters(params)).await,
-                QueryCommand::FlushIndex => server.flush_index().await,
-                QueryCommand::Exit => unreachable!(),
-            };
-
-            match result {
-                Ok(r) => println!("{}", extract_result_text(&r)),
-                Err(e) => eprintln!("Error: {}", e.message),
+            call_tool(&server, cmd)
+                .await
+                .map(|r| format!("{}\n", extract_result_text(&r)))
+                .map_err(|e| e.message.to_string())
+        })
+    })
+}
+
+/// Execute the script, the single command, or the REPL (see `run`), sending
+/// each command to `call` for its output.
+fn run_commands(
+    json: bool,
+    script: Option<Vec<(usize, Vec<String>)>>,
+    command: Vec<String>,
+    call: impl Fn(QueryCommand) -> Result<String, String>,
+) -> Result<()> {
+    // Output mode, switchable from the REPL with `format json|text`
+    let json_mode = Cell::new(json);
+
+    // Helper to execute a command, returning whether it succeeded
+    let execute_command = |mut cmd: QueryCommand| {
+        if let QueryCommand::Format { format } = &cmd {
+            json_mode.set(*format == OutputFormat::Json);
+            return true;
+        }
+        let json = json_mode.get();
+        if json {
+            cmd.force_json();
+        }
+        let name = cmd.name();
+
+        let output = call(cmd);
+
+        let ok = output.is_ok();
+        if json {
+            println!("{}", json_line(name, output));
+        } else {
+            match output {
+                Ok(out) => print!("{}", out),
+                Err(e) => eprintln!("Error: {}", e),
             }
-        });
+        }
+        ok
     };
 
+    // Script mode: execute each line in order, then exit
+    if let Some(commands) = script {
+        let mut failed = 0usize;
+        for (line, words) in &commands {
+            let mut args = vec!["".to_string()];
+            args.extend(words.iter().cloned());
+            match QueryCommand::try_parse_from(&args) {
+                Ok(QueryCommand::Exit) => break,
+                Ok(cmd) => {
+                    if !json_mode.get() && !matches!(cmd, QueryCommand::Format { .. }) {
+                        println!("--- {}", words.join(" "));
+                    }
+                    if !execute_command(cmd) {
+                        failed += 1;
+                    }
+                }
+                Err(e) => {
+                    eprintln!("line {line}: {}", e.render().to_string().trim_end());
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            anyhow::bail!("{failed} of {} script commands failed", commands.len());
+        }
+        return Ok(());
+    }
+
     // Single command mode: parse and execute, then exit
     if !command.is_empty() {
         // Prepend empty string for clap (it expects argv[0] to be program name)
@@ -138,7 +478,7 @@ pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
     }
 
     // Interactive REPL mode
-    println!("codeix query REPL — type 'help' for commands, 'exit' to quit");
+    eprintln!("codeix query REPL — type 'help' for commands, 'exit' to quit");
     let mut rl = ClapEditor::<QueryCommand>::builder().build();
     loop {
         match rl.read_command() {
@@ -170,6 +510,61 @@ pub fn run(root: &Path, watch: bool, command: Vec<String>) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::server::db::SearchOrder;
+
+    #[test]
+    fn test_parse_script() {
+        let script = "\
+# warm-up
+search Config -l 5
+
+  get-callers \"parse file\" --ref-kind call
+exit
+";
+        let commands = parse_script(script).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                (2, vec!["search", "Config", "-l", "5"]),
+                (4, vec!["get-callers", "parse file", "--ref-kind", "call"]),
+                (5, vec!["exit"]),
+            ]
+            .into_iter()
+            .map(|(line, words)| (line, words.into_iter().map(String::from).collect()))
+            .collect::<Vec<(usize, Vec<String>)>>()
+        );
+        // Every script line parses as a command
+        for (_, words) in &commands {
+            let args = std::iter::once("").chain(words.iter().map(String::as_str));
+            assert!(QueryCommand::try_parse_from(args).is_ok());
+        }
+
+        let err = parse_script("search \"unterminated\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn test_query_command_serde_round_trip() {
+        let cmd = QueryCommand::try_parse_from(["", "get-callers", "parse", "--ref-kind", "call"])
+            .unwrap();
+        let value = serde_json::to_value(&cmd).unwrap();
+        assert_eq!(value["command"], "get_callers");
+        assert_eq!(value["params"]["name"], "parse");
+        let back: QueryCommand = serde_json::from_value(value).unwrap();
+        if let QueryCommand::GetCallers(params) = back {
+            assert_eq!(params.reference_kind.as_deref(), Some("call"));
+        } else {
+            panic!("Expected GetCallers");
+        }
+
+        // Shortcuts and unit variants keep their own names
+        let cmd = QueryCommand::try_parse_from(["", "docs", "retry"]).unwrap();
+        let back: QueryCommand =
+            serde_json::from_str(&serde_json::to_string(&cmd).unwrap()).unwrap();
+        assert!(matches!(back, QueryCommand::Docs(p) if p.query == "retry"));
+        let value = serde_json::to_value(QueryCommand::FlushIndex).unwrap();
+        assert_eq!(value["command"], "flush_index");
+    }
 
     #[test]
     fn test_query_command_parse() {
@@ -210,6 +605,114 @@ mod tests {
         let cmd = QueryCommand::try_parse_from(["", "search", "foo"]).unwrap();
         if let QueryCommand::Search(params) = cmd {
             assert_eq!(params.query, "foo");
+            assert!(!params.group);
+            assert!(!params.include_tests);
+        } else {
+            panic!("Expected Search");
+        }
+
+        // Test docs/files shortcuts (search with preset filters)
+        let cmd = QueryCommand::try_parse_from(["", "docs", "retry policy"]).unwrap();
+        assert_eq!(cmd.name(), "search");
+        if let QueryCommand::Docs(params) = cmd {
+            assert_eq!(params.query, "retry policy");
+        } else {
+            panic!("Expected Docs");
+        }
+        let cmd = QueryCommand::try_parse_from(["", "files", "config", "-p", "api"]).unwrap();
+        if let QueryCommand::Files(params) = cmd {
+            assert_eq!(params.query, "config");
+            assert_eq!(params.project, Some("api".to_string()));
+        } else {
+            panic!("Expected Files");
+        }
+
+        // Test search grouped by project
+        let cmd = QueryCommand::try_parse_from(["", "search", "foo", "--group", "--include-tests"])
+            .unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert!(params.group);
+            assert!(params.include_tests);
+            assert_eq!(params.order_by, SearchOrder::Relevance);
+        } else {
+            panic!("Expected Search");
+        }
+        let cmd =
+            QueryCommand::try_parse_from(["", "search", "foo", "--order-by", "path"]).unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert_eq!(params.order_by, SearchOrder::Path);
+        } else {
+            panic!("Expected Search");
+        }
+
+        // Test count with filters
+        let cmd =
+            QueryCommand::try_parse_from(["", "count", "parse*", "--scope", "symbol"]).unwrap();
+        if let QueryCommand::Count(params) = cmd {
+            assert_eq!(params.query, "parse*");
+            assert_eq!(params.scope, Some(vec!["symbol".to_string()]));
+        } else {
+            panic!("Expected Count");
+        }
+
+        // Test grep with filters
+        let cmd =
+            QueryCommand::try_parse_from(["", "grep", "TODO(", "-k", "comment", "-f", "src/*"])
+                .unwrap();
+        if let QueryCommand::Grep(params) = cmd {
+            assert_eq!(params.pattern, "TODO(");
+            assert_eq!(params.kind, Some("comment".to_string()));
+            assert_eq!(params.path, Some("src/*".to_string()));
+        } else {
+            panic!("Expected Grep");
+        }
+
+        // Test unreferenced with a kind filter
+        let cmd = QueryCommand::try_parse_from(["", "unreferenced", "--kind", "function,method"])
+            .unwrap();
+        if let QueryCommand::Unreferenced(params) = cmd {
+            assert_eq!(
+                params.kind,
+                Some(vec!["function".to_string(), "method".to_string()])
+            );
+        } else {
+            panic!("Expected Unreferenced");
+        }
+
+        // Test call-tree with a depth limit
+        let cmd = QueryCommand::try_parse_from(["", "call-tree", "parse", "-d", "5"]).unwrap();
+        if let QueryCommand::CallTree(params) = cmd {
+            assert_eq!(params.name, "parse");
+            assert_eq!(params.max_depth, Some(5));
+        } else {
+            panic!("Expected CallTree");
+        }
+
+        // Test list-symbols with kind and project
+        let cmd =
+            QueryCommand::try_parse_from(["", "list-symbols", "-k", "interface", "-p", "core"])
+                .unwrap();
+        if let QueryCommand::ListSymbols(params) = cmd {
+            assert_eq!(params.kind, Some("interface".to_string()));
+            assert_eq!(params.project, Some("core".to_string()));
+        } else {
+            panic!("Expected ListSymbols");
+        }
+
+        // Test members across files
+        let cmd =
+            QueryCommand::try_parse_from(["", "members", "UserService", "-p", "core"]).unwrap();
+        if let QueryCommand::Members(params) = cmd {
+            assert_eq!(params.parent, "UserService");
+            assert_eq!(params.project, Some("core".to_string()));
+        } else {
+            panic!("Expected Members");
+        }
+
+        // Test search without a query (enumeration)
+        let cmd = QueryCommand::try_parse_from(["", "search", "--scope", "symbol"]).unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert_eq!(params.query, "");
         } else {
             panic!("Expected Search");
         }
@@ -237,10 +740,79 @@ mod tests {
             panic!("Expected Search");
         }
 
+        // Test search with language filter
+        let cmd =
+            QueryCommand::try_parse_from(["", "search", "parse", "--lang", "python,ruby"]).unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert_eq!(
+                params.lang,
+                Some(vec!["python".to_string(), "ruby".to_string()])
+            );
+        } else {
+            panic!("Expected Search");
+        }
+
+        // Test search with excluded path globs
+        let cmd =
+            QueryCommand::try_parse_from(["", "search", "parse", "-x", "*tests/*,*/generated/*"])
+                .unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert_eq!(
+                params.exclude_paths,
+                Some(vec!["*tests/*".to_string(), "*/generated/*".to_string()])
+            );
+        } else {
+            panic!("Expected Search");
+        }
+
+        // Test get-callers across several projects
+        let cmd =
+            QueryCommand::try_parse_from(["", "get-callers", "handle", "--projects", "api,web"])
+                .unwrap();
+        if let QueryCommand::GetCallers(params) = cmd {
+            assert_eq!(
+                params.projects,
+                Some(vec!["api".to_string(), "web".to_string()])
+            );
+        } else {
+            panic!("Expected GetCallers");
+        }
+
+        // Test get-callees with a set of reference kinds
+        let cmd = QueryCommand::try_parse_from([
+            "",
+            "get-callees",
+            "main",
+            "--ref-kinds",
+            "call,instantiation",
+        ])
+        .unwrap();
+        if let QueryCommand::GetCallees(params) = cmd {
+            assert_eq!(
+                params.reference_kinds,
+                Some(vec!["call".to_string(), "instantiation".to_string()])
+            );
+            assert!(params.reference_kind.is_none());
+        } else {
+            panic!("Expected GetCallees");
+        }
+
+        // Test search with preset
+        let cmd = QueryCommand::try_parse_from(["", "search", "foo", "--preset", "docs"]).unwrap();
+        if let QueryCommand::Search(params) = cmd {
+            assert_eq!(params.preset, Some("docs".to_string()));
+            assert!(params.scope.is_none());
+        } else {
+            panic!("Expected Search");
+        }
+
         // Test get-file-symbols with file (positional required arg)
-        let cmd = QueryCommand::try_parse_from(["", "get-file-symbols", "src/main.rs"]).unwrap();
+        let cmd =
+            QueryCommand::try_parse_from(["", "get-file-symbols", "src/main.rs", "-p", "api"])
+                .unwrap();
         if let QueryCommand::GetFileSymbols(params) = cmd {
             assert_eq!(params.file, "src/main.rs");
+            assert_eq!(params.project.as_deref(), Some("api"));
         } else {
             panic!("Expected GetFileSymbols");
         }
@@ -253,6 +825,214 @@ mod tests {
             panic!("Expected GetCallers");
         }
 
+        // Test find-undocumented with filters
+        let cmd = QueryCommand::try_parse_from([
+            "",
+            "find-undocumented",
+            "-k",
+            "function,class",
+            "-v",
+            "internal",
+        ])
+        .unwrap();
+        if let QueryCommand::FindUndocumented(params) = cmd {
+            assert_eq!(
+                params.kind,
+                Some(vec!["function".to_string(), "class".to_string()])
+            );
+            assert_eq!(params.visibility, Some("internal".to_string()));
+        } else {
+            panic!("Expected FindUndocumented");
+        }
+
+        // Test resolve with call-site location
+        let cmd = QueryCommand::try_parse_from(["", "resolve", "self.handle", "-f", "src/app.py"])
+            .unwrap();
+        if let QueryCommand::Resolve(params) = cmd {
+            assert_eq!(params.name, "self.handle");
+            assert_eq!(params.file, Some("src/app.py".to_string()));
+        } else {
+            panic!("Expected Resolve");
+        }
+
+        // Test definition with a ranking hint and alternatives limit
+        let cmd = QueryCommand::try_parse_from([
+            "",
+            "definition",
+            "Config",
+            "-f",
+            "src/app.py",
+            "-l",
+            "3",
+        ])
+        .unwrap();
+        if let QueryCommand::Definition(params) = cmd {
+            assert_eq!(params.name, "Config");
+            assert_eq!(params.file, Some("src/app.py".to_string()));
+            assert_eq!(params.limit, Some(3));
+        } else {
+            panic!("Expected Definition");
+        }
+
+        // Test symbol-diff against a saved index
+        let cmd = QueryCommand::try_parse_from(["", "symbol-diff", "base/.codeindex", "-p", "web"])
+            .unwrap();
+        if let QueryCommand::SymbolDiff(params) = cmd {
+            assert_eq!(params.baseline, "base/.codeindex");
+            assert_eq!(params.project, Some("web".to_string()));
+        } else {
+            panic!("Expected SymbolDiff");
+        }
+
+        // Test diff scoped to a project
+        let cmd = QueryCommand::try_parse_from(["", "diff", "-p", "web"]).unwrap();
+        if let QueryCommand::Diff(params) = cmd {
+            assert_eq!(params.project, Some("web".to_string()));
+        } else {
+            panic!("Expected Diff");
+        }
+
+        // Test find-import-cycles scoped to a project
+        let cmd = QueryCommand::try_parse_from(["", "find-import-cycles", "-p", "web"]).unwrap();
+        if let QueryCommand::FindImportCycles(params) = cmd {
+            assert_eq!(params.project, Some("web".to_string()));
+        } else {
+            panic!("Expected FindImportCycles");
+        }
+
+        // Test unused-imports for a file
+        let cmd = QueryCommand::try_parse_from(["", "unused-imports", "src/app.py", "-p", "svc"])
+            .unwrap();
+        if let QueryCommand::UnusedImports(params) = cmd {
+            assert_eq!(params.file, "src/app.py");
+            assert_eq!(params.project, Some("svc".to_string()));
+        } else {
+            panic!("Expected UnusedImports");
+        }
+
+        // Test api-diff against a saved baseline
+        let baseline = concat!(env!("CARGO_MANIFEST_DIR"), "/.codeindex");
+        let cmd = QueryCommand::try_parse_from([
+            "",
+            "api-diff",
+            "-p",
+            "libs/core",
+            "--baseline",
+            baseline,
+        ])
+        .unwrap();
+        if let QueryCommand::ApiDiff(params) = cmd {
+            assert_eq!(params.project, Some("libs/core".to_string()));
+            assert_eq!(params.baseline.as_deref(), Some(baseline));
+            assert!(params.base_project.is_none());
+        } else {
+            panic!("Expected ApiDiff");
+        }
+
+        // Test stats
+        let cmd = QueryCommand::try_parse_from(["", "stats", "-p", "libs/utils"]).unwrap();
+        if let QueryCommand::Stats(params) = cmd {
+            assert_eq!(params.project, Some("libs/utils".to_string()));
+            assert_eq!(params.format, OutputFormat::Text);
+        } else {
+            panic!("Expected Stats");
+        }
+
+        // Test manifest
+        let cmd = QueryCommand::try_parse_from(["", "manifest", "-p", "web"]).unwrap();
+        if let QueryCommand::Manifest(params) = cmd {
+            assert_eq!(params.project, Some("web".to_string()));
+        } else {
+            panic!("Expected Manifest");
+        }
+
+        // Test get-snippet
+        let cmd =
+            QueryCommand::try_parse_from(["", "get-snippet", "src/a.rs", "10", "20"]).unwrap();
+        if let QueryCommand::GetSnippet(params) = cmd {
+            assert_eq!(params.file, "src/a.rs");
+            assert_eq!((params.line_start, params.line_end), (10, 20));
+            assert!(params.project.is_none());
+        } else {
+            panic!("Expected GetSnippet");
+        }
+
+        // Test at
+        let cmd =
+            QueryCommand::try_parse_from(["", "at", "src/app.py", "142", "-p", "web"]).unwrap();
+        if let QueryCommand::At(params) = cmd {
+            assert_eq!(params.file, "src/app.py");
+            assert_eq!(params.line, 142);
+            assert_eq!(params.project, Some("web".to_string()));
+        } else {
+            panic!("Expected At");
+        }
+        assert!(QueryCommand::try_parse_from(["", "at", "src/app.py"]).is_err());
+
+        // Test outline
+        let cmd = QueryCommand::try_parse_from([
+            "",
+            "outline",
+            "src/main.rs",
+            "-v",
+            "private",
+            "-p",
+            "api",
+        ])
+        .unwrap();
+        if let QueryCommand::Outline(params) = cmd {
+            assert_eq!(params.file, "src/main.rs");
+            assert_eq!(params.project.as_deref(), Some("api"));
+            assert_eq!(params.visibility, Some("private".to_string()));
+            assert_eq!(params.format, OutputFormat::Text);
+        } else {
+            panic!("Expected Outline");
+        }
+
+        // Test projects
+        let cmd = QueryCommand::try_parse_from(["", "projects", "--format", "json"]).unwrap();
+        if let QueryCommand::Projects(params) = cmd {
+            assert_eq!(params.format, OutputFormat::Json);
+        } else {
+            panic!("Expected Projects");
+        }
+
+        // Test recent
+        let cmd =
+            QueryCommand::try_parse_from(["", "recent", "--since-secs", "600", "-l", "5"]).unwrap();
+        if let QueryCommand::Recent(params) = cmd {
+            assert_eq!(params.since_secs, Some(600));
+            assert_eq!(params.limit, Some(5));
+            assert_eq!(params.project, None);
+        } else {
+            panic!("Expected Recent");
+        }
+
+        // Test tree
+        let cmd = QueryCommand::try_parse_from(["", "tree", "src", "--depth", "1"]).unwrap();
+        if let QueryCommand::Tree(params) = cmd {
+            assert_eq!(params.path, Some("src".to_string()));
+            assert_eq!(params.depth, 1);
+            assert_eq!(params.cap, 5);
+        } else {
+            panic!("Expected Tree");
+        }
+
+        // Test reindex
+        let cmd = QueryCommand::try_parse_from(["", "reindex", "src/server", "-p", "libs/utils"])
+            .unwrap();
+        if let QueryCommand::Reindex(params) = cmd {
+            assert_eq!(params.path, Some("src/server".to_string()));
+            assert_eq!(params.project, Some("libs/utils".to_string()));
+        } else {
+            panic!("Expected Reindex");
+        }
+        let cmd = QueryCommand::try_parse_from(["", "reindex"]).unwrap();
+        assert!(matches!(
+            cmd,
+            QueryCommand::Reindex(ReindexParams { path: None, .. })
+        ));
+
         // Test exit command
         let cmd = QueryCommand::try_parse_from(["", "exit"]).unwrap();
         assert!(matches!(cmd, QueryCommand::Exit));
@@ -260,5 +1040,47 @@ mod tests {
         // Test quit alias
         let cmd = QueryCommand::try_parse_from(["", "quit"]).unwrap();
         assert!(matches!(cmd, QueryCommand::Exit));
+
+        // Test format command
+        let cmd = QueryCommand::try_parse_from(["", "format", "json"]).unwrap();
+        assert!(matches!(
+            cmd,
+            QueryCommand::Format {
+                format: OutputFormat::Json
+            }
+        ));
+        assert!(QueryCommand::try_parse_from(["", "format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_force_json_and_json_line() {
+        let mut cmd =
+            QueryCommand::try_parse_from(["", "search", "foo", "--format", "text"]).unwrap();
+        cmd.force_json();
+        assert_eq!(cmd.name(), "search");
+        if let QueryCommand::Search(params) = &cmd {
+            assert_eq!(params.format, OutputFormat::Json);
+        } else {
+            panic!("Expected Search");
+        }
+
+        // JSON output is embedded as a value, plain text as a string or number
+        let line = json_line("search", Ok("[{\"name\":\"foo\"}]\n".to_string()));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["command"], "search");
+        assert_eq!(value["result"][0]["name"], "foo");
+        assert!(!line.contains('\n'));
+
+        let line = json_line("count", Ok("42\n".to_string()));
+        assert_eq!(line, r#"{"command":"count","result":42}"#);
+
+        let line = json_line("flush_index", Ok("Index flushed\n".to_string()));
+        assert_eq!(
+            line,
+            r#"{"command":"flush_index","result":"Index flushed"}"#
+        );
+
+        let line = json_line("search", Err("bad regex".to_string()));
+        assert_eq!(line, r#"{"command":"search","error":"bad regex"}"#);
     }
 }
@@ -0,0 +1,87 @@
+//! Client side of `codeix query --connect ADDR`.
+//!
+//! Instead of loading its own copy of the index, the query process sends each
+//! command to a server started with `serve --http ADDR`, so it sees that
+//! server's in-memory state. A command is one `GET /query?command=<JSON>`
+//! request carrying the serialized [`QueryCommand`]; the response body is
+//! `{"output": ...}` with the text the command prints locally, or `{"error": ...}`.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use crate::cli::query::QueryCommand;
+
+/// How long to wait for a server to accept the connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long to wait for a response once connected, so a stuck server can't
+/// hang the query process (searches on the server time out well before this).
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Whether something accepts connections on `addr`.
+pub fn is_reachable(addr: SocketAddr) -> bool {
+    TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok()
+}
+
+/// Run `cmd` on the server at `addr`, returning its output or error message.
+pub fn call(addr: SocketAddr, cmd: &QueryCommand) -> Result<String, String> {
+    let command = serde_json::to_string(cmd).map_err(|e| e.to_string())?;
+    let (status, body) = get(
+        addr,
+        &format!("/query?command={}", percent_encode(&command)),
+    )
+    .map_err(|e| format!("request to {addr} failed: {e}"))?;
+    let body: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|_| format!("unexpected response from {addr}: {status}"))?;
+    match (body["output"].as_str(), body["error"].as_str()) {
+        (Some(output), _) => Ok(format!("{output}\n")),
+        (None, Some(error)) => Err(error.to_string()),
+        (None, None) => Err(format!("unexpected response from {addr}: {status}")),
+    }
+}
+
+/// Send a GET request for `target` and return the status line and body.
+/// The server closes the connection after one response.
+fn get(addr: SocketAddr, target: &str) -> std::io::Result<(String, String)> {
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    write!(
+        stream,
+        "GET {target} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n\r\n"
+    )?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed HTTP response")
+    })?;
+    let status = head.lines().next().unwrap_or_default().to_string();
+    Ok((status, body.to_string()))
+}
+
+/// Percent-encode everything but unreserved characters (RFC 3986).
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(
+            percent_encode(r#"{"q":"a b/ü"}"#),
+            "%7B%22q%22%3A%22a%20b%2F%C3%BC%22%7D"
+        );
+        assert_eq!(percent_encode("get_callers-1.0~"), "get_callers-1.0~");
+    }
+}
@@ -1,28 +1,41 @@
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::mpsc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 
-use crate::cli::build::build_index_to_db;
+use crate::cli::build::{BuildOptions, build_index_to_db};
 use crate::mount::MountedEvent;
-use crate::mount::handler::{flush_mount_to_disk, run_event_loop};
+use crate::mount::handler::{WatchOptions, flush_mount_to_disk, run_event_loop};
 use crate::server::mcp::start_server;
 
 /// Run the `serve` subcommand: load the index into an in-memory SQLite FTS5
-/// database and start the MCP server over stdio.
-pub fn run(path: &Path, watch: bool) -> Result<()> {
-    let _root = path
-        .canonicalize()
-        .with_context(|| format!("cannot resolve path: {}", path.display()))?;
-
+/// database (or the persisted `.codeindex/index.sqlite` with `options.persist`)
+/// and start the MCP server over stdio. `options` are used as given (see
+/// [`BuildOptions`]), except that FTS is always on and `.codeindex/` is loaded
+/// when present. With `http` (and the `http` feature), a read-only HTTP/JSON
+/// endpoint is served on that address as well. With `watch`, file changes are
+/// picked up with its timing (see [`run_event_loop`]). A search running longer
+/// than `search_timeout` is aborted with an error (zero disables the limit).
+///
+/// Several `paths` are served as one workspace: their projects are named after
+/// each root's directory (`api`, `web/libs/ui`), so one `search` spans them all.
+pub fn run(
+    paths: &[PathBuf],
+    options: &BuildOptions,
+    watch: Option<WatchOptions>,
+    search_timeout: Duration,
+    http: Option<SocketAddr>,
+) -> Result<()> {
     // If watch mode: create channel BEFORE building
     // This way directories are watched during the single walk (no second walk needed)
     // Channel carries (mount_root, event) tuples for direct mount lookup
     let (tx, rx): (
         Option<mpsc::Sender<MountedEvent>>,
         Option<mpsc::Receiver<MountedEvent>>,
-    ) = if watch {
+    ) = if watch.is_some() {
         tracing::info!("starting watch mode");
         let (tx, rx) = mpsc::channel();
         (Some(tx), Some(rx))
@@ -34,8 +47,16 @@ pub fn run(path: &Path, watch: bool) -> Result<()> {
     // Serve mode needs FTS for search functionality
     // load_from_cache=true: load from .codeindex/ if available
     // Pass tx to initialize notify watchers during walk (single walk strategy)
-    let (mount_table, db) =
-        build_index_to_db(path, true, true, tx.clone()).context("failed to build/load index")?;
+    let options = BuildOptions {
+        enable_fts: true,
+        load_from_cache: true,
+        ..options.clone()
+    };
+    let (mount_table, db) = build_index_to_db(paths, &options, tx.clone(), None)
+        .context("failed to build/load index")?;
+    db.lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .set_search_timeout((!search_timeout.is_zero()).then_some(search_timeout));
 
     // Flush any dirty mounts to disk (projects that were indexed, not loaded)
     {
@@ -52,12 +73,12 @@ pub fn run(path: &Path, watch: bool) -> Result<()> {
 
     // Spawn event loop AFTER build (needs mount_table and db)
     // But notify watchers are already initialized and watching during build
-    if let (Some(tx), Some(rx)) = (tx, rx) {
+    if let (Some(tx), Some(rx), Some(watch)) = (tx, rx, watch) {
         let mount_table_clone = Arc::clone(&mount_table);
         let db_clone = Arc::clone(&db);
 
         std::thread::spawn(move || {
-            if let Err(e) = run_event_loop(rx, tx, mount_table_clone, db_clone) {
+            if let Err(e) = run_event_loop(rx, tx, mount_table_clone, db_clone, watch) {
                 tracing::error!("event loop error: {}", e);
             }
         });
@@ -67,6 +88,23 @@ pub fn run(path: &Path, watch: bool) -> Result<()> {
     let rt = tokio::runtime::Runtime::new().context("failed to create tokio runtime")?;
 
     rt.block_on(async {
+        if let Some(addr) = http {
+            #[cfg(feature = "http")]
+            {
+                let db = Arc::clone(&db);
+                let mount_table = Arc::clone(&mount_table);
+                tokio::spawn(async move {
+                    if let Err(e) =
+                        crate::server::http::start_http_server(addr, db, mount_table).await
+                    {
+                        tracing::error!("HTTP endpoint error: {e:#}");
+                    }
+                });
+            }
+            #[cfg(not(feature = "http"))]
+            anyhow::bail!("cannot serve HTTP on {addr}: built without the `http` feature");
+        }
+
         tracing::info!("starting MCP server on stdio");
         start_server(db, mount_table).await
     })
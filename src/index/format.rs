@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
 
+/// Format version written to `index.json`.
+///
+/// Readers accept any index with the same major version: minor bumps only add
+/// optional fields, which serde ignores when it does not know them.
+pub const FORMAT_VERSION: &str = "1.0";
+
 /// `index.json` manifest — top-level metadata for a `.codeindex/` directory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexManifest {
@@ -24,6 +30,9 @@ pub struct FileEntry {
     /// File description extracted from the source (e.g., docstring, frontmatter).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Whether the path looks like a test file (see [`crate::utils::test_files`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_test: bool,
 }
 
 /// One line in `symbols.jsonl` — a symbol extracted from the AST.
@@ -35,6 +44,9 @@ pub struct SymbolEntry {
     pub line: [u32; 2],
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent: Option<String>,
+    /// Declaration text as written (parameters, return type), e.g. "def main(args: list[str]) -> int"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tokens: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -53,6 +65,8 @@ pub struct SymbolOutput {
     pub line: [u32; 2],
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
     #[serde(skip_serializing_if = "String::is_empty")]
     pub project: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -66,6 +80,7 @@ impl SymbolOutput {
             name: entry.name.clone(),
             line: entry.line,
             parent: entry.parent.clone(),
+            sig: entry.sig.clone(),
             project: entry.project.clone(),
             context,
         }
@@ -92,7 +107,8 @@ pub struct ReferenceEntry {
     pub file: String,
     /// Symbol being referenced (e.g. "os.path.join", "MyClass", "fetch")
     pub name: String,
-    /// Kind of reference: "call", "import", "type_annotation", "instantiation", "definition"
+    /// Kind of reference: "call", "import", "type_annotation", "instantiation", "definition",
+    /// "derive", "attribute"
     pub kind: String,
     /// Line range [start, end] where this reference appears (1-based)
     pub line: [u32; 2],
@@ -2,9 +2,11 @@ use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 
-use super::format::{FileEntry, IndexManifest, ReferenceEntry, SymbolEntry, TextEntry};
+use super::format::{
+    FORMAT_VERSION, FileEntry, IndexManifest, ReferenceEntry, SymbolEntry, TextEntry,
+};
 
 /// Convenience alias for the tuple returned by `read_index`.
 pub type IndexData = (
@@ -17,12 +19,16 @@ pub type IndexData = (
 
 /// Read an existing `.codeindex/` directory from disk.
 /// Returns the manifest, files, symbols, texts, and references.
+///
+/// Indexes written by a newer minor version are accepted (unknown fields are
+/// ignored); a different major version is rejected.
 pub fn read_index(path: &Path) -> Result<IndexData> {
     let manifest: IndexManifest = {
         let data =
             fs::read_to_string(path.join("index.json")).context("failed to read index.json")?;
         serde_json::from_str(&data).context("failed to parse index.json")?
     };
+    check_version(&manifest.version)?;
 
     let files = read_jsonl(&path.join("files.jsonl")).context("failed to read files.jsonl")?;
 
@@ -37,6 +43,19 @@ pub fn read_index(path: &Path) -> Result<IndexData> {
     Ok((manifest, files, symbols, texts, references))
 }
 
+/// Reject manifests whose major version differs from the one we write.
+fn check_version(version: &str) -> Result<()> {
+    let major = |v: &str| v.split('.').next().unwrap_or_default().trim().to_string();
+    if major(version) != major(FORMAT_VERSION) {
+        bail!(
+            "unsupported index version {} (this build reads {}.x)",
+            version,
+            major(FORMAT_VERSION)
+        );
+    }
+    Ok(())
+}
+
 /// Read a JSONL file into a Vec of deserialized items.
 fn read_jsonl<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
     let file = fs::File::open(path)?;
@@ -55,3 +74,62 @@ fn read_jsonl<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
 
     Ok(items)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_index_files(dir: &Path, version: &str) {
+        fs::write(
+            dir.join("index.json"),
+            format!(
+                r#"{{"version":"{version}","name":"demo","root":".","languages":["rust"],"generator":"codeix 9.9"}}"#
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("files.jsonl"),
+            r#"{"path":"src/lib.rs","lang":"rust","hash":"abc","lines":3,"encoding":"utf-8"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("symbols.jsonl"),
+            r#"{"file":"src/lib.rs","name":"run","kind":"function","line":[1,3],"deprecated":true}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("texts.jsonl"),
+            r#"{"file":"src/lib.rs","kind":"comment","line":[1,1],"text":"hello world","lang":"en"}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("references.jsonl"),
+            r#"{"file":"src/lib.rs","name":"helper","kind":"call","line":[2,2],"target":"src/util.rs"}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_newer_minor_version_with_unknown_fields() {
+        let tmp = TempDir::new().unwrap();
+        write_index_files(tmp.path(), "1.7");
+
+        let (manifest, files, symbols, texts, refs) = read_index(tmp.path()).unwrap();
+        assert_eq!(manifest.version, "1.7");
+        assert_eq!(files.len(), 1);
+        assert_eq!(symbols[0].name, "run");
+        assert!(symbols[0].sig.is_none()); // absent in older indexes
+        assert_eq!(texts[0].text, "hello world");
+        assert_eq!(refs[0].name, "helper");
+    }
+
+    #[test]
+    fn test_newer_major_version_rejected() {
+        let tmp = TempDir::new().unwrap();
+        write_index_files(tmp.path(), "2.0");
+
+        let err = read_index(tmp.path()).unwrap_err();
+        assert!(err.to_string().contains("unsupported index version 2.0"));
+    }
+}
@@ -1,5 +1,5 @@
 use std::io::IsTerminal;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -14,6 +14,33 @@ struct Cli {
     #[arg(short = 'r', long = "root", global = true, default_value = ".")]
     root: String,
 
+    /// Also index files excluded by .gitignore (built-in exclusions and .codeixignore still apply)
+    #[arg(long, global = true)]
+    no_gitignore: bool,
+
+    /// Walk into symlinked directories, indexing each real directory once
+    #[arg(long, global = true)]
+    follow_symlinks: bool,
+
+    /// Record files larger than this many bytes without parsing them for symbols
+    #[arg(long, global = true, value_name = "BYTES", default_value_t = codeix::mount::DEFAULT_MAX_FILE_BYTES)]
+    max_file_bytes: u64,
+
+    /// Threads parsing files during the initial walk (0 = one per CPU)
+    #[arg(
+        short = 'j',
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = 1
+    )]
+    jobs: usize,
+
+    /// Read and write indexes under this directory instead of each project's
+    /// .codeindex/ (subprojects go to their relative path under it)
+    #[arg(long, global = true, value_name = "DIR")]
+    index_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -21,12 +48,63 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Build the .codeindex/ for discovered projects
-    Build,
+    Build {
+        /// Keep the search database in .codeindex/index.sqlite and reuse it next run
+        #[arg(long)]
+        persist: bool,
+        /// Only re-index files changed between this git revision and HEAD (reuses .codeindex/)
+        #[arg(long, value_name = "REV", conflicts_with = "stdin")]
+        since: Option<String>,
+        /// Parse one file's source from stdin and print its symbols/texts/refs as JSONL
+        #[arg(long, requires = "path", conflicts_with = "persist")]
+        stdin: bool,
+        /// File path to report for --stdin (also picks the language by name or extension)
+        #[arg(long, value_name = "FILE", requires = "stdin")]
+        path: Option<String>,
+        /// Language of the --stdin source (e.g. "rust"); default: detected from --path
+        #[arg(long, requires = "stdin")]
+        lang: Option<String>,
+        /// Print nothing on stderr but errors (no progress line or summary)
+        #[arg(short, long, conflicts_with = "verbose")]
+        quiet: bool,
+        /// Also print a line per file indexed or failed
+        #[arg(short, long)]
+        verbose: bool,
+    },
     /// Start the MCP server (default when stdin is piped)
     Serve {
+        /// Workspace roots to serve together (defaults to --root). With several,
+        /// projects are named after each root's directory
+        #[arg(value_name = "ROOT")]
+        paths: Vec<PathBuf>,
         /// Disable file watching
         #[arg(long)]
         no_watch: bool,
+        /// Keep the search database in .codeindex/index.sqlite and reuse it next run
+        #[arg(long)]
+        persist: bool,
+        /// Match search terms anywhere inside words (trigram tokenizer; terms need 3+ characters)
+        #[arg(long, conflicts_with = "persist")]
+        substring: bool,
+        /// Milliseconds a changed file must stay quiet before it is re-indexed
+        #[arg(long, value_name = "MS", default_value_t = codeix::mount::handler::DEFAULT_DEBOUNCE_DELAY.as_millis() as u64, value_parser = clap::value_parser!(u64).range(1..))]
+        debounce_ms: u64,
+        /// Longest wait in milliseconds for a watch event before pending changes are checked
+        #[arg(long, value_name = "MS", default_value_t = codeix::mount::handler::DEFAULT_POLL_INTERVAL.as_millis() as u64, value_parser = clap::value_parser!(u64).range(1..))]
+        poll_ms: u64,
+        /// Seconds between checks for watched projects whose directory vanished (0 disables)
+        #[arg(long, value_name = "SECS", default_value_t = codeix::mount::handler::DEFAULT_VALIDATE_INTERVAL.as_secs())]
+        validate_interval: u64,
+        /// Seconds between writes of pending index changes to .codeindex/ (0, the default, only flushes on shutdown or `flush_index`)
+        #[arg(long, value_name = "SECS", default_value_t = 0)]
+        flush_every: u64,
+        /// Seconds a search may run before it is aborted with a timeout error (0 disables)
+        #[arg(long, value_name = "SECS", default_value_t = codeix::server::db::DEFAULT_SEARCH_TIMEOUT.as_secs())]
+        search_timeout: u64,
+        /// Also serve search/explore/callers as read-only HTTP/JSON on this address (e.g. 127.0.0.1:8080)
+        #[cfg(feature = "http")]
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
     },
     /// Interactive query REPL (default when in a terminal)
     ///
@@ -35,6 +113,16 @@ enum Commands {
         /// Disable file watching
         #[arg(long)]
         no_watch: bool,
+        /// Print each result as a JSON line ({"command": ..., "result": ...}) on stdout
+        #[arg(long)]
+        json: bool,
+        /// Run the commands of FILE, one per line (`#` starts a comment; "-" reads stdin)
+        #[arg(long, value_name = "FILE", conflicts_with = "command")]
+        script: Option<PathBuf>,
+        /// Send commands to the server running `serve --http` on this address instead of
+        /// indexing locally (falls back to the local index when nothing answers there)
+        #[arg(long, value_name = "ADDR")]
+        connect: Option<std::net::SocketAddr>,
         /// Command to execute (if omitted, starts REPL)
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
@@ -54,23 +142,122 @@ fn main() -> Result<()> {
             // Interactive terminal: default to query REPL
             Commands::Query {
                 no_watch: false,
+                json: false,
+                script: None,
+                connect: None,
                 command: vec![],
             }
         } else {
             // Piped stdin (e.g. MCP client): default to serve
-            Commands::Serve { no_watch: false }
+            Commands::Serve {
+                paths: vec![],
+                no_watch: false,
+                persist: false,
+                substring: false,
+                debounce_ms: codeix::mount::handler::DEFAULT_DEBOUNCE_DELAY.as_millis() as u64,
+                poll_ms: codeix::mount::handler::DEFAULT_POLL_INTERVAL.as_millis() as u64,
+                validate_interval: codeix::mount::handler::DEFAULT_VALIDATE_INTERVAL.as_secs(),
+                flush_every: 0,
+                search_timeout: codeix::server::db::DEFAULT_SEARCH_TIMEOUT.as_secs(),
+                #[cfg(feature = "http")]
+                http: None,
+            }
         }
     });
 
+    let options = codeix::cli::build::BuildOptions {
+        index_ignored: cli.no_gitignore,
+        follow_symlinks: cli.follow_symlinks,
+        max_file_bytes: cli.max_file_bytes,
+        jobs: cli.jobs,
+        index_dir: cli.index_dir,
+        ..Default::default()
+    };
+
     match command {
-        Commands::Build => {
-            codeix::cli::build::run(root)?;
+        Commands::Build {
+            stdin: true,
+            path: Some(path),
+            lang,
+            ..
+        } => {
+            codeix::cli::build::run_stdin(&path, lang.as_deref())?;
+        }
+        Commands::Build {
+            persist,
+            since,
+            quiet,
+            verbose,
+            ..
+        } => {
+            let verbosity = if quiet {
+                codeix::cli::progress::Verbosity::Quiet
+            } else if verbose {
+                codeix::cli::progress::Verbosity::Verbose
+            } else {
+                codeix::cli::progress::Verbosity::Normal
+            };
+            codeix::cli::build::run(
+                root,
+                &codeix::cli::build::BuildOptions { persist, ..options },
+                since.as_deref(),
+                verbosity,
+            )?;
         }
-        Commands::Serve { no_watch } => {
-            codeix::cli::serve::run(root, !no_watch)?;
+        Commands::Serve {
+            paths,
+            no_watch,
+            persist,
+            substring,
+            debounce_ms,
+            poll_ms,
+            validate_interval,
+            flush_every,
+            search_timeout,
+            #[cfg(feature = "http")]
+            http,
+        } => {
+            #[cfg(not(feature = "http"))]
+            let http = None;
+            let paths = if paths.is_empty() {
+                vec![root.to_path_buf()]
+            } else {
+                paths
+            };
+            let watch = (!no_watch).then(|| codeix::mount::handler::WatchOptions {
+                debounce: std::time::Duration::from_millis(debounce_ms),
+                poll_interval: std::time::Duration::from_millis(poll_ms),
+                validate_interval: std::time::Duration::from_secs(validate_interval),
+                flush_interval: std::time::Duration::from_secs(flush_every),
+            });
+            codeix::cli::serve::run(
+                &paths,
+                &codeix::cli::build::BuildOptions {
+                    persist,
+                    substring,
+                    ..options
+                },
+                watch,
+                std::time::Duration::from_secs(search_timeout),
+                http,
+            )?;
         }
-        Commands::Query { no_watch, command } => {
-            codeix::cli::query::run(root, !no_watch, command)?;
+        Commands::Query {
+            no_watch,
+            json,
+            script,
+            connect,
+            command,
+        } => {
+            codeix::cli::query::run(
+                root,
+                connect,
+                !no_watch,
+                json,
+                &options,
+                script.as_deref(),
+                command,
+            )?;
         }
     }
 
@@ -1,24 +1,36 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
-use notify::event::EventKind;
+use notify::event::{CreateKind, EventKind, RemoveKind};
 
-use crate::index::format::{FileEntry, IndexManifest};
+use crate::index::format::{
+    FORMAT_VERSION, FileEntry, IndexManifest, ReferenceEntry, SymbolEntry, TextEntry,
+};
 use crate::index::reader::read_index;
 use crate::index::writer::write_index;
-use crate::mount::{FsEvent, MountMode, MountTable, MountedEvent, is_removal_event};
-use crate::parser::languages::detect_language;
-use crate::parser::metadata::extract_file_metadata;
-use crate::parser::treesitter::parse_file;
+use crate::mount::{
+    FsEvent, Mount, MountMode, MountTable, MountedEvent, is_removal_event, path_event_kind,
+};
+use crate::parser::augment::TokenAugmentor;
+use crate::parser::filter::FilterPolicy;
+use crate::parser::languages::{detect_language_for_path, is_parsed_language, is_path_independent};
+use crate::parser::metadata::{FileMetadata, extract_file_metadata, extract_text_file_metadata};
+use crate::parser::treesitter::{ParseError, parse_file_checked};
 use crate::server::db::SearchDb;
-use crate::utils::hasher::hash_bytes;
-
-const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
-const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+use crate::server::index_diff::{IndexDiff, diff_index};
+use crate::utils::binary::is_binary;
+use crate::utils::hasher::{hash_bytes, hash_file};
+use crate::utils::test_files::is_test_path;
+
+/// Default quiet time after a path's last event before it is re-indexed (`--debounce-ms`).
+pub const DEFAULT_DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+/// Default longest wait for a watch event before pending ones are checked (`--poll-ms`).
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1000);
 /// Trigger file name for external flush requests (e.g., from `codeix build` when server holds lock).
 /// Written at project root (not inside .codeindex/) so inotify picks it up.
 const FLUSH_TRIGGER_FILE: &str = ".codeindex.flush";
@@ -26,18 +38,93 @@ const FLUSH_TRIGGER_FILE: &str = ".codeindex.flush";
 const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
 /// How often to poll for trigger file deletion
 const FLUSH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Default interval of the sweep for mounts whose root vanished (`--validate-interval`).
+pub const DEFAULT_VALIDATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Timing of [`run_event_loop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// Quiet time after a path's last event before it is re-indexed (`--debounce-ms`)
+    pub debounce: Duration,
+    /// Longest wait for a watch event before pending ones are checked (`--poll-ms`)
+    pub poll_interval: Duration,
+    /// Interval of the sweep for mounts whose root vanished, never if zero
+    /// (`--validate-interval`)
+    pub validate_interval: Duration,
+    /// Interval of the write of dirty mounts to disk, never if zero (`--flush-every`)
+    pub flush_interval: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: DEFAULT_DEBOUNCE_DELAY,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            validate_interval: DEFAULT_VALIDATE_INTERVAL,
+            flush_interval: Duration::ZERO,
+        }
+    }
+}
+
+/// What happened to a file reported through an [`IndexedFile`] notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexStatus {
+    /// Parsed (or copied from an identical file) and stored
+    Indexed,
+    /// Hash unchanged since it was last stored: nothing re-parsed
+    Unchanged,
+    /// Stored without symbols because parsing failed, or not stored at all
+    /// (unreadable file; see the warning log)
+    Failed,
+}
+
+/// Progress notification sent once per file a walk or change handles.
+///
+/// Library users can pass a `Sender<IndexedFile>` to `on_project_discovery` or
+/// `process_file_change` to stream indexing results without scraping logs;
+/// `codeix build` renders them as its progress line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedFile {
+    /// File path relative to the project root
+    pub path: String,
+    /// Project path (relative from workspace root, empty for root project)
+    pub project: String,
+    /// Number of symbols extracted from the file (0 unless `Indexed`)
+    pub symbol_count: usize,
+    pub status: IndexStatus,
+}
 
 /// Run the main event loop for file watching.
 ///
 /// Receives events from all mounts via `rx` (notify watchers already initialized).
 /// Each event includes the mount root, avoiding the need for mount lookup.
 /// Uses `tx` for passing to new project discoveries.
+///
+/// A path is handled once no event arrived for it during `options.debounce`;
+/// pending paths are checked at least every `options.poll_interval`, so a path
+/// may wait up to `debounce + poll_interval`.
+///
+/// Every `options.validate_interval` (never if zero), mounts whose root no longer
+/// exists are dropped (see `drop_vanished_mounts`): a safety net for deletions
+/// notify did not report.
+///
+/// Every `options.flush_interval` (never if zero), dirty mounts are written to
+/// `.codeindex/` (see `flush_dirty_mounts`), bounding how stale the index on
+/// disk can be if the server is killed. Unlike the flush-on-change dropped in
+/// issue #10, this batches every change of the interval into one write.
 pub fn run_event_loop(
     rx: Receiver<MountedEvent>,
     tx: Sender<MountedEvent>,
     mount_table: Arc<Mutex<MountTable>>,
     db: Arc<Mutex<SearchDb>>,
+    options: WatchOptions,
 ) -> Result<()> {
+    let WatchOptions {
+        debounce,
+        poll_interval,
+        validate_interval,
+        flush_interval,
+    } = options;
     let total_watched = {
         let mt = mount_table
             .lock()
@@ -49,14 +136,18 @@ pub fn run_event_loop(
 
     // Debounce state: path -> (last event time, event kind, mount root)
     let mut pending: HashMap<PathBuf, (Instant, EventKind, PathBuf)> = HashMap::new();
+    let mut last_validation = Instant::now();
+    let mut last_flush = Instant::now();
 
     loop {
         // Wait for events with timeout
-        match rx.recv_timeout(POLL_INTERVAL) {
+        match rx.recv_timeout(poll_interval) {
             Ok((mount_root, Ok(event))) => {
                 let now = Instant::now();
-                for path in event.paths {
-                    pending.insert(path, (now, event.kind, mount_root.clone()));
+                let path_count = event.paths.len();
+                for (i, path) in event.paths.into_iter().enumerate() {
+                    let kind = path_event_kind(&event.kind, i, path_count);
+                    pending.insert(path, (now, kind, mount_root.clone()));
                 }
             }
             Ok((_, Err(e))) => {
@@ -80,7 +171,7 @@ pub fn run_event_loop(
         let now = Instant::now();
         let ready: Vec<(PathBuf, EventKind, PathBuf)> = pending
             .iter()
-            .filter(|&(_, (time, _, _))| now.duration_since(*time) >= DEBOUNCE_DELAY)
+            .filter(|&(_, (time, _, _))| now.duration_since(*time) >= debounce)
             .map(|(path, (_, kind, mount_root))| (path.clone(), *kind, mount_root.clone()))
             .collect();
 
@@ -89,13 +180,50 @@ pub fn run_event_loop(
                 pending.remove(path);
             }
 
+            // A project whose .git just appeared gets walked in full on discovery:
+            // per-file events still settling under its root would only index twice.
+            // An already mounted root is not walked again, so its events must stay.
+            let new_roots: Vec<&Path> = {
+                let mt = mount_table
+                    .lock()
+                    .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+                ready
+                    .iter()
+                    .filter(|(path, kind, _)| {
+                        matches!(kind, EventKind::Create(_))
+                            && path.file_name().is_some_and(|n| n == ".git")
+                    })
+                    .filter_map(|(path, _, _)| path.parent())
+                    .filter(|root| !is_mounted_root(&mt, root))
+                    .collect()
+            };
+            for root in new_roots {
+                pending.retain(|pending_path, _| !pending_path.starts_with(root));
+            }
+
             if let Err(e) = handle_events(&ready, &mount_table, &db, tx.clone()) {
                 tracing::error!("error handling watch events: {}", e);
             }
         }
 
-        // Note: Auto-flush disabled (issue #10). Use flush_index MCP tool to flush explicitly.
-        // Mounts are still marked dirty and will be flushed on graceful shutdown.
+        if !validate_interval.is_zero() && last_validation.elapsed() >= validate_interval {
+            last_validation = Instant::now();
+            if let Err(e) = drop_vanished_mounts(&mount_table, &db) {
+                tracing::error!("error validating mounts: {}", e);
+            }
+        }
+
+        if !flush_interval.is_zero() && last_flush.elapsed() >= flush_interval {
+            last_flush = Instant::now();
+            match flush_dirty_mounts(&mount_table, &db) {
+                Ok(0) => {}
+                Ok(flushed) => tracing::info!("periodic flush: {} projects", flushed),
+                Err(e) => tracing::error!("error flushing dirty mounts: {}", e),
+            }
+        }
+
+        // Note: Auto-flush on change disabled (issue #10). Use flush_index MCP tool or
+        // --flush-every to flush; dirty mounts are also flushed on graceful shutdown.
         // External flush requests via .codeindex.flush are handled in handle_events().
     }
 
@@ -109,6 +237,7 @@ pub fn run_event_loop(
 /// - `load_from_cache`: If true (serve mode), try loading from .codeindex/ first.
 ///   If false (build mode), always re-index
 /// - `tx`: If provided, initializes file watcher during walk
+/// - `progress`: If provided, receives an `IndexedFile` for each file walked
 ///
 /// Flow:
 /// 1. If already mounted, skip
@@ -122,6 +251,7 @@ pub fn on_project_discovery(
     db: &Arc<Mutex<SearchDb>>,
     load_from_cache: bool,
     tx: Option<Sender<MountedEvent>>,
+    progress: Option<&Sender<IndexedFile>>,
 ) -> Result<()> {
     let mut mt = mount_table
         .lock()
@@ -138,6 +268,7 @@ pub fn on_project_discovery(
     // Mount the new project (tries RW, falls back to RO if lock held)
     let mount = mt.mount(project_root)?;
     let is_read_only = mount.mode == MountMode::ReadOnly;
+    let index_dir = mount.index_dir().to_path_buf();
 
     let project_name = project_root
         .file_name()
@@ -162,8 +293,29 @@ pub fn on_project_discovery(
 
     drop(mt);
 
+    // A persisted database that already holds this project only needs a walk:
+    // unchanged files are skipped by hash, changed ones re-parsed, vanished ones dropped
+    let db_guard = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+    if db_guard.has_project(&project_str)? {
+        if is_read_only {
+            // Another process owns this project: drop our copy and take its .codeindex/
+            db_guard.remove_project(&project_str)?;
+            db_guard.rebuild_fts_project(&project_str)?;
+        } else {
+            drop(db_guard);
+            tracing::info!(
+                "refreshing '{}' ({}) from persisted database",
+                project_name,
+                mode_str
+            );
+            return walk_project(project_root, mount_table, db, load_from_cache, tx, progress);
+        }
+    }
+    drop(db_guard);
+
     // Try loading from .codeindex/ first (only if load_from_cache is true)
-    let index_dir = project_root.join(".codeindex");
     if load_from_cache && index_dir.is_dir() {
         match read_index(&index_dir) {
             Ok((manifest, idx_files, idx_symbols, idx_texts, idx_refs)) => {
@@ -194,7 +346,13 @@ pub fn on_project_discovery(
                 // Walk to set up directory watches and discover subprojects.
                 // Subprojects are loaded from their own .codeindex/ directories.
                 // Even if tx=None (no watcher), we still need to discover subprojects.
-                init_watchers_and_discover_subprojects(project_root, mount_table, db, tx)?;
+                init_watchers_and_discover_subprojects(
+                    project_root,
+                    mount_table,
+                    db,
+                    tx,
+                    progress,
+                )?;
 
                 // Loaded from cache - no need to index files
                 return Ok(());
@@ -230,7 +388,7 @@ pub fn on_project_discovery(
     tracing::info!("indexing '{}' ({})", project_name, mode_str);
 
     // Walk and index all files in the new project (also discovers subprojects)
-    walk_project(project_root, mount_table, db, load_from_cache, tx)?;
+    walk_project(project_root, mount_table, db, load_from_cache, tx, progress)?;
 
     Ok(())
 }
@@ -243,6 +401,7 @@ fn init_watchers_and_discover_subprojects(
     mount_table: &Arc<Mutex<MountTable>>,
     db: &Arc<Mutex<SearchDb>>,
     tx: Option<Sender<MountedEvent>>,
+    progress: Option<&Sender<IndexedFile>>,
 ) -> Result<()> {
     // Collect subprojects to process after releasing lock
     let mut subprojects: Vec<PathBuf> = Vec::new();
@@ -273,7 +432,7 @@ fn init_watchers_and_discover_subprojects(
     // Process discovered subprojects - load from their own .codeindex/
     // Pass load_from_cache=true so subprojects also load from cache
     for root in &subprojects {
-        if let Err(e) = on_project_discovery(root, mount_table, db, true, tx.clone()) {
+        if let Err(e) = on_project_discovery(root, mount_table, db, true, tx.clone(), progress) {
             tracing::warn!("failed to load subproject {}: {}", root.display(), e);
         }
     }
@@ -287,19 +446,25 @@ fn init_watchers_and_discover_subprojects(
 /// Parameters:
 /// - `load_from_cache`: passed to recursive on_project_discovery calls for subprojects
 /// - `tx`: If provided, initializes watcher and adds directories during walk
+/// - `progress`: If provided, receives an `IndexedFile` for each file walked
 fn walk_project(
     project_root: &Path,
     mount_table: &Arc<Mutex<MountTable>>,
     db: &Arc<Mutex<SearchDb>>,
     load_from_cache: bool,
     tx: Option<Sender<MountedEvent>>,
+    progress: Option<&Sender<IndexedFile>>,
 ) -> Result<()> {
     // Use relative project path from workspace root
-    let project_str = {
+    let (project_str, max_file_bytes, jobs) = {
         let mt = mount_table
             .lock()
             .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
-        mt.relative_project(project_root)
+        (
+            mt.relative_project(project_root),
+            mt.max_file_bytes(),
+            mt.jobs(),
+        )
     };
 
     // Collect events first, then process them
@@ -307,7 +472,7 @@ fn walk_project(
     let mut files: Vec<(PathBuf, String)> = Vec::new();
     let mut subprojects: Vec<PathBuf> = Vec::new();
 
-    {
+    let (policy, reuse_from) = {
         let mut mt = mount_table
             .lock()
             .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
@@ -339,44 +504,95 @@ fn walk_project(
             }
             Ok(())
         })?;
-    } // MountTable lock released here
+        let policy = mount.filter_policy();
+        let reuse_from = mt.projects_with_policy(&policy);
+        (policy, reuse_from)
+    }; // MountTable lock released here
 
     // Process files
-    let mut file_count = 0u32;
-    for (abs_path, rel_path) in &files {
-        file_count += 1;
-        if file_count.is_multiple_of(100) {
-            tracing::info!(
-                "processed {} files so far for project '{}'",
-                file_count,
-                project_str
-            );
+    let outcomes = if jobs > 1 && files.len() > 1 {
+        process_files_parallel(
+            &files,
+            &project_str,
+            db,
+            max_file_bytes,
+            &policy,
+            &reuse_from,
+            jobs,
+            progress,
+        )?
+    } else {
+        let mut outcomes = Vec::with_capacity(files.len());
+        for (abs_path, rel_path) in &files {
+            outcomes.push(process_file_change(
+                abs_path,
+                rel_path,
+                &project_str,
+                db,
+                max_file_bytes,
+                &policy,
+                &reuse_from,
+                progress,
+            ));
+            if outcomes.len().is_multiple_of(100) {
+                tracing::info!(
+                    "processed {} files so far for project '{}'",
+                    outcomes.len(),
+                    project_str
+                );
+            }
         }
-        if let Err(e) = process_file_change(abs_path, rel_path, &project_str, db) {
-            tracing::warn!("failed to index {}: {}", rel_path, e);
+        outcomes
+    };
+
+    let file_count = files.len();
+    let mut partial_count = 0u32;
+    let mut failed_count = 0u32;
+    for ((_, rel_path), outcome) in files.iter().zip(outcomes) {
+        match outcome {
+            Ok(ParseOutcome::Complete) => {}
+            Ok(ParseOutcome::Partial { .. }) => partial_count += 1,
+            Ok(ParseOutcome::Failed(_)) => failed_count += 1,
+            Err(e) => {
+                tracing::warn!("failed to index {}: {}", rel_path, e);
+                notify_indexed(progress, rel_path, &project_str, 0, IndexStatus::Failed);
+            }
         }
     }
 
     // Process subprojects (always - this is the single walk strategy)
     // Pass load_from_cache to recursive calls
     for root in &subprojects {
-        if let Err(e) = on_project_discovery(root, mount_table, db, load_from_cache, tx.clone()) {
+        if let Err(e) =
+            on_project_discovery(root, mount_table, db, load_from_cache, tx.clone(), progress)
+        {
             tracing::warn!("failed to handle subproject {}: {}", root.display(), e);
         }
     }
 
     tracing::info!(
-        "finished indexing project '{}': {} files, {} subprojects",
+        "finished indexing project '{}': {} files ({} with syntax errors, {} failed to parse), {} subprojects",
         project_str,
         file_count,
+        partial_count,
+        failed_count,
         subprojects.len()
     );
 
-    // Rebuild FTS after batch indexing
+    // Rebuild this project's FTS rows after batch indexing (other projects are untouched)
     let db_guard = db
         .lock()
         .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
-    db_guard.rebuild_fts()?;
+
+    // Drop files no longer on disk (only a persisted database can hold any)
+    let walked: HashSet<&str> = files.iter().map(|(_, rel)| rel.as_str()).collect();
+    for stale in db_guard.file_paths(&project_str)? {
+        if !walked.contains(stale.as_str()) {
+            db_guard.remove_file(&project_str, &stale)?;
+        }
+    }
+
+    db_guard.rebuild_fts_project(&project_str)?;
 
     // Mark mount as dirty
     mount_table
@@ -387,6 +603,143 @@ fn walk_project(
     Ok(())
 }
 
+/// Index the files of a walk on `jobs` threads, for [`walk_project`].
+///
+/// Stored hashes are read up front so workers never lock the database: they
+/// skip unchanged files, then read and parse the rest. Upserts then run on the
+/// single connection in walk order, so the rows (and the FTS rebuilt from them)
+/// do not depend on thread scheduling. Each file then goes through the same
+/// [`copy_identical_file`] step as in [`process_file_change`]: a file identical
+/// to one stored before it is parsed for nothing, but stored as the same copy,
+/// so the rows do not depend on `jobs` either.
+///
+/// Returns one outcome per file, in the order of `files`.
+#[allow(clippy::too_many_arguments)]
+fn process_files_parallel(
+    files: &[(PathBuf, String)],
+    project: &str,
+    db: &Arc<Mutex<SearchDb>>,
+    max_file_bytes: u64,
+    policy: &FilterPolicy,
+    reuse_from: &[String],
+    jobs: usize,
+    progress: Option<&Sender<IndexedFile>>,
+) -> Result<Vec<Result<ParseOutcome>>> {
+    let stored = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .file_hashes(project)?;
+
+    // Workers pull the next file index until the list is exhausted
+    let next = AtomicUsize::new(0);
+    let mut extracted: Vec<Option<Result<Option<ExtractedFile>>>> =
+        (0..files.len()).map(|_| None).collect();
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.min(files.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((abs_path, rel_path)) = files.get(i) else {
+                            break;
+                        };
+                        let old_hash = stored.get(rel_path).map(String::as_str);
+                        done.push((
+                            i,
+                            read_and_extract(
+                                abs_path,
+                                rel_path,
+                                project,
+                                old_hash,
+                                max_file_bytes,
+                                policy,
+                            ),
+                        ));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for worker in workers {
+            // A panicking worker leaves its files as None (reported below)
+            if let Ok(done) = worker.join() {
+                for (i, result) in done {
+                    extracted[i] = Some(result);
+                }
+            }
+        }
+    });
+
+    let db_guard = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+    let mut outcomes = Vec::with_capacity(files.len());
+    for ((_, rel_path), result) in files.iter().zip(extracted) {
+        outcomes.push(match result {
+            None => Err(anyhow::anyhow!("indexing thread panicked")),
+            Some(Err(e)) => Err(e),
+            // Unchanged since the stored hash
+            Some(Ok(None)) => {
+                notify_indexed(progress, rel_path, project, 0, IndexStatus::Unchanged);
+                Ok(ParseOutcome::Complete)
+            }
+            Some(Ok(Some(parsed))) => copy_identical_file(
+                &db_guard,
+                &parsed.entry.hash,
+                parsed.entry.lang.as_deref(),
+                rel_path,
+                project,
+                reuse_from,
+            )
+            .map(|copy| copy.unwrap_or(parsed))
+            .and_then(|file| {
+                store_extracted(&db_guard, project, &file)?;
+                notify_parsed(progress, rel_path, project, &file.symbols, &file.outcome);
+                Ok(file.outcome)
+            }),
+        });
+    }
+    Ok(outcomes)
+}
+
+/// Read and parse one file unless its streamed hash equals `old_hash`
+/// (then None). Touches neither the database nor the mount table.
+fn read_and_extract(
+    abs_path: &Path,
+    rel_path: &str,
+    project: &str,
+    old_hash: Option<&str>,
+    max_file_bytes: u64,
+    policy: &FilterPolicy,
+) -> Result<Option<ExtractedFile>> {
+    if let Some(old_hash) = old_hash
+        && hash_file(abs_path).with_context(|| format!("failed to hash {}", rel_path))? == old_hash
+    {
+        tracing::trace!(
+            "skipping unchanged file: {} (project: {})",
+            rel_path,
+            project
+        );
+        return Ok(None);
+    }
+
+    let content =
+        std::fs::read(abs_path).with_context(|| format!("failed to read {}", rel_path))?;
+    let hash = hash_bytes(&content);
+    let lang = detect_language_for_path(abs_path, &content).map(String::from);
+    tracing::info!("indexing file: {} (project: {})", rel_path, project);
+    Ok(Some(extract_file(
+        &content,
+        hash,
+        lang,
+        rel_path,
+        project,
+        max_file_bytes,
+        policy,
+    )))
+}
+
 /// Handle a batch of file system events.
 ///
 /// All logic (gitignore, SKIP_ENTRIES, project detection, watches) is delegated
@@ -459,12 +812,19 @@ fn handle_events(
     }
 
     // Process mount events
+    let mount_events = {
+        let mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        coalesce_project_events(mount_events, |root| is_mounted_root(&mt, root))
+    };
     for event in mount_events {
         match event {
             FsEvent::ProjectAdded { root } => {
                 // Discover the new project (watcher is initialized during walk)
                 // Watch mode always uses cache (load_from_cache=true)
-                if let Err(e) = on_project_discovery(&root, mount_table, db, true, Some(tx.clone()))
+                if let Err(e) =
+                    on_project_discovery(&root, mount_table, db, true, Some(tx.clone()), None)
                 {
                     tracing::warn!("failed to handle project discovery: {}", e);
                 }
@@ -473,16 +833,44 @@ fn handle_events(
                 let abs_path = mount.join(&path);
 
                 // Compute relative project path from workspace root
-                let project_str = {
+                let (project_str, max_file_bytes, policy, reuse_from) = {
                     let mt = mount_table
                         .lock()
                         .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
-                    mt.relative_project(&mount)
+                    let policy = mt
+                        .find_mount(&mount)
+                        .map(Mount::filter_policy)
+                        .unwrap_or_default();
+                    let reuse_from = mt.projects_with_policy(&policy);
+                    (
+                        mt.relative_project(&mount),
+                        mt.max_file_bytes(),
+                        policy,
+                        reuse_from,
+                    )
                 };
 
-                if let Err(e) = process_file_change(&abs_path, &path, &project_str, db) {
+                if let Err(e) = process_file_change(
+                    &abs_path,
+                    &path,
+                    &project_str,
+                    db,
+                    max_file_bytes,
+                    &policy,
+                    &reuse_from,
+                    None,
+                ) {
                     tracing::warn!("failed to process file {}: {}", path, e);
                 } else {
+                    // Refresh only this file's FTS rows
+                    if let Err(e) = db
+                        .lock()
+                        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+                        .update_fts_for_file(&project_str, &path)
+                    {
+                        tracing::warn!("failed to update search index for {}: {}", path, e);
+                    }
+
                     // Mark mount as dirty
                     mount_table
                         .lock()
@@ -508,6 +896,10 @@ fn handle_events(
                 if let Err(e) = db_guard.remove_file(&project_str, &path) {
                     tracing::warn!("failed to remove file {}: {}", path, e);
                 }
+                if let Err(e) = db_guard.update_fts_for_file(&project_str, &path) {
+                    tracing::warn!("failed to update search index for {}: {}", path, e);
+                }
+                drop(db_guard);
                 // Mark mount as dirty
                 mount_table
                     .lock()
@@ -515,192 +907,790 @@ fn handle_events(
                     .map(|mut mt| mt.mark_dirty_canonical(&abs_path));
             }
             FsEvent::ProjectRemoved { root } => {
-                // Compute relative project path from workspace root
-                let project_str = {
-                    let mt = mount_table
-                        .lock()
-                        .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
-                    mt.relative_project(&root)
-                };
-
-                tracing::info!("project removed: {} ({})", project_str, root.display());
-
-                // Remove project data from DB
-                let db_guard = db
-                    .lock()
-                    .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
-                if let Err(e) = db_guard.remove_project(&project_str) {
-                    tracing::warn!("failed to remove project {}: {}", project_str, e);
-                }
-                drop(db_guard);
-
-                // Unmount the project (use unmount_path since directory may be deleted)
-                let mut mt = mount_table
-                    .lock()
-                    .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
-                if !mt.unmount_path(&root) {
-                    tracing::debug!("project was not mounted: {}", root.display());
+                tracing::info!("project removed: {}", root.display());
+                if let Err(e) = remove_mounted_project(&root, mount_table, db) {
+                    tracing::warn!("failed to remove project {}: {}", root.display(), e);
                 }
             }
             FsEvent::DirIgnored => {} // Not emitted from notify events
         }
     }
 
-    // Rebuild FTS indexes once after all changes
-    {
-        let db_guard = db
-            .lock()
-            .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
-        db_guard.rebuild_fts()?;
-    }
-
     Ok(())
 }
 
-/// Process a single file change (create or modify).
-pub fn process_file_change(
-    abs_path: &Path,
-    rel_path: &str,
-    project: &str,
+/// Drop a project from the DB (rebuilding its FTS rows) and unmount it.
+fn remove_mounted_project(
+    root: &Path,
+    mount_table: &Arc<Mutex<MountTable>>,
     db: &Arc<Mutex<SearchDb>>,
 ) -> Result<()> {
-    // Read file content once
-    let content =
-        std::fs::read(abs_path).with_context(|| format!("failed to read {}", rel_path))?;
-
-    // Hash the content
-    let new_hash = hash_bytes(&content);
+    // Compute relative project path from workspace root
+    let project_str = {
+        let mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        mt.relative_project(root)
+    };
 
-    // Check if hash changed
+    // Remove project data from DB
     let db_guard = db
         .lock()
         .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
-    if let Some(old_hash) = db_guard.get_file_hash(project, rel_path)?
-        && old_hash == new_hash
-    {
-        // No change, skip
-        tracing::trace!(
-            "skipping unchanged file: {} (project: {})",
-            rel_path,
-            project
-        );
-        return Ok(());
+    if let Err(e) = db_guard.remove_project(&project_str) {
+        tracing::warn!("failed to remove project {}: {}", project_str, e);
     }
+    db_guard.rebuild_fts_project(&project_str)?;
     drop(db_guard);
 
-    tracing::info!("indexing file: {} (project: {})", rel_path, project);
-
-    // Count lines
-    let line_count = count_lines(&content);
-
-    // Detect language
-    let lang = abs_path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .and_then(detect_language)
-        .map(String::from);
-
-    let mut symbols = Vec::new();
-    let mut texts = Vec::new();
-    let mut references = Vec::new();
-    let mut title = None;
-    let mut description = None;
-
-    // Parse source files for symbols, texts, and references
-    if let Some(ref lang_name) = lang {
-        match parse_file(&content, lang_name, rel_path) {
-            Ok((file_symbols, file_texts, file_refs)) => {
-                symbols = file_symbols;
-                texts = file_texts;
-                references = file_refs;
-            }
-            Err(e) => {
-                tracing::warn!("failed to parse {}: {}", rel_path, e);
-            }
-        }
-
-        // Extract file metadata (title and description)
-        let metadata = extract_file_metadata(&content, lang_name);
-        title = metadata.title;
-        description = metadata.description;
+    // Unmount the project (use unmount_path since directory may be deleted)
+    let mut mt = mount_table
+        .lock()
+        .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+    if !mt.unmount_path(root) {
+        tracing::debug!("project was not mounted: {}", root.display());
     }
+    Ok(())
+}
 
-    let file_entry = FileEntry {
-        path: rel_path.to_string(),
-        lang,
-        hash: new_hash,
-        lines: line_count,
-        project: project.to_string(),
-        title,
-        description,
+/// Remove every mount whose root directory no longer exists, with its DB rows.
+///
+/// Complements `ProjectRemoved` events: notify can miss a deletion (e.g. an
+/// `rm -rf` of a nested tree whose events got coalesced). Returns the number
+/// of mounts removed.
+pub fn drop_vanished_mounts(
+    mount_table: &Arc<Mutex<MountTable>>,
+    db: &Arc<Mutex<SearchDb>>,
+) -> Result<usize> {
+    let vanished: Vec<PathBuf> = {
+        let mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        mt.iter()
+            .map(|(root, _)| root.clone())
+            .filter(|root| !root.exists())
+            .collect()
     };
+    for root in &vanished {
+        tracing::info!("mount root vanished: {}", root.display());
+        remove_mounted_project(root, mount_table, db)?;
+    }
+    Ok(vanished.len())
+}
 
-    // Upsert into database
+/// Remove the DB rows of every project without a mount, rebuilding its FTS rows.
+///
+/// A persisted database keeps whatever projects earlier runs stored; once
+/// discovery has mounted everything still on disk, the others (deleted, or no
+/// longer a project) would stay searchable forever. Returns the projects removed.
+pub fn drop_unmounted_projects(
+    mount_table: &Arc<Mutex<MountTable>>,
+    db: &Arc<Mutex<SearchDb>>,
+) -> Result<Vec<String>> {
+    let mounted: HashSet<String> = {
+        let mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        mt.iter()
+            .map(|(root, _)| mt.relative_project(root))
+            .collect()
+    };
     let db_guard = db
         .lock()
         .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
-    db_guard.upsert_file(project, &file_entry, &symbols, &texts, &references)?;
-
-    Ok(())
+    let stale: Vec<String> = db_guard
+        .list_projects()?
+        .into_iter()
+        .filter(|project| !mounted.contains(project))
+        .collect();
+    for project in &stale {
+        tracing::info!("dropping project '{}': no longer mounted", project);
+        db_guard.remove_project(project)?;
+        db_guard.rebuild_fts_project(project)?;
+    }
+    Ok(stale)
 }
 
-/// Request a flush from a running server by creating a trigger file.
-/// Waits for the server to delete the file (confirming flush) or times out.
-fn request_flush_and_wait(project_root: &Path) -> Result<()> {
-    let trigger_path = project_root.join(FLUSH_TRIGGER_FILE);
-
-    // Create trigger file
-    std::fs::write(&trigger_path, "").with_context(|| {
-        format!(
-            "failed to create flush trigger at {}",
-            trigger_path.display()
-        )
-    })?;
-
-    tracing::info!(
-        "requesting flush from server (trigger: {})",
-        trigger_path.display()
-    );
+/// Whether `root` (as reported by notify, possibly not canonical) is mounted.
+fn is_mounted_root(mt: &MountTable, root: &Path) -> bool {
+    mt.is_mounted(root) || root.canonicalize().is_ok_and(|root| mt.is_mounted(&root))
+}
 
-    // Wait for server to delete the trigger file
-    let start = std::time::Instant::now();
-    while trigger_path.exists() {
-        if start.elapsed() > FLUSH_TIMEOUT {
-            // Clean up trigger file on timeout
-            let _ = std::fs::remove_file(&trigger_path);
-            anyhow::bail!(
-                "timeout waiting for server to flush ({}s). Is codeix serve running?",
-                FLUSH_TIMEOUT.as_secs()
-            );
-        }
-        std::thread::sleep(FLUSH_POLL_INTERVAL);
+/// Coalesce a burst of mount events around newly discovered projects.
+///
+/// Each `ProjectAdded` root is kept once, and file events under it are dropped:
+/// the discovery walk indexes the whole tree, so handling them as well would
+/// index files twice (and into the parent project) or chase files that are
+/// still being moved into place, e.g. during `git worktree add`. Roots for which
+/// `is_mounted` holds are not walked again, so their file events are kept.
+fn coalesce_project_events(
+    events: Vec<FsEvent>,
+    is_mounted: impl Fn(&Path) -> bool,
+) -> Vec<FsEvent> {
+    let new_roots: Vec<PathBuf> = events
+        .iter()
+        .filter_map(|event| match event {
+            FsEvent::ProjectAdded { root } if !is_mounted(root) => Some(root.clone()),
+            _ => None,
+        })
+        .collect();
+    if !events
+        .iter()
+        .any(|event| matches!(event, FsEvent::ProjectAdded { .. }))
+    {
+        return events;
     }
 
-    tracing::info!("flush completed by server");
-    Ok(())
+    let under_new_root = |abs: &Path| new_roots.iter().any(|root| abs.starts_with(root));
+    let mut seen_roots = HashSet::new();
+    events
+        .into_iter()
+        .filter(|event| match event {
+            FsEvent::ProjectAdded { root } => seen_roots.insert(root.clone()),
+            FsEvent::FileAdded { mount, path } | FsEvent::FileRemoved { mount, path } => {
+                !under_new_root(&mount.join(path))
+            }
+            FsEvent::ProjectRemoved { .. } | FsEvent::DirIgnored => true,
+        })
+        .collect()
 }
 
-/// Handle flush trigger file (.codeindex.flush) - flush and delete to signal completion.
-fn handle_flush_trigger(
-    trigger_path: &Path,
+/// Force a re-parse of a project's files, or only those under `path`
+/// (a file or a directory, relative to the project root).
+///
+/// Stored rows of every matching file are removed first, so `process_file_change`
+/// parses them again even when their hash is unchanged (e.g. after a parser fix);
+/// matching files gone from disk stay removed. The mount is walked again to find
+/// the files on disk; subprojects are left to their own mounts.
+///
+/// Returns the number of files reprocessed.
+pub fn reindex_project(
     mount_table: &Arc<Mutex<MountTable>>,
     db: &Arc<Mutex<SearchDb>>,
-) -> Result<()> {
-    let mount_root = trigger_path
-        .parent()
-        .ok_or_else(|| anyhow::anyhow!("trigger has no parent"))?;
-
-    tracing::info!(
-        "flush requested via trigger file for {}",
-        mount_root.display()
-    );
+    project: &str,
+    path: Option<&str>,
+) -> Result<usize> {
+    let prefix = path.map(|p| p.trim_matches('/')).filter(|p| !p.is_empty());
+    let matches = |rel: &str| match prefix {
+        None => true,
+        Some(p) => {
+            rel == p
+                || rel
+                    .strip_prefix(p)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        }
+    };
 
-    // Flush the mount
-    {
-        let mt = mount_table
+    // Walk the mount again for the matching files currently on disk
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    let (project_root, max_file_bytes, policy, reuse_from) = {
+        let mut mt = mount_table
             .lock()
             .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
-        flush_mount_to_disk(mount_root, &mt, db)?;
+        let max_file_bytes = mt.max_file_bytes();
+        let project_root = mt
+            .project_root(project)
+            .ok_or_else(|| anyhow::anyhow!("project not mounted: '{}'", project))?;
+        let mount = mt
+            .find_mount_mut(&project_root)
+            .ok_or_else(|| anyhow::anyhow!("no mount found for {}", project_root.display()))?;
+        mount.walk(|event| {
+            if let FsEvent::FileAdded { mount, path } = event
+                && matches(&path)
+            {
+                files.push((mount.join(&path), path));
+            }
+            Ok(())
+        })?;
+        // Pick up edits to .codeix.toml since the project was mounted
+        mount.load_filter_policy();
+        let policy = mount.filter_policy();
+        let reuse_from = mt.projects_with_policy(&policy);
+        (project_root, max_file_bytes, policy, reuse_from)
+    }; // MountTable lock released here
+
+    {
+        let db_guard = db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+        for stored in db_guard.file_paths(project)? {
+            if matches(&stored) {
+                db_guard.remove_file(project, &stored)?;
+            }
+        }
+    }
+
+    let mut reprocessed = 0;
+    for (abs_path, rel_path) in &files {
+        match process_file_change(
+            abs_path,
+            rel_path,
+            project,
+            db,
+            max_file_bytes,
+            &policy,
+            &reuse_from,
+            None,
+        ) {
+            Ok(_) => reprocessed += 1,
+            Err(e) => tracing::warn!("failed to reindex {}: {}", rel_path, e),
+        }
+    }
+
+    db.lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .rebuild_fts_project(project)?;
+    mount_table
+        .lock()
+        .ok()
+        .map(|mut mt| mt.mark_dirty(&project_root));
+
+    tracing::info!(
+        "reindexed {} files in project '{}'{}",
+        reprocessed,
+        project,
+        prefix.map(|p| format!(" under {p}")).unwrap_or_default()
+    );
+    Ok(reprocessed)
+}
+
+/// Reindex only the files git reports as changed between `since` and HEAD in
+/// one mounted project (`build --since`), then mark its mount dirty.
+///
+/// Listed files still go through the hash check, so unchanged ones stay cheap;
+/// deleted ones are removed. Paths inside subprojects or ignored by the mount's
+/// rules are skipped. Falls back to a full walk when the project is not in a
+/// git work tree or `since` does not resolve.
+///
+/// Returns the number of changed files processed; `progress` receives an
+/// `IndexedFile` for each.
+pub fn index_changed_since(
+    project_root: &Path,
+    mount_table: &Arc<Mutex<MountTable>>,
+    db: &Arc<Mutex<SearchDb>>,
+    since: &str,
+    progress: Option<&Sender<IndexedFile>>,
+) -> Result<usize> {
+    let Some(changed) = git_changed_files(project_root, since) else {
+        tracing::warn!(
+            "cannot diff {} against '{}', walking it in full",
+            project_root.display(),
+            since
+        );
+        walk_project(project_root, mount_table, db, false, None, progress)?;
+        return Ok(0);
+    };
+
+    // Let the mount filter the listed paths like walker and watcher events
+    let mut events: Vec<FsEvent> = Vec::new();
+    let (project_str, max_file_bytes, policy, reuse_from) = {
+        let mut mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        for rel in &changed {
+            let abs_path = project_root.join(rel);
+            // Files of a subproject belong to that subproject's own mount
+            if mt
+                .find_mount_canonical(&abs_path)
+                .is_none_or(|m| m.root != project_root)
+            {
+                continue;
+            }
+            let mount = mt
+                .find_mount_mut_canonical(project_root)
+                .ok_or_else(|| anyhow::anyhow!("no mount found for {}", project_root.display()))?;
+            let kind = if abs_path.is_file() {
+                EventKind::Create(CreateKind::File)
+            } else {
+                EventKind::Remove(RemoveKind::File)
+            };
+            events.extend(mount.on_fs_event(&abs_path, &kind));
+        }
+        let policy = mt
+            .find_mount_canonical(project_root)
+            .map(Mount::filter_policy)
+            .unwrap_or_default();
+        let reuse_from = mt.projects_with_policy(&policy);
+        (
+            mt.relative_project(project_root),
+            mt.max_file_bytes(),
+            policy,
+            reuse_from,
+        )
+    }; // MountTable lock released here
+
+    let mut processed = 0;
+    for event in &events {
+        match event {
+            FsEvent::FileAdded { mount, path } => {
+                match process_file_change(
+                    &mount.join(path),
+                    path,
+                    &project_str,
+                    db,
+                    max_file_bytes,
+                    &policy,
+                    &reuse_from,
+                    progress,
+                ) {
+                    Ok(_) => processed += 1,
+                    Err(e) => {
+                        tracing::warn!("failed to index {}: {}", path, e);
+                        notify_indexed(progress, path, &project_str, 0, IndexStatus::Failed);
+                    }
+                }
+            }
+            FsEvent::FileRemoved { path, .. } => {
+                db.lock()
+                    .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+                    .remove_file(&project_str, path)?;
+                processed += 1;
+            }
+            FsEvent::ProjectAdded { .. } | FsEvent::ProjectRemoved { .. } | FsEvent::DirIgnored => {
+            }
+        }
+    }
+
+    db.lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .rebuild_fts_project(&project_str)?;
+    mount_table
+        .lock()
+        .ok()
+        .map(|mut mt| mt.mark_dirty(project_root));
+
+    tracing::info!(
+        "reindexed {} files changed since '{}' in project '{}'",
+        processed,
+        since,
+        project_str
+    );
+    Ok(processed)
+}
+
+/// Paths (relative to `project_root`) that `git diff <since>..HEAD` lists,
+/// or None if git cannot run there or `since` is not a valid revision.
+fn git_changed_files(project_root: &Path, since: &str) -> Option<Vec<String>> {
+    // A leading dash would be read as an option, not a revision
+    if since.is_empty() || since.starts_with('-') {
+        return None;
+    }
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(project_root)
+        .args(["diff", "--name-only", "-z", "--relative", "--no-renames"])
+        .arg(format!("{since}..HEAD"))
+        .arg("--")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        tracing::debug!(
+            "git diff failed in {}: {}",
+            project_root.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    Some(
+        output
+            .stdout
+            .split(|&b| b == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).into_owned())
+            .collect(),
+    )
+}
+
+/// How a file's content fared in [`process_file_change`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseOutcome {
+    /// Parsed cleanly, reused from an identical cleanly parsed file, unchanged,
+    /// or skipped on purpose (no parser, too large, binary)
+    Complete,
+    /// Parsed, but the syntax tree has errors from this line on
+    Partial { line: u32 },
+    /// Parsing failed; the file is recorded without symbols
+    Failed(ParseError),
+}
+
+/// Process a single file change (create or modify).
+///
+/// Files larger than `max_file_bytes` get a `FileEntry` (lines, hash, language)
+/// but no symbols, texts or references: tree-sitting a generated bundle costs
+/// seconds and memory for results nobody searches. The same applies to binary
+/// content (see [`is_binary`]), whatever its extension claims.
+///
+/// A file identical to one already indexed in a project of `reuse_from` (the
+/// projects parsing with the same `policy`, see
+/// [`MountTable::projects_with_policy`]) takes a copy of its extraction results.
+///
+/// A parse failure is not an error: the file is still recorded, and the
+/// returned [`ParseOutcome`] says whether its symbols are complete.
+#[allow(clippy::too_many_arguments)]
+pub fn process_file_change(
+    abs_path: &Path,
+    rel_path: &str,
+    project: &str,
+    db: &Arc<Mutex<SearchDb>>,
+    max_file_bytes: u64,
+    policy: &FilterPolicy,
+    reuse_from: &[String],
+    progress: Option<&Sender<IndexedFile>>,
+) -> Result<ParseOutcome> {
+    // Check if hash changed before buffering the file: the stored hash is
+    // compared against a streamed hash, so unchanged files are never read whole
+    let old_hash = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .get_file_hash(project, rel_path)?;
+    if let Some(old_hash) = old_hash
+        && hash_file(abs_path).with_context(|| format!("failed to hash {}", rel_path))? == old_hash
+    {
+        // No change, skip
+        tracing::trace!(
+            "skipping unchanged file: {} (project: {})",
+            rel_path,
+            project
+        );
+        notify_indexed(progress, rel_path, project, 0, IndexStatus::Unchanged);
+        return Ok(ParseOutcome::Complete);
+    }
+
+    // Read file content once, and hash what we actually parse (the file may
+    // have changed again since the streamed hash)
+    let content =
+        std::fs::read(abs_path).with_context(|| format!("failed to read {}", rel_path))?;
+    let new_hash = hash_bytes(&content);
+
+    let db_guard = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+
+    // Detect language (file name, extension, then shebang)
+    let lang = detect_language_for_path(abs_path, &content).map(String::from);
+
+    // Same content already parsed elsewhere (copied file across projects)
+    if let Some(copy) = copy_identical_file(
+        &db_guard,
+        &new_hash,
+        lang.as_deref(),
+        rel_path,
+        project,
+        reuse_from,
+    )? {
+        store_extracted(&db_guard, project, &copy)?;
+        drop(db_guard);
+        notify_parsed(progress, rel_path, project, &copy.symbols, &copy.outcome);
+        return Ok(copy.outcome);
+    }
+    drop(db_guard);
+
+    tracing::info!("indexing file: {} (project: {})", rel_path, project);
+
+    let extracted = extract_file(
+        &content,
+        new_hash,
+        lang,
+        rel_path,
+        project,
+        max_file_bytes,
+        policy,
+    );
+
+    // Upsert into database
+    let db_guard = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+    store_extracted(&db_guard, project, &extracted)?;
+    drop(db_guard);
+    notify_parsed(
+        progress,
+        rel_path,
+        project,
+        &extracted.symbols,
+        &extracted.outcome,
+    );
+
+    Ok(extracted.outcome)
+}
+
+/// The extraction results of an identical file, rewritten to `rel_path` in
+/// `project`, or None if no file can stand in for this one.
+///
+/// Only projects of `reuse_from` (same filter policy) qualify, only cleanly
+/// parsed originals, and only languages whose extraction ignores the path
+/// (plain-text metadata depends on the extension, so unparsed files never do).
+fn copy_identical_file(
+    db: &SearchDb,
+    hash: &str,
+    lang: Option<&str>,
+    rel_path: &str,
+    project: &str,
+    reuse_from: &[String],
+) -> Result<Option<ExtractedFile>> {
+    let original = match lang {
+        Some(lang_name) if is_path_independent(lang_name) => {
+            db.find_file_by_hash(hash, Some(lang_name), reuse_from)?
+        }
+        _ => None,
+    };
+    let Some(original) = original else {
+        return Ok(None);
+    };
+
+    let (mut symbols, mut texts, mut references) =
+        db.get_file_contents(&original.project, &original.path)?;
+    for s in &mut symbols {
+        s.file = rel_path.to_string();
+        s.project = project.to_string();
+    }
+    for t in &mut texts {
+        t.file = rel_path.to_string();
+        t.project = project.to_string();
+    }
+    for r in &mut references {
+        r.file = rel_path.to_string();
+        r.project = project.to_string();
+    }
+
+    tracing::info!(
+        "indexing file: {} (project: {}, reused from {}/{})",
+        rel_path,
+        project,
+        original.project,
+        original.path
+    );
+    let entry = FileEntry {
+        path: rel_path.to_string(),
+        project: project.to_string(),
+        is_test: is_test_path(rel_path),
+        ..original
+    };
+    Ok(Some(ExtractedFile {
+        entry,
+        symbols,
+        texts,
+        references,
+        outcome: ParseOutcome::Complete,
+        parsed_clean: true,
+    }))
+}
+
+/// Upsert an extracted file, marking it as a source for copies if it parsed cleanly.
+fn store_extracted(db: &SearchDb, project: &str, file: &ExtractedFile) -> Result<()> {
+    db.upsert_file(
+        project,
+        &file.entry,
+        &file.symbols,
+        &file.texts,
+        &file.references,
+    )?;
+    if file.parsed_clean {
+        db.mark_parsed_clean(project, &file.entry.path)?;
+    }
+    Ok(())
+}
+
+/// A file hashed and parsed without touching the database, ready to upsert.
+struct ExtractedFile {
+    entry: FileEntry,
+    symbols: Vec<SymbolEntry>,
+    texts: Vec<TextEntry>,
+    references: Vec<ReferenceEntry>,
+    outcome: ParseOutcome,
+    /// Parsed in full without syntax errors (not skipped, partial or failed)
+    parsed_clean: bool,
+}
+
+/// Parse `content` (unless too large, binary or of a label-only language) and
+/// build its `FileEntry`. Pure: safe to run on several threads at once.
+fn extract_file(
+    content: &[u8],
+    hash: String,
+    lang: Option<String>,
+    rel_path: &str,
+    project: &str,
+    max_file_bytes: u64,
+    policy: &FilterPolicy,
+) -> ExtractedFile {
+    // Count lines
+    let line_count = count_lines(content);
+
+    let mut symbols = Vec::new();
+    let mut texts = Vec::new();
+    let mut references = Vec::new();
+
+    let skip_parse = if content.len() as u64 > max_file_bytes {
+        tracing::debug!(
+            "not parsing {} (project: {}): {} bytes exceeds limit of {}",
+            rel_path,
+            project,
+            content.len(),
+            max_file_bytes
+        );
+        true
+    } else if is_binary(content) {
+        tracing::debug!(
+            "not parsing {} (project: {}): binary content",
+            rel_path,
+            project
+        );
+        true
+    } else {
+        false
+    };
+
+    // Parse source files for symbols, texts, and references
+    let mut outcome = ParseOutcome::Complete;
+    let mut parsed_clean = false;
+    if let Some(ref lang_name) = lang
+        && !skip_parse
+        && is_parsed_language(lang_name)
+    {
+        let call_tokens = policy.call_tokens();
+        let augmentors: Vec<&dyn TokenAugmentor> = call_tokens
+            .iter()
+            .map(|a| a as &dyn TokenAugmentor)
+            .collect();
+        match parse_file_checked(content, lang_name, rel_path, &augmentors, policy) {
+            Ok(parsed) => {
+                if let Some(line) = parsed.error_line {
+                    tracing::debug!("syntax errors in {} from line {}", rel_path, line);
+                    outcome = ParseOutcome::Partial { line };
+                } else {
+                    parsed_clean = true;
+                }
+                symbols = parsed.symbols;
+                texts = parsed.texts;
+                references = parsed.references;
+            }
+            Err(e) => {
+                tracing::warn!("failed to parse {}: {}", rel_path, e);
+                outcome = ParseOutcome::Failed(e);
+            }
+        }
+    }
+
+    // Extract file metadata (title and description); plain-text files have no
+    // parser but can still declare them in frontmatter
+    let metadata = match &lang {
+        _ if skip_parse => FileMetadata::default(),
+        Some(lang_name) => extract_file_metadata(content, lang_name),
+        None => extract_text_file_metadata(content, rel_path),
+    };
+
+    let entry = FileEntry {
+        path: rel_path.to_string(),
+        lang,
+        hash,
+        lines: line_count,
+        project: project.to_string(),
+        title: metadata.title,
+        description: metadata.description,
+        is_test: is_test_path(rel_path),
+    };
+
+    ExtractedFile {
+        entry,
+        symbols,
+        texts,
+        references,
+        outcome,
+        parsed_clean,
+    }
+}
+
+/// Send an `IndexedFile` notification if a progress channel was provided.
+/// A disconnected receiver is not an error: the consumer just stopped listening.
+fn notify_indexed(
+    progress: Option<&Sender<IndexedFile>>,
+    rel_path: &str,
+    project: &str,
+    symbol_count: usize,
+    status: IndexStatus,
+) {
+    if let Some(progress) = progress {
+        let _ = progress.send(IndexedFile {
+            path: rel_path.to_string(),
+            project: project.to_string(),
+            symbol_count,
+            status,
+        });
+    }
+}
+
+/// Notify about a freshly parsed and stored file, failed if its parse was.
+fn notify_parsed(
+    progress: Option<&Sender<IndexedFile>>,
+    rel_path: &str,
+    project: &str,
+    symbols: &[SymbolEntry],
+    outcome: &ParseOutcome,
+) {
+    let status = match outcome {
+        ParseOutcome::Failed(_) => IndexStatus::Failed,
+        ParseOutcome::Complete | ParseOutcome::Partial { .. } => IndexStatus::Indexed,
+    };
+    notify_indexed(progress, rel_path, project, symbols.len(), status);
+}
+
+/// Request a flush from a running server by creating a trigger file.
+/// Waits for the server to delete the file (confirming flush) or times out.
+fn request_flush_and_wait(project_root: &Path) -> Result<()> {
+    let trigger_path = project_root.join(FLUSH_TRIGGER_FILE);
+
+    // Create trigger file
+    std::fs::write(&trigger_path, "").with_context(|| {
+        format!(
+            "failed to create flush trigger at {}",
+            trigger_path.display()
+        )
+    })?;
+
+    tracing::info!(
+        "requesting flush from server (trigger: {})",
+        trigger_path.display()
+    );
+
+    // Wait for server to delete the trigger file
+    let start = std::time::Instant::now();
+    while trigger_path.exists() {
+        if start.elapsed() > FLUSH_TIMEOUT {
+            // Clean up trigger file on timeout
+            let _ = std::fs::remove_file(&trigger_path);
+            anyhow::bail!(
+                "timeout waiting for server to flush ({}s). Is codeix serve running?",
+                FLUSH_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(FLUSH_POLL_INTERVAL);
+    }
+
+    tracing::info!("flush completed by server");
+    Ok(())
+}
+
+/// Handle flush trigger file (.codeindex.flush) - flush and delete to signal completion.
+fn handle_flush_trigger(
+    trigger_path: &Path,
+    mount_table: &Arc<Mutex<MountTable>>,
+    db: &Arc<Mutex<SearchDb>>,
+) -> Result<()> {
+    let mount_root = trigger_path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("trigger has no parent"))?;
+
+    tracing::info!(
+        "flush requested via trigger file for {}",
+        mount_root.display()
+    );
+
+    // Flush the mount
+    {
+        let mt = mount_table
+            .lock()
+            .map_err(|e| anyhow::anyhow!("mount table lock poisoned: {e}"))?;
+        flush_mount_to_disk(mount_root, &mt, db)?;
     }
 
     // Clear dirty flag
@@ -796,21 +1786,9 @@ pub fn flush_mount_to_disk(
         }
     }
 
-    // Derive project name from directory name
-    let name = mount_root
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    let manifest = IndexManifest {
-        version: "1.0".to_string(),
-        name,
-        root: ".".to_string(),
-        languages: languages.into_iter().collect(),
-    };
+    let manifest = index_manifest(mount_root, languages.into_iter().collect());
 
-    let output_dir = mount_root.join(".codeindex");
+    let output_dir = mount_table.index_dir(mount_root);
     write_index(&output_dir, &manifest, &files, &symbols, &texts, &refs)?;
 
     tracing::debug!(
@@ -825,11 +1803,75 @@ pub fn flush_mount_to_disk(
     Ok(())
 }
 
-/// Flush the entire index from memory to disk (legacy single-project).
-/// This is used during initial index building before MountTable is set up.
-pub fn flush_index_to_disk(root: &Path, db: &Arc<Mutex<SearchDb>>) -> Result<()> {
-    let db_guard = db
-        .lock()
+/// The `index.json` manifest of a project rooted at `root`, named after its directory.
+fn index_manifest(root: &Path, languages: Vec<String>) -> IndexManifest {
+    IndexManifest {
+        version: FORMAT_VERSION.to_string(),
+        name: root
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        root: ".".to_string(),
+        languages,
+    }
+}
+
+/// The manifest `flush_mount_to_disk` would write for a mount, with languages
+/// read from the live database rather than a possibly stale `index.json`.
+pub fn live_manifest(
+    mount_root: &Path,
+    mount_table: &MountTable,
+    db: &Arc<Mutex<SearchDb>>,
+) -> Result<IndexManifest> {
+    let project_str = mount_table.relative_project(mount_root);
+    let languages = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?
+        .languages(&project_str)?;
+    Ok(index_manifest(mount_root, languages))
+}
+
+/// Compare a mount's live index with its `.codeindex/` on disk: what
+/// `flush_mount_to_disk` would change. A mount never flushed compares against
+/// an empty index.
+pub fn diff_mount_with_disk(
+    mount_root: &Path,
+    mount_table: &MountTable,
+    db: &Arc<Mutex<SearchDb>>,
+) -> Result<IndexDiff> {
+    let project_str = mount_table.relative_project(mount_root);
+
+    let index_dir = mount_table.index_dir(mount_root);
+    // index.json doubles as the lock file: only files.jsonl marks a flushed index
+    let (disk_files, disk_symbols) = if index_dir.join("files.jsonl").is_file() {
+        let (_, files, symbols, _, _) = read_index(&index_dir)
+            .with_context(|| format!("failed to read {}", index_dir.display()))?;
+        (files, symbols)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let db_guard = db
+        .lock()
+        .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
+    let (live_files, live_symbols, _, _) = db_guard.export_for_project(&project_str)?;
+    drop(db_guard);
+
+    Ok(diff_index(
+        &project_str,
+        &disk_files,
+        &disk_symbols,
+        &live_files,
+        &live_symbols,
+    ))
+}
+
+/// Flush the entire index from memory to disk (legacy single-project).
+/// This is used during initial index building before MountTable is set up.
+pub fn flush_index_to_disk(root: &Path, db: &Arc<Mutex<SearchDb>>) -> Result<()> {
+    let db_guard = db
+        .lock()
         .map_err(|e| anyhow::anyhow!("db lock poisoned: {e}"))?;
     let (files, symbols, texts, refs) = db_guard.export_all()?;
     drop(db_guard);
@@ -842,19 +1884,7 @@ pub fn flush_index_to_disk(root: &Path, db: &Arc<Mutex<SearchDb>>) -> Result<()>
         }
     }
 
-    // Derive project name from directory name
-    let name = root
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    let manifest = IndexManifest {
-        version: "1.0".to_string(),
-        name,
-        root: ".".to_string(),
-        languages: languages.into_iter().collect(),
-    };
+    let manifest = index_manifest(root, languages.into_iter().collect());
 
     let output_dir = root.join(".codeindex");
     write_index(&output_dir, &manifest, &files, &symbols, &texts, &refs)?;
@@ -871,6 +1901,9 @@ pub fn flush_index_to_disk(root: &Path, db: &Arc<Mutex<SearchDb>>) -> Result<()>
 }
 
 /// Count the number of lines in a byte buffer.
+///
+/// Only `\n` terminates a line, so a `\r\n` pair counts once and CRLF files get
+/// the same count as their LF equivalent.
 fn count_lines(content: &[u8]) -> u32 {
     if content.is_empty() {
         return 0;
@@ -887,6 +1920,7 @@ fn count_lines(content: &[u8]) -> u32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::server::db::{SearchFilters, SearchQuery};
     use std::fs;
     use tempfile::TempDir;
 
@@ -903,6 +1937,15 @@ mod tests {
         fs::write(path, content).unwrap();
     }
 
+    #[test]
+    fn test_count_lines_crlf() {
+        assert_eq!(count_lines(b""), 0);
+        assert_eq!(count_lines(b"a\nb\n"), 2);
+        assert_eq!(count_lines(b"a\r\nb\r\n"), 2);
+        assert_eq!(count_lines(b"a\r\nb"), 2);
+        assert_eq!(count_lines(b"a\r\n\r\nb\r\n"), count_lines(b"a\n\nb\n"));
+    }
+
     #[test]
     fn test_single_project_indexing() {
         let tmp = TempDir::new().unwrap();
@@ -924,7 +1967,7 @@ mod tests {
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         // Verify: should have 2 files indexed
         let db_guard = db.lock().unwrap();
@@ -934,16 +1977,15 @@ mod tests {
 
         // Search for the main function (private, use visibility="private" to include all)
         let results = db_guard
-            .search(
-                "main",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("main")
+            })
             .unwrap();
         let symbols: Vec<_> = results
             .iter()
@@ -960,16 +2002,15 @@ mod tests {
 
         // Search for greet function (public)
         let results = db_guard
-            .search(
-                "greet",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("greet")
+            })
             .unwrap();
         let symbols: Vec<_> = results
             .iter()
@@ -1000,160 +2041,1087 @@ mod tests {
         create_git_marker(&subproject);
         create_source_file(&subproject.join("src/lib.rs"), "pub fn utility() {}\n");
 
-        // Index from root (load_from_cache=false to force indexing)
+        // Index from root (load_from_cache=false to force indexing)
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // Verify: should have 2 projects
+        let db_guard = db.lock().unwrap();
+        let projects = db_guard.list_projects().unwrap();
+        assert_eq!(projects.len(), 2);
+        assert!(projects.contains(&"".to_string())); // Root
+        assert!(projects.contains(&"libs/utils".to_string())); // Subproject
+
+        // Root project should have app_main (private fn, use visibility="private" to include all)
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("app_main")
+            })
+            .unwrap();
+        let symbols: Vec<_> = results
+            .iter()
+            .filter_map(|r| match r {
+                crate::server::db::SearchResult::Symbol(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].project, "");
+
+        // Subproject should have utility (public fn, default visibility would work but use private for consistency)
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    projects: vec!["libs/utils".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("utility")
+            })
+            .unwrap();
+        let symbols: Vec<_> = results
+            .iter()
+            .filter_map(|r| match r {
+                crate::server::db::SearchResult::Symbol(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].project, "libs/utils");
+    }
+
+    #[test]
+    fn test_coalesce_project_events() {
+        let mount = PathBuf::from("/ws");
+        let file_added = |path: &str| FsEvent::FileAdded {
+            mount: mount.clone(),
+            path: path.to_string(),
+        };
+        let events = vec![
+            file_added("wt/src/a.rs"),
+            FsEvent::ProjectAdded {
+                root: PathBuf::from("/ws/wt"),
+            },
+            file_added("wt/src/b.rs"),
+            FsEvent::FileRemoved {
+                mount: mount.clone(),
+                path: "wt/old.rs".to_string(),
+            },
+            file_added("wt2/c.rs"),
+            // The directory create and its .git create both report the project
+            FsEvent::ProjectAdded {
+                root: PathBuf::from("/ws/wt"),
+            },
+        ];
+
+        let summarize = |coalesced: Vec<FsEvent>| -> Vec<String> {
+            coalesced
+                .iter()
+                .map(|event| match event {
+                    FsEvent::ProjectAdded { root } => format!("project {}", root.display()),
+                    FsEvent::FileAdded { path, .. } => format!("added {path}"),
+                    FsEvent::FileRemoved { path, .. } => format!("removed {path}"),
+                    other => format!("{other:?}"),
+                })
+                .collect()
+        };
+        // "wt2" shares a prefix with "wt" but is not under it
+        assert_eq!(
+            summarize(coalesce_project_events(events.clone(), |_| false)),
+            vec!["project /ws/wt", "added wt2/c.rs"]
+        );
+
+        // An already mounted root is not walked again: its file events stay
+        assert_eq!(
+            summarize(coalesce_project_events(events, |root| root == Path::new("/ws/wt"))),
+            vec![
+                "added wt/src/a.rs",
+                "project /ws/wt",
+                "added wt/src/b.rs",
+                "removed wt/old.rs",
+                "added wt2/c.rs",
+            ]
+        );
+
+        // Without a new project, events pass through untouched
+        let coalesced = coalesce_project_events(vec![file_added("src/a.rs")], |_| false);
+        assert_eq!(coalesced.len(), 1);
+    }
+
+    #[test]
+    fn test_event_loop_honors_debounce() {
+        let tmp = TempDir::new().unwrap();
+
+        // Each loop keeps its mount (and its index lock) alive: one root per run
+        let indexed_after = |name: &str, debounce: Duration, wait: Duration| -> bool {
+            let root = tmp.path().canonicalize().unwrap().join(name);
+            create_git_marker(&root);
+            create_source_file(&root.join("main.rs"), "fn main() {}\n");
+            let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+            let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+            on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+            create_source_file(&root.join("new.rs"), "fn added() {}\n");
+
+            let (tx, rx) = std::sync::mpsc::channel();
+            let event = notify::Event::new(EventKind::Create(CreateKind::File))
+                .add_path(root.join("new.rs"));
+            tx.send((root.clone(), Ok(event))).unwrap();
+            let loop_db = Arc::clone(&db);
+            std::thread::spawn(move || {
+                run_event_loop(
+                    rx,
+                    tx,
+                    mount_table,
+                    loop_db,
+                    WatchOptions {
+                        debounce,
+                        poll_interval: Duration::from_millis(10),
+                        validate_interval: Duration::ZERO,
+                        flush_interval: Duration::ZERO,
+                    },
+                )
+            });
+
+            let deadline = Instant::now() + wait;
+            while Instant::now() < deadline {
+                let paths = db.lock().unwrap().file_paths("").unwrap();
+                if paths.contains(&"new.rs".to_string()) {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            false
+        };
+
+        // Still settling under a long debounce, picked up quickly under a short one
+        assert!(!indexed_after(
+            "slow",
+            Duration::from_secs(60),
+            Duration::from_millis(300)
+        ));
+        assert!(indexed_after(
+            "fast",
+            Duration::from_millis(20),
+            Duration::from_secs(5)
+        ));
+    }
+
+    #[test]
+    fn test_event_loop_keeps_edits_when_mounted_root_gets_git() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // `git init` at the mounted root, then an edit still settling when it is handled
+        let (tx, rx) = std::sync::mpsc::channel();
+        let git =
+            notify::Event::new(EventKind::Create(CreateKind::Folder)).add_path(root.join(".git"));
+        tx.send((root.clone(), Ok(git))).unwrap();
+        let loop_tx = tx.clone();
+        let loop_db = Arc::clone(&db);
+        std::thread::spawn(move || {
+            run_event_loop(
+                rx,
+                loop_tx,
+                mount_table,
+                loop_db,
+                WatchOptions {
+                    debounce: Duration::from_millis(200),
+                    poll_interval: Duration::from_millis(10),
+                    validate_interval: Duration::ZERO,
+                    flush_interval: Duration::ZERO,
+                },
+            )
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        create_source_file(&root.join("main.rs"), "fn main() {}\nfn edited() {}\n");
+        let edit = notify::Event::new(EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(root.join("main.rs"));
+        tx.send((root.clone(), Ok(edit))).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut indexed = false;
+        while !indexed && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+            let (symbols, _, _) = db.lock().unwrap().get_file_contents("", "main.rs").unwrap();
+            indexed = symbols.iter().any(|s| s.name == "edited");
+        }
+        assert!(indexed, "edit under an already mounted root was dropped");
+    }
+
+    #[test]
+    fn test_diff_mount_with_disk() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+        create_source_file(&root.join("lib.rs"), "pub fn a() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // Never flushed: everything is new
+        let diff = diff_mount_with_disk(&root, &mount_table.lock().unwrap(), &db).unwrap();
+        assert_eq!(diff.added, vec!["lib.rs", "main.rs"]);
+
+        flush_dirty_mounts(&mount_table, &db).unwrap();
+        let diff = diff_mount_with_disk(&root, &mount_table.lock().unwrap(), &db).unwrap();
+        assert!(diff.is_empty());
+
+        create_source_file(&root.join("lib.rs"), "pub fn a() {}\npub fn b() {}\n");
+        create_source_file(&root.join("new.rs"), "fn new() {}\n");
+        for rel in ["lib.rs", "new.rs"] {
+            process_file_change(
+                &root.join(rel),
+                rel,
+                "",
+                &db,
+                u64::MAX,
+                &FilterPolicy::default(),
+                &[],
+                None,
+            )
+            .unwrap();
+        }
+        let diff = diff_mount_with_disk(&root, &mount_table.lock().unwrap(), &db).unwrap();
+        assert_eq!(diff.added, vec!["new.rs"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].path, "lib.rs");
+        assert_eq!(diff.modified[0].added_symbols, 1);
+    }
+
+    #[test]
+    fn test_live_manifest() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+        flush_dirty_mounts(&mount_table, &db).unwrap();
+
+        // A file added since the flush shows up before the next one
+        create_source_file(&root.join("app.py"), "def app():\n    pass\n");
+        process_file_change(
+            &root.join("app.py"),
+            "app.py",
+            "",
+            &db,
+            u64::MAX,
+            &FilterPolicy::default(),
+            &[],
+            None,
+        )
+        .unwrap();
+
+        let manifest = live_manifest(&root, &mount_table.lock().unwrap(), &db).unwrap();
+        assert_eq!(manifest.name, root.file_name().unwrap().to_str().unwrap());
+        assert_eq!(manifest.version, FORMAT_VERSION);
+        assert_eq!(manifest.root, ".");
+        assert_eq!(manifest.languages, vec!["python", "rust"]);
+    }
+
+    #[test]
+    fn test_event_loop_flushes_on_interval() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+        {
+            let mut mt = mount_table.lock().unwrap();
+            mt.find_mount_mut(&root).unwrap().mark_dirty();
+        }
+        assert!(!root.join(".codeindex/files.jsonl").exists());
+
+        // Keep tx alive so the loop does not shut down (and flush) on disconnect
+        let (tx, rx) = std::sync::mpsc::channel();
+        let loop_tx = tx.clone();
+        let loop_mounts = Arc::clone(&mount_table);
+        let loop_db = Arc::clone(&db);
+        std::thread::spawn(move || {
+            run_event_loop(
+                rx,
+                loop_tx,
+                loop_mounts,
+                loop_db,
+                WatchOptions {
+                    poll_interval: Duration::from_millis(10),
+                    validate_interval: Duration::ZERO,
+                    flush_interval: Duration::from_millis(50),
+                    ..WatchOptions::default()
+                },
+            )
+        });
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let flushed = loop {
+            let dirty = mount_table.lock().unwrap().find_mount(&root).unwrap().dirty;
+            if !dirty || Instant::now() >= deadline {
+                break !dirty;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert!(flushed, "dirty mount was not flushed within the interval");
+        assert!(root.join(".codeindex/files.jsonl").exists());
+        drop(tx);
+    }
+
+    #[test]
+    fn test_index_dir_outside_tree() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("repo");
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+        create_git_marker(&root.join("libs/utils"));
+        create_source_file(&root.join("libs/utils/lib.rs"), "pub fn helper() {}\n");
+        let root = root.canonicalize().unwrap();
+        let out = tmp.path().canonicalize().unwrap().join("indexes");
+
+        let mut mount_table = MountTable::new(root.clone());
+        mount_table.set_index_dir(Some(out.clone()));
+        let mount_table = Arc::new(Mutex::new(mount_table));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+        {
+            let mt = mount_table.lock().unwrap();
+            for (project_root, _) in mt.iter() {
+                flush_mount_to_disk(project_root, &mt, &db).unwrap();
+            }
+        }
+        assert!(out.join("files.jsonl").exists());
+        assert!(out.join("libs/utils/symbols.jsonl").exists());
+        assert!(!root.join(".codeindex").exists());
+        assert!(!root.join("libs/utils/.codeindex").exists());
+        drop(mount_table);
+
+        // A later run finds the indexes there
+        let mut mount_table = MountTable::new(root.clone());
+        mount_table.set_index_dir(Some(out.clone()));
+        let mount_table = Arc::new(Mutex::new(mount_table));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, true, None, None).unwrap();
+        let mt = mount_table.lock().unwrap();
+        assert!(mt.iter().all(|(_, mount)| !mount.dirty));
+        let db = db.lock().unwrap();
+        assert_eq!(
+            db.export_for_project("libs/utils").unwrap().1[0].name,
+            "helper"
+        );
+    }
+
+    #[test]
+    fn test_project_added_burst_walks_once() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // A worktree lands: files and its .git in one interleaved burst
+        let worktree = root.join("wt");
+        create_source_file(&worktree.join("src/a.rs"), "pub fn wt_a() {}\n");
+        create_source_file(&worktree.join("src/b.rs"), "pub fn wt_b() {}\n");
+        create_git_marker(&worktree);
+        let file = EventKind::Create(notify::event::CreateKind::File);
+        let folder = EventKind::Create(notify::event::CreateKind::Folder);
+        let events = vec![
+            (worktree.join("src/a.rs"), file, root.clone()),
+            (worktree.join(".git"), folder, root.clone()),
+            (worktree.join("src/b.rs"), file, root.clone()),
+            (worktree.clone(), folder, root.clone()),
+        ];
+        let (tx, _rx) = std::sync::mpsc::channel();
+        handle_events(&events, &mount_table, &db, tx).unwrap();
+
+        let db_guard = db.lock().unwrap();
+        assert_eq!(
+            db_guard.list_projects().unwrap(),
+            vec!["".to_string(), "wt".to_string()]
+        );
+        // Indexed once, by the worktree's own walk, never into the root project
+        assert_eq!(db_guard.project_stats("wt").unwrap().files, 2);
+        assert_eq!(
+            db_guard.file_paths("").unwrap(),
+            vec!["main.rs".to_string()]
+        );
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("wt_a")
+            })
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].project(), "wt");
+    }
+
+    #[test]
+    fn test_worktree_git_file_adds_project() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn main() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // `git worktree add` writes a .git *file* pointing at the common dir
+        let worktree = root.join("wt");
+        create_source_file(&worktree.join("lib.rs"), "pub fn wt_lib() {}\n");
+        let git_file = worktree.join(".git");
+        fs::write(&git_file, "gitdir: /repo/.git/worktrees/wt\n").unwrap();
+        let create_file = EventKind::Create(notify::event::CreateKind::File);
+
+        {
+            let mut mt = mount_table.lock().unwrap();
+            let mount = mt.find_mount_mut(&root).unwrap();
+            match mount.on_fs_event(&git_file, &create_file) {
+                Some(FsEvent::ProjectAdded { root: added }) => assert_eq!(added, worktree),
+                other => panic!("expected ProjectAdded, got {:?}", other),
+            }
+            // The mount's own .git file is not a new project
+            assert!(
+                mount
+                    .on_fs_event(&root.join(".git"), &create_file)
+                    .is_none()
+            );
+        }
+
+        let (tx, _rx) = std::sync::mpsc::channel();
+        handle_events(
+            &[(git_file.clone(), create_file, root.clone())],
+            &mount_table,
+            &db,
+            tx,
+        )
+        .unwrap();
+        assert!(mount_table.lock().unwrap().is_mounted(&worktree));
+        assert_eq!(
+            db.lock().unwrap().file_paths("wt").unwrap(),
+            vec!["lib.rs".to_string()]
+        );
+
+        // Removing the .git file removes the project, like a .git directory
+        let mut mt = mount_table.lock().unwrap();
+        let mount = mt.find_mount_mut(&root).unwrap();
+        let remove_file = EventKind::Remove(notify::event::RemoveKind::File);
+        match mount.on_fs_event(&git_file, &remove_file) {
+            Some(FsEvent::ProjectRemoved { root: removed }) => assert_eq!(removed, worktree),
+            other => panic!("expected ProjectRemoved, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_nested_subprojects() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+
+        // Create root project
+        create_git_marker(&root);
+        create_source_file(&root.join("root.rs"), "fn root_fn() {}\n");
+
+        // Create nested subprojects: root > libs/core > libs/core/nested
+        let core = root.join("libs/core");
+        create_git_marker(&core);
+        create_source_file(&core.join("core.rs"), "fn core_fn() {}\n");
+
+        let nested = core.join("nested");
+        create_git_marker(&nested);
+        create_source_file(&nested.join("nested.rs"), "fn nested_fn() {}\n");
+
+        // Index from root (load_from_cache=false to force indexing)
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        // Verify: should have 3 projects
+        let db_guard = db.lock().unwrap();
+        let projects = db_guard.list_projects().unwrap();
+        assert_eq!(projects.len(), 3);
+
+        // Each function should be in its respective project (private fns, use visibility="private")
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("root_fn")
+            })
+            .unwrap();
+        let root_syms: Vec<_> = results
+            .iter()
+            .filter_map(|r| match r {
+                crate::server::db::SearchResult::Symbol(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(root_syms.len(), 1);
+        assert_eq!(root_syms[0].project, "");
+
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    projects: vec!["libs/core".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("core_fn")
+            })
+            .unwrap();
+        let core_syms: Vec<_> = results
+            .iter()
+            .filter_map(|r| match r {
+                crate::server::db::SearchResult::Symbol(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(core_syms.len(), 1);
+
+        let results = db_guard
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    projects: vec!["libs/core/nested".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("nested_fn")
+            })
+            .unwrap();
+        let nested_syms: Vec<_> = results
+            .iter()
+            .filter_map(|r| match r {
+                crate::server::db::SearchResult::Symbol(s) => Some(s),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(nested_syms.len(), 1);
+    }
+
+    #[test]
+    fn test_progress_channel_receives_indexed_files() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+
+        create_git_marker(&root);
+        create_source_file(&root.join("lib.rs"), "pub fn a() {}\npub fn b() {}\n");
+        create_git_marker(&root.join("sub"));
+        create_source_file(&root.join("sub/main.rs"), "fn main() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        let (progress_tx, progress_rx) = std::sync::mpsc::channel();
+        on_project_discovery(&root, &mount_table, &db, false, None, Some(&progress_tx)).unwrap();
+        drop(progress_tx);
+
+        let mut indexed: Vec<IndexedFile> = progress_rx.iter().collect();
+        indexed.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            indexed,
+            vec![
+                IndexedFile {
+                    path: "lib.rs".to_string(),
+                    project: String::new(),
+                    symbol_count: 2,
+                    status: IndexStatus::Indexed,
+                },
+                IndexedFile {
+                    path: "main.rs".to_string(),
+                    project: "sub".to_string(),
+                    symbol_count: 1,
+                    status: IndexStatus::Indexed,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parallel_walk_matches_sequential() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        for i in 0..40 {
+            create_source_file(
+                &root.join(format!("src/m{i}.rs")),
+                &format!("pub fn f{i}() {{ g{i}(); }}\n"),
+            );
+        }
+        create_source_file(&root.join("src/broken.rs"), "fn ok() {}\nfn broken( {\n");
+        create_source_file(&root.join("README.md"), "# Title\n\nSome text.\n");
+
+        let index = |jobs: usize| {
+            let mut mount_table = MountTable::new(root.clone());
+            mount_table.set_jobs(jobs);
+            let mount_table = Arc::new(Mutex::new(mount_table));
+            let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+            on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+            let export = format!("{:?}", db.lock().unwrap().export_for_project("").unwrap());
+            mount_table.lock().unwrap().unmount(&root).unwrap();
+            (export, mount_table, db)
+        };
+
+        let (sequential, _, _) = index(1);
+        let (parallel, mount_table, db) = index(4);
+        assert_eq!(parallel, sequential);
+
+        // Re-walking in parallel skips unchanged files by hash
+        create_source_file(&root.join("src/m7.rs"), "pub fn changed() {}\n");
+        mount_table.lock().unwrap().mount(&root).unwrap();
+        let (progress_tx, progress_rx) = std::sync::mpsc::channel();
+        walk_project(&root, &mount_table, &db, false, None, Some(&progress_tx)).unwrap();
+        drop(progress_tx);
+        let indexed: Vec<String> = progress_rx
+            .iter()
+            .filter(|f| f.status != IndexStatus::Unchanged)
+            .map(|f| f.path)
+            .collect();
+        assert_eq!(indexed, vec!["src/m7.rs"]);
+    }
+
+    #[test]
+    fn test_codeix_toml_allows_builtin_references() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(
+            &root.join("app.py"),
+            "def run(rows):\n    return map(str, rows)\n",
+        );
+        let calls = || {
+            let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+            let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+            on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+            let (_, _, refs) = db.lock().unwrap().get_file_contents("", "app.py").unwrap();
+            mount_table.lock().unwrap().unmount(&root).unwrap();
+            refs.into_iter()
+                .filter(|r| r.kind == "call")
+                .map(|r| r.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(calls().is_empty());
+        fs::write(root.join(".codeix.toml"), "allow_references = [\"map\"]\n").unwrap();
+        assert_eq!(calls(), vec!["map"]);
+    }
+
+    #[test]
+    fn test_codeix_toml_adds_call_argument_tokens() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(
+            &root.join("app.py"),
+            "@app.route(\"/users/{id}\")\ndef show_user(id):\n    pass\n",
+        );
+        fs::write(root.join(".codeix.toml"), "token_calls = [\"route\"]\n").unwrap();
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        let (symbols, _, _) = db.lock().unwrap().get_file_contents("", "app.py").unwrap();
+        let show_user = symbols.iter().find(|s| s.name == "show_user").unwrap();
+        assert!(
+            show_user
+                .tokens
+                .as_deref()
+                .is_some_and(|t| t.contains("/users/{id}")),
+            "{:?}",
+            show_user.tokens
+        );
+    }
+
+    #[test]
+    fn test_identical_files_reuse_extraction() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+
+        // Same generated file copied into two subprojects under different paths
+        let content = "/// Shared helper.\npub fn shared_helper() {\n    other();\n}\n";
+        create_git_marker(&root);
+        for (sub, path) in [("a", "gen/shared.rs"), ("b", "src/copy.rs")] {
+            create_git_marker(&root.join(sub));
+            create_source_file(&root.join(sub).join(path), content);
+        }
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        let db_guard = db.lock().unwrap();
+        for (project, path) in [("a", "gen/shared.rs"), ("b", "src/copy.rs")] {
+            let (symbols, texts, refs) = db_guard.get_file_contents(project, path).unwrap();
+            assert_eq!(symbols.len(), 1, "{project}/{path}");
+            assert_eq!(symbols[0].name, "shared_helper");
+            assert!(
+                symbols
+                    .iter()
+                    .all(|s| s.file == path && s.project == project)
+            );
+            assert!(texts.iter().all(|t| t.file == path && t.project == project));
+            assert!(!refs.is_empty());
+            assert!(refs.iter().all(|r| r.file == path && r.project == project));
+        }
+    }
+
+    #[test]
+    fn test_identical_rust_files_resolve_mod_from_own_path() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+
+        // `mod client;` names a file next to each copy
+        create_git_marker(&root);
+        for dir in ["src/db", "src/net"] {
+            create_source_file(&root.join(dir).join("mod.rs"), "mod client;\n");
+        }
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        let db_guard = db.lock().unwrap();
+        for dir in ["src/db", "src/net"] {
+            let (_, _, refs) = db_guard
+                .get_file_contents("", &format!("{dir}/mod.rs"))
+                .unwrap();
+            let imports: Vec<_> = refs.iter().filter(|r| r.kind == "import").collect();
+            assert_eq!(imports.len(), 1, "{dir}");
+            assert_eq!(imports[0].name, format!("{dir}/client.rs"));
+        }
+    }
+
+    #[test]
+    fn test_identical_files_not_reused_across_filter_policies() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+
+        // `a` keeps the builtin `map` call, `b` (walked after it) does not
+        let content = "def run(rows):\n    return map(str, rows)\n";
+        create_git_marker(&root);
+        for sub in ["a", "b"] {
+            create_git_marker(&root.join(sub));
+            create_source_file(&root.join(sub).join("app.py"), content);
+        }
+        fs::write(
+            root.join("a/.codeix.toml"),
+            "allow_references = [\"map\"]\n",
+        )
+        .unwrap();
+
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
-
-        // Verify: should have 2 projects
         let db_guard = db.lock().unwrap();
-        let projects = db_guard.list_projects().unwrap();
-        assert_eq!(projects.len(), 2);
-        assert!(projects.contains(&"".to_string())); // Root
-        assert!(projects.contains(&"libs/utils".to_string())); // Subproject
+        let calls = |project: &str| {
+            let (_, _, refs) = db_guard.get_file_contents(project, "app.py").unwrap();
+            refs.into_iter()
+                .filter(|r| r.kind == "call")
+                .map(|r| r.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(calls("a"), vec!["map"]);
+        assert!(calls("b").is_empty());
+    }
 
-        // Root project should have app_main (private fn, use visibility="private" to include all)
-        let results = db_guard
-            .search(
-                "app_main",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
-            .unwrap();
-        let symbols: Vec<_> = results
-            .iter()
-            .filter_map(|r| match r {
-                crate::server::db::SearchResult::Symbol(s) => Some(s),
-                _ => None,
-            })
-            .collect();
-        assert_eq!(symbols.len(), 1);
-        assert_eq!(symbols[0].project, "");
+    #[test]
+    fn test_oversized_file_recorded_without_symbols() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
 
-        // Subproject should have utility (public fn, default visibility would work but use private for consistency)
-        let results = db_guard
-            .search(
-                "utility",
-                &["symbol".to_string()],
-                &[],
-                None,
-                Some("libs/utils"),
-                Some("private"),
-                100,
-                0,
-            )
-            .unwrap();
-        let symbols: Vec<_> = results
-            .iter()
-            .filter_map(|r| match r {
-                crate::server::db::SearchResult::Symbol(s) => Some(s),
-                _ => None,
-            })
-            .collect();
+        // A generated file well past the limit, next to a regular one
+        let generated = (0..200)
+            .map(|i| format!("pub fn generated_{i}() {{ helper(); }}\n"))
+            .collect::<String>();
+        create_source_file(&root.join("src/bundle.rs"), &generated);
+        create_source_file(&root.join("src/lib.rs"), "pub fn small() {}\n");
+
+        let mut mount_table = MountTable::new(root.clone());
+        mount_table.set_max_file_bytes(1024);
+        let mount_table = Arc::new(Mutex::new(mount_table));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        let db_guard = db.lock().unwrap();
+        let (files, _, _, _) = db_guard.export_for_project("").unwrap();
+        let bundle = files.iter().find(|f| f.path == "src/bundle.rs").unwrap();
+        assert_eq!(bundle.lines, 200);
+        assert_eq!(bundle.lang.as_deref(), Some("rust"));
+        assert!(!bundle.hash.is_empty());
+
+        let (symbols, texts, refs) = db_guard.get_file_contents("", "src/bundle.rs").unwrap();
+        assert!(symbols.is_empty());
+        assert!(texts.is_empty());
+        assert!(refs.is_empty());
+
+        let (symbols, _, _) = db_guard.get_file_contents("", "src/lib.rs").unwrap();
         assert_eq!(symbols.len(), 1);
-        assert_eq!(symbols[0].project, "libs/utils");
     }
 
     #[test]
-    fn test_nested_subprojects() {
+    fn test_binary_file_recorded_without_symbols() {
         let tmp = TempDir::new().unwrap();
-        // Canonicalize for macOS where /var -> /private/var
         let root = tmp.path().canonicalize().unwrap();
+        // Code-looking extension, but NUL bytes inside
+        let path = root.join("data.js");
+        fs::write(&path, b"function decoy() {}\n\0\0\x01\x02").unwrap();
 
-        // Create root project
-        create_git_marker(&root);
-        create_source_file(&root.join("root.rs"), "fn root_fn() {}\n");
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        process_file_change(
+            &path,
+            "data.js",
+            "",
+            &db,
+            u64::MAX,
+            &FilterPolicy::default(),
+            &[],
+            None,
+        )
+        .unwrap();
 
-        // Create nested subprojects: root > libs/core > libs/core/nested
-        let core = root.join("libs/core");
-        create_git_marker(&core);
-        create_source_file(&core.join("core.rs"), "fn core_fn() {}\n");
+        let db_guard = db.lock().unwrap();
+        assert!(db_guard.get_file_hash("", "data.js").unwrap().is_some());
+        let (symbols, texts, refs) = db_guard.get_file_contents("", "data.js").unwrap();
+        assert!(symbols.is_empty() && texts.is_empty() && refs.is_empty());
+    }
 
-        let nested = core.join("nested");
-        create_git_marker(&nested);
-        create_source_file(&nested.join("nested.rs"), "fn nested_fn() {}\n");
+    #[test]
+    fn test_process_file_change_reports_partial_parse() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::write(root.join("ok.rs"), "fn ok() {}\n").unwrap();
+        fs::write(root.join("broken.rs"), "fn ok() {}\n\nfn broken( {\n").unwrap();
 
-        // Index from root (load_from_cache=false to force indexing)
-        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        let ok = process_file_change(
+            &root.join("ok.rs"),
+            "ok.rs",
+            "",
+            &db,
+            u64::MAX,
+            &FilterPolicy::default(),
+            &[],
+            None,
+        );
+        assert_eq!(ok.unwrap(), ParseOutcome::Complete);
+        let broken = process_file_change(
+            &root.join("broken.rs"),
+            "broken.rs",
+            "",
+            &db,
+            u64::MAX,
+            &FilterPolicy::default(),
+            &[],
+            None,
+        );
+        assert_eq!(broken.unwrap(), ParseOutcome::Partial { line: 3 });
+
+        // Partially parsed files are still indexed
+        let (symbols, _, _) = db
+            .lock()
+            .unwrap()
+            .get_file_contents("", "broken.rs")
+            .unwrap();
+        assert!(symbols.iter().any(|s| s.name == "ok"));
+    }
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+    #[test]
+    fn test_copy_of_partial_parse_reports_partial() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let broken = "def ok():\n    pass\n\ndef broken(:\n";
+        fs::write(root.join("a.py"), broken).unwrap();
+        fs::write(root.join("b.py"), broken).unwrap();
+        fs::write(root.join("c.py"), "def fine():\n    pass\n").unwrap();
+        fs::write(root.join("d.py"), "def fine():\n    pass\n").unwrap();
 
-        // Verify: should have 3 projects
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        let reuse_from = [String::new()];
+        let index = |name: &str| {
+            process_file_change(
+                &root.join(name),
+                name,
+                "",
+                &db,
+                u64::MAX,
+                &FilterPolicy::default(),
+                &reuse_from,
+                None,
+            )
+            .unwrap()
+        };
+        let first = index("a.py");
+        assert!(matches!(first, ParseOutcome::Partial { .. }));
+        // The identical file is parsed again rather than copied as complete
+        assert_eq!(index("b.py"), first);
+
+        assert_eq!(index("c.py"), ParseOutcome::Complete);
+        assert_eq!(index("d.py"), ParseOutcome::Complete);
         let db_guard = db.lock().unwrap();
-        let projects = db_guard.list_projects().unwrap();
-        assert_eq!(projects.len(), 3);
+        for name in ["a.py", "b.py", "c.py", "d.py"] {
+            let (symbols, _, _) = db_guard.get_file_contents("", name).unwrap();
+            assert!(!symbols.is_empty(), "{name}");
+        }
+    }
 
-        // Each function should be in its respective project (private fns, use visibility="private")
-        let results = db_guard
-            .search(
-                "root_fn",
-                &["symbol".to_string()],
+    #[test]
+    fn test_shebang_script_without_extension_is_parsed() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("bin")).unwrap();
+        fs::write(
+            root.join("bin/deploy"),
+            "#!/usr/bin/env python3\ndef deploy():\n    pass\n",
+        )
+        .unwrap();
+        fs::write(root.join("Makefile"), "all:\n\techo hi\n").unwrap();
+
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        for rel in ["bin/deploy", "Makefile"] {
+            process_file_change(
+                &root.join(rel),
+                rel,
+                "",
+                &db,
+                u64::MAX,
+                &FilterPolicy::default(),
                 &[],
                 None,
-                None,
-                Some("private"),
-                100,
-                0,
             )
             .unwrap();
-        let root_syms: Vec<_> = results
-            .iter()
-            .filter_map(|r| match r {
-                crate::server::db::SearchResult::Symbol(s) => Some(s),
-                _ => None,
-            })
-            .collect();
-        assert_eq!(root_syms.len(), 1);
-        assert_eq!(root_syms[0].project, "");
+        }
 
-        let results = db_guard
-            .search(
-                "core_fn",
-                &["symbol".to_string()],
+        let db_guard = db.lock().unwrap();
+        let (files, symbols, _, _) = db_guard.export_for_project("").unwrap();
+        let lang_of = |path: &str| {
+            files
+                .iter()
+                .find(|f| f.path == path)
+                .and_then(|f| f.lang.clone())
+        };
+        assert_eq!(lang_of("bin/deploy").as_deref(), Some("python"));
+        assert_eq!(lang_of("Makefile").as_deref(), Some("make"));
+        // Only the script is parsed; the Makefile is labelled, not parsed
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "deploy");
+        assert_eq!(symbols[0].file, "bin/deploy");
+    }
+
+    /// Peak RSS (VmHWM) in KiB; resetting it first via `clear_refs` (Linux only).
+    #[cfg(target_os = "linux")]
+    fn peak_rss_kib(reset: bool) -> u64 {
+        if reset {
+            fs::write("/proc/self/clear_refs", "5").unwrap();
+        }
+        let status = fs::read_to_string("/proc/self/status").unwrap();
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix("VmHWM:"))
+            .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+            .unwrap()
+    }
+
+    /// Peak memory when re-processing large unchanged files.
+    /// Run with: cargo test --release bench_unchanged_large_files_peak_rss -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn bench_unchanged_large_files_peak_rss() {
+        const FILES: usize = 8;
+        const SIZE: usize = 64 * 1024 * 1024;
+
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let line = "just some generated log output that nobody parses\n";
+        let content = line.repeat(SIZE / line.len());
+        for i in 0..FILES {
+            create_source_file(&root.join(format!("big{i}.log")), &content);
+        }
+        drop(content);
+
+        let db = Arc::new(Mutex::new(SearchDb::new_no_fts().unwrap()));
+        for i in 0..FILES {
+            let rel = format!("big{i}.log");
+            process_file_change(
+                &root.join(&rel),
+                &rel,
+                "",
+                &db,
+                u64::MAX,
+                &FilterPolicy::default(),
                 &[],
                 None,
-                Some("libs/core"),
-                Some("private"),
-                100,
-                0,
             )
             .unwrap();
-        let core_syms: Vec<_> = results
-            .iter()
-            .filter_map(|r| match r {
-                crate::server::db::SearchResult::Symbol(s) => Some(s),
-                _ => None,
-            })
-            .collect();
-        assert_eq!(core_syms.len(), 1);
+        }
 
-        let results = db_guard
-            .search(
-                "nested_fn",
-                &["symbol".to_string()],
+        let baseline = peak_rss_kib(true);
+        for i in 0..FILES {
+            let rel = format!("big{i}.log");
+            process_file_change(
+                &root.join(&rel),
+                &rel,
+                "",
+                &db,
+                u64::MAX,
+                &FilterPolicy::default(),
                 &[],
                 None,
-                Some("libs/core/nested"),
-                Some("private"),
-                100,
-                0,
             )
             .unwrap();
-        let nested_syms: Vec<_> = results
-            .iter()
-            .filter_map(|r| match r {
-                crate::server::db::SearchResult::Symbol(s) => Some(s),
-                _ => None,
-            })
-            .collect();
-        assert_eq!(nested_syms.len(), 1);
+        }
+        let peak = peak_rss_kib(false);
+        eprintln!(
+            "{FILES} unchanged files x {} MiB: peak RSS +{} KiB over baseline {} KiB",
+            SIZE >> 20,
+            peak.saturating_sub(baseline),
+            baseline
+        );
     }
 
     #[test]
@@ -1174,23 +3142,22 @@ mod tests {
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         // Verify: sub.rs should NOT appear in root project
         let db_guard = db.lock().unwrap();
 
         // Search without project filter - should find both (private fns, use visibility="private")
         let results = db_guard
-            .search(
-                "fn",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("fn")
+            })
             .unwrap();
         let all_symbols: Vec<_> = results
             .iter()
@@ -1235,7 +3202,7 @@ mod tests {
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         // Verify mount table has all 3 mounts
         let mt = mount_table.lock().unwrap();
@@ -1266,22 +3233,21 @@ mod tests {
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         let db_guard = db.lock().unwrap();
 
         // Without filter: should find 2 helpers (private fns, use visibility="private")
         let results = db_guard
-            .search(
-                "helper",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("helper")
+            })
             .unwrap();
         let all: Vec<_> = results
             .iter()
@@ -1300,16 +3266,16 @@ mod tests {
 
         // With sub filter: should find 1 (private fn, use visibility="private")
         let results = db_guard
-            .search(
-                "helper",
-                &["symbol".to_string()],
-                &[],
-                None,
-                Some("sub"),
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    projects: vec!["sub".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("helper")
+            })
             .unwrap();
         let sub_only: Vec<_> = results
             .iter()
@@ -1322,6 +3288,45 @@ mod tests {
         assert_eq!(sub_only[0].project, "sub");
     }
 
+    #[test]
+    fn test_multiple_workspace_roots_indexed_with_prefixes() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path().canonicalize().unwrap();
+        let api = base.join("api");
+        let web = base.join("web");
+        create_git_marker(&api);
+        create_git_marker(&web);
+        create_source_file(&api.join("server.rs"), "fn serve() {}\n");
+        create_source_file(&web.join("app.rs"), "fn render() {}\n");
+        create_git_marker(&web.join("libs/ui"));
+        create_source_file(&web.join("libs/ui/button.rs"), "fn click() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(
+            MountTable::with_roots(vec![api.clone(), web.clone()]).unwrap(),
+        ));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        for root in [&api, &web] {
+            on_project_discovery(root, &mount_table, &db, false, None, None).unwrap();
+        }
+
+        let db_guard = db.lock().unwrap();
+        let mut projects = db_guard.list_projects().unwrap();
+        projects.sort();
+        assert_eq!(projects, vec!["api", "web", "web/libs/ui"]);
+        let (files, _, _, _) = db_guard.export_for_project("web/libs/ui").unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "button.rs");
+        drop(db_guard);
+
+        // Prefixed projects resolve back to files on disk
+        let mt = mount_table.lock().unwrap();
+        let extractor =
+            crate::server::snippet::SnippetExtractor::with_roots(mt.workspace_roots().to_vec());
+        assert!(extractor.file_exists("api", "server.rs"));
+        assert!(extractor.file_exists("web/libs/ui", "button.rs"));
+        assert!(!extractor.file_exists("web", "server.rs"));
+    }
+
     #[test]
     fn test_relative_project_paths() {
         let tmp = TempDir::new().unwrap();
@@ -1338,7 +3343,7 @@ mod tests {
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
 
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         // Verify relative path is correct
         let db_guard = db.lock().unwrap();
@@ -1348,16 +3353,15 @@ mod tests {
 
         // Symbol should have correct project (private fn, use visibility="private")
         let results = db_guard
-            .search(
-                "deep_fn",
-                &["symbol".to_string()],
-                &[],
-                None,
-                None,
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("deep_fn")
+            })
             .unwrap();
         let symbols: Vec<_> = results
             .iter()
@@ -1369,6 +3373,233 @@ mod tests {
         assert_eq!(symbols[0].project, "path/to/deep/project");
     }
 
+    #[test]
+    fn test_reindex_project() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("src/a.rs"), "pub fn alpha() {}\n");
+        create_source_file(&root.join("src/b.rs"), "pub fn beta() {}\n");
+        create_source_file(&root.join("srcx.rs"), "pub fn gamma() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        let symbol_names = |db: &Arc<Mutex<SearchDb>>, file: &str| -> Vec<String> {
+            db.lock()
+                .unwrap()
+                .get_file_symbols(None, file, Some("private"), 100, 0)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+
+        // Simulate a stale extraction: same hash, symbols lost
+        {
+            let db_guard = db.lock().unwrap();
+            let hash = db_guard.get_file_hash("", "src/a.rs").unwrap().unwrap();
+            let file = FileEntry {
+                project: String::new(),
+                path: "src/a.rs".to_string(),
+                lang: Some("rust".to_string()),
+                hash,
+                lines: 1,
+                title: None,
+                description: None,
+                is_test: false,
+            };
+            db_guard.upsert_file("", &file, &[], &[], &[]).unwrap();
+        }
+        assert!(symbol_names(&db, "src/a.rs").is_empty());
+
+        // A single file is re-parsed despite its unchanged hash
+        assert_eq!(
+            reindex_project(&mount_table, &db, "", Some("src/a.rs")).unwrap(),
+            1
+        );
+        assert_eq!(symbol_names(&db, "src/a.rs"), vec!["alpha"]);
+
+        // A directory covers the files under it only ("srcx.rs" is not under "src")
+        assert_eq!(
+            reindex_project(&mount_table, &db, "", Some("src/")).unwrap(),
+            2
+        );
+
+        // The whole project, dropping files gone from disk
+        fs::remove_file(root.join("src/b.rs")).unwrap();
+        assert_eq!(reindex_project(&mount_table, &db, "", None).unwrap(), 2);
+        assert_eq!(
+            db.lock().unwrap().file_paths("").unwrap(),
+            vec!["src/a.rs".to_string(), "srcx.rs".to_string()]
+        );
+
+        assert!(reindex_project(&mount_table, &db, "missing", None).is_err());
+    }
+
+    #[test]
+    fn test_index_changed_since() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        if !git(&["init", "-q"]) {
+            return; // git not installed
+        }
+        create_source_file(&root.join("a.rs"), "pub fn alpha() {}\n");
+        create_source_file(&root.join("b.rs"), "pub fn beta() {}\n");
+        create_source_file(&root.join("d.rs"), "pub fn delta() {}\n");
+        assert!(git(&["add", "."]) && git(&["commit", "-qm", "one"]));
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+
+        create_source_file(&root.join("a.rs"), "pub fn alpha2() {}\n");
+        fs::remove_file(root.join("b.rs")).unwrap();
+        create_source_file(&root.join("c.rs"), "pub fn gamma() {}\n");
+        assert!(git(&["add", "-A"]) && git(&["commit", "-qm", "two"]));
+        // Changed on disk but not committed: not listed, left alone
+        create_source_file(&root.join("d.rs"), "pub fn delta2() {}\n");
+
+        let processed = index_changed_since(&root, &mount_table, &db, "HEAD~1", None).unwrap();
+        assert_eq!(processed, 3);
+
+        let db_guard = db.lock().unwrap();
+        let mut paths = db_guard.file_paths("").unwrap();
+        paths.sort();
+        assert_eq!(paths, vec!["a.rs", "c.rs", "d.rs"]);
+        let names: Vec<String> = db_guard
+            .get_file_symbols(None, "d.rs", Some("private"), 100, 0)
+            .unwrap()
+            .into_iter()
+            .chain(
+                db_guard
+                    .get_file_symbols(None, "a.rs", Some("private"), 100, 0)
+                    .unwrap(),
+            )
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["delta", "alpha2"]);
+        drop(db_guard);
+
+        // An unknown revision falls back to a full walk
+        assert_eq!(
+            index_changed_since(&root, &mount_table, &db, "nope", None).unwrap(),
+            0
+        );
+        let symbols = db
+            .lock()
+            .unwrap()
+            .get_file_symbols(None, "d.rs", Some("private"), 100, 0)
+            .unwrap();
+        assert_eq!(symbols[0].name, "delta2");
+    }
+
+    #[test]
+    fn test_persisted_db_reparses_only_changed_files() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+        let db_path = root.join(".codeindex/index.sqlite");
+
+        create_git_marker(&root);
+        create_source_file(&root.join("same.rs"), "pub fn same() {}\n");
+        create_source_file(&root.join("edited.rs"), "pub fn before() {}\n");
+        create_source_file(&root.join("gone.rs"), "pub fn gone() {}\n");
+
+        let index = |root: &Path| -> Vec<String> {
+            fs::create_dir_all(root.join(".codeindex")).unwrap();
+            let mount_table = Arc::new(Mutex::new(MountTable::new(root.to_path_buf())));
+            let db = Arc::new(Mutex::new(SearchDb::open_file(&db_path).unwrap().unwrap()));
+            let (progress_tx, progress_rx) = std::sync::mpsc::channel();
+            on_project_discovery(root, &mount_table, &db, false, None, Some(&progress_tx)).unwrap();
+            drop(progress_tx);
+            let mut parsed: Vec<String> = progress_rx
+                .iter()
+                .filter(|f| f.status != IndexStatus::Unchanged)
+                .map(|f| f.path)
+                .collect();
+            parsed.sort();
+            parsed
+        };
+
+        assert_eq!(index(&root), vec!["edited.rs", "gone.rs", "same.rs"]);
+
+        create_source_file(&root.join("edited.rs"), "pub fn after() {}\n");
+        fs::remove_file(root.join("gone.rs")).unwrap();
+
+        // Second run only re-parses the edited file and drops the deleted one
+        assert_eq!(index(&root), vec!["edited.rs"]);
+        let db = SearchDb::open_file(&db_path).unwrap().unwrap();
+        assert_eq!(
+            db.file_paths("").unwrap(),
+            vec!["edited.rs".to_string(), "same.rs".to_string()]
+        );
+        let results = db
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 10,
+                ..SearchQuery::new("after")
+            })
+            .unwrap();
+        assert!(!results.is_empty());
+        let results = db
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 10,
+                ..SearchQuery::new("before")
+            })
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_drop_vanished_mounts() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+        create_git_marker(&root);
+        create_source_file(&root.join("main.rs"), "fn root_fn() {}\n");
+        let sub = root.join("libs/sub");
+        create_git_marker(&sub);
+        create_source_file(&sub.join("lib.rs"), "fn sub_fn() {}\n");
+
+        let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
+        let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
+        assert_eq!(drop_vanished_mounts(&mount_table, &db).unwrap(), 0);
+
+        // Deleted without any event reaching the handler
+        fs::remove_dir_all(root.join("libs")).unwrap();
+        assert_eq!(drop_vanished_mounts(&mount_table, &db).unwrap(), 1);
+
+        assert!(!mount_table.lock().unwrap().is_mounted(&sub));
+        assert!(mount_table.lock().unwrap().is_mounted(&root));
+        let db_guard = db.lock().unwrap();
+        assert_eq!(db_guard.list_projects().unwrap(), vec![String::new()]);
+        assert!(
+            db_guard
+                .get_file_hash("libs/sub", "lib.rs")
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_project_removal_cleans_up_db() {
         let tmp = TempDir::new().unwrap();
@@ -1386,7 +3617,7 @@ mod tests {
         // Index
         let mount_table = Arc::new(Mutex::new(MountTable::new(root.clone())));
         let db = Arc::new(Mutex::new(SearchDb::new().unwrap()));
-        on_project_discovery(&root, &mount_table, &db, false, None).unwrap();
+        on_project_discovery(&root, &mount_table, &db, false, None, None).unwrap();
 
         // Verify initial state: 2 projects
         {
@@ -1398,16 +3629,15 @@ mod tests {
 
             // Both functions should exist (private fns, use visibility="private")
             let results = db_guard
-                .search(
-                    "fn",
-                    &["symbol".to_string()],
-                    &[],
-                    None,
-                    None,
-                    Some("private"),
-                    100,
-                    0,
-                )
+                .search(&SearchQuery {
+                    filters: SearchFilters {
+                        scope: vec!["symbol".to_string()],
+                        include_tests: true,
+                        visibility: Some("private".to_string()),
+                        ..Default::default()
+                    },
+                    ..SearchQuery::new("fn")
+                })
                 .unwrap();
             let symbols: Vec<_> = results
                 .iter()
@@ -1447,16 +3677,15 @@ mod tests {
 
             // Only root_fn should exist, sub_fn should be gone (private fn, use visibility="private")
             let results = db_guard
-                .search(
-                    "fn",
-                    &["symbol".to_string()],
-                    &[],
-                    None,
-                    None,
-                    Some("private"),
-                    100,
-                    0,
-                )
+                .search(&SearchQuery {
+                    filters: SearchFilters {
+                        scope: vec!["symbol".to_string()],
+                        include_tests: true,
+                        visibility: Some("private".to_string()),
+                        ..Default::default()
+                    },
+                    ..SearchQuery::new("fn")
+                })
                 .unwrap();
             let symbols: Vec<_> = results
                 .iter()
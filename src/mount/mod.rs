@@ -3,22 +3,21 @@ pub mod handler;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::sync::mpsc::Sender;
 
 use anyhow::{Context, Result};
 use fs2::FileExt;
-use ignore::gitignore::{Gitignore, GitignoreBuilder};
-use notify::event::{CreateKind, EventKind, RemoveKind};
+use ignore::gitignore::{Gitignore, GitignoreBuilder, gitconfig_excludes_path};
+use notify::event::{CreateKind, EventKind, ModifyKind, RemoveKind, RenameMode};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use walkdir::WalkDir;
 
-/// Built-in gitignore patterns (always applied).
-/// These are either internal directories, IDE config, or OS cruft.
+use crate::parser::filter::FilterPolicy;
+
+/// Built-in gitignore patterns for IDE config and OS cruft.
+/// Added before the user's ignore files, so a user negation (`!.vscode/`) re-includes them.
 const BUILTIN_GITIGNORE: &[&str] = &[
-    // Git internals (but .git itself is used for project detection)
-    ".git/",
-    // Our own output
-    ".codeindex/",
     // Editor/IDE directories
     ".vscode/",
     ".idea/",
@@ -29,6 +28,23 @@ const BUILTIN_GITIGNORE: &[&str] = &[
     ".Trashes/",
 ];
 
+/// Built-in patterns no ignore file can override: added after the user's ignore
+/// files, so they are the last match and win over any `!.git/` or `!.codeindex/`.
+const HARD_GITIGNORE: &[&str] = &[
+    // Git internals (but .git itself is used for project detection)
+    ".git/",
+    // Our own output
+    ".codeindex/",
+];
+
+/// Index-only ignore file: same syntax as `.gitignore`, but only affects
+/// what codeix indexes (e.g. generated or vendored code checked into git).
+const CODEIXIGNORE_FILE: &str = ".codeixignore";
+
+/// Default size limit for parsing a file (`--max-file-bytes`). Larger files
+/// (generated bundles, dumps) are recorded without symbols, texts or references.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
 /// Mount mode determines whether the index can be written to.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MountMode {
@@ -38,15 +54,46 @@ pub enum MountMode {
     ReadOnly,
 }
 
+impl MountMode {
+    /// `"read-write"` or `"read-only"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MountMode::ReadWrite => "read-write",
+            MountMode::ReadOnly => "read-only",
+        }
+    }
+}
+
 /// Check if an EventKind represents a removal operation.
 /// Used to determine if we can canonicalize the path (removed files can't be canonicalized).
+/// The source side of a rename counts: the path no longer exists.
 pub fn is_removal_event(kind: &EventKind) -> bool {
     matches!(
         kind,
-        EventKind::Remove(RemoveKind::File) | EventKind::Remove(RemoveKind::Folder)
+        EventKind::Remove(RemoveKind::File)
+            | EventKind::Remove(RemoveKind::Folder)
+            | EventKind::Modify(ModifyKind::Name(RenameMode::From))
     )
 }
 
+/// Kind to record for the `index`-th path of a notify event.
+///
+/// A `RenameMode::Both` event carries `[from, to]` in one event; splitting it
+/// into `From`/`To` keeps the side of each path once events are debounced per path.
+pub fn path_event_kind(kind: &EventKind, index: usize, path_count: usize) -> EventKind {
+    match kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if path_count == 2 => {
+            let mode = if index == 0 {
+                RenameMode::From
+            } else {
+                RenameMode::To
+            };
+            EventKind::Modify(ModifyKind::Name(mode))
+        }
+        _ => *kind,
+    }
+}
+
 /// Filesystem events emitted to external consumers (handler/DB).
 /// These are the result of processing raw notify events through mount rules.
 #[derive(Debug, Clone)]
@@ -98,10 +145,23 @@ pub struct Mount {
     gitignore: Option<Gitignore>,
     /// All gitignore files discovered (for rebuilding when new ones are added).
     gitignore_files: Vec<PathBuf>,
+    /// The user's global excludes file (`core.excludesFile`, or
+    /// `$XDG_CONFIG_HOME/git/ignore`), looked up once at mount time.
+    global_excludes: Option<PathBuf>,
+    /// Index files even if .gitignore excludes them (built-in patterns and
+    /// .codeixignore still apply).
+    index_ignored: bool,
+    /// Follow directory symlinks during the walk, each real directory once.
+    follow_symlinks: bool,
     /// File system watcher (only present for ReadWrite mounts).
     watcher: Option<RecommendedWatcher>,
     /// Directories currently being watched.
     watched_dirs: HashSet<PathBuf>,
+    /// Filter overrides from the project's `.codeix.toml`, read at mount time.
+    filter_policy: Arc<FilterPolicy>,
+    /// Where this project's index is read and written: `root/.codeindex`
+    /// unless `--index-dir` moved it (see [`MountTable::index_dir`]).
+    index_dir: PathBuf,
 }
 
 impl std::fmt::Debug for Mount {
@@ -117,7 +177,12 @@ impl std::fmt::Debug for Mount {
 
 impl Mount {
     /// Create a new read-only mount (no lock, no watcher).
-    fn new_ro(root: PathBuf) -> Result<Self> {
+    fn new_ro(
+        root: PathBuf,
+        index_dir: PathBuf,
+        index_ignored: bool,
+        follow_symlinks: bool,
+    ) -> Result<Self> {
         let mut mount = Self {
             root,
             mode: MountMode::ReadOnly,
@@ -125,27 +190,33 @@ impl Mount {
             dirty: false,
             gitignore: None,
             gitignore_files: Vec::new(),
+            global_excludes: gitconfig_excludes_path(),
+            index_ignored,
+            follow_symlinks,
             watcher: None,
             watched_dirs: HashSet::new(),
+            filter_policy: Arc::new(FilterPolicy::default()),
+            index_dir,
         };
         mount.init_gitignore()?;
+        mount.load_filter_policy();
         Ok(mount)
     }
 
-    /// Create a new read-write mount with exclusive flock.
+    /// Create a new read-write mount with exclusive flock on `index_dir/index.json`.
     /// Does NOT start notify - call `init_notify()` separately.
-    fn new_rw(root: PathBuf) -> Result<Self> {
-        // Create .codeindex directory if it doesn't exist
-        let codeindex_dir = root.join(".codeindex");
-        std::fs::create_dir_all(&codeindex_dir).with_context(|| {
-            format!(
-                "failed to create .codeindex directory at {:?}",
-                codeindex_dir
-            )
-        })?;
+    fn new_rw(
+        root: PathBuf,
+        index_dir: PathBuf,
+        index_ignored: bool,
+        follow_symlinks: bool,
+    ) -> Result<Self> {
+        // Create the index directory if it doesn't exist
+        std::fs::create_dir_all(&index_dir)
+            .with_context(|| format!("failed to create index directory at {:?}", index_dir))?;
 
         // Acquire exclusive lock on index.json
-        let lock_path = codeindex_dir.join("index.json");
+        let lock_path = index_dir.join("index.json");
         let lock_file = File::options()
             .read(true)
             .write(true)
@@ -165,17 +236,31 @@ impl Mount {
             dirty: false,
             gitignore: None,
             gitignore_files: Vec::new(),
+            global_excludes: gitconfig_excludes_path(),
+            index_ignored,
+            follow_symlinks,
             watcher: None,
             watched_dirs: HashSet::new(),
+            filter_policy: Arc::new(FilterPolicy::default()),
+            index_dir,
         };
         mount.init_gitignore()?;
+        mount.load_filter_policy();
         Ok(mount)
     }
 
-    /// Initialize gitignore with .git/info/exclude and root .gitignore.
+    /// Initialize gitignore with the global excludes file, .git/info/exclude,
+    /// root .gitignore and root .codeixignore (lowest to highest precedence, as git does).
     fn init_gitignore(&mut self) -> Result<()> {
         self.gitignore_files.clear();
 
+        // Add the global excludes file if it exists (e.g. `*.swp`)
+        if let Some(global) = &self.global_excludes
+            && global.is_file()
+        {
+            self.gitignore_files.push(global.clone());
+        }
+
         // Add .git/info/exclude if it exists
         let exclude_path = self.root.join(".git/info/exclude");
         if exclude_path.exists() {
@@ -188,23 +273,46 @@ impl Mount {
             self.gitignore_files.push(root_gitignore);
         }
 
+        // Add root .codeixignore after it, so its patterns layer on top
+        let root_codeixignore = self.root.join(CODEIXIGNORE_FILE);
+        if root_codeixignore.exists() {
+            self.gitignore_files.push(root_codeixignore);
+        }
+
         self.build_gitignore()
     }
 
-    /// Build gitignore from all tracked files plus built-in patterns.
+    /// Build gitignore from all tracked files (.gitignore and .codeixignore) plus built-in patterns.
+    ///
+    /// The last matching pattern wins, so lines are layered from lowest to highest precedence:
+    /// 1. `BUILTIN_GITIGNORE` (IDE/OS cruft), which user negations can override
+    /// 2. user ignore files, in `gitignore_files` order (global excludes, `.git/info/exclude`,
+    ///    root then nested `.gitignore`/`.codeixignore`)
+    /// 3. `HARD_GITIGNORE` (`.git/`, `.codeindex/`), which nothing overrides
+    ///
+    /// As in git, a negation cannot re-include a file whose parent directory is excluded:
+    /// the walker never descends into `build/`, so `!build/keep.txt` needs `build/*`.
     fn build_gitignore(&mut self) -> Result<()> {
         let mut builder = GitignoreBuilder::new(&self.root);
 
-        // Add built-in patterns first
+        // Add overridable built-in patterns first
         for pattern in BUILTIN_GITIGNORE {
             let _ = builder.add_line(None, pattern);
         }
 
-        // Add user gitignore files
+        // Add user gitignore files (only .codeixignore when indexing ignored files)
         for file in &self.gitignore_files {
+            if self.index_ignored && !file.ends_with(CODEIXIGNORE_FILE) {
+                continue;
+            }
             builder.add(file);
         }
 
+        // Hard exclusions last, so they win over user negations
+        for pattern in HARD_GITIGNORE {
+            let _ = builder.add_line(None, pattern);
+        }
+
         self.gitignore = Some(
             builder
                 .build()
@@ -234,6 +342,16 @@ impl Mount {
         match kind {
             EventKind::Create(CreateKind::Folder) => self.on_dir_added(abs_path),
             EventKind::Remove(RemoveKind::Folder) => self.on_dir_removed(abs_path),
+            // Atomic-rename saves (write a temp file, rename it over the target):
+            // the source is gone, the destination has new content
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => self.on_file_removed(abs_path),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To | RenameMode::Both)) => {
+                if abs_path.is_dir() {
+                    self.on_dir_added(abs_path)
+                } else {
+                    self.on_file_added(abs_path)
+                }
+            }
             EventKind::Create(CreateKind::File) | EventKind::Modify(_) => {
                 self.on_file_added(abs_path)
             }
@@ -246,7 +364,7 @@ impl Mount {
         let name = abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
         // Check if this is a .git directory -> project discovery
-        // (must check before gitignore since .git/ is in BUILTIN_GITIGNORE)
+        // (must check before gitignore since .git/ is in HARD_GITIGNORE)
         if name == ".git" {
             return abs_path.parent().map(|root| FsEvent::ProjectAdded {
                 root: root.to_path_buf(),
@@ -266,10 +384,12 @@ impl Mount {
             });
         }
 
-        // Check for .gitignore in this directory : must be loaded first before walking siblings
-        let dir_gitignore = abs_path.join(".gitignore");
-        if dir_gitignore.exists() {
-            self.add_gitignore(&dir_gitignore);
+        // Check for .gitignore/.codeixignore in this directory : must be loaded first before walking siblings
+        for ignore_file in [".gitignore", CODEIXIGNORE_FILE] {
+            let dir_ignore = abs_path.join(ignore_file);
+            if dir_ignore.exists() {
+                self.add_gitignore(&dir_ignore);
+            }
         }
 
         // Add watch for new directory
@@ -294,15 +414,26 @@ impl Mount {
     fn on_file_added(&mut self, abs_path: &Path) -> Option<FsEvent> {
         let name = abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
+        // A .git file (worktree, submodule) marks a project root like a .git directory.
+        // The mount's own .git file is not a new project.
+        if name == ".git" {
+            return abs_path
+                .parent()
+                .filter(|root| *root != self.root)
+                .map(|root| FsEvent::ProjectAdded {
+                    root: root.to_path_buf(),
+                });
+        }
+
         // Check gitignore
         if self.is_ignored(abs_path) {
             return None;
         }
 
-        // Check if it's a .gitignore file -> update rules
-        if name == ".gitignore" {
+        // Check if it's a .gitignore/.codeixignore file -> update rules
+        if name == ".gitignore" || name == CODEIXIGNORE_FILE {
             self.add_gitignore(abs_path);
-            return None; // Don't index .gitignore files themselves
+            return None; // Don't index ignore files themselves
         }
 
         // Skip hidden files (dotfiles)
@@ -324,6 +455,13 @@ impl Mount {
     fn on_file_removed(&mut self, abs_path: &Path) -> Option<FsEvent> {
         let name = abs_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
+        // Removing a .git file removes the project, like a .git directory
+        if name == ".git" {
+            return abs_path.parent().map(|root| FsEvent::ProjectRemoved {
+                root: root.to_path_buf(),
+            });
+        }
+
         // Skip hidden files
         if name.starts_with('.') {
             return None;
@@ -340,7 +478,7 @@ impl Mount {
         })
     }
 
-    /// Add a .gitignore file to the rules.
+    /// Add a .gitignore (or .codeixignore) file to the rules.
     fn add_gitignore(&mut self, gitignore_path: &Path) {
         // Track the file
         if !self.gitignore_files.contains(&gitignore_path.to_path_buf()) {
@@ -365,6 +503,13 @@ impl Mount {
     /// All the smart logic (gitignore, skip entries, project detection) is in on_fs_event.
     ///
     /// After walk completes, the built gitignore is stored for use by the watcher.
+    ///
+    /// Symlinks are skipped unless `follow_symlinks` is set. Then directory
+    /// symlinks are descended, but a directory whose canonical path was already
+    /// walked (through another link, or a link back to an ancestor) is skipped,
+    /// so each real tree is indexed once and cycles end. Broken links are skipped.
+    /// Siblings are walked in name order with real directories before links, so
+    /// a tree reachable both ways is always indexed under its real path.
     pub fn walk<F>(&mut self, mut on_event: F) -> Result<()>
     where
         F: FnMut(FsEvent) -> Result<()>,
@@ -376,10 +521,30 @@ impl Mount {
         // Add watch for mount root
         let _ = self.watch_dir(&root);
 
-        let mut iter = WalkDir::new(&root).follow_links(false).into_iter();
+        let mut iter = WalkDir::new(&root)
+            .follow_links(self.follow_symlinks)
+            .sort_by(|a, b| {
+                a.path_is_symlink()
+                    .cmp(&b.path_is_symlink())
+                    .then_with(|| a.file_name().cmp(b.file_name()))
+            })
+            .into_iter();
+        // Canonical directories walked so far (only tracked when following links)
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        if self.follow_symlinks {
+            visited.insert(root.canonicalize().unwrap_or_else(|_| root.clone()));
+        }
 
         while let Some(result) = iter.next() {
-            let entry = result?;
+            let entry = match result {
+                Ok(entry) => entry,
+                // Broken links and loops walkdir detects itself
+                Err(e) if self.follow_symlinks => {
+                    tracing::debug!("skipping {}: {}", e.path().unwrap_or(&root).display(), e);
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
             let abs_path = entry.path();
 
             // Skip mount root itself
@@ -400,6 +565,16 @@ impl Mount {
                 continue;
             }
 
+            if self.follow_symlinks
+                && entry.file_type().is_dir()
+                && let Ok(real) = abs_path.canonicalize()
+                && !visited.insert(real)
+            {
+                tracing::debug!("already walked {}, skipping", abs_path.display());
+                iter.skip_current_dir();
+                continue;
+            }
+
             // Determine event kind (same as notify would emit)
             let kind = if entry.file_type().is_dir() {
                 EventKind::Create(CreateKind::Folder)
@@ -474,9 +649,14 @@ impl Mount {
     }
 
     /// Check if a path should be ignored according to gitignore rules.
+    /// The index directory is always ignored, also when `--index-dir` puts it
+    /// inside the tree under another name.
     ///
-    /// Returns false if gitignore hasn't been built yet (walk() not called).
+    /// Returns false otherwise if gitignore hasn't been built yet (walk() not called).
     pub fn is_ignored(&self, path: &Path) -> bool {
+        if path.starts_with(&self.index_dir) {
+            return true;
+        }
         if let Some(ref gi) = self.gitignore {
             let is_dir = path.is_dir();
             gi.matched_path_or_any_parents(path, is_dir).is_ignore()
@@ -490,6 +670,25 @@ impl Mount {
         self.gitignore.as_ref()
     }
 
+    /// (Re)read the project's `.codeix.toml`. A malformed file is reported and
+    /// leaves the default policy in place.
+    pub fn load_filter_policy(&mut self) {
+        match FilterPolicy::load(&self.root) {
+            Ok(policy) => self.filter_policy = Arc::new(policy),
+            Err(e) => tracing::warn!("ignoring {:#}", e),
+        }
+    }
+
+    /// Filter overrides to parse this project's files with.
+    pub fn filter_policy(&self) -> Arc<FilterPolicy> {
+        Arc::clone(&self.filter_policy)
+    }
+
+    /// Directory this project's index is read from and written to.
+    pub fn index_dir(&self) -> &Path {
+        &self.index_dir
+    }
+
     /// Mark this mount as dirty (needs flushing).
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
@@ -510,45 +709,215 @@ impl Drop for Mount {
     }
 }
 
+/// A workspace root and the prefix carried by the project strings under it.
+///
+/// A single-root workspace has an empty prefix, so its root project is `""`.
+/// With several roots (`serve ~/work/api ~/work/web`), each root's directory
+/// name is the prefix: `"api"`, `"web"`, `"web/libs/ui"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceRoot {
+    pub path: PathBuf,
+    pub prefix: String,
+}
+
+/// Absolute directory of a relative project string, picking the workspace
+/// root whose prefix it starts with. Returns None if no root matches.
+pub fn resolve_project_dir(roots: &[WorkspaceRoot], project: &str) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        let rest = if root.prefix.is_empty() {
+            project
+        } else if project == root.prefix {
+            ""
+        } else {
+            project.strip_prefix(&root.prefix)?.strip_prefix('/')?
+        };
+        Some(if rest.is_empty() {
+            root.path.clone()
+        } else {
+            root.path.join(rest)
+        })
+    })
+}
+
 /// Table of mounted directories.
 #[derive(Debug)]
 pub struct MountTable {
-    /// Root of the workspace (where codeix was launched).
-    workspace_root: PathBuf,
+    /// Roots of the workspace (where codeix was launched, or the paths given to
+    /// `serve`). The first one is the primary root.
+    roots: Vec<WorkspaceRoot>,
     mounts: HashMap<PathBuf, Mount>,
+    /// Passed to every new mount: index files .gitignore excludes.
+    index_ignored: bool,
+    /// Passed to every new mount: follow directory symlinks during walks.
+    follow_symlinks: bool,
+    /// Files larger than this are recorded but not parsed.
+    max_file_bytes: u64,
+    /// Threads parsing the files of a project walk.
+    jobs: usize,
+    /// Directory holding every project's index instead of its `.codeindex/`.
+    index_dir: Option<PathBuf>,
 }
 
 impl MountTable {
     /// Create a new mount table with the given workspace root.
     pub fn new(workspace_root: PathBuf) -> Self {
+        Self::from_roots(vec![WorkspaceRoot {
+            path: workspace_root,
+            prefix: String::new(),
+        }])
+    }
+
+    fn from_roots(roots: Vec<WorkspaceRoot>) -> Self {
         Self {
-            workspace_root,
+            roots,
             mounts: HashMap::new(),
+            index_ignored: false,
+            follow_symlinks: false,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            jobs: 1,
+            index_dir: None,
+        }
+    }
+
+    /// Create a mount table over several workspace roots (expected canonical).
+    ///
+    /// With one root this is [`MountTable::new`]. With more, project strings are
+    /// prefixed by each root's directory name (see [`WorkspaceRoot`]), so roots
+    /// must have distinct names and must not contain one another.
+    pub fn with_roots(roots: Vec<PathBuf>) -> Result<Self> {
+        if roots.len() <= 1 {
+            let root = roots
+                .into_iter()
+                .next()
+                .context("no workspace root given")?;
+            return Ok(Self::new(root));
+        }
+
+        let mut workspace_roots: Vec<WorkspaceRoot> = Vec::new();
+        for path in roots {
+            let prefix = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .with_context(|| format!("workspace root has no name: {}", path.display()))?
+                .to_string();
+            for other in &workspace_roots {
+                if other.prefix == prefix {
+                    anyhow::bail!(
+                        "workspace roots {} and {} share the name '{}'",
+                        other.path.display(),
+                        path.display(),
+                        prefix
+                    );
+                }
+                if path.starts_with(&other.path) || other.path.starts_with(&path) {
+                    anyhow::bail!(
+                        "workspace roots {} and {} are nested",
+                        other.path.display(),
+                        path.display()
+                    );
+                }
+            }
+            workspace_roots.push(WorkspaceRoot { path, prefix });
         }
+        Ok(Self::from_roots(workspace_roots))
+    }
+
+    /// Index files excluded by .gitignore in mounts created from now on
+    /// (`--no-gitignore`). Built-in patterns and .codeixignore still apply.
+    pub fn set_index_ignored(&mut self, index_ignored: bool) {
+        self.index_ignored = index_ignored;
+    }
+
+    /// Walk into symlinked directories in mounts created from now on
+    /// (`--follow-symlinks`), indexing each real directory once.
+    pub fn set_follow_symlinks(&mut self, follow_symlinks: bool) {
+        self.follow_symlinks = follow_symlinks;
+    }
+
+    /// Record files larger than `max_file_bytes` with their line count, hash and
+    /// language only, skipping symbol/text/reference extraction.
+    pub fn set_max_file_bytes(&mut self, max_file_bytes: u64) {
+        self.max_file_bytes = max_file_bytes;
     }
 
-    /// Get the workspace root.
+    /// Size limit above which files are recorded but not parsed.
+    pub fn max_file_bytes(&self) -> u64 {
+        self.max_file_bytes
+    }
+
+    /// Parse the files of a project walk on `jobs` threads (`--jobs`); 0 means
+    /// one per available CPU. Database writes stay on one connection.
+    pub fn set_jobs(&mut self, jobs: usize) {
+        self.jobs = if jobs == 0 {
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            jobs
+        };
+    }
+
+    /// Threads parsing the files of a project walk (at least 1).
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Keep the indexes of mounts created from now on under `index_dir`
+    /// (`--index-dir`, expected absolute) rather than in each project's tree.
+    pub fn set_index_dir(&mut self, index_dir: Option<PathBuf>) {
+        self.index_dir = index_dir;
+    }
+
+    /// Directory holding the index of the project rooted at `project_root`:
+    /// `project_root/.codeindex` by default, or with `--index-dir` the
+    /// project's relative path under that directory (the directory itself for
+    /// the root project), so subprojects keep separate indexes.
+    pub fn index_dir(&self, project_root: &Path) -> PathBuf {
+        match &self.index_dir {
+            Some(base) => {
+                let project = self.relative_project(project_root);
+                if project.is_empty() {
+                    base.clone()
+                } else {
+                    base.join(project)
+                }
+            }
+            None => project_root.join(".codeindex"),
+        }
+    }
+
+    /// Get the primary workspace root (the first one).
     pub fn workspace_root(&self) -> &Path {
-        &self.workspace_root
+        &self.roots[0].path
+    }
+
+    /// All workspace roots, primary first.
+    pub fn workspace_roots(&self) -> &[WorkspaceRoot] {
+        &self.roots
     }
 
-    /// Compute the relative project path from workspace root.
-    /// Returns empty string for the root project.
+    /// Compute the relative project path from the workspace root containing it,
+    /// carrying that root's prefix. Returns empty string for the root project
+    /// of a single-root workspace.
     pub fn relative_project(&self, project_root: &Path) -> String {
-        project_root
-            .strip_prefix(&self.workspace_root)
-            .map(|p| p.to_string_lossy().replace('\\', "/"))
-            .unwrap_or_default()
+        let Some((root, rel)) = self
+            .roots
+            .iter()
+            .filter_map(|root| Some((root, project_root.strip_prefix(&root.path).ok()?)))
+            .max_by_key(|(root, _)| root.path.components().count())
+        else {
+            return String::new();
+        };
+        let rel = rel.to_string_lossy().replace('\\', "/");
+        match (root.prefix.is_empty(), rel.is_empty()) {
+            (true, _) => rel,
+            (false, true) => root.prefix.clone(),
+            (false, false) => format!("{}/{}", root.prefix, rel),
+        }
     }
 
     /// Get the absolute path for a relative project path.
     /// Returns None if the project is not mounted.
     pub fn project_root(&self, relative_path: &str) -> Option<PathBuf> {
-        let abs_path = if relative_path.is_empty() {
-            self.workspace_root.clone()
-        } else {
-            self.workspace_root.join(relative_path)
-        };
+        let abs_path = resolve_project_dir(&self.roots, relative_path)?;
         // Verify the project is actually mounted
         let canonical = abs_path.canonicalize().ok()?;
         if self.mounts.contains_key(&canonical) {
@@ -577,7 +946,13 @@ impl MountTable {
         }
 
         // Try RW first, fall back to RO if lock fails
-        let mount = match Mount::new_rw(root.clone()) {
+        let index_dir = self.index_dir(&root);
+        let mount = match Mount::new_rw(
+            root.clone(),
+            index_dir.clone(),
+            self.index_ignored,
+            self.follow_symlinks,
+        ) {
             Ok(m) => m,
             Err(e) => {
                 // Check if it's a lock error (contains "lock" in message)
@@ -587,7 +962,12 @@ impl MountTable {
                         root.display(),
                         e
                     );
-                    Mount::new_ro(root.clone())?
+                    Mount::new_ro(
+                        root.clone(),
+                        index_dir,
+                        self.index_ignored,
+                        self.follow_symlinks,
+                    )?
                 } else {
                     return Err(e);
                 }
@@ -609,7 +989,12 @@ impl MountTable {
             anyhow::bail!("directory already mounted: {:?}", root);
         }
 
-        let mount = Mount::new_rw(root.clone())?;
+        let mount = Mount::new_rw(
+            root.clone(),
+            self.index_dir(&root),
+            self.index_ignored,
+            self.follow_symlinks,
+        )?;
         self.mounts.insert(root.clone(), mount);
         Ok(self.mounts.get(&root).unwrap())
     }
@@ -625,7 +1010,12 @@ impl MountTable {
             anyhow::bail!("directory already mounted: {:?}", root);
         }
 
-        let mount = Mount::new_ro(root.clone())?;
+        let mount = Mount::new_ro(
+            root.clone(),
+            self.index_dir(&root),
+            self.index_ignored,
+            self.follow_symlinks,
+        )?;
         self.mounts.insert(root.clone(), mount);
         Ok(self.mounts.get(&root).unwrap())
     }
@@ -692,6 +1082,16 @@ impl MountTable {
         self.mounts.remove(root).is_some()
     }
 
+    /// Relative paths of the mounted projects parsing with `policy` (their
+    /// `.codeix.toml` filters match), whose files can stand in for identical ones.
+    pub fn projects_with_policy(&self, policy: &FilterPolicy) -> Vec<String> {
+        self.mounts
+            .iter()
+            .filter(|(_, mount)| *mount.filter_policy == *policy)
+            .map(|(root, _)| self.relative_project(root))
+            .collect()
+    }
+
     /// Iterate over all mounts.
     pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Mount)> {
         self.mounts.iter()
@@ -742,6 +1142,32 @@ mod tests {
         assert!(tmp.path().join(".codeindex/index.json").exists());
     }
 
+    #[test]
+    fn test_mount_index_dir() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().join("repo");
+        let out = tmp.path().join("indexes");
+        fs::create_dir_all(root.join("libs/utils")).unwrap();
+        let root = root.canonicalize().unwrap();
+        let mut table = MountTable::new(root.clone());
+        assert_eq!(table.index_dir(&root), root.join(".codeindex"));
+
+        table.set_index_dir(Some(out.clone()));
+        assert_eq!(table.index_dir(&root), out);
+        assert_eq!(
+            table.index_dir(&root.join("libs/utils")),
+            out.join("libs/utils")
+        );
+
+        // The lock file moves with the index, and the tree stays untouched
+        let mount = table.mount_rw(&root).unwrap();
+        assert_eq!(mount.index_dir(), out.as_path());
+        assert!(out.join("index.json").exists());
+        assert!(!root.join(".codeindex").exists());
+        assert!(table.mount_rw(root.join("libs/utils")).is_ok());
+        assert!(out.join("libs/utils/index.json").exists());
+    }
+
     #[test]
     fn test_mount_ro() {
         let tmp = TempDir::new().unwrap();
@@ -857,6 +1283,37 @@ mod tests {
         assert!(!files.iter().any(|f| f.ends_with(".log")));
     }
 
+    #[test]
+    fn test_global_excludes_respected() {
+        let tmp = TempDir::new().unwrap();
+        let config = TempDir::new().unwrap();
+        let global = config.path().join("ignore");
+        fs::write(&global, "*.swp\n").unwrap();
+
+        fs::write(tmp.path().join("main.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join(".main.rs.swp"), "swap").unwrap();
+
+        let mut table = MountTable::new(tmp.path().to_path_buf());
+        table.mount_ro(tmp.path()).unwrap();
+        let mount = table.find_mount_mut(tmp.path()).unwrap();
+        mount.global_excludes = Some(global);
+        mount.init_gitignore().unwrap();
+
+        let mut files = Vec::new();
+        mount
+            .walk(|event| {
+                if let FsEvent::FileAdded { path, .. } = event {
+                    files.push(path);
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(files, vec!["main.rs"]);
+        // The watcher filters with the same rules
+        assert!(mount.is_ignored(&tmp.path().join("other.swp")));
+    }
+
     #[test]
     fn test_symlinks_not_followed() {
         // Issue #36: symlinks in node_modules caused CPU spin
@@ -948,6 +1405,180 @@ mod tests {
         assert!(mount.watched_count() >= 4); // root, src, components, tests
     }
 
+    #[test]
+    fn test_index_ignored_walks_gitignored_files() {
+        let tmp = TempDir::new().unwrap();
+
+        fs::write(tmp.path().join(".gitignore"), "generated/\n").unwrap();
+        fs::write(tmp.path().join(".codeixignore"), "*.snap.rs\n").unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(tmp.path().join("src/ui.snap.rs"), "// snapshot").unwrap();
+        fs::create_dir_all(tmp.path().join("generated")).unwrap();
+        fs::write(tmp.path().join("generated/api.rs"), "pub fn api() {}").unwrap();
+        fs::create_dir_all(tmp.path().join(".codeindex")).unwrap();
+        fs::write(tmp.path().join(".codeindex/files.jsonl"), "{}").unwrap();
+
+        let walk = |index_ignored: bool| {
+            let mut table = MountTable::new(tmp.path().to_path_buf());
+            table.set_index_ignored(index_ignored);
+            table.mount_ro(tmp.path()).unwrap();
+            let mount = table.find_mount_mut(tmp.path()).unwrap();
+            let mut files = Vec::new();
+            mount
+                .walk(|event| {
+                    if let FsEvent::FileAdded { path, .. } = event {
+                        files.push(path);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            files.sort();
+            files
+        };
+
+        // Default: .gitignore applies
+        assert_eq!(walk(false), vec!["src/main.rs"]);
+        // Ignored generated/api.rs now appears; built-ins and .codeixignore still apply
+        assert_eq!(walk(true), vec!["generated/api.rs", "src/main.rs"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_walks_each_real_dir_once() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+
+        // Sibling link inside the mount, link to a tree outside it, and a cycle
+        fs::create_dir_all(tmp.path().join("libs/real")).unwrap();
+        fs::write(tmp.path().join("libs/real/a.rs"), "fn a() {}").unwrap();
+        symlink("real", tmp.path().join("libs/link")).unwrap();
+        symlink("..", tmp.path().join("libs/real/up")).unwrap();
+        fs::write(outside.path().join("b.rs"), "fn b() {}").unwrap();
+        symlink(outside.path(), tmp.path().join("vendored")).unwrap();
+        symlink("missing", tmp.path().join("broken")).unwrap();
+
+        let walk = |follow_symlinks: bool| {
+            let mut table = MountTable::new(tmp.path().to_path_buf());
+            table.set_follow_symlinks(follow_symlinks);
+            table.mount_ro(tmp.path()).unwrap();
+            let mount = table.find_mount_mut(tmp.path()).unwrap();
+            let mut files = Vec::new();
+            mount
+                .walk(|event| {
+                    if let FsEvent::FileAdded { path, .. } = event {
+                        files.push(path);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            files.sort();
+            files
+        };
+
+        // Default: symlinks are skipped
+        assert_eq!(walk(false), vec!["libs/real/a.rs"]);
+
+        // a.rs once, under the real path even though libs/link sorts first
+        assert_eq!(walk(true), vec!["libs/real/a.rs", "vendored/b.rs"]);
+    }
+
+    #[test]
+    fn test_codeixignore_excludes_from_walk() {
+        let tmp = TempDir::new().unwrap();
+
+        // .gitignore leaves gen/ and vendor/ tracked; .codeixignore drops them from the index
+        fs::write(tmp.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(tmp.path().join(".codeixignore"), "gen/\n").unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(tmp.path().join("gen")).unwrap();
+        fs::write(tmp.path().join("gen/api.pb.rs"), "// generated").unwrap();
+        fs::create_dir_all(tmp.path().join("vendor")).unwrap();
+        fs::write(tmp.path().join("vendor/.codeixignore"), "snapshot.rs\n").unwrap();
+        fs::write(tmp.path().join("vendor/snapshot.rs"), "// vendored").unwrap();
+        fs::write(tmp.path().join("vendor/lib.rs"), "pub fn lib() {}").unwrap();
+
+        let mut table = MountTable::new(tmp.path().to_path_buf());
+        table.mount_ro(tmp.path()).unwrap();
+        let mount = table.find_mount_mut(tmp.path()).unwrap();
+
+        let mut files = Vec::new();
+        mount
+            .walk(|event| {
+                if let FsEvent::FileAdded { path, .. } = event {
+                    files.push(path);
+                }
+                Ok(())
+            })
+            .unwrap();
+        files.sort();
+        assert_eq!(files, vec!["src/main.rs", "vendor/lib.rs"]);
+
+        // The paths are not excluded by .gitignore rules alone
+        let mut builder = GitignoreBuilder::new(tmp.path());
+        builder.add(tmp.path().join(".gitignore"));
+        let git_only = builder.build().unwrap();
+        assert!(
+            !git_only
+                .matched_path_or_any_parents(tmp.path().join("gen/api.pb.rs"), false)
+                .is_ignore()
+        );
+    }
+
+    #[test]
+    fn test_gitignore_negation_precedence() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize for macOS where /var -> /private/var
+        let root = tmp.path().canonicalize().unwrap();
+
+        fs::write(
+            root.join(".gitignore"),
+            "build/*\n!build/keep.txt\ndist/\n!dist/keep.txt\n!.vscode/\n!.codeindex/\n!.git/\n",
+        )
+        .unwrap();
+        for file in [
+            "src/main.rs",
+            "build/keep.txt",
+            "build/out.o",
+            "dist/keep.txt",
+            ".vscode/settings.json",
+            ".codeindex/symbols.jsonl",
+        ] {
+            fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut table = MountTable::new(root.clone());
+        table.mount_ro(&root).unwrap();
+        let mount = table.find_mount_mut(&root).unwrap();
+
+        let mut files = Vec::new();
+        mount
+            .walk(|event| {
+                if let FsEvent::FileAdded { path, .. } = event {
+                    files.push(path);
+                }
+                Ok(())
+            })
+            .unwrap();
+        files.sort();
+        // The negation re-includes build/keep.txt; the user re-includes .vscode/;
+        // dist/ itself is excluded so, as in git, nothing under it comes back
+        assert_eq!(
+            files,
+            vec![".vscode/settings.json", "build/keep.txt", "src/main.rs"]
+        );
+
+        // Hard exclusions win over the user's negations
+        assert!(mount.is_ignored(&root.join(".codeindex/symbols.jsonl")));
+        assert!(mount.is_ignored(&root.join(".git/HEAD")));
+        assert!(mount.is_ignored(&root.join("build/out.o")));
+        assert!(!mount.is_ignored(&root.join("build/keep.txt")));
+    }
+
     #[test]
     fn test_gitignore_built_on_mount() {
         // Verify gitignore is available immediately after mounting
@@ -1017,6 +1648,42 @@ mod tests {
         assert!(!files.iter().any(|f| f.contains("local_ignore")));
     }
 
+    #[test]
+    fn test_multiple_workspace_roots() {
+        let tmp = TempDir::new().unwrap();
+        let base = tmp.path().canonicalize().unwrap();
+        let api = base.join("api");
+        let web = base.join("web");
+        fs::create_dir_all(web.join("libs/ui")).unwrap();
+        fs::create_dir_all(&api).unwrap();
+
+        let mut table = MountTable::with_roots(vec![api.clone(), web.clone()]).unwrap();
+        assert_eq!(table.workspace_root(), api);
+        assert_eq!(table.relative_project(&api), "api");
+        assert_eq!(table.relative_project(&web.join("libs/ui")), "web/libs/ui");
+
+        table.mount_rw(&web).unwrap();
+        table.mount_rw(web.join("libs/ui")).unwrap();
+        assert_eq!(table.project_root("web"), Some(web.clone()));
+        assert_eq!(table.project_root("web/libs/ui"), Some(web.join("libs/ui")));
+        // Known root but not mounted, and unknown prefix
+        assert_eq!(table.project_root("api"), None);
+        assert_eq!(table.project_root("webapp"), None);
+
+        // A single root keeps unprefixed project strings
+        let single = MountTable::with_roots(vec![web.clone()]).unwrap();
+        assert_eq!(single.relative_project(&web), "");
+        assert_eq!(single.relative_project(&web.join("libs/ui")), "libs/ui");
+
+        // Roots must have distinct names and must not nest
+        let other_api = base.join("other/api");
+        fs::create_dir_all(&other_api).unwrap();
+        let err = MountTable::with_roots(vec![api.clone(), other_api]).unwrap_err();
+        assert!(err.to_string().contains("share the name 'api'"));
+        let err = MountTable::with_roots(vec![web.clone(), web.join("libs/ui")]).unwrap_err();
+        assert!(err.to_string().contains("nested"));
+    }
+
     #[test]
     fn test_on_dir_removed_emits_project_removed() {
         use notify::event::{EventKind, RemoveKind};
@@ -1064,4 +1731,43 @@ mod tests {
         // Should not emit any event for regular directories
         assert!(event.is_none());
     }
+
+    #[test]
+    fn test_rename_events() {
+        let tmp = TempDir::new().unwrap();
+        // Canonicalize to handle macOS /var -> /private/var symlink
+        let tmp_path = tmp.path().canonicalize().unwrap();
+        // Editor saved main.rs by writing draft.rs and renaming it over main.rs
+        fs::write(tmp_path.join("main.rs"), "fn main() {}").unwrap();
+        fs::create_dir_all(tmp_path.join("moved")).unwrap();
+
+        let mut table = MountTable::new(tmp_path.clone());
+        table.mount_ro(&tmp_path).unwrap();
+        let mount = table.find_mount_mut(&tmp_path).unwrap();
+
+        let both = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        let from = path_event_kind(&both, 0, 2);
+        let to = path_event_kind(&both, 1, 2);
+        assert_eq!(from, EventKind::Modify(ModifyKind::Name(RenameMode::From)));
+        assert_eq!(to, EventKind::Modify(ModifyKind::Name(RenameMode::To)));
+        assert!(is_removal_event(&from));
+        assert!(!is_removal_event(&to));
+        // Other events keep their kind
+        let create = EventKind::Create(CreateKind::File);
+        assert_eq!(path_event_kind(&create, 1, 2), create);
+
+        match mount.on_fs_event(&tmp_path.join("draft.rs"), &from) {
+            Some(FsEvent::FileRemoved { path, .. }) => assert_eq!(path, "draft.rs"),
+            other => panic!("expected FileRemoved, got {:?}", other),
+        }
+        for kind in [to, both] {
+            match mount.on_fs_event(&tmp_path.join("main.rs"), &kind) {
+                Some(FsEvent::FileAdded { path, .. }) => assert_eq!(path, "main.rs"),
+                other => panic!("expected FileAdded, got {:?}", other),
+            }
+        }
+
+        // A directory renamed into place is watched, not indexed as a file
+        assert!(mount.on_fs_event(&tmp_path.join("moved"), &to).is_none());
+    }
 }
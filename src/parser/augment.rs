@@ -0,0 +1,310 @@
+use tree_sitter::Node;
+
+use crate::index::format::SymbolEntry;
+
+/// Node kinds that annotate the item after them rather than the one they sit in
+/// (`@app.route(...)` in Python and TypeScript, `#[get(...)]` in Rust).
+const DECORATOR_KINDS: &[&str] = &["decorator", "attribute_item"];
+
+/// Call-like node kinds whose callee [`CallArgumentTokens`] matches.
+const CALL_KINDS: &[&str] = &[
+    "call_expression",       // JS, TS, Rust, Go, C, C++, Kotlin
+    "call",                  // Python, Ruby
+    "method_invocation",     // Java
+    "invocation_expression", // C#
+    "macro_invocation",      // Rust `route!(...)`
+    "attribute",             // Rust `#[route(...)]`, C# `[Route(...)]`
+    "annotation",            // Java `@Route(...)`
+];
+
+/// Fields naming the callee of a call-like node, tried in order.
+const CALLEE_FIELDS: &[&str] = &["function", "macro", "method", "name"];
+
+/// Contributes domain-specific strings to the `tokens` of extracted symbols.
+///
+/// Once a language extractor has run, every node of the tree is offered to each
+/// augmentor that applies to the file's language. The strings it returns are
+/// appended to the tokens of the innermost symbol containing the node; inside a
+/// decorator or attribute, that is the item it decorates. Strings outside any
+/// symbol are dropped.
+pub trait TokenAugmentor: Send + Sync {
+    /// Whether this augmentor runs for files of `language` (default: all).
+    fn applies_to(&self, language: &str) -> bool {
+        let _ = language;
+        true
+    }
+
+    /// Strings `node` contributes to its enclosing symbol (usually none).
+    fn tokens(&self, node: Node, source: &[u8]) -> Vec<String>;
+}
+
+/// Built-in augmentor: string-literal arguments of calls to the given function
+/// names, e.g. route paths of `route("/users/{id}")` or permission names of
+/// `@permission_required("billing.view")`.
+///
+/// The callee matches on its last segment, so `app.route` and `web::route` both
+/// count as `route`. Calls, macros, decorators, attributes and annotations are
+/// recognized.
+#[derive(Debug, Clone)]
+pub struct CallArgumentTokens {
+    functions: Vec<String>,
+}
+
+impl CallArgumentTokens {
+    /// Collect string arguments of calls to any of `functions`.
+    pub fn new<I, S>(functions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            functions: functions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl TokenAugmentor for CallArgumentTokens {
+    fn tokens(&self, node: Node, source: &[u8]) -> Vec<String> {
+        if !CALL_KINDS.contains(&node.kind()) {
+            return Vec::new();
+        }
+        let Some(callee) = CALLEE_FIELDS
+            .iter()
+            .find_map(|field| node.child_by_field_name(field))
+            .or_else(|| node.named_child(0))
+        else {
+            return Vec::new();
+        };
+        let callee_text = callee.utf8_text(source).unwrap_or("");
+        let name = callee_text
+            .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("");
+        if !self.functions.iter().any(|f| f == name) {
+            return Vec::new();
+        }
+
+        let mut strings = Vec::new();
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.id() != callee.id() {
+                collect_strings(child, source, &mut strings);
+            }
+        }
+        strings
+    }
+}
+
+/// Push the contents of every string literal under `node` (outermost only).
+fn collect_strings(node: Node, source: &[u8], out: &mut Vec<String>) {
+    let kind = node.kind();
+    if kind == "string" || kind.ends_with("string_literal") || kind == "template_string" {
+        if let Some(content) = node.utf8_text(source).ok().and_then(string_content)
+            && !content.is_empty()
+        {
+            out.push(content.to_string());
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_strings(child, source, out);
+    }
+}
+
+/// Text between a literal's quotes, without prefixes (`r#"…"#`, `f"…"`, `@"…"`).
+fn string_content(literal: &str) -> Option<&str> {
+    const QUOTES: &[char] = &['"', '\'', '`'];
+    let start = literal.find(QUOTES)?;
+    let end = literal.rfind(QUOTES)?;
+    (end > start).then(|| literal[start..=end].trim_matches(QUOTES))
+}
+
+/// Run `augmentors` over the tree rooted at `root` and append what they return
+/// to the tokens of the enclosing symbols.
+pub(crate) fn apply_augmentors(
+    root: Node,
+    source: &[u8],
+    language: &str,
+    augmentors: &[&dyn TokenAugmentor],
+    symbols: &mut [SymbolEntry],
+) {
+    let augmentors: Vec<&dyn TokenAugmentor> = augmentors
+        .iter()
+        .copied()
+        .filter(|a| a.applies_to(language))
+        .collect();
+    if augmentors.is_empty() || symbols.is_empty() {
+        return;
+    }
+
+    // Iterative pre-order walk: deep trees cannot overflow the stack
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        for augmentor in &augmentors {
+            let tokens = augmentor.tokens(node, source);
+            if !tokens.is_empty()
+                && let Some(symbol) = enclosing_symbol(node, symbols)
+            {
+                append_tokens(symbol, &tokens);
+            }
+        }
+
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        loop {
+            if !cursor.goto_parent() {
+                return;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+/// Innermost symbol whose line range contains `node`, or for a node inside a
+/// decorator, the item that decorator applies to.
+fn enclosing_symbol<'a>(node: Node, symbols: &'a mut [SymbolEntry]) -> Option<&'a mut SymbolEntry> {
+    let line = decorated_item(node).unwrap_or(node).start_position().row as u32 + 1;
+    symbols
+        .iter_mut()
+        .filter(|s| s.line[0] <= line && line <= s.line[1])
+        .min_by_key(|s| s.line[1] - s.line[0])
+}
+
+/// The item after the decorator that contains `node`, if any.
+fn decorated_item(node: Node) -> Option<Node> {
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if DECORATOR_KINDS.contains(&n.kind()) {
+            let mut next = n.next_named_sibling();
+            while let Some(sibling) = next
+                && (DECORATOR_KINDS.contains(&sibling.kind()) || sibling.kind().contains("comment"))
+            {
+                next = sibling.next_named_sibling();
+            }
+            return next;
+        }
+        current = n.parent();
+    }
+    None
+}
+
+/// Add `tokens` (space-separated, deduplicated) to a symbol's tokens.
+fn append_tokens(symbol: &mut SymbolEntry, tokens: &[String]) {
+    let mut merged = symbol.tokens.take().unwrap_or_default();
+    for token in tokens {
+        if !merged.split(' ').any(|t| t == token) {
+            if !merged.is_empty() {
+                merged.push(' ');
+            }
+            merged.push_str(token);
+        }
+    }
+    symbol.tokens = (!merged.is_empty()).then_some(merged);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::filter::FilterPolicy;
+    use crate::parser::treesitter::parse_file_checked;
+
+    fn tokens_of(source: &str, language: &str, file: &str, symbol: &str) -> Option<String> {
+        let routes = CallArgumentTokens::new(["route", "permission_required"]);
+        let parsed = parse_file_checked(
+            source.as_bytes(),
+            language,
+            file,
+            &[&routes],
+            &FilterPolicy::default(),
+        )
+        .unwrap();
+        parsed
+            .symbols
+            .into_iter()
+            .find(|s| s.name == symbol)
+            .unwrap()
+            .tokens
+    }
+
+    #[test]
+    fn test_string_arguments_of_configured_calls() {
+        let source = "\
+function setup(app) {
+  app.route(\"/users/{id}\", show);
+  log(\"not a route\");
+}
+";
+        let tokens = tokens_of(source, "javascript", "app.js", "setup").unwrap();
+        assert!(tokens.contains("/users/{id}"));
+        assert!(!tokens.contains("not a route"));
+    }
+
+    #[test]
+    fn test_decorator_arguments_go_to_decorated_method() {
+        let source = "\
+class Users:
+    @app.route(\"/users\")
+    @permission_required('users.view')
+    def index(self):
+        pass
+";
+        let tokens = tokens_of(source, "python", "users.py", "Users.index").unwrap();
+        assert!(tokens.contains("/users"));
+        assert!(tokens.contains("users.view"));
+        let class_tokens = tokens_of(source, "python", "users.py", "Users").unwrap_or_default();
+        assert!(!class_tokens.contains("/users"));
+    }
+
+    #[test]
+    fn test_rust_macro_and_attribute_arguments() {
+        let source = "\
+#[route(\"/health\")]
+fn health() {}
+
+fn admin() {
+    permission_required!(r#\"admin\"#);
+}
+";
+        let tokens = tokens_of(source, "rust", "lib.rs", "health").unwrap();
+        assert!(tokens.split(' ').any(|t| t == "/health"));
+        let tokens = tokens_of(source, "rust", "lib.rs", "admin").unwrap();
+        assert!(tokens.split(' ').any(|t| t == "admin"));
+    }
+
+    #[test]
+    fn test_applies_to_limits_languages() {
+        struct PythonOnly;
+        impl TokenAugmentor for PythonOnly {
+            fn applies_to(&self, language: &str) -> bool {
+                language == "python"
+            }
+            fn tokens(&self, node: Node, _source: &[u8]) -> Vec<String> {
+                if node.kind() == "identifier" {
+                    vec!["marker".to_string()]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+        let parsed = parse_file_checked(
+            b"fn f() { g(); }\n",
+            "rust",
+            "lib.rs",
+            &[&PythonOnly],
+            &FilterPolicy::default(),
+        )
+        .unwrap();
+        assert!(
+            !parsed.symbols[0]
+                .tokens
+                .as_deref()
+                .unwrap_or("")
+                .contains("marker")
+        );
+    }
+}
@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -287,7 +288,7 @@ fn extract_call_ref(
     };
 
     let name = get_call_name(func, source);
-    if name.is_empty() || is_c_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_c_builtin_call) {
         return;
     }
 
@@ -370,7 +371,7 @@ fn extract_function(
     let is_static = has_storage_class(node, source, "static");
     let visibility = if is_static { "private" } else { "public" };
 
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = extract_signature_to_brace(node, source);
 
     // Extract return type reference (if not primitive)
     if let Some(type_node) = find_child_by_field(node, "type") {
@@ -391,6 +392,7 @@ fn extract_function(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 }
 
@@ -463,7 +465,8 @@ fn extract_declaration(
                 // Function prototype
                 let name = extract_declarator_name(child, source);
                 if !name.is_empty() {
-                    let _sig = collapse_whitespace(node_text(node, source).trim());
+                    let sig =
+                        collapse_whitespace(node_text(node, source).trim().trim_end_matches(';'));
                     let kind = "function";
                     // Prototypes don't have a body, so no tokens
                     push_symbol(
@@ -476,6 +479,7 @@ fn extract_declaration(
                         None,
                         None,
                         Some(visibility.to_string()),
+                        Some(sig),
                     );
                 }
             }
@@ -500,6 +504,7 @@ fn extract_declaration(
                             None,
                             None,
                             Some(visibility.to_string()),
+                            None,
                         );
                     }
                 }
@@ -517,6 +522,7 @@ fn extract_declaration(
                         None,
                         None,
                         Some(visibility.to_string()),
+                        None,
                     );
                 }
             }
@@ -542,6 +548,7 @@ fn extract_declaration(
                         None,
                         None,
                         Some(visibility.to_string()),
+                        None,
                     );
                 }
             }
@@ -588,6 +595,7 @@ fn extract_struct_or_union(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     // Extract fields and their type references
@@ -615,6 +623,7 @@ fn extract_struct_or_union(
                             None,
                             None,
                             Some("public".to_string()),
+                            None,
                         );
                     }
                 }
@@ -653,6 +662,7 @@ fn extract_enum(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     // Extract enum constants
@@ -674,6 +684,7 @@ fn extract_enum(
                     None,
                     None,
                     Some("public".to_string()),
+                    None,
                 );
             }
         }
@@ -698,6 +709,7 @@ fn extract_typedef(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec
                 None,
                 None,
                 Some("public".to_string()),
+                None,
             );
         }
     }
@@ -729,6 +741,7 @@ fn extract_include(
             None,
             None,
             Some("private".to_string()),
+            None,
         );
         // Also add import reference
         references.push(ReferenceEntry {
@@ -765,6 +778,7 @@ fn extract_macro(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec<S
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 }
 
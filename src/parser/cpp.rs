@@ -2,9 +2,12 @@
 //!
 //! Extends C extraction with classes, namespaces, templates, and access specifiers.
 
+use std::collections::HashMap;
+
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -106,6 +109,176 @@ pub fn extract(
     );
 }
 
+/// Header file extensions whose body-less functions are declarations of
+/// functions defined in a source file. `.h` is shared with C, but its prototypes
+/// only pair up when a C++ source file defines the same function.
+const HEADER_EXTENSIONS: &[&str] = &["h", "hpp", "hh", "hxx", "h++"];
+
+/// Source file extensions holding the out-of-line definitions.
+const SOURCE_EXTENSIONS: &[&str] = &["cpp", "cc", "cxx"];
+
+/// Words that are part of a parameter's type, never its name.
+const TYPE_WORDS: &[&str] = &[
+    "void", "bool", "char", "short", "int", "long", "float", "double", "signed", "unsigned",
+    "wchar_t", "char8_t", "char16_t", "char32_t", "auto",
+];
+
+/// Words that qualify the type after them (`const Foo` is an unnamed parameter).
+const TYPE_QUALIFIERS: &[&str] = &[
+    "const", "volatile", "struct", "class", "enum", "union", "typename", "signed", "unsigned",
+];
+
+fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    path.rsplit_once('.')
+        .is_some_and(|(_, ext)| extensions.contains(&ext))
+}
+
+/// Whether functions in `path` can take part in a declaration/definition pair
+/// (a header or a C++ source file).
+pub fn is_header_or_source(path: &str) -> bool {
+    has_extension(path, HEADER_EXTENSIONS) || has_extension(path, SOURCE_EXTENSIONS)
+}
+
+/// Pair functions declared in a header with their definition in a source file.
+///
+/// A body-less `function`/`method` in a header pairs with a definition of the same
+/// name and parameter types (see [`parameter_key`]) in a source file, so overloads
+/// find their own definition; equal keys pair up in file/line order. Returns
+/// `(declaration, definition)` indices into `symbols`.
+pub fn declaration_pairs(symbols: &[SymbolEntry]) -> Vec<(usize, usize)> {
+    let is_function = |s: &SymbolEntry| s.kind == "function" || s.kind == "method";
+    fn key(s: &SymbolEntry) -> (&str, Option<String>) {
+        let params = s.sig.as_deref().and_then(|sig| parameter_key(&s.name, sig));
+        (s.name.as_str(), params)
+    }
+
+    let mut declarations: HashMap<(&str, Option<String>), Vec<usize>> = HashMap::new();
+    for (i, sym) in symbols.iter().enumerate() {
+        if is_function(sym) && sym.tokens.is_none() && has_extension(&sym.file, HEADER_EXTENSIONS) {
+            declarations.entry(key(sym)).or_default().push(i);
+        }
+    }
+    if declarations.is_empty() {
+        return Vec::new();
+    }
+    for indices in declarations.values_mut() {
+        indices.sort_by_key(|&i| (&symbols[i].file, symbols[i].line[0]));
+        indices.reverse(); // pop() yields them in order
+    }
+
+    let mut definitions: Vec<usize> = symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| is_function(s) && has_extension(&s.file, SOURCE_EXTENSIONS))
+        .map(|(i, _)| i)
+        .collect();
+    definitions.sort_by_key(|&i| (&symbols[i].file, symbols[i].line[0]));
+
+    definitions
+        .into_iter()
+        .filter_map(|def| {
+            let decl = declarations.get_mut(&key(&symbols[def]))?.pop()?;
+            Some((decl, def))
+        })
+        .collect()
+}
+
+/// Parameter types and trailing cv/ref qualifiers of the signature of function
+/// `name`, without parameter names or default values: `void resize(int factor = 1) const`
+/// and `void Shape::resize(int) const` both give `(int) const`.
+fn parameter_key(name: &str, sig: &str) -> Option<String> {
+    let base = name.rsplit('.').next().unwrap_or(name);
+    let open = [format!("{base}("), format!("{base} (")]
+        .iter()
+        .find_map(|call| sig.find(call.as_str()).map(|i| i + call.len() - 1))
+        .or_else(|| sig.find('('))?;
+
+    let mut depth = 0usize;
+    let mut close = None;
+    for (i, c) in sig[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+
+    let mut params: Vec<String> = split_top_level(&sig[open + 1..close])
+        .into_iter()
+        .filter_map(parameter_type)
+        .collect();
+    if params == ["void"] {
+        params.clear();
+    }
+    let qualifiers: String = sig[close + 1..]
+        .split_whitespace()
+        .take_while(|w| matches!(*w, "const" | "volatile" | "&" | "&&"))
+        .map(|w| format!(" {w}"))
+        .collect();
+    Some(format!("({}){qualifiers}", params.join(",")))
+}
+
+/// Split a parameter list at the commas outside brackets and template arguments.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+}
+
+/// The type of one parameter declaration (`const std::string& name = ""` →
+/// `const std::string&`), with whitespace around `*` and `&` removed.
+fn parameter_type(param: &str) -> Option<String> {
+    let param = param.split_once('=').map_or(param, |(decl, _)| decl).trim();
+    if param.is_empty() {
+        return None;
+    }
+    // An array suffix belongs to the type, whatever its bound
+    let (param, array) = match param.find('[') {
+        Some(i) => (param[..i].trim_end(), "[]"),
+        None => (param, ""),
+    };
+
+    // Drop a trailing identifier when what precedes it is a complete type
+    let name_start = param
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let (ty, last) = param.split_at(name_start);
+    let ty = ty.trim_end();
+    let named = !last.is_empty()
+        && !ty.is_empty()
+        && !ty.ends_with("::")
+        && !TYPE_WORDS.contains(&last)
+        && !ty.split_whitespace().all(|w| TYPE_QUALIFIERS.contains(&w));
+    let ty = if named { ty } else { param };
+
+    let ty = collapse_whitespace(ty)
+        .replace(" *", "*")
+        .replace("* ", "*")
+        .replace(" &", "&")
+        .replace("& ", "&");
+    Some(format!("{ty}{array}"))
+}
+
 // ---------------------------------------------------------------------------
 // Builtin detection for filtering noisy references
 // ---------------------------------------------------------------------------
@@ -297,6 +470,9 @@ fn walk_node(
         "declaration" => {
             extract_declaration(node, source, file_path, parent_ctx, access, symbols);
         }
+        "field_declaration" => {
+            extract_member_declaration(node, source, file_path, parent_ctx, access, symbols);
+        }
         "class_specifier" | "struct_specifier" => {
             extract_class(
                 node, source, file_path, kind, parent_ctx, symbols, texts, references, depth,
@@ -415,7 +591,7 @@ fn extract_call_ref(
     };
 
     let name = get_call_name(func, source);
-    if name.is_empty() || is_cpp_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_cpp_builtin_call) {
         return;
     }
 
@@ -444,7 +620,7 @@ fn extract_new_ref(
     };
 
     let name = get_type_name(type_node, source);
-    if name.is_empty() || is_cpp_builtin_call(&name) || is_cpp_primitive_type(&name) {
+    if name.is_empty() || is_filtered(&name, is_cpp_builtin_call) || is_cpp_primitive_type(&name) {
         return;
     }
 
@@ -551,7 +727,21 @@ fn extract_function(
     }
 
     let line = node_line_range(node);
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = extract_signature_to_brace(node, source);
+
+    // Out-of-line definitions (`void Foo::bar() {}`) belong to the qualifying scope,
+    // named like the in-class declaration: `ns.Foo.bar` with parent `ns.Foo`
+    let (parent, name) = match split_qualified_name(&name) {
+        Some((scope, base)) => (
+            Some(match parent_ctx {
+                Some(ctx) => format!("{ctx}.{scope}"),
+                None => scope,
+            }),
+            base,
+        ),
+        None => (parent_ctx.map(String::from), name),
+    };
+    let parent_ctx = parent.as_deref();
 
     let kind = if parent_ctx.is_some() {
         "method"
@@ -595,6 +785,85 @@ fn extract_function(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
+    );
+}
+
+/// Split a qualified declarator name (`ns::Foo<T>::bar`) into its dotted scope
+/// without template arguments (`ns.Foo`) and the base name (`bar`).
+fn split_qualified_name(name: &str) -> Option<(String, String)> {
+    // Match `::` outside template arguments only
+    let mut depth = 0usize;
+    let mut split = None;
+    let bytes = name.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'<' => depth += 1,
+            b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => split = Some(i),
+            _ => {}
+        }
+    }
+    let i = split?;
+    let (scope, base) = (&name[..i], &name[i + 2..]);
+    let mut dotted = String::new();
+    let mut depth = 0usize;
+    for c in scope.trim_start_matches("::").chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ if depth > 0 => {}
+            ':' if !dotted.ends_with('.') => dotted.push('.'),
+            ':' => {}
+            c if !c.is_whitespace() => dotted.push(c),
+            _ => {}
+        }
+    }
+    if dotted.is_empty() || base.is_empty() {
+        return None;
+    }
+    Some((dotted, base.to_string()))
+}
+
+/// Extract a member function declared (not defined) in a class body.
+fn extract_member_declaration(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    access: &str,
+    symbols: &mut Vec<SymbolEntry>,
+) {
+    let Some(parent) = parent_ctx else {
+        return;
+    };
+    // `int* get();`, `const T& name() const;` wrap the function declarator
+    let mut declarator = find_child_by_field(node, "declarator");
+    while let Some(d) = declarator
+        && matches!(d.kind(), "pointer_declarator" | "reference_declarator")
+    {
+        declarator = find_child_by_field(d, "declarator").or_else(|| d.named_child(0));
+    }
+    let Some(declarator) = declarator.filter(|d| d.kind() == "function_declarator") else {
+        return;
+    };
+    let name = extract_declarator_name(declarator, source);
+    if name.is_empty() {
+        return;
+    }
+
+    let sig = collapse_whitespace(node_text(node, source).trim().trim_end_matches(';'));
+    push_symbol(
+        symbols,
+        file_path,
+        format!("{parent}.{name}"),
+        "method",
+        node_line_range(node),
+        parent_ctx,
+        None,
+        None,
+        Some(access.to_string()),
+        Some(sig),
     );
 }
 
@@ -632,7 +901,8 @@ fn extract_declaration(
             "function_declarator" => {
                 let name = extract_declarator_name(child, source);
                 if !name.is_empty() {
-                    let _sig = collapse_whitespace(node_text(node, source).trim());
+                    let sig =
+                        collapse_whitespace(node_text(node, source).trim().trim_end_matches(';'));
                     let full_name = if let Some(parent) = parent_ctx {
                         format!("{parent}.{name}")
                     } else {
@@ -654,6 +924,7 @@ fn extract_declaration(
                         None,
                         None,
                         Some(visibility.clone()),
+                        Some(sig),
                     );
                 }
             }
@@ -681,6 +952,7 @@ fn extract_declaration(
                             None,
                             None,
                             Some(visibility.clone()),
+                            None,
                         );
                     }
                 }
@@ -708,6 +980,7 @@ fn extract_declaration(
                         None,
                         None,
                         Some(visibility.clone()),
+                        None,
                     );
                 }
             }
@@ -798,6 +1071,7 @@ fn extract_class(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     // Walk class body with access tracking
@@ -873,6 +1147,7 @@ fn extract_enum(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     // Extract enum values
@@ -894,6 +1169,7 @@ fn extract_enum(
                     None,
                     None,
                     Some("public".to_string()),
+                    None,
                 );
             }
         }
@@ -953,6 +1229,7 @@ fn extract_namespace(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     if let Some(body) = find_child_by_field(node, "body") {
@@ -1000,6 +1277,7 @@ fn extract_typedef(
                 None,
                 None,
                 Some("public".to_string()),
+                None,
             );
         }
     }
@@ -1034,6 +1312,7 @@ fn extract_using_alias(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 }
 
@@ -1061,6 +1340,7 @@ fn extract_using(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec<S
             None,
             None,
             Some("private".to_string()),
+            None,
         );
     }
 }
@@ -1089,6 +1369,7 @@ fn extract_include(
             None,
             None,
             Some("private".to_string()),
+            None,
         );
         // Also add import reference
         references.push(ReferenceEntry {
@@ -1125,6 +1406,7 @@ fn extract_macro(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec<S
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 }
 
@@ -1448,4 +1730,187 @@ public:
         assert!(type_refs.iter().any(|r| r.name == "Base"));
         assert!(type_refs.iter().any(|r| r.name == "CustomType"));
     }
+
+    /// Symbol results of a search for `name` with that exact name, as (file, lines).
+    fn search_symbol(db: &crate::server::db::SearchDb, name: &str) -> Vec<(String, [u32; 2])> {
+        use crate::server::db::{SearchQuery, SearchResult};
+        let base = name.rsplit('.').next().unwrap_or(name);
+        db.search(&SearchQuery::new(base))
+            .unwrap()
+            .into_iter()
+            .filter_map(|result| match result {
+                SearchResult::Symbol(s) if s.name == name => Some((s.file, s.line)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn file_entry(path: &str) -> crate::index::format::FileEntry {
+        crate::index::format::FileEntry {
+            path: path.to_string(),
+            lang: Some("cpp".to_string()),
+            hash: String::new(),
+            lines: 0,
+            project: String::new(),
+            title: None,
+            description: None,
+            is_test: false,
+        }
+    }
+
+    const SHAPE_HEADER: &[u8] = b"namespace geo {
+class Shape {
+public:
+    double area() const;
+    const std::string& label() const;
+private:
+    void resize(int factor);
+    int width;
+};
+}
+void log_shape(int id);
+void unrelated();";
+
+    const SHAPE_SOURCE: &[u8] = b"#include \"shape.hpp\"
+namespace geo {
+double Shape::area() const {
+    return compute_area(width);
+}
+}
+void geo::Shape::resize(int factor) {
+    scale(factor);
+}
+void log_shape(int id) {
+    emit(id);
+}";
+
+    #[test]
+    fn test_cpp_merge_declaration_and_definition() {
+        let (header_symbols, _, _) = parse_file(SHAPE_HEADER, "cpp", "shape.hpp").unwrap();
+        let (source_symbols, _, _) = parse_file(SHAPE_SOURCE, "cpp", "shape.cpp").unwrap();
+
+        // Member declarations and out-of-line definitions share the qualified name
+        let area_decl = find_sym(&header_symbols, "geo.Shape.area");
+        assert_eq!(area_decl.kind, "method");
+        assert_eq!(area_decl.parent.as_deref(), Some("geo.Shape"));
+        find_sym(&header_symbols, "geo.Shape.label");
+        assert!(!header_symbols.iter().any(|s| s.name == "geo.Shape.width"));
+        let resize_def = find_sym(&source_symbols, "geo.Shape.resize");
+        assert_eq!(resize_def.kind, "method");
+        assert_eq!(resize_def.parent.as_deref(), Some("geo.Shape"));
+
+        let all: Vec<SymbolEntry> = header_symbols.into_iter().chain(source_symbols).collect();
+        let db = crate::server::db::SearchDb::new().unwrap();
+        db.load("", &[], &all, &[], &[]).unwrap();
+
+        // Search finds one symbol each, at the definition
+        assert_eq!(
+            search_symbol(&db, "geo.Shape.area"),
+            vec![("shape.cpp".to_string(), [3, 5])]
+        );
+        assert_eq!(search_symbol(&db, "geo.Shape.resize")[0].0, "shape.cpp");
+        assert_eq!(search_symbol(&db, "log_shape").len(), 1);
+
+        // Declarations without a definition are still found
+        assert_eq!(search_symbol(&db, "geo.Shape.label")[0].0, "shape.hpp");
+        assert_eq!(search_symbol(&db, "unrelated")[0].0, "shape.hpp");
+
+        // The header keeps its declarations outside search
+        let (header_symbols, _, _) = db.get_file_contents("", "shape.hpp").unwrap();
+        find_sym(&header_symbols, "geo.Shape.area");
+        let (_, exported, _, _) = db.export_for_project("").unwrap();
+        assert_eq!(exported.len(), all.len());
+    }
+
+    #[test]
+    fn test_cpp_merge_follows_file_updates() {
+        let (header_symbols, _, _) = parse_file(SHAPE_HEADER, "cpp", "shape.hpp").unwrap();
+        let (source_symbols, _, _) = parse_file(SHAPE_SOURCE, "cpp", "shape.cpp").unwrap();
+        let db = crate::server::db::SearchDb::new().unwrap();
+        let upsert = |path: &str, symbols: &[SymbolEntry]| {
+            db.upsert_file("", &file_entry(path), symbols, &[], &[])
+                .unwrap();
+            db.update_fts_for_file("", path).unwrap();
+        };
+
+        upsert("shape.hpp", &header_symbols);
+        assert_eq!(search_symbol(&db, "log_shape")[0].0, "shape.hpp");
+
+        // A definition arriving later hides the declaration
+        upsert("shape.cpp", &source_symbols);
+        assert_eq!(
+            search_symbol(&db, "log_shape"),
+            vec![("shape.cpp".to_string(), [10, 12])]
+        );
+
+        // Re-indexing the header pairs it again
+        upsert("shape.hpp", &header_symbols);
+        assert_eq!(search_symbol(&db, "log_shape").len(), 1);
+
+        // Without the definition, the declaration is found again
+        db.remove_file("", "shape.cpp").unwrap();
+        db.update_fts_for_file("", "shape.cpp").unwrap();
+        assert_eq!(
+            search_symbol(&db, "log_shape"),
+            vec![("shape.hpp".to_string(), [11, 11])]
+        );
+    }
+
+    #[test]
+    fn test_cpp_declaration_pairs_match_overloads_by_signature() {
+        let header = b"void draw(int x);
+void draw(double x, int times = 1);
+inline void inline_only() { run(); }";
+        let source = b"void draw(double value, int) { paint(value); }";
+        let (header_symbols, _, _) = parse_file(header, "cpp", "draw.hh").unwrap();
+        let (source_symbols, _, _) = parse_file(source, "cpp", "draw.cc").unwrap();
+        let all: Vec<SymbolEntry> = header_symbols.into_iter().chain(source_symbols).collect();
+
+        // The definition pairs with the overload of the same parameter types,
+        // though it is not the first declaration and names its parameters differently
+        let pairs = declaration_pairs(&all);
+        assert_eq!(pairs.len(), 1);
+        let (decl, def) = pairs[0];
+        assert_eq!(all[decl].line, [2, 2]);
+        assert_eq!(all[def].file, "draw.cc");
+        // Header functions with a body are definitions, left alone
+        find_sym(&all, "inline_only");
+    }
+
+    #[test]
+    #[cfg(feature = "lang-c")]
+    fn test_cpp_declaration_pairs_h_header() {
+        // `.h` files are parsed as C; their prototypes pair with C++ definitions
+        let (header_symbols, _, _) = parse_file(b"int add(int a, int b);", "c", "math.h").unwrap();
+        let (source_symbols, _, _) = parse_file(
+            b"int add(int x, int y) { return x + y; }",
+            "cpp",
+            "math.cpp",
+        )
+        .unwrap();
+        let all: Vec<SymbolEntry> = header_symbols.into_iter().chain(source_symbols).collect();
+        assert_eq!(declaration_pairs(&all), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_cpp_parameter_key() {
+        assert_eq!(
+            parameter_key("geo.Shape.area", "double Shape::area() const").as_deref(),
+            Some("() const")
+        );
+        assert_eq!(
+            parameter_key(
+                "put",
+                "void put(const std::string & key, std::map<int, int> m, char *buf[] = nullptr)"
+            )
+            .as_deref(),
+            Some("(const std::string&,std::map<int, int>,char*[])")
+        );
+        // Unnamed parameters keep their whole type
+        assert_eq!(
+            parameter_key("f", "void f(unsigned long, const Foo, std::string)").as_deref(),
+            Some("(unsigned long,const Foo,std::string)")
+        );
+        assert_eq!(parameter_key("g", "int g(void)").as_deref(), Some("()"));
+    }
 }
@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -366,8 +367,9 @@ fn walk_node(
             extract_enum(node, source, file_path, parent_ctx, symbols);
         }
         "record_declaration" => {
+            // `record`, `record class` and `record struct` are all indexed as classes
             extract_type_decl(
-                node, source, file_path, "struct", parent_ctx, symbols, texts, references, depth,
+                node, source, file_path, "class", parent_ctx, symbols, texts, references, depth,
             );
             return;
         }
@@ -459,7 +461,7 @@ fn extract_call_ref(
     };
 
     let name = get_call_name(func, source);
-    if name.is_empty() || is_csharp_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_csharp_builtin_call) {
         return;
     }
 
@@ -488,7 +490,10 @@ fn extract_new_ref(
     };
 
     let name = get_type_name(type_node, source);
-    if name.is_empty() || is_csharp_builtin_call(&name) || is_csharp_primitive_type(&name) {
+    if name.is_empty()
+        || is_filtered(&name, is_csharp_builtin_call)
+        || is_csharp_primitive_type(&name)
+    {
         return;
     }
 
@@ -613,10 +618,46 @@ fn extract_type_decl(
     });
 
     let bases_str = bases
-        .map(|n| format!(" : {}", node_text(n, source)))
+        .map(|n| {
+            let text = node_text(n, source);
+            format!(" : {}", text.trim_start_matches(':').trim_start())
+        })
         .unwrap_or_default();
 
-    let _sig = format!("{kind} {name}{type_params}{bases_str}");
+    // Records: `record Point(int X, int Y)` declares a property per positional parameter
+    let is_record = node.kind() == "record_declaration";
+    let record_params = is_record
+        .then(|| {
+            let mut cursor = node.walk();
+            node.children(&mut cursor)
+                .find(|c| c.kind() == "parameter_list")
+        })
+        .flatten();
+    let keyword = if is_record {
+        let mut cursor = node.walk();
+        match node
+            .children(&mut cursor)
+            .find(|c| matches!(c.kind(), "struct" | "class"))
+        {
+            Some(k) => format!("record {}", k.kind()),
+            None => "record".to_string(),
+        }
+    } else {
+        kind.to_string()
+    };
+    let params_str = record_params
+        .map(|list| {
+            let mut cursor = list.walk();
+            let params: Vec<String> = list
+                .children(&mut cursor)
+                .filter(|c| c.kind() == "parameter")
+                .map(|p| csharp_signature(p, source))
+                .collect();
+            format!("({})", params.join(", "))
+        })
+        .unwrap_or_default();
+
+    let sig = format!("{keyword} {name}{type_params}{params_str}{bases_str}");
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -624,23 +665,38 @@ fn extract_type_decl(
         name.clone()
     };
 
+    extract_attribute_refs(node, source, file_path, &full_name, references);
+
     // Extract base type references
     if let Some(base_list) = bases {
         let mut cursor = base_list.walk();
         for child in base_list.children(&mut cursor) {
-            // Look for base types in base_list
-            if matches!(
-                child.kind(),
-                "identifier" | "identifier_name" | "generic_name" | "qualified_name"
-            ) {
-                extract_type_ref(child, source, file_path, Some(&full_name), references);
+            // Look for base types in base_list; `Base(First)` of a record names its type field
+            let base_type = if child.kind() == "primary_constructor_base_type" {
+                find_child_by_field(child, "type")
+            } else {
+                Some(child)
+            };
+            if let Some(base_type) = base_type
+                && matches!(
+                    base_type.kind(),
+                    "identifier" | "identifier_name" | "generic_name" | "qualified_name"
+                )
+            {
+                extract_type_ref(base_type, source, file_path, Some(&full_name), references);
             }
         }
     }
 
-    // Extract tokens from type body
-    let tokens = find_child_by_field(node, "body")
+    // Extract tokens from type body; records carry a `record` marker
+    let mut tokens = find_child_by_field(node, "body")
         .and_then(|body| filter_csharp_tokens(extract_tokens(body, source)));
+    if is_record {
+        tokens = Some(match tokens {
+            Some(t) => format!("record {t}"),
+            None => "record".to_string(),
+        });
+    }
 
     push_symbol(
         symbols,
@@ -652,8 +708,13 @@ fn extract_type_decl(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 
+    if let Some(list) = record_params {
+        extract_record_parameters(list, source, file_path, &full_name, symbols, references);
+    }
+
     // Walk body
     if let Some(body) = find_child_by_field(node, "body") {
         let mut cursor = body.walk();
@@ -672,6 +733,93 @@ fn extract_type_decl(
     }
 }
 
+/// Positional parameters of a record become its public properties.
+fn extract_record_parameters(
+    list: Node,
+    source: &[u8],
+    file_path: &str,
+    record: &str,
+    symbols: &mut Vec<SymbolEntry>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let mut cursor = list.walk();
+    for param in list.children(&mut cursor) {
+        if param.kind() != "parameter" {
+            continue;
+        }
+        let Some(name_node) = find_child_by_field(param, "name") else {
+            continue;
+        };
+        let full_name = format!("{record}.{}", node_text(name_node, source));
+        if let Some(param_type) = find_child_by_field(param, "type") {
+            extract_type_ref(param_type, source, file_path, Some(&full_name), references);
+        }
+        extract_attribute_refs(param, source, file_path, &full_name, references);
+        push_symbol(
+            symbols,
+            file_path,
+            full_name,
+            "property",
+            node_line_range(param),
+            Some(record),
+            None,
+            None,
+            Some("public".to_string()),
+            Some(csharp_signature(param, source)),
+        );
+    }
+}
+
+/// Extract an `annotation` reference for each attribute applied to a declaration
+/// (`[HttpGet("users")]`, `[property: JsonPropertyName("id")]`).
+fn extract_attribute_refs(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    caller: &str,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let mut cursor = node.walk();
+    for list in node.children(&mut cursor) {
+        if list.kind() != "attribute_list" {
+            continue;
+        }
+        let mut list_cursor = list.walk();
+        for attribute in list.children(&mut list_cursor) {
+            if attribute.kind() != "attribute" {
+                continue;
+            }
+            let Some(name_node) = find_child_by_field(attribute, "name") else {
+                continue;
+            };
+            references.push(ReferenceEntry {
+                file: file_path.to_string(),
+                name: node_text(name_node, source),
+                kind: "annotation".to_string(),
+                line: node_line_range(attribute),
+                caller: Some(caller.to_string()),
+                project: String::new(),
+            });
+        }
+    }
+}
+
+/// Signature of a declaration up to its body, without the attribute lists in
+/// front of it: `[HttpGet] public async Task<int> Load()` keeps `public async Task<int> Load()`.
+fn csharp_signature(node: Node, source: &[u8]) -> String {
+    let mut cursor = node.walk();
+    let start = node
+        .children(&mut cursor)
+        .find(|c| c.kind() != "attribute_list" && c.kind() != "comment")
+        .map_or(node.start_byte(), |c| c.start_byte());
+    let text = String::from_utf8_lossy(source.get(start..node.end_byte()).unwrap_or_default());
+    let end = text
+        .find('{')
+        .or_else(|| text.find(';'))
+        .unwrap_or(text.len());
+    collapse_whitespace(text[..end].trim())
+}
+
 fn extract_enum(
     node: Node,
     source: &[u8],
@@ -703,6 +851,7 @@ fn extract_enum(
         None,
         None,
         Some(visibility),
+        None,
     );
 
     // Extract enum members
@@ -724,6 +873,7 @@ fn extract_enum(
                     None,
                     None,
                     Some("public".to_string()),
+                    None,
                 );
             }
         }
@@ -764,6 +914,7 @@ fn extract_namespace(
         None,
         None,
         Some("public".to_string()),
+        None,
     );
 
     // Walk namespace body
@@ -826,7 +977,7 @@ fn extract_method(
 
     let line = node_line_range(node);
     let visibility = extract_csharp_visibility(node, source);
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = csharp_signature(node, source);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -834,6 +985,8 @@ fn extract_method(
         name
     };
 
+    extract_attribute_refs(node, source, file_path, &full_name, references);
+
     // Extract return type reference
     if let Some(ret_type) =
         find_child_by_field(node, "type").or_else(|| find_child_by_field(node, "returns"))
@@ -867,6 +1020,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -885,7 +1039,7 @@ fn extract_constructor(
 
     let line = node_line_range(node);
     let visibility = extract_csharp_visibility(node, source);
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = csharp_signature(node, source);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -893,6 +1047,8 @@ fn extract_constructor(
         name
     };
 
+    extract_attribute_refs(node, source, file_path, &full_name, references);
+
     // Extract parameter type references
     if let Some(params) = find_child_by_field(node, "parameters") {
         let mut cursor = params.walk();
@@ -919,6 +1075,7 @@ fn extract_constructor(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -948,6 +1105,7 @@ fn extract_property(
     if let Some(prop_type) = find_child_by_field(node, "type") {
         extract_type_ref(prop_type, source, file_path, Some(&full_name), references);
     }
+    extract_attribute_refs(node, source, file_path, &full_name, references);
 
     push_symbol(
         symbols,
@@ -959,9 +1117,32 @@ fn extract_property(
         None,
         None,
         Some(visibility),
+        Some(property_signature(node, source)),
     );
 }
 
+/// Signature of a property: type and name, plus the accessor list of an
+/// auto-property (`public int Age { get; init; }`).
+fn property_signature(node: Node, source: &[u8]) -> String {
+    let mut cursor = node.walk();
+    let start = node
+        .children(&mut cursor)
+        .find(|c| c.kind() != "attribute_list" && c.kind() != "comment")
+        .map_or(node.start_byte(), |c| c.start_byte());
+    let auto_accessors = find_child_by_field(node, "accessors").filter(|list| {
+        let text = node_text(*list, source);
+        let inner = text.trim().trim_start_matches('{').trim_end_matches('}');
+        !inner.contains('{') && !inner.contains("=>")
+    });
+    let end = match (auto_accessors, find_child_by_field(node, "name")) {
+        (Some(list), _) => list.end_byte(),
+        (None, Some(name)) => name.end_byte(),
+        (None, None) => node.end_byte(),
+    };
+    let text = String::from_utf8_lossy(source.get(start..end).unwrap_or_default());
+    collapse_whitespace(text.trim())
+}
+
 fn extract_field(
     node: Node,
     source: &[u8],
@@ -1018,6 +1199,7 @@ fn extract_field(
                         None,
                         None,
                         Some(visibility.clone()),
+                        None,
                     );
                 }
             }
@@ -1039,7 +1221,7 @@ fn extract_delegate(
 
     let line = node_line_range(node);
     let visibility = extract_csharp_visibility(node, source);
-    let _sig = collapse_whitespace(node_text(node, source).trim());
+    let sig = collapse_whitespace(node_text(node, source).trim().trim_end_matches(';'));
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -1057,6 +1239,7 @@ fn extract_delegate(
         None, // Delegates don't have bodies
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -1096,6 +1279,7 @@ fn extract_using(
                     None,
                     None,
                     Some("private".to_string()),
+                    None,
                 );
             }
             "name_equals" => {
@@ -1128,6 +1312,7 @@ fn extract_using(
                             None,
                             Some(a),
                             Some("private".to_string()),
+                            None,
                         );
                     }
                 }
@@ -1542,4 +1727,85 @@ using MyApp.Services;";
         assert!(types.iter().any(|r| r.name == "Animal"));
         assert!(types.iter().any(|r| r.name == "IWalkable"));
     }
+
+    #[test]
+    fn test_csharp_positional_record() {
+        let source = b"public record Person([property: JsonPropertyName(\"first\")] string First, string Last) : Entity(First);
+public record struct Point(int X, int Y);";
+        let (symbols, _texts, refs) = parse_file(source, "csharp", "test.cs").unwrap();
+
+        let person = find_sym(&symbols, "Person");
+        assert_eq!(person.kind, "class");
+        assert_eq!(
+            person.sig.as_deref(),
+            Some("record Person(string First, string Last) : Entity(First)")
+        );
+        assert!(person.tokens.as_deref().unwrap().contains("record"));
+
+        let first = find_sym(&symbols, "Person.First");
+        assert_eq!(first.kind, "property");
+        assert_eq!(first.parent.as_deref(), Some("Person"));
+        assert_eq!(first.sig.as_deref(), Some("string First"));
+        assert_eq!(find_sym(&symbols, "Person.Last").kind, "property");
+        assert!(refs.iter().any(|r| r.kind == "type_annotation"
+            && r.name == "Entity"
+            && r.caller.as_deref() == Some("Person")));
+        assert!(refs.iter().any(|r| r.kind == "annotation"
+            && r.name == "JsonPropertyName"
+            && r.caller.as_deref() == Some("Person.First")));
+
+        let point = find_sym(&symbols, "Point");
+        assert_eq!(point.kind, "class");
+        assert_eq!(
+            point.sig.as_deref(),
+            Some("record struct Point(int X, int Y)")
+        );
+        assert_eq!(find_sym(&symbols, "Point.Y").kind, "property");
+    }
+
+    #[test]
+    fn test_csharp_auto_property_signature() {
+        let source = b"public class User
+{
+    public int Age { get; init; }
+    public string Name => first + last;
+}";
+        let (symbols, _texts, _refs) = parse_file(source, "csharp", "test.cs").unwrap();
+        let age = find_sym(&symbols, "User.Age");
+        assert_eq!(age.kind, "property");
+        assert_eq!(age.sig.as_deref(), Some("public int Age { get; init; }"));
+        let name = find_sym(&symbols, "User.Name");
+        assert_eq!(name.sig.as_deref(), Some("public string Name"));
+    }
+
+    #[test]
+    fn test_csharp_async_method_and_attributes() {
+        let source = b"[ApiController]
+public class UsersController
+{
+    [HttpGet(\"{id}\")]
+    [Authorize]
+    public async Task<int> Load(int id)
+    {
+        return await repo.Count();
+    }
+}";
+        let (symbols, _texts, refs) = parse_file(source, "csharp", "test.cs").unwrap();
+        let load = find_sym(&symbols, "UsersController.Load");
+        assert_eq!(load.kind, "method");
+        assert_eq!(
+            load.sig.as_deref(),
+            Some("public async Task<int> Load(int id)")
+        );
+
+        let annotations: Vec<_> = refs.iter().filter(|r| r.kind == "annotation").collect();
+        assert!(annotations.iter().any(|r| r.name == "HttpGet"
+            && r.caller.as_deref() == Some("UsersController.Load")));
+        assert!(annotations.iter().any(|r| r.name == "Authorize"));
+        assert!(
+            annotations.iter().any(
+                |r| r.name == "ApiController" && r.caller.as_deref() == Some("UsersController")
+            )
+        );
+    }
 }
@@ -0,0 +1,170 @@
+//! Per-project overrides of the builtin and stopword filters, read from an
+//! optional `.codeix.toml` at the project root:
+//!
+//! ```toml
+//! # Calls the builtin filters would drop, kept as references
+//! allow_references = ["map", "filter"]
+//! # Extra words dropped from symbol tokens, on top of the universal stopwords
+//! stopwords = ["acme"]
+//! # Calls whose string arguments join the enclosing symbol's tokens
+//! token_calls = ["route", "permission_required"]
+//! ```
+//!
+//! Each extractor reaches its builtin check deep inside the tree walk, so the
+//! policy is not threaded through every walker: [`with_policy`] installs it for
+//! the current thread while a file is extracted, and [`is_filtered`] consults it.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::index::format::SymbolEntry;
+use crate::parser::augment::CallArgumentTokens;
+
+/// Name of the per-project configuration file.
+pub const CONFIG_FILE: &str = ".codeix.toml";
+
+/// What a project changes about the builtin filters of the extractors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterPolicy {
+    /// Builtin names kept as references. A call matches on its full name
+    /// (`arr.map`) or its last segment (`map`).
+    pub allow_references: BTreeSet<String>,
+    /// Extra stopwords removed from symbol tokens (case-insensitive)
+    pub stopwords: BTreeSet<String>,
+    /// Functions whose string-literal arguments are added to the tokens of the
+    /// enclosing symbol (see [`CallArgumentTokens`])
+    pub token_calls: BTreeSet<String>,
+}
+
+impl FilterPolicy {
+    /// Read `.codeix.toml` from `project_root`; no file means the default policy.
+    pub fn load(project_root: &Path) -> Result<Self> {
+        let path = project_root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+        };
+        let mut policy: Self = toml::from_str(&content)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        policy.stopwords = policy.stopwords.iter().map(|w| w.to_lowercase()).collect();
+        Ok(policy)
+    }
+
+    /// Whether this policy changes nothing.
+    pub fn is_empty(&self) -> bool {
+        self.allow_references.is_empty() && self.stopwords.is_empty() && self.token_calls.is_empty()
+    }
+
+    /// The augmentor collecting the arguments of `token_calls`, if any are listed.
+    pub fn call_tokens(&self) -> Option<CallArgumentTokens> {
+        (!self.token_calls.is_empty()).then(|| CallArgumentTokens::new(&self.token_calls))
+    }
+
+    /// Whether a call the builtin filters drop should be kept as a reference.
+    pub fn allows_reference(&self, name: &str) -> bool {
+        let last = name.rsplit(['.', ':', '>']).next().unwrap_or(name);
+        self.allow_references.contains(name) || self.allow_references.contains(last)
+    }
+
+    /// Remove the extra stopwords from the tokens of `symbols`.
+    pub fn strip_stopwords(&self, symbols: &mut [SymbolEntry]) {
+        if self.stopwords.is_empty() {
+            return;
+        }
+        for symbol in symbols {
+            let Some(tokens) = &symbol.tokens else {
+                continue;
+            };
+            let kept: Vec<&str> = tokens
+                .split_whitespace()
+                .filter(|t| !self.stopwords.contains(&t.to_lowercase()))
+                .collect();
+            symbol.tokens = (!kept.is_empty()).then(|| kept.join(" "));
+        }
+    }
+}
+
+thread_local! {
+    /// Policy of the file being extracted on this thread (see [`with_policy`]).
+    static ACTIVE: RefCell<Option<FilterPolicy>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `policy` as this thread's active policy, restoring the previous
+/// one afterwards (also when `f` panics).
+pub fn with_policy<T>(policy: &FilterPolicy, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<FilterPolicy>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTIVE.set(self.0.take());
+        }
+    }
+
+    if policy.allow_references.is_empty() && ACTIVE.with_borrow(Option::is_none) {
+        return f();
+    }
+    let _restore = Restore(ACTIVE.replace(Some(policy.clone())));
+    f()
+}
+
+/// Whether the extractor should drop a reference to `name`: `builtin` says it
+/// is a builtin, and the active policy does not allow it back.
+pub fn is_filtered(name: &str, builtin: impl Fn(&str) -> bool) -> bool {
+    builtin(name)
+        && !ACTIVE.with_borrow(|policy| policy.as_ref().is_some_and(|p| p.allows_reference(name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_policy() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(
+            FilterPolicy::load(tmp.path()).unwrap(),
+            FilterPolicy::default()
+        );
+
+        std::fs::write(
+            tmp.path().join(CONFIG_FILE),
+            "allow_references = [\"map\"]\nstopwords = [\"Acme\"]\ntoken_calls = [\"route\"]\n",
+        )
+        .unwrap();
+        let policy = FilterPolicy::load(tmp.path()).unwrap();
+        assert!(policy.allows_reference("map"));
+        assert!(policy.allows_reference("items.map"));
+        assert!(!policy.allows_reference("filter"));
+        assert!(policy.stopwords.contains("acme"));
+        assert!(policy.call_tokens().is_some());
+        assert!(FilterPolicy::default().call_tokens().is_none());
+
+        std::fs::write(tmp.path().join(CONFIG_FILE), "allow = [\"map\"]\n").unwrap();
+        assert!(FilterPolicy::load(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn test_with_policy_scopes_overrides() {
+        let is_builtin = |name: &str| name == "map";
+        let policy = FilterPolicy {
+            allow_references: BTreeSet::from(["map".to_string()]),
+            ..FilterPolicy::default()
+        };
+
+        assert!(is_filtered("map", is_builtin));
+        with_policy(&policy, || {
+            assert!(!is_filtered("map", is_builtin));
+            // A nested default policy takes over until it returns
+            with_policy(&FilterPolicy::default(), || {
+                assert!(is_filtered("map", is_builtin))
+            });
+            assert!(!is_filtered("map", is_builtin));
+        });
+        assert!(is_filtered("map", is_builtin));
+    }
+}
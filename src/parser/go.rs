@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -127,6 +128,7 @@ fn extract_function(
         tokens,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Extract type references from parameters
@@ -218,6 +220,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Extract type references from parameters
@@ -303,6 +306,7 @@ fn extract_type_spec(
         None,
         None,
         Some(visibility),
+        None,
     );
 
     // For structs, extract fields and their type references
@@ -318,9 +322,28 @@ fn extract_type_spec(
             let mut cursor = field_list.walk();
             for child in field_list.children(&mut cursor) {
                 if child.kind() == "field_declaration" {
-                    if let Some(field_name_node) = find_child_by_field(child, "name") {
-                        let field_name = node_text(field_name_node, source);
-                        let field_line = node_line_range(child);
+                    // `X, Y int` declares several fields; an embedded field (`io.Reader`,
+                    // `*Base`) is named after its type, without package or pointer
+                    let mut field_names: Vec<String> = {
+                        let mut c = child.walk();
+                        child
+                            .children_by_field_name("name", &mut c)
+                            .map(|n| node_text(n, source))
+                            .collect()
+                    };
+                    if field_names.is_empty()
+                        && let Some(field_type) = find_child_by_field(child, "type")
+                    {
+                        let type_name = node_text(field_type, source);
+                        let type_name = type_name.trim_start_matches('*');
+                        let base = type_name.split('[').next().unwrap_or(type_name);
+                        let base = base.rsplit('.').next().unwrap_or(base).trim();
+                        if !base.is_empty() {
+                            field_names.push(base.to_string());
+                        }
+                    }
+                    let field_line = node_line_range(child);
+                    for field_name in field_names {
                         let field_vis = go_visibility(&field_name);
                         push_symbol(
                             symbols,
@@ -332,6 +355,7 @@ fn extract_type_spec(
                             None,
                             None,
                             Some(field_vis),
+                            None,
                         );
                     }
                     // Extract type references from field type
@@ -355,7 +379,8 @@ fn extract_type_spec(
         if type_n.kind() == "interface_type" {
             let mut cursor = type_n.walk();
             for child in type_n.children(&mut cursor) {
-                if child.kind() == "method_spec"
+                // `method_elem` in current grammars, `method_spec` in older ones
+                if matches!(child.kind(), "method_elem" | "method_spec")
                     && let Some(method_name_node) = find_child_by_field(child, "name")
                 {
                     let method_name = node_text(method_name_node, source);
@@ -369,9 +394,10 @@ fn extract_type_spec(
                         "method",
                         method_line,
                         Some(&name),
-                        Some(method_sig),
+                        None,
                         None,
                         Some(method_vis),
+                        Some(method_sig),
                     );
                 }
             }
@@ -407,6 +433,7 @@ fn extract_var_const(
                     None,
                     None,
                     Some(visibility),
+                    None,
                 );
             }
             // Handle multiple names in one spec: `var a, b, c int`
@@ -433,6 +460,7 @@ fn extract_var_const(
                         None,
                         None,
                         Some(extra_vis),
+                        None,
                     );
                 }
             }
@@ -469,6 +497,7 @@ fn extract_imports(
                     None,
                     alias,
                     Some("private".to_string()),
+                    None,
                 );
                 // Also record as import reference
                 references.push(ReferenceEntry {
@@ -504,6 +533,7 @@ fn extract_imports(
                             None,
                             alias,
                             Some("private".to_string()),
+                            None,
                         );
                         // Also record as import reference
                         references.push(ReferenceEntry {
@@ -537,6 +567,7 @@ fn extract_package(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec
                 None,
                 None,
                 Some("public".to_string()),
+                None,
             );
         }
     }
@@ -565,7 +596,7 @@ fn extract_call(
     };
 
     // Skip builtins
-    if is_go_builtin_call(&name) {
+    if is_filtered(&name, is_go_builtin_call) {
         return;
     }
 
@@ -677,6 +708,10 @@ fn is_go_primitive_type(name: &str) -> bool {
     )
 }
 
+/// Comments, plus the directives the go tool reads from them, recorded at
+/// file level: build constraints (`//go:build linux && amd64`, or the older
+/// `// +build linux,386`) before the package clause as `build_tag` with the
+/// constraint as text, and `//go:generate` lines as `generate` with the command.
 fn extract_go_comment(
     node: Node,
     source: &[u8],
@@ -684,7 +719,43 @@ fn extract_go_comment(
     parent_ctx: Option<&str>,
     texts: &mut Vec<TextEntry>,
 ) {
-    extract_comment(node, source, file_path, parent_ctx, texts);
+    let raw = node_text(node, source);
+    let directive = if let Some(constraint) = raw.strip_prefix("//go:build ") {
+        is_file_header(node).then_some(("build_tag", constraint))
+    } else if let Some(constraint) = raw
+        .strip_prefix("//")
+        .and_then(|rest| rest.trim_start().strip_prefix("+build "))
+    {
+        is_file_header(node).then_some(("build_tag", constraint))
+    } else {
+        raw.strip_prefix("//go:generate ")
+            .map(|command| ("generate", command))
+    };
+
+    match directive {
+        Some((kind, text)) if !text.trim().is_empty() => texts.push(TextEntry {
+            file: file_path.to_string(),
+            kind: kind.to_string(),
+            line: node_line_range(node),
+            text: text.trim().to_string(),
+            parent: None,
+            project: String::new(),
+        }),
+        _ => extract_comment(node, source, file_path, parent_ctx, texts),
+    }
+}
+
+/// Whether `node` comes before the package clause, where the go tool looks
+/// for build constraints (only comments may precede it).
+fn is_file_header(node: Node) -> bool {
+    let mut prev = node.prev_sibling();
+    while let Some(sibling) = prev {
+        if sibling.kind() != "comment" {
+            return false;
+        }
+        prev = sibling.prev_sibling();
+    }
+    node.parent().is_some_and(|p| p.kind() == "source_file")
 }
 
 fn go_visibility(name: &str) -> String {
@@ -844,9 +915,59 @@ type Reader interface {
         assert_eq!(reader.kind, "interface");
         assert_eq!(reader.visibility.as_deref(), Some("public"));
 
-        // Interface methods may or may not be extracted depending on implementation
-        // Just verify the interface itself is extracted correctly
-        assert!(symbols.len() >= 2); // at least package + interface
+        let read = find_sym(&symbols, "Reader.Read");
+        assert_eq!(read.kind, "method");
+        assert_eq!(read.parent.as_deref(), Some("Reader"));
+        assert_eq!(read.visibility.as_deref(), Some("public"));
+        assert_eq!(read.sig.as_deref(), Some("Read() (int, error)"));
+        assert!(read.tokens.is_none());
+
+        let close = find_sym(&symbols, "Reader.close");
+        assert_eq!(close.visibility.as_deref(), Some("private"));
+    }
+
+    #[test]
+    fn test_go_struct_fields_and_interface_methods_are_children() {
+        let source = b"package main
+
+type Point struct {
+    X, Y int
+    label string
+    io.Reader
+    *Base
+}
+
+type Shape interface {
+    Area() float64
+    fmt.Stringer
+}";
+        let (symbols, _texts, _refs) = parse_file(source, "go", "test.go").unwrap();
+
+        let children = |parent: &str| -> Vec<(&str, &str, &str)> {
+            symbols
+                .iter()
+                .filter(|s| s.parent.as_deref() == Some(parent))
+                .map(|s| {
+                    (
+                        s.name.as_str(),
+                        s.kind.as_str(),
+                        s.visibility.as_deref().unwrap_or(""),
+                    )
+                })
+                .collect()
+        };
+        assert_eq!(
+            children("Point"),
+            vec![
+                ("Point.X", "property", "public"),
+                ("Point.Y", "property", "public"),
+                ("Point.label", "property", "private"),
+                ("Point.Reader", "property", "public"),
+                ("Point.Base", "property", "public"),
+            ]
+        );
+        // Embedded interfaces are not methods
+        assert_eq!(children("Shape"), vec![("Shape.Area", "method", "public")]);
     }
 
     #[test]
@@ -931,6 +1052,51 @@ func Helper() {}
         assert!(texts.iter().any(|t| t.kind == "comment"));
     }
 
+    #[test]
+    fn test_go_directives() {
+        let source = b"//go:build linux && (amd64 || arm64)
+// +build linux,amd64 linux,arm64
+
+// Package sys talks to the kernel.
+package sys
+
+//go:generate stringer -type=Mode
+//go:generate go run gen.go -out tables.go
+
+// +build ignored here
+func Open() {}
+";
+        let (_, texts, _) = parse_file(source, "go", "sys_linux.go").unwrap();
+        let of_kind = |kind: &str| -> Vec<&str> {
+            texts
+                .iter()
+                .filter(|t| t.kind == kind)
+                .map(|t| t.text.as_str())
+                .collect()
+        };
+
+        assert_eq!(
+            of_kind("build_tag"),
+            vec!["linux && (amd64 || arm64)", "linux,amd64 linux,arm64"]
+        );
+        assert_eq!(
+            of_kind("generate"),
+            vec!["stringer -type=Mode", "go run gen.go -out tables.go"]
+        );
+        assert!(
+            texts
+                .iter()
+                .filter(|t| t.kind == "build_tag" || t.kind == "generate")
+                .all(|t| t.parent.is_none())
+        );
+        let build = texts.iter().find(|t| t.kind == "build_tag").unwrap();
+        assert_eq!(build.line, [1, 1]);
+
+        // A constraint after the package clause means nothing to the go tool
+        assert!(of_kind("comment").contains(&"+build ignored here"));
+        assert!(of_kind("comment").contains(&"Package sys talks to the kernel."));
+    }
+
     #[test]
     fn test_go_call_references() {
         let source = b"package main
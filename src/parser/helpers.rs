@@ -124,6 +124,17 @@ pub fn collapse_whitespace(s: &str) -> String {
     result
 }
 
+/// Turn `\r\n` line endings into `\n` and drop a trailing `\r`, so a text
+/// extracted from a CRLF file is the same as from its LF equivalent.
+pub fn normalize_line_endings(text: &mut String) {
+    if !text.contains('\r') {
+        return;
+    }
+    *text = text.replace("\r\n", "\n");
+    let trimmed = text.trim_end_matches('\r').len();
+    text.truncate(trimmed);
+}
+
 /// Strip `///` or `//!` prefix from each line of a doc comment.
 pub fn strip_doc_comment_prefix(raw: &str) -> String {
     raw.lines()
@@ -296,6 +307,7 @@ pub fn push_symbol(
     tokens: Option<String>,
     alias: Option<String>,
     visibility: Option<String>,
+    sig: Option<String>,
 ) {
     symbols.push(SymbolEntry {
         file: file_path.to_string(),
@@ -303,6 +315,7 @@ pub fn push_symbol(
         kind: kind.to_string(),
         line,
         parent: parent.map(String::from),
+        sig,
         tokens,
         alias,
         visibility,
@@ -331,6 +344,20 @@ pub fn extract_signature_to_brace(node: Node, source: &[u8]) -> String {
     }
 }
 
+/// Extract a declaration signature: everything before the node's `body` field,
+/// without a trailing `:` (Python). Falls back to `extract_signature_to_brace`
+/// for nodes without a body. Collapses whitespace.
+pub fn extract_signature_before_body(node: Node, source: &[u8]) -> String {
+    let Some(body) = find_child_by_field(node, "body") else {
+        return extract_signature_to_brace(node, source);
+    };
+    let Some(bytes) = source.get(node.start_byte()..body.start_byte()) else {
+        return String::new();
+    };
+    let text = String::from_utf8_lossy(bytes);
+    collapse_whitespace(text.trim().trim_end_matches(':').trim_end())
+}
+
 /// Extract identifier tokens from a tree-sitter node for FTS indexing.
 ///
 /// Recursively walks the AST to collect all identifier nodes, filters by
@@ -366,7 +393,95 @@ pub fn extract_tokens(node: Node, source: &[u8]) -> Option<String> {
     }
 }
 
+/// Split an identifier into its words on `snake_case`/`kebab-case` separators
+/// and camelCase humps: `parseAsync` → `parse`, `Async`; `HTTPServer` → `HTTP`, `Server`.
+///
+/// Digits stay with the word before them (`utf8Decode` → `utf8`, `Decode`).
+/// A single-word identifier comes back as itself.
+pub fn split_identifier(ident: &str) -> Vec<String> {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // `fooBar`, `utf8Decode`, or the capital starting a word after an acronym (`HTTPServer`)
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
 /// Recursively collect identifier text from AST nodes.
+/// Longest run of literal text (in bytes) a constant adds to its tokens.
+pub const MAX_LITERAL_TOKENS_LEN: usize = 160;
+
+/// Append the number and string literals of a constant's initializer to its
+/// identifier `tokens`, so `search 30000` finds `const TIMEOUT_MS = 30000`.
+///
+/// Strings lose their quotes; literals nested in objects, arrays and dicts count
+/// too. The literal text is capped at [`MAX_LITERAL_TOKENS_LEN`] bytes.
+pub fn with_literal_tokens(tokens: Option<String>, value: Node, source: &[u8]) -> Option<String> {
+    let mut literals = String::new();
+    collect_literals(value, source, &mut literals, 0);
+    match (tokens, literals.is_empty()) {
+        (tokens, true) => tokens,
+        (None, false) => Some(literals),
+        (Some(tokens), false) => Some(format!("{tokens} {literals}")),
+    }
+}
+
+fn collect_literals(node: Node, source: &[u8], out: &mut String, depth: usize) {
+    if depth > 100 || out.len() >= MAX_LITERAL_TOKENS_LEN {
+        return;
+    }
+
+    let literal = match node.kind() {
+        // JS/TS "number", Python "integer"/"float"
+        "number" | "integer" | "float" => Some(node_text(node, source)),
+        // JS/TS/Python strings (template strings included): the text between the quotes
+        "string" | "template_string" => {
+            let text = node_text(node, source);
+            let start = text.find(['"', '\'', '`']).unwrap_or(0);
+            Some(text[start..].trim_matches(['"', '\'', '`']).to_string())
+        }
+        _ => None,
+    };
+    if let Some(literal) = literal {
+        let literal = collapse_whitespace(literal.trim());
+        if !literal.is_empty() {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            let room = MAX_LITERAL_TOKENS_LEN.saturating_sub(out.len());
+            let mut end = literal.len().min(room);
+            while !literal.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.push_str(&literal[..end]);
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_literals(child, source, out, depth + 1);
+    }
+}
+
 fn collect_identifiers(node: Node, source: &[u8], tokens: &mut HashSet<String>, depth: usize) {
     // Prevent stack overflow on deeply nested code
     if depth > 100 {
@@ -385,3 +500,26 @@ fn collect_identifiers(node: Node, source: &[u8], tokens: &mut HashSet<String>,
         collect_identifiers(child, source, tokens, depth + 1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("parseAsync"), vec!["parse", "Async"]);
+        assert_eq!(split_identifier("ParseAsync"), vec!["Parse", "Async"]);
+        assert_eq!(split_identifier("parse_async"), vec!["parse", "async"]);
+        assert_eq!(split_identifier("parse-async"), vec!["parse", "async"]);
+        assert_eq!(split_identifier("HTTPServer"), vec!["HTTP", "Server"]);
+        assert_eq!(
+            split_identifier("getHTTPResponse"),
+            vec!["get", "HTTP", "Response"]
+        );
+        assert_eq!(split_identifier("MAX_RETRIES"), vec!["MAX", "RETRIES"]);
+        assert_eq!(split_identifier("utf8Decode"), vec!["utf8", "Decode"]);
+        assert_eq!(split_identifier("__init__"), vec!["init"]);
+        assert_eq!(split_identifier("parse"), vec!["parse"]);
+        assert!(split_identifier("_").is_empty());
+    }
+}
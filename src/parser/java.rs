@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -360,7 +361,7 @@ fn extract_call_ref(
     references: &mut Vec<ReferenceEntry>,
 ) {
     let name = get_call_name(node, source);
-    if name.is_empty() || is_java_builtin(&name) {
+    if name.is_empty() || is_filtered(&name, is_java_builtin) {
         return;
     }
 
@@ -390,7 +391,7 @@ fn extract_new_ref(
     };
 
     let name = get_type_name(type_node, source);
-    if name.is_empty() || is_java_builtin(&name) || is_java_primitive_type(&name) {
+    if name.is_empty() || is_filtered(&name, is_java_builtin) || is_java_primitive_type(&name) {
         return;
     }
 
@@ -467,7 +468,7 @@ fn extract_type_refs(
     references: &mut Vec<ReferenceEntry>,
 ) {
     let name = get_type_name(node, source);
-    if name.is_empty() || is_java_primitive_type(&name) || is_java_builtin(&name) {
+    if name.is_empty() || is_java_primitive_type(&name) || is_filtered(&name, is_java_builtin) {
         return;
     }
 
@@ -482,6 +483,43 @@ fn extract_type_refs(
     });
 }
 
+/// Extract an `annotation` reference for each annotation in the modifiers of a
+/// declaration (`@RestController`, `@GetMapping("/")`), with the declared symbol as caller.
+/// `@Override` is skipped as noise.
+fn extract_annotation_refs(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    caller: &str,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let mut cursor = node.walk();
+    let Some(modifiers) = node.children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+        return;
+    };
+    let mut cursor = modifiers.walk();
+    for annotation in modifiers.children(&mut cursor) {
+        if !matches!(annotation.kind(), "marker_annotation" | "annotation") {
+            continue;
+        }
+        let Some(name_node) = find_child_by_field(annotation, "name") else {
+            continue;
+        };
+        let name = node_text(name_node, source);
+        if name == "Override" {
+            continue;
+        }
+        references.push(ReferenceEntry {
+            file: file_path.to_string(),
+            name,
+            kind: "annotation".to_string(),
+            line: node_line_range(annotation),
+            caller: Some(caller.to_string()),
+            project: String::new(),
+        });
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn extract_class(
     node: Node,
@@ -503,7 +541,7 @@ fn extract_class(
     let visibility = extract_java_visibility(node, source);
 
     // Build signature
-    let _sig = build_class_signature(node, source, &name, kind);
+    let sig = build_class_signature(node, source, &name, kind);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -511,18 +549,21 @@ fn extract_class(
         name.clone()
     };
 
-    // Extract superclass reference
-    if let Some(superclass) = find_child_by_field(node, "superclass") {
-        extract_type_refs(superclass, source, file_path, Some(&full_name), references);
-    }
-
-    // Extract interfaces references
-    if let Some(interfaces) = find_child_by_field(node, "interfaces") {
-        let mut cursor = interfaces.walk();
-        for child in interfaces.children(&mut cursor) {
+    // Extract superclass and interface references (`extends`/`implements`,
+    // and `extends` of an interface)
+    let mut cursor = node.walk();
+    for clause in node.children(&mut cursor) {
+        if !matches!(
+            clause.kind(),
+            "superclass" | "super_interfaces" | "extends_interfaces"
+        ) {
+            continue;
+        }
+        let mut clause_cursor = clause.walk();
+        for child in clause.named_children(&mut clause_cursor) {
             if child.kind() == "type_list" {
                 let mut type_cursor = child.walk();
-                for type_child in child.children(&mut type_cursor) {
+                for type_child in child.named_children(&mut type_cursor) {
                     extract_type_refs(type_child, source, file_path, Some(&full_name), references);
                 }
             } else {
@@ -530,6 +571,7 @@ fn extract_class(
             }
         }
     }
+    extract_annotation_refs(node, source, file_path, &full_name, references);
 
     // Extract tokens from class body
     let tokens = find_child_by_field(node, "body")
@@ -545,6 +587,7 @@ fn extract_class(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 
     // Walk class body
@@ -580,7 +623,7 @@ fn extract_method(
 
     let line = node_line_range(node);
     let visibility = extract_java_visibility(node, source);
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = extract_signature_to_brace(node, source);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -588,6 +631,8 @@ fn extract_method(
         name
     };
 
+    extract_annotation_refs(node, source, file_path, &full_name, references);
+
     // Extract return type reference
     if let Some(return_type) = find_child_by_field(node, "type") {
         extract_type_refs(return_type, source, file_path, Some(&full_name), references);
@@ -619,6 +664,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -637,7 +683,7 @@ fn extract_constructor(
 
     let line = node_line_range(node);
     let visibility = extract_java_visibility(node, source);
-    let _sig = extract_signature_to_brace(node, source);
+    let sig = extract_signature_to_brace(node, source);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -645,6 +691,8 @@ fn extract_constructor(
         name
     };
 
+    extract_annotation_refs(node, source, file_path, &full_name, references);
+
     // Extract parameter type references
     if let Some(params) = find_child_by_field(node, "parameters") {
         let mut cursor = params.walk();
@@ -671,6 +719,7 @@ fn extract_constructor(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -724,6 +773,7 @@ fn extract_field(
                 None,
                 None,
                 Some(visibility.clone()),
+                None,
             );
         }
     }
@@ -753,6 +803,7 @@ fn extract_import(
                 None,
                 None,
                 Some("private".to_string()),
+                None,
             );
             // Also add import reference
             references.push(ReferenceEntry {
@@ -783,6 +834,7 @@ fn extract_package(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec
                 None,
                 None,
                 Some("public".to_string()),
+                None,
             );
         }
     }
@@ -1084,4 +1136,57 @@ import java.io.File;";
         assert!(type_refs.iter().any(|r| r.name == "CustomResult"));
         assert!(type_refs.iter().any(|r| r.name == "InputData"));
     }
+
+    #[test]
+    fn test_java_supertype_and_annotation_references() {
+        let source = b"@RestController
+@RequestMapping(\"/users\")
+public class UserController extends BaseController implements Serializable, Auditable<User> {
+    @Autowired
+    public UserController(UserService service) {}
+
+    @Override
+    public String toString() { return \"\"; }
+
+    @GetMapping(\"/{id}\")
+    public User get(long id) { return null; }
+}
+
+interface Auditable<T> extends Comparable<T>, java.io.Closeable {}";
+        let (_symbols, _texts, refs) = parse_file(source, "java", "test.java").unwrap();
+
+        let refs_of = |kind: &str| -> Vec<(String, String)> {
+            refs.iter()
+                .filter(|r| r.kind == kind)
+                .map(|r| (r.name.clone(), r.caller.clone().unwrap_or_default()))
+                .collect()
+        };
+        let supertypes = refs_of("type_annotation");
+        for (name, caller) in [
+            ("BaseController", "UserController"),
+            ("Serializable", "UserController"),
+            ("Auditable", "UserController"),
+            ("Comparable", "Auditable"),
+            ("java.io.Closeable", "Auditable"),
+        ] {
+            assert!(
+                supertypes.contains(&(name.to_string(), caller.to_string())),
+                "missing {name} <- {caller}: {supertypes:?}"
+            );
+        }
+
+        let annotations = refs_of("annotation");
+        assert_eq!(
+            annotations,
+            vec![
+                ("RestController".to_string(), "UserController".to_string()),
+                ("RequestMapping".to_string(), "UserController".to_string()),
+                (
+                    "Autowired".to_string(),
+                    "UserController.UserController".to_string()
+                ),
+                ("GetMapping".to_string(), "UserController.get".to_string()),
+            ]
+        );
+    }
 }
@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -346,7 +347,7 @@ fn extract_call_ref(
     };
 
     let name = get_call_name(func, source);
-    if name.is_empty() || is_js_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_js_builtin_call) {
         return;
     }
 
@@ -375,7 +376,7 @@ fn extract_new_ref(
     };
 
     let name = get_call_name(constructor, source);
-    if name.is_empty() || is_js_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_js_builtin_call) {
         return;
     }
 
@@ -437,7 +438,7 @@ fn extract_function_decl(
     };
 
     let line = node_line_range(node);
-    let _sig = build_function_signature(node, source, &name);
+    let sig = build_function_signature(node, source, &name);
 
     let is_exported = node
         .parent()
@@ -475,6 +476,7 @@ fn extract_function_decl(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -507,7 +509,7 @@ fn extract_class(
     };
 
     // Build class signature with extends
-    let _sig = build_class_signature(node, source, &name);
+    let sig = build_class_signature(node, source, &name);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -533,7 +535,9 @@ fn extract_class(
                                 .find(|n| n.kind() == "identifier")
                         }) {
                             let super_name = node_text(superclass, source);
-                            if !super_name.is_empty() && !is_js_builtin_call(&super_name) {
+                            if !super_name.is_empty()
+                                && !is_filtered(&super_name, is_js_builtin_call)
+                            {
                                 references.push(ReferenceEntry {
                                     file: file_path.to_string(),
                                     name: super_name,
@@ -547,7 +551,7 @@ fn extract_class(
                     } else if matches!(heritage_child.kind(), "identifier" | "member_expression") {
                         // Direct identifier in heritage
                         let super_name = node_text(heritage_child, source);
-                        if !super_name.is_empty() && !is_js_builtin_call(&super_name) {
+                        if !super_name.is_empty() && !is_filtered(&super_name, is_js_builtin_call) {
                             references.push(ReferenceEntry {
                                 file: file_path.to_string(),
                                 name: super_name,
@@ -567,7 +571,7 @@ fn extract_class(
                     child.children(&mut c).find(|n| n.kind() == "identifier")
                 }) {
                     let super_name = node_text(superclass, source);
-                    if !super_name.is_empty() && !is_js_builtin_call(&super_name) {
+                    if !super_name.is_empty() && !is_filtered(&super_name, is_js_builtin_call) {
                         references.push(ReferenceEntry {
                             file: file_path.to_string(),
                             name: super_name,
@@ -597,6 +601,7 @@ fn extract_class(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 
     // Walk class body
@@ -676,7 +681,7 @@ fn extract_method(
     } else {
         format!("{} ", sig_parts.join(" "))
     };
-    let _sig = format!("{prefix}{name}{params}");
+    let sig = format!("{prefix}{name}{params}");
 
     let visibility = if name.starts_with('#') {
         "private".to_string()
@@ -706,6 +711,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 }
 
@@ -779,8 +785,16 @@ fn extract_variable_decl(
                     name
                 };
 
-                // Extract tokens from variable value (for arrow functions etc.)
-                let tokens = value_node.and_then(|v| filter_js_tokens(extract_tokens(v, source)));
+                // Extract tokens from variable value (for arrow functions etc.);
+                // constants also keep their literals
+                let tokens = value_node.and_then(|v| {
+                    let tokens = filter_js_tokens(extract_tokens(v, source));
+                    if kind == "constant" {
+                        with_literal_tokens(tokens, v, source)
+                    } else {
+                        tokens
+                    }
+                });
 
                 push_symbol(
                     symbols,
@@ -792,6 +806,7 @@ fn extract_variable_decl(
                     tokens,
                     None,
                     Some(visibility.clone()),
+                    None,
                 );
             }
         }
@@ -835,6 +850,7 @@ fn extract_import(
                             None,
                             Some(name),
                             Some("private".to_string()),
+                            None,
                         );
                         // Also add import reference
                         references.push(ReferenceEntry {
@@ -868,6 +884,7 @@ fn extract_import(
                                         None,
                                         alias,
                                         Some("private".to_string()),
+                                        None,
                                     );
                                     // Also add import reference
                                     references.push(ReferenceEntry {
@@ -904,6 +921,7 @@ fn extract_import(
                             None,
                             alias,
                             Some("private".to_string()),
+                            None,
                         );
                         // Also add import reference
                         references.push(ReferenceEntry {
@@ -1100,6 +1118,32 @@ const asyncFn = async (x) => x * 2;";
         assert_eq!(async_fn.kind, "function");
     }
 
+    #[test]
+    fn test_js_constant_literal_tokens() {
+        let long = "x".repeat(MAX_LITERAL_TOKENS_LEN * 2);
+        let source = format!(
+            "const TIMEOUT_MS = 30000;
+const RETRY = {{ backoff: 'exponential', max: 5 }};
+let timeout = 45000;
+const LONG = [1.5, \"{long}\"];"
+        );
+        let (symbols, _texts, _refs) =
+            parse_file(source.as_bytes(), "javascript", "test.js").unwrap();
+
+        let timeout = find_sym(&symbols, "TIMEOUT_MS");
+        assert_eq!(timeout.tokens.as_deref(), Some("30000"));
+
+        let retry = find_sym(&symbols, "RETRY").tokens.as_deref().unwrap();
+        assert_eq!(retry, "exponential 5");
+
+        // Only constants keep literals
+        assert!(find_sym(&symbols, "timeout").tokens.is_none());
+
+        let long = find_sym(&symbols, "LONG").tokens.as_deref().unwrap();
+        assert!(long.starts_with("1.5 xxx"));
+        assert_eq!(long.len(), MAX_LITERAL_TOKENS_LEN);
+    }
+
     #[test]
     fn test_js_imports() {
         let source = b"import React from 'react';
@@ -0,0 +1,1145 @@
+//! Kotlin symbol and text extraction.
+
+use tree_sitter::{Node, Tree};
+
+use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
+use crate::parser::helpers::*;
+use crate::parser::treesitter::MAX_DEPTH;
+
+/// Kotlin-specific stopwords (keywords and common patterns)
+const KOTLIN_STOPWORDS: &[&str] = &[
+    // Keywords
+    "val",
+    "var",
+    "fun",
+    "object",
+    "companion",
+    "data",
+    "sealed",
+    "open",
+    "override",
+    "internal",
+    "lateinit",
+    "null",
+    "is",
+    "as",
+    "when",
+    "it",
+    "init",
+    "import",
+    "package",
+    // Builtin types
+    "unit",
+    "any",
+    "nothing",
+    "int",
+    "long",
+    "short",
+    "byte",
+    "float",
+    "double",
+    "boolean",
+    "char",
+    "string",
+];
+
+/// Filter Kotlin-specific stopwords from extracted tokens.
+fn filter_kotlin_tokens(tokens: Option<String>) -> Option<String> {
+    tokens.and_then(|t| {
+        let filtered: Vec<&str> = t
+            .split_whitespace()
+            .filter(|tok| !KOTLIN_STOPWORDS.contains(&tok.to_lowercase().as_str()))
+            .collect();
+        if filtered.is_empty() {
+            None
+        } else {
+            Some(filtered.join(" "))
+        }
+    })
+}
+
+pub fn extract(
+    tree: &Tree,
+    source: &[u8],
+    file_path: &str,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let root = tree.root_node();
+    walk_node(root, source, file_path, None, symbols, texts, references, 0);
+}
+
+// ---------------------------------------------------------------------------
+// Builtin detection for filtering noisy references
+// ---------------------------------------------------------------------------
+
+/// Check if a call name is a Kotlin stdlib builtin.
+fn is_kotlin_builtin(name: &str) -> bool {
+    // Check for builtin object method calls (e.g., System.currentTimeMillis, Math.max)
+    if let Some(obj) = name.split('.').next()
+        && matches!(obj, "System" | "Math" | "Log" | "Dispatchers" | "Result")
+    {
+        return true;
+    }
+
+    // Member calls are filtered by method name (e.g., items.map, value.let)
+    let method = name.rsplit('.').next().unwrap_or(name);
+
+    matches!(
+        method,
+        // Console and preconditions
+        "println"
+        | "print"
+        | "readLine"
+        | "require"
+        | "requireNotNull"
+        | "check"
+        | "checkNotNull"
+        | "error"
+        | "assert"
+        | "TODO"
+        // Scope functions
+        | "let"
+        | "run"
+        | "with"
+        | "apply"
+        | "also"
+        | "takeIf"
+        | "takeUnless"
+        | "repeat"
+        | "lazy"
+        | "use"
+        // Collection builders
+        | "listOf"
+        | "listOfNotNull"
+        | "mutableListOf"
+        | "arrayListOf"
+        | "emptyList"
+        | "setOf"
+        | "mutableSetOf"
+        | "hashSetOf"
+        | "emptySet"
+        | "mapOf"
+        | "mutableMapOf"
+        | "hashMapOf"
+        | "emptyMap"
+        | "arrayOf"
+        | "intArrayOf"
+        | "emptyArray"
+        | "sequenceOf"
+        | "buildList"
+        | "buildMap"
+        | "buildString"
+        | "to"
+        | "Pair"
+        | "Triple"
+        // Collection methods
+        | "add"
+        | "addAll"
+        | "remove"
+        | "get"
+        | "set"
+        | "put"
+        | "contains"
+        | "containsKey"
+        | "isEmpty"
+        | "isNotEmpty"
+        | "isNullOrEmpty"
+        | "map"
+        | "mapNotNull"
+        | "filter"
+        | "filterNot"
+        | "filterNotNull"
+        | "forEach"
+        | "forEachIndexed"
+        | "first"
+        | "firstOrNull"
+        | "last"
+        | "lastOrNull"
+        | "single"
+        | "any"
+        | "all"
+        | "none"
+        | "count"
+        | "find"
+        | "flatMap"
+        | "fold"
+        | "reduce"
+        | "sorted"
+        | "sortedBy"
+        | "groupBy"
+        | "associate"
+        | "associateBy"
+        | "joinToString"
+        | "toList"
+        | "toMutableList"
+        | "toSet"
+        | "toMap"
+        | "toTypedArray"
+        | "asSequence"
+        // String and conversion methods
+        | "toString"
+        | "toInt"
+        | "toLong"
+        | "toDouble"
+        | "toFloat"
+        | "toBoolean"
+        | "format"
+        | "trim"
+        | "split"
+        | "replace"
+        | "substring"
+        | "startsWith"
+        | "endsWith"
+        | "lowercase"
+        | "uppercase"
+        | "equals"
+        | "hashCode"
+        | "copy"
+        // Common types
+        | "Any"
+        | "Unit"
+        | "String"
+        | "Int"
+        | "Long"
+        | "Short"
+        | "Byte"
+        | "Float"
+        | "Double"
+        | "Boolean"
+        | "Char"
+        | "Array"
+        | "IntArray"
+        | "StringBuilder"
+        | "Regex"
+        | "Exception"
+        | "RuntimeException"
+        | "IllegalArgumentException"
+        | "IllegalStateException"
+        | "Throwable"
+        | "Error"
+        | "Thread"
+        // Test framework
+        | "assertEquals"
+        | "assertTrue"
+        | "assertFalse"
+        | "assertNull"
+        | "assertNotNull"
+        | "assertThrows"
+        | "fail"
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn walk_node(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+    depth: usize,
+) {
+    // Prevent stack overflow on deeply nested code
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    let kind = node.kind();
+
+    match kind {
+        "class_declaration" => {
+            extract_class(
+                node, source, file_path, parent_ctx, symbols, texts, references, depth,
+            );
+            return;
+        }
+        "object_declaration" => {
+            extract_object(
+                node, source, file_path, parent_ctx, symbols, texts, references, depth,
+            );
+            return;
+        }
+        "companion_object" => {
+            extract_companion(
+                node, source, file_path, parent_ctx, symbols, texts, references, depth,
+            );
+            return;
+        }
+        "function_declaration" => {
+            extract_function(
+                node, source, file_path, parent_ctx, symbols, texts, references, depth,
+            );
+            return;
+        }
+        "property_declaration" => {
+            extract_property(node, source, file_path, parent_ctx, symbols);
+        }
+        "type_alias" => {
+            extract_type_alias(node, source, file_path, parent_ctx, symbols);
+        }
+        "import" => {
+            extract_import(node, source, file_path, symbols, references);
+            return;
+        }
+        "package_header" => {
+            extract_package(node, source, file_path, symbols);
+            return;
+        }
+        "line_comment" | "block_comment" => {
+            extract_comment(node, source, file_path, parent_ctx, texts);
+            return;
+        }
+        "string_literal" | "multiline_string_literal" => {
+            extract_string(node, source, file_path, parent_ctx, texts);
+            return;
+        }
+
+        // --- Reference extraction ---
+        "call_expression" => {
+            extract_call_ref(node, source, file_path, parent_ctx, references);
+        }
+
+        _ => {}
+    }
+
+    // Recurse
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk_node(
+            child,
+            source,
+            file_path,
+            parent_ctx,
+            symbols,
+            texts,
+            references,
+            depth + 1,
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Reference extraction
+// ---------------------------------------------------------------------------
+
+/// Extract a call reference.
+///
+/// Kotlin has no `new` keyword, so a call whose callee name is capitalized
+/// (e.g. `User("a")`, `Outer.Inner()`) is recorded as an instantiation.
+fn extract_call_ref(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let callee = match node.named_child(0) {
+        Some(c) => c,
+        None => return,
+    };
+
+    let name = get_call_name(callee, source);
+    if name.is_empty() || is_filtered(&name, is_kotlin_builtin) {
+        return;
+    }
+
+    let is_constructor = name
+        .rsplit('.')
+        .next()
+        .and_then(|last| last.chars().next())
+        .is_some_and(|c| c.is_uppercase());
+    let kind = if is_constructor {
+        "instantiation"
+    } else {
+        "call"
+    };
+
+    let line = node_line_range(node);
+    references.push(ReferenceEntry {
+        file: file_path.to_string(),
+        name,
+        kind: kind.to_string(),
+        line,
+        caller: parent_ctx.map(String::from),
+        project: String::new(),
+    });
+}
+
+/// Get the name of a call's callee expression.
+fn get_call_name(node: Node, source: &[u8]) -> String {
+    match node.kind() {
+        "identifier" => node_text(node, source),
+        "navigation_expression" => {
+            // receiver.method — keep the receiver only when it is a plain name path
+            let method = match node.named_child(node.named_child_count().saturating_sub(1) as u32) {
+                Some(m) if m.kind() == "identifier" => node_text(m, source),
+                _ => return String::new(),
+            };
+            match node.named_child(0) {
+                Some(r) if is_name_path(r) => format!("{}.{}", node_text(r, source), method),
+                _ => method,
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// Whether a node is a dotted chain of identifiers (`a`, `a.b.c`).
+fn is_name_path(node: Node) -> bool {
+    match node.kind() {
+        "identifier" => true,
+        "navigation_expression" => {
+            let mut cursor = node.walk();
+            node.named_children(&mut cursor).all(is_name_path)
+        }
+        _ => false,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Symbol extraction
+// ---------------------------------------------------------------------------
+
+#[allow(clippy::too_many_arguments)]
+fn extract_class(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+    depth: usize,
+) {
+    let name = match find_child_by_field(node, "name") {
+        Some(n) => node_text(n, source),
+        None => return,
+    };
+
+    let line = node_line_range(node);
+    let visibility = extract_kotlin_visibility(node, source);
+    let sig = build_signature(node, source);
+
+    let is_interface = has_child_kind(node, "interface");
+    let kind = if is_interface {
+        "interface"
+    } else if has_modifier(node, source, "enum") {
+        "enum"
+    } else if has_modifier(node, source, "annotation") {
+        "annotation"
+    } else {
+        "class"
+    };
+
+    let full_name = if let Some(parent) = parent_ctx {
+        format!("{parent}.{name}")
+    } else {
+        name
+    };
+
+    // Extract tokens from class body
+    let tokens = find_child_of_kind(node, &["class_body", "enum_class_body"])
+        .and_then(|body| filter_kotlin_tokens(extract_tokens(body, source)));
+
+    push_symbol(
+        symbols,
+        file_path,
+        full_name.clone(),
+        kind,
+        line,
+        parent_ctx,
+        tokens,
+        None,
+        Some(visibility),
+        Some(sig),
+    );
+
+    // Primary constructor `val`/`var` parameters are properties of the class
+    if let Some(params) = find_child_of_kind(node, &["primary_constructor"])
+        .and_then(|ctor| find_child_of_kind(ctor, &["class_parameters"]))
+    {
+        let mut cursor = params.walk();
+        for param in params.children(&mut cursor) {
+            if param.kind() != "class_parameter"
+                || !(has_child_kind(param, "val") || has_child_kind(param, "var"))
+            {
+                continue;
+            }
+            if let Some(name_node) = find_child_of_kind(param, &["identifier"]) {
+                push_symbol(
+                    symbols,
+                    file_path,
+                    format!("{full_name}.{}", node_text(name_node, source)),
+                    "property",
+                    node_line_range(param),
+                    Some(&full_name),
+                    None,
+                    None,
+                    Some(extract_kotlin_visibility(param, source)),
+                    Some(collapse_whitespace(node_text(param, source).trim())),
+                );
+            }
+        }
+    }
+
+    // Walk the class (constructor defaults, delegation, body) with the class as context
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk_node(
+            child,
+            source,
+            file_path,
+            Some(&full_name),
+            symbols,
+            texts,
+            references,
+            depth + 1,
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn extract_object(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+    depth: usize,
+) {
+    let name = match find_child_by_field(node, "name") {
+        Some(n) => node_text(n, source),
+        None => return,
+    };
+
+    let line = node_line_range(node);
+    let visibility = extract_kotlin_visibility(node, source);
+    let sig = build_signature(node, source);
+
+    let full_name = if let Some(parent) = parent_ctx {
+        format!("{parent}.{name}")
+    } else {
+        name
+    };
+
+    let tokens = find_child_of_kind(node, &["class_body"])
+        .and_then(|body| filter_kotlin_tokens(extract_tokens(body, source)));
+
+    push_symbol(
+        symbols,
+        file_path,
+        full_name.clone(),
+        "object",
+        line,
+        parent_ctx,
+        tokens,
+        None,
+        Some(visibility),
+        Some(sig),
+    );
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk_node(
+            child,
+            source,
+            file_path,
+            Some(&full_name),
+            symbols,
+            texts,
+            references,
+            depth + 1,
+        );
+    }
+}
+
+/// Extract a companion object.
+///
+/// The companion itself is recorded as `Class.Companion` (or its explicit
+/// name), but its members are walked with the enclosing class as context so
+/// that `Class.create()` shows up as a child of `Class`.
+#[allow(clippy::too_many_arguments)]
+fn extract_companion(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+    depth: usize,
+) {
+    let name = find_child_by_field(node, "name")
+        .map(|n| node_text(n, source))
+        .unwrap_or_else(|| "Companion".to_string());
+
+    let line = node_line_range(node);
+    let visibility = extract_kotlin_visibility(node, source);
+    let sig = build_signature(node, source);
+
+    let full_name = if let Some(parent) = parent_ctx {
+        format!("{parent}.{name}")
+    } else {
+        name
+    };
+
+    let tokens = find_child_of_kind(node, &["class_body"])
+        .and_then(|body| filter_kotlin_tokens(extract_tokens(body, source)));
+
+    push_symbol(
+        symbols,
+        file_path,
+        full_name,
+        "object",
+        line,
+        parent_ctx,
+        tokens,
+        None,
+        Some(visibility),
+        Some(sig),
+    );
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk_node(
+            child,
+            source,
+            file_path,
+            parent_ctx,
+            symbols,
+            texts,
+            references,
+            depth + 1,
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn extract_function(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    texts: &mut Vec<TextEntry>,
+    references: &mut Vec<ReferenceEntry>,
+    depth: usize,
+) {
+    let name = match find_child_by_field(node, "name") {
+        Some(n) => node_text(n, source),
+        None => return,
+    };
+
+    let line = node_line_range(node);
+    let visibility = extract_kotlin_visibility(node, source);
+    let sig = build_signature(node, source);
+
+    let kind = if is_member(node) {
+        "method"
+    } else {
+        "function"
+    };
+
+    let full_name = if let Some(parent) = parent_ctx {
+        format!("{parent}.{name}")
+    } else {
+        name
+    };
+
+    // Extract tokens from function body
+    let tokens = find_child_of_kind(node, &["function_body"])
+        .and_then(|body| filter_kotlin_tokens(extract_tokens(body, source)));
+
+    push_symbol(
+        symbols,
+        file_path,
+        full_name.clone(),
+        kind,
+        line,
+        parent_ctx,
+        tokens,
+        None,
+        Some(visibility),
+        Some(sig),
+    );
+
+    // Recurse into parameters and body with function name as context
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        walk_node(
+            child,
+            source,
+            file_path,
+            Some(&full_name),
+            symbols,
+            texts,
+            references,
+            depth + 1,
+        );
+    }
+}
+
+fn extract_property(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+) {
+    // Local `val`/`var` inside function bodies are not symbols
+    let is_top_level = node.parent().is_some_and(|p| p.kind() == "source_file");
+    if !is_top_level && !is_member(node) {
+        return;
+    }
+
+    let line = node_line_range(node);
+    let visibility = extract_kotlin_visibility(node, source);
+    let sig = build_signature(node, source);
+
+    let kind = if has_modifier(node, source, "const") {
+        "constant"
+    } else if is_top_level {
+        "variable"
+    } else {
+        "property"
+    };
+
+    // `val x` or destructuring `val (a, b)`
+    let mut declarations = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "variable_declaration" => declarations.push(child),
+            "multi_variable_declaration" => {
+                let mut inner = child.walk();
+                declarations.extend(
+                    child
+                        .children(&mut inner)
+                        .filter(|c| c.kind() == "variable_declaration"),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for decl in declarations {
+        let Some(name_node) = find_child_of_kind(decl, &["identifier"]) else {
+            continue;
+        };
+        let name = node_text(name_node, source);
+
+        let full_name = if let Some(parent) = parent_ctx {
+            format!("{parent}.{name}")
+        } else {
+            name
+        };
+
+        push_symbol(
+            symbols,
+            file_path,
+            full_name,
+            kind,
+            line,
+            parent_ctx,
+            None,
+            None,
+            Some(visibility.clone()),
+            Some(sig.clone()),
+        );
+    }
+}
+
+fn extract_type_alias(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+) {
+    let name = match find_child_by_field(node, "type") {
+        Some(n) => node_text(n, source),
+        None => return,
+    };
+
+    let full_name = if let Some(parent) = parent_ctx {
+        format!("{parent}.{name}")
+    } else {
+        name
+    };
+
+    push_symbol(
+        symbols,
+        file_path,
+        full_name,
+        "type_alias",
+        node_line_range(node),
+        parent_ctx,
+        None,
+        None,
+        Some(extract_kotlin_visibility(node, source)),
+        Some(collapse_whitespace(node_text(node, source).trim())),
+    );
+}
+
+fn extract_import(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    symbols: &mut Vec<SymbolEntry>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let line = node_line_range(node);
+
+    let name = match find_child_of_kind(node, &["qualified_identifier", "identifier"]) {
+        Some(n) => node_text(n, source),
+        None => return,
+    };
+
+    // `import a.b.C as D` — the alias is the identifier after `as`
+    let mut alias = None;
+    let mut after_as = false;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "as" => after_as = true,
+            "identifier" if after_as => alias = Some(node_text(child, source)),
+            _ => {}
+        }
+    }
+
+    push_symbol(
+        symbols,
+        file_path,
+        name.clone(),
+        "import",
+        line,
+        None,
+        None,
+        alias,
+        Some("private".to_string()),
+        None,
+    );
+    references.push(ReferenceEntry {
+        file: file_path.to_string(),
+        name,
+        kind: "import".to_string(),
+        line,
+        caller: None,
+        project: String::new(),
+    });
+}
+
+fn extract_package(node: Node, source: &[u8], file_path: &str, symbols: &mut Vec<SymbolEntry>) {
+    if let Some(name_node) = find_child_of_kind(node, &["qualified_identifier", "identifier"]) {
+        push_symbol(
+            symbols,
+            file_path,
+            node_text(name_node, source),
+            "module",
+            node_line_range(node),
+            None,
+            None,
+            None,
+            Some("public".to_string()),
+            None,
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Map Kotlin visibility modifiers. Declarations are public by default;
+/// `protected` is reported as `internal` like in the Java extractor.
+fn extract_kotlin_visibility(node: Node, source: &[u8]) -> String {
+    let Some(modifiers) = find_child_of_kind(node, &["modifiers"]) else {
+        return "public".to_string();
+    };
+    let mut cursor = modifiers.walk();
+    for child in modifiers.children(&mut cursor) {
+        if child.kind() == "visibility_modifier" {
+            return match node_text(child, source).as_str() {
+                "private" => "private",
+                "internal" | "protected" => "internal",
+                _ => "public",
+            }
+            .to_string();
+        }
+    }
+    "public".to_string()
+}
+
+/// Whether the declaration's `modifiers` contain the given keyword.
+fn has_modifier(node: Node, source: &[u8], modifier: &str) -> bool {
+    let Some(modifiers) = find_child_of_kind(node, &["modifiers"]) else {
+        return false;
+    };
+    let mut cursor = modifiers.walk();
+    modifiers
+        .children(&mut cursor)
+        .any(|c| c.kind() != "annotation" && node_text(c, source) == modifier)
+}
+
+/// Whether a declaration sits directly in a class, object or companion body.
+fn is_member(node: Node) -> bool {
+    node.parent()
+        .is_some_and(|p| matches!(p.kind(), "class_body" | "enum_class_body"))
+}
+
+fn has_child_kind(node: Node, kind: &str) -> bool {
+    find_child_of_kind(node, &[kind]).is_some()
+}
+
+fn find_child_of_kind<'a>(node: Node<'a>, kinds: &[&str]) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .find(|c| kinds.contains(&c.kind()))
+}
+
+/// Build a declaration signature: modifiers without annotations, followed by
+/// the header up to the body, initializer, or accessor.
+fn build_signature(node: Node, source: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut header_start = None;
+    let mut header_end = node.end_byte();
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "modifiers" => {
+                let mut inner = child.walk();
+                parts.extend(
+                    child
+                        .children(&mut inner)
+                        .filter(|m| m.kind() != "annotation")
+                        .map(|m| node_text(m, source)),
+                );
+            }
+            "class_body" | "enum_class_body" | "function_body" | "=" | "property_delegate"
+            | "getter" | "setter" => {
+                header_end = child.start_byte();
+                break;
+            }
+            _ => {
+                header_start.get_or_insert(child.start_byte());
+            }
+        }
+    }
+
+    if let Some(start) = header_start
+        && let Some(header) = source.get(start..header_end)
+    {
+        parts.push(String::from_utf8_lossy(header).trim().to_string());
+    }
+    collapse_whitespace(&parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::treesitter::parse_file;
+
+    fn find_sym<'a>(symbols: &'a [SymbolEntry], name: &str) -> &'a SymbolEntry {
+        symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("symbol not found: {name}"))
+    }
+
+    #[test]
+    fn test_kotlin_data_class() {
+        let source = b"data class User(val name: String, private val age: Int, note: String) {
+    internal var count: Int = 0
+
+    fun greet(other: User): String {
+        val local = other.name
+        return local
+    }
+}";
+        let (symbols, _texts, _refs) = parse_file(source, "kotlin", "User.kt").unwrap();
+
+        let user = find_sym(&symbols, "User");
+        assert_eq!(user.kind, "class");
+        assert_eq!(user.visibility.as_deref(), Some("public"));
+        assert_eq!(
+            user.sig.as_deref(),
+            Some("data class User(val name: String, private val age: Int, note: String)")
+        );
+
+        let name = find_sym(&symbols, "User.name");
+        assert_eq!(name.kind, "property");
+        assert_eq!(name.parent.as_deref(), Some("User"));
+        assert_eq!(name.visibility.as_deref(), Some("public"));
+        assert_eq!(
+            find_sym(&symbols, "User.age").visibility.as_deref(),
+            Some("private")
+        );
+        // Plain constructor parameters are not properties
+        assert!(!symbols.iter().any(|s| s.name == "User.note"));
+
+        let count = find_sym(&symbols, "User.count");
+        assert_eq!(count.kind, "property");
+        assert_eq!(count.visibility.as_deref(), Some("internal"));
+        assert_eq!(count.sig.as_deref(), Some("internal var count: Int"));
+
+        let greet = find_sym(&symbols, "User.greet");
+        assert_eq!(greet.kind, "method");
+        assert_eq!(greet.sig.as_deref(), Some("fun greet(other: User): String"));
+
+        // Locals are not symbols
+        assert!(!symbols.iter().any(|s| s.name.ends_with("local")));
+    }
+
+    #[test]
+    fn test_kotlin_companion_members_belong_to_class() {
+        let source = b"class Repo {
+    companion object Factory {
+        const val MAX = 10
+        fun create(): Repo = Repo()
+    }
+}
+
+class Cache {
+    companion object {
+        fun empty() = Cache()
+    }
+}";
+        let (symbols, _texts, _refs) = parse_file(source, "kotlin", "Repo.kt").unwrap();
+
+        let factory = find_sym(&symbols, "Repo.Factory");
+        assert_eq!(factory.kind, "object");
+        assert_eq!(factory.parent.as_deref(), Some("Repo"));
+
+        let create = find_sym(&symbols, "Repo.create");
+        assert_eq!(create.kind, "method");
+        assert_eq!(create.parent.as_deref(), Some("Repo"));
+
+        let max = find_sym(&symbols, "Repo.MAX");
+        assert_eq!(max.kind, "constant");
+        assert_eq!(max.parent.as_deref(), Some("Repo"));
+
+        assert_eq!(find_sym(&symbols, "Cache.Companion").kind, "object");
+        assert_eq!(
+            find_sym(&symbols, "Cache.empty").parent.as_deref(),
+            Some("Cache")
+        );
+    }
+
+    #[test]
+    fn test_kotlin_objects_and_top_level() {
+        let source = b"package com.example.app
+
+object Registry {
+    private fun register(x: Any) {}
+}
+
+interface Named { val name: String }
+enum class Color { RED, GREEN }
+sealed class Shape
+internal fun helper(a: Int): Int = a + 1
+val CONFIG = 1
+typealias Handler = (Int) -> Unit
+@Deprecated(\"old\") fun legacy() {}";
+        let (symbols, _texts, _refs) = parse_file(source, "kotlin", "App.kt").unwrap();
+
+        let pkg = symbols.iter().find(|s| s.kind == "module").unwrap();
+        assert_eq!(pkg.name, "com.example.app");
+
+        assert_eq!(find_sym(&symbols, "Registry").kind, "object");
+        let register = find_sym(&symbols, "Registry.register");
+        assert_eq!(register.kind, "method");
+        assert_eq!(register.visibility.as_deref(), Some("private"));
+
+        assert_eq!(find_sym(&symbols, "Named").kind, "interface");
+        assert_eq!(find_sym(&symbols, "Named.name").kind, "property");
+        assert_eq!(find_sym(&symbols, "Color").kind, "enum");
+        assert_eq!(find_sym(&symbols, "Shape").kind, "class");
+
+        let helper = find_sym(&symbols, "helper");
+        assert_eq!(helper.kind, "function");
+        assert_eq!(helper.visibility.as_deref(), Some("internal"));
+        assert_eq!(
+            helper.sig.as_deref(),
+            Some("internal fun helper(a: Int): Int")
+        );
+
+        assert_eq!(find_sym(&symbols, "CONFIG").kind, "variable");
+        assert_eq!(find_sym(&symbols, "Handler").kind, "type_alias");
+        // Annotations are left out of signatures
+        assert_eq!(
+            find_sym(&symbols, "legacy").sig.as_deref(),
+            Some("fun legacy()")
+        );
+    }
+
+    #[test]
+    fn test_kotlin_imports() {
+        let source = b"import kotlin.math.max
+import com.foo.Bar as Baz
+import com.foo.util.*";
+        let (symbols, _texts, refs) = parse_file(source, "kotlin", "App.kt").unwrap();
+
+        let max = find_sym(&symbols, "kotlin.math.max");
+        assert_eq!(max.kind, "import");
+        assert_eq!(
+            find_sym(&symbols, "com.foo.Bar").alias.as_deref(),
+            Some("Baz")
+        );
+        assert_eq!(find_sym(&symbols, "com.foo.util").kind, "import");
+
+        let imports: Vec<_> = refs.iter().filter(|r| r.kind == "import").collect();
+        assert_eq!(imports.len(), 3);
+    }
+
+    #[test]
+    fn test_kotlin_call_references() {
+        let source = b"class Service {
+    fun run() {
+        println(\"start\")
+        val user = User(\"a\")
+        repository.save(user)
+        process(user)
+        items.map { it.id }
+    }
+}";
+        let (_symbols, _texts, refs) = parse_file(source, "kotlin", "Service.kt").unwrap();
+
+        let calls: Vec<_> = refs.iter().filter(|r| r.kind == "call").collect();
+        assert!(calls.iter().any(|r| r.name == "repository.save"));
+        assert!(
+            calls
+                .iter()
+                .any(|r| r.name == "process" && r.caller.as_deref() == Some("Service.run"))
+        );
+        // Stdlib builtins are filtered
+        assert!(!refs.iter().any(|r| r.name == "println"));
+        assert!(!refs.iter().any(|r| r.name.ends_with("map")));
+
+        let instantiations: Vec<_> = refs.iter().filter(|r| r.kind == "instantiation").collect();
+        assert!(instantiations.iter().any(|r| r.name == "User"));
+    }
+
+    #[test]
+    fn test_kotlin_comments_and_strings() {
+        let source = b"/** KDoc for the service */
+class Documented {
+    // TODO: tidy up
+    val greeting = \"Hello, world\"
+}";
+        let (_symbols, texts, _refs) = parse_file(source, "kotlin", "Doc.kt").unwrap();
+        assert!(texts.iter().any(|t| t.kind == "docstring"));
+        assert!(texts.iter().any(|t| t.kind == "comment"));
+        assert!(
+            texts
+                .iter()
+                .any(|t| t.kind == "string" && t.text == "Hello, world")
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use anyhow::Result;
 use tree_sitter::Language;
 
@@ -40,6 +42,9 @@ pub fn get_language(name: &str) -> Result<Language> {
         #[cfg(feature = "lang-csharp")]
         "csharp" => Ok(tree_sitter_c_sharp::LANGUAGE.into()),
 
+        #[cfg(feature = "lang-kotlin")]
+        "kotlin" => Ok(tree_sitter_kotlin_ng::LANGUAGE.into()),
+
         // Markdown uses custom parser (tree-sitter-md) with MarkdownParser,
         // but we still register the block language for consistency
         #[cfg(feature = "lang-markdown")]
@@ -63,6 +68,7 @@ pub fn detect_language(extension: &str) -> Option<&'static str> {
         "cpp" | "cc" | "cxx" | "hpp" | "hxx" | "hh" | "h++" => Some("cpp"),
         "rb" | "rake" | "gemspec" => Some("ruby"),
         "cs" => Some("csharp"),
+        "kt" | "kts" => Some("kotlin"),
         "html" | "htm" => Some("html"),
         "vue" => Some("vue"),
         "svelte" => Some("svelte"),
@@ -71,3 +77,115 @@ pub fn detect_language(extension: &str) -> Option<&'static str> {
         _ => None,
     }
 }
+
+/// Languages detected only to label files (explore, stats); they have no
+/// grammar, so their files are recorded without symbols.
+const LABEL_ONLY_LANGUAGES: &[&str] = &["dockerfile", "make", "cmake"];
+
+/// Whether files of `language` go through [`crate::parser::treesitter::parse_file`].
+pub fn is_parsed_language(language: &str) -> bool {
+    !LABEL_ONLY_LANGUAGES.contains(&language)
+}
+
+/// Languages whose extraction reads the file's path, not just its content:
+/// Rust `mod foo;` imports name the file expected next to the declaring one.
+const PATH_DEPENDENT_LANGUAGES: &[&str] = &["rust"];
+
+/// Whether two files of `language` with the same content extract the same
+/// results wherever they live, so one can take a copy of the other's.
+pub fn is_path_independent(language: &str) -> bool {
+    !PATH_DEPENDENT_LANGUAGES.contains(&language)
+}
+
+/// Detect the language of a file from its name, extension, and leading bytes.
+///
+/// Well-known basenames come first (`Dockerfile`, `Makefile`, `CMakeLists.txt`,
+/// `Rakefile`, `Gemfile`, ...), then the extension (see [`detect_language`]).
+/// A file matching neither is sniffed for a `#!` line naming a Python, Ruby or
+/// Node interpreter (`#!/usr/bin/env python3`). `head` may be the whole
+/// content or just its first bytes.
+pub fn detect_language_for_path(path: &Path, head: &[u8]) -> Option<&'static str> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if let Some(lang) = detect_language_by_name(name) {
+        return Some(lang);
+    }
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => detect_language(ext),
+        None => detect_language_by_shebang(head),
+    }
+}
+
+/// Detect the language of a file from well-known basenames.
+fn detect_language_by_name(name: &str) -> Option<&'static str> {
+    match name {
+        "Dockerfile" | "Containerfile" => Some("dockerfile"),
+        _ if name.starts_with("Dockerfile.") => Some("dockerfile"),
+        "Makefile" | "makefile" | "GNUmakefile" => Some("make"),
+        "CMakeLists.txt" => Some("cmake"),
+        "Rakefile" | "Gemfile" | "Guardfile" | "Podfile" | "Vagrantfile" | "Brewfile"
+        | "Fastfile" => Some("ruby"),
+        "SConstruct" | "SConscript" => Some("python"),
+        _ => None,
+    }
+}
+
+/// Detect the language of a script from its `#!` line.
+fn detect_language_by_shebang(head: &[u8]) -> Option<&'static str> {
+    let line = head.strip_prefix(b"#!")?;
+    let line = line.split(|&b| b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?;
+
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        // `#!/usr/bin/env -S python3 -u`: the first non-flag word is the interpreter
+        interpreter = words.find(|w| !w.starts_with('-'))?;
+    }
+
+    match interpreter {
+        _ if interpreter.starts_with("python") => Some("python"),
+        "ruby" | "jruby" => Some("ruby"),
+        "node" | "nodejs" | "deno" | "bun" => Some("javascript"),
+        "ts-node" | "tsx" => Some("typescript"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_for_path_by_name() {
+        let detect = |path: &str| detect_language_for_path(Path::new(path), b"");
+        assert_eq!(detect("Dockerfile"), Some("dockerfile"));
+        assert_eq!(detect("docker/Dockerfile.dev"), Some("dockerfile"));
+        assert_eq!(detect("Makefile"), Some("make"));
+        assert_eq!(detect("src/CMakeLists.txt"), Some("cmake"));
+        assert_eq!(detect("Gemfile"), Some("ruby"));
+        assert_eq!(detect("notes.txt"), None);
+        assert_eq!(detect("src/main.rs"), Some("rust"));
+        assert!(!is_parsed_language("make"));
+        assert!(is_parsed_language("ruby"));
+    }
+
+    #[test]
+    fn test_detect_language_for_path_by_shebang() {
+        let detect = |head: &str| detect_language_for_path(Path::new("bin/tool"), head.as_bytes());
+        assert_eq!(
+            detect("#!/usr/bin/env python3\nimport sys\n"),
+            Some("python")
+        );
+        assert_eq!(detect("#!/usr/bin/python3.12"), Some("python"));
+        assert_eq!(detect("#!/usr/bin/env -S ruby -w\n"), Some("ruby"));
+        assert_eq!(detect("#!/usr/bin/env node\n"), Some("javascript"));
+        assert_eq!(detect("#!/bin/sh\n"), None);
+        assert_eq!(detect("print('no shebang')\n"), None);
+
+        // An extension wins over the shebang
+        assert_eq!(
+            detect_language_for_path(Path::new("run.rb"), b"#!/usr/bin/env python3\n"),
+            Some("ruby")
+        );
+    }
+}
@@ -103,6 +103,7 @@ fn extract_atx_heading(
         None,
         None,
         None,
+        None,
     );
 }
 
@@ -134,6 +135,7 @@ fn extract_setext_heading(
         None,
         None,
         None,
+        None,
     );
 }
 
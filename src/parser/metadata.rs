@@ -3,6 +3,7 @@
 //! Extracts file-level metadata from source code for improved search relevance.
 //! Each language has specific conventions for documenting files:
 //! - Markdown: YAML frontmatter or first heading/paragraph
+//! - Plain text (`.txt`, `.rst`, ...): YAML frontmatter only
 //! - Python: Module docstring
 //! - Rust: `//!` module docs
 //! - JavaScript/TypeScript: Top-level JSDoc
@@ -12,10 +13,10 @@
 //! - Ruby: Top file comment
 //! - C#: Top-level XML doc comment
 
-use tree_sitter::{Parser, Tree};
+use tree_sitter::Tree;
 
 use crate::parser::helpers::{node_text, strip_string_quotes};
-use crate::parser::languages::get_language;
+use crate::parser::treesitter::parse_tree;
 
 /// File metadata: title and description.
 #[derive(Debug, Clone, Default)]
@@ -76,10 +77,35 @@ pub fn extract_file_metadata(source: &[u8], language: &str) -> FileMetadata {
         _ => FileMetadata::default(),
     };
 
-    // Post-process: truncate description to first sentence/line
+    truncate_metadata(result)
+}
+
+/// Extensions of plain-text files whose frontmatter is read (they have no parser).
+const TEXT_EXTENSIONS: &[&str] = &["txt", "text", "rst", "adoc"];
+
+/// Extract file metadata of a file without a language: the YAML frontmatter
+/// of plain-text files (see `TEXT_EXTENSIONS`), nothing for anything else.
+pub fn extract_text_file_metadata(source: &[u8], path: &str) -> FileMetadata {
+    let is_text = std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| TEXT_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    if !is_text {
+        return FileMetadata::default();
+    }
+    let meta = std::str::from_utf8(source)
+        .ok()
+        .and_then(extract_yaml_frontmatter)
+        .map(|(meta, _)| meta)
+        .unwrap_or_default();
+    truncate_metadata(meta)
+}
+
+/// Post-process: truncate title to the first line, description to the first sentence/line.
+fn truncate_metadata(meta: FileMetadata) -> FileMetadata {
     FileMetadata {
-        title: result.title.map(|t| truncate_to_line(&t)),
-        description: result.description.map(|d| truncate_to_sentence(&d)),
+        title: meta.title.map(|t| truncate_to_line(&t)),
+        description: meta.description.map(|d| truncate_to_sentence(&d)),
     }
 }
 
@@ -88,19 +114,9 @@ fn extract_with_tree<F>(source: &[u8], language: &str, extractor: F) -> FileMeta
 where
     F: FnOnce(&Tree, &[u8]) -> FileMetadata,
 {
-    let lang = match get_language(language) {
-        Ok(l) => l,
-        Err(_) => return FileMetadata::default(),
-    };
-
-    let mut parser = Parser::new();
-    if parser.set_language(&lang).is_err() {
-        return FileMetadata::default();
-    }
-
-    match parser.parse(source, None) {
-        Some(tree) => extractor(&tree, source),
-        None => FileMetadata::default(),
+    match parse_tree(language, source) {
+        Ok(Some(tree)) => extractor(&tree, source),
+        Ok(None) | Err(_) => FileMetadata::default(),
     }
 }
 
@@ -148,14 +164,18 @@ fn extract_markdown_metadata(source: &[u8]) -> FileMetadata {
         Err(_) => return FileMetadata::default(),
     };
 
-    // Try YAML frontmatter first
-    if let Some(meta) = extract_yaml_frontmatter(text)
-        && !meta.is_empty()
-    {
-        return meta;
-    }
+    // YAML frontmatter keys win; the body fills in whichever is missing
+    let (front, body) = extract_yaml_frontmatter(text).unwrap_or_default();
+    let fallback = extract_markdown_body_metadata(if front.is_empty() { text } else { body });
+    FileMetadata::new(
+        front.title.or(fallback.title),
+        front.description.or(fallback.description),
+    )
+}
 
-    // Fallback: first heading as title, first paragraph as description
+/// First heading as title, first paragraph after it as description.
+#[cfg(feature = "lang-markdown")]
+fn extract_markdown_body_metadata(text: &str) -> FileMetadata {
     let mut title = None;
     let mut description = None;
     let mut in_code_block = false;
@@ -198,22 +218,36 @@ fn extract_markdown_metadata(source: &[u8]) -> FileMetadata {
     FileMetadata::new(title, description)
 }
 
-#[cfg(feature = "lang-markdown")]
-fn extract_yaml_frontmatter(text: &str) -> Option<FileMetadata> {
-    // YAML frontmatter starts with --- on first line
-    if !text.starts_with("---") {
+/// Parse the `title:`/`description:` keys of a leading YAML frontmatter block
+/// (`---` on the first line up to the next `---` line).
+///
+/// Returns the metadata and the text after the block; None without frontmatter.
+fn extract_yaml_frontmatter(text: &str) -> Option<(FileMetadata, &str)> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let (first, mut rest) = text.split_once('\n')?;
+    if first.trim_end() != "---" {
         return None;
     }
 
-    // Find closing ---
-    let content = text.get(3..)?;
-    let end_idx = content.find("\n---")?;
-    let yaml_content = content.get(..end_idx)?;
+    // Find the closing --- line
+    let mut yaml_lines = Vec::new();
+    loop {
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        if line.trim_end() == "---" {
+            rest = next;
+            break;
+        }
+        if next.is_empty() {
+            return None;
+        }
+        yaml_lines.push(line);
+        rest = next;
+    }
 
     let mut title = None;
     let mut description = None;
 
-    for line in yaml_content.lines() {
+    for line in yaml_lines {
         let trimmed = line.trim();
         if let Some(value) = trimmed.strip_prefix("title:") {
             let value = value.trim().trim_matches('"').trim_matches('\'');
@@ -228,7 +262,7 @@ fn extract_yaml_frontmatter(text: &str) -> Option<FileMetadata> {
         }
     }
 
-    Some(FileMetadata::new(title, description))
+    Some((FileMetadata::new(title, description), rest))
 }
 
 // ---------------------------------------------------------------------------
@@ -713,13 +747,55 @@ mod tests {
         assert_eq!(result, "First line\nSecond line\nThird");
     }
 
-    #[cfg(feature = "lang-markdown")]
     #[test]
     fn test_yaml_frontmatter() {
         let text = "---\ntitle: My Title\ndescription: My description\n---\n# Heading";
-        let meta = extract_yaml_frontmatter(text).unwrap();
+        let (meta, body) = extract_yaml_frontmatter(text).unwrap();
         assert_eq!(meta.title, Some("My Title".to_string()));
         assert_eq!(meta.description, Some("My description".to_string()));
+        assert_eq!(body, "# Heading");
+
+        // Must open on the first line and be closed
+        assert!(extract_yaml_frontmatter("# Heading\n---\ntitle: x\n---\n").is_none());
+        assert!(extract_yaml_frontmatter("---\ntitle: x\nno end").is_none());
+        assert!(extract_yaml_frontmatter("----\ntitle: x\n----\n").is_none());
+        // CRLF line endings
+        let (meta, _) = extract_yaml_frontmatter("---\r\ntitle: 'Quoted'\r\n---\r\n").unwrap();
+        assert_eq!(meta.title, Some("Quoted".to_string()));
+    }
+
+    #[cfg(feature = "lang-markdown")]
+    #[test]
+    fn test_markdown_frontmatter_overrides_heading() {
+        let source =
+            b"---\ntitle: Front Title\ndescription: From the front.\n---\n# Heading\n\nBody text.";
+        let meta = extract_file_metadata(source, "markdown");
+        assert_eq!(meta.title, Some("Front Title".to_string()));
+        assert_eq!(meta.description, Some("From the front.".to_string()));
+
+        // A key missing from the frontmatter comes from the body
+        let source = b"---\ntitle: Front Title\ntags: [a]\n---\n# Heading\n\nBody text.";
+        let meta = extract_file_metadata(source, "markdown");
+        assert_eq!(meta.title, Some("Front Title".to_string()));
+        assert_eq!(meta.description, Some("Body text.".to_string()));
+
+        // Frontmatter without either key: heading and paragraph of the body
+        let source = b"---\ntags: [a]\n---\n# Heading\n\nBody text.";
+        let meta = extract_file_metadata(source, "markdown");
+        assert_eq!(meta.title, Some("Heading".to_string()));
+        assert_eq!(meta.description, Some("Body text.".to_string()));
+    }
+
+    #[test]
+    fn test_text_file_frontmatter() {
+        let source = b"---\ntitle: Release notes\ndescription: What changed. And why.\n---\nv1.0\n";
+        let meta = extract_text_file_metadata(source, "docs/NOTES.txt");
+        assert_eq!(meta.title, Some("Release notes".to_string()));
+        assert_eq!(meta.description, Some("What changed.".to_string()));
+
+        // No frontmatter, or not a text file: nothing
+        assert!(extract_text_file_metadata(b"v1.0\n", "NOTES.txt").is_empty());
+        assert!(extract_text_file_metadata(source, "config.yaml").is_empty());
     }
 
     #[cfg(feature = "lang-markdown")]
@@ -1,3 +1,5 @@
+pub mod augment;
+pub mod filter;
 pub mod helpers;
 pub mod languages;
 pub mod metadata;
@@ -35,5 +37,8 @@ pub mod ruby;
 #[cfg(feature = "lang-csharp")]
 pub mod csharp;
 
+#[cfg(feature = "lang-kotlin")]
+pub mod kotlin;
+
 #[cfg(feature = "lang-markdown")]
 pub mod markdown;
@@ -5,6 +5,7 @@ use std::collections::HashSet;
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -121,9 +122,24 @@ fn walk_node(
             extract_import_from(node, source, file_path, symbols, references);
         }
         "decorated_definition" => {
-            // Recurse into the definition inside the decorator
+            extract_decorators(node, source, file_path, parent_ctx, references);
+
+            // Recurse into the definition, and into decorator arguments for nested
+            // calls and strings (the decorator itself is recorded above)
             let mut cursor = node.walk();
             for child in node.children(&mut cursor) {
+                let child = if child.kind() == "decorator" {
+                    match child
+                        .named_child(0)
+                        .filter(|e| e.kind() == "call")
+                        .and_then(|call| find_child_by_field(call, "arguments"))
+                    {
+                        Some(arguments) => arguments,
+                        None => continue,
+                    }
+                } else {
+                    child
+                };
                 walk_node(
                     child,
                     source,
@@ -241,6 +257,7 @@ fn extract_function(
         tokens,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Recurse into function body for nested definitions and references
@@ -325,6 +342,7 @@ fn extract_class(
         tokens,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Walk class body
@@ -384,6 +402,7 @@ fn extract_import(
                     None,
                     None,
                     Some("private".to_string()),
+                    None,
                 );
                 // Also record as import reference
                 references.push(ReferenceEntry {
@@ -411,6 +430,7 @@ fn extract_import(
                         None,
                         alias,
                         Some("private".to_string()),
+                        None,
                     );
                     // Also record as import reference
                     references.push(ReferenceEntry {
@@ -428,6 +448,16 @@ fn extract_import(
     }
 }
 
+/// Join a `from` module and an imported name: `pkg` + `x` -> `pkg.x`. Relative
+/// modules keep their leading dots: `..pkg` + `x` -> `..pkg.x`, `.` + `x` -> `.x`.
+fn qualify_import(module: &str, imported: &str) -> String {
+    if module.is_empty() || module.ends_with('.') {
+        format!("{module}{imported}")
+    } else {
+        format!("{module}.{imported}")
+    }
+}
+
 fn extract_import_from(
     node: Node,
     source: &[u8],
@@ -455,11 +485,7 @@ fn extract_import_from(
                     continue;
                 }
                 let imported = node_text(child, source);
-                let full_import = if module.is_empty() {
-                    imported
-                } else {
-                    format!("{module}.{imported}")
-                };
+                let full_import = qualify_import(&module, &imported);
                 push_symbol(
                     symbols,
                     file_path,
@@ -470,6 +496,7 @@ fn extract_import_from(
                     None,
                     None,
                     Some("private".to_string()),
+                    None,
                 );
                 // Also record as import reference
                 references.push(ReferenceEntry {
@@ -486,11 +513,7 @@ fn extract_import_from(
                 let alias_node = find_child_by_field(child, "alias");
                 if let Some(n) = name_node {
                     let imported = node_text(n, source);
-                    let full_import = if module.is_empty() {
-                        imported
-                    } else {
-                        format!("{module}.{imported}")
-                    };
+                    let full_import = qualify_import(&module, &imported);
                     let alias = alias_node.map(|a| node_text(a, source));
                     push_symbol(
                         symbols,
@@ -502,6 +525,7 @@ fn extract_import_from(
                         None,
                         alias,
                         Some("private".to_string()),
+                        None,
                     );
                     // Also record as import reference
                     references.push(ReferenceEntry {
@@ -515,11 +539,7 @@ fn extract_import_from(
                 }
             }
             "wildcard_import" => {
-                let full_import = if module.is_empty() {
-                    "*".to_string()
-                } else {
-                    format!("{module}.*")
-                };
+                let full_import = qualify_import(&module, "*");
                 push_symbol(
                     symbols,
                     file_path,
@@ -530,6 +550,7 @@ fn extract_import_from(
                     None,
                     None,
                     Some("private".to_string()),
+                    None,
                 );
                 // Also record as import reference
                 references.push(ReferenceEntry {
@@ -595,6 +616,13 @@ fn extract_assignment(
         name
     };
 
+    // Constants (often config values) are searchable by their literal values
+    let tokens = if kind == "constant" {
+        find_child_by_field(node, "right").and_then(|v| with_literal_tokens(None, v, source))
+    } else {
+        None
+    };
+
     push_symbol(
         symbols,
         file_path,
@@ -602,12 +630,63 @@ fn extract_assignment(
         kind,
         line,
         parent_ctx,
-        None,
+        tokens,
         None,
         Some(visibility),
+        None,
     );
 }
 
+/// Extract the decorators of a decorated function/class as `decorator` references.
+/// `name` is the decorator's dotted name without arguments (`@app.route("/")` -> `app.route`),
+/// `caller` the decorated definition.
+fn extract_decorators(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let Some(name) = find_child_by_field(node, "definition")
+        .and_then(|def| find_child_by_field(def, "name"))
+        .map(|n| node_text(n, source))
+    else {
+        return;
+    };
+    let decorated = match parent_ctx {
+        Some(parent) => format!("{parent}.{name}"),
+        None => name,
+    };
+
+    let mut cursor = node.walk();
+    for decorator in node.children(&mut cursor) {
+        if decorator.kind() != "decorator" {
+            continue;
+        }
+        let Some(mut expr) = decorator.named_child(0) else {
+            continue;
+        };
+        if expr.kind() == "call"
+            && let Some(func) = find_child_by_field(expr, "function")
+        {
+            expr = func;
+        }
+        // Subscripts, lambdas and other expressions have no dotted name
+        if !matches!(expr.kind(), "identifier" | "attribute") {
+            continue;
+        }
+
+        references.push(ReferenceEntry {
+            file: file_path.to_string(),
+            name: collapse_whitespace(&node_text(expr, source)),
+            kind: "decorator".to_string(),
+            line: node_line_range(decorator),
+            caller: Some(decorated.clone()),
+            project: String::new(),
+        });
+    }
+}
+
 /// Extract a function call as a reference.
 /// Handles: simple calls (foo()), method calls (obj.method()), chained calls (a.b.c()).
 fn extract_call(
@@ -643,7 +722,7 @@ fn extract_call(
     };
 
     // Skip builtins and common patterns that aren't useful references
-    if is_builtin_call(&name) {
+    if is_filtered(&name, is_builtin_call) {
         return;
     }
 
@@ -821,6 +900,7 @@ async def fetch_data():
         // Tokens should contain identifiers from the function body (name param filtered by stopwords)
         // Token may be None if all identifiers are filtered as stopwords
         assert_eq!(hello.visibility.as_deref(), Some("public"));
+        assert_eq!(hello.sig.as_deref(), Some("def hello(name)"));
 
         let priv_fn = find_sym(&symbols, "_private");
         assert_eq!(priv_fn.visibility.as_deref(), Some("internal"));
@@ -828,6 +908,7 @@ async def fetch_data():
         assert!(priv_fn.tokens.is_none());
 
         let async_fn = find_sym(&symbols, "fetch_data");
+        assert_eq!(async_fn.sig.as_deref(), Some("async def fetch_data()"));
         // Body just returns None, no meaningful tokens after filtering
         assert!(async_fn.tokens.is_none());
     }
@@ -913,6 +994,23 @@ class Config:
         assert_eq!(config.kind, "class");
     }
 
+    #[test]
+    fn test_python_constant_literal_tokens() {
+        let source = b"SETTINGS = {\"timeout\": 30, \"host\": f\"db.internal\"}
+TOTAL: float = 2.5
+name = 'not a constant'
+";
+        let (symbols, _texts, _refs) = parse_file(source, "python", "test.py").unwrap();
+
+        let settings = find_sym(&symbols, "SETTINGS");
+        assert_eq!(
+            settings.tokens.as_deref(),
+            Some("timeout 30 host db.internal")
+        );
+        assert_eq!(find_sym(&symbols, "TOTAL").tokens.as_deref(), Some("2.5"));
+        assert!(find_sym(&symbols, "name").tokens.is_none());
+    }
+
     #[test]
     fn test_python_visibility() {
         let source = b"def public_fn():
@@ -988,6 +1086,100 @@ def some_function():
         );
     }
 
+    #[test]
+    fn test_python_relative_and_aliased_imports() {
+        let source = b"import numpy as np
+import os.path, a.b as ab
+from ..pkg import thing as t, other
+from . import sibling
+from ... import up as u
+from .mod import *
+from . import *
+from x.y import (p, q as qq)";
+        let (symbols, _texts, refs) = parse_file(source, "python", "pkg/sub/m.py").unwrap();
+
+        let imports: Vec<(&str, Option<&str>)> = symbols
+            .iter()
+            .filter(|s| s.kind == "import")
+            .map(|s| (s.name.as_str(), s.alias.as_deref()))
+            .collect();
+        assert_eq!(
+            imports,
+            vec![
+                ("numpy", Some("np")),
+                ("os.path", None),
+                ("a.b", Some("ab")),
+                ("..pkg.thing", Some("t")),
+                ("..pkg.other", None),
+                (".sibling", None),
+                ("...up", Some("u")),
+                (".mod.*", None),
+                (".*", None),
+                ("x.y.p", None),
+                ("x.y.q", Some("qq")),
+            ]
+        );
+
+        // Every import is also a reference, under the same name
+        let import_refs: Vec<&str> = refs
+            .iter()
+            .filter(|r| r.kind == "import")
+            .map(|r| r.name.as_str())
+            .collect();
+        let names: Vec<&str> = imports.iter().map(|(name, _)| *name).collect();
+        assert_eq!(import_refs, names);
+    }
+
+    #[test]
+    fn test_python_decorator_references() {
+        let source = b"@app.route(\"/users\", methods=get_methods())
+@login_required
+def list_users():
+    pass
+
+class User:
+    @property
+    def name(self):
+        return self._name
+
+    @functools.lru_cache(maxsize=None)
+    def load(self):
+        pass
+
+@dataclass
+class Point:
+    x: int";
+        let (_symbols, _texts, refs) = parse_file(source, "python", "views.py").unwrap();
+
+        let decorators: Vec<(&str, Option<&str>, u32)> = refs
+            .iter()
+            .filter(|r| r.kind == "decorator")
+            .map(|r| (r.name.as_str(), r.caller.as_deref(), r.line[0]))
+            .collect();
+        assert_eq!(
+            decorators,
+            vec![
+                ("app.route", Some("list_users"), 1),
+                ("login_required", Some("list_users"), 2),
+                ("property", Some("User.name"), 7),
+                ("functools.lru_cache", Some("User.load"), 11),
+                ("dataclass", Some("Point"), 15),
+            ]
+        );
+
+        // The decorator call itself is not also a call reference, but calls in
+        // its arguments are
+        assert!(
+            !refs
+                .iter()
+                .any(|r| r.kind == "call" && r.name == "app.route")
+        );
+        assert!(
+            refs.iter()
+                .any(|r| r.kind == "call" && r.name == "get_methods")
+        );
+    }
+
     #[test]
     fn test_python_dunder_all_visibility() {
         let source = b"__all__ = ['foo', 'PublicClass', 'CONSTANT']
@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -390,7 +391,7 @@ fn extract_method(
         .map(|n| node_text(n, source))
         .unwrap_or_default();
 
-    let _sig = format!("def {name}{params}");
+    let sig = format!("def {name}{params}");
 
     let kind = if parent_ctx.is_some() {
         "method"
@@ -418,6 +419,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility),
+        Some(sig),
     );
 
     // Recurse for nested definitions and call references
@@ -461,7 +463,7 @@ fn extract_singleton_method(
         .map(|n| node_text(n, source))
         .unwrap_or_default();
 
-    let _sig = format!("def self.{name}{params}");
+    let sig = format!("def self.{name}{params}");
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -483,6 +485,7 @@ fn extract_singleton_method(
         tokens,
         None,
         Some("public".to_string()),
+        Some(sig),
     );
 
     // Recurse for nested definitions and call references
@@ -528,7 +531,7 @@ fn extract_class(
         .map(|n| format!(" < {}", node_text(n, source)))
         .unwrap_or_default();
 
-    let _sig = format!("class {name}{superclass_str}");
+    let sig = format!("class {name}{superclass_str}");
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -553,7 +556,7 @@ fn extract_class(
         } else {
             String::new()
         };
-        if !super_name.is_empty() && !is_ruby_builtin_call(&super_name) {
+        if !super_name.is_empty() && !is_filtered(&super_name, is_ruby_builtin_call) {
             references.push(ReferenceEntry {
                 file: file_path.to_string(),
                 name: super_name,
@@ -579,6 +582,7 @@ fn extract_class(
         tokens,
         None,
         Some("public".to_string()),
+        Some(sig),
     );
 
     // Walk class body
@@ -633,6 +637,7 @@ fn extract_module(
         None,
         None,
         Some("public".to_string()),
+        Some(format!("module {name}")),
     );
 
     if let Some(body) = find_child_by_field(node, "body") {
@@ -685,25 +690,24 @@ fn extract_assignment(
                 None,
                 None,
                 Some("public".to_string()),
+                None,
             );
         }
-        "identifier" => {
-            // Module/class level assignment
-            if parent_ctx.is_none() {
-                // Only capture top-level assignments
-                let visibility = ruby_visibility(&name);
-                push_symbol(
-                    symbols,
-                    file_path,
-                    name,
-                    "variable",
-                    line,
-                    parent_ctx,
-                    None,
-                    None,
-                    Some(visibility),
-                );
-            }
+        // Module/class level assignment; only capture top-level assignments
+        "identifier" if parent_ctx.is_none() => {
+            let visibility = ruby_visibility(&name);
+            push_symbol(
+                symbols,
+                file_path,
+                name,
+                "variable",
+                line,
+                parent_ctx,
+                None,
+                None,
+                Some(visibility),
+                None,
+            );
         }
         "instance_variable" | "class_variable" => {
             // @var or @@var
@@ -727,6 +731,7 @@ fn extract_assignment(
                 None,
                 None,
                 Some(visibility),
+                None,
             );
         }
         _ => {}
@@ -767,6 +772,7 @@ fn extract_call(
                                 None,
                                 None,
                                 Some("private".to_string()),
+                                None,
                             );
                             // Also add import reference
                             references.push(ReferenceEntry {
@@ -782,9 +788,52 @@ fn extract_call(
                 }
             }
         }
+        "include" | "extend" | "prepend" => {
+            // Mixins: each module is a type reference from the enclosing class/module
+            for arg in call_arguments(node) {
+                if matches!(arg.kind(), "constant" | "scope_resolution") {
+                    references.push(ReferenceEntry {
+                        file: file_path.to_string(),
+                        name: node_text(arg, source),
+                        kind: "type_annotation".to_string(),
+                        line: node_line_range(arg),
+                        caller: parent_ctx.map(String::from),
+                        project: String::new(),
+                    });
+                }
+            }
+        }
+        "attr_accessor" | "attr_reader" | "attr_writer" | "attr" => {
+            // `attr_accessor :name, :email` declares one property per symbol
+            let Some(owner) = parent_ctx else {
+                return;
+            };
+            for arg in call_arguments(node) {
+                let attr = match arg.kind() {
+                    "simple_symbol" => node_text(arg, source).trim_start_matches(':').to_string(),
+                    "string" => strip_string_quotes(&node_text(arg, source)),
+                    _ => continue,
+                };
+                if attr.is_empty() {
+                    continue;
+                }
+                push_symbol(
+                    symbols,
+                    file_path,
+                    format!("{owner}.{attr}"),
+                    "property",
+                    line,
+                    Some(owner),
+                    None,
+                    None,
+                    Some("public".to_string()),
+                    Some(format!("{method} :{attr}")),
+                );
+            }
+        }
         _ => {
             // Extract other method calls as references
-            if !is_ruby_builtin_call(&method) {
+            if !is_filtered(&method, is_ruby_builtin_call) {
                 // Build the full call name including receiver
                 let call_name = if let Some(receiver) = find_child_by_field(node, "receiver") {
                     let receiver_name = node_text(receiver, source);
@@ -812,6 +861,15 @@ fn extract_call(
     }
 }
 
+/// Argument nodes of a call (`include A, B` → `A`, `B`), skipping punctuation.
+fn call_arguments(node: Node) -> Vec<Node> {
+    let Some(args) = find_child_by_field(node, "arguments") else {
+        return Vec::new();
+    };
+    let mut cursor = args.walk();
+    args.named_children(&mut cursor).collect()
+}
+
 fn extract_ruby_comment(
     node: Node,
     source: &[u8],
@@ -1087,4 +1145,49 @@ end";
         assert!(type_refs.iter().any(|r| r.name == "Animal"));
         assert!(type_refs.iter().any(|r| r.name == "BaseWidget"));
     }
+
+    #[test]
+    fn test_ruby_mixins_and_attr_accessors() {
+        let source = b"module Greeting
+end
+
+class User
+  include Greeting
+  include Comparable, Serializable::Json
+  extend ClassMethods
+  attr_accessor :name, :email
+  attr_reader :id
+end";
+        let (symbols, _texts, refs) = parse_file(source, "ruby", "test.rb").unwrap();
+
+        let greeting = find_sym(&symbols, "Greeting");
+        assert_eq!(greeting.kind, "module");
+        assert_eq!(greeting.sig.as_deref(), Some("module Greeting"));
+
+        let mixins: Vec<(&str, Option<&str>, u32)> = refs
+            .iter()
+            .filter(|r| r.kind == "type_annotation")
+            .map(|r| (r.name.as_str(), r.caller.as_deref(), r.line[0]))
+            .collect();
+        assert_eq!(
+            mixins,
+            vec![
+                ("Greeting", Some("User"), 5),
+                ("Comparable", Some("User"), 6),
+                ("Serializable::Json", Some("User"), 6),
+                ("ClassMethods", Some("User"), 7),
+            ]
+        );
+
+        for (name, sig) in [
+            ("User.name", "attr_accessor :name"),
+            ("User.email", "attr_accessor :email"),
+            ("User.id", "attr_reader :id"),
+        ] {
+            let attr = find_sym(&symbols, name);
+            assert_eq!(attr.kind, "property");
+            assert_eq!(attr.parent.as_deref(), Some("User"));
+            assert_eq!(attr.sig.as_deref(), Some(sig));
+        }
+    }
 }
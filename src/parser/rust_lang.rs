@@ -3,6 +3,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -47,7 +48,7 @@ fn walk_node(
             extract_struct(node, source, file_path, parent_ctx, symbols, references);
         }
         "enum_item" => {
-            extract_named_symbol(node, source, file_path, "enum", parent_ctx, symbols);
+            extract_enum(node, source, file_path, parent_ctx, symbols, references);
         }
         "trait_item" => {
             extract_named_symbol(node, source, file_path, "interface", parent_ctx, symbols);
@@ -57,6 +58,33 @@ fn walk_node(
         }
         "mod_item" => {
             extract_named_symbol(node, source, file_path, "module", parent_ctx, symbols);
+            let Some(body) = find_child_by_field(node, "body") else {
+                // `mod foo;`: the contents live in another file
+                extract_mod_declaration(node, source, file_path, references);
+                return;
+            };
+            // `mod foo { ... }`: the body is a fresh item scope whose top-level
+            // items belong to the module
+            let first = symbols.len();
+            let mut cursor = body.walk();
+            for child in body.children(&mut cursor) {
+                walk_node(
+                    child,
+                    source,
+                    file_path,
+                    None,
+                    symbols,
+                    texts,
+                    references,
+                    depth + 1,
+                );
+            }
+            if let Some(name) = find_child_by_field(node, "name").map(|n| node_text(n, source)) {
+                for symbol in &mut symbols[first..] {
+                    symbol.parent.get_or_insert_with(|| name.clone());
+                }
+            }
+            return;
         }
         "const_item" => {
             extract_named_symbol(node, source, file_path, "constant", parent_ctx, symbols);
@@ -74,6 +102,13 @@ fn walk_node(
         "call_expression" => {
             extract_call(node, source, file_path, parent_ctx, references);
         }
+        "attribute_item" | "inner_attribute_item" => {
+            extract_doc_attribute(node, source, file_path, parent_ctx, texts);
+            if kind == "attribute_item" {
+                extract_attribute(node, source, file_path, parent_ctx, references);
+            }
+            return;
+        }
         "line_comment" | "block_comment" => {
             extract_rust_comment(node, source, file_path, parent_ctx, texts);
             return;
@@ -154,6 +189,7 @@ fn extract_function(
         tokens,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Extract type references from parameters
@@ -210,6 +246,7 @@ fn extract_named_symbol(
         None,
         None,
         Some(visibility),
+        None,
     );
 }
 
@@ -239,6 +276,7 @@ fn extract_struct(
         None,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Extract type references from struct fields
@@ -247,6 +285,80 @@ fn extract_struct(
     }
 }
 
+fn extract_enum(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    symbols: &mut Vec<SymbolEntry>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    extract_named_symbol(node, source, file_path, "enum", parent_ctx, symbols);
+
+    let (Some(name_node), Some(body)) = (
+        find_child_by_field(node, "name"),
+        find_child_by_field(node, "body"),
+    ) else {
+        return;
+    };
+    let name = node_text(name_node, source);
+    let visibility = extract_visibility(node, source);
+
+    // Variants are children of the enum and share its visibility
+    let mut cursor = body.walk();
+    for variant in body.children(&mut cursor) {
+        if variant.kind() != "enum_variant" {
+            continue;
+        }
+        let Some(variant_name) = find_child_by_field(variant, "name") else {
+            continue;
+        };
+
+        let fields = find_child_by_field(variant, "body");
+        let tokens = fields
+            .map(|f| filter_rust_tokens(&variant_field_tokens(f, source)))
+            .filter(|t| !t.is_empty());
+
+        push_symbol(
+            symbols,
+            file_path,
+            format!("{name}.{}", node_text(variant_name, source)),
+            "variant",
+            node_line_range(variant),
+            Some(&name),
+            tokens,
+            None,
+            Some(visibility.clone()),
+            Some(collapse_whitespace(&node_text(variant, source))),
+        );
+
+        if let Some(fields) = fields {
+            extract_type_refs_from_node(fields, source, file_path, Some(&name), references);
+        }
+    }
+}
+
+/// Field names and types of a struct-like or tuple variant, in source order.
+fn variant_field_tokens(fields: Node, source: &[u8]) -> String {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut stack = vec![fields];
+    while let Some(n) = stack.pop() {
+        if matches!(
+            n.kind(),
+            "field_identifier" | "type_identifier" | "identifier"
+        ) {
+            let text = node_text(n, source);
+            if !tokens.contains(&text) && !is_rust_primitive_type(&text) {
+                tokens.push(text);
+            }
+        }
+        let mut cursor = n.walk();
+        let children: Vec<_> = n.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    tokens.join(" ")
+}
+
 #[allow(clippy::too_many_arguments)]
 fn extract_impl(
     node: Node,
@@ -261,14 +373,27 @@ fn extract_impl(
     let line = node_line_range(node);
     let visibility = extract_visibility(node, source);
 
-    let trait_name = find_child_by_field(node, "trait").map(|n| node_text(n, source));
+    let trait_node = find_child_by_field(node, "trait");
 
-    let kind = if trait_name.is_some() {
+    let kind = if trait_node.is_some() {
         "trait_impl"
     } else {
         "impl"
     };
 
+    // `impl Trait for Type` is a reference to the trait from the implementing type,
+    // so implementors show up as its callers
+    if let Some(trait_node) = trait_node {
+        references.push(ReferenceEntry {
+            file: file_path.to_string(),
+            name: base_type_name(trait_node, source),
+            kind: "type_annotation".to_string(),
+            line: node_line_range(trait_node),
+            caller: Some(impl_type_name.clone()),
+            project: String::new(),
+        });
+    }
+
     // impl blocks are containers, no meaningful tokens
     push_symbol(
         symbols,
@@ -280,6 +405,7 @@ fn extract_impl(
         None,
         None,
         Some(visibility),
+        Some(extract_signature_before_body(node, source)),
     );
 
     // Walk children of the body to find methods
@@ -338,11 +464,25 @@ fn extract_impl(
 
 fn extract_impl_type_name(node: Node, source: &[u8]) -> String {
     if let Some(type_node) = find_child_by_field(node, "type") {
-        return node_text(type_node, source);
+        return base_type_name(type_node, source);
     }
     "Unknown".to_string()
 }
 
+/// Base name of an impl's trait or self type, without generics, references or
+/// path: `Foo<T>` → `Foo`, `&'a mut Foo` → `Foo`, `fmt::Display` → `Display`.
+fn base_type_name(node: Node, source: &[u8]) -> String {
+    let inner = match node.kind() {
+        "generic_type" | "reference_type" | "pointer_type" => find_child_by_field(node, "type"),
+        "scoped_type_identifier" => find_child_by_field(node, "name"),
+        _ => None,
+    };
+    match inner {
+        Some(inner) => base_type_name(inner, source),
+        None => node_text(node, source),
+    }
+}
+
 fn extract_use(
     node: Node,
     source: &[u8],
@@ -390,6 +530,7 @@ fn extract_use_paths(
                     None,
                     alias,
                     Some(visibility.to_string()),
+                    None,
                 );
                 // Also record as import reference
                 references.push(ReferenceEntry {
@@ -422,6 +563,7 @@ fn extract_use_paths(
                 None,
                 None,
                 Some(visibility.to_string()),
+                None,
             );
             // Also record as import reference
             references.push(ReferenceEntry {
@@ -445,6 +587,7 @@ fn extract_use_paths(
                 None,
                 None,
                 Some(visibility.to_string()),
+                None,
             );
             // Also record as import reference
             references.push(ReferenceEntry {
@@ -505,7 +648,7 @@ fn extract_call(
     };
 
     // Skip macros and builtins
-    if is_rust_builtin_call(&name) {
+    if is_filtered(&name, is_rust_builtin_call) {
         return;
     }
 
@@ -519,6 +662,171 @@ fn extract_call(
     });
 }
 
+/// Record `mod foo;` as an `import` reference to the file expected to hold the
+/// module, as a path from the project root (`src/parser/foo.rs`).
+///
+/// Follows rustc's lookup: children of `lib.rs`, `main.rs` and `mod.rs` sit
+/// next to the file, those of `parser.rs` in `parser/`, with any enclosing
+/// inline modules as further directories. A `#[path = "..."]` attribute wins.
+/// `foo/mod.rs` is tried when resolving the import (see `server::imports`).
+fn extract_mod_declaration(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let Some(name) = find_child_by_field(node, "name").map(|n| node_text(n, source)) else {
+        return;
+    };
+
+    // Enclosing inline modules, outermost first
+    let mut inline = Vec::new();
+    let mut current = node;
+    while let Some(body) = current.parent().filter(|p| p.kind() == "declaration_list")
+        && let Some(module) = body.parent().filter(|p| p.kind() == "mod_item")
+    {
+        inline.extend(find_child_by_field(module, "name").map(|n| node_text(n, source)));
+        current = module;
+    }
+    inline.reverse();
+
+    let (dir, file_name) = file_path.rsplit_once('/').unwrap_or(("", file_path));
+    let mut module_dir: Vec<&str> = dir.split('/').filter(|d| !d.is_empty()).collect();
+    let path_attr = mod_path_attribute(node, source);
+    if path_attr.is_none() || !inline.is_empty() {
+        let stem = file_name.strip_suffix(".rs").unwrap_or(file_name);
+        if !matches!(stem, "lib" | "main" | "mod") {
+            module_dir.push(stem);
+        }
+        module_dir.extend(inline.iter().map(String::as_str));
+    }
+    let target = path_attr.unwrap_or_else(|| format!("{name}.rs"));
+    module_dir.push(&target);
+
+    references.push(ReferenceEntry {
+        file: file_path.to_string(),
+        name: module_dir.join("/"),
+        kind: "import".to_string(),
+        line: node_line_range(node),
+        caller: inline.last().cloned(),
+        project: String::new(),
+    });
+}
+
+/// Value of a `#[path = "..."]` attribute among those preceding `node`.
+fn mod_path_attribute(node: Node, source: &[u8]) -> Option<String> {
+    let mut prev = node.prev_named_sibling();
+    while let Some(sibling) = prev {
+        match sibling.kind() {
+            "attribute_item" => {
+                let attr = sibling
+                    .named_children(&mut sibling.walk())
+                    .find(|c| c.kind() == "attribute")?;
+                if attr.named_child(0).map(|p| node_text(p, source)).as_deref() == Some("path")
+                    && let Some(value) = find_child_by_field(attr, "value")
+                {
+                    return Some(strip_string_quotes(&node_text(value, source)));
+                }
+            }
+            "line_comment" | "block_comment" => {}
+            _ => return None,
+        }
+        prev = sibling.prev_named_sibling();
+    }
+    None
+}
+
+/// Extract an outer attribute (`#[...]`) as references attached to the annotated item.
+///
+/// `#[derive(A, B)]` yields one `derive` reference per trait; any other attribute
+/// (`#[test]`, `#[tokio::main]`, `#[serde(...)]`) yields one `attribute` reference
+/// named after its path. Lint and doc attributes are skipped as noise.
+fn extract_attribute(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    references: &mut Vec<ReferenceEntry>,
+) {
+    let Some(attr) = node
+        .named_children(&mut node.walk())
+        .find(|c| c.kind() == "attribute")
+    else {
+        return;
+    };
+    let Some(path) = attr.named_child(0) else {
+        return;
+    };
+    let path = node_text(path, source);
+    if matches!(
+        path.as_str(),
+        "allow" | "warn" | "deny" | "forbid" | "expect" | "doc"
+    ) {
+        return;
+    }
+
+    let line = node_line_range(node);
+    let caller = annotated_item_name(node, source, parent_ctx).or(parent_ctx.map(String::from));
+    let mut push_ref = |name: String, kind: &str| {
+        references.push(ReferenceEntry {
+            file: file_path.to_string(),
+            name,
+            kind: kind.to_string(),
+            line,
+            caller: caller.clone(),
+            project: String::new(),
+        });
+    };
+
+    if path == "derive" {
+        let Some(args) = find_child_by_field(attr, "arguments") else {
+            return;
+        };
+        let args = node_text(args, source);
+        let inner = args
+            .trim()
+            .trim_start_matches(['(', '[', '{'])
+            .trim_end_matches([')', ']', '}']);
+        for derived in inner.split(',') {
+            let derived: String = derived.split_whitespace().collect();
+            if !derived.is_empty() {
+                push_ref(derived, "derive");
+            }
+        }
+    } else {
+        push_ref(path, "attribute");
+    }
+}
+
+/// Find the symbol name of the item an attribute is attached to.
+///
+/// Attributes are siblings preceding the item, possibly interleaved with other
+/// attributes and comments. Returns the name as the item's symbol would be recorded.
+fn annotated_item_name(node: Node, source: &[u8], parent_ctx: Option<&str>) -> Option<String> {
+    let mut next = node.next_named_sibling();
+    while let Some(item) = next {
+        match item.kind() {
+            "attribute_item" | "line_comment" | "block_comment" => {
+                next = item.next_named_sibling();
+            }
+            "function_item" => {
+                let name = node_text(find_child_by_field(item, "name")?, source);
+                return Some(match parent_ctx {
+                    Some(parent) => format!("{parent}.{name}"),
+                    None => name,
+                });
+            }
+            "impl_item" => return Some(extract_impl_type_name(item, source)),
+            "struct_item" | "enum_item" | "trait_item" | "type_item" | "mod_item"
+            | "const_item" | "static_item" | "union_item" => {
+                return find_child_by_field(item, "name").map(|n| node_text(n, source));
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
 /// Check if a call is to a Rust builtin/macro that we want to skip.
 fn is_rust_builtin_call(name: &str) -> bool {
     let base = name.split("::").last().unwrap_or(name);
@@ -776,6 +1084,9 @@ fn try_parse_macro_body(
 }
 
 /// Rust-specific comment extraction (handles ///, //!, /**, etc.)
+///
+/// Outer doc comments (`///`, `/** */`) are parented to the item they document,
+/// inner ones (`//!`, `/*! */`) to the enclosing module.
 fn extract_rust_comment(
     node: Node,
     source: &[u8],
@@ -783,7 +1094,78 @@ fn extract_rust_comment(
     parent_ctx: Option<&str>,
     texts: &mut Vec<TextEntry>,
 ) {
-    extract_comment(node, source, file_path, parent_ctx, texts);
+    let raw = node_text(node, source);
+    let is_outer_doc = (raw.starts_with("///") && !raw.starts_with("////"))
+        || (raw.starts_with("/**") && !raw.starts_with("/**/"));
+    let is_inner_doc = raw.starts_with("//!") || raw.starts_with("/*!");
+
+    let parent = if is_outer_doc {
+        annotated_item_name(node, source, parent_ctx)
+    } else if is_inner_doc {
+        enclosing_module_name(node, source)
+    } else {
+        None
+    };
+    extract_comment(
+        node,
+        source,
+        file_path,
+        parent.as_deref().or(parent_ctx),
+        texts,
+    );
+}
+
+/// Extract `#[doc = "..."]` / `#![doc = "..."]` attributes as docstrings, with the
+/// same parenting as the equivalent doc comments.
+fn extract_doc_attribute(
+    node: Node,
+    source: &[u8],
+    file_path: &str,
+    parent_ctx: Option<&str>,
+    texts: &mut Vec<TextEntry>,
+) {
+    let Some(attr) = node
+        .named_children(&mut node.walk())
+        .find(|c| c.kind() == "attribute")
+    else {
+        return;
+    };
+    if attr.named_child(0).map(|p| node_text(p, source)).as_deref() != Some("doc") {
+        return;
+    }
+    let Some(value) = find_child_by_field(attr, "value")
+        .filter(|v| matches!(v.kind(), "string_literal" | "raw_string_literal"))
+    else {
+        return;
+    };
+
+    let text = strip_string_quotes(&node_text(value, source))
+        .trim()
+        .to_string();
+    if is_trivial_text(&text) {
+        return;
+    }
+
+    let parent = if node.kind() == "inner_attribute_item" {
+        enclosing_module_name(node, source)
+    } else {
+        annotated_item_name(node, source, parent_ctx)
+    };
+    texts.push(TextEntry {
+        file: file_path.to_string(),
+        kind: "docstring".to_string(),
+        line: node_line_range(node),
+        text,
+        parent: parent.or(parent_ctx.map(String::from)),
+        project: String::new(),
+    });
+}
+
+/// Name of the inline `mod` whose body contains `node`, if any.
+fn enclosing_module_name(node: Node, source: &[u8]) -> Option<String> {
+    let body = node.parent().filter(|p| p.kind() == "declaration_list")?;
+    let module = body.parent().filter(|p| p.kind() == "mod_item")?;
+    find_child_by_field(module, "name").map(|n| node_text(n, source))
 }
 
 #[cfg(test)]
@@ -815,6 +1197,10 @@ fn private_helper() {
         // Tokens contain identifiers from function body (format, name)
         // Token may be None if all identifiers are filtered as stopwords
         assert_eq!(hello.visibility.as_deref(), Some("public"));
+        assert_eq!(
+            hello.sig.as_deref(),
+            Some("pub fn hello(name: &str) -> String")
+        );
 
         let helper = find_sym(&symbols, "private_helper");
         assert_eq!(helper.kind, "function");
@@ -894,6 +1280,41 @@ impl Display for Foo {
         assert!(trait_impl.tokens.is_none());
     }
 
+    #[test]
+    fn test_rust_trait_impl_references() {
+        let source = b"impl fmt::Display for Foo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+}
+
+impl<T: Clone> From<T> for Wrapper<T> {
+    fn from(value: T) -> Self { Wrapper(value) }
+}
+
+impl<'a, T> Foo<T> {
+    pub fn get(&self) -> &T { &self.0 }
+}";
+        let (symbols, _texts, refs) = parse_file(source, "rust", "test.rs").unwrap();
+
+        let implemented: Vec<_> = refs
+            .iter()
+            .filter(|r| r.kind == "type_annotation")
+            .map(|r| (r.name.as_str(), r.caller.as_deref()))
+            .collect();
+        assert!(implemented.contains(&("Display", Some("Foo"))));
+        assert!(implemented.contains(&("From", Some("Wrapper"))));
+
+        // Generic inherent impls attach methods to the base type name
+        let impl_sym = symbols.iter().find(|s| s.kind == "impl").unwrap();
+        assert_eq!(impl_sym.name, "Foo");
+        assert_eq!(impl_sym.sig.as_deref(), Some("impl<'a, T> Foo<T>"));
+        let get = find_sym(&symbols, "Foo.get");
+        assert_eq!(get.parent.as_deref(), Some("Foo"));
+        assert_eq!(
+            find_sym(&symbols, "Wrapper.from").parent.as_deref(),
+            Some("Wrapper")
+        );
+    }
+
     #[test]
     fn test_rust_use() {
         let source = b"use std::collections::HashMap;
@@ -925,6 +1346,35 @@ pub use std::fmt::Debug;";
         assert_eq!(result.visibility.as_deref(), Some("public"));
     }
 
+    #[test]
+    fn test_rust_enum_variants() {
+        let source = b"pub enum SearchResult {
+    Symbol(SymbolEntry),
+    File { path: String, lang: Language },
+    Empty,
+}
+
+enum Hidden { One = 1 }";
+        let (symbols, _texts, _refs) = parse_file(source, "rust", "test.rs").unwrap();
+
+        let symbol = find_sym(&symbols, "SearchResult.Symbol");
+        assert_eq!(symbol.kind, "variant");
+        assert_eq!(symbol.parent.as_deref(), Some("SearchResult"));
+        assert_eq!(symbol.visibility.as_deref(), Some("public"));
+        assert_eq!(symbol.tokens.as_deref(), Some("SymbolEntry"));
+        assert_eq!(symbol.sig.as_deref(), Some("Symbol(SymbolEntry)"));
+
+        let file = find_sym(&symbols, "SearchResult.File");
+        assert_eq!(file.tokens.as_deref(), Some("path String lang Language"));
+
+        let empty = find_sym(&symbols, "SearchResult.Empty");
+        assert!(empty.tokens.is_none());
+
+        let one = find_sym(&symbols, "Hidden.One");
+        assert_eq!(one.visibility.as_deref(), Some("private"));
+        assert_eq!(one.line, [7, 7]);
+    }
+
     #[test]
     fn test_rust_mod() {
         let source = b"pub mod utils;
@@ -939,6 +1389,71 @@ mod private_mod;";
         assert_eq!(priv_mod.visibility.as_deref(), Some("private"));
     }
 
+    #[test]
+    fn test_rust_inline_mod_children() {
+        let source = b"mod outer {
+    pub struct Config;
+    impl Config {
+        fn load() {}
+    }
+    mod inner {
+        fn helper() {}
+    }
+}
+fn top() {}";
+        let (symbols, _texts, _refs) = parse_file(source, "rust", "src/lib.rs").unwrap();
+
+        assert_eq!(find_sym(&symbols, "outer").parent, None);
+        assert_eq!(
+            find_sym(&symbols, "Config").parent.as_deref(),
+            Some("outer")
+        );
+        // Items nested deeper keep their own parent
+        assert_eq!(
+            find_sym(&symbols, "Config.load").parent.as_deref(),
+            Some("Config")
+        );
+        assert_eq!(find_sym(&symbols, "inner").parent.as_deref(), Some("outer"));
+        let helper = find_sym(&symbols, "helper");
+        assert_eq!(helper.kind, "function");
+        assert_eq!(helper.parent.as_deref(), Some("inner"));
+        assert_eq!(find_sym(&symbols, "top").parent, None);
+    }
+
+    #[test]
+    fn test_rust_mod_declaration_references() {
+        let mod_imports = |source: &[u8], path: &str| -> Vec<(String, Option<String>)> {
+            let (_, _, refs) = parse_file(source, "rust", path).unwrap();
+            refs.into_iter()
+                .filter(|r| r.kind == "import")
+                .map(|r| (r.name, r.caller))
+                .collect()
+        };
+
+        assert_eq!(
+            mod_imports(b"pub mod parser;\nmod server { mod db; }", "src/lib.rs"),
+            vec![
+                ("src/parser.rs".to_string(), None),
+                ("src/server/db.rs".to_string(), Some("server".to_string())),
+            ]
+        );
+        assert_eq!(
+            mod_imports(b"mod rust_lang;", "src/parser.rs"),
+            vec![("src/parser/rust_lang.rs".to_string(), None)]
+        );
+        assert_eq!(
+            mod_imports(b"mod handler;", "src/mount/mod.rs"),
+            vec![("src/mount/handler.rs".to_string(), None)]
+        );
+        assert_eq!(
+            mod_imports(
+                b"#[path = \"gen/out.rs\"]\n#[allow(dead_code)]\nmod generated;",
+                "src/main.rs"
+            ),
+            vec![("src/gen/out.rs".to_string(), None)]
+        );
+    }
+
     #[test]
     fn test_rust_const() {
         let source = b"pub const MAX: usize = 100;
@@ -964,6 +1479,54 @@ pub fn documented() {}
 fn helper() {}";
         let (_symbols, texts, _refs) = parse_file(source, "rust", "test.rs").unwrap();
         assert!(texts.iter().any(|t| t.kind == "comment"));
+
+        let doc = texts.iter().find(|t| t.kind == "docstring").unwrap();
+        assert_eq!(doc.text, "This is a doc comment\nfor the function");
+        assert_eq!(doc.parent.as_deref(), Some("documented"));
+        assert_eq!(doc.line, [1, 2]);
+    }
+
+    #[test]
+    fn test_rust_doc_comments_bound_to_items() {
+        let source = b"mod codec {
+    //! Zero-copy deserialization helpers.
+
+    /// Decodes a frame without copying.
+    #[inline]
+    pub fn decode() {}
+
+    #[doc = \"Wire-level frame header.\"]
+    pub struct Header;
+
+    /// ok
+    pub enum Kind { A }
+}
+
+struct Parser;
+
+impl Parser {
+    /// Feeds more input into the parser.
+    fn feed(&mut self) {}
+}";
+        let (_symbols, texts, _refs) = parse_file(source, "rust", "test.rs").unwrap();
+
+        let doc_for = |text: &str| {
+            texts
+                .iter()
+                .find(|t| t.kind == "docstring" && t.text == text)
+                .unwrap_or_else(|| panic!("docstring not found: {text}"))
+                .parent
+                .as_deref()
+        };
+        assert_eq!(doc_for("Zero-copy deserialization helpers."), Some("codec"));
+        assert_eq!(doc_for("Decodes a frame without copying."), Some("decode"));
+        assert_eq!(doc_for("Wire-level frame header."), Some("Header"));
+        assert_eq!(
+            doc_for("Feeds more input into the parser."),
+            Some("Parser.feed")
+        );
+        // Trivial doc comments are still filtered
+        assert!(!texts.iter().any(|t| t.text == "ok"));
     }
 
     #[test]
@@ -1038,6 +1601,56 @@ fn process(input: CustomType) -> ResultType {
         );
     }
 
+    #[test]
+    fn test_rust_attribute_references() {
+        let source = b"#[derive(Debug, Clone, serde::Serialize)]
+pub struct Config {
+    #[serde(default)]
+    name: String,
+}
+
+#[tokio::main]
+#[allow(dead_code)]
+async fn main() {}
+
+impl Config {
+    #[inline]
+    pub fn name(&self) -> &str { &self.name }
+}
+
+mod tests {
+    #[test]
+    fn it_works() {}
+}";
+        let (_, _, refs) = parse_file(source, "rust", "test.rs").unwrap();
+
+        let derives: Vec<&str> = refs
+            .iter()
+            .filter(|r| r.kind == "derive")
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(derives, vec!["Debug", "Clone", "serde::Serialize"]);
+        assert!(
+            refs.iter()
+                .filter(|r| r.kind == "derive")
+                .all(|r| r.caller.as_deref() == Some("Config") && r.line == [1, 1])
+        );
+
+        let attr = |name: &str| {
+            refs.iter()
+                .find(|r| r.kind == "attribute" && r.name == name)
+                .unwrap_or_else(|| panic!("attribute not found: {name}"))
+        };
+        assert_eq!(attr("tokio::main").caller.as_deref(), Some("main"));
+        assert_eq!(attr("inline").caller.as_deref(), Some("Config.name"));
+        assert_eq!(attr("test").caller.as_deref(), Some("it_works"));
+        // Field attributes attach to the enclosing context
+        assert_eq!(attr("serde").caller, None);
+
+        // Lint attributes are skipped
+        assert!(!refs.iter().any(|r| r.name == "allow"));
+    }
+
     #[test]
     fn test_rust_macro_body_parsing() {
         // Test that we can extract symbols from inside macro invocations
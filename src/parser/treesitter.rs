@@ -1,7 +1,12 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use anyhow::Result;
 use tree_sitter::{Parser, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::augment::{TokenAugmentor, apply_augmentors};
+use crate::parser::filter::{FilterPolicy, with_policy};
 use crate::parser::helpers::*;
 use crate::parser::languages::get_language;
 use crate::parser::sfc;
@@ -9,12 +14,92 @@ use crate::parser::sfc;
 /// Maximum recursion depth for AST traversal to prevent stack overflow on deeply nested code.
 pub const MAX_DEPTH: usize = 150;
 
+thread_local! {
+    /// One parser per language and thread, reused for every file: each indexing
+    /// worker pays for `Parser::new` and loading a grammar once, not per file.
+    static PARSERS: RefCell<HashMap<String, Parser>> = RefCell::new(HashMap::new());
+}
+
+/// Parse `source` with this thread's cached parser for `language`.
+///
+/// Fails if the language has no grammar; returns None if tree-sitter gave up
+/// (the parser is reset, so the next file starts clean).
+pub(crate) fn parse_tree(language: &str, source: &[u8]) -> Result<Option<Tree>> {
+    PARSERS.with_borrow_mut(|parsers| {
+        let parser = match parsers.get_mut(language) {
+            Some(parser) => parser,
+            None => {
+                let mut parser = Parser::new();
+                parser.set_language(&get_language(language)?)?;
+                parsers.entry(language.to_string()).or_insert(parser)
+            }
+        };
+        let tree = parser.parse(source, None);
+        if tree.is_none() {
+            parser.reset();
+        }
+        Ok(tree)
+    })
+}
+
+/// Why [`parse_file_checked`] produced nothing for a file.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    /// No grammar for this language (unknown, or its `lang-*` feature is off)
+    #[error("unsupported language: {0}")]
+    UnsupportedLanguage(String),
+    /// The grammar could not be loaded or tree-sitter returned no tree
+    #[error("failed to parse {file}: {message}")]
+    Failed { file: String, message: String },
+    /// The extractor panicked; the panic was caught so indexing goes on
+    #[error("extractor panicked on {file}: {message}")]
+    Panicked { file: String, message: String },
+}
+
+/// Symbols, texts and references extracted from one file.
+#[derive(Debug, Default)]
+pub struct ParsedFile {
+    pub symbols: Vec<SymbolEntry>,
+    pub texts: Vec<TextEntry>,
+    pub references: Vec<ReferenceEntry>,
+    /// First line (1-based) of an ERROR or MISSING node when the syntax tree has
+    /// errors: everything was extracted, but symbols past it may be missing or wrong
+    pub error_line: Option<u32>,
+}
+
 /// Parse a single file using tree-sitter and extract symbols, text blocks, and references.
+///
+/// Errors are flattened into `anyhow`; use [`parse_file_checked`] to tell them
+/// apart, to learn whether the file only parsed partially, to add tokens
+/// with [`TokenAugmentor`]s, or to apply a project's [`FilterPolicy`].
 pub fn parse_file(
     source: &[u8],
     language: &str,
     file_path: &str,
 ) -> Result<(Vec<SymbolEntry>, Vec<TextEntry>, Vec<ReferenceEntry>)> {
+    let parsed = parse_file_checked(source, language, file_path, &[], &FilterPolicy::default())?;
+    Ok((parsed.symbols, parsed.texts, parsed.references))
+}
+
+/// Parse a single file like [`parse_file`], reporting failures as a [`ParseError`]
+/// and syntax errors in [`ParsedFile::error_line`]. A panicking extractor becomes
+/// [`ParseError::Panicked`] instead of unwinding into the caller.
+///
+/// `augmentors` contribute extra strings to symbol tokens (tree-sitter
+/// languages only; SFC and markdown files ignore them). `policy` overrides the
+/// builtin filters of the extractors and adds stopwords to symbol tokens.
+pub fn parse_file_checked(
+    source: &[u8],
+    language: &str,
+    file_path: &str,
+    augmentors: &[&dyn TokenAugmentor],
+    policy: &FilterPolicy,
+) -> Result<ParsedFile, ParseError> {
+    let failed = |e: anyhow::Error| ParseError::Failed {
+        file: file_path.to_string(),
+        message: e.to_string(),
+    };
+
     // SFC preprocessing: extract script blocks from Vue/Svelte/Astro files
     let sfc_ext = match language {
         "html" => Some("html"),
@@ -25,24 +110,93 @@ pub fn parse_file(
     };
 
     if let Some(ext) = sfc_ext {
-        return parse_sfc(source, ext, file_path);
+        let (symbols, texts, references) =
+            parse_sfc(source, ext, file_path, policy).map_err(failed)?;
+        return Ok(ParsedFile {
+            symbols,
+            texts,
+            references,
+            error_line: None,
+        });
     }
 
     // Markdown uses a custom two-pass parser (tree-sitter-md with MarkdownParser)
     #[cfg(feature = "lang-markdown")]
     if language == "markdown" {
-        let (symbols, texts) = crate::parser::markdown::parse_and_extract(source, file_path)?;
-        return Ok((symbols, texts, Vec::new()));
+        let (symbols, mut texts) =
+            crate::parser::markdown::parse_and_extract(source, file_path).map_err(failed)?;
+        for text in &mut texts {
+            normalize_line_endings(&mut text.text);
+        }
+        return Ok(ParsedFile {
+            symbols,
+            texts,
+            ..ParsedFile::default()
+        });
     }
 
-    let lang = get_language(language)?;
-    let mut parser = Parser::new();
-    parser.set_language(&lang)?;
+    if get_language(language).is_err() {
+        return Err(ParseError::UnsupportedLanguage(language.to_string()));
+    }
+    let tree = parse_tree(language, source)
+        .map_err(failed)?
+        .ok_or_else(|| failed(anyhow::anyhow!("tree-sitter returned no tree")))?;
+
+    let (symbols, texts, references) =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let (mut symbols, texts, references) =
+                with_policy(policy, || extract_tree(&tree, source, language, file_path));
+            policy.strip_stopwords(&mut symbols);
+            apply_augmentors(tree.root_node(), source, language, augmentors, &mut symbols);
+            (symbols, texts, references)
+        }))
+        .map_err(|payload| ParseError::Panicked {
+            file: file_path.to_string(),
+            message: payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string()),
+        })?;
+
+    Ok(ParsedFile {
+        symbols,
+        texts,
+        references,
+        error_line: first_error_line(&tree),
+    })
+}
 
-    let tree = parser
-        .parse(source, None)
-        .ok_or_else(|| anyhow::anyhow!("failed to parse {file_path}"))?;
+/// 1-based line of the first ERROR or MISSING node, or None for a clean tree.
+fn first_error_line(tree: &Tree) -> Option<u32> {
+    let mut node = tree.root_node();
+    if !node.has_error() {
+        return None;
+    }
+    // Follow the first erroneous child down to the node that is the error
+    'descend: loop {
+        if node.is_error() || node.is_missing() {
+            break;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.has_error() {
+                node = child;
+                continue 'descend;
+            }
+        }
+        break;
+    }
+    Some(node.start_position().row as u32 + 1)
+}
 
+/// Run the language's extractor over a parsed tree.
+fn extract_tree(
+    tree: &Tree,
+    source: &[u8],
+    language: &str,
+    file_path: &str,
+) -> (Vec<SymbolEntry>, Vec<TextEntry>, Vec<ReferenceEntry>) {
     let mut symbols = Vec::new();
     let mut texts = Vec::new();
     let mut references = Vec::new();
@@ -50,7 +204,7 @@ pub fn parse_file(
     match language {
         #[cfg(feature = "lang-rust")]
         "rust" => crate::parser::rust_lang::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -60,7 +214,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-python")]
         "python" => crate::parser::python::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -70,7 +224,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-javascript")]
         "javascript" => crate::parser::javascript::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -80,7 +234,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-typescript")]
         "typescript" | "tsx" => crate::parser::typescript::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -90,7 +244,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-go")]
         "go" => crate::parser::go::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -100,7 +254,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-java")]
         "java" => crate::parser::java::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -110,7 +264,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-c")]
         "c" => crate::parser::c_lang::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -120,7 +274,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-cpp")]
         "cpp" => crate::parser::cpp::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -130,7 +284,7 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-ruby")]
         "ruby" => crate::parser::ruby::extract(
-            &tree,
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -140,7 +294,17 @@ pub fn parse_file(
 
         #[cfg(feature = "lang-csharp")]
         "csharp" => crate::parser::csharp::extract(
-            &tree,
+            tree,
+            source,
+            file_path,
+            &mut symbols,
+            &mut texts,
+            &mut references,
+        ),
+
+        #[cfg(feature = "lang-kotlin")]
+        "kotlin" => crate::parser::kotlin::extract(
+            tree,
             source,
             file_path,
             &mut symbols,
@@ -150,14 +314,17 @@ pub fn parse_file(
 
         _ => {
             // For unsupported languages, just extract comments and strings
-            extract_texts_generic(&tree, source, file_path, &mut texts);
+            extract_texts_generic(tree, source, file_path, &mut texts);
         }
     }
 
+    for text in &mut texts {
+        normalize_line_endings(&mut text.text);
+    }
     // Merge consecutive doc comments (/// lines) into single entries
     texts = merge_consecutive_texts(texts);
 
-    Ok((symbols, texts, references))
+    (symbols, texts, references)
 }
 
 // ---------------------------------------------------------------------------
@@ -254,6 +421,7 @@ fn parse_sfc(
     source: &[u8],
     extension: &str,
     file_path: &str,
+    policy: &FilterPolicy,
 ) -> Result<(Vec<SymbolEntry>, Vec<TextEntry>, Vec<ReferenceEntry>)> {
     let blocks = sfc::extract_script_blocks(source, extension);
 
@@ -268,8 +436,8 @@ fn parse_sfc(
     for block in &blocks {
         // Parse each script block with the detected language
         let (mut symbols, mut texts, mut refs) =
-            match parse_file(&block.content, block.lang, file_path) {
-                Ok(result) => result,
+            match parse_file_checked(&block.content, block.lang, file_path, &[], policy) {
+                Ok(parsed) => (parsed.symbols, parsed.texts, parsed.references),
                 Err(e) => {
                     tracing::warn!(
                         "failed to parse {} script block in {}: {}",
@@ -306,3 +474,189 @@ fn parse_sfc(
 
     Ok((all_symbols, all_texts, all_refs))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_parsers_interleave_languages() {
+        let rust = b"pub fn a() {}\npub struct B;\n";
+        let python = b"def c():\n    pass\n";
+        let names = |source: &[u8], language: &str| -> Vec<String> {
+            parse_file_checked(source, language, "f", &[], &FilterPolicy::default())
+                .unwrap()
+                .symbols
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+        let first = names(rust, "rust");
+        assert_eq!(names(python, "python"), vec!["c"]);
+        // A broken file does not leave state behind in the reused parser
+        assert!(
+            parse_file_checked(
+                b"fn broken( {\n",
+                "rust",
+                "f",
+                &[],
+                &FilterPolicy::default()
+            )
+            .unwrap()
+            .error_line
+            .is_some()
+        );
+        assert_eq!(names(rust, "rust"), first);
+        assert_eq!(first, vec!["a", "B"]);
+    }
+
+    /// Compare the per-file setup of a fresh `Parser` with the thread's cached one,
+    /// and the cost of a whole tiny-file parse each way.
+    /// Run with `cargo test --release -- --ignored bench_parser_setup --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_parser_setup_fresh_vs_cached() {
+        const FILES: u32 = 100_000;
+        let source = b"fn a() {}\n";
+        let time = |f: &dyn Fn()| {
+            let start = std::time::Instant::now();
+            for _ in 0..FILES {
+                f();
+            }
+            start.elapsed() / FILES
+        };
+
+        let fresh_setup = time(&|| {
+            let mut parser = Parser::new();
+            parser.set_language(&get_language("rust").unwrap()).unwrap();
+        });
+        let cached_setup = time(&|| {
+            PARSERS.with_borrow_mut(|parsers| {
+                if !parsers.contains_key("rust") {
+                    let mut parser = Parser::new();
+                    parser.set_language(&get_language("rust").unwrap()).unwrap();
+                    parsers.insert("rust".to_string(), parser);
+                }
+                assert!(parsers.get_mut("rust").is_some());
+            })
+        });
+        let fresh_parse = time(&|| {
+            let mut parser = Parser::new();
+            parser.set_language(&get_language("rust").unwrap()).unwrap();
+            parser.parse(source, None).unwrap();
+        });
+        let cached_parse = time(&|| {
+            parse_tree("rust", source).unwrap().unwrap();
+        });
+
+        println!(
+            "per file: setup {fresh_setup:?} fresh vs {cached_setup:?} cached; \
+             tiny-file parse {fresh_parse:?} fresh vs {cached_parse:?} cached"
+        );
+    }
+
+    #[test]
+    fn test_parse_file_checked_clean_source() {
+        let parsed = parse_file_checked(
+            b"fn a() {}\n",
+            "rust",
+            "src/lib.rs",
+            &[],
+            &FilterPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(parsed.error_line, None);
+        assert!(parsed.symbols.iter().any(|s| s.name == "a"));
+    }
+
+    #[test]
+    fn test_parse_file_checked_reports_error_line() {
+        let parsed = parse_file_checked(
+            b"fn a() {}\n\nfn b( {\n",
+            "rust",
+            "src/lib.rs",
+            &[],
+            &FilterPolicy::default(),
+        )
+        .unwrap();
+        assert_eq!(parsed.error_line, Some(3));
+        // Symbols before the error are still extracted
+        assert!(parsed.symbols.iter().any(|s| s.name == "a"));
+    }
+
+    #[test]
+    fn test_parse_file_checked_unsupported_language() {
+        let err =
+            parse_file_checked(b"", "cobol", "x.cbl", &[], &FilterPolicy::default()).unwrap_err();
+        assert_eq!(err, ParseError::UnsupportedLanguage("cobol".to_string()));
+    }
+
+    #[test]
+    fn test_crlf_matches_lf() {
+        let lf = "\
+# Settings loader
+def load(path):
+    \"\"\"Read settings.
+
+    Returns a dict.
+    \"\"\"
+    return {}  # nothing yet, see TODO
+";
+        let crlf = lf.replace('\n', "\r\n");
+        let parse = |source: &str| {
+            parse_file_checked(
+                source.as_bytes(),
+                "python",
+                "s.py",
+                &[],
+                &FilterPolicy::default(),
+            )
+            .unwrap()
+        };
+        let (lf, crlf) = (parse(lf), parse(&crlf));
+
+        let texts = |parsed: &ParsedFile| -> Vec<(String, [u32; 2], String)> {
+            parsed
+                .texts
+                .iter()
+                .map(|t| (t.kind.clone(), t.line, t.text.clone()))
+                .collect()
+        };
+        assert!(!lf.texts.is_empty());
+        assert_eq!(texts(&crlf), texts(&lf));
+        assert!(crlf.texts.iter().all(|t| !t.text.contains('\r')));
+
+        let lines = |parsed: &ParsedFile| -> Vec<[u32; 2]> {
+            parsed.symbols.iter().map(|s| s.line).collect()
+        };
+        assert_eq!(lines(&crlf), lines(&lf));
+    }
+
+    #[test]
+    fn test_filter_policy_overrides_builtins() {
+        let source = b"def run(rows):\n    helper(rows)\n    return map(str, filter(None, rows))\n";
+        let calls = |policy: &FilterPolicy| -> (Vec<String>, Option<String>) {
+            let parsed = parse_file_checked(source, "python", "run.py", &[], policy).unwrap();
+            let calls = parsed
+                .references
+                .into_iter()
+                .filter(|r| r.kind == "call")
+                .map(|r| r.name)
+                .collect();
+            (calls, parsed.symbols[0].tokens.clone())
+        };
+
+        let (default_calls, default_tokens) = calls(&FilterPolicy::default());
+        assert_eq!(default_calls, vec!["helper"]);
+        assert!(default_tokens.unwrap().contains("helper"));
+
+        let policy = FilterPolicy {
+            allow_references: ["map".to_string()].into(),
+            stopwords: ["helper".to_string()].into(),
+            ..FilterPolicy::default()
+        };
+        let (calls, tokens) = calls(&policy);
+        assert_eq!(calls, vec!["helper", "map"]);
+        assert!(!tokens.unwrap_or_default().contains("helper"));
+    }
+}
@@ -7,6 +7,7 @@
 use tree_sitter::{Node, Tree};
 
 use crate::index::format::{ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::filter::is_filtered;
 use crate::parser::helpers::*;
 use crate::parser::treesitter::MAX_DEPTH;
 
@@ -484,7 +485,7 @@ fn extract_call_ref(
     };
 
     let name = get_call_name(func, source);
-    if name.is_empty() || is_ts_builtin_call(&name) {
+    if name.is_empty() || is_filtered(&name, is_ts_builtin_call) {
         return;
     }
 
@@ -513,7 +514,7 @@ fn extract_new_ref(
     };
 
     let name = get_call_name(constructor, source);
-    if name.is_empty() || is_ts_builtin_call(&name) || is_ts_primitive_type(&name) {
+    if name.is_empty() || is_filtered(&name, is_ts_builtin_call) || is_ts_primitive_type(&name) {
         return;
     }
 
@@ -667,7 +668,7 @@ fn extract_function_decl(
     };
 
     let line = node_line_range(node);
-    let _sig = build_function_signature(node, source, &name);
+    let sig = build_function_signature(node, source, &name);
 
     let is_exported = node
         .parent()
@@ -701,6 +702,7 @@ fn extract_function_decl(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 
     // Recurse into function body with function name as context
@@ -745,7 +747,7 @@ fn extract_class(
     let visibility = if is_exported { "public" } else { "private" };
 
     let is_abstract = node.kind() == "abstract_class_declaration";
-    let _sig = build_class_signature(node, source, &name, is_abstract);
+    let sig = build_class_signature(node, source, &name, is_abstract);
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -775,6 +777,7 @@ fn extract_class(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 
     if let Some(body) = find_child_by_field(node, "body") {
@@ -864,7 +867,7 @@ fn extract_method(
     } else {
         format!("{} ", sig_parts.join(" "))
     };
-    let _sig = format!("{prefix}{name}{params}{return_type}");
+    let sig = format!("{prefix}{name}{params}{return_type}");
 
     let visibility = match access_modifier.as_deref() {
         Some("private") => "private",
@@ -893,6 +896,7 @@ fn extract_method(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 
     // Recurse into method body with method name as context
@@ -975,8 +979,15 @@ fn extract_variable_decl(
                     name
                 };
 
-                // Extract tokens from variable value
-                let tokens = value_node.and_then(|v| filter_ts_tokens(extract_tokens(v, source)));
+                // Extract tokens from variable value; constants also keep their literals
+                let tokens = value_node.and_then(|v| {
+                    let tokens = filter_ts_tokens(extract_tokens(v, source));
+                    if kind == "constant" {
+                        with_literal_tokens(tokens, v, source)
+                    } else {
+                        tokens
+                    }
+                });
 
                 push_symbol(
                     symbols,
@@ -988,6 +999,7 @@ fn extract_variable_decl(
                     tokens,
                     None,
                     Some(visibility.to_string()),
+                    None,
                 );
             }
         }
@@ -1028,6 +1040,7 @@ fn extract_import(
                             None,
                             Some(name.clone()),
                             Some("private".to_string()),
+                            None,
                         );
                         // Also add import reference
                         references.push(ReferenceEntry {
@@ -1059,6 +1072,7 @@ fn extract_import(
                                         None,
                                         alias,
                                         Some("private".to_string()),
+                                        None,
                                     );
                                     // Also add import reference
                                     references.push(ReferenceEntry {
@@ -1093,6 +1107,7 @@ fn extract_import(
                             None,
                             alias,
                             Some("private".to_string()),
+                            None,
                         );
                         // Also add import reference
                         references.push(ReferenceEntry {
@@ -1151,7 +1166,7 @@ fn extract_interface(
         .map(|n| format!(" extends {}", node_text(n, source)))
         .unwrap_or_default();
 
-    let _sig = format!("interface {name}{type_params}{extends}");
+    let sig = format!("interface {name}{type_params}{extends}");
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -1178,6 +1193,7 @@ fn extract_interface(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 
     // Walk interface body for method signatures and extract type refs
@@ -1205,6 +1221,7 @@ fn extract_interface(
                             None,
                             None,
                             Some("public".to_string()),
+                            None,
                         );
                     }
                     // Extract type refs from member type annotations
@@ -1251,7 +1268,7 @@ fn extract_type_alias(
         .map(|n| node_text(n, source))
         .unwrap_or_default();
 
-    let _sig = format!("type {name}{type_params}");
+    let sig = format!("type {name}{type_params}");
 
     let full_name = if let Some(parent) = parent_ctx {
         format!("{parent}.{name}")
@@ -1278,6 +1295,7 @@ fn extract_type_alias(
         tokens,
         None,
         Some(visibility.to_string()),
+        Some(sig),
     );
 }
 
@@ -1316,6 +1334,7 @@ fn extract_enum(
         None,
         None,
         Some(visibility.to_string()),
+        None,
     );
 }
 
@@ -1358,6 +1377,7 @@ fn extract_namespace(
         None,
         None,
         Some(visibility.to_string()),
+        None,
     );
 
     // Recurse into namespace body
@@ -1490,6 +1510,13 @@ async function fetch(): Promise<Data> {
 
         let greet = find_sym(&symbols, "greet");
         assert_eq!(greet.kind, "function");
+        assert!(
+            greet
+                .sig
+                .as_deref()
+                .unwrap()
+                .contains("greet(name: string)")
+        );
         // Token extraction is enabled (may be None if body has no tokens after filtering)
 
         let fetch_fn = find_sym(&symbols, "fetch");
@@ -1602,6 +1629,20 @@ export class Worker extends Base {
         assert_eq!(helper.parent.as_deref(), Some("Utils"));
     }
 
+    #[test]
+    fn test_ts_constant_literal_tokens() {
+        let source = b"export const TIMEOUT_MS: number = 30_000;
+export const ENDPOINTS = { users: `/api/users`, health: \"/healthz\" } as const;";
+        let (symbols, _texts, _refs) = parse_file(source, "typescript", "test.ts").unwrap();
+
+        let timeout = find_sym(&symbols, "TIMEOUT_MS");
+        assert_eq!(timeout.kind, "constant");
+        assert_eq!(timeout.tokens.as_deref(), Some("30_000"));
+
+        let endpoints = find_sym(&symbols, "ENDPOINTS").tokens.as_deref().unwrap();
+        assert!(endpoints.ends_with("/api/users /healthz"));
+    }
+
     #[test]
     fn test_ts_imports() {
         let source = b"import React from 'react';
@@ -0,0 +1,189 @@
+//! Public API surface comparison between two projects or index snapshots.
+//!
+//! The API surface is the set of public, non-import symbols. Symbols are keyed
+//! by qualified name (`Class.method`); a name present on both sides whose
+//! kind or signature differs is reported as changed.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::index::format::SymbolEntry;
+
+/// One symbol of an API surface, as reported in a diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiSymbol {
+    pub name: String,
+    pub kind: String,
+    pub file: String,
+    pub line: [u32; 2],
+}
+
+/// A symbol whose kind or signature differs between the two sides.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ApiChange {
+    pub name: String,
+    pub before: String,
+    pub after: String,
+    pub file: String,
+    pub line: [u32; 2],
+}
+
+/// Result of comparing a baseline API surface against a current one.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ApiDiff {
+    pub added: Vec<ApiSymbol>,
+    pub removed: Vec<ApiSymbol>,
+    pub changed: Vec<ApiChange>,
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Whether a symbol belongs to the public API surface.
+pub fn is_api_symbol(symbol: &SymbolEntry) -> bool {
+    symbol.visibility.as_deref() == Some("public") && symbol.kind != "import"
+}
+
+/// Comparable signature of a symbol: its kind and declaration text
+/// (`method: pub fn send(&self)`), or its kind alone for symbols without one
+/// (constants, enum members, ...). The kind is always part of it, so a symbol
+/// that keeps its text but changes kind is reported too.
+fn api_signature(symbol: &SymbolEntry) -> String {
+    match &symbol.sig {
+        Some(sig) => format!("{}: {}", symbol.kind, sig),
+        None => symbol.kind.clone(),
+    }
+}
+
+/// Group API symbols by qualified name.
+fn surface(symbols: &[SymbolEntry]) -> BTreeMap<&str, Vec<&SymbolEntry>> {
+    let mut by_name: BTreeMap<&str, Vec<&SymbolEntry>> = BTreeMap::new();
+    for symbol in symbols.iter().filter(|s| is_api_symbol(s)) {
+        by_name.entry(&symbol.name).or_default().push(symbol);
+    }
+    by_name
+}
+
+/// Diff two API surfaces. Non-API symbols in either input are ignored.
+///
+/// Names defined more than once (overloads, `cfg`-gated variants) compare by the
+/// sorted set of their signatures, and are reported once.
+pub fn diff_api(before: &[SymbolEntry], after: &[SymbolEntry]) -> ApiDiff {
+    let signatures = |symbols: &[&SymbolEntry]| {
+        let mut sigs: Vec<String> = symbols.iter().map(|s| api_signature(s)).collect();
+        sigs.sort();
+        sigs.dedup();
+        sigs.join(" | ")
+    };
+    let to_api = |symbol: &SymbolEntry| ApiSymbol {
+        name: symbol.name.clone(),
+        kind: symbol.kind.clone(),
+        file: symbol.file.clone(),
+        line: symbol.line,
+    };
+
+    let before = surface(before);
+    let after = surface(after);
+    let mut diff = ApiDiff::default();
+
+    for (name, old) in &before {
+        match after.get(name) {
+            None => diff.removed.push(to_api(old[0])),
+            Some(new) => {
+                let (old_sig, new_sig) = (signatures(old), signatures(new));
+                if old_sig != new_sig {
+                    diff.changed.push(ApiChange {
+                        name: name.to_string(),
+                        before: old_sig,
+                        after: new_sig,
+                        file: new[0].file.clone(),
+                        line: new[0].line,
+                    });
+                }
+            }
+        }
+    }
+    for (name, new) in &after {
+        if !before.contains_key(name) {
+            diff.added.push(to_api(new[0]));
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, kind: &str, visibility: &str) -> SymbolEntry {
+        SymbolEntry {
+            file: "src/lib.rs".to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: [1, 3],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: Some(visibility.to_string()),
+            project: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_api() {
+        let before = vec![
+            symbol("Client", "struct", "public"),
+            symbol("Client.retry", "method", "public"),
+            symbol("helper", "function", "private"),
+            symbol("Config", "struct", "public"),
+        ];
+        let mut send = symbol("Client.send", "method", "public");
+        send.sig = Some("pub fn send(&self, body: &[u8], timeout: Duration)".to_string());
+        let mut old_send = send.clone();
+        old_send.sig = Some("pub fn send(&self, body: &[u8])".to_string());
+
+        let before = [before, vec![old_send]].concat();
+        let after = vec![
+            symbol("Client", "struct", "public"),
+            send,
+            symbol("Client.connect", "method", "public"),
+            symbol("helper", "function", "public"),
+            symbol("Config", "interface", "public"),
+            symbol("std.fmt", "import", "public"),
+        ];
+
+        let diff = diff_api(&before, &after);
+        let names = |symbols: &[ApiSymbol]| -> Vec<String> {
+            symbols.iter().map(|s| s.name.clone()).collect()
+        };
+        // Becoming public counts as an addition; imports are never API
+        assert_eq!(names(&diff.added), vec!["Client.connect", "helper"]);
+        assert_eq!(names(&diff.removed), vec!["Client.retry"]);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].name, "Client.send");
+        assert_eq!(
+            diff.changed[0].before,
+            "method: pub fn send(&self, body: &[u8])"
+        );
+        assert_eq!(diff.changed[1].name, "Config");
+        assert_eq!(diff.changed[1].before, "struct");
+        assert_eq!(diff.changed[1].after, "interface");
+
+        assert!(diff_api(&before, &before).is_empty());
+
+        // Same text, different kind
+        let mut alias = symbol("Handle", "struct", "public");
+        alias.sig = Some("pub Handle".to_string());
+        let mut as_type = alias.clone();
+        as_type.kind = "type".to_string();
+        let diff = diff_api(&[alias], &[as_type]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before, "struct: pub Handle");
+        assert_eq!(diff.changed[0].after, "type: pub Handle");
+    }
+}
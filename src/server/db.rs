@@ -1,8 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use anyhow::{Context, Result};
-use rusqlite::Connection;
-use serde::Serialize;
+use rmcp::schemars::{self, JsonSchema};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
 
 use crate::index::format::{FileEntry, ReferenceEntry, SymbolEntry, TextEntry};
+use crate::parser::helpers::split_identifier;
+use crate::server::imports;
+use crate::server::kinds::canonical_kind;
+use crate::utils::fuzzy::{edit_distance, max_edit_distance};
+use crate::utils::test_files::is_test_path;
 
 /// Convert visibility string to integer level for filtering.
 ///
@@ -47,124 +59,502 @@ pub enum SearchResult {
     Text(TextEntry),
 }
 
-/// An in-memory SQLite database with FTS5 virtual tables for fast text search
-/// over the code index.
+/// Order of `search` results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchOrder {
+    /// BM25 rank, exact name matches first (file order for an empty query)
+    #[default]
+    Relevance,
+    /// File path, then line
+    Path,
+    /// Name (case-insensitive), then file path and line
+    Name,
+    /// Line, then file path
+    Line,
+}
+
+/// Filters shared by every search entry point (`search`, `search_count`,
+/// `search_fuzzy`, `search_regex`). Empty lists and `None` filter nothing.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    /// Types to search ("symbol", "file", "text"). Empty = all
+    pub scope: Vec<String>,
+    /// Symbol kind, text kind, or file lang
+    pub kind: Vec<String>,
+    /// Language of the containing file (applies to every result type)
+    pub lang: Vec<String>,
+    /// File path, exact or a GLOB pattern with `*`
+    pub path: Option<String>,
+    /// Drop results whose file path matches any of these GLOB patterns
+    pub exclude_paths: Vec<String>,
+    /// Keep results from test files (see `FileEntry::is_test`)
+    pub include_tests: bool,
+    /// Keep results from any of these projects. Empty = all
+    pub projects: Vec<String>,
+    /// Minimum visibility level for symbols ("public", "internal", or "private"/None)
+    pub visibility: Option<String>,
+}
+
+/// A `search` request: the FTS5 query, its filters, matching options and the page.
+///
+/// Built from the MCP `SearchParams`, which the REPL and the HTTP endpoint
+/// also go through (see `SearchParams::to_query`).
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    /// FTS5 search query (supports * wildcards)
+    pub query: String,
+    pub filters: SearchFilters,
+    /// Only keep rows whose name/content contains the query terms with exact casing
+    pub case_sensitive: bool,
+    /// Only keep rows where bare terms match whole identifiers (`map` does not
+    /// match `unordered_map`); prefix terms (`parse*`) must start an identifier
+    pub whole_word: bool,
+    /// Only keep symbols/files whose name matches this regex (texts have no
+    /// name and are dropped). With an empty query, scans the symbols table instead of FTS
+    pub name_regex: Option<String>,
+    /// BM25 weights for the (name, file, content) columns. None = (3.0, 2.0, 1.0)
+    pub weights: Option<(f64, f64, f64)>,
+    /// Result order; `Relevance` ranks by BM25, the others sort by
+    /// path, name or line for output that is the same on every run
+    pub order_by: SearchOrder,
+    /// Fill `SearchPage::matches` with why each result matched (see `MatchInfo`)
+    pub explain: bool,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+impl SearchQuery {
+    /// A query with no filters, the first 100 results by relevance.
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            filters: SearchFilters::default(),
+            case_sensitive: false,
+            whole_word: false,
+            name_regex: None,
+            weights: None,
+            order_by: SearchOrder::Relevance,
+            explain: false,
+            limit: 100,
+            offset: 0,
+        }
+    }
+}
+
+/// Filters for `get_callers` and `get_callees`. Empty lists and `None` filter nothing.
+#[derive(Debug, Clone, Default)]
+pub struct RefFilters {
+    /// Reference kinds to keep (e.g. `call` and `instantiation`, leaving out `import` noise)
+    pub kinds: Vec<String>,
+    /// Keep references from any of these projects
+    pub projects: Vec<String>,
+    /// Only references to symbols at this visibility level or higher
+    pub visibility: Option<String>,
+    /// Collapse references sharing `(file, caller, name)` into the first one by
+    /// line (see `distinct_ref_condition`)
+    pub distinct_by_caller: bool,
+}
+
+/// Files before symbols before texts, for ties in the FTS sort keys.
+const FTS_TYPE_RANK: &str = "CASE type WHEN 'file' THEN 0 WHEN 'symbol' THEN 1 ELSE 2 END";
+
+/// Start line of an FTS row (files start at 0), looked up in its source table.
+const FTS_LINE: &str = "CASE type \
+     WHEN 'symbol' THEN (SELECT line_start FROM symbols WHERE symbols.rowid = rowid_ref) \
+     WHEN 'text' THEN (SELECT line_start FROM texts WHERE texts.rowid = rowid_ref) \
+     ELSE 0 END";
+
+impl SearchOrder {
+    /// ORDER BY keys over `search_fts` columns; empty for `Relevance`,
+    /// which ranks with BM25 instead. Ties end on the row itself, so the
+    /// order is the same on every run.
+    fn sort_keys(self) -> &'static [&'static str] {
+        match self {
+            SearchOrder::Relevance => &[],
+            SearchOrder::Path => &["path", FTS_LINE, FTS_TYPE_RANK, "rowid_ref"],
+            SearchOrder::Name => &["lower(name)", "path", FTS_LINE, FTS_TYPE_RANK, "rowid_ref"],
+            SearchOrder::Line => &[FTS_LINE, "path", FTS_TYPE_RANK, "rowid_ref"],
+        }
+    }
+}
+
+impl std::str::FromStr for SearchOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "relevance" => Ok(SearchOrder::Relevance),
+            "path" => Ok(SearchOrder::Path),
+            "name" => Ok(SearchOrder::Name),
+            "line" => Ok(SearchOrder::Line),
+            _ => Err(format!(
+                "invalid order '{}', expected 'relevance', 'path', 'name' or 'line'",
+                s
+            )),
+        }
+    }
+}
+
+/// Compare two symbols as `order_by` sorts FTS rows (`Relevance` keeps them equal).
+fn compare_symbols(a: &SymbolEntry, b: &SymbolEntry, order_by: SearchOrder) -> std::cmp::Ordering {
+    match order_by {
+        SearchOrder::Relevance => std::cmp::Ordering::Equal,
+        SearchOrder::Path => (&a.file, a.line[0]).cmp(&(&b.file, b.line[0])),
+        SearchOrder::Name => (a.name.to_lowercase(), &a.file, a.line[0]).cmp(&(
+            b.name.to_lowercase(),
+            &b.file,
+            b.line[0],
+        )),
+        SearchOrder::Line => (a.line[0], &a.file).cmp(&(b.line[0], &b.file)),
+    }
+}
+
+impl SearchResult {
+    /// Project the result belongs to.
+    pub fn project(&self) -> &str {
+        match self {
+            SearchResult::Symbol(s) => &s.project,
+            SearchResult::File(f) => &f.project,
+            SearchResult::Text(t) => &t.project,
+        }
+    }
+}
+
+/// Split relevance-ordered results into per-project groups.
+///
+/// Projects come in the order of their first, i.e. best-ranked, result;
+/// each group keeps the original result order.
+pub fn group_by_project<T>(results: Vec<T>, project: impl Fn(&T) -> &str) -> Vec<(String, Vec<T>)> {
+    let mut groups: Vec<(String, Vec<T>)> = Vec::new();
+    let mut index: BTreeMap<String, usize> = BTreeMap::new();
+    for result in results {
+        let name = project(&result).to_string();
+        let i = *index.entry(name.clone()).or_insert_with(|| {
+            groups.push((name, Vec::new()));
+            groups.len() - 1
+        });
+        groups[i].1.push(result);
+    }
+    groups
+}
+
+/// A reference reached by `get_callers_transitive`, with the level it was found at
+/// (1 = direct caller of the starting symbol).
+#[derive(Debug, Clone, Serialize)]
+pub struct CallTreeEntry {
+    pub depth: u32,
+    #[serde(flatten)]
+    pub reference: ReferenceEntry,
+}
+
+/// A symbol of a file outline, with the symbols nested under it (by `parent`),
+/// in line order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineNode {
+    #[serde(flatten)]
+    pub symbol: SymbolEntry,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineNode>,
+}
+
+/// A directory of `explore_tree`, with its subdirectories nested under it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExploreNode {
+    /// Directory path relative to the project root ("." for the root)
+    pub path: String,
+    /// Files directly in this directory per language ("other" when unknown)
+    pub langs: BTreeMap<String, usize>,
+    /// Names of up to `cap` files directly in this directory
+    pub files: Vec<String>,
+    /// Files directly in this directory left out of `files`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub more_files: usize,
+    /// Subdirectories exist below `depth` but are not listed
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ExploreNode>,
+}
+
+impl ExploreNode {
+    /// The child directory at `path`, created if missing.
+    fn child_mut(&mut self, path: String) -> &mut ExploreNode {
+        let index = match self.children.iter().position(|c| c.path == path) {
+            Some(index) => index,
+            None => {
+                self.children.push(ExploreNode {
+                    path,
+                    ..Default::default()
+                });
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[index]
+    }
+
+    fn sort(&mut self) {
+        self.children.sort_by(|a, b| a.path.cmp(&b.path));
+        self.children.iter_mut().for_each(ExploreNode::sort);
+    }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// One page of search results and the total number of matches across all pages.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    pub total: usize,
+    /// Why each result matched, in the same order as `results` (only with `explain`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<MatchInfo>,
+}
+
+/// Why a search result matched (for `search` with `explain`).
+///
+/// Scores are BM25 as ranked by FTS5: lower (more negative) is better. The
+/// per-column scores rank the row on that column alone, so they do not add
+/// up to `score`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchInfo {
+    pub score: f64,
+    pub name_score: f64,
+    pub file_score: f64,
+    pub content_score: f64,
+    /// Column with the best subscore: "name", "file" or "content"
+    pub matched_column: String,
+    /// Matched fragment of the content column, terms wrapped in `«»`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+/// Index metrics for one project (for the `stats` tool).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectStats {
+    pub project: String,
+    pub files: u64,
+    pub symbols: u64,
+    pub texts: u64,
+    pub refs: u64,
+    /// Total lines across the project's indexed files
+    pub lines: u64,
+    /// Symbol counts per kind, most frequent first
+    pub symbols_by_kind: Vec<(String, u64)>,
+    /// Languages present, sorted (like the manifest's `languages`)
+    pub languages: Vec<String>,
+    /// Per-language file and line counts, most files first
+    pub files_by_language: Vec<LanguageStats>,
+}
+
+/// File and line counts for one language.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageStats {
+    pub lang: String,
+    pub files: u64,
+    pub lines: u64,
+}
+
+/// A file with the time it was last written to the index (for the `recent` tool).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentFile {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub project: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    pub lines: u32,
+    /// Milliseconds since the Unix epoch
+    pub indexed_at: u64,
+}
+
+/// Line shift from which a symbol present on both sides counts as moved (for `symbol_diff`).
+pub const SYMBOL_MOVE_THRESHOLD: u32 = 10;
+
+/// A symbol added, removed, or moved between a baseline index and the live one
+/// (for the `symbol_diff` tool).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolDelta {
+    /// "added", "removed" or "moved"
+    pub change: String,
+    pub file: String,
+    pub name: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    /// Line range in the live index, or in the baseline for a removed symbol
+    pub line: [u32; 2],
+    /// Line range in the baseline of a moved symbol
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<[u32; 2]>,
+}
+
+/// Schema version stored in `PRAGMA user_version` of on-disk databases.
+/// Bump whenever the tables below change so stale files are rebuilt.
+const SCHEMA_VERSION: i32 = 8;
+
+/// Marks where the identifier sub-tokens start in an FTS `content` value
+/// (see `identifier_subtokens`). The tokenizer treats it as a separator.
+const SUBTOKEN_SEPARATOR: char = '\u{1f}';
+
+/// How long a search may run before it is interrupted (see [`SearchDb::with_timeout`]).
+pub const DEFAULT_SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An SQLite database with FTS5 virtual tables for fast text search over the
+/// code index. Lives in memory, or on disk when opened with [`SearchDb::open_file`].
 pub struct SearchDb {
     conn: Connection,
     /// Whether FTS5 virtual tables are enabled. Disabled in build mode to save memory.
     fts_enabled: bool,
+    /// Whether the FTS5 tables use the trigram tokenizer (see [`SearchDb::new_trigram`]).
+    trigram: bool,
+    /// Limit of a search run through [`SearchDb::with_timeout`]; `None` waits forever.
+    search_timeout: Option<Duration>,
+    /// Exclusive flock held for an on-disk database (see [`SearchDb::open_file`]).
+    /// Declared last so the connection closes before the lock is released.
+    _file_lock: Option<std::fs::File>,
 }
 
 impl SearchDb {
     /// Create a new in-memory database with FTS5 enabled (for serve mode).
     pub fn new() -> Result<Self> {
-        Self::new_internal(true)
+        Self::new_internal(true, false)
     }
 
     /// Create a new in-memory database without FTS5 (for build mode).
     /// This significantly reduces memory usage for large repositories.
     pub fn new_no_fts() -> Result<Self> {
-        Self::new_internal(false)
+        Self::new_internal(false, false)
     }
 
-    /// Internal constructor with configurable FTS support.
-    fn new_internal(fts_enabled: bool) -> Result<Self> {
-        let conn = Connection::open_in_memory()?;
-
-        // Content tables (store the actual data for retrieval)
-        conn.execute_batch(
-            "
-            CREATE TABLE files (
-                project     TEXT NOT NULL,
-                path        TEXT NOT NULL,
-                parent_path TEXT NOT NULL,
-                lang        TEXT,
-                hash        TEXT NOT NULL,
-                lines       INTEGER NOT NULL,
-                title       TEXT,
-                description TEXT,
-                PRIMARY KEY (project, path)
-            );
-            CREATE INDEX idx_files_parent ON files (project, parent_path);
-
-            CREATE TABLE symbols (
-                project       TEXT NOT NULL,
-                file       TEXT NOT NULL,
-                name       TEXT NOT NULL,
-                kind       TEXT NOT NULL,
-                line_start INTEGER NOT NULL,
-                line_end   INTEGER NOT NULL,
-                parent     TEXT,
-                tokens     TEXT,
-                alias      TEXT,
-                visibility TEXT,
-                visibility_level INTEGER NOT NULL DEFAULT 3
-            );
+    /// Create a new in-memory database whose FTS5 table uses the trigram
+    /// tokenizer (for `serve --substring`).
+    ///
+    /// Terms then match anywhere inside a word (`uthenticat` finds
+    /// `authenticate`), but a term shorter than three characters matches nothing.
+    pub fn new_trigram() -> Result<Self> {
+        Self::new_internal(true, true)
+    }
 
-            CREATE TABLE texts (
-                project       TEXT NOT NULL,
-                file       TEXT NOT NULL,
-                kind       TEXT NOT NULL,
-                line_start INTEGER NOT NULL,
-                line_end   INTEGER NOT NULL,
-                text       TEXT NOT NULL,
-                parent     TEXT
-            );
+    /// Open (or create) an on-disk database with FTS5 enabled.
+    ///
+    /// An existing file is reused as-is when its schema version matches, so
+    /// previously indexed files only need re-parsing if their hash changed.
+    /// A file written by another schema version is discarded and recreated.
+    ///
+    /// The database is held under an exclusive flock on `<path>.lock` for as
+    /// long as it is open; returns `None` when another process holds it.
+    pub fn open_file(path: &Path) -> Result<Option<Self>> {
+        let lock_path = path.with_extension("sqlite.lock");
+        let lock = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| format!("failed to open lock file {}", lock_path.display()))?;
+        if fs2::FileExt::try_lock_exclusive(&lock).is_err() {
+            return Ok(None);
+        }
 
-            CREATE TABLE refs (
-                project       TEXT NOT NULL,
-                file          TEXT NOT NULL,
-                name          TEXT NOT NULL,
-                kind          TEXT NOT NULL,
-                line_start    INTEGER NOT NULL,
-                line_end      INTEGER NOT NULL,
-                caller        TEXT
-            );
+        let conn = open_connection(path)?;
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version == SCHEMA_VERSION {
+            return Ok(Some(Self {
+                conn,
+                fts_enabled: true,
+                trigram: false,
+                search_timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+                _file_lock: Some(lock),
+            }));
+        }
 
-            -- Indexes for exact lookups
-            CREATE INDEX idx_symbols_project_file ON symbols(project, file);
-            CREATE INDEX idx_symbols_project_file_parent ON symbols(project, file, parent);
-            CREATE INDEX idx_symbols_project_file_kind ON symbols(project, file, kind);
-            CREATE INDEX idx_symbols_visibility ON symbols(project, visibility_level);
-            CREATE INDEX idx_texts_project_file ON texts(project, file);
-            CREATE INDEX idx_files_project ON files(project);
-            CREATE INDEX idx_symbols_project ON symbols(project);
-            CREATE INDEX idx_texts_project ON texts(project);
-
-            -- Indexes for reference queries
-            CREATE INDEX idx_refs_project_name ON refs(project, name);
-            CREATE INDEX idx_refs_project_caller ON refs(project, caller);
-            CREATE INDEX idx_refs_project_file ON refs(project, file);
-            CREATE INDEX idx_refs_project_name_kind ON refs(project, name, kind);
-            ",
-        )
-        .context("failed to create database schema")?;
-
-        // Unified FTS5 virtual table for full-text search (only when enabled)
-        // Three searchable columns with BM25 weighting: name (3x), file (2x), content (1x)
-        if fts_enabled {
-            conn.execute_batch(
-                "
-                CREATE VIRTUAL TABLE search_fts USING fts5(
-                    name,               -- symbol/file name (highest weight)
-                    file,               -- file path (medium weight)
-                    content,            -- tokens, docstrings, etc. (lower weight)
-                    type UNINDEXED,     -- 'symbol', 'file', 'text'
-                    rowid_ref UNINDEXED,-- rowid in source table
-                    path UNINDEXED,     -- file path (for GLOB filtering)
-                    kind UNINDEXED,     -- symbol/text kind, or file lang
-                    project UNINDEXED,  -- project filter
-                    visibility_level UNINDEXED -- 1=public, 2=internal, 3=private (0 for files/texts)
-                );
-                ",
-            )
-            .context("failed to create FTS5 table")?;
+        drop(conn);
+        for stale in [
+            path.to_path_buf(),
+            path.with_extension("sqlite-wal"),
+            path.with_extension("sqlite-shm"),
+        ] {
+            if stale.exists() {
+                std::fs::remove_file(&stale)
+                    .with_context(|| format!("failed to remove {}", stale.display()))?;
+            }
         }
 
-        Ok(Self { conn, fts_enabled })
+        let conn = open_connection(path)?;
+        create_schema(&conn, true)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(Some(Self {
+            conn,
+            fts_enabled: true,
+            trigram: false,
+            search_timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+            _file_lock: Some(lock),
+        }))
+    }
+
+    /// Internal constructor with configurable FTS support.
+    fn new_internal(fts_enabled: bool, trigram: bool) -> Result<Self> {
+        let conn = Connection::open_in_memory()?;
+        register_functions(&conn)?;
+        create_schema(&conn, fts_enabled)?;
+        Ok(Self {
+            conn,
+            fts_enabled,
+            trigram,
+            search_timeout: Some(DEFAULT_SEARCH_TIMEOUT),
+            _file_lock: None,
+        })
+    }
+
+    /// Interrupt searches run through [`SearchDb::with_timeout`] after `timeout`
+    /// (`None` disables the limit).
+    pub fn set_search_timeout(&mut self, timeout: Option<Duration>) {
+        self.search_timeout = timeout;
+    }
+
+    /// Run `f` (typically a search) under the search timeout.
+    ///
+    /// A watchdog thread holds the connection's interrupt handle and calls
+    /// `sqlite3_interrupt` once the timeout elapses, which makes the running
+    /// statement fail; that failure is reported as a timeout error. The caller
+    /// keeps the database lock throughout, so nothing else can be interrupted.
+    ///
+    /// The search entry points (`search`, `search_grouped`, `search_paged`,
+    /// `search_fuzzy`, `search_regex`, `search_count`) run under it themselves,
+    /// so MCP, the REPL and HTTP all get the same limit.
+    pub fn with_timeout<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        let Some(timeout) = self.search_timeout else {
+            return f(self);
+        };
+        let interrupt = self.conn.get_interrupt_handle();
+        let (done_tx, done_rx) = mpsc::channel::<()>();
+        let watchdog = std::thread::spawn(move || {
+            let timed_out = done_rx.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+            if timed_out {
+                interrupt.interrupt();
+            }
+            timed_out
+        });
+
+        let result = f(self);
+        drop(done_tx); // wakes the watchdog if it is still waiting
+        let timed_out = watchdog.join().unwrap_or(false);
+        match result {
+            Err(_) if timed_out => Err(anyhow::anyhow!(
+                "search timed out after {:.1}s; narrow the query (fewer OR terms or prefix wildcards)",
+                timeout.as_secs_f64()
+            )),
+            result => result,
+        }
     }
 
     /// Load index data into the database for a specific project.
+    ///
+    /// C++ declarations that have a definition in a source file are flagged so
+    /// search leaves them out (see [`mark_superseded_declarations`]).
     pub fn load(
         &self,
         project: &str,
@@ -174,11 +564,12 @@ impl SearchDb {
         references: &[ReferenceEntry],
     ) -> Result<()> {
         let tx = self.conn.unchecked_transaction()?;
+        let indexed_at = unix_millis(SystemTime::now());
 
         // Insert files
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO files (project, path, parent_path, lang, hash, lines, title, description) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                "INSERT INTO files (project, path, parent_path, lang, hash, lines, title, description, is_test, indexed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             )?;
             for f in files {
                 let parent_path = match f.path.rfind('/') {
@@ -193,7 +584,10 @@ impl SearchDb {
                     f.hash,
                     f.lines,
                     f.title,
-                    f.description
+                    f.description,
+                    // Indexes written before the flag existed are classified on load
+                    f.is_test || is_test_path(&f.path),
+                    indexed_at
                 ])?;
             }
         }
@@ -201,8 +595,8 @@ impl SearchDb {
         // Insert symbols
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO symbols (project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, visibility_level)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                "INSERT INTO symbols (project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, visibility_level, sig)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             )?;
             for s in symbols {
                 stmt.execute(rusqlite::params![
@@ -217,6 +611,7 @@ impl SearchDb {
                     s.alias,
                     s.visibility,
                     visibility_to_level(s.visibility.as_deref()),
+                    s.sig,
                 ])?;
             }
         }
@@ -247,54 +642,17 @@ impl SearchDb {
             }
         }
 
-        // Populate unified FTS5 index from content tables (only when FTS enabled)
-        // BM25 weights: name (3x), file (2x), content (1x)
+        #[cfg(feature = "lang-cpp")]
+        if symbols
+            .iter()
+            .any(|s| crate::parser::cpp::is_header_or_source(&s.file))
+        {
+            mark_superseded_declarations(&tx, project, None)?;
+        }
+
+        // Populate this project's FTS5 table only (only when FTS enabled)
         if self.fts_enabled {
-            tx.execute_batch(
-                "
-                -- Insert files: name=title, file=path, content=description
-                INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-                SELECT
-                    COALESCE(title, ''),
-                    COALESCE(path, ''),
-                    COALESCE(description, ''),
-                    'file',
-                    rowid,
-                    path,
-                    lang,
-                    project,
-                    0
-                FROM files;
-
-                -- Insert symbols: name=symbol name, file=path, content=kind + tokens
-                INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-                SELECT
-                    COALESCE(name, ''),
-                    COALESCE(file, ''),
-                    COALESCE(kind, '') || ' ' || COALESCE(tokens, ''),
-                    'symbol',
-                    rowid,
-                    file,
-                    kind,
-                    project,
-                    visibility_level
-                FROM symbols;
-
-                -- Insert texts: name=empty, file=path, content=text
-                INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-                SELECT
-                    '',
-                    COALESCE(file, ''),
-                    COALESCE(text, ''),
-                    'text',
-                    rowid,
-                    file,
-                    kind,
-                    project,
-                    0
-                FROM texts;
-                ",
-            )?;
+            populate_fts(&tx, project, self.trigram)?;
         }
 
         tx.commit()?;
@@ -303,141 +661,274 @@ impl SearchDb {
 
     /// Unified search across symbols, files, and texts.
     ///
-    /// Parameters:
-    /// - query: FTS5 search query (supports * wildcards)
-    /// - scope: Types to search ("symbol", "file", "text"). Empty = all.
-    /// - kind: Filter by kind (symbol kind, text kind, or file lang)
-    /// - path: Filter by file path (supports GLOB patterns with *)
-    /// - project: Filter by project
-    /// - visibility: Minimum visibility level for symbols ("public", "internal", or "private"/None)
-    /// - limit: Max results (default 100)
-    /// - offset: Pagination offset
+    /// See [`SearchQuery`] and [`SearchFilters`] for the parameters.
     ///
-    /// Returns results ordered by BM25 relevance.
+    /// With `Relevance`, an empty (or whitespace-only) query enumerates every
+    /// row matching the filters, in file order.
     ///
     /// The visibility filter only applies to symbol results (files and texts pass through).
     /// Filtering is done directly in the FTS5 query using the visibility column.
-    #[allow(clippy::too_many_arguments)]
-    pub fn search(
-        &self,
-        query: &str,
-        scope: &[String],
-        kind: &[String],
-        path: Option<&str>,
-        project: Option<&str>,
-        visibility: Option<&str>,
-        limit: u32,
-        offset: u32,
-    ) -> Result<Vec<SearchResult>> {
-        // Build FTS5 MATCH expression (searches all columns: name, file, content)
-        let fts_query = fts5_quote(query);
+    pub fn search(&self, query: &SearchQuery) -> Result<Vec<SearchResult>> {
+        self.search_paged(query).map(|page| page.results)
+    }
 
-        // Build WHERE clause for filters
-        let mut conditions = vec!["search_fts MATCH ?1".to_string()];
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(fts_query)];
+    /// Same as `search`, with the results grouped by project.
+    ///
+    /// Groups are ordered by their best BM25 hit (see `group_by_project`), so
+    /// a plain map keyed by project name would lose the ranking. `limit` and
+    /// `offset` apply to the results as a whole, not per project.
+    pub fn search_grouped(&self, query: &SearchQuery) -> Result<Vec<(String, Vec<SearchResult>)>> {
+        let results = self.search(query)?;
+        Ok(group_by_project(results, SearchResult::project))
+    }
 
-        // Scope filter (type)
-        if !scope.is_empty() {
-            let placeholders: Vec<String> = scope
-                .iter()
-                .enumerate()
-                .map(|(i, _)| format!("?{}", i + 2))
-                .collect();
-            conditions.push(format!("type IN ({})", placeholders.join(", ")));
-            for s in scope {
-                params.push(Box::new(s.clone()));
-            }
-        }
+    /// Same as `search`, plus the total number of matches before LIMIT/OFFSET.
+    ///
+    /// The FTS5 tables of the projects searched are queried together (see
+    /// [`fts_union`]). With one table, the total comes from a `COUNT(*) OVER ()`
+    /// window in the same statement, and only a page past the last match needs a
+    /// separate count (it has no row to carry the window value). With several,
+    /// each contributes only its best `offset + limit` rows to the merged page,
+    /// and the total is counted apart unless a short first page holds every match.
+    /// With `case_sensitive`/`whole_word`/`name_regex`, rows are checked after the
+    /// FTS match, so every candidate is scanned to page and count.
+    /// With `explain`, `matches` says why each result matched (see `MatchInfo`);
+    /// an empty query matches nothing to explain and leaves it empty.
+    pub fn search_paged(&self, search: &SearchQuery) -> Result<SearchPage> {
+        self.with_timeout(|db| db.search_paged_untimed(search))
+    }
 
-        // Kind filter
-        if !kind.is_empty() {
-            let start_param = params.len() + 1;
-            let placeholders: Vec<String> = kind
-                .iter()
-                .enumerate()
-                .map(|(i, _)| format!("?{}", start_param + i))
-                .collect();
-            conditions.push(format!("kind IN ({})", placeholders.join(", ")));
-            for k in kind {
-                params.push(Box::new(k.clone()));
-            }
+    /// `search_paged` without the timeout, for searches that are already under one.
+    fn search_paged_untimed(&self, search: &SearchQuery) -> Result<SearchPage> {
+        let query = search.query.as_str();
+        let filters = &search.filters;
+        let SearchQuery {
+            case_sensitive,
+            whole_word,
+            weights,
+            order_by,
+            explain,
+            limit,
+            offset,
+            ..
+        } = *search;
+
+        // An empty query enumerates everything matching the filters instead
+        // of handing FTS5 an empty MATCH expression
+        let enumerate = query.trim().is_empty();
+
+        // A regex alone has no term to MATCH on: scan symbol names directly
+        if enumerate
+            && let Some(pattern) = search.name_regex.as_deref()
+            && (filters.scope.is_empty() || filters.scope.iter().any(|s| s == "symbol"))
+        {
+            return self.search_regex_untimed(pattern, filters, order_by, limit, offset);
         }
-
-        let next_param = params.len() + 1;
-
-        // Path filter (supports GLOB)
-        if let Some(p) = path {
-            if p.contains('*') {
-                conditions.push(format!("path GLOB ?{}", next_param));
-            } else {
-                conditions.push(format!("path = ?{}", next_param));
-            }
-            params.push(Box::new(p.to_string()));
+        let name_regex = search
+            .name_regex
+            .as_deref()
+            .map(compile_name_regex)
+            .transpose()?;
+        // BM25 weights: name (3x), file (2x), content (1x) unless overridden
+        let (name_weight, file_weight, content_weight) = weights.unwrap_or((3.0, 2.0, 1.0));
+
+        // Only the tables of the projects searched are queried
+        let shards = fts_shards(&self.conn, &filters.projects)?;
+        if shards.is_empty() {
+            return Ok(SearchPage {
+                results: Vec::new(),
+                total: 0,
+                matches: Vec::new(),
+            });
         }
 
-        let next_param = params.len() + 1;
-
-        // Project filter
-        if let Some(proj) = project {
-            conditions.push(format!("project = ?{}", next_param));
-            params.push(Box::new(proj.to_string()));
-        }
+        let (where_clause, mut params) = search_where_clause(query, filters);
 
-        let next_param = params.len() + 1;
+        let order_keys = if order_by != SearchOrder::Relevance {
+            order_by
+                .sort_keys()
+                .iter()
+                .map(|key| key.to_string())
+                .collect()
+        } else if enumerate {
+            // No relevance to rank by: list in file order
+            vec![
+                "path".to_string(),
+                FTS_TYPE_RANK.to_string(),
+                "rowid_ref".to_string(),
+            ]
+        } else {
+            // Add exact match parameter for boosting
+            let exact_param = params.len() + 1;
+            // Extract first word from query for exact match comparison (lowercase)
+            let exact_term = query
+                .split_whitespace()
+                .next()
+                .unwrap_or(query)
+                .to_lowercase();
+            params.push(Box::new(exact_term));
+
+            if [name_weight, file_weight, content_weight]
+                .iter()
+                .any(|w| !w.is_finite() || *w < 0.0)
+            {
+                anyhow::bail!("BM25 weights must be finite and non-negative");
+            }
+            let weight_param = params.len() + 1;
+            params.push(Box::new(name_weight));
+            params.push(Box::new(file_weight));
+            params.push(Box::new(content_weight));
+
+            // Boost exact name matches with CASE (bm25 returns negative, so -1000 ranks first)
+            // Secondary sort by name length to prefer shorter matches
+            vec![
+                format!(
+                    "CASE WHEN lower(name) = ?{} THEN -1000 ELSE 0 END + bm25(search_fts, ?{}, ?{}, ?{})",
+                    exact_param,
+                    weight_param,
+                    weight_param + 1,
+                    weight_param + 2
+                ),
+                "length(name)".to_string(),
+            ]
+        };
 
-        // Visibility filter: visibility_level <= max_level
-        // Files/texts have level 0 (always pass), symbols have 1/2/3
-        if let Some(max_level) = visibility_max_level(visibility, "public") {
-            conditions.push(format!("visibility_level <= ?{}", next_param));
-            params.push(Box::new(max_level));
-        }
+        // Sort keys are computed in a subquery: FTS5 auxiliary functions (bm25)
+        // cannot be evaluated in the same SELECT as the COUNT(*) OVER () window
+        let sort_columns: Vec<String> = order_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| format!("{key} AS sort_{i}"))
+            .collect();
+        let order_by: Vec<String> = (0..order_keys.len()).map(|i| format!("sort_{i}")).collect();
+
+        let (type_rowid_pairs, total) = if case_sensitive || whole_word || name_regex.is_some() {
+            // The tokenizer folds case and splits identifiers, so these checks run on
+            // the stored name/content of every candidate; paging happens afterwards
+            let groups = query_term_groups(query);
+            let rows = format!(
+                "SELECT type, rowid_ref, name, file, content, {} FROM search_fts {}",
+                sort_columns.join(", "),
+                where_clause
+            );
+            let sql = format!(
+                "SELECT type, rowid_ref, name, file, content FROM ({}) ORDER BY {}",
+                fts_union(&shards, &rows),
+                order_by.join(", ")
+            );
+            let mut stmt = self.conn.prepare(&sql)?;
+            let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?;
 
-        // Add exact match parameter for boosting
-        let exact_param = params.len() + 1;
-        // Extract first word from query for exact match comparison (lowercase)
-        let exact_term = query
-            .split_whitespace()
-            .next()
-            .unwrap_or(query)
-            .to_lowercase();
-        params.push(Box::new(exact_term));
+            let mut type_rowid_pairs = Vec::new();
+            let mut total = 0usize;
+            for row in rows {
+                let (entry_type, rowid, name, file, content) = row?;
+                if let Some(re) = &name_regex {
+                    let matched = match entry_type.as_str() {
+                        "symbol" => regex_matches_name(re, &name, '.'),
+                        "file" => regex_matches_name(re, &file, '/'),
+                        _ => false,
+                    };
+                    if !matched {
+                        continue;
+                    }
+                }
+                // A file is also known by its path; symbols and texts only by name/content
+                let mut fields = vec![name.as_str(), strip_subtokens(&content)];
+                if entry_type == "file" {
+                    fields.push(file.as_str());
+                }
+                if (case_sensitive || whole_word)
+                    && !term_groups_match(&groups, &fields, case_sensitive, whole_word)
+                {
+                    continue;
+                }
+                if total >= offset as usize && type_rowid_pairs.len() < limit as usize {
+                    type_rowid_pairs.push((entry_type, rowid));
+                }
+                total += 1;
+            }
+            (type_rowid_pairs, total)
+        } else {
+            // Several tables each rank their own rows first, so the merge only
+            // sorts the candidates that can make the page
+            let merged = shards.len() > 1;
+            let rows = if merged {
+                let top_param = params.len() + 1;
+                params.push(Box::new(i64::from(offset) + i64::from(limit)));
+                format!(
+                    "SELECT * FROM (SELECT type, rowid_ref, {} FROM search_fts {} \
+                     ORDER BY {} LIMIT ?{})",
+                    sort_columns.join(", "),
+                    where_clause,
+                    order_by.join(", "),
+                    top_param
+                )
+            } else {
+                format!(
+                    "SELECT type, rowid_ref, {} FROM search_fts {}",
+                    sort_columns.join(", "),
+                    where_clause
+                )
+            };
 
-        // Add limit and offset
-        let limit_param = params.len() + 1;
-        let offset_param = params.len() + 2;
-        params.push(Box::new(limit));
-        params.push(Box::new(offset));
+            // Add limit and offset
+            let limit_param = params.len() + 1;
+            let offset_param = params.len() + 2;
+            params.push(Box::new(limit));
+            params.push(Box::new(offset));
 
-        // BM25 weights: name (3x), file (2x), content (1x)
-        // Boost exact name matches with CASE (bm25 returns negative, so -1000 ranks first)
-        // Secondary sort by name length to prefer shorter matches
-        let sql = format!(
-            "SELECT type, rowid_ref FROM search_fts WHERE {} \
-             ORDER BY CASE WHEN lower(name) = ?{} THEN -1000 ELSE 0 END + bm25(search_fts, 3.0, 2.0, 1.0), length(name) \
-             LIMIT ?{} OFFSET ?{}",
-            conditions.join(" AND "),
-            exact_param,
-            limit_param,
-            offset_param
-        );
+            let sql = format!(
+                "SELECT type, rowid_ref, COUNT(*) OVER () FROM ({}) \
+                 ORDER BY {} \
+                 LIMIT ?{} OFFSET ?{}",
+                fts_union(&shards, &rows),
+                order_by.join(", "),
+                limit_param,
+                offset_param
+            );
 
-        let mut stmt = self.conn.prepare(&sql)?;
-        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+            let mut stmt = self.conn.prepare(&sql)?;
+            let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
-        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-            let entry_type: String = row.get(0)?;
-            let rowid: i64 = row.get(1)?;
-            Ok((entry_type, rowid))
-        })?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
+                let entry_type: String = row.get(0)?;
+                let rowid: i64 = row.get(1)?;
+                let total: i64 = row.get(2)?;
+                Ok((entry_type, rowid, total))
+            })?;
 
-        // Collect (type, rowid) pairs
-        let mut type_rowid_pairs = Vec::new();
-        for row in rows {
-            type_rowid_pairs.push(row?);
-        }
+            // Collect (type, rowid) pairs
+            let mut type_rowid_pairs = Vec::new();
+            let mut total = None;
+            for row in rows {
+                let (entry_type, rowid, row_total) = row?;
+                total = Some(row_total as usize);
+                type_rowid_pairs.push((entry_type, rowid));
+            }
+            let total = match total {
+                // A short first page holds every match
+                _ if offset == 0 && type_rowid_pairs.len() < limit as usize => {
+                    type_rowid_pairs.len()
+                }
+                Some(total) if !merged => total,
+                _ => self.search_count_untimed(query, filters)?,
+            };
+            (type_rowid_pairs, total)
+        };
 
         // Fetch full records from content tables
         let mut results = Vec::new();
+        let mut matches = Vec::new();
         for (entry_type, rowid) in type_rowid_pairs {
             let result = match entry_type.as_str() {
                 "symbol" => self.get_symbol_by_rowid(rowid).map(SearchResult::Symbol)?,
@@ -445,308 +936,447 @@ impl SearchDb {
                 "text" => self.get_text_by_rowid(rowid).map(SearchResult::Text)?,
                 _ => continue,
             };
+            if explain && !enumerate {
+                matches.push(self.explain_match(
+                    query,
+                    result.project(),
+                    &entry_type,
+                    rowid,
+                    (name_weight, file_weight, content_weight),
+                )?);
+            }
             results.push(result);
         }
 
-        Ok(results)
-    }
-
-    /// Fetch a symbol by rowid.
-    fn get_symbol_by_rowid(&self, rowid: i64) -> Result<SymbolEntry> {
-        let mut stmt = self.conn.prepare(
-            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-             FROM symbols WHERE rowid = ?1",
-        )?;
-        stmt.query_row([rowid], |row| {
-            Ok(SymbolEntry {
-                project: row.get(0)?,
-                file: row.get(1)?,
-                name: row.get(2)?,
-                kind: row.get(3)?,
-                line: [row.get(4)?, row.get(5)?],
-                parent: row.get(6)?,
-                tokens: row.get(7)?,
-                alias: row.get(8)?,
-                visibility: row.get(9)?,
-            })
+        Ok(SearchPage {
+            results,
+            total,
+            matches,
         })
-        .context("failed to fetch symbol by rowid")
     }
 
-    /// Fetch a file by rowid.
-    fn get_file_by_rowid(&self, rowid: i64) -> Result<FileEntry> {
-        let mut stmt = self.conn.prepare(
-            "SELECT project, path, lang, hash, lines, title, description
-             FROM files WHERE rowid = ?1",
+    /// Score one FTS row of `project` against `query`, overall and per column.
+    ///
+    /// Runs the MATCH again restricted to the row: BM25 statistics cover the
+    /// project's whole table either way, so the scores equal those used for ranking.
+    fn explain_match(
+        &self,
+        query: &str,
+        project: &str,
+        entry_type: &str,
+        rowid: i64,
+        weights: (f64, f64, f64),
+    ) -> Result<MatchInfo> {
+        let (name_weight, file_weight, content_weight) = weights;
+        let table = find_fts_shard(&self.conn, project)?
+            .with_context(|| format!("no FTS table for project '{project}'"))?;
+        let sql = "SELECT bm25(search_fts, ?2, ?3, ?4), bm25(search_fts, ?2, 0, 0), \
+             bm25(search_fts, 0, ?3, 0), bm25(search_fts, 0, 0, ?4), \
+             snippet(search_fts, 2, '«', '»', '…', 12) \
+             FROM search_fts WHERE search_fts MATCH ?1 AND type = ?5 AND rowid_ref = ?6";
+        let (score, name_score, file_score, content_score, snippet) = self.conn.query_row(
+            &fts_union(&[table], sql),
+            rusqlite::params![
+                fts5_quote(query),
+                name_weight,
+                file_weight,
+                content_weight,
+                entry_type,
+                rowid
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, f64>(0)?,
+                    row.get::<_, f64>(1)?,
+                    row.get::<_, f64>(2)?,
+                    row.get::<_, f64>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            },
         )?;
-        stmt.query_row([rowid], |row| {
-            Ok(FileEntry {
-                project: row.get(0)?,
-                path: row.get(1)?,
-                lang: row.get(2)?,
-                hash: row.get(3)?,
-                lines: row.get(4)?,
-                title: row.get(5)?,
-                description: row.get(6)?,
+        let matched_column = [("name", name_score), ("file", file_score)]
+            .into_iter()
+            .fold(("content", content_score), |best, column| {
+                if column.1 < best.1 { column } else { best }
             })
+            .0
+            .to_string();
+        // A zero weight scores -0.0; report it as plain 0
+        let [score, name_score, file_score, content_score] =
+            [score, name_score, file_score, content_score].map(|s| if s == 0.0 { 0.0 } else { s });
+        // snippet() falls back to the start of the column when no term hit it
+        let snippet = snippet
+            .filter(|s| content_score < 0.0 && !s.is_empty())
+            .map(|s| s.replace(SUBTOKEN_SEPARATOR, " "));
+        Ok(MatchInfo {
+            score,
+            name_score,
+            file_score,
+            content_score,
+            matched_column,
+            snippet,
         })
-        .context("failed to fetch file by rowid")
     }
 
-    /// Fetch a text by rowid.
-    fn get_text_by_rowid(&self, rowid: i64) -> Result<TextEntry> {
-        let mut stmt = self.conn.prepare(
-            "SELECT project, file, kind, line_start, line_end, text, parent
-             FROM texts WHERE rowid = ?1",
-        )?;
-        stmt.query_row([rowid], |row| {
-            Ok(TextEntry {
-                project: row.get(0)?,
-                file: row.get(1)?,
-                kind: row.get(2)?,
-                line: [row.get(3)?, row.get(4)?],
-                text: row.get(5)?,
-                parent: row.get(6)?,
-            })
+    /// Find symbols whose name matches a regex, scanning the symbols table.
+    ///
+    /// Used by `search` when there is no query to MATCH on. The regex is tried
+    /// against the full name (`Server.handle_event`) and the base name
+    /// (`handle_event`). Other filters behave as in `search`; with `Relevance`,
+    /// results come in file order.
+    pub fn search_regex(
+        &self,
+        name_regex: &str,
+        filters: &SearchFilters,
+        order_by: SearchOrder,
+        limit: u32,
+        offset: u32,
+    ) -> Result<SearchPage> {
+        self.with_timeout(|db| {
+            db.search_regex_untimed(name_regex, filters, order_by, limit, offset)
         })
-        .context("failed to fetch text by rowid")
     }
 
-    /// Get all symbols in a file, ordered by start line.
-    ///
-    /// The file parameter supports glob patterns (*, ?, [...]).
-    /// If visibility is specified, only symbols at that visibility level or higher are returned.
-    /// Hierarchy: public > internal > private.
-    pub fn get_file_symbols(
+    /// `search_regex` without the timeout, for searches that are already under one.
+    fn search_regex_untimed(
         &self,
-        file: &str,
-        visibility: Option<&str>,
+        name_regex: &str,
+        filters: &SearchFilters,
+        order_by: SearchOrder,
         limit: u32,
         offset: u32,
-    ) -> Result<Vec<SymbolEntry>> {
-        let max_level = visibility_max_level(visibility, "public");
-        // Use GLOB for pattern matching if file contains wildcards
-        let file_op = if file.contains('*') || file.contains('?') || file.contains('[') {
-            "GLOB"
-        } else {
-            "="
-        };
+    ) -> Result<SearchPage> {
+        let re = compile_name_regex(name_regex)?;
 
-        let sql = match max_level {
-            Some(_) => {
-                format!(
-                    "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                     FROM symbols
-                     WHERE file {} ?1 AND visibility_level <= ?2
-                     ORDER BY file, line_start
-                     LIMIT ?3 OFFSET ?4",
-                    file_op
-                )
-            }
-            None => {
-                format!(
-                    "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                     FROM symbols
-                     WHERE file {} ?1
-                     ORDER BY file, line_start
-                     LIMIT ?2 OFFSET ?3",
-                    file_op
-                )
+        let mut matched = Vec::new();
+        self.scan_symbols(None, filters, |symbol| {
+            if regex_matches_name(&re, &symbol.name, '.') {
+                matched.push(symbol);
             }
-        };
+        })?;
+        if order_by != SearchOrder::Relevance {
+            matched.sort_by(|a, b| compare_symbols(a, b, order_by));
+        }
+        let total = matched.len();
+        let results = matched
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(SearchResult::Symbol)
+            .collect();
+        Ok(SearchPage {
+            results,
+            total,
+            matches: Vec::new(),
+        })
+    }
 
-        let mut stmt = self.conn.prepare(&sql)?;
+    /// Find symbols whose name is within a few edits of `query` (for `search`
+    /// with `fuzzy`), closest first.
+    ///
+    /// The query is compared, case-insensitively, with the full and the base
+    /// name; the allowed distance grows with its length (see `max_edit_distance`).
+    /// FTS first narrows candidates to names with a word starting with the
+    /// query's first three characters; when none of them is close enough (the
+    /// typo is in those characters), every symbol matching the filters is scanned.
+    /// The `scope` filter is ignored: only symbols are searched.
+    pub fn search_fuzzy(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        order_by: SearchOrder,
+        limit: u32,
+        offset: u32,
+    ) -> Result<SearchPage> {
+        self.with_timeout(|db| db.search_fuzzy_untimed(query, filters, order_by, limit, offset))
+    }
 
-        let rows: Vec<SymbolEntry> = match max_level {
-            Some(level) => stmt
-                .query_map(rusqlite::params![file, level, limit, offset], |row| {
-                    Ok(SymbolEntry {
-                        project: row.get(0)?,
-                        file: row.get(1)?,
-                        name: row.get(2)?,
-                        kind: row.get(3)?,
-                        line: [row.get(4)?, row.get(5)?],
-                        parent: row.get(6)?,
-                        tokens: row.get(7)?,
-                        alias: row.get(8)?,
-                        visibility: row.get(9)?,
-                    })
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?,
-            None => stmt
-                .query_map(rusqlite::params![file, limit, offset], |row| {
-                    Ok(SymbolEntry {
-                        project: row.get(0)?,
-                        file: row.get(1)?,
-                        name: row.get(2)?,
-                        kind: row.get(3)?,
-                        line: [row.get(4)?, row.get(5)?],
-                        parent: row.get(6)?,
-                        tokens: row.get(7)?,
-                        alias: row.get(8)?,
-                        visibility: row.get(9)?,
-                    })
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?,
+    /// `search_fuzzy` without the timeout, for searches that are already under one.
+    fn search_fuzzy_untimed(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        order_by: SearchOrder,
+        limit: u32,
+        offset: u32,
+    ) -> Result<SearchPage> {
+        let needle = query.trim().to_lowercase();
+        let max_distance = max_edit_distance(needle.chars().count());
+        let collect = |candidates: Option<&str>| -> Result<Vec<(usize, SymbolEntry)>> {
+            let mut hits = Vec::new();
+            self.scan_symbols(candidates, filters, |symbol| {
+                let name = symbol.name.to_lowercase();
+                let base = name
+                    .rsplit_once('.')
+                    .map_or(name.as_str(), |(_, base)| base);
+                let distance = edit_distance(&needle, base).min(edit_distance(&needle, &name));
+                if distance <= max_distance {
+                    hits.push((distance, symbol));
+                }
+            })?;
+            Ok(hits)
         };
 
-        Ok(rows)
+        let prefix: String = needle
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .take(3)
+            .collect();
+        let mut hits = Vec::new();
+        if self.fts_enabled && !prefix.is_empty() {
+            // Trigram terms already match inside words; unicode61 needs a prefix query
+            let candidates = if self.trigram {
+                format!("name : \"{prefix}\"")
+            } else {
+                format!("name : {prefix}*")
+            };
+            hits = collect(Some(&candidates))?;
+        }
+        if hits.is_empty() {
+            hits = collect(None)?;
+        }
+
+        // Closest first, then shorter names; stable, so ties stay in file order
+        match order_by {
+            SearchOrder::Relevance => {
+                hits.sort_by_key(|(distance, symbol)| (*distance, symbol.name.len()))
+            }
+            _ => hits.sort_by(|(_, a), (_, b)| compare_symbols(a, b, order_by)),
+        }
+        let total = hits.len();
+        let results = hits
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(_, symbol)| SearchResult::Symbol(symbol))
+            .collect();
+        Ok(SearchPage {
+            results,
+            total,
+            matches: Vec::new(),
+        })
     }
 
-    /// Get direct children of a symbol in a file.
+    /// Visit the symbols matching the `search` filters, in file order.
     ///
-    /// If visibility is specified, only symbols at that visibility level or higher are returned.
-    /// Hierarchy: public > internal > private.
-    pub fn get_children(
+    /// `candidates` is an optional FTS5 MATCH expression: only the symbol rows
+    /// it matches are visited.
+    fn scan_symbols(
         &self,
-        file: &str,
-        parent: &str,
-        visibility: Option<&str>,
-        limit: u32,
-        offset: u32,
-    ) -> Result<Vec<SymbolEntry>> {
-        let max_level = visibility_max_level(visibility, "public");
-
-        let sql = match max_level {
-            Some(_) => {
-                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                 FROM symbols
-                 WHERE file = ?1 AND parent = ?2 AND visibility_level <= ?3
-                 ORDER BY line_start
-                 LIMIT ?4 OFFSET ?5"
+        candidates: Option<&str>,
+        filters: &SearchFilters,
+        mut visit: impl FnMut(SymbolEntry),
+    ) -> Result<()> {
+        let SearchFilters {
+            kind,
+            lang,
+            path,
+            exclude_paths,
+            include_tests,
+            projects,
+            visibility,
+            ..
+        } = filters;
+        let mut conditions = vec!["s.superseded = 0".to_string()];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        if let Some(expr) = candidates {
+            let shards = fts_shards(&self.conn, projects)?;
+            if shards.is_empty() {
+                return Ok(());
             }
-            None => {
-                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                 FROM symbols
-                 WHERE file = ?1 AND parent = ?2
-                 ORDER BY line_start
-                 LIMIT ?3 OFFSET ?4"
-            }
-        };
-
-        let mut stmt = self.conn.prepare(sql)?;
-
-        let rows: Vec<SymbolEntry> = match max_level {
-            Some(level) => stmt
-                .query_map(
-                    rusqlite::params![file, parent, level, limit, offset],
-                    |row| {
-                        Ok(SymbolEntry {
-                            project: row.get(0)?,
-                            file: row.get(1)?,
-                            name: row.get(2)?,
-                            kind: row.get(3)?,
-                            line: [row.get(4)?, row.get(5)?],
-                            parent: row.get(6)?,
-                            tokens: row.get(7)?,
-                            alias: row.get(8)?,
-                            visibility: row.get(9)?,
-                        })
-                    },
-                )?
-                .collect::<std::result::Result<Vec<_>, _>>()?,
-            None => stmt
-                .query_map(rusqlite::params![file, parent, limit, offset], |row| {
-                    Ok(SymbolEntry {
-                        project: row.get(0)?,
-                        file: row.get(1)?,
-                        name: row.get(2)?,
-                        kind: row.get(3)?,
-                        line: [row.get(4)?, row.get(5)?],
-                        parent: row.get(6)?,
-                        tokens: row.get(7)?,
-                        alias: row.get(8)?,
-                        visibility: row.get(9)?,
-                    })
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?,
+            let rows =
+                "SELECT rowid_ref FROM search_fts WHERE search_fts MATCH ?1 AND type = 'symbol'";
+            conditions.push(format!("s.rowid IN ({})", fts_union(&shards, rows)));
+            params.push(Box::new(expr.to_string()));
+        }
+        if !projects.is_empty() {
+            let placeholders = vec!["?"; projects.len()].join(", ");
+            conditions.push(format!("s.project IN ({})", placeholders));
+            for p in projects {
+                params.push(Box::new(p.clone()));
+            }
+        }
+        if !kind.is_empty() {
+            let placeholders = vec!["?"; kind.len()].join(", ");
+            conditions.push(format!("s.kind IN ({})", placeholders));
+            for k in kind {
+                params.push(Box::new(canonical_kind(k)));
+            }
+        }
+        if !lang.is_empty() {
+            let placeholders = vec!["?"; lang.len()].join(", ");
+            conditions.push(format!("f.lang IN ({})", placeholders));
+            for l in lang {
+                params.push(Box::new(l.clone()));
+            }
+        }
+        if let Some(p) = path {
+            if p.contains('*') {
+                conditions.push("s.file GLOB ?".to_string());
+            } else {
+                conditions.push("s.file = ?".to_string());
+            }
+            params.push(Box::new(p.to_string()));
+        }
+        for pattern in exclude_paths {
+            conditions.push("s.file NOT GLOB ?".to_string());
+            params.push(Box::new(pattern.clone()));
+        }
+        if !include_tests {
+            conditions.push("COALESCE(f.is_test, 0) = 0".to_string());
+        }
+        if let Some(level) = visibility_max_level(visibility.as_deref(), "public") {
+            conditions.push("s.visibility_level <= ?".to_string());
+            params.push(Box::new(level));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
         };
 
-        Ok(rows)
+        let sql = format!(
+            "SELECT s.project, s.file, s.name, s.kind, s.line_start, s.line_end, s.parent, s.tokens, s.alias, s.visibility, s.sig
+             FROM symbols s
+             LEFT JOIN files f ON f.project = s.project AND f.path = s.file
+             {}
+             ORDER BY s.project, s.file, s.line_start",
+            where_clause
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
+
+        for row in rows {
+            visit(row?);
+        }
+        Ok(())
     }
 
-    /// Get all references TO a symbol (who calls/uses this symbol).
-    /// Returns references sorted by file, then line.
-    ///
-    /// Supports flexible name matching:
-    /// - Exact match: "self.handle_exception" matches "self.handle_exception"
-    /// - Base name match: "handle_exception" matches "self.handle_exception"
+    /// Count the rows `search` would match, without fetching them.
     ///
-    /// This handles OOP patterns where references are stored with receiver prefixes
-    /// (self., this., etc.) but users query with base method names.
+    /// Takes the same filters as `search` and runs the same WHERE clause as a
+    /// `COUNT(*)`, so gauging how broad a query is reads the content tables only to
+    /// leave out merged C++ declarations.
+    pub fn search_count(&self, query: &str, filters: &SearchFilters) -> Result<usize> {
+        self.with_timeout(|db| db.search_count_untimed(query, filters))
+    }
+
+    /// `search_count` without the timeout, for searches that are already under one.
+    fn search_count_untimed(&self, query: &str, filters: &SearchFilters) -> Result<usize> {
+        let shards = fts_shards(&self.conn, &filters.projects)?;
+        if shards.is_empty() {
+            return Ok(0);
+        }
+        let (where_clause, params) = search_where_clause(query, filters);
+        let rows = format!("SELECT 1 FROM search_fts {}", where_clause);
+        let sql = format!("SELECT COUNT(*) FROM ({})", fts_union(&shards, &rows));
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let count: i64 =
+            self.conn
+                .query_row(&sql, rusqlite::params_from_iter(param_refs), |row| {
+                    row.get(0)
+                })?;
+        Ok(count as usize)
+    }
+
+    /// Fetch a symbol by rowid.
+    fn get_symbol_by_rowid(&self, rowid: i64) -> Result<SymbolEntry> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols WHERE rowid = ?1",
+        )?;
+        stmt.query_row([rowid], symbol_from_row)
+            .context("failed to fetch symbol by rowid")
+    }
+
+    /// Fetch a file by rowid.
+    fn get_file_by_rowid(&self, rowid: i64) -> Result<FileEntry> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, path, lang, hash, lines, title, description, is_test
+             FROM files WHERE rowid = ?1",
+        )?;
+        stmt.query_row([rowid], |row| {
+            Ok(FileEntry {
+                project: row.get(0)?,
+                path: row.get(1)?,
+                lang: row.get(2)?,
+                hash: row.get(3)?,
+                lines: row.get(4)?,
+                title: row.get(5)?,
+                description: row.get(6)?,
+                is_test: row.get(7)?,
+            })
+        })
+        .context("failed to fetch file by rowid")
+    }
+
+    /// Fetch a text by rowid.
+    fn get_text_by_rowid(&self, rowid: i64) -> Result<TextEntry> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, kind, line_start, line_end, text, parent
+             FROM texts WHERE rowid = ?1",
+        )?;
+        stmt.query_row([rowid], |row| {
+            Ok(TextEntry {
+                project: row.get(0)?,
+                file: row.get(1)?,
+                kind: row.get(2)?,
+                line: [row.get(3)?, row.get(4)?],
+                text: row.get(5)?,
+                parent: row.get(6)?,
+            })
+        })
+        .context("failed to fetch text by rowid")
+    }
+
+    /// Get all symbols in a file, ordered by start line.
     ///
-    /// If visibility is specified, only references to symbols at that visibility level
-    /// or higher are returned. The target symbol's visibility is looked up in the symbols table.
-    pub fn get_callers(
+    /// The file parameter supports glob patterns (*, ?, [...]). With `project`, only
+    /// that project's file is read; otherwise same-named files of every project match.
+    /// If visibility is specified, only symbols at that visibility level or higher are returned.
+    /// Hierarchy: public > internal > private.
+    pub fn get_file_symbols(
         &self,
-        name: &str,
-        kind: Option<&str>,
         project: Option<&str>,
+        file: &str,
         visibility: Option<&str>,
         limit: u32,
         offset: u32,
-    ) -> Result<Vec<ReferenceEntry>> {
-        let max_level = visibility_max_level(visibility, "private");
-
-        // Match exact name OR names ending with .{name} (e.g., "self.foo" matches query "foo")
-        let mut conditions = vec!["(r.name = ? OR r.name LIKE ?)".to_string()];
-        let like_pattern = format!("%.{}", name);
-        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
-            vec![Box::new(name.to_string()), Box::new(like_pattern)];
+    ) -> Result<Vec<SymbolEntry>> {
+        // Use GLOB for pattern matching if file contains wildcards
+        let mut conditions = if file.contains('*') || file.contains('?') || file.contains('[') {
+            vec!["file GLOB ?"]
+        } else {
+            vec!["file = ?"]
+        };
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(file.to_string())];
 
-        if let Some(k) = kind {
-            conditions.push("r.kind = ?".to_string());
-            params.push(Box::new(k.to_string()));
-        }
         if let Some(p) = project {
-            conditions.push("r.project = ?".to_string());
+            conditions.push("project = ?");
             params.push(Box::new(p.to_string()));
         }
-
-        // Visibility filter: join with symbols to filter by target symbol's visibility_level
-        let sql = if let Some(level) = max_level {
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("visibility_level <= ?");
             params.push(Box::new(level));
-            params.push(Box::new(limit));
-            params.push(Box::new(offset));
-
-            format!(
-                "SELECT DISTINCT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
-                 FROM refs r
-                 INNER JOIN symbols s ON (s.name = r.name OR s.name LIKE '%.' || r.name)
-                                      AND s.project = r.project
-                 WHERE {} AND s.visibility_level <= ?
-                 ORDER BY r.file, r.line_start
-                 LIMIT ? OFFSET ?",
-                conditions.join(" AND ")
-            )
-        } else {
-            // No visibility filter, simple query
-            params.push(Box::new(limit));
-            params.push(Box::new(offset));
+        }
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
 
-            format!(
-                "SELECT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
-                 FROM refs r
-                 WHERE {}
-                 ORDER BY r.file, r.line_start
-                 LIMIT ? OFFSET ?",
-                conditions.join(" AND ")
-            )
-        };
+        let sql = format!(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE {}
+             ORDER BY file, line_start
+             LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        );
 
         let mut stmt = self.conn.prepare(&sql)?;
         let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
-        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-            Ok(ReferenceEntry {
-                project: row.get(0)?,
-                file: row.get(1)?,
-                name: row.get(2)?,
-                kind: row.get(3)?,
-                line: [row.get(4)?, row.get(5)?],
-                caller: row.get(6)?,
-            })
-        })?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
 
         let mut results = Vec::new();
         for row in rows {
@@ -755,85 +1385,380 @@ impl SearchDb {
         Ok(results)
     }
 
-    /// Get all references FROM a symbol (what does this symbol call/use).
-    /// Returns references sorted by file, then line.
+    /// Find the innermost symbol of a file whose line range contains `line`.
     ///
-    /// Supports flexible caller name matching:
-    /// - Exact match: "MyClass.method" matches "MyClass.method"
-    /// - Base name match: "method" matches "MyClass.method"
+    /// The smallest range wins; among symbols with the same range (e.g. a one-line
+    /// method in a one-line class), the deepest in the `parent` chain wins.
+    pub fn symbol_at(&self, project: &str, file: &str, line: u32) -> Result<Option<SymbolEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE project = ?1 AND file = ?2 AND line_start <= ?3 AND line_end >= ?3
+             ORDER BY line_end - line_start, line_start DESC",
+        )?;
+        let candidates: Vec<SymbolEntry> = stmt
+            .query_map(rusqlite::params![project, file, line], symbol_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let Some(first) = candidates.first() else {
+            return Ok(None);
+        };
+        let tied: Vec<&SymbolEntry> = candidates
+            .iter()
+            .take_while(|s| s.line == first.line)
+            .collect();
+        // Depth within the tied symbols: hops up the parent chain to one of them
+        let depth = |sym: &SymbolEntry| {
+            let mut depth = 0;
+            let mut parent = sym.parent.as_deref();
+            while let Some(name) = parent
+                && depth < tied.len()
+            {
+                match tied.iter().find(|s| s.name == name) {
+                    Some(p) => {
+                        depth += 1;
+                        parent = p.parent.as_deref();
+                    }
+                    None => break,
+                }
+            }
+            depth
+        };
+        let innermost = tied
+            .iter()
+            .enumerate()
+            // Keep SQL order on equal depth (max_by_key would keep the last)
+            .max_by_key(|(i, s)| (depth(s), std::cmp::Reverse(*i)))
+            .map(|(_, s)| (*s).clone());
+        Ok(innermost)
+    }
+
+    /// Get the docstrings attached to a symbol, in line order.
     ///
-    /// This handles qualified names where the caller context includes class prefixes.
+    /// Same rule as [`SearchDb::undocumented_symbols`]: a `docstring` text entry of
+    /// the symbol's file whose `parent` is the symbol's name.
+    pub fn symbol_docstrings(
+        &self,
+        project: &str,
+        file: &str,
+        name: &str,
+    ) -> Result<Vec<TextEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, kind, line_start, line_end, text, parent
+             FROM texts
+             WHERE project = ?1 AND file = ?2 AND kind = 'docstring' AND parent = ?3
+             ORDER BY line_start, rowid",
+        )?;
+        let texts = stmt
+            .query_map([project, file, name], |row| {
+                Ok(TextEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    kind: row.get(2)?,
+                    line: [row.get(3)?, row.get(4)?],
+                    text: row.get(5)?,
+                    parent: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(texts)
+    }
+
+    /// Get the symbols of a file as a tree, nesting each symbol under its `parent`.
     ///
-    /// If visibility is specified, only references to symbols at that visibility level
-    /// or higher are returned. The referenced symbol's visibility is looked up in the symbols table.
-    pub fn get_callees(
+    /// The file parameter supports glob patterns, like [`SearchDb::get_file_symbols`],
+    /// and matches in `project` only when given; parents are only looked up within
+    /// the same file of the same project. When several symbols share
+    /// the parent's name (e.g. a Rust struct and its `impl`), the nearest preceding one
+    /// whose line range encloses the child wins. Symbols whose parent was filtered out
+    /// by `visibility` (or is not indexed) are kept at the top level.
+    pub fn get_file_outline(
         &self,
-        caller: &str,
-        kind: Option<&str>,
         project: Option<&str>,
+        file: &str,
+        visibility: Option<&str>,
+    ) -> Result<Vec<OutlineNode>> {
+        let mut symbols = self.get_file_symbols(project, file, visibility, u32::MAX, 0)?;
+        // Enclosing symbols first, so a parent always precedes its children
+        symbols.sort_by(|a, b| {
+            (
+                &a.project,
+                &a.file,
+                a.line[0],
+                std::cmp::Reverse(a.line[1]),
+                a.name.len(),
+            )
+                .cmp(&(
+                    &b.project,
+                    &b.file,
+                    b.line[0],
+                    std::cmp::Reverse(b.line[1]),
+                    b.name.len(),
+                ))
+        });
+
+        let parents: Vec<Option<usize>> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, sym)| {
+                let parent = sym.parent.as_deref()?;
+                let mut candidates = symbols[..i].iter().enumerate().rev().filter(|(_, p)| {
+                    p.project == sym.project && p.file == sym.file && p.name == parent
+                });
+                let first = candidates.clone().next().map(|(j, _)| j);
+                candidates
+                    .find(|(_, p)| p.line[0] <= sym.line[0] && p.line[1] >= sym.line[1])
+                    .map(|(j, _)| j)
+                    .or(first)
+            })
+            .collect();
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); symbols.len()];
+        let mut roots = Vec::new();
+        for (i, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(p) => children[*p].push(i),
+                None => roots.push(i),
+            }
+        }
+
+        fn build(
+            i: usize,
+            symbols: &mut [Option<SymbolEntry>],
+            children: &[Vec<usize>],
+        ) -> OutlineNode {
+            OutlineNode {
+                symbol: symbols[i].take().expect("each symbol has a single parent"),
+                children: children[i]
+                    .iter()
+                    .map(|&c| build(c, symbols, children))
+                    .collect(),
+            }
+        }
+        let mut symbols: Vec<Option<SymbolEntry>> = symbols.into_iter().map(Some).collect();
+        Ok(roots
+            .into_iter()
+            .map(|i| build(i, &mut symbols, &children))
+            .collect())
+    }
+
+    /// Get direct children of a symbol in a file, of one project when `project` is given.
+    ///
+    /// If visibility is specified, only symbols at that visibility level or higher are returned.
+    /// Hierarchy: public > internal > private.
+    pub fn get_children(
+        &self,
+        project: Option<&str>,
+        file: &str,
+        parent: &str,
         visibility: Option<&str>,
         limit: u32,
         offset: u32,
-    ) -> Result<Vec<ReferenceEntry>> {
-        let max_level = visibility_max_level(visibility, "private");
-
-        // Match exact caller OR callers ending with .{caller} (e.g., "Class.method" matches query "method")
-        let mut conditions = vec!["(r.caller = ? OR r.caller LIKE ?)".to_string()];
-        let like_pattern = format!("%.{}", caller);
+    ) -> Result<Vec<SymbolEntry>> {
+        let mut conditions = vec!["file = ?", "parent = ?"];
         let mut params: Vec<Box<dyn rusqlite::ToSql>> =
-            vec![Box::new(caller.to_string()), Box::new(like_pattern)];
+            vec![Box::new(file.to_string()), Box::new(parent.to_string())];
 
-        if let Some(k) = kind {
-            conditions.push("r.kind = ?".to_string());
-            params.push(Box::new(k.to_string()));
+        if let Some(p) = project {
+            conditions.push("project = ?");
+            params.push(Box::new(p.to_string()));
         }
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("visibility_level <= ?");
+            params.push(Box::new(level));
+        }
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
+
+        let sql = format!(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE {}
+             ORDER BY line_start
+             LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Get direct children of a parent symbol across every file of a project (or
+    /// all projects), in file then line order.
+    ///
+    /// Unlike `get_children` the file need not be known, so members of a type
+    /// defined in several files (C# partial classes, reopened Ruby classes,
+    /// Swift extensions) come back together. Visibility defaults to "public".
+    pub fn get_children_by_parent(
+        &self,
+        project: Option<&str>,
+        parent: &str,
+        visibility: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<SymbolEntry>> {
+        let mut conditions = vec!["parent = ?"];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(parent.to_string())];
+
         if let Some(p) = project {
-            conditions.push("r.project = ?".to_string());
+            conditions.push("project = ?");
             params.push(Box::new(p.to_string()));
         }
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("visibility_level <= ?");
+            params.push(Box::new(level));
+        }
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
+
+        let sql = format!(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE {}
+             ORDER BY project, file, line_start, name
+             LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
 
-        // Visibility filter: join with symbols to filter by referenced symbol's visibility_level
-        let sql = if let Some(level) = max_level {
+    /// List every symbol of a project (or all projects), in file then line order.
+    ///
+    /// Reads the symbols table directly, so the listing is complete and
+    /// deterministic. Unlike `get_file_symbols` it spans files; unlike an
+    /// empty-query `search` it never mixes in files or texts.
+    ///
+    /// Visibility defaults to "public".
+    pub fn list_symbols(
+        &self,
+        project: Option<&str>,
+        kind: Option<&str>,
+        visibility: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<SymbolEntry>> {
+        let mut conditions = Vec::new();
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(p) = project {
+            conditions.push("project = ?");
+            params.push(Box::new(p.to_string()));
+        }
+        if let Some(k) = kind {
+            conditions.push("kind = ?");
+            params.push(Box::new(canonical_kind(k)));
+        }
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("visibility_level <= ?");
             params.push(Box::new(level));
-            params.push(Box::new(limit));
-            params.push(Box::new(offset));
+        }
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
 
-            format!(
-                "SELECT DISTINCT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
-                 FROM refs r
-                 INNER JOIN symbols s ON (s.name = r.name OR s.name LIKE '%.' || r.name)
-                                      AND s.project = r.project
-                 WHERE {} AND s.visibility_level <= ?
-                 ORDER BY r.file, r.line_start
-                 LIMIT ? OFFSET ?",
-                conditions.join(" AND ")
-            )
+        let where_clause = if conditions.is_empty() {
+            String::new()
         } else {
-            // No visibility filter, simple query
-            params.push(Box::new(limit));
-            params.push(Box::new(offset));
-
-            format!(
-                "SELECT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
-                 FROM refs r
-                 WHERE {}
-                 ORDER BY r.file, r.line_start
-                 LIMIT ? OFFSET ?",
-                conditions.join(" AND ")
-            )
+            format!("WHERE {}", conditions.join(" AND "))
         };
+        let sql = format!(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             {}
+             ORDER BY project, file, line_start, name
+             LIMIT ? OFFSET ?",
+            where_clause
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Find texts (comments, strings, docstrings) containing `substring` literally.
+    ///
+    /// A `LIKE '%...%'` scan over the texts table, bypassing FTS tokenization,
+    /// so punctuation and partial words match too. `%` and `_` in the input are
+    /// escaped. As with `LIKE`, ASCII letters match case-insensitively.
+    ///
+    /// Results are sorted by project, file, and line.
+    pub fn search_texts_literal(
+        &self,
+        substring: &str,
+        project: Option<&str>,
+        kind: Option<&str>,
+        path: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<TextEntry>> {
+        let escaped = substring
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let mut conditions = vec!["text LIKE ? ESCAPE '\\'"];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(format!("%{}%", escaped))];
+
+        if let Some(p) = project {
+            conditions.push("project = ?");
+            params.push(Box::new(p.to_string()));
+        }
+        if let Some(k) = kind {
+            conditions.push("kind = ?");
+            params.push(Box::new(canonical_kind(k)));
+        }
+        if let Some(p) = path {
+            conditions.push(if p.contains('*') {
+                "file GLOB ?"
+            } else {
+                "file = ?"
+            });
+            params.push(Box::new(p.to_string()));
+        }
+        params.push(Box::new(limit));
+
+        let sql = format!(
+            "SELECT project, file, kind, line_start, line_end, text, parent
+             FROM texts
+             WHERE {}
+             ORDER BY project, file, line_start, rowid
+             LIMIT ?",
+            conditions.join(" AND ")
+        );
 
         let mut stmt = self.conn.prepare(&sql)?;
         let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
         let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
-            Ok(ReferenceEntry {
+            Ok(TextEntry {
                 project: row.get(0)?,
                 file: row.get(1)?,
-                name: row.get(2)?,
-                kind: row.get(3)?,
-                line: [row.get(4)?, row.get(5)?],
-                caller: row.get(6)?,
+                kind: row.get(2)?,
+                line: [row.get(3)?, row.get(4)?],
+                text: row.get(5)?,
+                parent: row.get(6)?,
             })
         })?;
 
@@ -844,448 +1769,489 @@ impl SearchDb {
         Ok(results)
     }
 
-    /// Get the hash of a file from the DB (for change detection).
-    pub fn get_file_hash(&self, project: &str, path: &str) -> Result<Option<String>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT hash FROM files WHERE project = ?1 AND path = ?2")?;
-        let mut rows = stmt.query(rusqlite::params![project, path])?;
-        if let Some(row) = rows.next()? {
-            Ok(Some(row.get(0)?))
-        } else {
-            Ok(None)
-        }
-    }
-
-    /// Remove all data for a file (from files, symbols, texts, refs tables).
-    /// Does not rebuild FTS indexes - caller should call rebuild_fts() after batch operations.
-    pub fn remove_file(&self, project: &str, path: &str) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
+    /// Find symbols that have no attached docstring.
+    ///
+    /// A symbol counts as documented when a `docstring` text entry in the same
+    /// file has the symbol's name as its `parent`. Imports are never reported.
+    ///
+    /// Visibility defaults to "public" so private helpers aren't flagged unless
+    /// explicitly requested.
+    pub fn undocumented_symbols(
+        &self,
+        project: Option<&str>,
+        kind: &[String],
+        visibility: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<SymbolEntry>> {
+        let mut conditions = vec![
+            "t.rowid IS NULL".to_string(),
+            "s.kind != 'import'".to_string(),
+        ];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        tx.execute(
-            "DELETE FROM files WHERE project = ?1 AND path = ?2",
-            rusqlite::params![project, path],
-        )?;
-        tx.execute(
-            "DELETE FROM symbols WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, path],
-        )?;
-        tx.execute(
-            "DELETE FROM texts WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, path],
-        )?;
-        tx.execute(
-            "DELETE FROM refs WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, path],
-        )?;
+        if let Some(p) = project {
+            conditions.push("s.project = ?".to_string());
+            params.push(Box::new(p.to_string()));
+        }
+        if !kind.is_empty() {
+            let placeholders = vec!["?"; kind.len()].join(", ");
+            conditions.push(format!("s.kind IN ({})", placeholders));
+            for k in kind {
+                params.push(Box::new(canonical_kind(k)));
+            }
+        }
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("s.visibility_level <= ?".to_string());
+            params.push(Box::new(level));
+        }
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
 
-        tx.commit()?;
-        Ok(())
-    }
+        let sql = format!(
+            "SELECT s.project, s.file, s.name, s.kind, s.line_start, s.line_end, s.parent, s.tokens, s.alias, s.visibility, s.sig
+             FROM symbols s
+             LEFT JOIN texts t ON t.project = s.project AND t.file = s.file
+                              AND t.kind = 'docstring' AND t.parent = s.name
+             WHERE {}
+             ORDER BY s.project, s.file, s.line_start
+             LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        );
 
-    /// Remove all data for a project (files, symbols, texts, refs).
-    /// Does not rebuild FTS indexes - caller should call rebuild_fts() after batch operations.
-    pub fn remove_project(&self, project: &str) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
 
-        tx.execute(
-            "DELETE FROM files WHERE project = ?1",
-            rusqlite::params![project],
-        )?;
-        tx.execute(
-            "DELETE FROM symbols WHERE project = ?1",
-            rusqlite::params![project],
-        )?;
-        tx.execute(
-            "DELETE FROM texts WHERE project = ?1",
-            rusqlite::params![project],
-        )?;
-        tx.execute(
-            "DELETE FROM refs WHERE project = ?1",
-            rusqlite::params![project],
-        )?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
 
-        tx.commit()?;
-        Ok(())
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
     }
 
-    /// Upsert a single file and its symbols/texts/references.
-    /// Removes old data for this path first, then inserts new data.
-    /// Does not rebuild FTS indexes - caller should call rebuild_fts() after batch operations.
-    pub fn upsert_file(
+    /// Find symbols that no reference in their project points to (dead-code candidates).
+    ///
+    /// Matching follows `get_callers`, loosened to base names so a symbol is only
+    /// reported when nothing could be calling it: `Class.method` counts as referenced
+    /// by `self.method`, `this.method`, `obj.method` or `Class::method`. Imports,
+    /// modules, impl blocks and sections are never reported; entry points such as
+    /// `main` are, since nothing in the index calls them.
+    ///
+    /// Visibility defaults to "public", like `undocumented_symbols`.
+    pub fn find_unreferenced(
         &self,
-        project: &str,
-        file: &FileEntry,
-        symbols: &[SymbolEntry],
-        texts: &[TextEntry],
-        references: &[ReferenceEntry],
-    ) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
-
-        // Remove old data for this file
-        tx.execute(
-            "DELETE FROM files WHERE project = ?1 AND path = ?2",
-            rusqlite::params![project, &file.path],
-        )?;
-        tx.execute(
-            "DELETE FROM symbols WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, &file.path],
-        )?;
-        tx.execute(
-            "DELETE FROM texts WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, &file.path],
-        )?;
-        tx.execute(
-            "DELETE FROM refs WHERE project = ?1 AND file = ?2",
-            rusqlite::params![project, &file.path],
-        )?;
-
-        // Insert file
-        let parent_path = match file.path.rfind('/') {
-            Some(pos) => &file.path[..pos],
-            None => ".",
-        };
-        tx.execute(
-            "INSERT INTO files (project, path, parent_path, lang, hash, lines, title, description) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            rusqlite::params![project, file.path, parent_path, file.lang, file.hash, file.lines, file.title, file.description],
-        )?;
+        project: Option<&str>,
+        kind: &[String],
+        visibility: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<SymbolEntry>> {
+        let mut conditions =
+            vec!["s.kind NOT IN ('import', 'module', 'impl', 'section')".to_string()];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
-        // Insert symbols
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO symbols (project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, visibility_level)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-            )?;
-            for s in symbols {
-                stmt.execute(rusqlite::params![
-                    project,
-                    s.file,
-                    s.name,
-                    s.kind,
-                    s.line[0],
-                    s.line[1],
-                    s.parent,
-                    s.tokens,
-                    s.alias,
-                    s.visibility,
-                    visibility_to_level(s.visibility.as_deref()),
-                ])?;
+        if let Some(p) = project {
+            conditions.push("s.project = ?".to_string());
+            params.push(Box::new(p.to_string()));
+        }
+        if !kind.is_empty() {
+            let placeholders = vec!["?"; kind.len()].join(", ");
+            conditions.push(format!("s.kind IN ({})", placeholders));
+            for k in kind {
+                params.push(Box::new(canonical_kind(k)));
             }
         }
+        if let Some(level) = visibility_max_level(visibility, "public") {
+            conditions.push("s.visibility_level <= ?".to_string());
+            params.push(Box::new(level));
+        }
+        // Base names of everything referenced, per project (evaluated once)
+        conditions.push(format!(
+            "(s.project, {}) NOT IN (SELECT project, {} FROM refs)",
+            sql_base_name("s.name"),
+            sql_base_name("replace(name, '::', '.')")
+        ));
+        params.push(Box::new(limit));
+        params.push(Box::new(offset));
 
-        // Insert texts
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO texts (project, file, kind, line_start, line_end, text, parent)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            )?;
-            for t in texts {
-                stmt.execute(rusqlite::params![
-                    project, t.file, t.kind, t.line[0], t.line[1], t.text, t.parent,
-                ])?;
-            }
+        let sql = format!(
+            "SELECT s.project, s.file, s.name, s.kind, s.line_start, s.line_end, s.parent, s.tokens, s.alias, s.visibility, s.sig
+             FROM symbols s
+             WHERE {}
+             ORDER BY s.project, s.file, s.line_start
+             LIMIT ? OFFSET ?",
+            conditions.join(" AND ")
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), symbol_from_row)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
         }
+        Ok(results)
+    }
 
-        // Insert references
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO refs (project, file, name, kind, line_start, line_end, caller)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            )?;
-            for r in references {
-                stmt.execute(rusqlite::params![
-                    project, r.file, r.name, r.kind, r.line[0], r.line[1], r.caller,
-                ])?;
+    /// Resolve a reference name to candidate definitions, best match first.
+    ///
+    /// References store names as written at the call site (`self.handle`,
+    /// `os.path.join`, `Config::new`), so resolution is heuristic. Candidates are
+    /// symbols whose name equals the reference or shares its base name, scored by:
+    /// - exact name match (after normalizing `::` to `.` and dropping `self.`/`this.`): +100
+    /// - base-name match (`join` for `os.path.join`): +50
+    /// - defined in `from_file`: +30, or in the same directory: +10
+    /// - defined in `project`: +20
+    ///
+    /// `from_file` is relative to `project`, so the file and directory bonuses only
+    /// apply to candidates of that project (of any, without one). Other projects
+    /// still contribute candidates. The base name is compared as a literal suffix,
+    /// so `_` or `%` in it match only themselves.
+    /// Imports are not definitions and are never returned.
+    pub fn resolve_reference(
+        &self,
+        ref_name: &str,
+        from_file: Option<&str>,
+        project: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<(SymbolEntry, u32)>> {
+        let normalized = normalize_ref_name(ref_name);
+        let base = normalized
+            .rsplit('.')
+            .next()
+            .unwrap_or(&normalized)
+            .to_string();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE (name = ?1 OR name = ?2 OR name = ?3
+                    OR substr(name, -length(?3) - 1) = '.' || ?3)
+               AND kind != 'import'",
+        )?;
+        let candidates = stmt.query_map(
+            rusqlite::params![ref_name, normalized, base],
+            symbol_from_row,
+        )?;
+
+        let dir_of = |path: &str| path.rsplit_once('/').map(|(d, _)| d.to_string());
+        let from_dir = from_file.and_then(dir_of);
+
+        let mut scored = Vec::new();
+        for candidate in candidates {
+            let symbol = candidate?;
+            let mut score = if symbol.name == ref_name || symbol.name == normalized {
+                100
+            } else {
+                50
+            };
+            if project.is_none_or(|p| p == symbol.project) {
+                if from_file == Some(symbol.file.as_str()) {
+                    score += 30;
+                } else if from_dir.is_some() && dir_of(&symbol.file) == from_dir {
+                    score += 10;
+                }
+            }
+            if project == Some(symbol.project.as_str()) {
+                score += 20;
             }
+            scored.push((symbol, score));
         }
 
-        tx.commit()?;
-        Ok(())
+        // Highest score first; stable tie-break on location for deterministic output
+        scored.sort_by(|(a, sa), (b, sb)| {
+            sb.cmp(sa)
+                .then_with(|| a.project.cmp(&b.project))
+                .then_with(|| a.file.cmp(&b.file))
+                .then_with(|| a.line[0].cmp(&b.line[0]))
+        });
+        scored.truncate(limit as usize);
+        Ok(scored)
     }
 
-    /// Rebuild unified FTS5 index.
-    /// Call this after batch upsert/remove operations.
-    /// No-op when FTS is disabled (build mode).
-    pub fn rebuild_fts(&self) -> Result<()> {
-        if !self.fts_enabled {
-            return Ok(());
-        }
-        self.conn.execute_batch(
-            "
-            DELETE FROM search_fts;
-
-            -- Insert files: name=title, file=path, content=description
-            INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-            SELECT
-                COALESCE(title, ''),
-                COALESCE(path, ''),
-                COALESCE(description, ''),
-                'file',
-                rowid,
-                path,
-                lang,
-                project,
-                0
-            FROM files;
-
-            -- Insert symbols: name=symbol name, file=path, content=kind + tokens
-            INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-            SELECT
-                COALESCE(name, ''),
-                COALESCE(file, ''),
-                COALESCE(kind, '') || ' ' || COALESCE(tokens, ''),
-                'symbol',
-                rowid,
-                file,
-                kind,
-                project,
-                visibility_level
-            FROM symbols;
-
-            -- Insert texts: name=empty, file=path, content=text
-            INSERT INTO search_fts(name, file, content, type, rowid_ref, path, kind, project, visibility_level)
-            SELECT
-                '',
-                COALESCE(file, ''),
-                COALESCE(text, ''),
-                'text',
-                rowid,
-                file,
-                kind,
-                project,
-                0
-            FROM texts;
-            ",
+    /// Public, non-import symbols of a project (its API surface), ordered by name.
+    pub fn public_symbols(&self, project: &str) -> Result<Vec<SymbolEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols
+             WHERE project = ?1 AND visibility_level = 1 AND kind != 'import'
+             ORDER BY name, file, line_start",
         )?;
-        Ok(())
+        let rows = stmt.query_map([project], symbol_from_row)?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
     }
 
-    /// Export all data from DB back to vecs (for flushing to disk).
-    #[allow(clippy::type_complexity)]
-    pub fn export_all(
+    /// Compare `old_symbols` (e.g. a `.codeindex/` from the base branch) with the
+    /// live symbols of `project`, imports excluded.
+    ///
+    /// Symbols are matched by `(file, name, kind)`, pairing same-named ones
+    /// (overloads) in line order. A matched symbol whose start or end line
+    /// shifted by at least [`SYMBOL_MOVE_THRESHOLD`] is reported as moved;
+    /// unmatched ones as added or removed. Ordered by file, then line.
+    pub fn symbol_diff(
         &self,
-    ) -> Result<(
-        Vec<FileEntry>,
-        Vec<SymbolEntry>,
-        Vec<TextEntry>,
-        Vec<ReferenceEntry>,
-    )> {
-        let mut files = Vec::new();
-        let mut symbols = Vec::new();
-        let mut texts = Vec::new();
-        let mut references = Vec::new();
+        old_symbols: &[SymbolEntry],
+        project: &str,
+    ) -> Result<Vec<SymbolDelta>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT file, name, kind, line_start, line_end, visibility
+             FROM symbols
+             WHERE project = ?1 AND kind != 'import'
+             ORDER BY file, name, kind, line_start",
+        )?;
+        let rows = stmt.query_map([project], |row| {
+            Ok(SymbolDelta {
+                change: "added".to_string(),
+                file: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                line: [row.get(3)?, row.get(4)?],
+                visibility: row.get(5)?,
+                old_line: None,
+            })
+        })?;
+        let mut current: BTreeMap<(String, String, String), Vec<SymbolDelta>> = BTreeMap::new();
+        for row in rows {
+            let symbol = row?;
+            let key = (
+                symbol.file.clone(),
+                symbol.name.clone(),
+                symbol.kind.clone(),
+            );
+            current.entry(key).or_default().push(symbol);
+        }
 
-        // Export files
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, path, lang, hash, lines, title, description FROM files ORDER BY project, path",
-            )?;
-            let rows = stmt.query_map([], |row| {
-                Ok(FileEntry {
-                    project: row.get(0)?,
-                    path: row.get(1)?,
-                    lang: row.get(2)?,
-                    hash: row.get(3)?,
-                    lines: row.get(4)?,
-                    title: row.get(5)?,
-                    description: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                files.push(row?);
-            }
+        let mut old: BTreeMap<(String, String, String), Vec<&SymbolEntry>> = BTreeMap::new();
+        for symbol in old_symbols.iter().filter(|s| s.kind != "import") {
+            let key = (
+                symbol.file.clone(),
+                symbol.name.clone(),
+                symbol.kind.clone(),
+            );
+            old.entry(key).or_default().push(symbol);
+        }
+        for symbols in old.values_mut() {
+            symbols.sort_by_key(|s| s.line);
         }
 
-        // Export symbols
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                 FROM symbols
-                 ORDER BY project, file, line_start",
-            )?;
-            let rows = stmt.query_map([], |row| {
-                Ok(SymbolEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    name: row.get(2)?,
-                    kind: row.get(3)?,
-                    line: [row.get(4)?, row.get(5)?],
-                    parent: row.get(6)?,
-                    tokens: row.get(7)?,
-                    alias: row.get(8)?,
-                    visibility: row.get(9)?,
-                })
-            })?;
-            for row in rows {
-                symbols.push(row?);
+        let mut deltas = Vec::new();
+        for (key, symbols) in current {
+            let mut before = old.remove(&key).unwrap_or_default().into_iter();
+            for mut symbol in symbols {
+                match before.next() {
+                    Some(previous) => {
+                        let shift = |a: u32, b: u32| a.abs_diff(b);
+                        if shift(symbol.line[0], previous.line[0]) >= SYMBOL_MOVE_THRESHOLD
+                            || shift(symbol.line[1], previous.line[1]) >= SYMBOL_MOVE_THRESHOLD
+                        {
+                            symbol.change = "moved".to_string();
+                            symbol.old_line = Some(previous.line);
+                            deltas.push(symbol);
+                        }
+                    }
+                    None => deltas.push(symbol),
+                }
             }
+            old.insert(key, before.collect());
+        }
+        for symbol in old.into_values().flatten() {
+            deltas.push(SymbolDelta {
+                change: "removed".to_string(),
+                file: symbol.file.clone(),
+                name: symbol.name.clone(),
+                kind: symbol.kind.clone(),
+                visibility: symbol.visibility.clone(),
+                line: symbol.line,
+                old_line: None,
+            });
         }
+        deltas.sort_by(|a, b| (&a.file, a.line, &a.name).cmp(&(&b.file, b.line, &b.name)));
+        Ok(deltas)
+    }
 
-        // Export texts
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, kind, line_start, line_end, text, parent
-                 FROM texts
-                 ORDER BY project, file, line_start",
-            )?;
-            let rows = stmt.query_map([], |row| {
-                Ok(TextEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    kind: row.get(2)?,
-                    line: [row.get(3)?, row.get(4)?],
-                    text: row.get(5)?,
-                    parent: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                texts.push(row?);
+    /// Find circular import dependencies between files of one project.
+    ///
+    /// Builds the file-level graph from `import` references, resolving each
+    /// specifier to an indexed file of the same project (best-effort; external
+    /// packages are dropped), and returns every cycle as a sorted list of files.
+    pub fn find_import_cycles(&self, project: &str) -> Result<Vec<Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path FROM files WHERE project = ?1")?;
+        let files = stmt
+            .query_map([project], |row| row.get(0))?
+            .collect::<rusqlite::Result<HashSet<String>>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT file, name FROM refs WHERE project = ?1 AND kind = 'import'",
+        )?;
+        let rows = stmt.query_map([project], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut edges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for row in rows {
+            let (file, name) = row?;
+            if let Some(target) = imports::resolve_import(&name, &file, &files)
+                && target != file
+            {
+                edges.entry(file).or_default().insert(target);
             }
         }
+        Ok(imports::find_cycles(&edges))
+    }
 
-        // Export references
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, name, kind, line_start, line_end, caller
-                 FROM refs
-                 ORDER BY project, file, line_start",
-            )?;
-            let rows = stmt.query_map([], |row| {
-                Ok(ReferenceEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    name: row.get(2)?,
-                    kind: row.get(3)?,
-                    line: [row.get(4)?, row.get(5)?],
-                    caller: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                references.push(row?);
+    /// Import symbols of `file` that nothing else in the file uses.
+    ///
+    /// Each import is looked up by the names it binds (its alias, else the
+    /// first and last segments of the specifier, see `imports::import_bindings`)
+    /// among the identifiers of the file's non-import references and of its
+    /// symbols' signatures and tokens. Best-effort: a use the extractors don't
+    /// record (e.g. a bare attribute access at module level) reports the import
+    /// as unused, and imports whose bindings can't be told are never reported.
+    ///
+    /// Without `project`, `file` is looked up in every project.
+    pub fn unused_imports(&self, project: Option<&str>, file: &str) -> Result<Vec<SymbolEntry>> {
+        let projects = match project {
+            Some(project) => vec![project.to_string()],
+            None => {
+                let mut stmt = self
+                    .conn
+                    .prepare("SELECT project FROM files WHERE path = ?1 ORDER BY project")?;
+                stmt.query_map([file], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?
             }
-        }
+        };
 
-        Ok((files, symbols, texts, references))
+        let mut unused = Vec::new();
+        for project in projects {
+            let (symbols, _, refs) = self.get_file_contents(&project, file)?;
+            let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+            let mut used: HashSet<&str> = HashSet::new();
+            for r in refs.iter().filter(|r| r.kind != "import") {
+                used.extend(r.name.split(|c| !is_ident(c)));
+            }
+            for s in symbols.iter().filter(|s| s.kind != "import") {
+                for text in [&s.sig, &s.tokens].into_iter().flatten() {
+                    used.extend(text.split(|c| !is_ident(c)));
+                }
+            }
+
+            unused.extend(
+                symbols
+                    .iter()
+                    .filter(|s| s.kind == "import")
+                    .filter(|s| {
+                        let bindings = imports::import_bindings(&s.name, s.alias.as_deref());
+                        !bindings.is_empty() && !bindings.iter().any(|b| used.contains(b))
+                    })
+                    .cloned(),
+            );
+        }
+        Ok(unused)
     }
 
-    /// Export data for a specific project from DB back to vecs (for flushing to disk).
-    #[allow(clippy::type_complexity)]
-    pub fn export_for_project(
+    /// Get all references TO a symbol (who calls/uses this symbol).
+    /// Returns references sorted by file, then line.
+    ///
+    /// Supports flexible name matching:
+    /// - Exact match: "self.handle_exception" matches "self.handle_exception"
+    /// - Base name match: "handle_exception" matches "self.handle_exception"
+    ///
+    /// This handles OOP patterns where references are stored with receiver prefixes
+    /// (self., this., etc.) but users query with base method names.
+    ///
+    /// If visibility is specified, only references to symbols at that visibility level
+    /// or higher are returned. The target symbol's visibility is looked up in the symbols table.
+    /// See [`RefFilters`] for the other filters.
+    pub fn get_callers(
         &self,
-        project: &str,
-    ) -> Result<(
-        Vec<FileEntry>,
-        Vec<SymbolEntry>,
-        Vec<TextEntry>,
-        Vec<ReferenceEntry>,
-    )> {
-        let mut files = Vec::new();
-        let mut symbols = Vec::new();
-        let mut texts = Vec::new();
-        let mut references = Vec::new();
+        name: &str,
+        filters: &RefFilters,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ReferenceEntry>> {
+        let RefFilters {
+            kinds,
+            projects,
+            visibility,
+            distinct_by_caller,
+        } = filters;
+        let max_level = visibility_max_level(visibility.as_deref(), "private");
 
-        // Export files
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, path, lang, hash, lines, title, description FROM files WHERE project = ?1 ORDER BY path",
-            )?;
-            let rows = stmt.query_map([project], |row| {
-                Ok(FileEntry {
-                    project: row.get(0)?,
-                    path: row.get(1)?,
-                    lang: row.get(2)?,
-                    hash: row.get(3)?,
-                    lines: row.get(4)?,
-                    title: row.get(5)?,
-                    description: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                files.push(row?);
-            }
-        }
+        // Match exact name OR names ending with .{name} (e.g., "self.foo" matches query "foo")
+        let mut conditions = vec!["(r.name = ? OR r.name LIKE ?)".to_string()];
+        let like_pattern = format!("%.{}", name);
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
+            vec![Box::new(name.to_string()), Box::new(like_pattern)];
 
-        // Export symbols
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility
-                 FROM symbols
-                 WHERE project = ?1
-                 ORDER BY file, line_start",
-            )?;
-            let rows = stmt.query_map([project], |row| {
-                Ok(SymbolEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    name: row.get(2)?,
-                    kind: row.get(3)?,
-                    line: [row.get(4)?, row.get(5)?],
-                    parent: row.get(6)?,
-                    tokens: row.get(7)?,
-                    alias: row.get(8)?,
-                    visibility: row.get(9)?,
-                })
-            })?;
-            for row in rows {
-                symbols.push(row?);
-            }
+        if let Some(condition) = ref_kind_condition("r.kind", kinds, &mut params) {
+            conditions.push(condition);
         }
-
-        // Export texts
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, kind, line_start, line_end, text, parent
-                 FROM texts
-                 WHERE project = ?1
-                 ORDER BY file, line_start",
-            )?;
-            let rows = stmt.query_map([project], |row| {
-                Ok(TextEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    kind: row.get(2)?,
-                    line: [row.get(3)?, row.get(4)?],
-                    text: row.get(5)?,
-                    parent: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                texts.push(row?);
+        if !projects.is_empty() {
+            let placeholders = vec!["?"; projects.len()].join(", ");
+            conditions.push(format!("r.project IN ({})", placeholders));
+            for p in projects {
+                params.push(Box::new(p.clone()));
             }
         }
-
-        // Export references
-        {
-            let mut stmt = self.conn.prepare(
-                "SELECT project, file, name, kind, line_start, line_end, caller
-                 FROM refs
-                 WHERE project = ?1
-                 ORDER BY file, line_start",
-            )?;
-            let rows = stmt.query_map([project], |row| {
-                Ok(ReferenceEntry {
-                    project: row.get(0)?,
-                    file: row.get(1)?,
-                    name: row.get(2)?,
-                    kind: row.get(3)?,
-                    line: [row.get(4)?, row.get(5)?],
-                    caller: row.get(6)?,
-                })
-            })?;
-            for row in rows {
-                references.push(row?);
-            }
+        if *distinct_by_caller {
+            conditions.push(distinct_ref_condition(kinds, &mut params));
         }
 
-        Ok((files, symbols, texts, references))
-    }
+        // Visibility filter: join with symbols to filter by target symbol's visibility_level
+        let sql = if let Some(level) = max_level {
+            params.push(Box::new(level));
+            params.push(Box::new(limit));
+            params.push(Box::new(offset));
+
+            format!(
+                "SELECT DISTINCT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
+                 FROM refs r
+                 INNER JOIN symbols s ON (s.name = r.name OR s.name LIKE '%.' || r.name)
+                                      AND s.project = r.project
+                 WHERE {} AND s.visibility_level <= ?
+                 ORDER BY r.file, r.line_start
+                 LIMIT ? OFFSET ?",
+                conditions.join(" AND ")
+            )
+        } else {
+            // No visibility filter, simple query
+            params.push(Box::new(limit));
+            params.push(Box::new(offset));
+
+            format!(
+                "SELECT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
+                 FROM refs r
+                 WHERE {}
+                 ORDER BY r.file, r.line_start
+                 LIMIT ? OFFSET ?",
+                conditions.join(" AND ")
+            )
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
+            Ok(ReferenceEntry {
+                project: row.get(0)?,
+                file: row.get(1)?,
+                name: row.get(2)?,
+                kind: row.get(3)?,
+                line: [row.get(4)?, row.get(5)?],
+                caller: row.get(6)?,
+            })
+        })?;
 
-    /// List all unique projects in the database.
-    pub fn list_projects(&self) -> Result<Vec<String>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT DISTINCT project FROM files ORDER BY project")?;
-        let rows = stmt.query_map([], |row| row.get(0))?;
         let mut results = Vec::new();
         for row in rows {
             results.push(row?);
@@ -1293,693 +2259,5020 @@ impl SearchDb {
         Ok(results)
     }
 
-    /// Get directory overview: count of files per (parent_path, lang, min_visibility_level).
-    ///
-    /// Returns Vec of (parent_path, lang, min_visibility_level, count) tuples, sorted by parent_path.
-    /// Skips dotfiles (paths starting with '.' or containing '/.').
-    ///
-    /// min_visibility_level is the minimum (most visible) visibility level of symbols in each file:
-    /// - 0 = files with no symbols (documentation, configs, etc.)
-    /// - 1 = files with public symbols
-    /// - 2 = files with internal symbols (but no public)
-    /// - 3 = files with only private symbols
+    /// Walk callers transitively (impact analysis): who calls `name`, who calls those, etc.
     ///
-    /// This allows callers to filter/display based on visibility without hiding files entirely.
-    #[allow(clippy::type_complexity)]
-    pub fn explore_dir_overview(
+    /// Breadth-first over the `refs` graph. Each level looks up callers with the
+    /// same matching as `get_callers`, by base name, since callers are recorded
+    /// qualified (`Server.start`) while call sites are not (`self.start`). Every
+    /// distinct `caller` found becomes part of the next level; a visited set stops
+    /// cycles. Stops at `max_depth`, when a level finds nothing new, or at `limit`
+    /// references. Results are ordered by depth, then file and line.
+    pub fn get_callers_transitive(
         &self,
-        project: &str,
-        path_prefix: Option<&str>,
-    ) -> Result<Vec<(String, Option<String>, i32, usize)>> {
-        let base = path_prefix
-            .map(|p| p.trim_end_matches('/'))
-            .filter(|p| !p.is_empty());
-
-        // Query groups files by (parent_path, lang, min_visibility_level)
-        // First compute each file's minimum visibility level, then group by that
-        // Files without symbols get min_visibility_level = 0
-        match base {
-            Some(prefix) => {
-                let glob = format!("{}/*", prefix);
-                let mut stmt = self.conn.prepare(
-                    "WITH file_min_vis AS (
-                        SELECT f.parent_path, f.path, f.lang,
-                               COALESCE(MIN(s.visibility_level), 3) as min_vis
-                        FROM files f
-                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
-                        WHERE f.project = ?1 AND f.path GLOB ?2
-                          AND f.path NOT GLOB '.*' AND f.path NOT GLOB '*/.*'
-                        GROUP BY f.parent_path, f.path, f.lang
-                    )
-                    SELECT parent_path, lang, min_vis, COUNT(*) as cnt
-                    FROM file_min_vis
-                    GROUP BY parent_path, lang, min_vis
-                    ORDER BY parent_path, lang, min_vis",
-                )?;
-                stmt.query_map(rusqlite::params![project, glob], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, Option<String>>(1)?,
-                        row.get::<_, i32>(2)?,
-                        row.get::<_, usize>(3)?,
-                    ))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()
-                .context("explore_dir_overview query failed")
+        name: &str,
+        project: Option<&str>,
+        max_depth: u32,
+        limit: u32,
+    ) -> Result<Vec<CallTreeEntry>> {
+        let mut results = Vec::new();
+        let mut visited: HashSet<String> = HashSet::from([name.to_string()]);
+        let mut frontier = vec![name.to_string()];
+
+        for depth in 1..=max_depth {
+            let mut next = Vec::new();
+            let mut level = Vec::new();
+            let mut seen_refs = HashSet::new();
+            for node in &frontier {
+                let base = node.rsplit('.').next().unwrap_or(node);
+                let remaining = limit.saturating_sub((results.len() + level.len()) as u32);
+                if remaining == 0 {
+                    break;
+                }
+                let filters = RefFilters {
+                    projects: project.map(str::to_string).into_iter().collect(),
+                    ..Default::default()
+                };
+                for reference in self.get_callers(base, &filters, remaining, 0)? {
+                    // Nodes sharing a base name find the same call sites
+                    let key = (
+                        reference.project.clone(),
+                        reference.file.clone(),
+                        reference.line,
+                        reference.name.clone(),
+                    );
+                    if !seen_refs.insert(key) {
+                        continue;
+                    }
+                    if let Some(caller) = &reference.caller
+                        && visited.insert(caller.clone())
+                    {
+                        next.push(caller.clone());
+                    }
+                    level.push(CallTreeEntry { depth, reference });
+                }
             }
-            None => {
-                let mut stmt = self.conn.prepare(
-                    "WITH file_min_vis AS (
-                        SELECT f.parent_path, f.path, f.lang,
-                               COALESCE(MIN(s.visibility_level), 3) as min_vis
-                        FROM files f
-                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
-                        WHERE f.project = ?1
-                          AND f.path NOT GLOB '.*' AND f.path NOT GLOB '*/.*'
-                        GROUP BY f.parent_path, f.path, f.lang
-                    )
-                    SELECT parent_path, lang, min_vis, COUNT(*) as cnt
-                    FROM file_min_vis
-                    GROUP BY parent_path, lang, min_vis
-                    ORDER BY parent_path, lang, min_vis",
-                )?;
-                stmt.query_map(rusqlite::params![project], |row| {
-                    Ok((
-                        row.get::<_, String>(0)?,
-                        row.get::<_, Option<String>>(1)?,
-                        row.get::<_, i32>(2)?,
-                        row.get::<_, usize>(3)?,
-                    ))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()
-                .context("explore_dir_overview query failed")
+            level.sort_by(|a, b| {
+                (&a.reference.file, a.reference.line).cmp(&(&b.reference.file, b.reference.line))
+            });
+            results.append(&mut level);
+            if next.is_empty() || results.len() >= limit as usize {
+                break;
             }
+            frontier = next;
         }
+        Ok(results)
     }
 
-    /// Get files in a specific directory.
+    /// Get all references FROM a symbol (what does this symbol call/use).
+    /// Returns references sorted by file, then line.
     ///
-    /// Returns Vec of (filename, lang) tuples, sorted by filename.
-    pub fn explore_dir_files(
+    /// Supports flexible caller name matching:
+    /// - Exact match: "MyClass.method" matches "MyClass.method"
+    /// - Base name match: "method" matches "MyClass.method"
+    ///
+    /// This handles qualified names where the caller context includes class prefixes.
+    ///
+    /// If visibility is specified, only references to symbols at that visibility level
+    /// or higher are returned. The referenced symbol's visibility is looked up in the symbols table.
+    /// See [`RefFilters`] for the other filters.
+    pub fn get_callees(
+        &self,
+        caller: &str,
+        filters: &RefFilters,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<ReferenceEntry>> {
+        let RefFilters {
+            kinds,
+            projects,
+            visibility,
+            distinct_by_caller,
+        } = filters;
+        let max_level = visibility_max_level(visibility.as_deref(), "private");
+
+        // Match exact caller OR callers ending with .{caller} (e.g., "Class.method" matches query "method")
+        let mut conditions = vec!["(r.caller = ? OR r.caller LIKE ?)".to_string()];
+        let like_pattern = format!("%.{}", caller);
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> =
+            vec![Box::new(caller.to_string()), Box::new(like_pattern)];
+
+        if let Some(condition) = ref_kind_condition("r.kind", kinds, &mut params) {
+            conditions.push(condition);
+        }
+        if !projects.is_empty() {
+            let placeholders = vec!["?"; projects.len()].join(", ");
+            conditions.push(format!("r.project IN ({})", placeholders));
+            for p in projects {
+                params.push(Box::new(p.clone()));
+            }
+        }
+        if *distinct_by_caller {
+            conditions.push(distinct_ref_condition(kinds, &mut params));
+        }
+
+        // Visibility filter: join with symbols to filter by referenced symbol's visibility_level
+        let sql = if let Some(level) = max_level {
+            params.push(Box::new(level));
+            params.push(Box::new(limit));
+            params.push(Box::new(offset));
+
+            format!(
+                "SELECT DISTINCT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
+                 FROM refs r
+                 INNER JOIN symbols s ON (s.name = r.name OR s.name LIKE '%.' || r.name)
+                                      AND s.project = r.project
+                 WHERE {} AND s.visibility_level <= ?
+                 ORDER BY r.file, r.line_start
+                 LIMIT ? OFFSET ?",
+                conditions.join(" AND ")
+            )
+        } else {
+            // No visibility filter, simple query
+            params.push(Box::new(limit));
+            params.push(Box::new(offset));
+
+            format!(
+                "SELECT r.project, r.file, r.name, r.kind, r.line_start, r.line_end, r.caller
+                 FROM refs r
+                 WHERE {}
+                 ORDER BY r.file, r.line_start
+                 LIMIT ? OFFSET ?",
+                conditions.join(" AND ")
+            )
+        };
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+
+        let rows = stmt.query_map(rusqlite::params_from_iter(param_refs), |row| {
+            Ok(ReferenceEntry {
+                project: row.get(0)?,
+                file: row.get(1)?,
+                name: row.get(2)?,
+                kind: row.get(3)?,
+                line: [row.get(4)?, row.get(5)?],
+                caller: row.get(6)?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Get the hash of a file from the DB (for change detection).
+    pub fn get_file_hash(&self, project: &str, path: &str) -> Result<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT hash FROM files WHERE project = ?1 AND path = ?2")?;
+        let mut rows = stmt.query(rusqlite::params![project, path])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Content hash of every file of a project, keyed by path.
+    pub fn file_hashes(&self, project: &str) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, hash FROM files WHERE project = ?1")?;
+        let rows = stmt.query_map([project], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let mut results = HashMap::new();
+        for row in rows {
+            let (path, hash) = row?;
+            results.insert(path, hash);
+        }
+        Ok(results)
+    }
+
+    /// Find an already-indexed file with the given content hash and language in
+    /// one of `projects`. Used to reuse extraction results for duplicated files
+    /// instead of reparsing; `projects` are those whose extraction would match.
+    /// Only files marked by [`SearchDb::mark_parsed_clean`] qualify.
+    pub fn find_file_by_hash(
+        &self,
+        hash: &str,
+        lang: Option<&str>,
+        projects: &[String],
+    ) -> Result<Option<FileEntry>> {
+        if projects.is_empty() {
+            return Ok(None);
+        }
+        let placeholders = vec!["?"; projects.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT project, path, lang, hash, lines, title, description, is_test
+             FROM files WHERE hash = ? AND lang IS ? AND parsed_clean = 1
+             AND project IN ({placeholders}) LIMIT 1"
+        ))?;
+        let mut params: Vec<&dyn rusqlite::ToSql> = vec![&hash, &lang];
+        params.extend(projects.iter().map(|p| p as &dyn rusqlite::ToSql));
+        let mut rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+            Ok(FileEntry {
+                project: row.get(0)?,
+                path: row.get(1)?,
+                lang: row.get(2)?,
+                hash: row.get(3)?,
+                lines: row.get(4)?,
+                title: row.get(5)?,
+                description: row.get(6)?,
+                is_test: row.get(7)?,
+            })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Record that a file's results come from a full parse without syntax errors,
+    /// so identical files may copy them (see [`SearchDb::find_file_by_hash`]).
+    /// Files loaded from `.codeindex/`, skipped or partially parsed are not marked.
+    pub fn mark_parsed_clean(&self, project: &str, path: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE files SET parsed_clean = 1 WHERE project = ?1 AND path = ?2",
+            [project, path],
+        )?;
+        Ok(())
+    }
+
+    /// Get the symbols, texts, and references extracted from one file, in insertion order.
+    #[allow(clippy::type_complexity)]
+    pub fn get_file_contents(
         &self,
         project: &str,
-        parent_path: &str,
-    ) -> Result<Vec<(String, Option<String>)>> {
+        path: &str,
+    ) -> Result<(Vec<SymbolEntry>, Vec<TextEntry>, Vec<ReferenceEntry>)> {
         let mut stmt = self.conn.prepare(
-            "SELECT path, lang FROM files
-             WHERE project = ?1 AND parent_path = ?2
-             AND path NOT GLOB '.*' AND path NOT GLOB '*/.*'
-             ORDER BY path",
+            "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+             FROM symbols WHERE project = ?1 AND file = ?2 ORDER BY rowid",
         )?;
-        let rows: Vec<(String, Option<String>)> = stmt
-            .query_map(rusqlite::params![project, parent_path], |row| {
-                let path: String = row.get(0)?;
-                let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
-                Ok((filename, row.get(1)?))
+        let symbols = stmt
+            .query_map([project, path], symbol_from_row)?
+            .collect::<std::result::Result<_, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, kind, line_start, line_end, text, parent
+             FROM texts WHERE project = ?1 AND file = ?2 ORDER BY rowid",
+        )?;
+        let texts = stmt
+            .query_map([project, path], |row| {
+                Ok(TextEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    kind: row.get(2)?,
+                    line: [row.get(3)?, row.get(4)?],
+                    text: row.get(5)?,
+                    parent: row.get(6)?,
+                })
             })?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
+            .collect::<std::result::Result<_, _>>()?;
 
-        Ok(rows)
+        let mut stmt = self.conn.prepare(
+            "SELECT project, file, name, kind, line_start, line_end, caller
+             FROM refs WHERE project = ?1 AND file = ?2 ORDER BY rowid",
+        )?;
+        let references = stmt
+            .query_map([project, path], |row| {
+                Ok(ReferenceEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    name: row.get(2)?,
+                    kind: row.get(3)?,
+                    line: [row.get(4)?, row.get(5)?],
+                    caller: row.get(6)?,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok((symbols, texts, references))
     }
 
-    /// Get files capped at N per (parent_path, lang), excluding markdown.
-    ///
-    /// Returns Vec of (parent_path, filename, lang) tuples.
-    /// Uses ROW_NUMBER() to limit files per directory+language group.
+    /// Remove all data for a file (from files, symbols, texts, refs tables).
+    /// Does not touch FTS indexes - caller should call update_fts_for_file() for a single
+    /// change, or rebuild_fts_project() after batch operations.
+    pub fn remove_file(&self, project: &str, path: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        #[cfg(feature = "lang-cpp")]
+        let paired_names = crate::parser::cpp::is_header_or_source(path)
+            .then(|| function_names(&tx, project, path))
+            .transpose()?;
+
+        tx.execute(
+            "DELETE FROM files WHERE project = ?1 AND path = ?2",
+            rusqlite::params![project, path],
+        )?;
+        tx.execute(
+            "DELETE FROM symbols WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, path],
+        )?;
+        tx.execute(
+            "DELETE FROM texts WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, path],
+        )?;
+        tx.execute(
+            "DELETE FROM refs WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, path],
+        )?;
+
+        // Declarations the file's definitions superseded are searchable again
+        #[cfg(feature = "lang-cpp")]
+        if let Some(names) = paired_names {
+            mark_superseded_declarations(&tx, project, Some(&names))?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove all data for a project (files, symbols, texts, refs), and drop its FTS5 table.
+    pub fn remove_project(&self, project: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if self.fts_enabled {
+            drop_fts_shard(&tx, project)?;
+        }
+
+        tx.execute(
+            "DELETE FROM files WHERE project = ?1",
+            rusqlite::params![project],
+        )?;
+        tx.execute(
+            "DELETE FROM symbols WHERE project = ?1",
+            rusqlite::params![project],
+        )?;
+        tx.execute(
+            "DELETE FROM texts WHERE project = ?1",
+            rusqlite::params![project],
+        )?;
+        tx.execute(
+            "DELETE FROM refs WHERE project = ?1",
+            rusqlite::params![project],
+        )?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Files by the time they were last written to the index, most recent first.
     ///
-    /// If visibility is specified, only returns files that contain symbols at that
-    /// visibility level or higher.
-    pub fn explore_files_capped(
+    /// `load` and `upsert_file` stamp `indexed_at`, so this reflects what codeix
+    /// actually re-parsed (watcher events, `reindex`), not git history. With
+    /// `since`, only files indexed at or after that time are returned.
+    pub fn recent_files(
         &self,
-        project: &str,
-        path_prefix: Option<&str>,
-        visibility: Option<&str>,
-        cap: usize,
-    ) -> Result<Vec<(String, String, Option<String>)>> {
-        let base = path_prefix
-            .map(|p| p.trim_end_matches('/'))
-            .filter(|p| !p.is_empty());
-
-        // Cap to i64::MAX to avoid overflow when cap is usize::MAX
-        let cap_i64 = cap.min(i64::MAX as usize) as i64;
+        project: Option<&str>,
+        since: Option<SystemTime>,
+        limit: u32,
+    ) -> Result<Vec<RecentFile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT project, path, lang, lines, indexed_at FROM files
+             WHERE (?1 IS NULL OR project = ?1) AND indexed_at >= ?2
+             ORDER BY indexed_at DESC, project, path
+             LIMIT ?3",
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![project, since.map_or(0, unix_millis), limit],
+            |row| {
+                Ok(RecentFile {
+                    project: row.get(0)?,
+                    path: row.get(1)?,
+                    lang: row.get(2)?,
+                    lines: row.get(3)?,
+                    indexed_at: row.get::<_, i64>(4)?.max(0) as u64,
+                })
+            },
+        )?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
 
-        let max_level = visibility_max_level(visibility, "public");
+    /// Upsert a single file and its symbols/texts/references.
+    /// Removes old data for this path first, then inserts new data.
+    /// Does not touch FTS indexes - caller should call update_fts_for_file() for a single
+    /// change, or rebuild_fts_project() after batch operations.
+    pub fn upsert_file(
+        &self,
+        project: &str,
+        file: &FileEntry,
+        symbols: &[SymbolEntry],
+        texts: &[TextEntry],
+        references: &[ReferenceEntry],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        #[cfg(feature = "lang-cpp")]
+        let paired_names = crate::parser::cpp::is_header_or_source(&file.path)
+            .then(|| function_names(&tx, project, &file.path))
+            .transpose()?;
 
-        // Fetch files with known language (code + markdown)
-        // Files with lang=NULL are summarized as "+N other files" from overview
-        // When filtering by visibility:
-        // - Files with no symbols have min_visibility_level = 3 (private, hidden by default)
-        // - Files with symbols use the minimum visibility level of their symbols
-        let rows: Vec<(String, String, Option<String>)> = match (max_level, base) {
-            (Some(level), Some(prefix)) => {
-                let glob = format!("{}/*", prefix);
-                let mut stmt = self.conn.prepare(
-                    "WITH file_visibility AS (
-                        SELECT f.parent_path, f.path, f.lang,
-                               COALESCE(MIN(s.visibility_level), 3) as min_vis
-                        FROM files f
-                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
-                        WHERE f.project = ?1 AND f.path GLOB ?2
-                          AND f.lang IS NOT NULL
-                        GROUP BY f.parent_path, f.path, f.lang
-                    ),
-                    filtered_files AS (
-                        SELECT parent_path, path, lang
-                        FROM file_visibility
-                        WHERE min_vis <= ?3
-                    ),
-                    ranked AS (
-                        SELECT parent_path, path, lang,
-                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
-                        FROM filtered_files
-                    )
-                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?4 ORDER BY parent_path, path",
-                )?;
-                stmt.query_map(rusqlite::params![project, glob, level, cap_i64], |row| {
-                    let parent: String = row.get(0)?;
-                    let path: String = row.get(1)?;
-                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
-                    Ok((parent, filename, row.get(2)?))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?
-            }
-            (Some(level), None) => {
-                let mut stmt = self.conn.prepare(
-                    "WITH file_visibility AS (
-                        SELECT f.parent_path, f.path, f.lang,
-                               COALESCE(MIN(s.visibility_level), 3) as min_vis
-                        FROM files f
-                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
-                        WHERE f.project = ?1
-                          AND f.lang IS NOT NULL
-                        GROUP BY f.parent_path, f.path, f.lang
-                    ),
-                    filtered_files AS (
-                        SELECT parent_path, path, lang
-                        FROM file_visibility
-                        WHERE min_vis <= ?2
-                    ),
-                    ranked AS (
-                        SELECT parent_path, path, lang,
-                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
-                        FROM filtered_files
-                    )
-                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?3 ORDER BY parent_path, path",
-                )?;
-                stmt.query_map(rusqlite::params![project, level, cap_i64], |row| {
-                    let parent: String = row.get(0)?;
-                    let path: String = row.get(1)?;
-                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
-                    Ok((parent, filename, row.get(2)?))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?
+        // Remove old data for this file
+        tx.execute(
+            "DELETE FROM files WHERE project = ?1 AND path = ?2",
+            rusqlite::params![project, &file.path],
+        )?;
+        tx.execute(
+            "DELETE FROM symbols WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, &file.path],
+        )?;
+        tx.execute(
+            "DELETE FROM texts WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, &file.path],
+        )?;
+        tx.execute(
+            "DELETE FROM refs WHERE project = ?1 AND file = ?2",
+            rusqlite::params![project, &file.path],
+        )?;
+
+        // Insert file
+        let parent_path = match file.path.rfind('/') {
+            Some(pos) => &file.path[..pos],
+            None => ".",
+        };
+        tx.execute(
+            "INSERT INTO files (project, path, parent_path, lang, hash, lines, title, description, is_test, indexed_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![project, file.path, parent_path, file.lang, file.hash, file.lines, file.title, file.description, file.is_test, unix_millis(SystemTime::now())],
+        )?;
+
+        // Insert symbols
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO symbols (project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, visibility_level, sig)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            for s in symbols {
+                stmt.execute(rusqlite::params![
+                    project,
+                    s.file,
+                    s.name,
+                    s.kind,
+                    s.line[0],
+                    s.line[1],
+                    s.parent,
+                    s.tokens,
+                    s.alias,
+                    s.visibility,
+                    visibility_to_level(s.visibility.as_deref()),
+                    s.sig,
+                ])?;
             }
-            (None, Some(prefix)) => {
-                let glob = format!("{}/*", prefix);
-                let mut stmt = self.conn.prepare(
-                    "WITH ranked AS (
-                        SELECT parent_path, path, lang,
-                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
-                        FROM files
-                        WHERE project = ?1 AND path GLOB ?2
-                          AND lang IS NOT NULL
-                    )
-                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?3 ORDER BY parent_path, path",
-                )?;
-                stmt.query_map(rusqlite::params![project, glob, cap_i64], |row| {
-                    let parent: String = row.get(0)?;
-                    let path: String = row.get(1)?;
-                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
-                    Ok((parent, filename, row.get(2)?))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?
+        }
+
+        // Insert texts
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO texts (project, file, kind, line_start, line_end, text, parent)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for t in texts {
+                stmt.execute(rusqlite::params![
+                    project, t.file, t.kind, t.line[0], t.line[1], t.text, t.parent,
+                ])?;
             }
-            (None, None) => {
-                let mut stmt = self.conn.prepare(
-                    "WITH ranked AS (
-                        SELECT parent_path, path, lang,
-                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
-                        FROM files
-                        WHERE project = ?1
-                          AND lang IS NOT NULL
-                    )
-                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?2 ORDER BY parent_path, path",
-                )?;
-                stmt.query_map(rusqlite::params![project, cap_i64], |row| {
-                    let parent: String = row.get(0)?;
-                    let path: String = row.get(1)?;
-                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
-                    Ok((parent, filename, row.get(2)?))
-                })?
-                .collect::<std::result::Result<Vec<_>, _>>()?
+        }
+
+        // Insert references
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO refs (project, file, name, kind, line_start, line_end, caller)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for r in references {
+                stmt.execute(rusqlite::params![
+                    project, r.file, r.name, r.kind, r.line[0], r.line[1], r.caller,
+                ])?;
             }
+        }
+
+        // Functions the file declared or defined before or now may pair differently
+        #[cfg(feature = "lang-cpp")]
+        if let Some(mut names) = paired_names {
+            names.extend(
+                symbols
+                    .iter()
+                    .filter(|s| s.kind == "function" || s.kind == "method")
+                    .map(|s| s.name.clone()),
+            );
+            mark_superseded_declarations(&tx, project, Some(&names))?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Rebuild the FTS5 tables of every project.
+    /// Call this after a bulk load; single changes go through update_fts_for_file().
+    /// No-op when FTS is disabled (build mode).
+    pub fn rebuild_fts(&self) -> Result<()> {
+        if !self.fts_enabled {
+            return Ok(());
+        }
+        let query_projects = |sql: &str| -> Result<Vec<String>> {
+            let mut stmt = self.conn.prepare(sql)?;
+            let projects = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<std::result::Result<_, _>>()?;
+            Ok(projects)
         };
+        let sharded = query_projects("SELECT project FROM fts_shards")?;
+        let projects = query_projects(
+            "SELECT project FROM files UNION SELECT project FROM symbols UNION SELECT project FROM texts",
+        )?;
+        let tx = self.conn.unchecked_transaction()?;
+        for project in &sharded {
+            drop_fts_shard(&tx, project)?;
+        }
+        for project in &projects {
+            populate_fts(&tx, project, self.trigram)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
 
-        Ok(rows)
+    /// Rebuild the FTS5 table of a single project, leaving other projects untouched:
+    /// the table is dropped and refilled, so the cost scales with that project's size.
+    /// Use this after (re)indexing one project; a project without rows loses its table.
+    /// No-op when FTS is disabled (build mode).
+    pub fn rebuild_fts_project(&self, project: &str) -> Result<()> {
+        if !self.fts_enabled {
+            return Ok(());
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        drop_fts_shard(&tx, project)?;
+        let has_rows: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM files WHERE project = ?1)
+                 OR EXISTS(SELECT 1 FROM symbols WHERE project = ?1)
+                 OR EXISTS(SELECT 1 FROM texts WHERE project = ?1)",
+            [project],
+            |row| row.get(0),
+        )?;
+        if has_rows {
+            populate_fts(&tx, project, self.trigram)?;
+        }
+        tx.commit()?;
+        Ok(())
     }
-}
 
-/// Transform query for FTS5:
-/// - `a|b` → `(a OR b)` (pipe as OR shorthand)
-/// - `a|b|c` → `(a OR b OR c)`
-/// - Pipes inside quotes are preserved literally
-///
-/// See: https://www.sqlite.org/fts5.html#full_text_query_syntax
-///
-/// FTS5 syntax examples:
-/// - `parseAsync` — single term
-/// - `parse|safe` or `parse OR safe` — match either term
-/// - `parse safe` — match both terms (implicit AND)
-/// - `parse*` — prefix search (matches parseAsync, parseString, etc.)
-/// - `"safe parse"` — phrase search (exact sequence)
-/// - `parse NOT test` — exclude results containing "test"
-/// - `NEAR(parse async, 5)` — terms within 5 tokens of each other
-fn fts5_quote(s: &str) -> String {
-    transform_pipe_to_or(s)
-}
+    /// Refresh the FTS5 rows of a single file from the content tables.
+    /// Call this after `upsert_file()`/`remove_file()` for a single change: only the
+    /// rows of that file are deleted and re-inserted, the rest of the index is untouched.
+    /// A removed file simply loses its rows.
+    /// No-op when FTS is disabled (build mode).
+    pub fn update_fts_for_file(&self, project: &str, path: &str) -> Result<()> {
+        if !self.fts_enabled {
+            return Ok(());
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        delete_fts_rows(&tx, project, path, self.trigram)?;
+        insert_fts_rows(&tx, project, Some(path), self.trigram)?;
+        tx.commit()?;
+        Ok(())
+    }
 
-/// Transform `a|b|c` → `(a OR b OR c)` outside of quoted strings.
-///
-/// Only terms directly connected by `|` form an OR group.
-/// `prefix foo|bar suffix` → `prefix (foo OR bar) suffix`
-fn transform_pipe_to_or(s: &str) -> String {
-    if !s.contains('|') {
-        return s.to_string();
+    /// Export all data from DB back to vecs (for flushing to disk).
+    #[allow(clippy::type_complexity)]
+    pub fn export_all(
+        &self,
+    ) -> Result<(
+        Vec<FileEntry>,
+        Vec<SymbolEntry>,
+        Vec<TextEntry>,
+        Vec<ReferenceEntry>,
+    )> {
+        let mut files = Vec::new();
+        let mut symbols = Vec::new();
+        let mut texts = Vec::new();
+        let mut references = Vec::new();
+
+        // Export files
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, path, lang, hash, lines, title, description, is_test FROM files ORDER BY project, path",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(FileEntry {
+                    project: row.get(0)?,
+                    path: row.get(1)?,
+                    lang: row.get(2)?,
+                    hash: row.get(3)?,
+                    lines: row.get(4)?,
+                    title: row.get(5)?,
+                    description: row.get(6)?,
+                    is_test: row.get(7)?,
+                })
+            })?;
+            for row in rows {
+                files.push(row?);
+            }
+        }
+
+        // Export symbols
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+                 FROM symbols
+                 ORDER BY project, file, line_start",
+            )?;
+            let rows = stmt.query_map([], symbol_from_row)?;
+            for row in rows {
+                symbols.push(row?);
+            }
+        }
+
+        // Export texts
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, kind, line_start, line_end, text, parent
+                 FROM texts
+                 ORDER BY project, file, line_start",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(TextEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    kind: row.get(2)?,
+                    line: [row.get(3)?, row.get(4)?],
+                    text: row.get(5)?,
+                    parent: row.get(6)?,
+                })
+            })?;
+            for row in rows {
+                texts.push(row?);
+            }
+        }
+
+        // Export references
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, name, kind, line_start, line_end, caller
+                 FROM refs
+                 ORDER BY project, file, line_start",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(ReferenceEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    name: row.get(2)?,
+                    kind: row.get(3)?,
+                    line: [row.get(4)?, row.get(5)?],
+                    caller: row.get(6)?,
+                })
+            })?;
+            for row in rows {
+                references.push(row?);
+            }
+        }
+
+        Ok((files, symbols, texts, references))
+    }
+
+    /// Export data for a specific project from DB back to vecs (for flushing to disk).
+    #[allow(clippy::type_complexity)]
+    pub fn export_for_project(
+        &self,
+        project: &str,
+    ) -> Result<(
+        Vec<FileEntry>,
+        Vec<SymbolEntry>,
+        Vec<TextEntry>,
+        Vec<ReferenceEntry>,
+    )> {
+        let mut files = Vec::new();
+        let mut symbols = Vec::new();
+        let mut texts = Vec::new();
+        let mut references = Vec::new();
+
+        // Export files
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, path, lang, hash, lines, title, description, is_test FROM files WHERE project = ?1 ORDER BY path",
+            )?;
+            let rows = stmt.query_map([project], |row| {
+                Ok(FileEntry {
+                    project: row.get(0)?,
+                    path: row.get(1)?,
+                    lang: row.get(2)?,
+                    hash: row.get(3)?,
+                    lines: row.get(4)?,
+                    title: row.get(5)?,
+                    description: row.get(6)?,
+                    is_test: row.get(7)?,
+                })
+            })?;
+            for row in rows {
+                files.push(row?);
+            }
+        }
+
+        // Export symbols
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig
+                 FROM symbols
+                 WHERE project = ?1
+                 ORDER BY file, line_start",
+            )?;
+            let rows = stmt.query_map([project], symbol_from_row)?;
+            for row in rows {
+                symbols.push(row?);
+            }
+        }
+
+        // Export texts
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, kind, line_start, line_end, text, parent
+                 FROM texts
+                 WHERE project = ?1
+                 ORDER BY file, line_start",
+            )?;
+            let rows = stmt.query_map([project], |row| {
+                Ok(TextEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    kind: row.get(2)?,
+                    line: [row.get(3)?, row.get(4)?],
+                    text: row.get(5)?,
+                    parent: row.get(6)?,
+                })
+            })?;
+            for row in rows {
+                texts.push(row?);
+            }
+        }
+
+        // Export references
+        {
+            let mut stmt = self.conn.prepare(
+                "SELECT project, file, name, kind, line_start, line_end, caller
+                 FROM refs
+                 WHERE project = ?1
+                 ORDER BY file, line_start",
+            )?;
+            let rows = stmt.query_map([project], |row| {
+                Ok(ReferenceEntry {
+                    project: row.get(0)?,
+                    file: row.get(1)?,
+                    name: row.get(2)?,
+                    kind: row.get(3)?,
+                    line: [row.get(4)?, row.get(5)?],
+                    caller: row.get(6)?,
+                })
+            })?;
+            for row in rows {
+                references.push(row?);
+            }
+        }
+
+        Ok((files, symbols, texts, references))
+    }
+
+    /// List all unique projects in the database.
+    pub fn list_projects(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT project FROM files ORDER BY project")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Whether any file of the project is stored (e.g. from a persisted database).
+    pub fn has_project(&self, project: &str) -> Result<bool> {
+        let exists = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM files WHERE project = ?1)",
+            [project],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// List the paths of all files stored for a project.
+    pub fn file_paths(&self, project: &str) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path FROM files WHERE project = ?1 ORDER BY path")?;
+        let rows = stmt.query_map([project], |row| row.get(0))?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    /// Compute index metrics for one project, read from the content tables.
+    ///
+    /// Read-only and independent of FTS. An unknown project yields all zeros.
+    pub fn project_stats(&self, project: &str) -> Result<ProjectStats> {
+        let count = |table: &str| -> Result<u64> {
+            let sql = format!("SELECT COUNT(*) FROM {table} WHERE project = ?1");
+            Ok(self.conn.query_row(&sql, [project], |row| row.get(0))?)
+        };
+
+        let mut stats = ProjectStats {
+            project: project.to_string(),
+            files: count("files")?,
+            symbols: count("symbols")?,
+            texts: count("texts")?,
+            refs: count("refs")?,
+            lines: self.conn.query_row(
+                "SELECT COALESCE(SUM(lines), 0) FROM files WHERE project = ?1",
+                [project],
+                |row| row.get(0),
+            )?,
+            ..Default::default()
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT kind, COUNT(*) AS n FROM symbols WHERE project = ?1
+             GROUP BY kind ORDER BY n DESC, kind",
+        )?;
+        stats.symbols_by_kind = stmt
+            .query_map([project], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<_, _>>()?;
+
+        stats.languages = self.languages(project)?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT COALESCE(lang, 'other'), COUNT(*) AS n, COALESCE(SUM(lines), 0)
+             FROM files WHERE project = ?1 GROUP BY 1 ORDER BY n DESC, 1",
+        )?;
+        stats.files_by_language = stmt
+            .query_map([project], |row| {
+                Ok(LanguageStats {
+                    lang: row.get(0)?,
+                    files: row.get(1)?,
+                    lines: row.get(2)?,
+                })
+            })?
+            .collect::<std::result::Result<_, _>>()?;
+
+        Ok(stats)
+    }
+
+    /// Languages of a project's files, sorted (files with no language are skipped).
+    pub fn languages(&self, project: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT lang FROM files
+             WHERE project = ?1 AND lang IS NOT NULL ORDER BY lang",
+        )?;
+        let languages = stmt
+            .query_map([project], |row| row.get(0))?
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(languages)
+    }
+
+    /// Get directory overview: count of files per (parent_path, lang, min_visibility_level).
+    ///
+    /// Returns Vec of (parent_path, lang, min_visibility_level, count) tuples, sorted by parent_path.
+    /// Skips dotfiles (paths starting with '.' or containing '/.').
+    ///
+    /// min_visibility_level is the minimum (most visible) visibility level of symbols in each file:
+    /// - 0 = files with no symbols (documentation, configs, etc.)
+    /// - 1 = files with public symbols
+    /// - 2 = files with internal symbols (but no public)
+    /// - 3 = files with only private symbols
+    ///
+    /// This allows callers to filter/display based on visibility without hiding files entirely.
+    #[allow(clippy::type_complexity)]
+    pub fn explore_dir_overview(
+        &self,
+        project: &str,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<(String, Option<String>, i32, usize)>> {
+        let base = path_prefix
+            .map(|p| p.trim_end_matches('/'))
+            .filter(|p| !p.is_empty());
+
+        // Query groups files by (parent_path, lang, min_visibility_level)
+        // First compute each file's minimum visibility level, then group by that
+        // Files without symbols get min_visibility_level = 0
+        match base {
+            Some(prefix) => {
+                let glob = format!("{}/*", prefix);
+                let mut stmt = self.conn.prepare(
+                    "WITH file_min_vis AS (
+                        SELECT f.parent_path, f.path, f.lang,
+                               COALESCE(MIN(s.visibility_level), 3) as min_vis
+                        FROM files f
+                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
+                        WHERE f.project = ?1 AND f.path GLOB ?2
+                          AND f.path NOT GLOB '.*' AND f.path NOT GLOB '*/.*'
+                        GROUP BY f.parent_path, f.path, f.lang
+                    )
+                    SELECT parent_path, lang, min_vis, COUNT(*) as cnt
+                    FROM file_min_vis
+                    GROUP BY parent_path, lang, min_vis
+                    ORDER BY parent_path, lang, min_vis",
+                )?;
+                stmt.query_map(rusqlite::params![project, glob], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, i32>(2)?,
+                        row.get::<_, usize>(3)?,
+                    ))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .context("explore_dir_overview query failed")
+            }
+            None => {
+                let mut stmt = self.conn.prepare(
+                    "WITH file_min_vis AS (
+                        SELECT f.parent_path, f.path, f.lang,
+                               COALESCE(MIN(s.visibility_level), 3) as min_vis
+                        FROM files f
+                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
+                        WHERE f.project = ?1
+                          AND f.path NOT GLOB '.*' AND f.path NOT GLOB '*/.*'
+                        GROUP BY f.parent_path, f.path, f.lang
+                    )
+                    SELECT parent_path, lang, min_vis, COUNT(*) as cnt
+                    FROM file_min_vis
+                    GROUP BY parent_path, lang, min_vis
+                    ORDER BY parent_path, lang, min_vis",
+                )?;
+                stmt.query_map(rusqlite::params![project], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, i32>(2)?,
+                        row.get::<_, usize>(3)?,
+                    ))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .context("explore_dir_overview query failed")
+            }
+        }
+    }
+
+    /// Get files in a specific directory.
+    ///
+    /// Returns Vec of (filename, lang) tuples, sorted by filename.
+    pub fn explore_dir_files(
+        &self,
+        project: &str,
+        parent_path: &str,
+    ) -> Result<Vec<(String, Option<String>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path, lang FROM files
+             WHERE project = ?1 AND parent_path = ?2
+             AND path NOT GLOB '.*' AND path NOT GLOB '*/.*'
+             ORDER BY path",
+        )?;
+        let rows: Vec<(String, Option<String>)> = stmt
+            .query_map(rusqlite::params![project, parent_path], |row| {
+                let path: String = row.get(0)?;
+                let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
+                Ok((filename, row.get(1)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    /// Get files capped at N per (parent_path, lang), excluding markdown.
+    ///
+    /// Returns Vec of (parent_path, filename, lang) tuples.
+    /// Uses ROW_NUMBER() to limit files per directory+language group.
+    ///
+    /// If visibility is specified, only returns files that contain symbols at that
+    /// visibility level or higher.
+    pub fn explore_files_capped(
+        &self,
+        project: &str,
+        path_prefix: Option<&str>,
+        visibility: Option<&str>,
+        cap: usize,
+    ) -> Result<Vec<(String, String, Option<String>)>> {
+        let base = path_prefix
+            .map(|p| p.trim_end_matches('/'))
+            .filter(|p| !p.is_empty());
+
+        // Cap to i64::MAX to avoid overflow when cap is usize::MAX
+        let cap_i64 = cap.min(i64::MAX as usize) as i64;
+
+        let max_level = visibility_max_level(visibility, "public");
+
+        // Fetch files with known language (code + markdown)
+        // Files with lang=NULL are summarized as "+N other files" from overview
+        // When filtering by visibility:
+        // - Files with no symbols have min_visibility_level = 3 (private, hidden by default)
+        // - Files with symbols use the minimum visibility level of their symbols
+        let rows: Vec<(String, String, Option<String>)> = match (max_level, base) {
+            (Some(level), Some(prefix)) => {
+                let glob = format!("{}/*", prefix);
+                let mut stmt = self.conn.prepare(
+                    "WITH file_visibility AS (
+                        SELECT f.parent_path, f.path, f.lang,
+                               COALESCE(MIN(s.visibility_level), 3) as min_vis
+                        FROM files f
+                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
+                        WHERE f.project = ?1 AND f.path GLOB ?2
+                          AND f.lang IS NOT NULL
+                        GROUP BY f.parent_path, f.path, f.lang
+                    ),
+                    filtered_files AS (
+                        SELECT parent_path, path, lang
+                        FROM file_visibility
+                        WHERE min_vis <= ?3
+                    ),
+                    ranked AS (
+                        SELECT parent_path, path, lang,
+                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
+                        FROM filtered_files
+                    )
+                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?4 ORDER BY parent_path, path",
+                )?;
+                stmt.query_map(rusqlite::params![project, glob, level, cap_i64], |row| {
+                    let parent: String = row.get(0)?;
+                    let path: String = row.get(1)?;
+                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    Ok((parent, filename, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+            }
+            (Some(level), None) => {
+                let mut stmt = self.conn.prepare(
+                    "WITH file_visibility AS (
+                        SELECT f.parent_path, f.path, f.lang,
+                               COALESCE(MIN(s.visibility_level), 3) as min_vis
+                        FROM files f
+                        LEFT JOIN symbols s ON s.project = f.project AND s.file = f.path
+                        WHERE f.project = ?1
+                          AND f.lang IS NOT NULL
+                        GROUP BY f.parent_path, f.path, f.lang
+                    ),
+                    filtered_files AS (
+                        SELECT parent_path, path, lang
+                        FROM file_visibility
+                        WHERE min_vis <= ?2
+                    ),
+                    ranked AS (
+                        SELECT parent_path, path, lang,
+                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
+                        FROM filtered_files
+                    )
+                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?3 ORDER BY parent_path, path",
+                )?;
+                stmt.query_map(rusqlite::params![project, level, cap_i64], |row| {
+                    let parent: String = row.get(0)?;
+                    let path: String = row.get(1)?;
+                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    Ok((parent, filename, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+            }
+            (None, Some(prefix)) => {
+                let glob = format!("{}/*", prefix);
+                let mut stmt = self.conn.prepare(
+                    "WITH ranked AS (
+                        SELECT parent_path, path, lang,
+                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
+                        FROM files
+                        WHERE project = ?1 AND path GLOB ?2
+                          AND lang IS NOT NULL
+                    )
+                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?3 ORDER BY parent_path, path",
+                )?;
+                stmt.query_map(rusqlite::params![project, glob, cap_i64], |row| {
+                    let parent: String = row.get(0)?;
+                    let path: String = row.get(1)?;
+                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    Ok((parent, filename, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+            }
+            (None, None) => {
+                let mut stmt = self.conn.prepare(
+                    "WITH ranked AS (
+                        SELECT parent_path, path, lang,
+                               ROW_NUMBER() OVER (PARTITION BY parent_path, lang ORDER BY path) as rn
+                        FROM files
+                        WHERE project = ?1
+                          AND lang IS NOT NULL
+                    )
+                    SELECT parent_path, path, lang FROM ranked WHERE rn <= ?2 ORDER BY parent_path, path",
+                )?;
+                stmt.query_map(rusqlite::params![project, cap_i64], |row| {
+                    let parent: String = row.get(0)?;
+                    let path: String = row.get(1)?;
+                    let filename = path.rsplit('/').next().unwrap_or(&path).to_string();
+                    Ok((parent, filename, row.get(2)?))
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?
+            }
+        };
+
+        Ok(rows)
+    }
+
+    /// Get the directory tree under `path_prefix` in one call: each directory
+    /// with its file counts per language and up to `cap` of its files, nested
+    /// down to `depth` levels below the prefix (0 = the prefix directory only).
+    ///
+    /// Counts come from `explore_dir_overview` and cover every file; the listed
+    /// files come from `explore_files_capped`, so `visibility` only affects
+    /// which files are shown. Dotfiles are skipped, as in `explore`.
+    pub fn explore_tree(
+        &self,
+        project: &str,
+        path_prefix: Option<&str>,
+        visibility: Option<&str>,
+        depth: usize,
+        cap: usize,
+    ) -> Result<ExploreNode> {
+        let base = path_prefix.map(|p| p.trim_matches('/')).unwrap_or("");
+
+        let mut dirs: BTreeMap<String, ExploreNode> = BTreeMap::new();
+        for (dir, lang, _min_vis, count) in self.explore_dir_overview(project, Some(base))? {
+            let node = dirs.entry(dir.clone()).or_insert_with(|| ExploreNode {
+                path: dir,
+                ..Default::default()
+            });
+            *node
+                .langs
+                .entry(lang.unwrap_or_else(|| "other".to_string()))
+                .or_default() += count;
+        }
+        // Capped per (directory, language): trim to `cap` per directory
+        for (dir, filename, _lang) in
+            self.explore_files_capped(project, Some(base), visibility, cap)?
+        {
+            if let Some(node) = dirs.get_mut(&dir)
+                && node.files.len() < cap
+            {
+                node.files.push(filename);
+            }
+        }
+
+        let mut root = ExploreNode {
+            path: if base.is_empty() { "." } else { base }.to_string(),
+            ..Default::default()
+        };
+        for (dir, mut node) in dirs {
+            node.more_files = node.langs.values().sum::<usize>() - node.files.len();
+            let rel = match dir.as_str() {
+                "." => "",
+                _ if base.is_empty() => dir.as_str(),
+                _ => dir[base.len()..].trim_start_matches('/'),
+            };
+            let components: Vec<&str> = rel.split('/').filter(|c| !c.is_empty()).collect();
+
+            let mut target = &mut root;
+            for (level, component) in components.iter().enumerate() {
+                if level == depth {
+                    target.truncated = true;
+                    break;
+                }
+                let prefix = if target.path == "." {
+                    String::new()
+                } else {
+                    format!("{}/", target.path)
+                };
+                target = target.child_mut(format!("{prefix}{component}"));
+            }
+            if components.len() <= depth {
+                let children = std::mem::take(&mut target.children);
+                let truncated = target.truncated;
+                *target = ExploreNode {
+                    children,
+                    truncated,
+                    ..node
+                };
+            }
+        }
+        root.sort();
+        Ok(root)
+    }
+}
+
+/// SQL condition keeping rows whose `column` is one of `kinds` (canonical
+/// names, pushed onto `params`), or `None` when `kinds` is empty.
+fn ref_kind_condition(
+    column: &str,
+    kinds: &[String],
+    params: &mut Vec<Box<dyn rusqlite::ToSql>>,
+) -> Option<String> {
+    if kinds.is_empty() {
+        return None;
+    }
+    let placeholders = vec!["?"; kinds.len()].join(", ");
+    for kind in kinds {
+        params.push(Box::new(canonical_kind(kind)));
+    }
+    Some(format!("{} IN ({})", column, placeholders))
+}
+
+/// SQL condition keeping a `refs r` row only if no other reference with the same
+/// project, file, caller and name comes before it (by line, then rowid).
+///
+/// With `kinds` (pushed onto `params`), only references of those kinds compete,
+/// so a kind filter cannot hide the one reference a group keeps.
+fn distinct_ref_condition(kinds: &[String], params: &mut Vec<Box<dyn rusqlite::ToSql>>) -> String {
+    let same_kind = ref_kind_condition("d.kind", kinds, params)
+        .map(|condition| format!(" AND {}", condition))
+        .unwrap_or_default();
+    format!(
+        "NOT EXISTS (SELECT 1 FROM refs d \
+         WHERE d.project = r.project AND d.name = r.name AND d.file = r.file \
+         AND d.caller IS r.caller{} \
+         AND (d.line_start < r.line_start OR (d.line_start = r.line_start AND d.rowid < r.rowid)))",
+        same_kind
+    )
+}
+
+/// Map a row selecting `project, file, name, kind, line_start, line_end, parent,
+/// tokens, alias, visibility, sig` (in that order) to a `SymbolEntry`.
+fn symbol_from_row(row: &rusqlite::Row) -> rusqlite::Result<SymbolEntry> {
+    Ok(SymbolEntry {
+        project: row.get(0)?,
+        file: row.get(1)?,
+        name: row.get(2)?,
+        kind: row.get(3)?,
+        line: [row.get(4)?, row.get(5)?],
+        parent: row.get(6)?,
+        tokens: row.get(7)?,
+        alias: row.get(8)?,
+        visibility: row.get(9)?,
+        sig: row.get(10)?,
+    })
+}
+
+/// Normalize a reference name for comparison with symbol names:
+/// `::` paths become `.` (symbols use `Parent.child`), and receiver prefixes are dropped.
+fn normalize_ref_name(name: &str) -> String {
+    let normalized = name.replace("::", ".");
+    let mut rest = normalized.as_str();
+    for prefix in ["self.", "this.", "crate.", "super.", "Self."] {
+        if let Some(stripped) = rest.strip_prefix(prefix) {
+            rest = stripped;
+        }
+    }
+    rest.to_string()
+}
+
+/// Milliseconds since the Unix epoch, as stored in `files.indexed_at`.
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+}
+
+/// Open an on-disk connection tuned for a single writer.
+fn open_connection(path: &Path) -> Result<Connection> {
+    let conn =
+        Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    register_functions(&conn)?;
+    Ok(conn)
+}
+
+/// Register the SQL functions used while populating FTS5.
+fn register_functions(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function(
+        "identifier_subtokens",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let text = ctx.get::<Option<String>>(0)?;
+            Ok(identifier_subtokens(text.as_deref().unwrap_or("")))
+        },
+    )?;
+    Ok(())
+}
+
+/// Words of the compound identifiers in `text` (`parseAsync` → `parse Async`),
+/// so FTS matches `parse async` against `parseAsync`.
+///
+/// Only camelCase humps are split: `unicode61` already separates `snake_case`
+/// and `kebab-case` words, so repeating them would only skew BM25 scores.
+///
+/// The result starts with `SUBTOKEN_SEPARATOR` so it can be appended to a
+/// `content` value and cut off again; it is empty when no identifier splits.
+fn identifier_subtokens(text: &str) -> String {
+    let mut seen = HashSet::new();
+    let mut out = String::new();
+    let identifiers = text.split(|c: char| !c.is_alphanumeric());
+    for identifier in identifiers {
+        let words = split_identifier(identifier);
+        if words.len() < 2 {
+            continue;
+        }
+        for word in words {
+            if seen.insert(word.to_lowercase()) {
+                out.push(if out.is_empty() {
+                    SUBTOKEN_SEPARATOR
+                } else {
+                    ' '
+                });
+                out.push_str(&word);
+            }
+        }
+    }
+    out
+}
+
+/// `content` as stored by the indexer, without the appended identifier sub-tokens.
+fn strip_subtokens(content: &str) -> &str {
+    content
+        .split_once(SUBTOKEN_SEPARATOR)
+        .map_or(content, |(original, _)| original)
+}
+
+/// Create the content tables, their indexes and (optionally) the registry of the
+/// per-project FTS5 tables (see [`fts_shard`]).
+fn create_schema(conn: &Connection, fts_enabled: bool) -> Result<()> {
+    // Content tables (store the actual data for retrieval)
+    conn.execute_batch(
+        "
+        CREATE TABLE files (
+            project     TEXT NOT NULL,
+            path        TEXT NOT NULL,
+            parent_path TEXT NOT NULL,
+            lang        TEXT,
+            hash        TEXT NOT NULL,
+            lines       INTEGER NOT NULL,
+            title       TEXT,
+            description TEXT,
+            is_test     INTEGER NOT NULL DEFAULT 0,
+            indexed_at  INTEGER NOT NULL DEFAULT 0,
+            parsed_clean INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (project, path)
+        );
+        CREATE INDEX idx_files_indexed_at ON files (indexed_at);
+        CREATE INDEX idx_files_parent ON files (project, parent_path);
+        CREATE INDEX idx_files_hash ON files (hash);
+
+        CREATE TABLE symbols (
+            project       TEXT NOT NULL,
+            file       TEXT NOT NULL,
+            name       TEXT NOT NULL,
+            kind       TEXT NOT NULL,
+            line_start INTEGER NOT NULL,
+            line_end   INTEGER NOT NULL,
+            parent     TEXT,
+            tokens     TEXT,
+            alias      TEXT,
+            visibility TEXT,
+            visibility_level INTEGER NOT NULL DEFAULT 3,
+            sig        TEXT,
+            superseded INTEGER NOT NULL DEFAULT 0 -- 1 = C++ declaration with a definition
+        );
+
+        CREATE TABLE texts (
+            project       TEXT NOT NULL,
+            file       TEXT NOT NULL,
+            kind       TEXT NOT NULL,
+            line_start INTEGER NOT NULL,
+            line_end   INTEGER NOT NULL,
+            text       TEXT NOT NULL,
+            parent     TEXT
+        );
+
+        CREATE TABLE refs (
+            project       TEXT NOT NULL,
+            file          TEXT NOT NULL,
+            name          TEXT NOT NULL,
+            kind          TEXT NOT NULL,
+            line_start    INTEGER NOT NULL,
+            line_end      INTEGER NOT NULL,
+            caller        TEXT
+        );
+
+        -- Indexes for exact lookups
+        CREATE INDEX idx_symbols_project_file ON symbols(project, file);
+        CREATE INDEX idx_symbols_project_file_parent ON symbols(project, file, parent);
+        CREATE INDEX idx_symbols_project_file_kind ON symbols(project, file, kind);
+        CREATE INDEX idx_symbols_visibility ON symbols(project, visibility_level);
+        CREATE INDEX idx_texts_project_file ON texts(project, file);
+        CREATE INDEX idx_files_project ON files(project);
+        CREATE INDEX idx_symbols_project ON symbols(project);
+        CREATE INDEX idx_symbols_project_name ON symbols(project, name);
+        CREATE INDEX idx_symbols_superseded ON symbols(superseded) WHERE superseded = 1;
+        CREATE INDEX idx_texts_project ON texts(project);
+
+        -- Indexes for reference queries
+        CREATE INDEX idx_refs_project_name ON refs(project, name);
+        CREATE INDEX idx_refs_project_caller ON refs(project, caller);
+        CREATE INDEX idx_refs_project_file ON refs(project, file);
+        CREATE INDEX idx_refs_project_name_kind ON refs(project, name, kind);
+        ",
+    )
+    .context("failed to create database schema")?;
+
+    // Each project's FTS5 rows live in their own table, `search_fts_<id>`
+    if fts_enabled {
+        conn.execute_batch(
+            "
+            CREATE TABLE fts_shards (
+                id      INTEGER PRIMARY KEY,
+                project TEXT NOT NULL UNIQUE
+            );
+            ",
+        )
+        .context("failed to create FTS shard registry")?;
+    }
+
+    Ok(())
+}
+
+/// Create an FTS5 table for one project's search rows, tokenized by `unicode61`
+/// or, with `trigram`, by overlapping 3-character sequences.
+/// Three searchable columns with BM25 weighting: name (3x), file (2x), content (1x)
+fn create_fts_table(conn: &Connection, table: &str, trigram: bool) -> Result<()> {
+    let tokenize = if trigram {
+        ", tokenize = 'trigram'"
+    } else {
+        ""
+    };
+    conn.execute_batch(&format!(
+        "
+        CREATE VIRTUAL TABLE {table} USING fts5(
+            name,               -- symbol/file name (highest weight)
+            file,               -- file path (medium weight)
+            content,            -- tokens, docstrings, etc. (lower weight)
+            type UNINDEXED,     -- 'symbol', 'file', 'text'
+            rowid_ref UNINDEXED,-- rowid in source table
+            path UNINDEXED,     -- file path (for GLOB filtering)
+            kind UNINDEXED,     -- symbol/text kind, or file lang
+            project UNINDEXED,  -- project filter
+            visibility_level UNINDEXED, -- 1=public, 2=internal, 3=private (0 for files/texts)
+            lang UNINDEXED,     -- language of the containing file (all row types)
+            is_test UNINDEXED   -- 1 when the containing file looks like a test
+            {tokenize}
+        );
+        "
+    ))
+    .context("failed to create FTS5 table")?;
+    Ok(())
+}
+
+/// Name of the FTS5 table holding `project`'s search rows, created on first use.
+///
+/// Every project gets its own table (a shard), so rebuilding or dropping one
+/// project never touches the rows of another, and a search scoped to projects
+/// only runs `MATCH` over their tables (see [`fts_union`]).
+fn fts_shard(conn: &Connection, project: &str, trigram: bool) -> Result<String> {
+    if let Some(table) = find_fts_shard(conn, project)? {
+        return Ok(table);
+    }
+    conn.execute("INSERT INTO fts_shards (project) VALUES (?1)", [project])?;
+    let table = format!("search_fts_{}", conn.last_insert_rowid());
+    create_fts_table(conn, &table, trigram)?;
+    Ok(table)
+}
+
+/// Name of the FTS5 table of `project`, if it has one.
+fn find_fts_shard(conn: &Connection, project: &str) -> Result<Option<String>> {
+    let id: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM fts_shards WHERE project = ?1",
+            [project],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(id.map(|id| format!("search_fts_{id}")))
+}
+
+/// Names of the FTS5 tables of `projects` (of every project when empty).
+fn fts_shards(conn: &Connection, projects: &[String]) -> Result<Vec<String>> {
+    if projects.is_empty() {
+        let mut stmt = conn.prepare("SELECT id FROM fts_shards ORDER BY id")?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        return Ok(ids
+            .into_iter()
+            .map(|id| format!("search_fts_{id}"))
+            .collect());
+    }
+    let mut tables = Vec::new();
+    for project in projects {
+        tables.extend(find_fts_shard(conn, project)?);
+    }
+    Ok(tables)
+}
+
+/// Drop the FTS5 table of `project`, if it has one.
+fn drop_fts_shard(conn: &Connection, project: &str) -> Result<()> {
+    if let Some(table) = find_fts_shard(conn, project)? {
+        conn.execute_batch(&format!("DROP TABLE {table}"))?;
+        conn.execute("DELETE FROM fts_shards WHERE project = ?1", [project])?;
+    }
+    Ok(())
+}
+
+/// Fan `sql`, a SELECT written against `search_fts`, out to every table of
+/// `shards` and chain the copies with `UNION ALL`.
+///
+/// Positional parameters (`?N`) are shared by all copies. Each copy ranks with
+/// its own table's BM25 statistics, so scores from different projects are
+/// comparable only roughly; exact name matches still rank first.
+fn fts_union(shards: &[String], sql: &str) -> String {
+    shards
+        .iter()
+        .map(|table| sql.replace("search_fts", table))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+/// Names of the functions and methods of `file`.
+#[cfg(feature = "lang-cpp")]
+fn function_names(conn: &Connection, project: &str, file: &str) -> Result<BTreeSet<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT name FROM symbols
+         WHERE project = ?1 AND file = ?2 AND kind IN ('function', 'method')",
+    )?;
+    let names = stmt
+        .query_map([project, file], |row| row.get(0))?
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(names)
+}
+
+/// Flag the C++ declarations among the functions `names` of `project` (all of
+/// them with `None`) that have a definition, and clear the flag on the others
+/// (see [`crate::parser::cpp::declaration_pairs`]).
+///
+/// Search leaves flagged rows out, so a function declared in a header and defined
+/// in a source file is found once, at its definition. The rows themselves stay:
+/// file outlines and exports keep the declaration.
+#[cfg(feature = "lang-cpp")]
+fn mark_superseded_declarations(
+    conn: &Connection,
+    project: &str,
+    names: Option<&BTreeSet<String>>,
+) -> Result<()> {
+    use crate::parser::cpp::{declaration_pairs, is_header_or_source};
+
+    const COLUMNS: &str = "project, file, name, kind, line_start, line_end, parent, tokens, alias, visibility, sig, rowid, superseded";
+    fn read(row: &rusqlite::Row) -> rusqlite::Result<(SymbolEntry, i64, bool)> {
+        Ok((symbol_from_row(row)?, row.get(11)?, row.get(12)?))
+    }
+
+    let mut rows = Vec::new();
+    match names {
+        None => {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {COLUMNS} FROM symbols
+                 WHERE project = ?1 AND kind IN ('function', 'method')"
+            ))?;
+            for row in stmt.query_map([project], read)? {
+                rows.push(row?);
+            }
+        }
+        Some(names) => {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {COLUMNS} FROM symbols
+                 WHERE project = ?1 AND name = ?2 AND kind IN ('function', 'method')"
+            ))?;
+            for name in names {
+                for row in stmt.query_map([project, name], read)? {
+                    rows.push(row?);
+                }
+            }
+        }
+    }
+
+    let (symbols, flags): (Vec<SymbolEntry>, Vec<(i64, bool)>) = rows
+        .into_iter()
+        .filter(|(s, _, _)| is_header_or_source(&s.file))
+        .map(|(s, rowid, flagged)| (s, (rowid, flagged)))
+        .unzip();
+    let superseded: HashSet<usize> = declaration_pairs(&symbols)
+        .into_iter()
+        .map(|(decl, _)| decl)
+        .collect();
+    let mut update = conn.prepare("UPDATE symbols SET superseded = ?1 WHERE rowid = ?2")?;
+    for (i, (rowid, flagged)) in flags.into_iter().enumerate() {
+        let flag = superseded.contains(&i);
+        if flag != flagged {
+            update.execute(rusqlite::params![flag, rowid])?;
+        }
+    }
+    Ok(())
+}
+
+/// Insert the FTS5 rows for one project from the content tables.
+/// BM25 weights: name (3x), file (2x), content (1x)
+fn populate_fts(conn: &Connection, project: &str, trigram: bool) -> Result<()> {
+    insert_fts_rows(conn, project, None, trigram)
+}
+
+/// Insert the FTS5 rows of a project, or of a single file in it when `path` is
+/// given, into the project's table (created with the `trigram` tokenizer if new).
+fn insert_fts_rows(
+    conn: &Connection,
+    project: &str,
+    path: Option<&str>,
+    trigram: bool,
+) -> Result<()> {
+    let table = fts_shard(conn, project, trigram)?;
+    let (file_filter, symbol_filter, text_filter) = match path {
+        Some(_) => (" AND path = ?2", " AND s.file = ?2", " AND t.file = ?2"),
+        None => ("", "", ""),
+    };
+    let params: Vec<&dyn rusqlite::ToSql> = match &path {
+        Some(path) => vec![&project, path],
+        None => vec![&project],
+    };
+
+    // Every content column is followed by the sub-tokens of compound identifiers
+    // (see `identifier_subtokens`), drawn from the name column too
+
+    // Files: name=title, file=path, content=description
+    conn.execute(
+        &format!(
+            "INSERT INTO {table}(name, file, content, type, rowid_ref, path, kind, project, visibility_level, lang, is_test)
+             SELECT
+                 COALESCE(title, ''),
+                 COALESCE(path, ''),
+                 COALESCE(description, '')
+                     || identifier_subtokens(COALESCE(title, '') || ' ' || COALESCE(description, '')),
+                 'file',
+                 rowid,
+                 path,
+                 lang,
+                 project,
+                 0,
+                 lang,
+                 is_test
+             FROM files WHERE project = ?1{file_filter}"
+        ),
+        params.as_slice(),
+    )?;
+
+    // Symbols: name=symbol name, file=path, content=kind + sig + tokens, lang from the file
+    conn.execute(
+        &format!(
+            "INSERT INTO {table}(name, file, content, type, rowid_ref, path, kind, project, visibility_level, lang, is_test)
+             SELECT
+                 COALESCE(s.name, ''),
+                 COALESCE(s.file, ''),
+                 COALESCE(s.kind, '') || ' ' || COALESCE(s.sig, '') || ' ' || COALESCE(s.tokens, '')
+                     || identifier_subtokens(
+                         COALESCE(s.name, '') || ' ' || COALESCE(s.sig, '') || ' ' || COALESCE(s.tokens, '')
+                     ),
+                 'symbol',
+                 s.rowid,
+                 s.file,
+                 s.kind,
+                 s.project,
+                 s.visibility_level,
+                 f.lang,
+                 COALESCE(f.is_test, 0)
+             FROM symbols s
+             LEFT JOIN files f ON f.project = s.project AND f.path = s.file
+             WHERE s.project = ?1{symbol_filter}"
+        ),
+        params.as_slice(),
+    )?;
+
+    // Texts: name=empty, file=path, content=text, lang from the file
+    conn.execute(
+        &format!(
+            "INSERT INTO {table}(name, file, content, type, rowid_ref, path, kind, project, visibility_level, lang, is_test)
+             SELECT
+                 '',
+                 COALESCE(t.file, ''),
+                 COALESCE(t.text, '') || identifier_subtokens(t.text),
+                 'text',
+                 t.rowid,
+                 t.file,
+                 t.kind,
+                 t.project,
+                 0,
+                 f.lang,
+                 COALESCE(f.is_test, 0)
+             FROM texts t
+             LEFT JOIN files f ON f.project = t.project AND f.path = t.file
+             WHERE t.project = ?1{text_filter}"
+        ),
+        params.as_slice(),
+    )?;
+
+    Ok(())
+}
+
+/// Delete the FTS5 rows (file, symbols, texts) of a single file.
+///
+/// `path` is UNINDEXED, so filtering on it alone would scan the project's table.
+/// The indexed `file` column holds the same path: a phrase match on it narrows
+/// the candidates through the full-text index, and the exact `path` comparison
+/// discards files whose path merely contains the same token sequence.
+fn delete_fts_rows(conn: &Connection, project: &str, path: &str, trigram: bool) -> Result<()> {
+    let Some(table) = find_fts_shard(conn, project)? else {
+        return Ok(());
+    };
+    if !path.chars().any(char::is_alphanumeric) || (trigram && path.chars().count() < 3) {
+        // No tokens (or trigrams) to match on: fall back to the scan
+        conn.execute(&format!("DELETE FROM {table} WHERE path = ?1"), [path])?;
+        return Ok(());
+    }
+    let phrase = format!("file:\"{}\"", path.replace('"', "\"\""));
+    conn.execute(
+        &format!(
+            "DELETE FROM {table} WHERE rowid IN (
+                 SELECT rowid FROM {table} WHERE {table} MATCH ?1 AND path = ?2
+             )"
+        ),
+        rusqlite::params![phrase, path],
+    )?;
+    Ok(())
+}
+
+/// Build the FTS5 WHERE clause shared by `search` and `search_count`.
+/// Returns the clause (empty when there is nothing to filter) and its positional parameters.
+fn search_where_clause(
+    query: &str,
+    filters: &SearchFilters,
+) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+    let SearchFilters {
+        scope,
+        kind,
+        lang,
+        path,
+        exclude_paths,
+        include_tests,
+        projects,
+        visibility,
+    } = filters;
+    let enumerate = query.trim().is_empty();
+
+    // Build WHERE clause for filters; C++ declarations merged into their
+    // definition are never results (see `mark_superseded_declarations`)
+    let mut conditions = vec![
+        "NOT (type = 'symbol' AND rowid_ref IN (SELECT rowid FROM symbols WHERE superseded = 1))"
+            .to_string(),
+    ];
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    if !enumerate {
+        // Build FTS5 MATCH expression (searches all columns: name, file, content)
+        conditions.push("search_fts MATCH ?1".to_string());
+        params.push(Box::new(fts5_quote(query)));
+    }
+
+    // Scope filter (type)
+    if !scope.is_empty() {
+        let start_param = params.len() + 1;
+        let placeholders: Vec<String> = scope
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("?{}", start_param + i))
+            .collect();
+        conditions.push(format!("type IN ({})", placeholders.join(", ")));
+        for s in scope {
+            params.push(Box::new(s.clone()));
+        }
+    }
+
+    // Kind filter
+    if !kind.is_empty() {
+        let start_param = params.len() + 1;
+        let placeholders: Vec<String> = kind
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("?{}", start_param + i))
+            .collect();
+        conditions.push(format!("kind IN ({})", placeholders.join(", ")));
+        for k in kind {
+            params.push(Box::new(canonical_kind(k)));
+        }
+    }
+
+    // Language filter
+    if !lang.is_empty() {
+        let start_param = params.len() + 1;
+        let placeholders: Vec<String> = lang
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("?{}", start_param + i))
+            .collect();
+        conditions.push(format!("lang IN ({})", placeholders.join(", ")));
+        for l in lang {
+            params.push(Box::new(l.clone()));
+        }
+    }
+
+    let next_param = params.len() + 1;
+
+    // Path filter (supports GLOB)
+    if let Some(p) = path {
+        if p.contains('*') {
+            conditions.push(format!("path GLOB ?{}", next_param));
+        } else {
+            conditions.push(format!("path = ?{}", next_param));
+        }
+        params.push(Box::new(p.to_string()));
+    }
+
+    // Excluded path globs
+    for pattern in exclude_paths {
+        conditions.push(format!("path NOT GLOB ?{}", params.len() + 1));
+        params.push(Box::new(pattern.clone()));
+    }
+
+    // Test files
+    if !include_tests {
+        conditions.push("is_test = 0".to_string());
+    }
+
+    // Project filter
+    if !projects.is_empty() {
+        let start_param = params.len() + 1;
+        let placeholders: Vec<String> = projects
+            .iter()
+            .enumerate()
+            .map(|(i, _)| format!("?{}", start_param + i))
+            .collect();
+        conditions.push(format!("project IN ({})", placeholders.join(", ")));
+        for p in projects {
+            params.push(Box::new(p.clone()));
+        }
+    }
+
+    let next_param = params.len() + 1;
+
+    // Visibility filter: visibility_level <= max_level
+    // Files/texts have level 0 (always pass), symbols have 1/2/3
+    if let Some(max_level) = visibility_max_level(visibility.as_deref(), "public") {
+        conditions.push(format!("visibility_level <= ?{}", next_param));
+        params.push(Box::new(max_level));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+    (where_clause, params)
+}
+
+/// SQL expression for the part of `expr` after its last `.` (the whole value if it has none).
+///
+/// `rtrim(x, chars)` strips every trailing character that is not a dot, leaving the
+/// qualifier; removing that qualifier from `x` leaves the base name.
+fn sql_base_name(expr: &str) -> String {
+    format!("replace({expr}, rtrim({expr}, replace({expr}, '.', '')), '')")
+}
+
+/// Compile a `name_regex` filter, reporting an invalid pattern as an error.
+fn compile_name_regex(pattern: &str) -> Result<regex::Regex> {
+    regex::Regex::new(pattern).with_context(|| format!("invalid name regex '{pattern}'"))
+}
+
+/// Whether `re` matches `name` or its last `separator`-delimited segment
+/// (`Server.handle_event` → `handle_event`, `src/lib.rs` → `lib.rs`).
+fn regex_matches_name(re: &regex::Regex, name: &str, separator: char) -> bool {
+    re.is_match(name)
+        || name
+            .rsplit_once(separator)
+            .is_some_and(|(_, base)| re.is_match(base))
+}
+
+/// One term of a search query, as written (casing preserved).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryTerm {
+    text: String,
+    /// Trailing `*`: the term only has to start a word
+    prefix: bool,
+}
+
+/// Split a search query into the terms a matching row must contain.
+///
+/// Each group lists alternatives (`a|b`, `a OR b`); a row must match at least one
+/// term of every group. Operators, parentheses, column filters and `NOT` terms
+/// are dropped; a quoted phrase is kept as one term.
+fn query_term_groups(query: &str) -> Vec<Vec<QueryTerm>> {
+    // Tokenize, keeping quoted phrases whole
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '|' if !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push("OR".to_string());
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    let mut groups: Vec<Vec<QueryTerm>> = Vec::new();
+    let mut join_previous = false;
+    let mut negated = false;
+    for token in tokens {
+        match token.as_str() {
+            "OR" => {
+                join_previous = true;
+                continue;
+            }
+            "AND" => continue,
+            "NOT" => {
+                negated = true;
+                continue;
+            }
+            _ => {}
+        }
+
+        let mut text = token.trim_matches(|c| matches!(c, '(' | ')' | ',' | '^'));
+        text = text.strip_prefix("NEAR(").unwrap_or(text);
+        if let Some((column, rest)) = text.split_once(':')
+            && matches!(column, "name" | "file" | "content")
+        {
+            text = rest;
+        }
+        let prefix = text.ends_with('*');
+        let text = text.trim_end_matches('*').trim_matches('"').to_string();
+        // NEAR distances are not terms
+        let is_distance = token.ends_with(')') && text.chars().all(|c| c.is_ascii_digit());
+        if std::mem::take(&mut negated) || text.is_empty() || is_distance {
+            join_previous = false;
+            continue;
+        }
+
+        let term = QueryTerm { text, prefix };
+        match groups.last_mut() {
+            Some(group) if join_previous => group.push(term),
+            _ => groups.push(vec![term]),
+        }
+        join_previous = false;
+    }
+    groups
+}
+
+/// Whether every term group has a term found in one of `fields`.
+fn term_groups_match(
+    groups: &[Vec<QueryTerm>],
+    fields: &[&str],
+    case_sensitive: bool,
+    whole_word: bool,
+) -> bool {
+    groups.iter().all(|group| {
+        group.iter().any(|term| {
+            fields
+                .iter()
+                .any(|field| term_matches(field, term, case_sensitive, whole_word))
+        })
+    })
+}
+
+/// Whether `term` occurs in `text`, optionally with exact casing and on identifier boundaries.
+fn term_matches(text: &str, term: &QueryTerm, case_sensitive: bool, whole_word: bool) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let (haystack, needle) = if case_sensitive {
+        (text.to_string(), term.text.clone())
+    } else {
+        (text.to_lowercase(), term.text.to_lowercase())
+    };
+    if !whole_word {
+        return haystack.contains(&needle);
+    }
+    haystack.match_indices(&needle).any(|(start, _)| {
+        let end = start + needle.len();
+        let starts_word = !haystack[..start]
+            .chars()
+            .next_back()
+            .is_some_and(is_word_char);
+        let ends_word = term.prefix || !haystack[end..].chars().next().is_some_and(is_word_char);
+        starts_word && ends_word
+    })
+}
+
+/// Transform query for FTS5:
+/// - `a|b` → `(a OR b)` (pipe as OR shorthand)
+/// - `a|b|c` → `(a OR b OR c)`
+/// - Pipes inside quotes are preserved literally
+///
+/// See: https://www.sqlite.org/fts5.html#full_text_query_syntax
+///
+/// FTS5 syntax examples:
+/// - `parseAsync` — single term
+/// - `parse|safe` or `parse OR safe` — match either term
+/// - `parse safe` — match both terms (implicit AND)
+/// - `parse*` — prefix search (matches parseAsync, parseString, etc.)
+/// - `"safe parse"` — phrase search (exact sequence)
+/// - `parse NOT test` — exclude results containing "test"
+/// - `NEAR(parse async, 5)` — terms within 5 tokens of each other
+fn fts5_quote(s: &str) -> String {
+    transform_pipe_to_or(s)
+}
+
+/// Transform `a|b|c` → `(a OR b OR c)` outside of quoted strings.
+///
+/// Only terms directly connected by `|` form an OR group.
+/// `prefix foo|bar suffix` → `prefix (foo OR bar) suffix`
+fn transform_pipe_to_or(s: &str) -> String {
+    if !s.contains('|') {
+        return s.to_string();
+    }
+
+    let mut result = String::with_capacity(s.len() * 2);
+    let mut in_quote = false;
+    let mut current_token = String::new();
+
+    for c in s.chars() {
+        if c == '"' {
+            in_quote = !in_quote;
+            current_token.push(c);
+        } else if c.is_whitespace() && !in_quote {
+            // End of token - flush it
+            if !current_token.is_empty() {
+                result.push_str(&expand_pipes(&current_token));
+                current_token.clear();
+            }
+            result.push(c);
+        } else {
+            current_token.push(c);
+        }
+    }
+
+    // Flush remaining token
+    if !current_token.is_empty() {
+        result.push_str(&expand_pipes(&current_token));
+    }
+
+    result
+}
+
+/// Expand pipes in a single token: `a|b|c` → `(a OR b OR c)`
+fn expand_pipes(token: &str) -> String {
+    // Don't expand if token is entirely quoted
+    if token.starts_with('"') && token.ends_with('"') {
+        return token.to_string();
+    }
+
+    if !token.contains('|') {
+        return token.to_string();
+    }
+
+    let parts: Vec<&str> = token.split('|').collect();
+    if parts.len() == 1 {
+        return token.to_string();
+    }
+
+    format!("({})", parts.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transform_pipe_to_or() {
+        // No pipe - pass through
+        assert_eq!(transform_pipe_to_or("foo bar"), "foo bar");
+        assert_eq!(transform_pipe_to_or("foo OR bar"), "foo OR bar");
+
+        // Simple pipe
+        assert_eq!(transform_pipe_to_or("foo|bar"), "(foo OR bar)");
+        assert_eq!(transform_pipe_to_or("a|b|c"), "(a OR b OR c)");
+
+        // Pipe with surrounding terms
+        assert_eq!(transform_pipe_to_or("foo|bar baz"), "(foo OR bar) baz");
+        assert_eq!(
+            transform_pipe_to_or("prefix foo|bar"),
+            "prefix (foo OR bar)"
+        );
+        assert_eq!(
+            transform_pipe_to_or("prefix foo|bar suffix"),
+            "prefix (foo OR bar) suffix"
+        );
+
+        // Pipe inside quotes preserved
+        assert_eq!(transform_pipe_to_or("\"foo|bar\""), "\"foo|bar\"");
+        assert_eq!(
+            transform_pipe_to_or("test \"foo|bar\" other"),
+            "test \"foo|bar\" other"
+        );
+
+        // Mixed: pipe outside quotes, literal inside
+        assert_eq!(transform_pipe_to_or("a|b \"c|d\""), "(a OR b) \"c|d\"");
+
+        // With prefix wildcard
+        assert_eq!(
+            transform_pipe_to_or("hand*|context*"),
+            "(hand* OR context*)"
+        );
+    }
+
+    fn setup_test_db_with_refs(refs: &[ReferenceEntry]) -> SearchDb {
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("test", &[], &[], &[], refs).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_get_callers_transitive() {
+        let call = |name: &str, caller: &str, line: u32| ReferenceEntry {
+            project: "p".to_string(),
+            file: "app.py".to_string(),
+            name: name.to_string(),
+            kind: "call".to_string(),
+            line: [line, line],
+            caller: Some(caller.to_string()),
+        };
+        // leaf <- Svc.mid (as self.leaf) <- top <- leaf (cycle), and other <- top
+        let refs = vec![
+            call("self.leaf", "Svc.mid", 1),
+            call("svc.mid", "top", 2),
+            call("top", "leaf", 3),
+            call("top", "other", 4),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("p", &[], &[], &[], &refs).unwrap();
+
+        let tree = db.get_callers_transitive("leaf", None, 10, 100).unwrap();
+        let levels: Vec<(u32, Option<&str>)> = tree
+            .iter()
+            .map(|e| (e.depth, e.reference.caller.as_deref()))
+            .collect();
+        // The cycle back to `leaf` is reported once and not walked again
+        assert_eq!(
+            levels,
+            vec![
+                (1, Some("Svc.mid")),
+                (2, Some("top")),
+                (3, Some("leaf")),
+                (3, Some("other")),
+            ]
+        );
+
+        let tree = db.get_callers_transitive("leaf", None, 2, 100).unwrap();
+        assert_eq!(tree.len(), 2);
+        let tree = db.get_callers_transitive("leaf", None, 10, 3).unwrap();
+        assert_eq!(tree.len(), 3);
+    }
+
+    #[test]
+    fn test_distinct_by_caller() {
+        let make = |line: u32, caller: &str| ReferenceEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "log".to_string(),
+            kind: "call".to_string(),
+            line: [line, line],
+            caller: Some(caller.to_string()),
+        };
+        // `run` calls `log` twice, `stop` once
+        let refs = vec![make(12, "run"), make(10, "run"), make(20, "stop")];
+        let db = setup_test_db_with_refs(&refs);
+
+        let all = db
+            .get_callers(
+                "log",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        let distinct = db
+            .get_callers(
+                "log",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    distinct_by_caller: true,
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(distinct.len(), 2);
+        let run = distinct
+            .iter()
+            .find(|r| r.caller.as_deref() == Some("run"))
+            .unwrap();
+        assert_eq!(run.line, [10, 10], "keeps the first reference by line");
+
+        let callees = db
+            .get_callees(
+                "run",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(callees.len(), 2);
+        let callees = db
+            .get_callees(
+                "run",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    distinct_by_caller: true,
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(callees.len(), 1);
+        assert_eq!(callees[0].line, [10, 10]);
+    }
+
+    #[test]
+    fn test_get_callers_base_name_match() {
+        // Insert a reference with a "self." prefixed name (as Python parser produces)
+        let refs = vec![ReferenceEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "self.handle_exception".to_string(),
+            kind: "call".to_string(),
+            line: [100, 100],
+            caller: Some("full_dispatch_request".to_string()),
+        }];
+        let db = setup_test_db_with_refs(&refs);
+
+        // Query with base name should find the reference
+        // Use visibility="private" to skip symbol join (no symbols in test data)
+        let results = db
+            .get_callers(
+                "handle_exception",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "self.handle_exception");
+        assert_eq!(results[0].caller.as_deref(), Some("full_dispatch_request"));
+    }
+
+    #[test]
+    fn test_get_callers_several_projects() {
+        let call = |project: &str| ReferenceEntry {
+            project: project.to_string(),
+            file: "app.py".to_string(),
+            name: "handle".to_string(),
+            kind: "call".to_string(),
+            line: [1, 1],
+            caller: Some("main".to_string()),
+        };
+        let db = SearchDb::new_no_fts().unwrap();
+        for project in ["a", "b", "c"] {
+            db.load(project, &[], &[], &[], &[call(project)]).unwrap();
+        }
+        let projects_of = |projects: &[&str]| -> Vec<String> {
+            let projects: Vec<String> = projects.iter().map(|p| p.to_string()).collect();
+            let mut found: Vec<String> = db
+                .get_callers(
+                    "handle",
+                    &RefFilters {
+                        projects: projects.to_vec(),
+                        visibility: Some("private".to_string()),
+                        ..Default::default()
+                    },
+                    100,
+                    0,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|r| r.project)
+                .collect();
+            found.sort();
+            found
+        };
+        assert_eq!(projects_of(&["a", "c"]), vec!["a", "c"]);
+        assert_eq!(projects_of(&[]), vec!["a", "b", "c"]);
+        // The single-project form is the one-element filter
+        let single = db
+            .get_callers(
+                "handle",
+                &RefFilters {
+                    projects: vec!["b".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].project, "b");
+    }
+
+    #[test]
+    fn test_get_callers_exact_match_still_works() {
+        // Insert a reference with full name
+        let refs = vec![ReferenceEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "self.handle_exception".to_string(),
+            kind: "call".to_string(),
+            line: [100, 100],
+            caller: Some("dispatch".to_string()),
+        }];
+        let db = setup_test_db_with_refs(&refs);
+
+        // Query with exact name should still work
+        // Use visibility="private" to skip symbol join (no symbols in test data)
+        let results = db
+            .get_callers(
+                "self.handle_exception",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "self.handle_exception");
+    }
+
+    #[test]
+    fn test_get_callers_no_false_positives() {
+        // Insert references
+        let refs = vec![
+            ReferenceEntry {
+                project: String::new(),
+                file: "app.py".to_string(),
+                name: "self.handle_exception".to_string(),
+                kind: "call".to_string(),
+                line: [100, 100],
+                caller: None,
+            },
+            ReferenceEntry {
+                project: String::new(),
+                file: "app.py".to_string(),
+                name: "handle_user_exception".to_string(),
+                kind: "call".to_string(),
+                line: [200, 200],
+                caller: None,
+            },
+        ];
+        let db = setup_test_db_with_refs(&refs);
+
+        // Query for "handle_exception" should NOT match "handle_user_exception"
+        // because it doesn't end with ".handle_exception"
+        // Use visibility="private" to skip symbol join (no symbols in test data)
+        let results = db
+            .get_callers(
+                "handle_exception",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "self.handle_exception");
+    }
+
+    #[test]
+    fn test_get_callees_base_name_match() {
+        // Insert a reference with a qualified caller name
+        let refs = vec![ReferenceEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "os.path.join".to_string(),
+            kind: "call".to_string(),
+            line: [100, 100],
+            caller: Some("MyClass.process_data".to_string()),
+        }];
+        let db = setup_test_db_with_refs(&refs);
+
+        // Query with base caller name should find the reference
+        // Use visibility="private" to skip symbol join (no symbols in test data)
+        let results = db
+            .get_callees(
+                "process_data",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "os.path.join");
+        assert_eq!(results[0].caller.as_deref(), Some("MyClass.process_data"));
+    }
+
+    #[test]
+    fn test_get_callees_exact_match_still_works() {
+        // Insert a reference
+        let refs = vec![ReferenceEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "helper".to_string(),
+            kind: "call".to_string(),
+            line: [100, 100],
+            caller: Some("MyClass.method".to_string()),
+        }];
+        let db = setup_test_db_with_refs(&refs);
+
+        // Query with exact caller name should work
+        // Use visibility="private" to skip symbol join (no symbols in test data)
+        let results = db
+            .get_callees(
+                "MyClass.method",
+                &RefFilters {
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "helper");
+    }
+
+    #[test]
+    fn test_get_callees_several_kinds() {
+        let reference = |name: &str, kind: &str, line: u32| ReferenceEntry {
+            project: "test".to_string(),
+            file: "app.py".to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: [line, line],
+            caller: Some("main".to_string()),
+        };
+        let db = setup_test_db_with_refs(&[
+            reference("Config", "import", 1),
+            reference("Config", "type_annotation", 3),
+            reference("Config", "instantiation", 4),
+            reference("load", "call", 5),
+            reference("load", "call", 6),
+        ]);
+        let kinds = ["call".to_string(), "instantiation".to_string()];
+        let callees = |distinct: bool| -> Vec<(String, u32)> {
+            db.get_callees(
+                "main",
+                &RefFilters {
+                    kinds: kinds.to_vec(),
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    distinct_by_caller: distinct,
+                },
+                100,
+                0,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.kind, r.line[0]))
+            .collect()
+        };
+        let expected = |pairs: &[(&str, u32)]| -> Vec<(String, u32)> {
+            pairs.iter().map(|(k, l)| (k.to_string(), *l)).collect()
+        };
+        assert_eq!(
+            callees(false),
+            expected(&[("instantiation", 4), ("call", 5), ("call", 6)])
+        );
+        // The import on line 1 must not hide the instantiation it shares a name with
+        assert_eq!(
+            callees(true),
+            expected(&[("instantiation", 4), ("call", 5)])
+        );
+
+        // Callers take the same kind set; the single-kind wrapper is the one-element set
+        let callers = db
+            .get_callers(
+                "Config",
+                &RefFilters {
+                    kinds: kinds.to_vec(),
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(callers.len(), 1);
+        assert_eq!(callers[0].kind, "instantiation");
+        let imports = db
+            .get_callees(
+                "main",
+                &RefFilters {
+                    kinds: vec!["import".to_string()],
+                    projects: vec!["test".to_string()],
+                    visibility: Some("private".to_string()),
+                    ..Default::default()
+                },
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(imports.len(), 1);
+    }
+
+    /// A public symbol at lines 1-5 with no parent, signature, tokens or alias.
+    fn test_symbol(project: &str, file: &str, name: &str, kind: &str) -> SymbolEntry {
+        SymbolEntry {
+            project: project.to_string(),
+            file: file.to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: [1, 5],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: Some("public".to_string()),
+        }
+    }
+
+    // Visibility filter tests
+
+    fn setup_test_db_with_symbols(symbols: &[SymbolEntry]) -> SearchDb {
+        let db = SearchDb::new_no_fts().unwrap();
+        // Collect unique files by path
+        let mut seen = std::collections::HashSet::new();
+        let files: Vec<FileEntry> = symbols
+            .iter()
+            .filter(|s| seen.insert(s.file.clone()))
+            .map(|s| FileEntry {
+                project: s.project.clone(),
+                path: s.file.clone(),
+                lang: Some("rust".to_string()),
+                hash: "abc123".to_string(),
+                lines: 100,
+                title: None,
+                description: None,
+                is_test: false,
+            })
+            .collect();
+        db.load("test", &files, symbols, &[], &[]).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_visibility_max_level_function() {
+        // Test the visibility_max_level helper function with explicit values
+        assert_eq!(visibility_max_level(Some("public"), "public"), Some(1));
+        assert_eq!(visibility_max_level(Some("internal"), "public"), Some(2));
+        assert_eq!(visibility_max_level(Some("private"), "public"), None);
+        assert_eq!(visibility_max_level(Some("unknown"), "public"), None); // Unknown, no filter
+
+        // Test default behavior (None uses the default parameter)
+        assert_eq!(visibility_max_level(None, "public"), Some(1)); // Default: public
+        assert_eq!(visibility_max_level(None, "internal"), Some(2)); // Default: internal
+        assert_eq!(visibility_max_level(None, "private"), None); // Default: private (no filter)
+    }
+
+    #[test]
+    fn test_visibility_to_level_function() {
+        // Test the visibility_to_level helper function
+        assert_eq!(visibility_to_level(Some("public")), 1);
+        assert_eq!(visibility_to_level(Some("internal")), 2);
+        assert_eq!(visibility_to_level(Some("private")), 3);
+        assert_eq!(visibility_to_level(None), 3);
+    }
+
+    #[test]
+    fn test_get_file_symbols_visibility_filter() {
+        let symbols = vec![
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "public_fn".to_string(),
+                kind: "function".to_string(),
+                line: [10, 20],
+                parent: None,
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("public".to_string()),
+            },
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "internal_fn".to_string(),
+                kind: "function".to_string(),
+                line: [30, 40],
+                parent: None,
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("internal".to_string()),
+            },
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "private_fn".to_string(),
+                kind: "function".to_string(),
+                line: [50, 60],
+                parent: None,
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("private".to_string()),
+            },
+        ];
+        let db = setup_test_db_with_symbols(&symbols);
+
+        // Default (None) = public - returns only public
+        let results = db.get_file_symbols(None, "lib.rs", None, 100, 0).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "public_fn");
+
+        // Explicit public - same as default
+        let results = db
+            .get_file_symbols(None, "lib.rs", Some("public"), 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "public_fn");
+
+        // Internal filter - returns public and internal
+        let results = db
+            .get_file_symbols(None, "lib.rs", Some("internal"), 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        assert!(names.contains(&"public_fn"));
+        assert!(names.contains(&"internal_fn"));
+
+        // Private filter - returns all
+        let results = db
+            .get_file_symbols(None, "lib.rs", Some("private"), 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn test_get_file_outline() {
+        let sym =
+            |name: &str, kind: &str, line: [u32; 2], parent: Option<&str>, vis: &str| SymbolEntry {
+                line,
+                parent: parent.map(String::from),
+                visibility: Some(vis.to_string()),
+                ..test_symbol("test", "lib.rs", name, kind)
+            };
+        // Inserted out of line order on purpose
+        let symbols = vec![
+            sym("S.f", "method", [12, 12], Some("S"), "public"),
+            sym("S", "impl", [12, 12], None, "public"),
+            sym("S", "struct", [10, 10], None, "public"),
+            sym("m", "module", [1, 8], None, "public"),
+            sym("m.inner", "function", [2, 4], Some("m"), "private"),
+            sym(
+                "m.inner.local",
+                "function",
+                [3, 3],
+                Some("m.inner"),
+                "private",
+            ),
+            sym("m.b", "function", [6, 7], Some("m"), "public"),
+        ];
+        let db = setup_test_db_with_symbols(&symbols);
+
+        let outline = db
+            .get_file_outline(None, "lib.rs", Some("private"))
+            .unwrap();
+        let names = |nodes: &[OutlineNode]| -> Vec<String> {
+            nodes
+                .iter()
+                .map(|n| format!("{} {}", n.symbol.kind, n.symbol.name))
+                .collect()
+        };
+        assert_eq!(names(&outline), vec!["module m", "struct S", "impl S"]);
+        assert_eq!(
+            names(&outline[0].children),
+            vec!["function m.inner", "function m.b"]
+        );
+        assert_eq!(
+            names(&outline[0].children[0].children),
+            vec!["function m.inner.local"]
+        );
+        // The method nests under the impl enclosing it, not the struct
+        assert!(outline[1].children.is_empty());
+        assert_eq!(names(&outline[2].children), vec!["method S.f"]);
+
+        // A symbol whose parent is filtered out by visibility stays at the top level
+        let db = setup_test_db_with_symbols(&[
+            sym("Hidden", "class", [1, 9], None, "private"),
+            sym("Hidden.visible", "method", [2, 3], Some("Hidden"), "public"),
+        ]);
+        let outline = db.get_file_outline(None, "lib.rs", None).unwrap();
+        assert_eq!(names(&outline), vec!["method Hidden.visible"]);
+        assert!(
+            db.get_file_outline(None, "missing.rs", None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_symbol_at() {
+        let sym = |name: &str, line: [u32; 2], parent: Option<&str>| SymbolEntry {
+            line,
+            parent: parent.map(String::from),
+            ..test_symbol("test", "app.py", name, "function")
+        };
+        let symbols = vec![
+            sym("App", [1, 20], None),
+            sym("App.run", [5, 12], Some("App")),
+            sym("App.run.step", [8, 9], Some("App.run")),
+            // Same one-line range: the child wins regardless of insertion order
+            sym("Tiny.f", [30, 30], Some("Tiny")),
+            sym("Tiny", [30, 30], None),
+        ];
+        let db = setup_test_db_with_symbols(&symbols);
+        let at = |line: u32| {
+            db.symbol_at("test", "app.py", line)
+                .unwrap()
+                .map(|s| s.name)
+        };
+
+        assert_eq!(at(3).as_deref(), Some("App"));
+        assert_eq!(at(5).as_deref(), Some("App.run"));
+        assert_eq!(at(9).as_deref(), Some("App.run.step"));
+        assert_eq!(at(30).as_deref(), Some("Tiny.f"));
+        assert_eq!(at(25), None);
+        assert!(db.symbol_at("other", "app.py", 3).unwrap().is_none());
+        assert!(db.symbol_at("test", "missing.py", 3).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_get_children_visibility_filter() {
+        let symbols = vec![
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "MyStruct".to_string(),
+                kind: "struct".to_string(),
+                line: [1, 50],
+                parent: None,
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("public".to_string()),
+            },
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "public_method".to_string(),
+                kind: "method".to_string(),
+                line: [10, 15],
+                parent: Some("MyStruct".to_string()),
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("public".to_string()),
+            },
+            SymbolEntry {
+                project: "test".to_string(),
+                file: "lib.rs".to_string(),
+                name: "private_method".to_string(),
+                kind: "method".to_string(),
+                line: [20, 25],
+                parent: Some("MyStruct".to_string()),
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("private".to_string()),
+            },
+        ];
+        let db = setup_test_db_with_symbols(&symbols);
+
+        // Default (None) = public - returns only public method
+        let results = db
+            .get_children(None, "lib.rs", "MyStruct", None, 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "public_method");
+
+        // Private filter - returns all methods
+        let results = db
+            .get_children(None, "lib.rs", "MyStruct", Some("private"), 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_list_symbols() {
+        let symbol =
+            |file: &str, name: &str, kind: &str, line: u32, visibility: &str| SymbolEntry {
+                line: [line, line + 2],
+                visibility: Some(visibility.to_string()),
+                ..test_symbol("p", file, name, kind)
+            };
+        let symbols = vec![
+            symbol("src/b.rs", "Writer", "interface", 1, "public"),
+            symbol("src/a.rs", "Reader", "interface", 9, "public"),
+            symbol("src/a.rs", "Hidden", "interface", 3, "private"),
+            symbol("src/a.rs", "run", "function", 1, "public"),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("p", &[], &symbols, &[], &[]).unwrap();
+        db.load(
+            "q",
+            &[],
+            &[symbol("lib.rs", "Other", "interface", 1, "public")],
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        let names = |results: Vec<SymbolEntry>| -> Vec<String> {
+            results.into_iter().map(|s| s.name).collect()
+        };
+
+        // Public interfaces of one project, across files, in file/line order
+        let results = db
+            .list_symbols(Some("p"), Some("interface"), None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["Reader", "Writer"]);
+
+        let results = db
+            .list_symbols(Some("p"), Some("interface"), Some("private"), 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["Hidden", "Reader", "Writer"]);
+
+        // Paging, and no project filter spans every project
+        let results = db.list_symbols(None, None, None, 2, 1).unwrap();
+        assert_eq!(names(results), vec!["Reader", "Writer"]);
+
+        // Kind synonyms resolve to the stored kind
+        let results = db
+            .list_symbols(Some("p"), Some("fn"), None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["run"]);
+        let results = db
+            .list_symbols(Some("p"), Some("iface"), None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["Reader", "Writer"]);
+    }
+
+    #[test]
+    fn test_get_children_by_parent() {
+        let member = |file: &str, name: &str, line: u32, visibility: &str| SymbolEntry {
+            project: "p".to_string(),
+            file: file.to_string(),
+            name: name.to_string(),
+            kind: "method".to_string(),
+            line: [line, line + 2],
+            parent: Some("UserService".to_string()),
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: Some(visibility.to_string()),
+        };
+        // A partial class split over two files, plus an unrelated method
+        let mut other = member("src/Other.cs", "Run", 1, "public");
+        other.parent = Some("Other".to_string());
+        let symbols = vec![
+            member("src/UserService.Query.cs", "Find", 12, "public"),
+            member("src/UserService.cs", "Save", 20, "public"),
+            member("src/UserService.cs", "Load", 5, "public"),
+            member("src/UserService.cs", "Validate", 30, "private"),
+            other,
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("p", &[], &symbols, &[], &[]).unwrap();
+        db.load(
+            "q",
+            &[],
+            &[member("lib/UserService.cs", "Remote", 1, "public")],
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        let names = |results: Vec<SymbolEntry>| -> Vec<String> {
+            results.into_iter().map(|s| s.name).collect()
+        };
+
+        // Across files of one project, in file then line order
+        let results = db
+            .get_children_by_parent(Some("p"), "UserService", None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["Find", "Load", "Save"]);
+
+        let results = db
+            .get_children_by_parent(Some("p"), "UserService", Some("private"), 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["Find", "Load", "Save", "Validate"]);
+
+        // No project spans them all; paging applies
+        let results = db
+            .get_children_by_parent(None, "UserService", None, 2, 2)
+            .unwrap();
+        assert_eq!(names(results), vec!["Save", "Remote"]);
+    }
+
+    #[test]
+    fn test_file_symbols_partitioned_by_project() {
+        let symbol = |project: &str, name: &str, parent: Option<&str>, line: u32| SymbolEntry {
+            line: [line, line + 1],
+            parent: parent.map(str::to_string),
+            ..test_symbol(project, "src/lib.rs", name, "function")
+        };
+        // Both projects have a src/lib.rs with a `Config`
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load(
+            "api",
+            &[],
+            &[
+                symbol("api", "Config", None, 1),
+                symbol("api", "Config.port", Some("Config"), 2),
+            ],
+            &[],
+            &[],
+        )
+        .unwrap();
+        db.load(
+            "web",
+            &[],
+            &[
+                symbol("web", "Config", None, 1),
+                symbol("web", "Config.theme", Some("Config"), 2),
+                symbol("web", "render", None, 5),
+            ],
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        let names = |results: Vec<SymbolEntry>| -> Vec<String> {
+            results
+                .into_iter()
+                .map(|s| format!("{}:{}", s.project, s.name))
+                .collect()
+        };
+
+        let results = db
+            .get_file_symbols(Some("api"), "src/lib.rs", None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["api:Config", "api:Config.port"]);
+        let results = db
+            .get_file_symbols(Some("web"), "src/*.rs", None, 100, 0)
+            .unwrap();
+        assert_eq!(
+            names(results),
+            vec!["web:Config", "web:Config.theme", "web:render"]
+        );
+        // Without a project the path matches in both
+        let results = db
+            .get_file_symbols(None, "src/lib.rs", None, 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 5);
+
+        let results = db
+            .get_children(Some("web"), "src/lib.rs", "Config", None, 100, 0)
+            .unwrap();
+        assert_eq!(names(results), vec!["web:Config.theme"]);
+        let results = db
+            .get_children(None, "src/lib.rs", "Config", None, 100, 0)
+            .unwrap();
+        assert_eq!(results.len(), 2);
+
+        let tree = |outline: Vec<OutlineNode>| -> Vec<(String, Vec<String>)> {
+            outline
+                .into_iter()
+                .map(|node| {
+                    let name = |s: &SymbolEntry| format!("{}:{}", s.project, s.name);
+                    let children = node.children.iter().map(|c| name(&c.symbol)).collect();
+                    (name(&node.symbol), children)
+                })
+                .collect()
+        };
+        let outline = db
+            .get_file_outline(Some("web"), "src/lib.rs", None)
+            .unwrap();
+        assert_eq!(
+            tree(outline),
+            vec![
+                (
+                    "web:Config".to_string(),
+                    vec!["web:Config.theme".to_string()]
+                ),
+                ("web:render".to_string(), vec![]),
+            ]
+        );
+        // Without a project, each project's children stay under its own parent
+        let outline = db.get_file_outline(None, "src/lib.rs", None).unwrap();
+        assert_eq!(
+            tree(outline),
+            vec![
+                (
+                    "api:Config".to_string(),
+                    vec!["api:Config.port".to_string()]
+                ),
+                (
+                    "web:Config".to_string(),
+                    vec!["web:Config.theme".to_string()]
+                ),
+                ("web:render".to_string(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_search_kind_synonyms() {
+        let symbol = |name: &str, kind: &str| SymbolEntry {
+            line: [1, 2],
+            ..test_symbol("", "src/lib.rs", name, kind)
+        };
+        let db = SearchDb::new().unwrap();
+        db.load(
+            "",
+            &[],
+            &[
+                symbol("parse_config", "function"),
+                symbol("ParseError", "class"),
+            ],
+            &[],
+            &[],
+        )
+        .unwrap();
+
+        let scope = ["symbol".to_string()];
+        let names = |kind: &str| -> Vec<String> {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    kind: vec![kind.to_string()],
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("parse*")
+            })
+            .unwrap()
+            .into_iter()
+            .filter_map(|r| match r {
+                SearchResult::Symbol(s) => Some(s.name),
+                _ => None,
+            })
+            .collect()
+        };
+        assert_eq!(names("func"), vec!["parse_config"]);
+        assert_eq!(names("cls"), vec!["ParseError"]);
+        assert_eq!(names("function"), vec!["parse_config"]);
+    }
+
+    #[test]
+    fn test_search_texts_literal() {
+        let text = |file: &str, kind: &str, line: u32, text: &str| TextEntry {
+            project: "p".to_string(),
+            file: file.to_string(),
+            kind: kind.to_string(),
+            line: [line, line],
+            text: text.to_string(),
+            parent: None,
+        };
+        let texts = vec![
+            text("src/b.rs", "comment", 4, "TODO(alice): drop this"),
+            text("src/a.rs", "string", 9, "config.toml"),
+            text("src/a.rs", "comment", 2, "see TODO(bob)"),
+            text("src/a.rs", "comment", 5, "50% done_ish"),
+            text("src/a.rs", "comment", 6, "500 doneXish"),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("p", &[], &[], &texts, &[]).unwrap();
+
+        let lines = |results: Vec<TextEntry>| -> Vec<String> {
+            results
+                .into_iter()
+                .map(|t| format!("{}:{}", t.file, t.line[0]))
+                .collect()
+        };
+
+        // Punctuation FTS would tokenize away, in file then line order
+        let results = db
+            .search_texts_literal("TODO(", None, None, None, 100)
+            .unwrap();
+        assert_eq!(lines(results), vec!["src/a.rs:2", "src/b.rs:4"]);
+
+        // LIKE wildcards in the input are literal
+        let results = db
+            .search_texts_literal("0% done_", None, None, None, 100)
+            .unwrap();
+        assert_eq!(lines(results), vec!["src/a.rs:5"]);
+
+        // Kind, path, project filters and limit
+        let results = db
+            .search_texts_literal(".", Some("p"), Some("string"), Some("src/*"), 100)
+            .unwrap();
+        assert_eq!(lines(results), vec!["src/a.rs:9"]);
+        let results = db
+            .search_texts_literal("todo", Some("other"), None, None, 100)
+            .unwrap();
+        assert!(results.is_empty());
+        let results = db
+            .search_texts_literal("o", None, Some("comment"), Some("src/a.rs"), 2)
+            .unwrap();
+        assert_eq!(lines(results), vec!["src/a.rs:2", "src/a.rs:5"]);
+    }
+
+    #[test]
+    fn test_undocumented_symbols() {
+        let symbol = |name: &str, kind: &str, visibility: &str| SymbolEntry {
+            visibility: Some(visibility.to_string()),
+            ..test_symbol("test", "app.py", name, kind)
+        };
+        let symbols = vec![
+            symbol("documented", "function", "public"),
+            symbol("bare", "function", "public"),
+            symbol("_helper", "function", "private"),
+            symbol("os", "import", "public"),
+        ];
+        let texts = vec![TextEntry {
+            project: "test".to_string(),
+            file: "app.py".to_string(),
+            kind: "docstring".to_string(),
+            line: [2, 2],
+            text: "Explains what this does.".to_string(),
+            parent: Some("documented".to_string()),
+        }];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("test", &[], &symbols, &texts, &[]).unwrap();
+
+        // Default visibility (public): only the bare public function
+        let results = db.undocumented_symbols(None, &[], None, 100, 0).unwrap();
+        let names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["bare"]);
+
+        // Private widens to include helpers, still never imports
+        let results = db
+            .undocumented_symbols(Some("test"), &[], Some("private"), 100, 0)
+            .unwrap();
+        let mut names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["_helper", "bare"]);
+
+        // Kind filter
+        let results = db
+            .undocumented_symbols(None, &["class".to_string()], None, 100, 0)
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_symbol_docstrings() {
+        let text = |kind: &str, line: u32, text: &str, parent: Option<&str>| TextEntry {
+            project: "test".to_string(),
+            file: "app.py".to_string(),
+            kind: kind.to_string(),
+            line: [line, line],
+            text: text.to_string(),
+            parent: parent.map(String::from),
+        };
+        let texts = vec![
+            text("docstring", 6, "Run the app.", Some("App.run")),
+            text("comment", 7, "not a docstring", Some("App.run")),
+            text("docstring", 2, "The app.", Some("App")),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("test", &[], &[], &texts, &[]).unwrap();
+
+        let docs = db.symbol_docstrings("test", "app.py", "App.run").unwrap();
+        let lines: Vec<&str> = docs.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(lines, vec!["Run the app."]);
+        assert!(
+            db.symbol_docstrings("test", "other.py", "App")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_symbol_diff() {
+        let symbol = |name: &str, kind: &str, line: u32| SymbolEntry {
+            line: [line, line + 4],
+            ..test_symbol("test", "app.rs", name, kind)
+        };
+        let old = vec![
+            symbol("kept", "function", 1),
+            symbol("nudged", "function", 10),
+            symbol("moved", "function", 20),
+            symbol("gone", "function", 30),
+            symbol("std.fmt", "import", 1),
+        ];
+        let current = vec![
+            symbol("kept", "function", 1),
+            symbol("nudged", "function", 12),
+            symbol("moved", "function", 60),
+            symbol("fresh", "function", 40),
+            // Same name, other kind: a different symbol
+            symbol("kept", "struct", 50),
+            symbol("std.io", "import", 1),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("test", &[], &current, &[], &[]).unwrap();
+
+        let deltas = db.symbol_diff(&old, "test").unwrap();
+        let summary: Vec<(&str, &str, &str)> = deltas
+            .iter()
+            .map(|d| (d.change.as_str(), d.name.as_str(), d.kind.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("removed", "gone", "function"),
+                ("added", "fresh", "function"),
+                ("added", "kept", "struct"),
+                ("moved", "moved", "function"),
+            ]
+        );
+        assert_eq!(deltas[3].line, [60, 64]);
+        assert_eq!(deltas[3].old_line, Some([20, 24]));
+
+        let old = current.clone();
+        assert!(db.symbol_diff(&old, "test").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_unreferenced() {
+        let symbol = |name: &str, kind: &str, visibility: &str| SymbolEntry {
+            visibility: Some(visibility.to_string()),
+            ..test_symbol("test", "app.rs", name, kind)
+        };
+        let reference = |project: &str, name: &str| ReferenceEntry {
+            project: project.to_string(),
+            file: "app.rs".to_string(),
+            name: name.to_string(),
+            kind: "call".to_string(),
+            line: [3, 3],
+            caller: Some("main".to_string()),
+        };
+        let symbols = vec![
+            symbol("called", "function", "public"),
+            symbol("Server.start", "method", "public"),
+            symbol("Server.new", "method", "public"),
+            symbol("Server.spin", "method", "private"),
+            symbol("dead", "function", "public"),
+            symbol("_dead_helper", "function", "private"),
+            symbol("Server", "impl", "public"),
+            symbol("std", "import", "public"),
+        ];
+        let refs = vec![
+            reference("test", "called"),
+            reference("test", "self.start"),
+            reference("test", "Server::new"),
+            reference("test", "this.spin"),
+        ];
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("test", &[], &symbols, &[], &refs).unwrap();
+        // Same name in another project does not count
+        db.load("other", &[], &[], &[], &[reference("other", "dead")])
+            .unwrap();
+
+        // Default visibility (public); impls and imports are never reported
+        let results = db.find_unreferenced(None, &[], None, 100, 0).unwrap();
+        let names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["dead"]);
+
+        let results = db
+            .find_unreferenced(Some("test"), &[], Some("private"), 100, 0)
+            .unwrap();
+        let mut names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["_dead_helper", "dead"]);
+
+        let results = db
+            .find_unreferenced(None, &["method".to_string()], None, 100, 0)
+            .unwrap();
+        assert!(results.is_empty());
+    }
+
+    fn project_symbols(project: &str, count: usize) -> (Vec<FileEntry>, Vec<SymbolEntry>) {
+        let files = (0..count)
+            .map(|i| FileEntry {
+                project: String::new(),
+                path: format!("src/mod_{i}.rs"),
+                lang: Some("rust".to_string()),
+                hash: "abc123".to_string(),
+                lines: 10,
+                title: None,
+                description: None,
+                is_test: false,
+            })
+            .collect();
+        let symbols = (0..count)
+            .map(|i| SymbolEntry {
+                project: String::new(),
+                file: format!("src/mod_{i}.rs"),
+                name: format!("{}_handler_{i}", project.replace('/', "_")),
+                kind: "function".to_string(),
+                line: [1, 5],
+                parent: None,
+                sig: None,
+                tokens: Some("request response".to_string()),
+                alias: None,
+                visibility: Some("public".to_string()),
+            })
+            .collect();
+        (files, symbols)
+    }
+
+    fn fts_rows(db: &SearchDb, project: &str) -> i64 {
+        let Some(table) = find_fts_shard(&db.conn, project).unwrap() else {
+            return 0;
+        };
+        db.conn
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fts_is_partitioned_by_project() {
+        let db = SearchDb::new().unwrap();
+        let (files_a, symbols_a) = project_symbols("a", 3);
+        let (files_b, symbols_b) = project_symbols("libs/b", 2);
+        db.load("", &files_a, &symbols_a, &[], &[]).unwrap();
+        db.load("libs/b", &files_b, &symbols_b, &[], &[]).unwrap();
+
+        // Loading a second project must not re-insert the first one's rows
+        assert_eq!(fts_rows(&db, ""), 6);
+        assert_eq!(fts_rows(&db, "libs/b"), 4);
+
+        // Per-project rebuild only touches that project
+        db.remove_file("libs/b", "src/mod_1.rs").unwrap();
+        db.rebuild_fts_project("libs/b").unwrap();
+        assert_eq!(fts_rows(&db, ""), 6);
+        assert_eq!(fts_rows(&db, "libs/b"), 2);
+
+        // Full rebuild gives the same result
+        db.rebuild_fts().unwrap();
+        assert_eq!(fts_rows(&db, ""), 6);
+        assert_eq!(fts_rows(&db, "libs/b"), 2);
+
+        let results = db
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    projects: vec!["libs/b".to_string()],
+                    ..Default::default()
+                },
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Removing a project drops its table, leaving the other one alone
+        let table_a = find_fts_shard(&db.conn, "").unwrap();
+        db.remove_project("libs/b").unwrap();
+        assert_eq!(find_fts_shard(&db.conn, "libs/b").unwrap(), None);
+        assert_eq!(find_fts_shard(&db.conn, "").unwrap(), table_a);
+        assert_eq!(fts_rows(&db, ""), 6);
+    }
+
+    #[test]
+    fn test_search_pages_merge_project_tables() {
+        let db = SearchDb::new().unwrap();
+        for project in ["a", "b", "c"] {
+            let (files, symbols) = project_symbols(project, 5);
+            db.load(project, &files, &symbols, &[], &[]).unwrap();
+        }
+        let page = |limit: u32, offset: u32| {
+            db.search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    ..Default::default()
+                },
+                order_by: SearchOrder::Name,
+                limit,
+                offset,
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap()
+        };
+        let names = |page: SearchPage| -> Vec<String> {
+            page.results
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.name,
+                    other => panic!("unexpected {other:?}"),
+                })
+                .collect()
+        };
+
+        let all = names(page(100, 0));
+        assert_eq!(all.len(), 15);
+        let mut paged = Vec::new();
+        for offset in (0..15).step_by(4) {
+            let page = page(4, offset);
+            assert_eq!(page.total, 15);
+            paged.extend(names(page));
+        }
+        assert_eq!(paged, all);
+        assert_eq!(page(4, 20).total, 15);
+    }
+
+    #[test]
+    fn test_search_multiple_projects() {
+        let db = SearchDb::new().unwrap();
+        for project in ["a", "b", "c"] {
+            let (files, symbols) = project_symbols(project, 2);
+            db.load(project, &files, &symbols, &[], &[]).unwrap();
+        }
+        let scope = ["symbol".to_string()];
+        let projects = ["a".to_string(), "c".to_string()];
+
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    projects: projects.to_vec(),
+                    ..Default::default()
+                },
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        let mut found: Vec<&str> = page.results.iter().map(|r| r.project()).collect();
+        found.sort();
+        found.dedup();
+        assert_eq!(found, vec!["a", "c"]);
+        assert_eq!(page.total, 4);
+        assert_eq!(
+            db.search_count(
+                "handler*",
+                &SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    projects: projects.to_vec(),
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            4
+        );
+        // Empty means every project
+        assert_eq!(
+            db.search_count(
+                "handler*",
+                &SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_trigram_substring_search() {
+        let symbol = |file: &str, name: &str| test_symbol("", file, name, "function");
+        let symbols = vec![symbol("src/auth.rs", "authenticate"), symbol("a", "login")];
+        let scope = ["symbol".to_string()];
+        let names = |db: &SearchDb, query: &str| {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 10,
+                ..SearchQuery::new(query)
+            })
+            .unwrap()
+            .into_iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.name,
+                other => panic!("expected symbol, got {other:?}"),
+            })
+            .collect::<Vec<_>>()
+        };
+
+        let words = SearchDb::new().unwrap();
+        words.load("", &[], &symbols, &[], &[]).unwrap();
+        assert!(names(&words, "uthenticat").is_empty());
+
+        let db = SearchDb::new_trigram().unwrap();
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+        assert_eq!(names(&db, "uthenticat"), vec!["authenticate"]);
+        assert_eq!(names(&db, "authenticate"), vec!["authenticate"]);
+        // Too short to form a trigram
+        assert!(names(&db, "au").is_empty());
+
+        // Per-file updates still find the rows to replace, even for a path too short to match on
+        db.remove_file("", "a").unwrap();
+        db.update_fts_for_file("", "a").unwrap();
+        assert_eq!(fts_rows(&db, ""), 1);
+        db.remove_file("", "src/auth.rs").unwrap();
+        db.update_fts_for_file("", "src/auth.rs").unwrap();
+        assert_eq!(fts_rows(&db, ""), 0);
+    }
+
+    #[test]
+    fn test_update_fts_for_file() {
+        let db = SearchDb::new().unwrap();
+        let (mut files, mut symbols) = project_symbols("a", 3);
+        // Same token sequence as src/mod_1.rs in its path: must survive updates of that file
+        files.push(FileEntry {
+            path: "nested/src/mod_1.rs".to_string(),
+            ..files[1].clone()
+        });
+        symbols.push(SymbolEntry {
+            file: "nested/src/mod_1.rs".to_string(),
+            name: "nested_handler".to_string(),
+            ..symbols[1].clone()
+        });
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+        assert_eq!(fts_rows(&db, ""), 8);
+
+        let search = |query: &str| {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new(query)
+            })
+            .unwrap()
+            .into_iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.file,
+                SearchResult::File(f) => f.path,
+                SearchResult::Text(t) => t.file,
+            })
+            .collect::<Vec<_>>()
+        };
+
+        // Upsert: the old symbol disappears, the new one is found, no rebuild needed
+        let renamed = SymbolEntry {
+            name: "renamed_handler".to_string(),
+            ..symbols[1].clone()
+        };
+        db.upsert_file("", &files[1], &[renamed], &[], &[]).unwrap();
+        db.update_fts_for_file("", "src/mod_1.rs").unwrap();
+        assert_eq!(fts_rows(&db, ""), 8);
+        assert_eq!(search("renamed_handler"), vec!["src/mod_1.rs"]);
+        assert!(search("a_handler_1").is_empty());
+        assert_eq!(search("nested_handler"), vec!["nested/src/mod_1.rs"]);
+
+        // Remove: only that file's rows go away
+        db.remove_file("", "src/mod_0.rs").unwrap();
+        db.update_fts_for_file("", "src/mod_0.rs").unwrap();
+        assert_eq!(fts_rows(&db, ""), 6);
+        assert!(search("a_handler_0").is_empty());
+        assert_eq!(search("a_handler_2"), vec!["src/mod_2.rs"]);
+    }
+
+    #[test]
+    fn test_search_count_matches_search() {
+        let db = SearchDb::new().unwrap();
+        let (files, symbols) = project_symbols("a", 5);
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let count = db
+            .search_count(
+                "handler*",
+                &SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let results = db
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(count, results.len());
+
+        // Filters apply, and an empty query counts everything (files + symbols)
+        let count = db
+            .search_count(
+                "handler*",
+                &SearchFilters {
+                    path: Some("src/mod_1.rs".to_string()),
+                    include_tests: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            db.search_count(
+                "",
+                &SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_search_exclude_paths() {
+        let db = SearchDb::new().unwrap();
+        let (files, symbols) = project_symbols("a", 5);
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let exclude = ["*mod_1*".to_string(), "src/mod_3.rs".to_string()];
+        let files_of = |results: Vec<SearchResult>| -> Vec<String> {
+            results
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.file,
+                    other => panic!("expected symbol, got {other:?}"),
+                })
+                .collect()
+        };
+        let expected = vec!["src/mod_0.rs", "src/mod_2.rs", "src/mod_4.rs"];
+
+        let mut results = files_of(
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    exclude_paths: exclude.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap(),
+        );
+        results.sort();
+        assert_eq!(results, expected);
+        assert_eq!(
+            db.search_count(
+                "handler*",
+                &SearchFilters {
+                    scope: scope.to_vec(),
+                    exclude_paths: exclude.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                }
+            )
+            .unwrap(),
+            3
+        );
+
+        // The symbol-table scans (regex, fuzzy) honor exclusions too
+        let page = db
+            .search_regex(
+                "_handler_",
+                &SearchFilters {
+                    exclude_paths: exclude.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(files_of(page.results), expected);
+        let page = db
+            .search_fuzzy(
+                "a_handler_1",
+                &SearchFilters {
+                    exclude_paths: exclude.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                100,
+                0,
+            )
+            .unwrap();
+        assert!(
+            files_of(page.results)
+                .iter()
+                .all(|f| expected.contains(&f.as_str()))
+        );
+    }
+
+    #[test]
+    fn test_search_leaves_out_test_files() {
+        let db = SearchDb::new().unwrap();
+        let (mut files, mut symbols) = project_symbols("a", 3);
+        // Not flagged, as in an index written before `is_test`: classified on load
+        files[1].path = "tests/mod_1.rs".to_string();
+        symbols[1].file = "tests/mod_1.rs".to_string();
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+
+        let files_of = |include_tests: bool| -> Vec<String> {
+            let mut files: Vec<String> = db
+                .search(&SearchQuery {
+                    filters: SearchFilters {
+                        include_tests,
+                        ..Default::default()
+                    },
+                    ..SearchQuery::new("handler*")
+                })
+                .unwrap()
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.file,
+                    SearchResult::File(f) => f.path,
+                    SearchResult::Text(t) => t.file,
+                })
+                .collect();
+            files.sort();
+            files
+        };
+        assert_eq!(files_of(false), vec!["src/mod_0.rs", "src/mod_2.rs"]);
+        assert_eq!(
+            files_of(true),
+            vec!["src/mod_0.rs", "src/mod_2.rs", "tests/mod_1.rs"]
+        );
+        assert_eq!(
+            db.search_count("handler*", &SearchFilters::default())
+                .unwrap(),
+            2
+        );
+
+        // A flagged file is excluded whatever its path, by the symbol scans too
+        let file = FileEntry {
+            path: "src/mod_2.rs".to_string(),
+            is_test: true,
+            ..files[2].clone()
+        };
+        db.upsert_file("", &file, &symbols[2..], &[], &[]).unwrap();
+        db.update_fts_for_file("", &file.path).unwrap();
+        assert_eq!(files_of(false), vec!["src/mod_0.rs"]);
+        let page = db
+            .search_regex(
+                "_handler_",
+                &SearchFilters::default(),
+                SearchOrder::Relevance,
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(page.total, 1);
+        let page = db
+            .search_regex(
+                "_handler_",
+                &SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                100,
+                0,
+            )
+            .unwrap();
+        assert_eq!(page.total, 3);
+    }
+
+    #[test]
+    fn test_search_order_by() {
+        let db = SearchDb::new().unwrap();
+        let file = |path: &str| FileEntry {
+            project: String::new(),
+            path: path.to_string(),
+            lang: Some("rust".to_string()),
+            hash: path.to_string(),
+            lines: 20,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let symbol = |path: &str, name: &str, line: u32| SymbolEntry {
+            line: [line, line + 1],
+            tokens: Some("widget".to_string()),
+            ..test_symbol("", path, name, "function")
+        };
+        let files = vec![file("src/b.rs"), file("src/a.rs")];
+        let symbols = vec![
+            symbol("src/b.rs", "zeta", 3),
+            symbol("src/b.rs", "Alpha", 10),
+            symbol("src/a.rs", "gamma", 7),
+            symbol("src/a.rs", "beta", 1),
+        ];
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+        let scope = ["symbol".to_string()];
+        let names = |query: &str, name_regex: Option<&str>, order_by: SearchOrder| -> Vec<String> {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                name_regex: name_regex.map(str::to_string),
+                order_by,
+                ..SearchQuery::new(query)
+            })
+            .unwrap()
+            .into_iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.name,
+                other => panic!("unexpected result: {other:?}"),
+            })
+            .collect()
+        };
+
+        assert_eq!(
+            names("widget", None, SearchOrder::Path),
+            vec!["beta", "gamma", "zeta", "Alpha"]
+        );
+        assert_eq!(
+            names("widget", None, SearchOrder::Name),
+            vec!["Alpha", "beta", "gamma", "zeta"]
+        );
+        assert_eq!(
+            names("widget", None, SearchOrder::Line),
+            vec!["beta", "zeta", "gamma", "Alpha"]
+        );
+        // Relevance is the default and still ranks by BM25 (every row ties here)
+        assert_eq!(names("widget", None, SearchOrder::Relevance).len(), 4);
+
+        // A regex without a query scans symbols, sorted the same way
+        assert_eq!(
+            names("", Some("a$"), SearchOrder::Name),
+            vec!["Alpha", "beta", "gamma", "zeta"]
+        );
+        assert_eq!(
+            names("", Some("a$"), SearchOrder::Line),
+            vec!["beta", "zeta", "gamma", "Alpha"]
+        );
+    }
+
+    #[test]
+    fn test_search_paged_reports_total() {
+        let db = SearchDb::new().unwrap();
+        let (files, symbols) = project_symbols("a", 7);
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+        let scope = ["symbol".to_string()];
+
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 3,
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        assert_eq!(page.results.len(), 3);
+        assert_eq!(page.total, 7);
+
+        // Last partial page, and a page past the end, still know the total
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 3,
+                offset: 6,
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.total, 7);
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 3,
+                offset: 9,
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        assert!(page.results.is_empty());
+        assert_eq!(page.total, 7);
+
+        // Enumeration (empty query) counts the same way
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 5,
+                ..SearchQuery::new("")
+            })
+            .unwrap();
+        assert_eq!(page.total, 14);
+    }
+
+    #[test]
+    fn test_query_term_groups() {
+        let term = |text: &str, prefix: bool| QueryTerm {
+            text: text.to_string(),
+            prefix,
+        };
+        assert_eq!(
+            query_term_groups("Parser parse*"),
+            vec![vec![term("Parser", false)], vec![term("parse", true)]]
+        );
+        assert_eq!(
+            query_term_groups("map|Map OR set NOT test"),
+            vec![vec![
+                term("map", false),
+                term("Map", false),
+                term("set", false)
+            ]]
+        );
+        assert_eq!(
+            query_term_groups("\"safe parse\" name:Foo"),
+            vec![vec![term("safe parse", false)], vec![term("Foo", false)]]
+        );
+        assert_eq!(
+            query_term_groups("NEAR(parse async, 5)"),
+            vec![vec![term("parse", false)], vec![term("async", false)]]
+        );
+    }
+
+    #[test]
+    fn test_search_grouped() {
+        let db = SearchDb::new().unwrap();
+        let (files, symbols) = project_symbols("a", 2);
+        db.load("libs/a", &files, &symbols, &[], &[]).unwrap();
+        let (files, mut symbols) = project_symbols("b", 3);
+        // An exact name match ranks project "libs/b" first
+        symbols[1].name = "handler".to_string();
+        db.load("libs/b", &files, &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let groups = db
+            .search_grouped(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("handler*")
+            })
+            .unwrap();
+        let summary: Vec<(String, usize)> = groups
+            .iter()
+            .map(|(project, results)| (project.clone(), results.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("libs/b".to_string(), 3), ("libs/a".to_string(), 2)]
+        );
+        match &groups[0].1[0] {
+            SearchResult::Symbol(s) => assert_eq!(s.name, "handler"),
+            other => panic!("expected symbol, got {other:?}"),
+        }
+        assert!(
+            groups
+                .iter()
+                .all(|(project, results)| results.iter().all(|r| r.project() == project))
+        );
     }
 
-    let mut result = String::with_capacity(s.len() * 2);
-    let mut in_quote = false;
-    let mut current_token = String::new();
+    #[test]
+    fn test_search_bm25_weights() {
+        let symbol = |name: &str, tokens: Option<&str>| SymbolEntry {
+            tokens: tokens.map(str::to_string),
+            ..test_symbol("", "src/lib.rs", name, "function")
+        };
+        let db = SearchDb::new().unwrap();
+        let symbols = vec![
+            symbol("cache_get", None),
+            symbol("load", Some("cache entry cache hit")),
+        ];
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let search = |weights: Option<(f64, f64, f64)>| {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                weights,
+                limit: 10,
+                ..SearchQuery::new("cache")
+            })
+            .map(|results| {
+                results
+                    .into_iter()
+                    .map(|r| match r {
+                        SearchResult::Symbol(s) => s.name,
+                        other => panic!("expected symbol, got {other:?}"),
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
 
-    for c in s.chars() {
-        if c == '"' {
-            in_quote = !in_quote;
-            current_token.push(c);
-        } else if c.is_whitespace() && !in_quote {
-            // End of token - flush it
-            if !current_token.is_empty() {
-                result.push_str(&expand_pipes(&current_token));
-                current_token.clear();
-            }
-            result.push(c);
-        } else {
-            current_token.push(c);
-        }
-    }
+        // Default weights favour the name match
+        assert_eq!(search(None).unwrap(), vec!["cache_get", "load"]);
+        assert_eq!(
+            search(Some((3.0, 2.0, 1.0))).unwrap(),
+            vec!["cache_get", "load"]
+        );
 
-    // Flush remaining token
-    if !current_token.is_empty() {
-        result.push_str(&expand_pipes(&current_token));
+        // Weighting content only favours the symbol whose tokens mention it
+        assert_eq!(
+            search(Some((0.0, 0.0, 1.0))).unwrap(),
+            vec!["load", "cache_get"]
+        );
+
+        assert!(search(Some((-1.0, 2.0, 1.0))).is_err());
     }
 
-    result
-}
+    #[test]
+    fn test_search_matches_identifier_words() {
+        let symbol = |name: &str, tokens: Option<&str>| SymbolEntry {
+            tokens: tokens.map(str::to_string),
+            ..test_symbol("", "src/lib.rs", name, "function")
+        };
+        let db = SearchDb::new().unwrap();
+        let symbols = vec![
+            symbol("parseAsync", None),
+            symbol("load", Some("HTTPClient retry")),
+            symbol("parser", None),
+        ];
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let search = |query: &str, whole_word: bool| {
+            db.search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                whole_word,
+                limit: 10,
+                ..SearchQuery::new(query)
+            })
+            .unwrap()
+            .into_iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.name,
+                other => panic!("expected symbol, got {other:?}"),
+            })
+            .collect::<Vec<_>>()
+        };
 
-/// Expand pipes in a single token: `a|b|c` → `(a OR b OR c)`
-fn expand_pipes(token: &str) -> String {
-    // Don't expand if token is entirely quoted
-    if token.starts_with('"') && token.ends_with('"') {
-        return token.to_string();
+        // Camel humps of names and tokens are searchable words
+        assert_eq!(search("parse async", false), vec!["parseAsync"]);
+        assert_eq!(search("http client", false), vec!["load"]);
+        // The whole identifier still matches
+        assert_eq!(search("parseasync", false), vec!["parseAsync"]);
+        // whole_word checks the stored text, not the added sub-tokens
+        assert!(search("async", true).is_empty());
     }
 
-    if !token.contains('|') {
-        return token.to_string();
-    }
+    #[test]
+    fn test_search_fuzzy() {
+        let symbol = |name: &str, file: &str| test_symbol("", file, name, "function");
+        let symbols = vec![
+            symbol("Auth.authenticate", "src/auth.rs"),
+            symbol("authorize", "src/auth.rs"),
+            symbol("parse_config", "src/config.rs"),
+            symbol("renderers", "src/view.rs"),
+            symbol("render", "src/view.rs"),
+        ];
+        for db in [SearchDb::new().unwrap(), SearchDb::new_trigram().unwrap()] {
+            db.load("", &[], &symbols, &[], &[]).unwrap();
+            let names = |query: &str| -> Vec<String> {
+                db.search_fuzzy(
+                    query,
+                    &SearchFilters {
+                        include_tests: true,
+                        ..Default::default()
+                    },
+                    SearchOrder::Relevance,
+                    10,
+                    0,
+                )
+                .unwrap()
+                .results
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.name,
+                    other => panic!("unexpected result {other:?}"),
+                })
+                .collect()
+            };
 
-    let parts: Vec<&str> = token.split('|').collect();
-    if parts.len() == 1 {
-        return token.to_string();
+            // Narrowed by FTS on the first three characters
+            assert_eq!(names("authetnicate"), vec!["Auth.authenticate"]);
+            assert_eq!(names("Authorise"), vec!["authorize"]);
+            // Typo within the first characters: found by the full scan
+            assert_eq!(names("prase_config"), vec!["parse_config"]);
+            // Short terms tolerate no edits; far names are left out
+            assert!(names("rnd").is_empty());
+            assert!(names("xyzzy").is_empty());
+            // Closest first
+            assert_eq!(names("renders"), vec!["render", "renderers"]);
+        }
+
+        let db = SearchDb::new().unwrap();
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+        // Filters and paging apply
+        let page = db
+            .search_fuzzy(
+                "renders",
+                &SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                1,
+                1,
+            )
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.results.len(), 1);
+        let page = db
+            .search_fuzzy(
+                "authorize",
+                &SearchFilters {
+                    kind: vec!["class".to_string()],
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                10,
+                0,
+            )
+            .unwrap();
+        assert_eq!(page.total, 0);
     }
 
-    format!("({})", parts.join(" OR "))
-}
+    #[test]
+    fn test_search_explain() {
+        let symbol = |name: &str, tokens: Option<&str>| SymbolEntry {
+            tokens: tokens.map(str::to_string),
+            ..test_symbol("", "src/lib.rs", name, "function")
+        };
+        let db = SearchDb::new().unwrap();
+        let symbols = vec![
+            symbol("cache_get", None),
+            symbol("load", Some("reads the cache entry")),
+        ];
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let search = |query: &str, explain: bool| {
+            db.search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                explain,
+                limit: 10,
+                ..SearchQuery::new(query)
+            })
+            .unwrap()
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let page = search("cache", true);
+        assert_eq!(page.matches.len(), page.results.len());
+        let names: Vec<&str> = page
+            .results
+            .iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.name.as_str(),
+                other => panic!("expected symbol, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(names, vec!["cache_get", "load"]);
+
+        let by_name = &page.matches[0];
+        assert_eq!(by_name.matched_column, "name");
+        assert!(by_name.name_score < 0.0);
+        assert_eq!(by_name.content_score, 0.0);
+        assert!(by_name.snippet.is_none());
+
+        let by_content = &page.matches[1];
+        assert_eq!(by_content.matched_column, "content");
+        assert!(by_content.score < 0.0);
+        assert_eq!(by_content.name_score, 0.0);
+        assert!(by_content.snippet.as_deref().unwrap().contains("«cache»"));
+
+        // Off by default, and nothing to explain without a query
+        assert!(search("cache", false).matches.is_empty());
+        assert!(search("", true).matches.is_empty());
+    }
 
     #[test]
-    fn test_transform_pipe_to_or() {
-        // No pipe - pass through
-        assert_eq!(transform_pipe_to_or("foo bar"), "foo bar");
-        assert_eq!(transform_pipe_to_or("foo OR bar"), "foo OR bar");
+    fn test_search_explain_identifier_words() {
+        let db = SearchDb::new().unwrap();
+        let symbols = vec![test_symbol("", "src/lib.rs", "parseAsync", "function")];
+        db.load("", &[], &symbols, &[], &[]).unwrap();
+
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    ..Default::default()
+                },
+                explain: true,
+                limit: 10,
+                ..SearchQuery::new("async")
+            })
+            .unwrap();
+        assert_eq!(page.results.len(), 1);
+
+        // `parseAsync` is one name token: its humps match in the content column
+        let info = &page.matches[0];
+        assert_eq!(info.matched_column, "content");
+        assert_eq!(info.name_score, 0.0);
+        let snippet = info.snippet.as_deref().unwrap();
+        assert!(snippet.contains("«Async»"));
+        assert!(!snippet.contains(SUBTOKEN_SEPARATOR));
+    }
 
-        // Simple pipe
-        assert_eq!(transform_pipe_to_or("foo|bar"), "(foo OR bar)");
-        assert_eq!(transform_pipe_to_or("a|b|c"), "(a OR b OR c)");
+    #[test]
+    fn test_search_case_sensitive_and_whole_word() {
+        let symbol = |name: &str| test_symbol("", "src/lib.rs", name, "struct");
+        let file = FileEntry {
+            project: String::new(),
+            path: "src/lib.rs".to_string(),
+            lang: Some("rust".to_string()),
+            hash: "abc123".to_string(),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let db = SearchDb::new().unwrap();
+        let symbols = vec![
+            symbol("Parser"),
+            symbol("parser"),
+            symbol("map"),
+            symbol("unordered_map"),
+            symbol("parse_args"),
+        ];
+        db.load("", &[file], &symbols, &[], &[]).unwrap();
+
+        let scope = ["symbol".to_string()];
+        let names = |query: &str, case_sensitive: bool, whole_word: bool| {
+            let mut names: Vec<String> = db
+                .search(&SearchQuery {
+                    filters: SearchFilters {
+                        scope: scope.to_vec(),
+                        include_tests: true,
+                        ..Default::default()
+                    },
+                    case_sensitive,
+                    whole_word,
+                    ..SearchQuery::new(query)
+                })
+                .unwrap()
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.name,
+                    other => panic!("unexpected {other:?}"),
+                })
+                .collect();
+            names.sort();
+            names
+        };
 
-        // Pipe with surrounding terms
-        assert_eq!(transform_pipe_to_or("foo|bar baz"), "(foo OR bar) baz");
-        assert_eq!(
-            transform_pipe_to_or("prefix foo|bar"),
-            "prefix (foo OR bar)"
-        );
-        assert_eq!(
-            transform_pipe_to_or("prefix foo|bar suffix"),
-            "prefix (foo OR bar) suffix"
-        );
+        // Default behaviour is unchanged
+        assert_eq!(names("parser", false, false), vec!["Parser", "parser"]);
+        assert_eq!(names("map", false, false), vec!["map", "unordered_map"]);
 
-        // Pipe inside quotes preserved
-        assert_eq!(transform_pipe_to_or("\"foo|bar\""), "\"foo|bar\"");
+        assert_eq!(names("Parser", true, false), vec!["Parser"]);
+        assert_eq!(names("map", false, true), vec!["map"]);
+        // Prefix terms only need to start an identifier
         assert_eq!(
-            transform_pipe_to_or("test \"foo|bar\" other"),
-            "test \"foo|bar\" other"
+            names("parse*", false, true),
+            vec!["Parser", "parse_args", "parser"]
         );
+        assert_eq!(names("parse*", true, true), vec!["parse_args", "parser"]);
+
+        // Paging and totals apply to the filtered rows
+        let page = db
+            .search_paged(&SearchQuery {
+                filters: SearchFilters {
+                    scope: scope.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                case_sensitive: true,
+                limit: 1,
+                offset: 1,
+                ..SearchQuery::new("parse*")
+            })
+            .unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.total, 2);
+    }
 
-        // Mixed: pipe outside quotes, literal inside
-        assert_eq!(transform_pipe_to_or("a|b \"c|d\""), "(a OR b) \"c|d\"");
+    #[test]
+    fn test_search_name_regex() {
+        let db = SearchDb::new().unwrap();
+        let (files, mut symbols) = project_symbols("a", 2);
+        for (name, sig) in [
+            ("Bus.handle_click_event", "fn handle_click_event(&self)"),
+            ("handle_event", "fn handle_event()"),
+            ("handle_key_event_loop", "fn handle_key_event_loop()"),
+        ] {
+            symbols.push(SymbolEntry {
+                name: name.to_string(),
+                sig: Some(sig.to_string()),
+                ..symbols[0].clone()
+            });
+        }
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+
+        let names = |query: &str| {
+            let page = db
+                .search_paged(&SearchQuery {
+                    filters: SearchFilters {
+                        include_tests: true,
+                        ..Default::default()
+                    },
+                    name_regex: Some("^handle_.*_event$".to_string()),
+                    ..SearchQuery::new(query)
+                })
+                .unwrap();
+            let names: Vec<String> = page
+                .results
+                .into_iter()
+                .map(|r| match r {
+                    SearchResult::Symbol(s) => s.name,
+                    other => panic!("unexpected {other:?}"),
+                })
+                .collect();
+            (names, page.total)
+        };
 
-        // With prefix wildcard
+        // Empty query scans the symbols table; base names match too
+        assert_eq!(names(""), (vec!["Bus.handle_click_event".to_string()], 1));
+        // With a query, FTS narrows first
         assert_eq!(
-            transform_pipe_to_or("hand*|context*"),
-            "(hand* OR context*)"
+            names("handle*"),
+            (vec!["Bus.handle_click_event".to_string()], 1)
         );
-    }
 
-    fn setup_test_db_with_refs(refs: &[ReferenceEntry]) -> SearchDb {
-        let db = SearchDb::new_no_fts().unwrap();
-        db.load("test", &[], &[], &[], refs).unwrap();
-        db
+        let err = db
+            .search_regex(
+                "handle_(",
+                &SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                SearchOrder::Relevance,
+                10,
+                0,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid name regex"));
     }
 
     #[test]
-    fn test_get_callers_base_name_match() {
-        // Insert a reference with a "self." prefixed name (as Python parser produces)
-        let refs = vec![ReferenceEntry {
+    fn test_search_lang_filter() {
+        let file = |path: &str, lang: &str| FileEntry {
             project: String::new(),
-            file: "app.py".to_string(),
-            name: "self.handle_exception".to_string(),
-            kind: "call".to_string(),
-            line: [100, 100],
-            caller: Some("full_dispatch_request".to_string()),
-        }];
-        let db = setup_test_db_with_refs(&refs);
+            path: path.to_string(),
+            lang: Some(lang.to_string()),
+            hash: format!("hash-{path}"),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let function = |file: &str| SymbolEntry {
+            project: String::new(),
+            file: file.to_string(),
+            name: "parse".to_string(),
+            kind: "function".to_string(),
+            line: [1, 5],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: Some("public".to_string()),
+        };
+        let db = SearchDb::new().unwrap();
+        db.load(
+            "",
+            &[file("cli/args.py", "python"), file("src/args.rs", "rust")],
+            &[function("cli/args.py"), function("src/args.rs")],
+            &[],
+            &[],
+        )
+        .unwrap();
 
-        // Query with base name should find the reference
-        // Use visibility="private" to skip symbol join (no symbols in test data)
+        // Symbol rows carry their file's language
+        let python = ["python".to_string()];
         let results = db
-            .get_callers(
-                "handle_exception",
-                None,
-                Some("test"),
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    kind: vec!["function".to_string()],
+                    lang: python.to_vec(),
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("parse")
+            })
             .unwrap();
-
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "self.handle_exception");
-        assert_eq!(results[0].caller.as_deref(), Some("full_dispatch_request"));
-    }
-
-    #[test]
-    fn test_get_callers_exact_match_still_works() {
-        // Insert a reference with full name
-        let refs = vec![ReferenceEntry {
-            project: String::new(),
-            file: "app.py".to_string(),
-            name: "self.handle_exception".to_string(),
-            kind: "call".to_string(),
-            line: [100, 100],
-            caller: Some("dispatch".to_string()),
-        }];
-        let db = setup_test_db_with_refs(&refs);
+        match &results[0] {
+            SearchResult::Symbol(s) => assert_eq!(s.file, "cli/args.py"),
+            other => panic!("expected symbol, got {other:?}"),
+        }
 
-        // Query with exact name should still work
-        // Use visibility="private" to skip symbol join (no symbols in test data)
+        // Without a lang filter both languages match
         let results = db
-            .get_callers(
-                "self.handle_exception",
-                None,
-                Some("test"),
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                ..SearchQuery::new("parse")
+            })
             .unwrap();
-
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "self.handle_exception");
+        assert_eq!(results.len(), 2);
     }
 
     #[test]
-    fn test_get_callers_no_false_positives() {
-        // Insert references
-        let refs = vec![
-            ReferenceEntry {
-                project: String::new(),
-                file: "app.py".to_string(),
-                name: "self.handle_exception".to_string(),
-                kind: "call".to_string(),
-                line: [100, 100],
-                caller: None,
-            },
-            ReferenceEntry {
-                project: String::new(),
-                file: "app.py".to_string(),
-                name: "handle_user_exception".to_string(),
-                kind: "call".to_string(),
-                line: [200, 200],
-                caller: None,
-            },
-        ];
-        let db = setup_test_db_with_refs(&refs);
+    fn test_search_empty_query_enumerates() {
+        let db = SearchDb::new().unwrap();
+        let (files_a, symbols_a) = project_symbols("a", 3);
+        let (files_b, symbols_b) = project_symbols("b", 2);
+        db.load("a", &files_a, &symbols_a, &[], &[]).unwrap();
+        db.load("b", &files_b, &symbols_b, &[], &[]).unwrap();
 
-        // Query for "handle_exception" should NOT match "handle_user_exception"
-        // because it doesn't end with ".handle_exception"
-        // Use visibility="private" to skip symbol join (no symbols in test data)
         let results = db
-            .get_callers(
-                "handle_exception",
-                None,
-                Some("test"),
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    scope: vec!["symbol".to_string()],
+                    include_tests: true,
+                    projects: vec!["a".to_string()],
+                    ..Default::default()
+                },
+                ..SearchQuery::new("")
+            })
             .unwrap();
+        let names: Vec<&str> = results
+            .iter()
+            .map(|r| match r {
+                SearchResult::Symbol(s) => s.name.as_str(),
+                other => panic!("expected symbol, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(names, vec!["a_handler_0", "a_handler_1", "a_handler_2"]);
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "self.handle_exception");
+        // Whitespace-only behaves the same; pagination still applies
+        let results = db
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 2,
+                offset: 1,
+                ..SearchQuery::new(" ")
+            })
+            .unwrap();
+        assert_eq!(results.len(), 2);
     }
 
     #[test]
-    fn test_get_callees_base_name_match() {
-        // Insert a reference with a qualified caller name
-        let refs = vec![ReferenceEntry {
+    fn test_search_matches_signature() {
+        let db = SearchDb::new().unwrap();
+        let symbol = SymbolEntry {
             project: String::new(),
-            file: "app.py".to_string(),
-            name: "os.path.join".to_string(),
-            kind: "call".to_string(),
-            line: [100, 100],
-            caller: Some("MyClass.process_data".to_string()),
-        }];
-        let db = setup_test_db_with_refs(&refs);
+            file: "auth.py".to_string(),
+            name: "validate_user".to_string(),
+            kind: "function".to_string(),
+            line: [1, 5],
+            parent: None,
+            sig: Some("def validate_user(email: str, password: str) -> bool".to_string()),
+            tokens: None,
+            alias: None,
+            visibility: Some("public".to_string()),
+        };
+        db.load("", &[], std::slice::from_ref(&symbol), &[], &[])
+            .unwrap();
 
-        // Query with base caller name should find the reference
-        // Use visibility="private" to skip symbol join (no symbols in test data)
         let results = db
-            .get_callees("process_data", None, Some("test"), Some("private"), 100, 0)
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 10,
+                ..SearchQuery::new("\"(email: str, password: str)\"")
+            })
             .unwrap();
-
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "os.path.join");
-        assert_eq!(results[0].caller.as_deref(), Some("MyClass.process_data"));
+
+        // Signatures round-trip through load/export
+        let (_, symbols, _, _) = db.export_for_project("").unwrap();
+        assert_eq!(symbols[0].sig, symbol.sig);
     }
 
     #[test]
-    fn test_get_callees_exact_match_still_works() {
-        // Insert a reference
-        let refs = vec![ReferenceEntry {
+    fn test_open_file_persists_across_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.sqlite");
+        let file = FileEntry {
             project: String::new(),
-            file: "app.py".to_string(),
-            name: "helper".to_string(),
-            kind: "call".to_string(),
-            line: [100, 100],
-            caller: Some("MyClass.method".to_string()),
-        }];
-        let db = setup_test_db_with_refs(&refs);
+            path: "src/lib.rs".to_string(),
+            lang: Some("rust".to_string()),
+            hash: "hash-src/lib.rs".to_string(),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let symbol = SymbolEntry {
+            project: String::new(),
+            file: "src/lib.rs".to_string(),
+            name: "persisted_fn".to_string(),
+            kind: "function".to_string(),
+            line: [1, 3],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: Some("public".to_string()),
+        };
 
-        // Query with exact caller name should work
-        // Use visibility="private" to skip symbol join (no symbols in test data)
+        {
+            let db = SearchDb::open_file(&path).unwrap().unwrap();
+            assert!(!db.has_project("").unwrap());
+            db.load("", std::slice::from_ref(&file), &[symbol], &[], &[])
+                .unwrap();
+        }
+
+        // Reopening keeps rows, hashes and FTS
+        let db = SearchDb::open_file(&path).unwrap().unwrap();
+        assert!(db.has_project("").unwrap());
+        assert_eq!(db.file_paths("").unwrap(), vec!["src/lib.rs".to_string()]);
+        assert_eq!(
+            db.get_file_hash("", "src/lib.rs").unwrap().as_deref(),
+            Some("hash-src/lib.rs")
+        );
         let results = db
-            .get_callees(
-                "MyClass.method",
-                None,
-                Some("test"),
-                Some("private"),
-                100,
-                0,
-            )
+            .search(&SearchQuery {
+                filters: SearchFilters {
+                    include_tests: true,
+                    ..Default::default()
+                },
+                limit: 10,
+                ..SearchQuery::new("persisted_fn")
+            })
             .unwrap();
+        assert!(!results.is_empty());
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "helper");
+        // A file from another schema version is rebuilt empty
+        db.conn
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        drop(db);
+        let db = SearchDb::open_file(&path).unwrap().unwrap();
+        assert!(!db.has_project("").unwrap());
     }
 
-    // Visibility filter tests
+    /// Compare one FTS5 table holding every project with the per-project tables,
+    /// on a 20-project workspace: searches scoped to one project, unscoped
+    /// searches, and dropping one project's rows before it is repopulated. The
+    /// single table is filled from the shards' rows, and both layouts run the
+    /// same statements.
+    /// Run with `cargo test --release -- --ignored bench_fts_single_vs_sharded --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_fts_single_vs_sharded() {
+        const RUNS: u32 = 50;
+        let db = SearchDb::new().unwrap();
+        for p in 0..20 {
+            let project = format!("proj{p}");
+            let (files, symbols) = project_symbols(&project, 2_000);
+            db.load(&project, &files, &symbols, &[], &[]).unwrap();
+        }
+        let shards = fts_shards(&db.conn, &[]).unwrap();
+        let single = vec!["single_fts".to_string()];
+        create_fts_table(&db.conn, &single[0], false).unwrap();
+        for table in &shards {
+            db.conn
+                .execute_batch(&format!("INSERT INTO single_fts SELECT * FROM {table}"))
+                .unwrap();
+        }
 
-    fn setup_test_db_with_symbols(symbols: &[SymbolEntry]) -> SearchDb {
-        let db = SearchDb::new_no_fts().unwrap();
-        // Collect unique files by path
-        let mut seen = std::collections::HashSet::new();
-        let files: Vec<FileEntry> = symbols
-            .iter()
-            .filter(|s| seen.insert(s.file.clone()))
-            .map(|s| FileEntry {
-                project: s.project.clone(),
-                path: s.file.clone(),
-                lang: Some("rust".to_string()),
-                hash: "abc123".to_string(),
-                lines: 100,
-                title: None,
-                description: None,
-            })
-            .collect();
-        db.load("test", &files, symbols, &[], &[]).unwrap();
-        db
+        // The 20 best matches, merged from the best 20 of each table as `search` does
+        let time_search = |tables: &[String], project_filter: &str| {
+            let rows = format!(
+                "SELECT * FROM (SELECT rowid_ref, bm25(search_fts) AS rank FROM search_fts \
+                 WHERE search_fts MATCH 'handler'{project_filter} ORDER BY rank LIMIT 20)"
+            );
+            let sql = format!(
+                "SELECT rowid_ref FROM ({}) ORDER BY rank LIMIT 20",
+                fts_union(tables, &rows)
+            );
+            let start = std::time::Instant::now();
+            for _ in 0..RUNS {
+                let mut stmt = db.conn.prepare(&sql).unwrap();
+                let found = stmt.query_map([], |row| row.get::<_, i64>(0)).unwrap();
+                assert_eq!(found.count(), 20);
+            }
+            start.elapsed() / RUNS
+        };
+        let scoped = " AND project = 'proj7'";
+        let proj7 = fts_shards(&db.conn, &["proj7".to_string()]).unwrap();
+        println!(
+            "20 projects x 2000 files, scoped to one project: single table {:?}, sharded {:?}",
+            time_search(&single, scoped),
+            time_search(&proj7, scoped)
+        );
+        println!(
+            "20 projects x 2000 files, unscoped: single table {:?}, sharded {:?}",
+            time_search(&single, ""),
+            time_search(&shards, "")
+        );
+
+        // Rebuilding a project drops its rows, then inserts them the same way in both layouts
+        let start = std::time::Instant::now();
+        db.conn
+            .execute_batch("DELETE FROM single_fts WHERE project = 'proj7'")
+            .unwrap();
+        let single_drop = start.elapsed();
+        let start = std::time::Instant::now();
+        drop_fts_shard(&db.conn, "proj7").unwrap();
+        let sharded_drop = start.elapsed();
+        let start = std::time::Instant::now();
+        db.rebuild_fts_project("proj7").unwrap();
+        let rebuild = start.elapsed();
+        let start = std::time::Instant::now();
+        db.rebuild_fts().unwrap();
+        let full = start.elapsed();
+        println!(
+            "dropping one project's rows: single table {single_drop:?}, sharded {sharded_drop:?}; \
+             one-project rebuild {rebuild:?}, full rebuild {full:?}"
+        );
     }
 
     #[test]
-    fn test_visibility_max_level_function() {
-        // Test the visibility_max_level helper function with explicit values
-        assert_eq!(visibility_max_level(Some("public"), "public"), Some(1));
-        assert_eq!(visibility_max_level(Some("internal"), "public"), Some(2));
-        assert_eq!(visibility_max_level(Some("private"), "public"), None);
-        assert_eq!(visibility_max_level(Some("unknown"), "public"), None); // Unknown, no filter
+    fn test_project_stats() {
+        let db = SearchDb::new_no_fts().unwrap();
+        let (files, symbols) = project_symbols("a", 3);
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+        let (files, symbols) = project_symbols("b", 2);
+        db.load("libs/b", &files, &symbols[..1], &[], &[]).unwrap();
+
+        let stats = db.project_stats("").unwrap();
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.symbols, 3);
+        assert_eq!(stats.lines, 30);
+        assert_eq!(stats.symbols_by_kind, vec![("function".to_string(), 3)]);
+        assert_eq!(stats.languages, vec!["rust".to_string()]);
+        assert_eq!(
+            stats.files_by_language,
+            vec![LanguageStats {
+                lang: "rust".to_string(),
+                files: 3,
+                lines: 30,
+            }]
+        );
 
-        // Test default behavior (None uses the default parameter)
-        assert_eq!(visibility_max_level(None, "public"), Some(1)); // Default: public
-        assert_eq!(visibility_max_level(None, "internal"), Some(2)); // Default: internal
-        assert_eq!(visibility_max_level(None, "private"), None); // Default: private (no filter)
+        let stats = db.project_stats("libs/b").unwrap();
+        assert_eq!(stats.project, "libs/b");
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.symbols, 1);
+        assert_eq!(stats.texts, 0);
+        assert_eq!(stats.refs, 0);
+
+        let stats = db.project_stats("missing").unwrap();
+        assert_eq!(stats.files, 0);
+        assert!(stats.languages.is_empty());
     }
 
     #[test]
-    fn test_visibility_to_level_function() {
-        // Test the visibility_to_level helper function
-        assert_eq!(visibility_to_level(Some("public")), 1);
-        assert_eq!(visibility_to_level(Some("internal")), 2);
-        assert_eq!(visibility_to_level(Some("private")), 3);
-        assert_eq!(visibility_to_level(None), 3);
+    fn test_recent_files() {
+        let file = |path: &str| FileEntry {
+            project: String::new(),
+            path: path.to_string(),
+            lang: Some("rust".to_string()),
+            hash: path.to_string(),
+            lines: 1,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load("", &[file("a.rs"), file("b.rs")], &[], &[], &[])
+            .unwrap();
+        db.load("lib", &[file("c.rs")], &[], &[], &[]).unwrap();
+
+        // Re-parsing a file moves it to the front
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let cutoff = SystemTime::now();
+        db.upsert_file("", &file("b.rs"), &[], &[], &[]).unwrap();
+
+        let recent = db.recent_files(None, None, 10).unwrap();
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].path, "b.rs");
+        assert!(recent[0].indexed_at >= recent[1].indexed_at);
+
+        let since = db.recent_files(None, Some(cutoff), 10).unwrap();
+        let paths: Vec<&str> = since.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["b.rs"]);
+
+        let lib = db.recent_files(Some("lib"), None, 10).unwrap();
+        assert_eq!(lib.len(), 1);
+        assert_eq!(lib[0].path, "c.rs");
+        assert_eq!(db.recent_files(None, None, 1).unwrap().len(), 1);
     }
 
     #[test]
-    fn test_get_file_symbols_visibility_filter() {
-        let symbols = vec![
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "public_fn".to_string(),
-                kind: "function".to_string(),
-                line: [10, 20],
-                parent: None,
-                tokens: None,
-                alias: None,
-                visibility: Some("public".to_string()),
-            },
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "internal_fn".to_string(),
-                kind: "function".to_string(),
-                line: [30, 40],
-                parent: None,
-                tokens: None,
-                alias: None,
-                visibility: Some("internal".to_string()),
-            },
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "private_fn".to_string(),
-                kind: "function".to_string(),
-                line: [50, 60],
-                parent: None,
-                tokens: None,
-                alias: None,
-                visibility: Some("private".to_string()),
-            },
-        ];
-        let db = setup_test_db_with_symbols(&symbols);
-
-        // Default (None) = public - returns only public
-        let results = db.get_file_symbols("lib.rs", None, 100, 0).unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "public_fn");
+    fn test_resolve_reference_scoring() {
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load(
+            "app",
+            &[],
+            &[
+                test_symbol("app", "src/server.py", "Server.handle", "method"),
+                test_symbol("app", "src/client.py", "Client.handle", "method"),
+                test_symbol("app", "src/server.py", "handle", "import"),
+            ],
+            &[],
+            &[],
+        )
+        .unwrap();
+        db.load(
+            "lib",
+            &[],
+            &[test_symbol("lib", "config.rs", "Config.new", "method")],
+            &[],
+            &[],
+        )
+        .unwrap();
 
-        // Explicit public - same as default
+        // Same-file candidate wins among base-name matches; imports are excluded
         let results = db
-            .get_file_symbols("lib.rs", Some("public"), 100, 0)
+            .resolve_reference("self.handle", Some("src/server.py"), Some("app"), 10)
             .unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "public_fn");
+        let ranked: Vec<(&str, u32)> = results
+            .iter()
+            .map(|(s, score)| (s.name.as_str(), *score))
+            .collect();
+        assert_eq!(ranked, vec![("Server.handle", 100), ("Client.handle", 80)]);
 
-        // Internal filter - returns public and internal
+        // Definitions in other projects are still found, without the project bonus
         let results = db
-            .get_file_symbols("lib.rs", Some("internal"), 100, 0)
+            .resolve_reference("Config::new", Some("src/server.py"), Some("app"), 10)
             .unwrap();
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
-        assert!(names.contains(&"public_fn"));
-        assert!(names.contains(&"internal_fn"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.project, "lib");
+        assert_eq!(results[0].1, 100);
 
-        // Private filter - returns all
-        let results = db
-            .get_file_symbols("lib.rs", Some("private"), 100, 0)
-            .unwrap();
-        assert_eq!(results.len(), 3);
+        // Rust paths normalize to the Parent.child naming used for symbols
+        let results = db.resolve_reference("Config::new", None, None, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "Config.new");
+        assert_eq!(results[0].1, 100);
+
+        // `_` in the base name is not a wildcard
+        db.load(
+            "lib",
+            &[],
+            &[
+                test_symbol("lib", "util.rs", "Util.get_x", "method"),
+                test_symbol("lib", "util.rs", "Util.getax", "method"),
+            ],
+            &[],
+            &[],
+        )
+        .unwrap();
+        let results = db.resolve_reference("get_x", None, None, 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "Util.get_x");
+        assert!(
+            db.resolve_reference("get%", None, None, 10)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Unknown names resolve to nothing
+        assert!(
+            db.resolve_reference("missing", None, None, 10)
+                .unwrap()
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_get_children_visibility_filter() {
-        let symbols = vec![
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "MyStruct".to_string(),
-                kind: "struct".to_string(),
-                line: [1, 50],
-                parent: None,
-                tokens: None,
-                alias: None,
-                visibility: Some("public".to_string()),
-            },
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "public_method".to_string(),
-                kind: "method".to_string(),
-                line: [10, 15],
-                parent: Some("MyStruct".to_string()),
-                tokens: None,
-                alias: None,
-                visibility: Some("public".to_string()),
-            },
-            SymbolEntry {
-                project: "test".to_string(),
-                file: "lib.rs".to_string(),
-                name: "private_method".to_string(),
-                kind: "method".to_string(),
-                line: [20, 25],
-                parent: Some("MyStruct".to_string()),
-                tokens: None,
-                alias: None,
-                visibility: Some("private".to_string()),
-            },
-        ];
-        let db = setup_test_db_with_symbols(&symbols);
+    fn test_find_import_cycles() {
+        let file = |path: &str| FileEntry {
+            project: "web".to_string(),
+            path: path.to_string(),
+            lang: Some("typescript".to_string()),
+            hash: format!("hash-{path}"),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let import = |file: &str, name: &str| ReferenceEntry {
+            file: file.to_string(),
+            name: name.to_string(),
+            kind: "import".to_string(),
+            line: [1, 1],
+            caller: None,
+            project: "web".to_string(),
+        };
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load(
+            "web",
+            &[file("src/a.ts"), file("src/b.ts"), file("src/lib/c.ts")],
+            &[],
+            &[],
+            &[
+                import("src/a.ts", "./b.render"),
+                import("src/b.ts", "./lib/c.*"),
+                import("src/lib/c.ts", "../a"),
+                import("src/lib/c.ts", "react.useState"),
+                import("src/a.ts", "./a.self"),
+            ],
+        )
+        .unwrap();
 
-        // Default (None) = public - returns only public method
-        let results = db.get_children("lib.rs", "MyStruct", None, 100, 0).unwrap();
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "public_method");
+        let cycles = db.find_import_cycles("web").unwrap();
+        assert_eq!(
+            cycles,
+            vec![vec![
+                "src/a.ts".to_string(),
+                "src/b.ts".to_string(),
+                "src/lib/c.ts".to_string()
+            ]]
+        );
 
-        // Private filter - returns all methods
+        // Other projects see no cycles
+        assert!(db.find_import_cycles("other").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_timeout() {
+        let mut db = SearchDb::new().unwrap();
+        db.set_search_timeout(Some(Duration::from_millis(50)));
+
+        // A query that never finishes on its own is interrupted
+        let started = std::time::Instant::now();
+        let err = db
+            .with_timeout(|db| {
+                Ok(db.conn.query_row(
+                    "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n)
+                     SELECT count(*) FROM n",
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )?)
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // The connection keeps working, and fast searches are unaffected
         let results = db
-            .get_children("lib.rs", "MyStruct", Some("private"), 100, 0)
+            .search(&SearchQuery {
+                limit: 10,
+                ..SearchQuery::new("anything")
+            })
             .unwrap();
-        assert_eq!(results.len(), 2);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_entry_points_time_out() {
+        let mut db = SearchDb::new().unwrap();
+        let (files, symbols) = project_symbols("a", 5_000);
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+        db.set_search_timeout(Some(Duration::from_millis(1)));
+
+        // case_sensitive checks every candidate row, far longer than the limit
+        let query = SearchQuery {
+            case_sensitive: true,
+            ..SearchQuery::new("handler*")
+        };
+        let err = db.search_paged(&query).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        let err = db.search_grouped(&query).unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+
+        db.set_search_timeout(None);
+        assert_eq!(db.search_paged(&query).unwrap().total, 5_000);
+    }
+
+    #[test]
+    fn test_unused_imports() {
+        let symbol = |name: &str, kind: &str, line: u32, alias: Option<&str>| SymbolEntry {
+            line: [line, line],
+            alias: alias.map(str::to_string),
+            visibility: None,
+            ..test_symbol("svc", "app.py", name, kind)
+        };
+        let reference = |name: &str, kind: &str, line: u32| ReferenceEntry {
+            file: "app.py".to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: [line, line],
+            caller: Some("run".to_string()),
+            project: "svc".to_string(),
+        };
+        let file = FileEntry {
+            project: "svc".to_string(),
+            path: "app.py".to_string(),
+            lang: Some("python".to_string()),
+            hash: "abc".to_string(),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let mut run = symbol("run", "function", 8, None);
+        run.sig = Some("def run(user: User) -> Response".to_string());
+        run.tokens = Some("parse_it".to_string());
+        let db = SearchDb::new_no_fts().unwrap();
+        db.load(
+            "svc",
+            &[file],
+            &[
+                symbol("os", "import", 1, None),
+                symbol("os.path", "import", 2, Some("osp")),
+                symbol("json", "import", 3, None),
+                symbol("utils.dump", "import", 4, None),
+                symbol(".models.User", "import", 5, None),
+                symbol(".http.Response", "import", 6, None),
+                symbol("helpers.*", "import", 7, None),
+                run,
+            ],
+            &[],
+            &[
+                reference("json", "import", 3),
+                reference("os.getcwd", "call", 9),
+                reference("osp.join", "call", 9),
+            ],
+        )
+        .unwrap();
+
+        // `os`, `osp` are called, `User` and `Response` appear in the signature;
+        // the glob import binds nothing we can check
+        let unused = db.unused_imports(Some("svc"), "app.py").unwrap();
+        let names: Vec<&str> = unused.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["json", "utils.dump"]);
+
+        // The project is found from the file when omitted
+        assert_eq!(db.unused_imports(None, "app.py").unwrap().len(), 2);
+        assert!(
+            db.unused_imports(Some("other"), "app.py")
+                .unwrap()
+                .is_empty()
+        );
     }
 
     #[test]
@@ -1998,6 +7291,7 @@ mod tests {
                 lines: 0, // Empty file
                 title: None,
                 description: None,
+                is_test: false,
             },
             FileEntry {
                 project: "test".to_string(),
@@ -2007,6 +7301,7 @@ mod tests {
                 lines: 50,
                 title: None,
                 description: None,
+                is_test: false,
             },
         ];
 
@@ -2018,6 +7313,7 @@ mod tests {
             kind: "function".to_string(),
             line: [1, 10],
             parent: None,
+            sig: None,
             tokens: None,
             alias: None,
             visibility: Some("public".to_string()),
@@ -2051,6 +7347,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_explore_tree() {
+        let file = |path: &str, lang: Option<&str>| FileEntry {
+            project: String::new(),
+            path: path.to_string(),
+            lang: lang.map(str::to_string),
+            hash: path.to_string(),
+            lines: 1,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        let files = vec![
+            file("README.md", Some("markdown")),
+            file("src/a.rs", Some("rust")),
+            file("src/b.rs", Some("rust")),
+            file("src/c.rs", Some("rust")),
+            file("src/data.bin", None),
+            file("src/server/db.rs", Some("rust")),
+            file("src/server/http/routes.rs", Some("rust")),
+            file(".github/ci.yml", None),
+        ];
+        let symbols: Vec<SymbolEntry> = files
+            .iter()
+            .filter(|f| f.path.ends_with(".rs"))
+            .map(|f| SymbolEntry {
+                project: String::new(),
+                file: f.path.clone(),
+                name: "item".to_string(),
+                kind: "function".to_string(),
+                line: [1, 1],
+                parent: None,
+                sig: None,
+                tokens: None,
+                alias: None,
+                visibility: Some("public".to_string()),
+            })
+            .collect();
+        let db = SearchDb::new().unwrap();
+        db.load("", &files, &symbols, &[], &[]).unwrap();
+
+        let tree = db.explore_tree("", None, Some("private"), 2, 2).unwrap();
+        assert_eq!(tree.path, ".");
+        assert_eq!(tree.files, vec!["README.md"]);
+        // Dot directories are skipped
+        assert_eq!(tree.children.len(), 1);
+        let src = &tree.children[0];
+        assert_eq!(src.path, "src");
+        assert_eq!(
+            src.langs,
+            BTreeMap::from([("other".to_string(), 1), ("rust".to_string(), 3)])
+        );
+        assert_eq!(src.files, vec!["a.rs", "b.rs"]);
+        assert_eq!(src.more_files, 2);
+        let server = &src.children[0];
+        assert_eq!(server.path, "src/server");
+        assert_eq!(server.files, vec!["db.rs"]);
+        // src/server/http is below depth 2
+        assert!(server.truncated);
+        assert!(server.children.is_empty());
+
+        // Starting from a subdirectory (trailing slash ignored)
+        let tree = db
+            .explore_tree("", Some("src/server/"), None, 5, 10)
+            .unwrap();
+        assert_eq!(tree.path, "src/server");
+        assert_eq!(tree.children[0].path, "src/server/http");
+        assert_eq!(tree.children[0].files, vec!["routes.rs"]);
+        assert!(!tree.truncated);
+    }
+
     #[test]
     fn test_explore_files_capped_with_visibility_filter() {
         // Test that explore_files_capped filters by visibility correctly
@@ -2065,6 +7432,7 @@ mod tests {
                 lines: 0,
                 title: None,
                 description: None,
+                is_test: false,
             },
             FileEntry {
                 project: "test".to_string(),
@@ -2074,6 +7442,7 @@ mod tests {
                 lines: 50,
                 title: None,
                 description: None,
+                is_test: false,
             },
             FileEntry {
                 project: "test".to_string(),
@@ -2083,6 +7452,7 @@ mod tests {
                 lines: 30,
                 title: None,
                 description: None,
+                is_test: false,
             },
         ];
 
@@ -2094,6 +7464,7 @@ mod tests {
                 kind: "function".to_string(),
                 line: [1, 10],
                 parent: None,
+                sig: None,
                 tokens: None,
                 alias: None,
                 visibility: Some("public".to_string()),
@@ -2105,6 +7476,7 @@ mod tests {
                 kind: "function".to_string(),
                 line: [1, 10],
                 parent: None,
+                sig: None,
                 tokens: None,
                 alias: None,
                 visibility: Some("private".to_string()),
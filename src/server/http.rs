@@ -0,0 +1,451 @@
+//! Read-only HTTP/JSON endpoint for clients that don't speak MCP (`serve --http ADDR`).
+//!
+//! Exposes three tools as GET routes:
+//! - `/search` — `search` (`q` is the query)
+//! - `/explore` — `explore`
+//! - `/callers` — `get_callers`
+//!
+//! Query parameters are the tool parameters under their MCP or CLI names
+//! (`/search?q=parse&scope=symbol,file&project=libs/utils&limit=5`), and the
+//! response body is the tool's JSON output. Requests go through the same
+//! [`CodeIndexServer`] as MCP, so both share one database. No route writes to the index.
+//!
+//! `/query?command=<JSON>` runs a `codeix query` command for `query --connect`
+//! (see [`crate::cli::remote`]) and answers `{"output": ...}` with what the
+//! command would print. Only read-only commands are accepted (see
+//! [`served_over_http`]): nothing that writes, and nothing that reads saved
+//! indexes or manifests from disk. Source text of indexed files is served, as
+//! the snippets of `/search` already are (`get-snippet`, `definition`).
+//!
+//! The server speaks just enough HTTP/1.1 for that: one request per connection,
+//! no body, `Connection: close`.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use clap::{Args, Command, FromArgMatches};
+use rmcp::ErrorData as McpError;
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::{CallToolResult, ErrorCode};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::db::SearchDb;
+use super::mcp::{
+    CodeIndexServer, ExploreParams, GetCallersParams, SearchParams, extract_result_text,
+};
+use crate::cli::query::{QueryCommand, call_tool};
+use crate::mount::MountTable;
+
+/// Largest request head accepted; queries are short and requests have no body.
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// Serve the HTTP endpoint on `addr` until the listener fails.
+pub async fn start_http_server(
+    addr: SocketAddr,
+    db: Arc<Mutex<SearchDb>>,
+    mount_table: Arc<Mutex<MountTable>>,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind HTTP endpoint to {addr}"))?;
+    tracing::info!("HTTP endpoint listening on {}", listener.local_addr()?);
+    serve(listener, CodeIndexServer::new(db, mount_table)).await
+}
+
+/// Accept connections on `listener`, one task per connection.
+async fn serve(listener: TcpListener, server: CodeIndexServer) -> Result<()> {
+    loop {
+        let (stream, peer) = listener
+            .accept()
+            .await
+            .context("HTTP endpoint accept failed")?;
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &server).await {
+                tracing::debug!("HTTP connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, server: &CodeIndexServer) -> Result<()> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let (status, body) = loop {
+        if request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let head = String::from_utf8_lossy(&request);
+            break respond(server, head.lines().next().unwrap_or("")).await;
+        }
+        if request.len() > MAX_REQUEST_BYTES {
+            break error_response("431 Request Header Fields Too Large", "request too large");
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            // Closed before a full request head arrived
+            return Ok(());
+        }
+        request.extend_from_slice(&chunk[..n]);
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Route a request line (`GET /search?q=parse HTTP/1.1`) to its tool.
+/// Returns the status line and the JSON body.
+async fn respond(server: &CodeIndexServer, request_line: &str) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return error_response("400 Bad Request", "malformed request line");
+    };
+    if method != "GET" {
+        return error_response("405 Method Not Allowed", "only GET is supported");
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let pairs = match parse_query(query) {
+        Ok(pairs) => pairs,
+        Err(e) => return error_response("400 Bad Request", &e),
+    };
+
+    let result = match path {
+        "/search" => match parse_params::<SearchParams>(&pairs) {
+            Ok(params) => server.search(Parameters(params)).await,
+            Err(e) => return error_response("400 Bad Request", &e),
+        },
+        "/explore" => match parse_params::<ExploreParams>(&pairs) {
+            Ok(params) => server.explore(Parameters(params)).await,
+            Err(e) => return error_response("400 Bad Request", &e),
+        },
+        "/callers" => match parse_params::<GetCallersParams>(&pairs) {
+            Ok(params) => server.get_callers(Parameters(params)).await,
+            Err(e) => return error_response("400 Bad Request", &e),
+        },
+        "/query" => return query_response(server, &pairs).await,
+        _ => return error_response("404 Not Found", &format!("unknown endpoint '{path}'")),
+    };
+    tool_response(result)
+}
+
+/// Run the serialized [`QueryCommand`] in the `command` parameter.
+async fn query_response(
+    server: &CodeIndexServer,
+    pairs: &[(String, String)],
+) -> (&'static str, String) {
+    let Some((_, command)) = pairs.iter().find(|(key, _)| key == "command") else {
+        return error_response("400 Bad Request", "missing parameter 'command'");
+    };
+    let cmd: QueryCommand = match serde_json::from_str(command) {
+        Ok(cmd) => cmd,
+        Err(e) => return error_response("400 Bad Request", &format!("invalid command: {e}")),
+    };
+    if !served_over_http(&cmd) {
+        return error_response(
+            "403 Forbidden",
+            &format!("'{}' is not available over HTTP", cmd.name()),
+        );
+    }
+    match call_tool(server, cmd).await {
+        Ok(result) => (
+            "200 OK",
+            serde_json::json!({ "output": extract_result_text(&result) }).to_string(),
+        ),
+        Err(e) => tool_response(Err(e)),
+    }
+}
+
+/// Whether `/query` runs `cmd`: read-only queries on the index and the indexed files.
+fn served_over_http(cmd: &QueryCommand) -> bool {
+    match cmd {
+        QueryCommand::Search(_)
+        | QueryCommand::Docs(_)
+        | QueryCommand::Files(_)
+        | QueryCommand::Count(_)
+        | QueryCommand::Grep(_)
+        | QueryCommand::GetFileSymbols(_)
+        | QueryCommand::At(_)
+        | QueryCommand::Outline(_)
+        | QueryCommand::GetChildren(_)
+        | QueryCommand::Members(_)
+        | QueryCommand::ListSymbols(_)
+        | QueryCommand::Explore(_)
+        | QueryCommand::Tree(_)
+        | QueryCommand::GetCallers(_)
+        | QueryCommand::GetCallees(_)
+        | QueryCommand::CallTree(_)
+        | QueryCommand::FindUndocumented(_)
+        | QueryCommand::Unreferenced(_)
+        | QueryCommand::Resolve(_)
+        | QueryCommand::FindImportCycles(_)
+        | QueryCommand::UnusedImports(_)
+        | QueryCommand::Stats(_)
+        | QueryCommand::Projects(_)
+        | QueryCommand::Recent(_)
+        | QueryCommand::GetSnippet(_)
+        | QueryCommand::Definition(_) => true,
+        // Saved indexes and manifests on disk, and writes
+        QueryCommand::ApiDiff(_)
+        | QueryCommand::SymbolDiff(_)
+        | QueryCommand::Manifest(_)
+        | QueryCommand::Diff(_)
+        | QueryCommand::Reindex(_)
+        | QueryCommand::FlushIndex
+        | QueryCommand::Format { .. }
+        | QueryCommand::Exit => false,
+    }
+}
+
+fn tool_response(result: Result<CallToolResult, McpError>) -> (&'static str, String) {
+    match result {
+        Ok(result) => ("200 OK", extract_result_text(&result)),
+        Err(e) if e.code == ErrorCode::INVALID_PARAMS => {
+            error_response("400 Bad Request", &e.message)
+        }
+        Err(e) => error_response("500 Internal Server Error", &e.message),
+    }
+}
+
+fn error_response(status: &'static str, message: &str) -> (&'static str, String) {
+    (status, serde_json::json!({ "error": message }).to_string())
+}
+
+/// Split and percent-decode a query string (`q=parse+async&limit=5`).
+fn parse_query(query: &str) -> Result<Vec<(String, String)>, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((percent_decode(key)?, percent_decode(value)?))
+        })
+        .collect()
+}
+
+fn percent_decode(s: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        match b {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = tail
+                    .get(..2)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or_else(|| format!("invalid percent-encoding in '{s}'"))?;
+                bytes.push(hex);
+                rest = &tail[2..];
+                continue;
+            }
+            _ => bytes.push(b),
+        }
+        rest = tail;
+    }
+    String::from_utf8(bytes).map_err(|_| format!("'{s}' is not valid UTF-8"))
+}
+
+/// Build tool parameters from query pairs through their clap definition, so
+/// defaults and comma-separated lists behave as on the command line.
+///
+/// Keys are field names (`context_lines`) or long flags (`context-lines`, `ref-kind`);
+/// `q` is the search query. Output is always JSON, whatever `format` says.
+fn parse_params<P: Args + FromArgMatches>(pairs: &[(String, String)]) -> Result<P, String> {
+    let command = P::augment_args(Command::new("codeix").no_binary_name(true));
+    let mut argv = Vec::new();
+    let mut positionals = Vec::new();
+    for (key, value) in pairs {
+        let key = if key == "q" { "query" } else { key.as_str() };
+        let arg = command
+            .get_arguments()
+            .find(|a| {
+                a.get_id() == key
+                    || a.get_long()
+                        .is_some_and(|long| long == key || long == key.replace('_', "-"))
+            })
+            .ok_or_else(|| format!("unknown parameter '{key}'"))?;
+        if arg.get_id() == "format" {
+            continue;
+        }
+        if arg.is_positional() {
+            positionals.push((arg.get_index().unwrap_or(0), value.clone()));
+            continue;
+        }
+        let flag = match arg.get_long() {
+            Some(long) => format!("--{long}"),
+            None => format!("-{}", arg.get_short().unwrap_or_default()),
+        };
+        if arg.get_action().takes_values() {
+            argv.push(format!("{flag}={value}"));
+        } else {
+            match value.as_str() {
+                "" | "true" | "1" => argv.push(flag),
+                "false" | "0" => {}
+                _ => return Err(format!("'{key}' expects true or false, got '{value}'")),
+            }
+        }
+    }
+    if command.get_arguments().any(|a| a.get_id() == "format") {
+        argv.push("--format=json".to_string());
+    }
+    positionals.sort_by_key(|(index, _)| *index);
+    argv.push("--".to_string());
+    argv.extend(positionals.into_iter().map(|(_, value)| value));
+
+    let matches = command.try_get_matches_from(argv).map_err(|e| {
+        // First line only: the rest is usage help meant for a terminal
+        let message = e.to_string();
+        message.lines().next().unwrap_or_default().to_string()
+    })?;
+    P::from_arg_matches(&matches).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::format::FileEntry;
+    use crate::utils::format::OutputFormat;
+    use clap::Parser;
+
+    fn pairs(query: &str) -> Vec<(String, String)> {
+        parse_query(query).unwrap()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            pairs("q=parse+async&path=src%2F*.rs&empty"),
+            vec![
+                ("q".to_string(), "parse async".to_string()),
+                ("path".to_string(), "src/*.rs".to_string()),
+                ("empty".to_string(), String::new()),
+            ]
+        );
+        assert!(parse_query("q=%zz").is_err());
+        assert!(parse_query("q=%ff").is_err());
+    }
+
+    #[test]
+    fn test_parse_params() {
+        let params: SearchParams = parse_params(&pairs(
+            "q=-handler&scope=symbol,file&limit=5&whole_word=true&case_sensitive=false&format=text",
+        ))
+        .unwrap();
+        assert_eq!(params.query, "-handler");
+        assert_eq!(
+            params.scope,
+            Some(vec!["symbol".to_string(), "file".to_string()])
+        );
+        assert_eq!(params.limit, Some(5));
+        assert!(params.whole_word);
+        assert!(!params.case_sensitive);
+        assert_eq!(params.format, OutputFormat::Json);
+
+        // Long flag names work too, defaults apply to what is left out
+        let params: GetCallersParams =
+            parse_params(&pairs("name=parse&ref-kind=call&context_lines=0")).unwrap();
+        assert_eq!(params.name, "parse");
+        assert_eq!(params.reference_kind.as_deref(), Some("call"));
+        assert_eq!(params.context_lines, Some(0));
+        let params: ExploreParams = parse_params(&[]).unwrap();
+        assert_eq!(params.max_entries, 200);
+
+        assert!(parse_params::<SearchParams>(&pairs("bogus=1")).is_err());
+        assert!(parse_params::<SearchParams>(&pairs("limit=many")).is_err());
+        assert!(parse_params::<SearchParams>(&pairs("explain=maybe")).is_err());
+        assert!(parse_params::<GetCallersParams>(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_endpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/parser.rs"), "fn parse() {}\n").unwrap();
+        let db = SearchDb::new().unwrap();
+        let file = FileEntry {
+            project: String::new(),
+            path: "src/parser.rs".to_string(),
+            lang: Some("rust".to_string()),
+            hash: String::new(),
+            lines: 10,
+            title: None,
+            description: None,
+            is_test: false,
+        };
+        db.load("", &[file], &[], &[], &[]).unwrap();
+        let server = CodeIndexServer::new(
+            Arc::new(Mutex::new(db)),
+            Arc::new(Mutex::new({
+                let root = dir.path().canonicalize().unwrap();
+                let mut mount_table = MountTable::new(root.clone());
+                mount_table.mount_ro(&root).unwrap();
+                mount_table
+            })),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, server));
+
+        let get = |request: &'static str| async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status = head.lines().next().unwrap().to_string();
+            (
+                status,
+                serde_json::from_str::<serde_json::Value>(body).unwrap(),
+            )
+        };
+
+        let (status, body) = get("GET /search?q=parser&scope=file HTTP/1.1\r\n\r\n").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body[0]["type"], "file");
+        assert_eq!(body[0]["path"], "src/parser.rs");
+
+        let (status, body) = get("GET /search?nope=1 HTTP/1.1\r\n\r\n").await;
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        assert_eq!(body["error"], "unknown parameter 'nope'");
+
+        let (status, _) = get("GET /reindex HTTP/1.1\r\n\r\n").await;
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        let (status, _) = get("POST /search?q=parser HTTP/1.1\r\n\r\n").await;
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+
+        // `query --connect` runs any read-only command, in its own output format
+        let remote = |words: &'static [&'static str]| {
+            let cmd =
+                QueryCommand::try_parse_from(std::iter::once("").chain(words.iter().copied()))
+                    .unwrap();
+            tokio::task::spawn_blocking(move || crate::cli::remote::call(addr, &cmd))
+        };
+        let output = remote(&["files", "parser", "--format", "text"])
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(output.contains("src/parser.rs"));
+        assert!(!output.trim_start().starts_with('['));
+        let output = remote(&["stats", "--format", "json"])
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&output).is_ok());
+        let err = remote(&["flush-index"]).await.unwrap().unwrap_err();
+        assert_eq!(err, "'flush_index' is not available over HTTP");
+        let err = remote(&["symbol-diff", "HEAD"]).await.unwrap().unwrap_err();
+        assert_eq!(err, "'symbol_diff' is not available over HTTP");
+        // Source of indexed files is served, like search snippets
+        let output = remote(&["get-snippet", "src/parser.rs", "1", "1"])
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(output.contains("fn parse()"));
+        assert!(crate::cli::remote::is_reachable(addr));
+    }
+}
@@ -0,0 +1,421 @@
+//! File-level import graph: resolving import references to indexed files,
+//! finding circular dependencies, and the names an import binds in its file.
+//!
+//! Import references store the specifier as written (`./utils.helper`,
+//! `pkg.mod.name`, `.sibling.func`, `myheader.h`), except Rust `mod foo;`
+//! declarations, which the parser already turns into the expected file path
+//! (`src/parser/foo.rs`). Resolution is best-effort:
+//! only specifiers that map onto a file of the same project become edges.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Extensions tried for extension-less JS/TS specifiers (`./foo` → `./foo.ts`).
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts"];
+
+/// Resolve an import reference name to an indexed file of the same project.
+///
+/// `files` holds the project's file paths (relative to the project root).
+/// Returns `None` for external packages or specifiers we can't map.
+pub fn resolve_import(name: &str, from_file: &str, files: &HashSet<String>) -> Option<String> {
+    let from_dir = from_file.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+
+    if name.starts_with("./") || name.starts_with("../") {
+        return resolve_js_relative(name, from_dir, files);
+    }
+    if name.starts_with('.') {
+        return resolve_python_relative(name, from_dir, files);
+    }
+    if from_file.ends_with(".rs") && name.ends_with(".rs") {
+        // `mod foo;`: `foo.rs`, or the older `foo/mod.rs` layout
+        let path = join_path("", name)?;
+        let mod_rs = format!("{}/mod.rs", path.strip_suffix(".rs").unwrap_or(&path));
+        return [path, mod_rs].into_iter().find(|p| files.contains(p));
+    }
+    if name.contains('/') || has_source_extension(name) {
+        // Path-like includes (`#include "util/log.h"`): relative to the file, then anywhere
+        return join_path(from_dir, name)
+            .filter(|p| files.contains(p))
+            .or_else(|| resolve_by_suffix(name, files));
+    }
+
+    // Dotted module path (Python, Java, ...): try longest prefix first,
+    // since trailing segments may be imported names rather than modules
+    let segments: Vec<&str> = name.split('.').filter(|s| *s != "*").collect();
+    for len in (1..=segments.len()).rev() {
+        let module = segments[..len].join("/");
+        for candidate in [format!("{module}.py"), format!("{module}/__init__.py")] {
+            if files.contains(&candidate) {
+                return Some(candidate);
+            }
+            if let Some(found) = resolve_by_suffix(&candidate, files) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// `./utils.helper` → `utils.ts`: the specifier may carry a trailing `.name`
+/// for named imports, so strip dotted segments until a file matches.
+fn resolve_js_relative(name: &str, from_dir: &str, files: &HashSet<String>) -> Option<String> {
+    let mut spec = name.trim_end_matches(".*");
+    loop {
+        if let Some(base) = join_path(from_dir, spec) {
+            if files.contains(&base) {
+                return Some(base);
+            }
+            for ext in JS_EXTENSIONS {
+                let candidate = format!("{base}.{ext}");
+                if files.contains(&candidate) {
+                    return Some(candidate);
+                }
+                let index = format!("{base}/index.{ext}");
+                if files.contains(&index) {
+                    return Some(index);
+                }
+            }
+        }
+        let (rest, last) = spec.rsplit_once('.')?;
+        // Don't strip into the `./` or `../` prefix itself
+        if rest.is_empty() || rest.ends_with('/') || last.contains('/') {
+            return None;
+        }
+        spec = rest;
+    }
+}
+
+/// `.sibling.func` / `..pkg.mod` → path relative to the importing package.
+///
+/// Each leading dot is one package level, as in Python: `from . import x` is
+/// stored as `.x` (the importing package), `from .. import x` as `..x` (its parent).
+fn resolve_python_relative(name: &str, from_dir: &str, files: &HashSet<String>) -> Option<String> {
+    let dots = name.chars().take_while(|c| *c == '.').count();
+    let rest = &name[dots..];
+    let mut base = from_dir.to_string();
+    for _ in 1..dots {
+        base = base.rsplit_once('/').map(|(d, _)| d.to_string())?;
+        if base.is_empty() {
+            return None;
+        }
+    }
+    let segments: Vec<&str> = rest.split('.').filter(|s| !s.is_empty()).collect();
+    for len in (0..=segments.len()).rev() {
+        let module = segments[..len].join("/");
+        let stem = match (base.is_empty(), module.is_empty()) {
+            (_, true) => base.clone(),
+            (true, false) => module,
+            (false, false) => format!("{base}/{module}"),
+        };
+        let candidates = if len == 0 {
+            vec![format!("{stem}/__init__.py")]
+        } else {
+            vec![format!("{stem}.py"), format!("{stem}/__init__.py")]
+        };
+        for candidate in candidates {
+            let candidate = candidate.trim_start_matches('/').to_string();
+            if files.contains(&candidate) {
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+/// Unique file whose path ends with `/suffix` (or equals it).
+fn resolve_by_suffix(suffix: &str, files: &HashSet<String>) -> Option<String> {
+    let needle = format!("/{suffix}");
+    let mut matches = files
+        .iter()
+        .filter(|f| f.as_str() == suffix || f.ends_with(&needle));
+    let first = matches.next()?;
+    if matches.next().is_some() {
+        return None; // ambiguous
+    }
+    Some(first.clone())
+}
+
+fn has_source_extension(name: &str) -> bool {
+    matches!(
+        name.rsplit_once('.').map(|(_, ext)| ext),
+        Some("h" | "hpp" | "hxx" | "hh" | "c" | "cc" | "cpp" | "inc")
+    )
+}
+
+/// Join a relative specifier onto a directory, normalizing `.` and `..`.
+/// Returns `None` if the path escapes the project root.
+fn join_path(dir: &str, spec: &str) -> Option<String> {
+    let mut parts: Vec<&str> = dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in spec.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            p => parts.push(p),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Strongly-connected components with more than one file (i.e. import cycles).
+///
+/// Each cycle is sorted, and cycles are ordered by their first file, so output
+/// is deterministic.
+pub fn find_cycles(edges: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<String>> {
+    // Iterative Tarjan's algorithm (import graphs can be deep)
+    let mut nodes: BTreeSet<&str> = BTreeSet::new();
+    for (from, tos) in edges {
+        nodes.insert(from);
+        nodes.extend(tos.iter().map(String::as_str));
+    }
+    let index_of: BTreeMap<&str, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let names: Vec<&str> = nodes.into_iter().collect();
+    let adjacency: Vec<Vec<usize>> = names
+        .iter()
+        .map(|n| {
+            edges
+                .get(*n)
+                .map(|tos| tos.iter().map(|t| index_of[t.as_str()]).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let n = names.len();
+    let mut index = vec![usize::MAX; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut cycles = Vec::new();
+
+    for start in 0..n {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        // (node, next child position)
+        let mut work = vec![(start, 0usize)];
+        index[start] = next_index;
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some(&mut (v, ref mut child)) = work.last_mut() {
+            if let Some(&w) = adjacency[v].get(*child) {
+                *child += 1;
+                if index[w] == usize::MAX {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(names[w].to_string());
+                    if w == v {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    cycles.push(component);
+                }
+            }
+        }
+    }
+
+    cycles.sort();
+    cycles
+}
+
+/// Names an import symbol brings into scope: its alias if it has one,
+/// otherwise the first and last segments of the specifier (`os.path` binds
+/// `os`, `utils.dump` binds `dump`, `github.com/a/yaml` binds `yaml`).
+///
+/// Returns nothing for imports whose bindings can't be told from the name:
+/// globs without alias, grouped Rust `use` lists, `#include`s and Go's
+/// side-effect (`_`) and dot imports.
+pub fn import_bindings<'a>(name: &'a str, alias: Option<&'a str>) -> Vec<&'a str> {
+    if let Some(alias) = alias {
+        return if matches!(alias, "_" | ".") {
+            Vec::new()
+        } else {
+            vec![alias]
+        };
+    }
+    if name.contains('{') || name.ends_with('*') || has_source_extension(name) {
+        return Vec::new();
+    }
+    let segments: Vec<&str> = name.split(['.', '/', ':', '\\']).collect();
+    let is_ident = |s: &&str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    };
+    let mut bindings = Vec::new();
+    // Relative specifiers (`./util.foo`, `.models.User`) bind their imported name only
+    if !name.starts_with('.')
+        && let Some(first) = segments.first().copied().filter(is_ident)
+    {
+        bindings.push(first);
+    }
+    if let Some(last) = segments.last().copied().filter(is_ident)
+        && !bindings.contains(&last)
+    {
+        bindings.push(last);
+    }
+    bindings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_set(paths: &[&str]) -> HashSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_import_specifiers() {
+        let files = file_set(&[
+            "src/app.ts",
+            "src/utils.ts",
+            "src/components/index.tsx",
+            "pkg/__init__.py",
+            "pkg/models.py",
+            "pkg/sub/views.py",
+            "include/log.h",
+            "src/lib.rs",
+            "src/parser.rs",
+            "src/parser/mod.rs",
+            "src/server/mod.rs",
+        ]);
+
+        // JS/TS relative, with named-import suffix and index files
+        assert_eq!(
+            resolve_import("./utils.helper", "src/app.ts", &files),
+            Some("src/utils.ts".to_string())
+        );
+        assert_eq!(
+            resolve_import("./components.*", "src/app.ts", &files),
+            Some("src/components/index.tsx".to_string())
+        );
+        assert_eq!(resolve_import("./missing.x", "src/app.ts", &files), None);
+        assert_eq!(resolve_import("react.useState", "src/app.ts", &files), None);
+
+        // Python absolute and relative
+        assert_eq!(
+            resolve_import("pkg.models.User", "pkg/sub/views.py", &files),
+            Some("pkg/models.py".to_string())
+        );
+        assert_eq!(
+            resolve_import("..models.User", "pkg/sub/views.py", &files),
+            Some("pkg/models.py".to_string())
+        );
+        assert_eq!(
+            resolve_import(".views.index", "pkg/sub/other.py", &files),
+            Some("pkg/sub/views.py".to_string())
+        );
+        assert_eq!(
+            resolve_import("os.path.join", "pkg/models.py", &files),
+            None
+        );
+
+        // `from .. import x` is exactly one package up, never the importing one
+        let nested = file_set(&["pkg/__init__.py", "pkg/sub/views.py", "pkg/sub/x.py"]);
+        assert_eq!(
+            resolve_import("..x", "pkg/sub/views.py", &nested),
+            Some("pkg/__init__.py".to_string())
+        );
+        assert_eq!(
+            resolve_import(".x", "pkg/sub/views.py", &nested),
+            Some("pkg/sub/x.py".to_string())
+        );
+        let with_parent = file_set(&["pkg/x.py", "pkg/sub/views.py", "pkg/sub/x.py"]);
+        assert_eq!(
+            resolve_import("..x", "pkg/sub/views.py", &with_parent),
+            Some("pkg/x.py".to_string())
+        );
+
+        // Path-like includes
+        assert_eq!(
+            resolve_import("log.h", "src/main.c", &files),
+            Some("include/log.h".to_string())
+        );
+
+        // Rust `mod` declarations: `foo.rs` before `foo/mod.rs`
+        assert_eq!(
+            resolve_import("src/parser.rs", "src/lib.rs", &files),
+            Some("src/parser.rs".to_string())
+        );
+        assert_eq!(
+            resolve_import("src/server.rs", "src/lib.rs", &files),
+            Some("src/server/mod.rs".to_string())
+        );
+        assert_eq!(
+            resolve_import("src/server/../gen/out.rs", "src/lib.rs", &files),
+            None
+        );
+    }
+
+    #[test]
+    fn test_import_bindings() {
+        assert_eq!(import_bindings("os.path", None), vec!["os", "path"]);
+        assert_eq!(import_bindings("os.path", Some("osp")), vec!["osp"]);
+        assert_eq!(import_bindings("json", None), vec!["json"]);
+        assert_eq!(import_bindings(".models.User", None), vec!["User"]);
+        assert_eq!(import_bindings("./util.foo", None), vec!["foo"]);
+        assert_eq!(
+            import_bindings("std::collections::HashMap", None),
+            vec!["std", "HashMap"]
+        );
+        assert_eq!(
+            import_bindings("github.com/a/yaml", None),
+            vec!["github", "yaml"]
+        );
+        assert!(import_bindings("./util.*", None).is_empty());
+        assert!(import_bindings("helpers.*", None).is_empty());
+        assert!(import_bindings("embed", Some("_")).is_empty());
+        assert!(import_bindings("crate::a::{b, c as d}", None).is_empty());
+        assert!(import_bindings("stdio.h", None).is_empty());
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let mut edges: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut edge = |a: &str, b: &str| {
+            edges
+                .entry(a.to_string())
+                .or_default()
+                .insert(b.to_string());
+        };
+        edge("a.py", "b.py");
+        edge("b.py", "c.py");
+        edge("c.py", "a.py");
+        edge("c.py", "d.py");
+        edge("x.ts", "y.ts");
+        edge("y.ts", "x.ts");
+        edge("d.py", "e.py");
+
+        assert_eq!(
+            find_cycles(&edges),
+            vec![
+                vec!["a.py".to_string(), "b.py".to_string(), "c.py".to_string()],
+                vec!["x.ts".to_string(), "y.ts".to_string()],
+            ]
+        );
+    }
+}
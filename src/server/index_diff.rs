@@ -0,0 +1,160 @@
+//! Comparison of a project's live index against its `.codeindex/` on disk.
+//!
+//! Files are keyed by path, symbols by `(file, name, kind, line)`. A file on
+//! both sides whose set of symbols differs is reported as modified, which is
+//! what a `flush_index` would rewrite.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::index::format::{FileEntry, SymbolEntry};
+
+/// A file present on both sides whose symbols differ.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModifiedFile {
+    pub path: String,
+    /// Symbols in the live index only
+    pub added_symbols: usize,
+    /// Symbols on disk only
+    pub removed_symbols: usize,
+}
+
+/// Differences between the index on disk and the live database for one project.
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct IndexDiff {
+    /// Relative path from the workspace root ("" for the root project)
+    pub project: String,
+    /// Files in the live index only
+    pub added: Vec<String>,
+    /// Files on disk only
+    pub removed: Vec<String>,
+    pub modified: Vec<ModifiedFile>,
+}
+
+impl IndexDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+type SymbolKey<'a> = (&'a str, &'a str, [u32; 2]);
+
+/// Group symbols by file, keyed by `(name, kind, line)`.
+fn symbols_by_file(symbols: &[SymbolEntry]) -> BTreeMap<&str, BTreeSet<SymbolKey<'_>>> {
+    let mut by_file: BTreeMap<&str, BTreeSet<SymbolKey<'_>>> = BTreeMap::new();
+    for symbol in symbols {
+        by_file
+            .entry(&symbol.file)
+            .or_default()
+            .insert((&symbol.name, &symbol.kind, symbol.line));
+    }
+    by_file
+}
+
+/// Diff the index on disk (`disk_*`) against the live one (`live_*`) of `project`.
+pub fn diff_index(
+    project: &str,
+    disk_files: &[FileEntry],
+    disk_symbols: &[SymbolEntry],
+    live_files: &[FileEntry],
+    live_symbols: &[SymbolEntry],
+) -> IndexDiff {
+    let disk_paths: BTreeSet<&str> = disk_files.iter().map(|f| f.path.as_str()).collect();
+    let live_paths: BTreeSet<&str> = live_files.iter().map(|f| f.path.as_str()).collect();
+    let disk_symbols = symbols_by_file(disk_symbols);
+    let live_symbols = symbols_by_file(live_symbols);
+    let empty = BTreeSet::new();
+
+    let mut diff = IndexDiff {
+        project: project.to_string(),
+        added: live_paths
+            .difference(&disk_paths)
+            .map(|p| p.to_string())
+            .collect(),
+        removed: disk_paths
+            .difference(&live_paths)
+            .map(|p| p.to_string())
+            .collect(),
+        modified: Vec::new(),
+    };
+    for path in disk_paths.intersection(&live_paths) {
+        let before = disk_symbols.get(path).unwrap_or(&empty);
+        let after = live_symbols.get(path).unwrap_or(&empty);
+        let added_symbols = after.difference(before).count();
+        let removed_symbols = before.difference(after).count();
+        if added_symbols > 0 || removed_symbols > 0 {
+            diff.modified.push(ModifiedFile {
+                path: path.to_string(),
+                added_symbols,
+                removed_symbols,
+            });
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> FileEntry {
+        FileEntry {
+            path: path.to_string(),
+            lang: Some("rust".to_string()),
+            hash: String::new(),
+            lines: 10,
+            project: String::new(),
+            title: None,
+            description: None,
+            is_test: false,
+        }
+    }
+
+    fn symbol(file: &str, name: &str, line: u32) -> SymbolEntry {
+        SymbolEntry {
+            file: file.to_string(),
+            name: name.to_string(),
+            kind: "function".to_string(),
+            line: [line, line + 2],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: None,
+            project: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_index() {
+        let disk_files = vec![file("a.rs"), file("b.rs"), file("old.rs")];
+        let disk_symbols = vec![
+            symbol("a.rs", "keep", 1),
+            symbol("b.rs", "moved", 1),
+            symbol("b.rs", "gone", 5),
+            symbol("old.rs", "old", 1),
+        ];
+        let live_files = vec![file("a.rs"), file("b.rs"), file("new.rs")];
+        let live_symbols = vec![
+            symbol("a.rs", "keep", 1),
+            symbol("b.rs", "moved", 3),
+            symbol("new.rs", "new", 1),
+        ];
+
+        let diff = diff_index("", &disk_files, &disk_symbols, &live_files, &live_symbols);
+        assert_eq!(diff.added, vec!["new.rs"]);
+        assert_eq!(diff.removed, vec!["old.rs"]);
+        // A symbol whose lines moved counts as removed and added
+        assert_eq!(
+            diff.modified,
+            vec![ModifiedFile {
+                path: "b.rs".to_string(),
+                added_symbols: 1,
+                removed_symbols: 2,
+            }]
+        );
+
+        assert!(diff_index("", &disk_files, &disk_symbols, &disk_files, &disk_symbols).is_empty());
+    }
+}
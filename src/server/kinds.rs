@@ -0,0 +1,107 @@
+//! Synonyms accepted in `kind` filters.
+//!
+//! The index stores one canonical name per kind (`function`, `class`,
+//! `interface`, ...; see the kind tables in `docs/architecture.md`), but
+//! callers often write `fn`, `func` or `cls`. Filters map such synonyms to the
+//! canonical name before querying, so they match instead of returning nothing.
+//! Unknown names are passed through unchanged.
+
+/// `(synonym, canonical kind)` pairs, matched case-insensitively.
+const KIND_ALIASES: &[(&str, &str)] = &[
+    // Symbol kinds
+    ("fn", "function"),
+    ("func", "function"),
+    ("fun", "function"),
+    ("def", "function"),
+    ("procedure", "function"),
+    ("meth", "method"),
+    ("cls", "class"),
+    ("iface", "interface"),
+    ("trait", "interface"),
+    ("protocol", "interface"),
+    ("var", "variable"),
+    ("let", "variable"),
+    ("const", "constant"),
+    ("field", "property"),
+    ("member", "property"),
+    ("prop", "property"),
+    ("mod", "module"),
+    ("namespace", "module"),
+    ("package", "module"),
+    ("enumeration", "enum"),
+    ("heading", "section"),
+    // Text kinds
+    ("doc", "docstring"),
+    ("str", "string"),
+    ("build_constraint", "build_tag"),
+    // Reference kinds
+    ("calls", "call"),
+    ("imports", "import"),
+];
+
+/// Canonical name of a symbol, text or reference kind.
+///
+/// `"fn"`, `"Func"` and `"function"` all give `"function"`; a name that is not
+/// a known synonym is returned as given.
+pub fn canonical_kind(kind: &str) -> String {
+    KIND_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(kind))
+        .map_or_else(|| kind.to_string(), |(_, canonical)| canonical.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_kind() {
+        assert_eq!(canonical_kind("fn"), "function");
+        assert_eq!(canonical_kind("FUNC"), "function");
+        assert_eq!(canonical_kind("cls"), "class");
+        assert_eq!(canonical_kind("iface"), "interface");
+        assert_eq!(canonical_kind("var"), "variable");
+        assert_eq!(canonical_kind("calls"), "call");
+
+        // Canonical and unknown names pass through unchanged
+        assert_eq!(canonical_kind("function"), "function");
+        assert_eq!(canonical_kind("todo"), "todo");
+        assert_eq!(canonical_kind("Widget"), "Widget");
+    }
+
+    #[test]
+    fn test_aliases_are_not_canonical_kinds() {
+        // A synonym must never shadow a kind the parsers emit
+        for canonical in [
+            "function",
+            "method",
+            "class",
+            "struct",
+            "interface",
+            "enum",
+            "variant",
+            "constant",
+            "variable",
+            "property",
+            "module",
+            "object",
+            "import",
+            "impl",
+            "section",
+            "docstring",
+            "comment",
+            "string",
+            "build_tag",
+            "generate",
+            "call",
+            "type_annotation",
+            "instantiation",
+            "attribute",
+        ] {
+            assert!(
+                !KIND_ALIASES.iter().any(|(alias, _)| *alias == canonical),
+                "{canonical} is both a kind and an alias"
+            );
+        }
+    }
+}
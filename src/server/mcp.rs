@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::Result;
 use clap::Args;
@@ -17,16 +19,28 @@ use rmcp::{
     tool, tool_handler, tool_router,
     transport::stdio,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::db::{SearchDb, SearchResult};
-use super::snippet::SnippetExtractor;
+use super::api_diff::diff_api;
+use super::db::{
+    RefFilters, SearchDb, SearchFilters, SearchOrder, SearchQuery, SearchResult, group_by_project,
+};
+use super::snippet::{SnippetExtractor, SourceSnippet, read_line_range};
 use crate::index::format::{SymbolEntry, SymbolOutput};
+use crate::index::reader::read_index;
 use crate::mount::MountTable;
-use crate::mount::handler::flush_dirty_mounts;
+use crate::mount::handler::{
+    diff_mount_with_disk, flush_dirty_mounts, live_manifest, reindex_project,
+};
 use crate::utils::format::{
-    EnrichedSearchResult, ExploreResult, OutputFormat, ReferenceWithSnippet, SymbolWithSnippet,
-    format_explore, format_references, format_search_results, format_symbols,
+    CallTreeNode, Definition, EnrichedSearchResult, ExplainedSearchResult, ExploreResult,
+    ImportCycle, MountedProject, OutputFormat, ReferenceWithSnippet, ScoredSymbol, SearchGroup,
+    SymbolDiff, SymbolWithSnippet, format_api_diff, format_call_tree, format_definition,
+    format_explained_search_results, format_explore, format_explore_tree,
+    format_grouped_search_results, format_import_cycles, format_index_diffs, format_manifest,
+    format_outline, format_projects, format_recent_files, format_references, format_scored_symbols,
+    format_search_results, format_source_snippet, format_stats, format_symbol_at,
+    format_symbol_diff, format_symbols, format_texts,
 };
 use crate::utils::manifest;
 
@@ -39,31 +53,165 @@ fn normalize_context_lines(value: Option<i32>) -> i32 {
     }
 }
 
+/// Result types a search `scope` can name.
+pub const SEARCH_SCOPES: &[&str] = &["symbol", "file", "text"];
+
+/// Reject scope values that name no result type (they would match nothing).
+fn check_scope(scope: Option<&[String]>) -> Result<(), McpError> {
+    if let Some(unknown) = scope
+        .unwrap_or_default()
+        .iter()
+        .find(|s| !SEARCH_SCOPES.contains(&s.as_str()))
+    {
+        return Err(McpError::invalid_params(
+            format!(
+                "unknown scope '{unknown}' (expected one of: {})",
+                SEARCH_SCOPES.join(", ")
+            ),
+            None,
+        ));
+    }
+    Ok(())
+}
+
+/// Values a tool call filters on: a single-value parameter (`project`) plus its
+/// list form (`projects`), deduplicated (empty = all).
+fn merge_filter(one: Option<String>, many: Option<Vec<String>>) -> Vec<String> {
+    let mut filter: Vec<String> = one.into_iter().collect();
+    for p in many.unwrap_or_default() {
+        if !filter.contains(&p) {
+            filter.push(p);
+        }
+    }
+    filter
+}
+
+/// Named shortcut for a common combination of search filters.
+///
+/// - `api`: scope=symbol, visibility=public — the public API surface
+/// - `all`: every scope, visibility=private — no visibility filtering
+/// - `docs`: scope=text, kind=docstring,comment — prose about the code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchPreset {
+    pub scope: &'static [&'static str],
+    pub kind: &'static [&'static str],
+    pub visibility: Option<&'static str>,
+}
+
+impl SearchPreset {
+    /// Preset names accepted by `search`.
+    pub const NAMES: &'static [&'static str] = &["api", "all", "docs"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "api" => Some(Self {
+                scope: &["symbol"],
+                kind: &[],
+                visibility: Some("public"),
+            }),
+            "all" => Some(Self {
+                scope: &[],
+                kind: &[],
+                visibility: Some("private"),
+            }),
+            "docs" => Some(Self {
+                scope: &["text"],
+                kind: &["docstring", "comment"],
+                visibility: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
 // Parameter structs for each tool - shared between MCP and REPL
 // NOTE: When adding/removing/renaming tools, also update src/cli/query.rs (QueryCommand enum)
 
 /// Parameters for the unified search tool.
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct SearchParams {
-    /// Search query (FTS5 syntax, supports * wildcards)
+    /// Search query (FTS5 syntax, supports * wildcards). Empty lists everything matching the filters.
+    #[arg(default_value = "")]
+    #[serde(default)]
     pub query: String,
+    /// Filter preset: "api" (public symbols), "all" (everything, no visibility filter),
+    /// "docs" (docstrings and comments). Explicit scope/kind/visibility override it.
+    #[arg(long)]
+    pub preset: Option<String>,
     /// Scope: types to search. Comma-separated: "symbol", "file", "text". Default: all.
     #[arg(short, long, value_delimiter = ',')]
     pub scope: Option<Vec<String>>,
     /// Filter by kind (symbol kind, text kind, or file language). Comma-separated for multiple.
+    /// Synonyms map to the stored kind: "fn"/"func" → "function", "cls" → "class", "iface" → "interface".
     #[arg(short, long, value_delimiter = ',')]
     pub kind: Option<Vec<String>>,
+    /// Filter by language of the containing file (e.g. "python"). Comma-separated for multiple.
+    #[arg(long, value_delimiter = ',')]
+    pub lang: Option<Vec<String>>,
     /// Filter by file path. Supports glob patterns with * (e.g. "src/*.py")
     #[arg(short = 'f', long)]
     pub path: Option<String>,
+    /// Drop results whose file path matches any of these glob patterns (`*` also matches `/`,
+    /// e.g. "*tests/*", "*/generated/*"). Comma-separated for multiple.
+    #[arg(short = 'x', long, value_delimiter = ',')]
+    pub exclude_paths: Option<Vec<String>>,
+    /// Include results from files that look like tests (`tests/`, `*_test.go`, `*.spec.ts`,
+    /// `test_*.py`, ...). Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub include_tests: bool,
     /// Filter by project (relative path from workspace root, e.g. "libs/utils")
     #[arg(short, long)]
     pub project: Option<String>,
+    /// Filter by several projects at once (e.g. ["api", "web"]), combined with `project`. Comma-separated on the CLI.
+    #[arg(long, value_delimiter = ',')]
+    pub projects: Option<Vec<String>>,
     /// Minimum visibility level: "public" (default), "internal", or "private".
     /// Hierarchical filter: public > internal > private.
     /// Example: visibility="internal" returns public AND internal symbols.
     #[arg(short = 'v', long)]
     pub visibility: Option<String>,
+    /// Match query terms with exact casing (`Parser` does not match `parser`). Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Match bare terms as whole identifiers (`map` does not match `unordered_map`). Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Keep only symbols/files whose name matches this regex (e.g. "^handle_.*_event$").
+    /// Tried against the full and the base name. With an empty query, scans all symbols.
+    #[arg(long)]
+    pub name_regex: Option<String>,
+    /// BM25 weight of the name column (default: 3.0). Must be non-negative.
+    #[arg(long)]
+    pub name_weight: Option<f64>,
+    /// BM25 weight of the file path column (default: 2.0). Must be non-negative.
+    #[arg(long)]
+    pub file_weight: Option<f64>,
+    /// BM25 weight of the content column — tokens, docstrings, comments (default: 1.0).
+    /// Must be non-negative. Raise it to favour implementation details over names.
+    #[arg(long)]
+    pub content_weight: Option<f64>,
+    /// Group results by project, projects ordered by their best hit. Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub group: bool,
+    /// Explain each match: BM25 score per column and the matched content fragment. Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub explain: bool,
+    /// Match symbol names within a few typos of the query (`authetnicate` finds
+    /// `authenticate`), closest first. Searches symbols only. Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub fuzzy: bool,
+    /// Result order: "relevance" (default, BM25 rank; closest first with fuzzy), "path"
+    /// (file path, then line), "name" (case-insensitive), or "line". The last three are stable
+    /// across runs, e.g. for outlines or snapshot tests.
+    #[arg(long, default_value = "relevance")]
+    #[serde(default)]
+    pub order_by: SearchOrder,
     /// Maximum number of results to return (default: 100)
     #[arg(short, long)]
     pub limit: Option<u32>,
@@ -80,10 +228,77 @@ pub struct SearchParams {
     pub format: OutputFormat,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+/// Parameters for the count tool (same filters as search, no pagination).
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct CountParams {
+    /// Search query (FTS5 syntax, supports * wildcards). Empty counts everything matching the filters.
+    #[arg(default_value = "")]
+    #[serde(default)]
+    pub query: String,
+    /// Scope: types to count. Comma-separated: "symbol", "file", "text". Default: all.
+    #[arg(short, long, value_delimiter = ',')]
+    pub scope: Option<Vec<String>>,
+    /// Filter by kind (symbol kind, text kind, or file language). Comma-separated for multiple.
+    /// Synonyms map to the stored kind: "fn"/"func" → "function", "cls" → "class", "iface" → "interface".
+    #[arg(short, long, value_delimiter = ',')]
+    pub kind: Option<Vec<String>>,
+    /// Filter by language of the containing file (e.g. "python"). Comma-separated for multiple.
+    #[arg(long, value_delimiter = ',')]
+    pub lang: Option<Vec<String>>,
+    /// Filter by file path. Supports glob patterns with * (e.g. "src/*.py")
+    #[arg(short = 'f', long)]
+    pub path: Option<String>,
+    /// Drop results whose file path matches any of these glob patterns (`*` also matches `/`,
+    /// e.g. "*tests/*", "*/generated/*"). Comma-separated for multiple.
+    #[arg(short = 'x', long, value_delimiter = ',')]
+    pub exclude_paths: Option<Vec<String>>,
+    /// Include results from files that look like tests (`tests/`, `*_test.go`, `*.spec.ts`,
+    /// `test_*.py`, ...). Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub include_tests: bool,
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Filter by several projects at once (e.g. ["api", "web"]), combined with `project`. Comma-separated on the CLI.
+    #[arg(long, value_delimiter = ',')]
+    pub projects: Option<Vec<String>>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+}
+
+/// Parameters for the grep tool (literal substring match over texts).
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct GrepParams {
+    /// Substring to find, matched literally (no FTS tokenization, no wildcards)
+    pub pattern: String,
+    /// Filter by text kind (e.g. "comment", "string", "docstring")
+    #[arg(short, long)]
+    pub kind: Option<String>,
+    /// Filter by file path. Supports glob patterns with * (e.g. "src/*.py")
+    #[arg(short = 'f', long)]
+    pub path: Option<String>,
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Maximum number of results to return (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct GetFileSymbolsParams {
     /// File path to get symbols for
     pub file: String,
+    /// Project the file belongs to (relative path from workspace root, e.g. "libs/utils").
+    /// Omit to match the path in every project.
+    #[arg(short, long)]
+    pub project: Option<String>,
     /// Minimum visibility level: "public" (default), "internal", or "private".
     /// Hierarchical filter: public > internal > private.
     /// Example: visibility="internal" returns public AND internal symbols.
@@ -105,12 +320,68 @@ pub struct GetFileSymbolsParams {
     pub format: OutputFormat,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct GetSnippetParams {
+    /// File path (relative to the project root)
+    pub file: String,
+    /// First line to return (1-based, inclusive)
+    pub line_start: u32,
+    /// Last line to return (inclusive). Clamped to the file; at most 500 lines are returned
+    pub line_end: u32,
+    /// Project (relative path from workspace root). Default: the root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct AtParams {
+    /// File path (relative to the project root)
+    pub file: String,
+    /// Line number (1-based)
+    pub line: u32,
+    /// Project (relative path from workspace root). Default: the root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Lines of code context (recommended: 10). 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct OutlineParams {
+    /// File path to outline
+    pub file: String,
+    /// Project the file belongs to (relative path from workspace root, e.g. "libs/utils").
+    /// Omit to match the path in every project.
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct GetChildrenParams {
     /// File path containing the parent symbol
     pub file: String,
     /// Name of the parent symbol
     pub parent: String,
+    /// Project the file belongs to (relative path from workspace root, e.g. "libs/utils").
+    /// Omit to match the path in every project.
+    #[arg(short, long)]
+    pub project: Option<String>,
     /// Minimum visibility level: "public" (default), "internal", or "private".
     /// Hierarchical filter: public > internal > private.
     /// Example: visibility="internal" returns public AND internal symbols.
@@ -132,22 +403,39 @@ pub struct GetChildrenParams {
     pub format: OutputFormat,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct GetCallersParams {
     /// Symbol name to find callers for (e.g. "my_function", "MyClass.method")
     pub name: String,
-    /// Filter by reference kind (e.g. "call", "import", "type_annotation").
+    /// Filter by reference kind (e.g. "call", "import", "type_annotation", "decorator", "annotation").
     /// Note: This filters the type of reference, not the symbol kind.
     #[arg(short = 'k', long = "ref-kind")]
     pub reference_kind: Option<String>,
+    /// Reference kinds to keep, on top of `reference_kind` (e.g. ["call", "instantiation"]
+    /// to leave out imports and type annotations)
+    #[arg(long = "ref-kinds", value_delimiter = ',')]
+    pub reference_kinds: Option<Vec<String>>,
     /// Filter by project (relative path from workspace root, e.g. "libs/utils")
     #[arg(short, long)]
     pub project: Option<String>,
+    /// Filter by several projects at once (e.g. ["api", "web"]), combined with `project`. Comma-separated on the CLI.
+    #[arg(long, value_delimiter = ',')]
+    pub projects: Option<Vec<String>>,
     /// Minimum visibility level of the target symbol: "public", "internal", or "private" (default).
     /// Hierarchical filter: public > internal > private.
     /// Example: visibility="public" returns only callers of public symbols.
     #[arg(short = 'v', long)]
     pub visibility: Option<String>,
+    /// Collapse references with the same file, caller and name into one (the first line),
+    /// so a function calling the target twenty times is listed once. Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub distinct_by_caller: bool,
+    /// Resolve each reference to the definition of the symbol it names and add
+    /// def_file/def_line (plus def_project when it lives in another project). Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub with_definition: bool,
     /// Maximum number of results to return (default: 100)
     #[arg(short, long)]
     pub limit: Option<u32>,
@@ -164,14 +452,41 @@ pub struct GetCallersParams {
     pub format: OutputFormat,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct CallTreeParams {
+    /// Symbol name to trace callers of (e.g. "my_function", "MyClass.method")
+    pub name: String,
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Maximum number of caller levels to follow (default: 3)
+    #[arg(short = 'd', long)]
+    pub max_depth: Option<u32>,
+    /// Maximum number of references to return across all levels (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Lines of code context per result (recommended: 10). Provides type info, docs, and surrounding code.
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct GetCalleesParams {
     /// Symbol name to find callees for (e.g. "my_function", "MyClass.method")
     pub caller: String,
-    /// Filter by reference kind (e.g. "call", "import", "type_annotation").
+    /// Filter by reference kind (e.g. "call", "import", "type_annotation", "decorator", "annotation").
     /// Note: This filters the type of reference, not the symbol kind.
     #[arg(short = 'k', long = "ref-kind")]
     pub reference_kind: Option<String>,
+    /// Reference kinds to keep, on top of `reference_kind` (e.g. ["call", "instantiation"]
+    /// to leave out imports and type annotations)
+    #[arg(long = "ref-kinds", value_delimiter = ',')]
+    pub reference_kinds: Option<Vec<String>>,
     /// Filter by project (relative path from workspace root, e.g. "libs/utils")
     #[arg(short, long)]
     pub project: Option<String>,
@@ -180,6 +495,11 @@ pub struct GetCalleesParams {
     /// Example: visibility="public" returns only callees that are public symbols.
     #[arg(short = 'v', long)]
     pub visibility: Option<String>,
+    /// Collapse references with the same file, caller and name into one (the first line),
+    /// so a callee used twenty times is listed once. Default: false.
+    #[arg(long)]
+    #[serde(default)]
+    pub distinct_by_caller: bool,
     /// Maximum number of results to return (default: 100)
     #[arg(short, long)]
     pub limit: Option<u32>,
@@ -196,7 +516,7 @@ pub struct GetCalleesParams {
     pub format: OutputFormat,
 }
 
-#[derive(Debug, Deserialize, JsonSchema, Args)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
 pub struct ExploreParams {
     /// Filter to directory path (relative to project root, e.g. "src/server")
     pub path: Option<String>,
@@ -217,6 +537,327 @@ pub struct ExploreParams {
     pub format: OutputFormat,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct TreeParams {
+    /// Directory to start from (relative to project root, e.g. "src/server"). Default: the project root.
+    pub path: Option<String>,
+    /// Filter by project (relative path from workspace root, defaults to root project)
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Minimum visibility level of the files listed: "public" (default), "internal", or "private".
+    /// File counts always cover every file.
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Directory levels to descend below `path` (default: 3)
+    #[arg(short, long, default_value = "3")]
+    #[serde(default = "default_tree_depth")]
+    pub depth: u32,
+    /// Files listed per directory (default: 5); the rest are counted as "+N files"
+    #[arg(short, long, default_value = "5")]
+    #[serde(default = "default_tree_cap")]
+    pub cap: u32,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+fn default_tree_depth() -> u32 {
+    3
+}
+
+fn default_tree_cap() -> u32 {
+    5
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct MembersParams {
+    /// Name of the parent symbol (e.g. a class), matched in every file
+    pub parent: String,
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    /// Hierarchical filter: public > internal > private.
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Lines of code context per result (recommended: 10). Provides type info, docs, and surrounding code.
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Maximum number of results to return (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Number of results to skip for pagination (default: 0)
+    #[arg(short, long)]
+    pub offset: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ListSymbolsParams {
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Filter by symbol kind (e.g. "function", "class", "interface"; synonyms like "fn" or "cls" work too)
+    #[arg(short, long)]
+    pub kind: Option<String>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    /// Hierarchical filter: public > internal > private.
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Lines of code context per result (recommended: 0 for listings).
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Maximum number of results to return (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Number of results to skip for pagination (default: 0)
+    #[arg(short, long)]
+    pub offset: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct FindUndocumentedParams {
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Filter by symbol kind (e.g. "function", "class"). Comma-separated for multiple.
+    #[arg(short, long, value_delimiter = ',')]
+    pub kind: Option<Vec<String>>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    /// Hierarchical filter: public > internal > private.
+    /// Example: visibility="internal" reports undocumented public AND internal symbols.
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Lines of code context per result (recommended: 10). Provides type info, docs, and surrounding code.
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Maximum number of results to return (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Number of results to skip for pagination (default: 0)
+    #[arg(short, long)]
+    pub offset: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct UnreferencedParams {
+    /// Filter by project (relative path from workspace root, e.g. "libs/utils")
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Filter by symbol kind (e.g. "function", "method"). Comma-separated for multiple.
+    #[arg(short, long, value_delimiter = ',')]
+    pub kind: Option<Vec<String>>,
+    /// Minimum visibility level: "public" (default), "internal", or "private".
+    /// Hierarchical filter: public > internal > private.
+    /// Example: visibility="internal" reports unreferenced public AND internal symbols.
+    #[arg(short = 'v', long)]
+    pub visibility: Option<String>,
+    /// Lines of code context per result (recommended: 10). Provides type info, docs, and surrounding code.
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Maximum number of results to return (default: 100)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Number of results to skip for pagination (default: 0)
+    #[arg(short, long)]
+    pub offset: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ResolveParams {
+    /// Reference name as found at the call site (e.g. "self.handle", "os.path.join", "Config::new")
+    pub name: String,
+    /// File containing the reference; definitions in the same file or directory rank higher
+    #[arg(short = 'f', long)]
+    pub file: Option<String>,
+    /// Project containing the reference (relative path from workspace root); same-project definitions rank higher
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Lines of code context per result (recommended: 10). Provides type info, docs, and surrounding code.
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Maximum number of candidates to return (default: 10)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct DefinitionParams {
+    /// Symbol name (e.g. "Config", "Config.load", "os.path.join")
+    pub name: String,
+    /// File the name is used in; definitions in the same file or directory rank higher
+    #[arg(short = 'f', long)]
+    pub file: Option<String>,
+    /// Project the name is used in (relative path from workspace root); same-project definitions rank higher
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Maximum number of alternative candidates to list (default: 5)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct FindImportCyclesParams {
+    /// Limit to one project (relative path from workspace root); all projects if omitted
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct UnusedImportsParams {
+    /// File path (relative to the project root)
+    pub file: String,
+    /// Project of the file (relative path from workspace root); looked up from the file if omitted
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Lines of code context per result (recommended: 0, the import line is enough).
+    /// 0=metadata only, -1=full definition, N=N lines.
+    #[arg(long)]
+    pub context_lines: Option<i32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ApiDiffParams {
+    /// Project to check (relative path from workspace root). Default: root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Compare against another indexed project (relative path from workspace root)
+    #[arg(long)]
+    pub base_project: Option<String>,
+    /// Compare against a saved index: a `.codeindex/` directory, or a directory containing one
+    /// (relative to the workspace root or absolute)
+    #[arg(long)]
+    pub baseline: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the symbol_diff tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct SymbolDiffParams {
+    /// Saved index to compare against: a `.codeindex/` directory, or a directory containing one
+    /// (relative to the workspace root or absolute)
+    pub baseline: String,
+    /// Project to check (relative path from workspace root). Default: root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct StatsParams {
+    /// Limit to one project (relative path from workspace root). Default: every project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the recent tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct RecentParams {
+    /// Limit to one project (relative path from workspace root). Default: every project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Only files indexed within the last N seconds. Default: no cutoff
+    #[arg(short, long)]
+    pub since_secs: Option<u64>,
+    /// Maximum number of files to return (default: 20)
+    #[arg(short, long)]
+    pub limit: Option<u32>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the projects tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ProjectsParams {
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the diff tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct DiffParams {
+    /// Limit to one project (relative path from workspace root). Default: every mounted project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the manifest tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ManifestParams {
+    /// Project (relative path from workspace root). Default: the root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+    /// Output format: "json" (default for MCP) or "text" (default for CLI)
+    #[arg(long, default_value = "text")]
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// Parameters for the reindex tool.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Args)]
+pub struct ReindexParams {
+    /// File or directory to re-parse, relative to the project root. Default: the whole project
+    pub path: Option<String>,
+    /// Project (relative path from workspace root). Default: the root project
+    #[arg(short, long)]
+    pub project: Option<String>,
+}
+
 /// MCP server exposing code-index query tools and prompts.
 ///
 /// `SearchDb` wraps a `rusqlite::Connection` which is not `Sync`, so we protect
@@ -230,18 +871,111 @@ pub struct CodeIndexServer {
     prompt_router: PromptRouter<Self>,
 }
 
+impl SearchParams {
+    /// Expand `preset` into scope/kind/visibility, keeping any explicitly set filter.
+    pub fn apply_preset(&mut self) -> Result<(), String> {
+        let Some(name) = self.preset.as_deref() else {
+            return Ok(());
+        };
+        let preset = SearchPreset::from_name(name).ok_or_else(|| {
+            format!(
+                "unknown preset '{name}' (expected one of: {})",
+                SearchPreset::NAMES.join(", ")
+            )
+        })?;
+        let to_vec = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        if self.scope.is_none() && !preset.scope.is_empty() {
+            self.scope = Some(to_vec(preset.scope));
+        }
+        if self.kind.is_none() && !preset.kind.is_empty() {
+            self.kind = Some(to_vec(preset.kind));
+        }
+        if self.visibility.is_none() {
+            self.visibility = preset.visibility.map(str::to_string);
+        }
+        Ok(())
+    }
+
+    /// BM25 (name, file, content) weights, or None to keep the defaults.
+    /// Unset weights keep their default; negative or non-finite ones are rejected.
+    pub fn bm25_weights(&self) -> Result<Option<(f64, f64, f64)>, String> {
+        if self.name_weight.is_none() && self.file_weight.is_none() && self.content_weight.is_none()
+        {
+            return Ok(None);
+        }
+        let weights = (
+            self.name_weight.unwrap_or(3.0),
+            self.file_weight.unwrap_or(2.0),
+            self.content_weight.unwrap_or(1.0),
+        );
+        for (name, weight) in [
+            ("name_weight", weights.0),
+            ("file_weight", weights.1),
+            ("content_weight", weights.2),
+        ] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!(
+                    "{name} must be a non-negative number, got {weight}"
+                ));
+            }
+        }
+        Ok(Some(weights))
+    }
+
+    /// The database query for these params (after `apply_preset`).
+    pub fn to_query(&self) -> Result<SearchQuery, String> {
+        Ok(SearchQuery {
+            query: self.query.clone(),
+            filters: SearchFilters {
+                scope: self.scope.clone().unwrap_or_default(),
+                kind: self.kind.clone().unwrap_or_default(),
+                lang: self.lang.clone().unwrap_or_default(),
+                path: self.path.clone(),
+                exclude_paths: self.exclude_paths.clone().unwrap_or_default(),
+                include_tests: self.include_tests,
+                projects: merge_filter(self.project.clone(), self.projects.clone()),
+                visibility: self.visibility.clone(),
+            },
+            case_sensitive: self.case_sensitive,
+            whole_word: self.whole_word,
+            name_regex: self.name_regex.clone(),
+            weights: self.bm25_weights()?,
+            order_by: self.order_by,
+            explain: self.explain,
+            limit: self.limit.unwrap_or(10),
+            offset: self.offset.unwrap_or(0),
+        })
+    }
+}
+
+impl CountParams {
+    /// The search filters for these params.
+    fn filters(&self) -> SearchFilters {
+        SearchFilters {
+            scope: self.scope.clone().unwrap_or_default(),
+            kind: self.kind.clone().unwrap_or_default(),
+            lang: self.lang.clone().unwrap_or_default(),
+            path: self.path.clone(),
+            exclude_paths: self.exclude_paths.clone().unwrap_or_default(),
+            include_tests: self.include_tests,
+            projects: merge_filter(self.project.clone(), self.projects.clone()),
+            visibility: self.visibility.clone(),
+        }
+    }
+}
+
 impl CodeIndexServer {
     pub fn new(db: Arc<Mutex<SearchDb>>, mount_table: Arc<Mutex<MountTable>>) -> Self {
-        let workspace_root = mount_table
+        let workspace_roots = mount_table
             .lock()
             .expect("mount table lock poisoned")
-            .workspace_root()
-            .to_path_buf();
+            .workspace_roots()
+            .to_vec();
 
         Self {
             db,
             mount_table,
-            snippet_extractor: SnippetExtractor::new(workspace_root),
+            snippet_extractor: SnippetExtractor::with_roots(workspace_roots),
             tool_router: Self::tool_router(),
             prompt_router: Self::prompt_router(),
         }
@@ -303,10 +1037,66 @@ impl CodeIndexServer {
                 Some(ReferenceWithSnippet {
                     reference,
                     context: snippet,
+                    def_file: None,
+                    def_line: None,
+                    def_project: None,
                 })
             })
             .collect()
     }
+
+    /// Fill in where each reference's symbol is defined, using the best
+    /// candidate of `SearchDb::resolve_reference` (same file, then directory,
+    /// then project). References that resolve to nothing are left as is.
+    fn resolve_ref_definitions(&self, refs: &mut [ReferenceWithSnippet]) -> Result<(), McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        for r in refs {
+            let best = db
+                .resolve_reference(
+                    &r.reference.name,
+                    Some(&r.reference.file),
+                    Some(&r.reference.project),
+                    1,
+                )
+                .map_err(|e| {
+                    McpError::internal_error(format!("resolve_reference failed: {e}"), None)
+                })?
+                .pop();
+            if let Some((def, _)) = best {
+                r.def_line = Some(def.line[0]);
+                r.def_file = Some(def.file);
+                if def.project != r.reference.project {
+                    r.def_project = Some(def.project);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Symbols of a saved index: a `.codeindex/` directory, or a directory
+    /// containing one, relative to the workspace root or absolute.
+    fn read_baseline_symbols(&self, baseline: &str) -> Result<Vec<SymbolEntry>, McpError> {
+        let workspace_root = self
+            .mount_table
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("mount table lock poisoned: {e}"), None))?
+            .workspace_root()
+            .to_path_buf();
+        let mut dir = workspace_root.join(baseline);
+        if !dir.join("index.json").is_file() {
+            dir = dir.join(".codeindex");
+        }
+        let (_, _, symbols, _, _) = read_index(&dir).map_err(|e| {
+            McpError::invalid_params(
+                format!("cannot read baseline index '{baseline}': {e:#}"),
+                None,
+            )
+        })?;
+        Ok(symbols)
+    }
 }
 
 #[tool_router]
@@ -321,68 +1111,212 @@ impl CodeIndexServer {
 - `\"exact phrase\"` — literal match\n\
 - `foo NOT test` — exclude term\n\n\
 **Tip:** Use `|` to search multiple terms efficiently: `handler|middleware|context`\n\n\
-**Params:** query (required), limit (default 10), snippet_lines (default 10)\n\n\
-**Optional filters:** scope, kind, path, project, visibility"
+**Empty query:** lists everything matching the filters (e.g. all public symbols of a project), in file order\n\n\
+**Params:** query, limit (default 10), snippet_lines (default 10)\n\n\
+**Optional filters:** scope, kind, lang, path, exclude_paths, include_tests, project (or `projects` for several), visibility\n\n\
+**Excluding paths:** `exclude_paths` drops files matching any glob (e.g. `[\"*/generated/*\"]`); FTS `NOT` only excludes terms\n\n\
+**Test files:** left out unless `include_tests` is set (files under `tests/` or `__tests__/`, `*_test.go`, `*.spec.ts`, `test_*.py`, ...)\n\n\
+**Exact matching:** `case_sensitive` keeps exact casing (`Parser` vs `parser`), `whole_word` stops `map` matching `unordered_map`\n\n\
+**Regex names:** `name_regex` keeps symbols/files whose name matches (e.g. `^handle_.*_event$`); with an empty query it scans all symbols\n\n\
+**Grouping:** `group` returns one section per project, projects ordered by their best hit\n\n\
+**Explain:** `explain` adds why each result matched: its score, per-column scores, the best column and a content snippet\n\n\
+**Fuzzy:** `fuzzy` matches symbol names within a few typos of the query (`authetnicate` finds `authenticate`), closest first\n\n\
+**Ranking weights:** `name_weight` (3.0), `file_weight` (2.0), `content_weight` (1.0) tune BM25 per column; raise `content_weight` to favour implementation details\n\n\
+**Presets:** `preset` expands to common filters (explicit filters win):\n\
+- `api` — scope=symbol, visibility=public\n\
+- `all` — all scopes, visibility=private (no visibility filter)\n\
+- `docs` — scope=text, kind=docstring,comment"
     )]
     pub async fn search(
         &self,
-        Parameters(params): Parameters<SearchParams>,
+        Parameters(mut params): Parameters<SearchParams>,
     ) -> Result<CallToolResult, McpError> {
+        params
+            .apply_preset()
+            .map_err(|e| McpError::invalid_params(e, None))?;
+        check_scope(params.scope.as_deref())?;
+        let query = params
+            .to_query()
+            .map_err(|e| McpError::invalid_params(e, None))?;
+        if let Some(pattern) = &params.name_regex {
+            regex::Regex::new(pattern).map_err(|e| {
+                McpError::invalid_params(format!("invalid name_regex '{pattern}': {e}"), None)
+            })?;
+        }
+        if params.explain && params.group {
+            return Err(McpError::invalid_params(
+                "explain cannot be combined with group",
+                None,
+            ));
+        }
+        if params.fuzzy {
+            if params.explain {
+                return Err(McpError::invalid_params(
+                    "explain cannot be combined with fuzzy",
+                    None,
+                ));
+            }
+            if params.query.trim().is_empty() {
+                return Err(McpError::invalid_params("fuzzy needs a query", None));
+            }
+            if params
+                .scope
+                .as_ref()
+                .is_some_and(|scope| !scope.iter().any(|s| s == "symbol"))
+            {
+                return Err(McpError::invalid_params(
+                    "fuzzy only searches symbols",
+                    None,
+                ));
+            }
+        }
+
         let db = self
             .db
             .lock()
             .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
 
-        let scope = params.scope.unwrap_or_default();
-        let limit = params.limit.unwrap_or(10);
-        let offset = params.offset.unwrap_or(0);
-
-        let kind = params.kind.unwrap_or_default();
-        let results = db
-            .search(
-                &params.query,
-                &scope,
-                &kind,
-                params.path.as_deref(),
-                params.project.as_deref(),
-                params.visibility.as_deref(),
-                limit,
-                offset,
+        let page = if params.fuzzy {
+            db.search_fuzzy(
+                &query.query,
+                &query.filters,
+                query.order_by,
+                query.limit,
+                query.offset,
             )
-            .map_err(|e| McpError::internal_error(format!("search failed: {e}"), None))?;
+        } else {
+            db.search_paged(&query)
+        }
+        .map_err(|e| McpError::internal_error(format!("search failed: {e}"), None))?;
 
         drop(db); // Release lock before file I/O
 
         // Enrich symbol results with snippets
         let context_lines = normalize_context_lines(params.context_lines);
-        let enriched: Vec<EnrichedSearchResult> = results
+        let mut matches = page.matches.into_iter();
+        let mut explained = Vec::new();
+        let enriched: Vec<EnrichedSearchResult> = page
+            .results
             .into_iter()
-            .filter_map(|result| match result {
-                SearchResult::Symbol(symbol) => {
-                    // Filter out symbols whose files are missing
-                    if !self
-                        .snippet_extractor
-                        .file_exists(&symbol.project, &symbol.file)
-                    {
-                        return None;
-                    }
-                    let snippet = self.snippet_extractor.extract_snippet(
-                        &symbol.project,
-                        &symbol.file,
-                        symbol.line[0],
-                        symbol.line[1],
-                        context_lines,
-                    );
-                    Some(EnrichedSearchResult::Symbol(SymbolOutput::from_entry(
-                        &symbol, snippet,
-                    )))
+            .filter_map(|result| {
+                let match_info = matches.next();
+                let enriched = self.enrich_search_result(result, context_lines)?;
+                if let Some(match_info) = match_info {
+                    explained.push(match_info);
                 }
-                SearchResult::File(file) => Some(EnrichedSearchResult::File(file)),
-                SearchResult::Text(text) => Some(EnrichedSearchResult::Text(text)),
+                Some(enriched)
             })
             .collect();
 
-        let output = format_search_results(&enriched, params.format)
+        let output = if params.group {
+            let groups: Vec<SearchGroup> =
+                group_by_project(enriched, EnrichedSearchResult::project)
+                    .into_iter()
+                    .map(|(project, results)| SearchGroup { project, results })
+                    .collect();
+            format_grouped_search_results(&groups, page.total, params.format)
+        } else if params.explain {
+            let results: Vec<ExplainedSearchResult> = enriched
+                .into_iter()
+                .zip(explained)
+                .map(|(result, match_info)| ExplainedSearchResult { result, match_info })
+                .collect();
+            format_explained_search_results(&results, page.total, params.format)
+        } else {
+            format_search_results(&enriched, page.total, params.format)
+        }
+        .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Attach a snippet to a symbol result; None when its file is gone from disk.
+    fn enrich_search_result(
+        &self,
+        result: SearchResult,
+        context_lines: i32,
+    ) -> Option<EnrichedSearchResult> {
+        match result {
+            SearchResult::Symbol(symbol) => {
+                // Filter out symbols whose files are missing
+                if !self
+                    .snippet_extractor
+                    .file_exists(&symbol.project, &symbol.file)
+                {
+                    return None;
+                }
+                let snippet = self.snippet_extractor.extract_snippet(
+                    &symbol.project,
+                    &symbol.file,
+                    symbol.line[0],
+                    symbol.line[1],
+                    context_lines,
+                );
+                Some(EnrichedSearchResult::Symbol(SymbolOutput::from_entry(
+                    &symbol, snippet,
+                )))
+            }
+            SearchResult::File(file) => Some(EnrichedSearchResult::File(file)),
+            SearchResult::Text(text) => Some(EnrichedSearchResult::Text(text)),
+        }
+    }
+
+    /// Count search matches without fetching them.
+    #[tool(
+        description = "Count how many rows a search would return, without fetching them. \
+Takes the same query syntax and filters as `search` (scope, kind, lang, path, project, visibility). \
+Use it to check whether a query is too broad before fetching results."
+    )]
+    pub async fn count(
+        &self,
+        Parameters(params): Parameters<CountParams>,
+    ) -> Result<CallToolResult, McpError> {
+        check_scope(params.scope.as_deref())?;
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+
+        let count = db
+            .search_count(&params.query, &params.filters())
+            .map_err(|e| McpError::internal_error(format!("count failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            count.to_string(),
+        )]))
+    }
+
+    /// Find every literal occurrence of a substring in comments, strings, and docstrings.
+    #[tool(
+        description = "Find comments, strings, and docstrings containing a substring literally, like grep, in file and line order. \
+Use it when `search` misses a match because FTS tokenization drops punctuation or splits words (e.g. \"TODO(\", \"v1.2\", \"foo_bar\"). \
+ASCII letters match case-insensitively. Filter by kind, path, and project."
+    )]
+    pub async fn grep(
+        &self,
+        Parameters(params): Parameters<GrepParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if params.pattern.is_empty() {
+            return Err(McpError::invalid_params("pattern must not be empty", None));
+        }
+        let limit = params.limit.unwrap_or(100);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .search_texts_literal(
+                &params.pattern,
+                params.project.as_deref(),
+                params.kind.as_deref(),
+                params.path.as_deref(),
+                limit,
+            )
+            .map_err(|e| McpError::internal_error(format!("grep failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_texts(&results, params.format)
             .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(output)]))
@@ -404,7 +1338,13 @@ impl CodeIndexServer {
             .lock()
             .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
         let results = db
-            .get_file_symbols(&params.file, params.visibility.as_deref(), limit, offset)
+            .get_file_symbols(
+                params.project.as_deref(),
+                &params.file,
+                params.visibility.as_deref(),
+                limit,
+                offset,
+            )
             .map_err(|e| McpError::internal_error(format!("get_file_symbols failed: {e}"), None))?;
 
         drop(db); // Release lock before file I/O
@@ -418,6 +1358,109 @@ impl CodeIndexServer {
         Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
+    /// Read a range of lines from a file of a project.
+    #[tool(
+        description = "Read lines of a source file, verbatim (blank lines included), e.g. to see the code behind a search hit or a reference. \
+Takes the file relative to its project and a line range; out-of-range lines are clamped and at most 500 lines are returned (`truncated` is set when capped)."
+    )]
+    pub async fn get_snippet(
+        &self,
+        Parameters(params): Parameters<GetSnippetParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+        let root = self
+            .mount_table
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("mount table lock poisoned: {e}"), None))?
+            .project_root(&project)
+            .ok_or_else(|| {
+                McpError::invalid_params(format!("unknown project '{project}'"), None)
+            })?;
+
+        // Stay inside the project: no `..` or absolute paths out of the mount
+        let path = root
+            .join(&params.file)
+            .canonicalize()
+            .ok()
+            .filter(|path| path.starts_with(&root) && path.is_file())
+            .ok_or_else(|| {
+                McpError::invalid_params(
+                    format!("file not found in project: '{}'", params.file),
+                    None,
+                )
+            })?;
+        let (line, text, truncated) = read_line_range(&path, params.line_start, params.line_end)
+            .map_err(|e| McpError::internal_error(format!("get_snippet failed: {e}"), None))?;
+
+        let snippet = SourceSnippet {
+            file: params.file,
+            project,
+            line,
+            text,
+            truncated,
+        };
+        let output = format_source_snippet(&snippet, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Find the innermost symbol enclosing a line of a file.
+    #[tool(
+        description = "Find the innermost symbol (function, method, class...) whose line range contains a given line of a file, e.g. \"what encloses line 142 of src/app.py\". \
+Returns null when no symbol encloses the line. Useful for editor integrations and for mapping a stack trace or diff hunk to a symbol."
+    )]
+    pub async fn at(
+        &self,
+        Parameters(params): Parameters<AtParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let symbol = db
+            .symbol_at(&project, &params.file, params.line)
+            .map_err(|e| McpError::internal_error(format!("at failed: {e}"), None))?;
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_with_snippets(symbol.into_iter().collect(), context_lines);
+
+        let output = format_symbol_at(enriched.first(), params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Get the symbols of a file as a tree, nested by parent.
+    #[tool(
+        description = "Get the symbols of a file as a tree: each symbol lists the symbols nested under it (methods under classes, items under modules), in line order. \
+Use it instead of `get_file_symbols` to see a module's structure at a glance."
+    )]
+    pub async fn outline(
+        &self,
+        Parameters(params): Parameters<OutlineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let outline = db
+            .get_file_outline(
+                params.project.as_deref(),
+                &params.file,
+                params.visibility.as_deref(),
+            )
+            .map_err(|e| McpError::internal_error(format!("outline failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_outline(&outline, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
     /// Get direct children of a symbol (e.g. methods of a class).
     #[tool(
         description = "Get direct children of a symbol (e.g. methods of a class). Returns code snippets by default."
@@ -435,6 +1478,7 @@ impl CodeIndexServer {
             .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
         let results = db
             .get_children(
+                params.project.as_deref(),
                 &params.file,
                 &params.parent,
                 params.visibility.as_deref(),
@@ -665,81 +1709,771 @@ impl CodeIndexServer {
             directories: result_dirs,
         };
 
-        let output = format_explore(&result, params.format)
+        let output = format_explore(&result, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Directory tree of a project with per-language file counts.
+    #[tool(
+        description = "Show a project's directory tree: each directory with its file counts per language and up to `cap` of its files (default 5), down to `depth` levels (default 3). \
+Use 'path' to start from a subdirectory. Text output renders like tree(1)."
+    )]
+    pub async fn tree(
+        &self,
+        Parameters(params): Parameters<TreeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let tree = db
+            .explore_tree(
+                params.project.as_deref().unwrap_or(""),
+                params.path.as_deref(),
+                params.visibility.as_deref(),
+                params.depth as usize,
+                params.cap as usize,
+            )
+            .map_err(|e| McpError::internal_error(format!("explore_tree failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_explore_tree(&tree, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Get all references TO a symbol (who calls/uses this symbol).
+    #[tool(
+        description = "Find all places that call or reference a symbol. Returns references sorted by file and line. Useful for finding callers of a function/method. With `with_definition`, each reference also carries `def_file`/`def_line`, the location of the symbol it resolves to. Note: For struct/class fields and methods, use `get_children` instead."
+    )]
+    pub async fn get_callers(
+        &self,
+        Parameters(params): Parameters<GetCallersParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(100);
+        let offset = params.offset.unwrap_or(0);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .get_callers(
+                &params.name,
+                &RefFilters {
+                    kinds: merge_filter(params.reference_kind, params.reference_kinds),
+                    projects: merge_filter(params.project, params.projects),
+                    visibility: params.visibility,
+                    distinct_by_caller: params.distinct_by_caller,
+                },
+                limit,
+                offset,
+            )
+            .map_err(|e| McpError::internal_error(format!("get_callers failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let mut enriched = self.enrich_refs_with_snippets(results, context_lines);
+        if params.with_definition {
+            self.resolve_ref_definitions(&mut enriched)?;
+        }
+
+        let output = format_references(&enriched, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Walk callers transitively (who is affected by changing a symbol).
+    #[tool(
+        description = "Impact analysis: find every function transitively calling a symbol. Walks callers level by level (callers, their callers, ...) up to `max_depth` (default 3), with cycle protection. Each reference is annotated with the `depth` it was reached at (1 = direct caller). Callers are matched by base name, like get_callers."
+    )]
+    pub async fn call_tree(
+        &self,
+        Parameters(params): Parameters<CallTreeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_depth = params.max_depth.unwrap_or(3);
+        let limit = params.limit.unwrap_or(100);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let entries = db
+            .get_callers_transitive(&params.name, params.project.as_deref(), max_depth, limit)
+            .map_err(|e| McpError::internal_error(format!("call_tree failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let nodes: Vec<CallTreeNode> = entries
+            .into_iter()
+            .filter_map(|entry| {
+                let depth = entry.depth;
+                self.enrich_refs_with_snippets(vec![entry.reference], context_lines)
+                    .pop()
+                    .map(|reference| CallTreeNode { depth, reference })
+            })
+            .collect();
+
+        let output = format_call_tree(&nodes, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Get all references FROM a symbol (what does this symbol call/use).
+    #[tool(
+        description = "Find all symbols that a given function/method calls or references. Returns references sorted by file and line. Useful for understanding dependencies and call chains."
+    )]
+    pub async fn get_callees(
+        &self,
+        Parameters(params): Parameters<GetCalleesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(100);
+        let offset = params.offset.unwrap_or(0);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .get_callees(
+                &params.caller,
+                &RefFilters {
+                    kinds: merge_filter(params.reference_kind, params.reference_kinds),
+                    projects: params.project.into_iter().collect(),
+                    visibility: params.visibility,
+                    distinct_by_caller: params.distinct_by_caller,
+                },
+                limit,
+                offset,
+            )
+            .map_err(|e| McpError::internal_error(format!("get_callees failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_refs_with_snippets(results, context_lines);
+
+        let output = format_references(&enriched, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Get the members of a parent symbol in any file.
+    #[tool(
+        description = "Direct children of a parent symbol (e.g. all methods of class UserService) across every file of a project, ordered by file then line. Use when the file is unknown or the type spans files (C# partial classes, Ruby reopened classes, Swift extensions); get_children is the per-file variant. Filter by project and visibility (default: public)."
+    )]
+    pub async fn members(
+        &self,
+        Parameters(params): Parameters<MembersParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(100);
+        let offset = params.offset.unwrap_or(0);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .get_children_by_parent(
+                params.project.as_deref(),
+                &params.parent,
+                params.visibility.as_deref(),
+                limit,
+                offset,
+            )
+            .map_err(|e| McpError::internal_error(format!("members failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_with_snippets(results, context_lines);
+
+        let output = format_symbols(&enriched, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// List all symbols of a project, optionally of one kind.
+    #[tool(
+        description = "List every symbol of a project (e.g. all public interfaces in a project), ordered by file then line. Complete and deterministic — no search terms needed. Filter by project, kind, and visibility (default: public). Use get_file_symbols for a single file."
+    )]
+    pub async fn list_symbols(
+        &self,
+        Parameters(params): Parameters<ListSymbolsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(100);
+        let offset = params.offset.unwrap_or(0);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .list_symbols(
+                params.project.as_deref(),
+                params.kind.as_deref(),
+                params.visibility.as_deref(),
+                limit,
+                offset,
+            )
+            .map_err(|e| McpError::internal_error(format!("list_symbols failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_with_snippets(results, context_lines);
+
+        let output = format_symbols(&enriched, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Find symbols without an attached docstring.
+    #[tool(
+        description = "Find symbols that have no docstring, for documentation audits. Defaults to public symbols; imports are ignored. Filter by project, kind, and visibility."
+    )]
+    pub async fn find_undocumented(
+        &self,
+        Parameters(params): Parameters<FindUndocumentedParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(100);
+        let offset = params.offset.unwrap_or(0);
+        let kind = params.kind.unwrap_or_default();
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .undocumented_symbols(
+                params.project.as_deref(),
+                &kind,
+                params.visibility.as_deref(),
+                limit,
+                offset,
+            )
+            .map_err(|e| {
+                McpError::internal_error(format!("find_undocumented failed: {e}"), None)
+            })?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_with_snippets(results, context_lines);
+
+        let output = format_symbols(&enriched, params.format)
             .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
-    /// Get all references TO a symbol (who calls/uses this symbol).
+    /// Find symbols that nothing references.
     #[tool(
-        description = "Find all places that call or reference a symbol. Returns references sorted by file and line. Useful for finding callers of a function/method. Note: For struct/class fields and methods, use `get_children` instead."
+        description = "Find symbols that no reference in their project points to — dead-code candidates for cleanup. Base names are matched like get_callers, so a method called only as `self.foo` is not reported. Defaults to public symbols; imports, modules and impl blocks are ignored. Filter by project, kind (e.g. function,method), and visibility."
     )]
-    pub async fn get_callers(
+    pub async fn unreferenced(
         &self,
-        Parameters(params): Parameters<GetCallersParams>,
+        Parameters(params): Parameters<UnreferencedParams>,
     ) -> Result<CallToolResult, McpError> {
         let limit = params.limit.unwrap_or(100);
         let offset = params.offset.unwrap_or(0);
+        let kind = params.kind.unwrap_or_default();
 
         let db = self
             .db
             .lock()
             .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
         let results = db
-            .get_callers(
-                &params.name,
-                params.reference_kind.as_deref(),
+            .find_unreferenced(
                 params.project.as_deref(),
+                &kind,
                 params.visibility.as_deref(),
                 limit,
                 offset,
             )
-            .map_err(|e| McpError::internal_error(format!("get_callers failed: {e}"), None))?;
+            .map_err(|e| McpError::internal_error(format!("unreferenced failed: {e}"), None))?;
 
         drop(db); // Release lock before file I/O
 
         let context_lines = normalize_context_lines(params.context_lines);
-        let enriched = self.enrich_refs_with_snippets(results, context_lines);
+        let enriched = self.enrich_with_snippets(results, context_lines);
 
-        let output = format_references(&enriched, params.format)
+        let output = format_symbols(&enriched, params.format)
             .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
-    /// Get all references FROM a symbol (what does this symbol call/use).
+    /// Resolve a reference name to ranked candidate definitions.
     #[tool(
-        description = "Find all symbols that a given function/method calls or references. Returns references sorted by file and line. Useful for understanding dependencies and call chains."
+        description = "Resolve a reference (e.g. from get_callers/get_callees) to its likely definition. Returns candidate symbols ranked by score: exact name match, base-name match, same file/directory, same project. Pass `file` and `project` of the call site for best results."
     )]
-    pub async fn get_callees(
+    pub async fn resolve(
         &self,
-        Parameters(params): Parameters<GetCalleesParams>,
+        Parameters(params): Parameters<ResolveParams>,
     ) -> Result<CallToolResult, McpError> {
-        let limit = params.limit.unwrap_or(100);
-        let offset = params.offset.unwrap_or(0);
+        let limit = params.limit.unwrap_or(10);
 
         let db = self
             .db
             .lock()
             .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
         let results = db
-            .get_callees(
-                &params.caller,
-                params.reference_kind.as_deref(),
+            .resolve_reference(
+                &params.name,
+                params.file.as_deref(),
                 params.project.as_deref(),
-                params.visibility.as_deref(),
                 limit,
-                offset,
             )
-            .map_err(|e| McpError::internal_error(format!("get_callees failed: {e}"), None))?;
+            .map_err(|e| McpError::internal_error(format!("resolve failed: {e}"), None))?;
 
         drop(db); // Release lock before file I/O
 
         let context_lines = normalize_context_lines(params.context_lines);
-        let enriched = self.enrich_refs_with_snippets(results, context_lines);
+        let scored: Vec<ScoredSymbol> = results
+            .into_iter()
+            .filter(|(symbol, _)| {
+                self.snippet_extractor
+                    .file_exists(&symbol.project, &symbol.file)
+            })
+            .map(|(symbol, score)| {
+                let snippet = self.snippet_extractor.extract_snippet(
+                    &symbol.project,
+                    &symbol.file,
+                    symbol.line[0],
+                    symbol.line[1],
+                    context_lines,
+                );
+                ScoredSymbol {
+                    symbol: SymbolOutput::from_entry(&symbol, snippet),
+                    score,
+                }
+            })
+            .collect();
 
-        let output = format_references(&enriched, params.format)
+        let output = format_scored_symbols(&scored, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Get the source and docs of the best-ranked definition of a name.
+    #[tool(
+        description = "Jump to the definition of a name: returns the best-ranked symbol (ranked like `resolve`) with its full source lines, read verbatim from disk, and its docstrings. \
+Lower-ranked candidates are listed under `alternatives`. Returns null when nothing matches. Pass `file` and `project` of the usage site for best results."
+    )]
+    pub async fn definition(
+        &self,
+        Parameters(params): Parameters<DefinitionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let limit = params.limit.unwrap_or(5);
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let mut candidates: Vec<(SymbolEntry, u32)> = db
+            .resolve_reference(
+                &params.name,
+                params.file.as_deref(),
+                params.project.as_deref(),
+                limit.saturating_add(1),
+            )
+            .map_err(|e| McpError::internal_error(format!("definition failed: {e}"), None))?
+            .into_iter()
+            .filter(|(symbol, _)| {
+                self.snippet_extractor
+                    .file_exists(&symbol.project, &symbol.file)
+            })
+            .collect();
+        if candidates.is_empty() {
+            drop(db);
+            let output = format_definition(None, params.format).map_err(|e| {
+                McpError::internal_error(format!("serialization failed: {e}"), None)
+            })?;
+            return Ok(CallToolResult::success(vec![Content::text(output)]));
+        }
+        let (symbol, score) = candidates.remove(0);
+        let docs = db
+            .symbol_docstrings(&symbol.project, &symbol.file, &symbol.name)
+            .map_err(|e| McpError::internal_error(format!("definition failed: {e}"), None))?;
+        drop(db); // Release lock before file I/O
+
+        let path = self
+            .mount_table
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("mount table lock poisoned: {e}"), None))?
+            .project_root(&symbol.project)
+            .map(|root| root.join(&symbol.file))
+            .ok_or_else(|| {
+                McpError::internal_error(format!("unknown project '{}'", symbol.project), None)
+            })?;
+        let (line, text, truncated) = read_line_range(&path, symbol.line[0], symbol.line[1])
+            .map_err(|e| McpError::internal_error(format!("definition failed: {e}"), None))?;
+
+        let definition = Definition {
+            symbol: SymbolOutput::from_entry(&symbol, None),
+            kind: symbol.kind.clone(),
+            score,
+            source: SourceSnippet {
+                file: symbol.file.clone(),
+                project: symbol.project.clone(),
+                line,
+                text,
+                truncated,
+            },
+            docs,
+            alternatives: candidates
+                .into_iter()
+                .map(|(symbol, score)| ScoredSymbol {
+                    symbol: SymbolOutput::from_entry(&symbol, None),
+                    score,
+                })
+                .collect(),
+        };
+        let output = format_definition(Some(&definition), params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Find circular import dependencies between files.
+    #[tool(
+        description = "Detect circular import dependencies. Builds the file-level import graph from import references (specifiers resolved to indexed files of the same project, best-effort; external packages are ignored) and returns each cycle as the list of files involved."
+    )]
+    pub async fn find_import_cycles(
+        &self,
+        Parameters(params): Parameters<FindImportCyclesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let projects = match params.project {
+            Some(project) => vec![project],
+            None => db.list_projects().map_err(|e| {
+                McpError::internal_error(format!("find_import_cycles failed: {e}"), None)
+            })?,
+        };
+
+        let mut cycles = Vec::new();
+        for project in projects {
+            let found = db.find_import_cycles(&project).map_err(|e| {
+                McpError::internal_error(format!("find_import_cycles failed: {e}"), None)
+            })?;
+            cycles.extend(found.into_iter().map(|files| ImportCycle {
+                project: project.clone(),
+                files,
+            }));
+        }
+        drop(db);
+
+        let output = format_import_cycles(&cycles, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Find the imports of a file that the file never uses.
+    #[tool(
+        description = "List the imports of a file that nothing else in the file uses, for linters. Each import is matched by the name it binds (its alias, else the imported name) against the file's references, signatures and tokens. Best-effort: glob imports, grouped Rust `use` lists and #includes are never reported."
+    )]
+    pub async fn unused_imports(
+        &self,
+        Parameters(params): Parameters<UnusedImportsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let results = db
+            .unused_imports(params.project.as_deref(), &params.file)
+            .map_err(|e| McpError::internal_error(format!("unused_imports failed: {e}"), None))?;
+
+        drop(db); // Release lock before file I/O
+
+        let context_lines = normalize_context_lines(params.context_lines);
+        let enriched = self.enrich_with_snippets(results, context_lines);
+
+        let output = format_symbols(&enriched, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Compare the public API surface of a project against a baseline.
+    #[tool(
+        description = "Compare public API surfaces: symbols added, removed, or changed (different kind or signature) in `project` relative to a baseline. The baseline is either another indexed project (`base_project`) or a saved `.codeindex/` directory (`baseline`), e.g. a copy taken from the main branch in CI to detect breaking changes."
+    )]
+    pub async fn api_diff(
+        &self,
+        Parameters(params): Parameters<ApiDiffParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+
+        let before = match (params.base_project, params.baseline) {
+            (Some(base), None) => {
+                let db = self.db.lock().map_err(|e| {
+                    McpError::internal_error(format!("db lock poisoned: {e}"), None)
+                })?;
+                db.public_symbols(&base)
+                    .map_err(|e| McpError::internal_error(format!("api_diff failed: {e}"), None))?
+            }
+            (None, Some(baseline)) => self.read_baseline_symbols(&baseline)?,
+            _ => {
+                return Err(McpError::invalid_params(
+                    "api_diff needs exactly one of base_project or baseline".to_string(),
+                    None,
+                ));
+            }
+        };
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let after = db
+            .public_symbols(&project)
+            .map_err(|e| McpError::internal_error(format!("api_diff failed: {e}"), None))?;
+        drop(db);
+
+        let diff = diff_api(&before, &after);
+        let output = format_api_diff(&diff, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// List symbols added, removed, or moved relative to a saved index.
+    #[tool(
+        description = "Symbol-level changes of `project` relative to a saved `.codeindex/` (`baseline`, e.g. a copy taken from the base branch of a PR): symbols added, removed, or whose line range moved by 10+ lines, matched by file, name, and kind (imports excluded). Includes counts by kind and by visibility, so a reviewer can focus on changed public API."
+    )]
+    pub async fn symbol_diff(
+        &self,
+        Parameters(params): Parameters<SymbolDiffParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+        let old_symbols = self.read_baseline_symbols(&params.baseline)?;
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let deltas = db
+            .symbol_diff(&old_symbols, &project)
+            .map_err(|e| McpError::internal_error(format!("symbol_diff failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_symbol_diff(&SymbolDiff::new(deltas), params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Report index metrics per project.
+    #[tool(
+        description = "Index metrics per project: file, symbol, text, and reference counts, total lines, symbols by kind, and languages present. Read-only. Filter by project."
+    )]
+    pub async fn stats(
+        &self,
+        Parameters(params): Parameters<StatsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+
+        let projects = match params.project {
+            Some(project) => {
+                let exists = db
+                    .has_project(&project)
+                    .map_err(|e| McpError::internal_error(format!("stats failed: {e}"), None))?;
+                if !exists {
+                    return Err(McpError::invalid_params(
+                        format!("unknown project '{project}'"),
+                        None,
+                    ));
+                }
+                vec![project]
+            }
+            None => db
+                .list_projects()
+                .map_err(|e| McpError::internal_error(format!("stats failed: {e}"), None))?,
+        };
+        let stats = projects
+            .iter()
+            .map(|project| db.project_stats(project))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| McpError::internal_error(format!("stats failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_stats(&stats, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// List files by when they were last (re)indexed.
+    #[tool(
+        description = "List indexed files by when codeix last (re)parsed them, most recent first: the working set of a watch session. \
+This reflects watcher events and reindex calls, not git history. Filter by project, or by `since_secs` (only files indexed within the last N seconds). Default limit: 20."
+    )]
+    pub async fn recent(
+        &self,
+        Parameters(params): Parameters<RecentParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let now = SystemTime::now();
+        let since = params.since_secs.map(|secs| {
+            now.checked_sub(Duration::from_secs(secs))
+                .unwrap_or(UNIX_EPOCH)
+        });
+
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("db lock poisoned: {e}"), None))?;
+        let files = db
+            .recent_files(params.project.as_deref(), since, params.limit.unwrap_or(20))
+            .map_err(|e| McpError::internal_error(format!("recent failed: {e}"), None))?;
+        drop(db);
+
+        let output = format_recent_files(&files, now, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// List mounted projects with their roots, modes, and watch state.
+    #[tool(
+        description = "List the mounted projects from the mount table: relative path, absolute root, mode (read-write, or read-only when another codeix instance holds the index lock), \
+whether changes are waiting to be flushed, and how many directories are watched. A project is listed as soon as it is mounted, before any file is indexed."
+    )]
+    pub async fn projects(
+        &self,
+        Parameters(params): Parameters<ProjectsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mt = self.mount_table.lock().map_err(|e| {
+            McpError::internal_error(format!("mount table lock poisoned: {e}"), None)
+        })?;
+        let mut projects: Vec<MountedProject> = mt
+            .iter()
+            .map(|(root, mount)| MountedProject {
+                project: mt.relative_project(root),
+                root: root.display().to_string(),
+                mode: mount.mode.as_str().to_string(),
+                dirty: mount.dirty,
+                watched_count: mount.watched_count(),
+            })
+            .collect();
+        drop(mt);
+        projects.sort_by(|a, b| a.project.cmp(&b.project));
+
+        let output = format_projects(&projects, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Force a re-parse of a file, a directory, or a whole project.
+    #[tool(
+        description = "Force a re-parse of a file or directory (relative to the project root), or of the whole project when no path is given, even if file hashes are unchanged. \
+Use it when the index looks stale, e.g. after an extractor fix or when file watching missed a change. Returns the number of files reprocessed."
+    )]
+    pub async fn reindex(
+        &self,
+        Parameters(params): Parameters<ReindexParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+        let mounted = self
+            .mount_table
+            .lock()
+            .map_err(|e| McpError::internal_error(format!("mount table lock poisoned: {e}"), None))?
+            .project_root(&project)
+            .is_some();
+        if !mounted {
+            return Err(McpError::invalid_params(
+                format!("unknown project '{project}'"),
+                None,
+            ));
+        }
+
+        let count = reindex_project(
+            &self.mount_table,
+            &self.db,
+            &project,
+            params.path.as_deref(),
+        )
+        .map_err(|e| McpError::internal_error(format!("reindex failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            count.to_string(),
+        )]))
+    }
+
+    /// Compare the live index with .codeindex/ on disk.
+    #[tool(
+        description = "Preview what flush_index would write: per project, files added to or removed from the live index relative to its .codeindex/ on disk, and files whose symbols changed (keyed by name, kind, and line). Read-only. Filter by project."
+    )]
+    pub async fn diff(
+        &self,
+        Parameters(params): Parameters<DiffParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mt = self.mount_table.lock().map_err(|e| {
+            McpError::internal_error(format!("mount table lock poisoned: {e}"), None)
+        })?;
+        let roots: Vec<PathBuf> = match &params.project {
+            Some(project) => {
+                let root = mt.project_root(project).ok_or_else(|| {
+                    McpError::invalid_params(format!("unknown project '{project}'"), None)
+                })?;
+                vec![root]
+            }
+            None => mt.iter().map(|(root, _)| root.clone()).collect(),
+        };
+        let mut diffs = roots
+            .iter()
+            .map(|root| diff_mount_with_disk(root, &mt, &self.db))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| McpError::internal_error(format!("diff failed: {e:#}"), None))?;
+        drop(mt);
+        diffs.sort_by(|a, b| a.project.cmp(&b.project));
+
+        let output = format_index_diffs(&diffs, params.format)
+            .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    /// Return a project's index manifest.
+    #[tool(
+        description = "Cheap project primer: the index manifest of a project (name, index format version, root, and the languages of its files, read from the live index rather than .codeindex/index.json). Default: root project."
+    )]
+    pub async fn manifest(
+        &self,
+        Parameters(params): Parameters<ManifestParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let project = params.project.unwrap_or_default();
+        let mt = self.mount_table.lock().map_err(|e| {
+            McpError::internal_error(format!("mount table lock poisoned: {e}"), None)
+        })?;
+        let root = mt.project_root(&project).ok_or_else(|| {
+            McpError::invalid_params(format!("unknown project '{project}'"), None)
+        })?;
+        let manifest = live_manifest(&root, &mt, &self.db)
+            .map_err(|e| McpError::internal_error(format!("manifest failed: {e:#}"), None))?;
+        drop(mt);
+
+        let output = format_manifest(&manifest, params.format)
             .map_err(|e| McpError::internal_error(format!("serialization failed: {e}"), None))?;
 
         Ok(CallToolResult::success(vec![Content::text(output)]))
@@ -832,11 +2566,33 @@ impl ServerHandler for CodeIndexServer {
 
 **Tools:**
 - `explore`: Project structure — metadata, subprojects, files grouped by directory.
+- `tree`: Directory tree with per-language file counts and a few files per directory.
 - `search`: Unified FTS across symbols, files, and texts. BM25-ranked results.
+- `count`: Number of rows a search would return (same filters, no rows fetched).
+- `grep`: Literal substring match over comments, strings, and docstrings, in file order.
 - `get_file_symbols`: All symbols in a file, ordered by line number.
+- `get_snippet`: Lines of a file by range, verbatim (at most 500).
+- `at`: Innermost symbol enclosing a line of a file.
+- `outline`: Symbols of a file as a tree, nested by parent.
 - `get_children`: Direct children of a symbol (e.g., methods of a class).
+- `members`: Direct children of a parent symbol across every file (partial or reopened classes).
+- `list_symbols`: Every symbol of a project, optionally of one kind, in file order.
 - `get_callers`: Find all places that call/reference a symbol.
 - `get_callees`: Find all symbols that a function/method calls.
+- `call_tree`: Transitive callers of a symbol, annotated with depth (impact analysis).
+- `find_undocumented`: Symbols with no docstring (documentation audits).
+- `unreferenced`: Symbols nothing references (dead-code candidates).
+- `resolve`: Rank candidate definitions for a reference name (links refs to symbols).
+- `definition`: Source lines and docstrings of the best-ranked definition of a name, plus alternatives.
+- `find_import_cycles`: Circular import dependencies between files of a project.
+- `api_diff`: Public API added/removed/changed vs another project or a saved index.
+- `symbol_diff`: Symbols added/removed/moved vs a saved index, with counts by kind and visibility.
+- `stats`: Project total and per-project file/symbol/text/reference counts, lines, and per-language breakdown.
+- `manifest`: A project's name, index format version, root, and languages (cheap primer).
+- `projects`: Mounted projects with absolute roots, read-write/read-only mode, dirty flag, and watch count.
+- `recent`: Files most recently (re)indexed, e.g. what the watcher re-parsed this session.
+- `reindex`: Force a re-parse of a file, directory, or whole project.
+- `diff`: Files added, removed, or with changed symbols in the live index vs .codeindex/ on disk.
 - `flush_index`: Persist pending changes to .codeindex/ files.
 
 **Common parameters:**
@@ -887,3 +2643,105 @@ pub async fn start_server(
         .map_err(|e| anyhow::anyhow!("MCP runtime error: {e}"))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search_params(preset: Option<&str>) -> SearchParams {
+        SearchParams {
+            query: "handler".to_string(),
+            preset: preset.map(str::to_string),
+            scope: None,
+            kind: None,
+            lang: None,
+            path: None,
+            exclude_paths: None,
+            include_tests: false,
+            project: None,
+            projects: None,
+            visibility: None,
+            case_sensitive: false,
+            whole_word: false,
+            name_regex: None,
+            name_weight: None,
+            file_weight: None,
+            content_weight: None,
+            group: false,
+            explain: false,
+            fuzzy: false,
+            order_by: SearchOrder::Relevance,
+            limit: None,
+            offset: None,
+            context_lines: None,
+            format: OutputFormat::Json,
+        }
+    }
+
+    #[test]
+    fn test_search_preset_expansion() {
+        let mut params = search_params(Some("api"));
+        params.apply_preset().unwrap();
+        assert_eq!(params.scope, Some(vec!["symbol".to_string()]));
+        assert_eq!(params.kind, None);
+        assert_eq!(params.visibility, Some("public".to_string()));
+
+        let mut params = search_params(Some("all"));
+        params.apply_preset().unwrap();
+        assert_eq!(params.scope, None);
+        assert_eq!(params.visibility, Some("private".to_string()));
+
+        // Explicit filters override the preset
+        let mut params = search_params(Some("docs"));
+        params.kind = Some(vec!["todo".to_string()]);
+        params.apply_preset().unwrap();
+        assert_eq!(params.scope, Some(vec!["text".to_string()]));
+        assert_eq!(params.kind, Some(vec!["todo".to_string()]));
+
+        // No preset leaves params untouched
+        let mut params = search_params(None);
+        params.apply_preset().unwrap();
+        assert_eq!(params.scope, None);
+        assert_eq!(params.visibility, None);
+
+        assert!(search_params(Some("bogus")).apply_preset().is_err());
+    }
+
+    #[test]
+    fn test_check_scope() {
+        let scope = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert!(check_scope(None).is_ok());
+        assert!(check_scope(Some(&scope(&["symbol", "file", "text"]))).is_ok());
+        let err = check_scope(Some(&scope(&["symbol", "symbols"]))).unwrap_err();
+        assert!(err.message.contains("unknown scope 'symbols'"));
+    }
+
+    #[test]
+    fn test_merge_filter() {
+        let list = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert!(merge_filter(None, None).is_empty());
+        assert_eq!(merge_filter(Some("api".into()), None), list(&["api"]));
+        assert_eq!(
+            merge_filter(Some("api".into()), Some(list(&["web", "api"]))),
+            list(&["api", "web"])
+        );
+    }
+
+    #[test]
+    fn test_search_bm25_weights() {
+        let mut params = search_params(None);
+        assert_eq!(params.bm25_weights(), Ok(None));
+
+        // Unset weights keep their defaults
+        params.content_weight = Some(5.0);
+        assert_eq!(params.bm25_weights(), Ok(Some((3.0, 2.0, 5.0))));
+
+        params.name_weight = Some(0.0);
+        assert_eq!(params.bm25_weights(), Ok(Some((0.0, 2.0, 5.0))));
+
+        params.file_weight = Some(-1.0);
+        assert!(params.bm25_weights().unwrap_err().contains("file_weight"));
+        params.file_weight = Some(f64::NAN);
+        assert!(params.bm25_weights().is_err());
+    }
+}
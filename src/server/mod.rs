@@ -1,3 +1,9 @@
+pub mod api_diff;
 pub mod db;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod imports;
+pub mod index_diff;
+pub mod kinds;
 pub mod mcp;
 pub mod snippet;
@@ -1,5 +1,48 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::mount::{WorkspaceRoot, resolve_project_dir};
+
+/// Maximum number of lines returned by [`read_line_range`].
+pub const MAX_SNIPPET_LINES: u32 = 500;
+
+/// Source lines read verbatim from a file (for the get_snippet tool).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceSnippet {
+    pub file: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub project: String,
+    /// Line range actually returned, after clamping to the file; `[0, 0]` for an empty file
+    pub line: [u32; 2],
+    pub text: String,
+    /// True when the requested range was cut to [`MAX_SNIPPET_LINES`]
+    pub truncated: bool,
+}
+
+/// Read lines `line_start..=line_end` (1-indexed) of a file, blank lines included.
+///
+/// Out-of-range line numbers are clamped to the file, and the span is capped to
+/// [`MAX_SNIPPET_LINES`] lines from `line_start`. Returns the clamped range and text.
+pub fn read_line_range(
+    path: &Path,
+    line_start: u32,
+    line_end: u32,
+) -> std::io::Result<([u32; 2], String, bool)> {
+    let content = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+    let lines: Vec<&str> = content.lines().collect();
+    let count = lines.len() as u32;
+    if count == 0 {
+        return Ok(([0, 0], String::new(), false));
+    }
+
+    let start = line_start.clamp(1, count);
+    let end = line_end.clamp(start, count);
+    let capped = end.min(start + MAX_SNIPPET_LINES - 1);
+    let text = lines[(start - 1) as usize..capped as usize].join("\n");
+    Ok(([start, capped], text, capped < end))
+}
 
 /// Extracts code snippets from source files at query time.
 ///
@@ -7,22 +50,29 @@ use std::path::PathBuf;
 /// the specified line ranges from disk.
 #[derive(Clone)]
 pub struct SnippetExtractor {
-    workspace_root: PathBuf,
+    roots: Vec<WorkspaceRoot>,
 }
 
 impl SnippetExtractor {
     /// Create a new snippet extractor with the given workspace root.
     pub fn new(workspace_root: PathBuf) -> Self {
-        Self { workspace_root }
+        Self::with_roots(vec![WorkspaceRoot {
+            path: workspace_root,
+            prefix: String::new(),
+        }])
+    }
+
+    /// Create a snippet extractor over several workspace roots, resolving
+    /// prefixed project strings like the mount table does.
+    pub fn with_roots(roots: Vec<WorkspaceRoot>) -> Self {
+        Self { roots }
     }
 
     /// Resolve absolute file path from project and relative file path.
     fn resolve_file_path(&self, project: &str, file: &str) -> PathBuf {
-        if project.is_empty() {
-            self.workspace_root.join(file)
-        } else {
-            self.workspace_root.join(project).join(file)
-        }
+        resolve_project_dir(&self.roots, project)
+            .unwrap_or_else(|| self.roots[0].path.join(project))
+            .join(file)
     }
 
     /// Extract a code snippet from a file.
@@ -187,6 +237,60 @@ mod tests {
         assert_eq!(snippet, Some("pub fn test() {}".to_string()));
     }
 
+    #[test]
+    fn test_read_line_range() {
+        let tmp = TempDir::new().unwrap();
+        let path = create_test_file(tmp.path(), "test.rs", "line1\n\nline3\nline4\n");
+
+        // Blank lines are kept verbatim
+        let (line, text, truncated) = read_line_range(&path, 1, 3).unwrap();
+        assert_eq!(line, [1, 3]);
+        assert_eq!(text, "line1\n\nline3");
+        assert!(!truncated);
+
+        // Out-of-range numbers are clamped to the file
+        assert_eq!(
+            read_line_range(&path, 0, 99).unwrap(),
+            ([1, 4], "line1\n\nline3\nline4".to_string(), false)
+        );
+        assert_eq!(
+            read_line_range(&path, 10, 20).unwrap(),
+            ([4, 4], "line4".to_string(), false)
+        );
+        // An end before the start yields the start line
+        assert_eq!(read_line_range(&path, 3, 1).unwrap().1, "line3");
+
+        let empty = create_test_file(tmp.path(), "empty.rs", "");
+        assert_eq!(
+            read_line_range(&empty, 1, 5).unwrap(),
+            ([0, 0], String::new(), false)
+        );
+        assert!(read_line_range(&tmp.path().join("missing.rs"), 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_read_line_range_crlf() {
+        let tmp = TempDir::new().unwrap();
+        let path = create_test_file(tmp.path(), "test.rs", "line1\r\n\r\nline3\r\nline4\r\n");
+        assert_eq!(
+            read_line_range(&path, 1, 99).unwrap(),
+            ([1, 4], "line1\n\nline3\nline4".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_read_line_range_caps_span() {
+        let tmp = TempDir::new().unwrap();
+        let content: String = (1..=1200).map(|i| format!("l{i}\n")).collect();
+        let path = create_test_file(tmp.path(), "big.rs", &content);
+
+        let (line, text, truncated) = read_line_range(&path, 101, 1200).unwrap();
+        assert_eq!(line, [101, 100 + MAX_SNIPPET_LINES]);
+        assert_eq!(text.lines().count(), MAX_SNIPPET_LINES as usize);
+        assert!(text.starts_with("l101\n"));
+        assert!(truncated);
+    }
+
     #[test]
     fn test_file_exists() {
         let tmp = TempDir::new().unwrap();
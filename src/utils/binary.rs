@@ -0,0 +1,61 @@
+/// How many leading bytes are sniffed to classify a file.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Share of control bytes above which a sample is considered binary.
+const MAX_CONTROL_RATIO: f64 = 0.3;
+
+/// Guess whether content is binary from its first few KiB: any NUL byte, or
+/// a high share of control characters other than common whitespace.
+///
+/// Bytes >= 0x80 count as text so UTF-8 (and legacy 8-bit encodings) pass.
+/// This is a cheap sniff for files whose extension claims a language, not a
+/// validator: a text file with one NUL in its first 8 KiB is reported binary.
+pub fn is_binary(content: &[u8]) -> bool {
+    let sample = &content[..content.len().min(SNIFF_LEN)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b) || b == 0x7f)
+        .count();
+    control as f64 / sample.len() as f64 > MAX_CONTROL_RATIO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_samples() {
+        assert!(!is_binary(b""));
+        assert!(!is_binary(b"fn main() {\n\tprintln!(\"hi\");\r\n}\n"));
+        assert!(!is_binary("// héllo wörld — ✓\n".as_bytes()));
+        // ANSI escapes and form feeds show up in logs and old C sources
+        assert!(!is_binary(b"\x1b[31mred\x1b[0m\n\x0cpage two\n"));
+        // Latin-1 bytes are not valid UTF-8 but still text
+        assert!(!is_binary(b"caf\xe9 cr\xe8me\n"));
+    }
+
+    #[test]
+    fn test_binary_samples() {
+        // SQLite header, PNG signature, ELF header
+        assert!(is_binary(b"SQLite format 3\0\x10\0\x01\x01"));
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(is_binary(b"\x7fELF\x02\x01\x01\0\0\0\0\0"));
+        // No NUL, but mostly control bytes
+        assert!(is_binary(&[0x01, 0x02, 0x03, 0x04, b'a', 0x05, 0x06]));
+    }
+
+    #[test]
+    fn test_only_leading_bytes_are_sniffed() {
+        let mut content = "x".repeat(SNIFF_LEN).into_bytes();
+        content.push(0);
+        assert!(!is_binary(&content));
+        content.insert(0, 0);
+        assert!(is_binary(&content));
+    }
+}
@@ -5,15 +5,22 @@
 
 use std::collections::BTreeMap;
 use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use rmcp::schemars::{self, JsonSchema};
 use serde::{Deserialize, Serialize};
 
-use crate::index::format::{FileEntry, ReferenceEntry, SymbolOutput, TextEntry};
+use crate::index::format::{FileEntry, IndexManifest, ReferenceEntry, SymbolOutput, TextEntry};
+use crate::server::api_diff::ApiDiff;
+use crate::server::db::{
+    ExploreNode, MatchInfo, OutlineNode, ProjectStats, RecentFile, SymbolDelta,
+};
+use crate::server::index_diff::IndexDiff;
+use crate::server::snippet::{MAX_SNIPPET_LINES, SourceSnippet};
 use crate::utils::manifest::ProjectMetadata;
 
 /// Output format for tool results.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// JSON output (default, for MCP clients)
@@ -36,13 +43,91 @@ impl std::str::FromStr for OutputFormat {
 }
 
 /// Format a list of enriched search results.
+/// `total` is the number of matches across all pages; text output notes it when
+/// the page does not hold them all.
 pub fn format_search_results(
     results: &[EnrichedSearchResult],
+    total: usize,
     format: OutputFormat,
 ) -> Result<String, serde_json::Error> {
     match format {
         OutputFormat::Json => serde_json::to_string_pretty(results),
-        OutputFormat::Text => Ok(format_search_results_text(results)),
+        OutputFormat::Text => {
+            let mut out = format_search_results_text(results);
+            if total > results.len() {
+                let _ = writeln!(out, "(showing {} of {})", results.len(), total);
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// A search result with why it matched (for `search` with `explain`).
+#[derive(Debug, Serialize)]
+pub struct ExplainedSearchResult {
+    #[serde(flatten)]
+    pub result: EnrichedSearchResult,
+    #[serde(rename = "match")]
+    pub match_info: MatchInfo,
+}
+
+/// Format explained search results: each result is followed by its score
+/// line and, when the content column matched, the highlighted fragment.
+pub fn format_explained_search_results(
+    results: &[ExplainedSearchResult],
+    total: usize,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(results),
+        OutputFormat::Text => {
+            let mut out = String::new();
+            for explained in results {
+                write_search_result(&mut out, &explained.result, Some(&explained.match_info));
+            }
+            if total > results.len() {
+                let _ = writeln!(out, "(showing {} of {})", results.len(), total);
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Search results of one project (for `search` with `group`).
+#[derive(Debug, Serialize)]
+pub struct SearchGroup {
+    pub project: String,
+    pub results: Vec<EnrichedSearchResult>,
+}
+
+/// Format search results grouped by project, one section per project.
+pub fn format_grouped_search_results(
+    groups: &[SearchGroup],
+    total: usize,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(groups),
+        OutputFormat::Text => {
+            let mut out = String::new();
+            for (i, group) in groups.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                }
+                let name = if group.project.is_empty() {
+                    "(root)"
+                } else {
+                    &group.project
+                };
+                let _ = writeln!(out, "[{}] ({} results)", name, group.results.len());
+                out.push_str(&format_search_results_text(&group.results));
+            }
+            let shown: usize = groups.iter().map(|g| g.results.len()).sum();
+            if total > shown {
+                let _ = writeln!(out, "(showing {} of {})", shown, total);
+            }
+            Ok(out)
+        }
     }
 }
 
@@ -55,32 +140,70 @@ pub enum EnrichedSearchResult {
     Text(TextEntry),
 }
 
+impl EnrichedSearchResult {
+    /// Project the result belongs to.
+    pub fn project(&self) -> &str {
+        match self {
+            EnrichedSearchResult::Symbol(s) => &s.project,
+            EnrichedSearchResult::File(f) => &f.project,
+            EnrichedSearchResult::Text(t) => &t.project,
+        }
+    }
+}
+
 fn format_search_results_text(results: &[EnrichedSearchResult]) -> String {
     let mut out = String::new();
     for result in results {
-        match result {
-            EnrichedSearchResult::Symbol(symbol) => {
-                // file[line-range] symbol name
-                let location = format_location(&symbol.file, symbol.line);
-                let _ = writeln!(out, "{} symbol {}", location, symbol.name);
-                if let Some(snip) = &symbol.context {
-                    write_snippet(&mut out, snip);
-                }
-            }
-            EnrichedSearchResult::File(file) => {
-                // path file (lang, lines)
-                let lang = file.lang.as_deref().unwrap_or("-");
-                let _ = writeln!(out, "{} file ({}, {} lines)", file.path, lang, file.lines);
-            }
-            EnrichedSearchResult::Text(text) => {
-                // file[line] text kind preview
-                let location = format_location(&text.file, text.line);
-                let preview: String = text.text.chars().take(40).collect();
-                let _ = writeln!(out, "{} text {} {}...", location, text.kind, preview);
+        write_search_result(&mut out, result, None);
+    }
+    out
+}
+
+/// Write one result; `match_info` goes right under its header line, before any source context.
+fn write_search_result(
+    out: &mut String,
+    result: &EnrichedSearchResult,
+    match_info: Option<&MatchInfo>,
+) {
+    match result {
+        EnrichedSearchResult::Symbol(symbol) => {
+            // file[line-range] symbol name
+            let location = format_location(&symbol.file, symbol.line);
+            let _ = writeln!(out, "{} symbol {}", location, symbol.name);
+            write_match_info(out, match_info);
+            if let Some(snip) = &symbol.context {
+                write_snippet(out, snip);
             }
         }
+        EnrichedSearchResult::File(file) => {
+            // path file (lang, lines)
+            let lang = file.lang.as_deref().unwrap_or("-");
+            let _ = writeln!(out, "{} file ({}, {} lines)", file.path, lang, file.lines);
+            write_match_info(out, match_info);
+        }
+        EnrichedSearchResult::Text(text) => {
+            // file[line] text kind preview
+            let location = format_location(&text.file, text.line);
+            let preview: String = text.text.chars().take(40).collect();
+            let _ = writeln!(out, "{} text {} {}...", location, text.kind, preview);
+            write_match_info(out, match_info);
+        }
+    }
+}
+
+fn write_match_info(out: &mut String, match_info: Option<&MatchInfo>) {
+    let Some(m) = match_info else {
+        return;
+    };
+    // match column score -X (name -X, file -X, content -X)
+    let _ = writeln!(
+        out,
+        "  match {} score {:.2} (name {:.2}, file {:.2}, content {:.2})",
+        m.matched_column, m.score, m.name_score, m.file_score, m.content_score
+    );
+    if let Some(snippet) = &m.snippet {
+        let _ = writeln!(out, "  > {}", snippet.replace('\n', " "));
     }
-    out
 }
 
 /// Format file location with brackets, using range if start != end.
@@ -169,6 +292,186 @@ fn format_symbols_text(symbols: &[SymbolWithSnippet]) -> String {
     out
 }
 
+/// Format the symbol enclosing a position, if any (for at).
+pub fn format_symbol_at(
+    symbol: Option<&SymbolWithSnippet>,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match (format, symbol) {
+        (OutputFormat::Json, _) => serde_json::to_string_pretty(&symbol),
+        (OutputFormat::Text, Some(sym)) => Ok(format_symbols_text(std::slice::from_ref(sym))),
+        (OutputFormat::Text, None) => Ok("no enclosing symbol\n".to_string()),
+    }
+}
+
+/// Format source lines read from disk (for get_snippet).
+/// Text output is the code verbatim under a `file[range]` header.
+pub fn format_source_snippet(
+    snippet: &SourceSnippet,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(snippet),
+        OutputFormat::Text => {
+            let mut out = format!("{}\n", format_location(&snippet.file, snippet.line));
+            if !snippet.text.is_empty() {
+                let _ = writeln!(out, "{}", snippet.text);
+            }
+            if snippet.truncated {
+                let _ = writeln!(out, "... (capped at {} lines)", MAX_SNIPPET_LINES);
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Format a file outline as a tree (for outline).
+pub fn format_outline(
+    nodes: &[OutlineNode],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(nodes),
+        OutputFormat::Text => {
+            let mut out = String::new();
+            write_outline_text(&mut out, nodes, 0);
+            Ok(out)
+        }
+    }
+}
+
+fn write_outline_text(out: &mut String, nodes: &[OutlineNode], depth: usize) {
+    for node in nodes {
+        // file[line-range] kind name, indented by nesting depth
+        let sym = &node.symbol;
+        let location = format_location(&sym.file, sym.line);
+        let _ = writeln!(
+            out,
+            "{}{} {} {}",
+            "  ".repeat(depth),
+            location,
+            sym.kind,
+            sym.name
+        );
+        write_outline_text(out, &node.children, depth + 1);
+    }
+}
+
+/// Format text entries (for grep).
+pub fn format_texts(
+    texts: &[TextEntry],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(texts),
+        OutputFormat::Text => Ok(format_texts_text(texts)),
+    }
+}
+
+fn format_texts_text(texts: &[TextEntry]) -> String {
+    let mut out = String::new();
+    for text in texts {
+        // file[line-range] text kind, then the full text
+        let location = format_location(&text.file, text.line);
+        let _ = writeln!(out, "{} text {}", location, text.kind);
+        write_snippet(&mut out, &text.text);
+    }
+    out
+}
+
+/// Symbol with a relevance score (for resolve).
+#[derive(Debug, Serialize)]
+pub struct ScoredSymbol {
+    #[serde(flatten)]
+    pub symbol: SymbolOutput,
+    pub score: u32,
+}
+
+/// Format ranked candidate definitions (for resolve).
+pub fn format_scored_symbols(
+    symbols: &[ScoredSymbol],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(symbols),
+        OutputFormat::Text => Ok(format_scored_symbols_text(symbols)),
+    }
+}
+
+fn format_scored_symbols_text(symbols: &[ScoredSymbol]) -> String {
+    let mut out = String::new();
+    for scored in symbols {
+        // file[line-range] symbol name (score N)
+        let sym = &scored.symbol;
+        let location = format_location(&sym.file, sym.line);
+        let _ = writeln!(
+            out,
+            "{} symbol {} (score {})",
+            location, sym.name, scored.score
+        );
+        if let Some(snip) = &sym.context {
+            write_snippet(&mut out, snip);
+        }
+    }
+    out
+}
+
+/// Best-ranked definition of a name with its source and docs (for definition).
+#[derive(Debug, Serialize)]
+pub struct Definition {
+    #[serde(flatten)]
+    pub symbol: SymbolOutput,
+    pub kind: String,
+    pub score: u32,
+    /// The symbol's lines, read from disk
+    pub source: SourceSnippet,
+    /// Docstrings attached to the symbol
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub docs: Vec<TextEntry>,
+    /// Lower-ranked candidates, best first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alternatives: Vec<ScoredSymbol>,
+}
+
+/// Format a resolved definition, if any (for definition).
+/// Text output is the docs, then the code verbatim, then the other candidates.
+pub fn format_definition(
+    definition: Option<&Definition>,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match (format, definition) {
+        (OutputFormat::Json, _) => serde_json::to_string_pretty(&definition),
+        (OutputFormat::Text, Some(def)) => Ok(format_definition_text(def)),
+        (OutputFormat::Text, None) => Ok("no definition found\n".to_string()),
+    }
+}
+
+fn format_definition_text(def: &Definition) -> String {
+    // file[line-range] kind name (score N)
+    let sym = &def.symbol;
+    let mut out = format!(
+        "{} {} {} (score {})\n",
+        format_location(&sym.file, sym.line),
+        def.kind,
+        sym.name,
+        def.score
+    );
+    for doc in &def.docs {
+        write_snippet(&mut out, &doc.text);
+    }
+    if !def.source.text.is_empty() {
+        let _ = writeln!(out, "{}", def.source.text);
+    }
+    if def.source.truncated {
+        let _ = writeln!(out, "... (capped at {} lines)", MAX_SNIPPET_LINES);
+    }
+    if !def.alternatives.is_empty() {
+        out.push_str("\nalternatives:\n");
+        out.push_str(&format_scored_symbols_text(&def.alternatives));
+    }
+    out
+}
+
 /// Response wrapper for ReferenceEntry with optional context.
 #[derive(Debug, Serialize)]
 pub struct ReferenceWithSnippet {
@@ -176,6 +479,15 @@ pub struct ReferenceWithSnippet {
     pub reference: ReferenceEntry,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<String>,
+    /// File defining the referenced symbol, when definitions were resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub def_file: Option<String>,
+    /// First line of that definition (1-based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub def_line: Option<u32>,
+    /// Project of the definition, only set when it differs from the reference's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub def_project: Option<String>,
 }
 
 /// Format references (for get_callers, get_callees).
@@ -195,11 +507,22 @@ fn format_references_text(refs: &[ReferenceWithSnippet]) -> String {
         // file[line] ref kind caller -> name
         let location = format_location(&r.reference.file, r.reference.line);
         let caller = r.reference.caller.as_deref().unwrap_or("(top-level)");
-        let _ = writeln!(
+        let _ = write!(
             out,
             "{} ref {} {} -> {}",
             location, r.reference.kind, caller, r.reference.name
         );
+        if let (Some(file), Some(line)) = (&r.def_file, r.def_line) {
+            match &r.def_project {
+                Some(project) => {
+                    let _ = write!(out, " @ {}:{}[{}]", project, file, line);
+                }
+                None => {
+                    let _ = write!(out, " @ {}[{}]", file, line);
+                }
+            }
+        }
+        out.push('\n');
 
         // Snippet if present
         if let Some(snip) = &r.context {
@@ -209,6 +532,46 @@ fn format_references_text(refs: &[ReferenceWithSnippet]) -> String {
     out
 }
 
+/// A call_tree entry: a reference with the depth it was reached at.
+#[derive(Debug, Serialize)]
+pub struct CallTreeNode {
+    pub depth: u32,
+    #[serde(flatten)]
+    pub reference: ReferenceWithSnippet,
+}
+
+/// Format a transitive caller tree (for call_tree).
+pub fn format_call_tree(
+    nodes: &[CallTreeNode],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(nodes),
+        OutputFormat::Text => Ok(format_call_tree_text(nodes)),
+    }
+}
+
+fn format_call_tree_text(nodes: &[CallTreeNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        // [depth] file[line] ref kind caller -> name, indented by depth
+        let r = &node.reference.reference;
+        let indent = "  ".repeat(node.depth.saturating_sub(1) as usize);
+        let location = format_location(&r.file, r.line);
+        let caller = r.caller.as_deref().unwrap_or("(top-level)");
+        let _ = writeln!(
+            out,
+            "{}[{}] {} ref {} {} -> {}",
+            indent, node.depth, location, r.kind, caller, r.name
+        );
+
+        if let Some(snip) = &node.reference.context {
+            write_snippet(&mut out, snip);
+        }
+    }
+    out
+}
+
 /// Result of explore tool: project metadata + files grouped by directory.
 #[derive(Debug, Serialize)]
 pub struct ExploreResult {
@@ -422,6 +785,477 @@ fn format_explore_text(result: &ExploreResult) -> String {
     out
 }
 
+/// Format a directory tree (for tree).
+pub fn format_explore_tree(
+    node: &ExploreNode,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(node),
+        OutputFormat::Text => Ok(format_explore_tree_text(node)),
+    }
+}
+
+/// Render like `tree(1)`: directories first (with their file counts per
+/// language), then files, then a `directories, files` summary line.
+fn format_explore_tree_text(root: &ExploreNode) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{}{}", root.path, lang_counts(root));
+    let (mut dirs, mut files) = (0, 0);
+    write_explore_tree_children(&mut out, root, "", &mut dirs, &mut files);
+    let _ = writeln!(
+        out,
+        "\n{} director{}, {} file{}",
+        dirs,
+        if dirs == 1 { "y" } else { "ies" },
+        files,
+        if files == 1 { "" } else { "s" }
+    );
+    out
+}
+
+fn write_explore_tree_children(
+    out: &mut String,
+    node: &ExploreNode,
+    prefix: &str,
+    dirs: &mut usize,
+    files: &mut usize,
+) {
+    *files += node.langs.values().sum::<usize>();
+    let mut items: Vec<String> = node.files.clone();
+    if node.more_files > 0 {
+        items.push(format!("+{} files", node.more_files));
+    }
+    if node.truncated {
+        items.push("…".to_string());
+    }
+    let total = node.children.len() + items.len();
+
+    for (i, child) in node.children.iter().enumerate() {
+        *dirs += 1;
+        let is_last = i + 1 == total;
+        let name = child.path.rsplit('/').next().unwrap_or(&child.path);
+        let connector = if is_last { "└── " } else { "├── " };
+        let _ = writeln!(out, "{prefix}{connector}{name}/{}", lang_counts(child));
+        let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+        write_explore_tree_children(out, child, &child_prefix, dirs, files);
+    }
+    for (i, item) in items.iter().enumerate() {
+        let is_last = node.children.len() + i + 1 == total;
+        let connector = if is_last { "└── " } else { "├── " };
+        let _ = writeln!(out, "{prefix}{connector}{item}");
+    }
+}
+
+/// ` (12 rust, 1 markdown)`, most files first; empty for a directory without files.
+fn lang_counts(node: &ExploreNode) -> String {
+    if node.langs.is_empty() {
+        return String::new();
+    }
+    let mut counts: Vec<(&String, &usize)> = node.langs.iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let counts: Vec<String> = counts
+        .into_iter()
+        .map(|(lang, count)| format!("{count} {lang}"))
+        .collect();
+    format!(" ({})", counts.join(", "))
+}
+
+/// Format a public API diff (for api_diff).
+pub fn format_api_diff(diff: &ApiDiff, format: OutputFormat) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(diff),
+        OutputFormat::Text => Ok(format_api_diff_text(diff)),
+    }
+}
+
+fn format_api_diff_text(diff: &ApiDiff) -> String {
+    if diff.is_empty() {
+        return "no public API changes\n".to_string();
+    }
+    let mut out = String::new();
+    // + file[line-range] kind name / ~ file[line-range] name: before -> after
+    for (label, marker, symbols) in [("added", '+', &diff.added), ("removed", '-', &diff.removed)] {
+        if !symbols.is_empty() {
+            let _ = writeln!(out, "{} ({}):", label, symbols.len());
+            for sym in symbols {
+                let location = format_location(&sym.file, sym.line);
+                let _ = writeln!(out, "  {} {} {} {}", marker, location, sym.kind, sym.name);
+            }
+        }
+    }
+    if !diff.changed.is_empty() {
+        let _ = writeln!(out, "changed ({}):", diff.changed.len());
+        for change in &diff.changed {
+            let location = format_location(&change.file, change.line);
+            let _ = writeln!(
+                out,
+                "  ~ {} {}: {} -> {}",
+                location, change.name, change.before, change.after
+            );
+        }
+    }
+    out
+}
+
+/// Added/removed/moved counts of one kind or visibility (for symbol_diff).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct DeltaCounts {
+    pub added: usize,
+    pub removed: usize,
+    pub moved: usize,
+}
+
+/// Symbol-level changes against a baseline, with breakdowns (for symbol_diff).
+#[derive(Debug, Default, Serialize)]
+pub struct SymbolDiff {
+    pub changes: Vec<SymbolDelta>,
+    pub by_kind: BTreeMap<String, DeltaCounts>,
+    /// Keyed by visibility, "none" for symbols without one
+    pub by_visibility: BTreeMap<String, DeltaCounts>,
+}
+
+impl SymbolDiff {
+    pub fn new(changes: Vec<SymbolDelta>) -> Self {
+        let mut by_kind: BTreeMap<String, DeltaCounts> = BTreeMap::new();
+        let mut by_visibility: BTreeMap<String, DeltaCounts> = BTreeMap::new();
+        for delta in &changes {
+            let visibility = delta.visibility.as_deref().unwrap_or("none");
+            for counts in [
+                by_kind.entry(delta.kind.clone()).or_default(),
+                by_visibility.entry(visibility.to_string()).or_default(),
+            ] {
+                match delta.change.as_str() {
+                    "added" => counts.added += 1,
+                    "removed" => counts.removed += 1,
+                    _ => counts.moved += 1,
+                }
+            }
+        }
+        Self {
+            changes,
+            by_kind,
+            by_visibility,
+        }
+    }
+}
+
+/// Format symbol-level changes against a baseline (for symbol_diff).
+pub fn format_symbol_diff(
+    diff: &SymbolDiff,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(diff),
+        OutputFormat::Text => Ok(format_symbol_diff_text(diff)),
+    }
+}
+
+fn format_symbol_diff_text(diff: &SymbolDiff) -> String {
+    if diff.changes.is_empty() {
+        return "no symbol changes\n".to_string();
+    }
+    let mut out = String::new();
+    // + file[line-range] kind name (visibility) / ~ file[line-range] kind name (was old-range)
+    for (label, marker) in [("added", '+'), ("removed", '-'), ("moved", '~')] {
+        let changes: Vec<&SymbolDelta> =
+            diff.changes.iter().filter(|d| d.change == label).collect();
+        if changes.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "{} ({}):", label, changes.len());
+        for delta in changes {
+            let location = format_location(&delta.file, delta.line);
+            let _ = write!(
+                out,
+                "  {} {} {} {}",
+                marker, location, delta.kind, delta.name
+            );
+            if let Some(visibility) = &delta.visibility {
+                let _ = write!(out, " ({})", visibility);
+            }
+            if let Some([start, end]) = delta.old_line {
+                let _ = write!(out, " (was {}-{})", start, end);
+            }
+            out.push('\n');
+        }
+    }
+    for (label, breakdown) in [
+        ("by kind", &diff.by_kind),
+        ("by visibility", &diff.by_visibility),
+    ] {
+        let counts: Vec<String> = breakdown
+            .iter()
+            .map(|(key, c)| format!("{} +{} -{} ~{}", key, c.added, c.removed, c.moved))
+            .collect();
+        let _ = writeln!(out, "{}: {}", label, counts.join(", "));
+    }
+    out
+}
+
+/// Format differences between the live index and .codeindex/ on disk (for diff).
+pub fn format_index_diffs(
+    diffs: &[IndexDiff],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(diffs),
+        OutputFormat::Text => Ok(format_index_diffs_text(diffs)),
+    }
+}
+
+fn format_index_diffs_text(diffs: &[IndexDiff]) -> String {
+    if diffs.iter().all(IndexDiff::is_empty) {
+        return "index on disk is up to date\n".to_string();
+    }
+    let mut out = String::new();
+    // project: N added, N removed, N modified / + path / - path / ~ path (+N -N symbols)
+    for diff in diffs.iter().filter(|d| !d.is_empty()) {
+        let project = if diff.project.is_empty() {
+            "(root)"
+        } else {
+            diff.project.as_str()
+        };
+        let _ = writeln!(
+            out,
+            "{}: {} added, {} removed, {} modified",
+            project,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.modified.len()
+        );
+        for path in &diff.added {
+            let _ = writeln!(out, "  + {}", path);
+        }
+        for path in &diff.removed {
+            let _ = writeln!(out, "  - {}", path);
+        }
+        for file in &diff.modified {
+            let _ = writeln!(
+                out,
+                "  ~ {} (+{} -{} symbols)",
+                file.path, file.added_symbols, file.removed_symbols
+            );
+        }
+    }
+    out
+}
+
+/// Format a project's index manifest (for manifest).
+pub fn format_manifest(
+    manifest: &IndexManifest,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(manifest),
+        OutputFormat::Text => {
+            let mut out = String::new();
+            for (label, value) in [
+                ("name", manifest.name.as_str()),
+                ("version", manifest.version.as_str()),
+                ("root", manifest.root.as_str()),
+            ] {
+                let _ = writeln!(out, "{:<16}{}", label, value);
+            }
+            let _ = writeln!(out, "{:<16}{}", "languages", manifest.languages.join(", "));
+            Ok(out)
+        }
+    }
+}
+
+/// A mounted project as the mount table sees it (for projects).
+#[derive(Debug, Serialize)]
+pub struct MountedProject {
+    /// Relative path from the workspace root ("" for the root project)
+    pub project: String,
+    /// Absolute root directory on disk
+    pub root: String,
+    /// "read-write", or "read-only" when another instance holds the lock
+    pub mode: String,
+    /// Changes not yet flushed to .codeindex/
+    pub dirty: bool,
+    /// Directories with a live watch
+    pub watched_count: usize,
+}
+
+/// Format mounted projects (for projects).
+pub fn format_projects(
+    projects: &[MountedProject],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(projects),
+        OutputFormat::Text => Ok(format_projects_text(projects)),
+    }
+}
+
+fn format_projects_text(projects: &[MountedProject]) -> String {
+    let name = |p: &MountedProject| {
+        if p.project.is_empty() {
+            "(root)".to_string()
+        } else {
+            p.project.clone()
+        }
+    };
+    let width = projects.iter().map(|p| name(p).len()).max().unwrap_or(0);
+    let mut out = String::new();
+    // name  mode  dirty  N watched  root
+    for project in projects {
+        let _ = writeln!(
+            out,
+            "{:<width$}  {:<10}  {:<5}  {:>5} watched  {}",
+            name(project),
+            project.mode,
+            if project.dirty { "dirty" } else { "" },
+            project.watched_count,
+            project.root
+        );
+    }
+    out
+}
+
+/// Format recently indexed files (for recent), with ages relative to `now`.
+pub fn format_recent_files(
+    files: &[RecentFile],
+    now: SystemTime,
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(files),
+        OutputFormat::Text => Ok(format_recent_files_text(files, now)),
+    }
+}
+
+fn format_recent_files_text(files: &[RecentFile], now: SystemTime) -> String {
+    let now_ms = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    let mut out = String::new();
+    // age  [project:]path
+    for file in files {
+        let age = format_age(now_ms.saturating_sub(file.indexed_at) / 1000);
+        if file.project.is_empty() {
+            let _ = writeln!(out, "{:>8}  {}", age, file.path);
+        } else {
+            let _ = writeln!(out, "{:>8}  {}:{}", age, file.project, file.path);
+        }
+    }
+    out
+}
+
+/// Render an age in seconds with its largest unit: "just now", "42s ago", "5m ago", "3h ago", "2d ago".
+fn format_age(secs: u64) -> String {
+    match secs {
+        0 => "just now".to_string(),
+        s if s < 60 => format!("{s}s ago"),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+/// A circular import dependency between files of one project.
+#[derive(Debug, Serialize)]
+pub struct ImportCycle {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub project: String,
+    pub files: Vec<String>,
+}
+
+/// Format import cycles (for find_import_cycles).
+pub fn format_import_cycles(
+    cycles: &[ImportCycle],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(cycles),
+        OutputFormat::Text => Ok(format_import_cycles_text(cycles)),
+    }
+}
+
+fn format_import_cycles_text(cycles: &[ImportCycle]) -> String {
+    if cycles.is_empty() {
+        return "no import cycles found\n".to_string();
+    }
+    let mut out = String::new();
+    for cycle in cycles {
+        // [project] cycle (N files): a, b, c
+        if !cycle.project.is_empty() {
+            let _ = write!(out, "[{}] ", cycle.project);
+        }
+        let _ = writeln!(
+            out,
+            "cycle ({} files): {}",
+            cycle.files.len(),
+            cycle.files.join(", ")
+        );
+    }
+    out
+}
+
+/// Format per-project index statistics (for the stats tool).
+///
+/// JSON is `{"projects": N, "per_project": [...]}`; text starts with the
+/// project total, then one block per project.
+pub fn format_stats(
+    stats: &[ProjectStats],
+    format: OutputFormat,
+) -> Result<String, serde_json::Error> {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "projects": stats.len(),
+            "per_project": stats,
+        })),
+        OutputFormat::Text => Ok(format_stats_text(stats)),
+    }
+}
+
+fn format_stats_text(stats: &[ProjectStats]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{:<16}{:>10}", "projects", stats.len());
+    for project in stats {
+        out.push('\n');
+        let name = if project.project.is_empty() {
+            "(root)"
+        } else {
+            &project.project
+        };
+        let _ = writeln!(out, "[{}]", name);
+        for (label, value) in [
+            ("files", project.files),
+            ("symbols", project.symbols),
+            ("texts", project.texts),
+            ("refs", project.refs),
+            ("lines", project.lines),
+        ] {
+            let _ = writeln!(out, "{:<16}{:>10}", label, value);
+        }
+
+        if !project.symbols_by_kind.is_empty() {
+            let _ = writeln!(out, "symbols by kind");
+            for (kind, count) in &project.symbols_by_kind {
+                let _ = writeln!(out, "  {:<14}{:>10}", kind, count);
+            }
+        }
+
+        if !project.languages.is_empty() {
+            let _ = writeln!(out, "{:<16}{}", "languages", project.languages.join(", "));
+        }
+
+        if !project.files_by_language.is_empty() {
+            let _ = writeln!(out, "{:<16}{:>10}{:>12}", "by language", "files", "lines");
+            for lang in &project.files_by_language {
+                let _ = writeln!(
+                    out,
+                    "  {:<14}{:>10}{:>12}",
+                    lang.lang, lang.files, lang.lines
+                );
+            }
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -435,6 +1269,501 @@ mod tests {
         assert!("invalid".parse::<OutputFormat>().is_err());
     }
 
+    #[test]
+    fn test_format_stats_text_is_aligned() {
+        let stats = vec![
+            ProjectStats {
+                files: 12,
+                symbols: 340,
+                lines: 4200,
+                symbols_by_kind: vec![("function".to_string(), 300), ("struct".to_string(), 40)],
+                languages: vec!["python".to_string(), "rust".to_string()],
+                files_by_language: vec![crate::server::db::LanguageStats {
+                    lang: "rust".to_string(),
+                    files: 12,
+                    lines: 4200,
+                }],
+                ..Default::default()
+            },
+            ProjectStats {
+                project: "libs/utils".to_string(),
+                ..Default::default()
+            },
+        ];
+        let out = format_stats(&stats, OutputFormat::Text).unwrap();
+        assert!(out.starts_with("projects                 2\n\n[(root)]\n"));
+        assert!(out.contains("symbols                340\n"));
+        assert!(out.contains("lines                 4200\n"));
+        assert!(out.contains("  function             300\n"));
+        assert!(out.contains("languages       python, rust\n"));
+        assert!(out.contains("  rust                  12        4200\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_stats(&stats, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["projects"], 2);
+        assert_eq!(
+            json["per_project"][0]["files_by_language"][0]["lines"],
+            4200
+        );
+        assert!(out.contains("\n\n[libs/utils]\nfiles                    0\n"));
+    }
+
+    #[test]
+    fn test_format_projects_text() {
+        let projects = vec![
+            MountedProject {
+                project: String::new(),
+                root: "/work".to_string(),
+                mode: "read-write".to_string(),
+                dirty: true,
+                watched_count: 12,
+            },
+            MountedProject {
+                project: "libs/utils".to_string(),
+                root: "/work/libs/utils".to_string(),
+                mode: "read-only".to_string(),
+                dirty: false,
+                watched_count: 0,
+            },
+        ];
+        let out = format_projects(&projects, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "(root)      read-write  dirty     12 watched  /work\n\
+             libs/utils  read-only              0 watched  /work/libs/utils\n"
+        );
+    }
+
+    #[test]
+    fn test_format_recent_files_text() {
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let file = |project: &str, path: &str, age_secs: u64| RecentFile {
+            project: project.to_string(),
+            path: path.to_string(),
+            lang: Some("rust".to_string()),
+            lines: 10,
+            indexed_at: (1_000_000 - age_secs) * 1000,
+        };
+        let files = vec![
+            file("", "src/main.rs", 0),
+            file("", "src/db.rs", 42),
+            file("libs/core", "lib.rs", 7200),
+            file("", "README.md", 3 * 86400),
+        ];
+        let out = format_recent_files(&files, now, OutputFormat::Text).unwrap();
+        let expected = [
+            "just now  src/main.rs",
+            " 42s ago  src/db.rs",
+            "  2h ago  libs/core:lib.rs",
+            "  3d ago  README.md",
+        ];
+        assert_eq!(out, format!("{}\n", expected.join("\n")));
+    }
+
+    #[test]
+    fn test_format_api_diff_text() {
+        use crate::server::api_diff::{ApiChange, ApiSymbol};
+        let diff = ApiDiff {
+            added: vec![ApiSymbol {
+                name: "connect".to_string(),
+                kind: "function".to_string(),
+                file: "src/lib.rs".to_string(),
+                line: [3, 5],
+            }],
+            removed: vec![],
+            changed: vec![ApiChange {
+                name: "Config".to_string(),
+                before: "struct".to_string(),
+                after: "interface".to_string(),
+                file: "src/config.rs".to_string(),
+                line: [1, 1],
+            }],
+        };
+        let out = format_api_diff(&diff, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "added (1):\n  + src/lib.rs[3-5] function connect\n\
+             changed (1):\n  ~ src/config.rs[1] Config: struct -> interface\n"
+        );
+        let out = format_api_diff(&ApiDiff::default(), OutputFormat::Text).unwrap();
+        assert_eq!(out, "no public API changes\n");
+    }
+
+    #[test]
+    fn test_format_symbol_diff_text() {
+        let delta = |change: &str, name: &str, kind: &str, visibility: Option<&str>| SymbolDelta {
+            change: change.to_string(),
+            file: "src/lib.rs".to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            visibility: visibility.map(String::from),
+            line: [3, 5],
+            old_line: (change == "moved").then_some([30, 32]),
+        };
+        let diff = SymbolDiff::new(vec![
+            delta("added", "connect", "function", Some("public")),
+            delta("removed", "helper", "function", None),
+            delta("moved", "Client", "struct", Some("public")),
+        ]);
+        assert_eq!(diff.by_visibility["public"].added, 1);
+        assert_eq!(diff.by_visibility["public"].moved, 1);
+        let out = format_symbol_diff(&diff, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "added (1):\n  + src/lib.rs[3-5] function connect (public)\n\
+             removed (1):\n  - src/lib.rs[3-5] function helper\n\
+             moved (1):\n  ~ src/lib.rs[3-5] struct Client (public) (was 30-32)\n\
+             by kind: function +1 -1 ~0, struct +0 -0 ~1\n\
+             by visibility: none +0 -1 ~0, public +1 -0 ~1\n"
+        );
+        let out = format_symbol_diff(&SymbolDiff::default(), OutputFormat::Text).unwrap();
+        assert_eq!(out, "no symbol changes\n");
+    }
+
+    #[test]
+    fn test_format_index_diffs_text() {
+        use crate::server::index_diff::ModifiedFile;
+        let diffs = vec![
+            IndexDiff {
+                project: String::new(),
+                added: vec!["src/new.rs".to_string()],
+                removed: vec![],
+                modified: vec![ModifiedFile {
+                    path: "src/lib.rs".to_string(),
+                    added_symbols: 2,
+                    removed_symbols: 1,
+                }],
+            },
+            IndexDiff {
+                project: "web".to_string(),
+                ..IndexDiff::default()
+            },
+        ];
+        let out = format_index_diffs(&diffs, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "(root): 1 added, 0 removed, 1 modified\n  + src/new.rs\n  ~ src/lib.rs (+2 -1 symbols)\n"
+        );
+        let out = format_index_diffs(&diffs[1..], OutputFormat::Text).unwrap();
+        assert_eq!(out, "index on disk is up to date\n");
+    }
+
+    #[test]
+    fn test_format_manifest_text() {
+        let manifest = IndexManifest {
+            version: "1.0".to_string(),
+            name: "codeix".to_string(),
+            root: ".".to_string(),
+            languages: vec!["python".to_string(), "rust".to_string()],
+        };
+        let out = format_manifest(&manifest, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "name            codeix\nversion         1.0\nroot            .\nlanguages       python, rust\n"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&format_manifest(&manifest, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["languages"][1], "rust");
+    }
+
+    #[test]
+    fn test_format_import_cycles_text() {
+        let cycles = vec![ImportCycle {
+            project: "web".to_string(),
+            files: vec!["src/a.ts".to_string(), "src/b.ts".to_string()],
+        }];
+        let out = format_import_cycles(&cycles, OutputFormat::Text).unwrap();
+        assert_eq!(out, "[web] cycle (2 files): src/a.ts, src/b.ts\n");
+        let out = format_import_cycles(&[], OutputFormat::Text).unwrap();
+        assert_eq!(out, "no import cycles found\n");
+    }
+
+    #[test]
+    fn test_format_grouped_search_results_text() {
+        let file = |project: &str| {
+            EnrichedSearchResult::File(FileEntry {
+                project: project.to_string(),
+                path: "src/lib.rs".to_string(),
+                lang: Some("rust".to_string()),
+                hash: String::new(),
+                lines: 10,
+                title: None,
+                description: None,
+                is_test: false,
+            })
+        };
+        let groups = vec![
+            SearchGroup {
+                project: "libs/b".to_string(),
+                results: vec![file("libs/b")],
+            },
+            SearchGroup {
+                project: String::new(),
+                results: vec![file("")],
+            },
+        ];
+        let out = format_grouped_search_results(&groups, 3, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "[libs/b] (1 results)\nsrc/lib.rs file (rust, 10 lines)\n\n\
+             [(root)] (1 results)\nsrc/lib.rs file (rust, 10 lines)\n\
+             (showing 2 of 3)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_explained_search_results() {
+        let results = vec![ExplainedSearchResult {
+            result: EnrichedSearchResult::File(FileEntry {
+                project: String::new(),
+                path: "src/cache.rs".to_string(),
+                lang: Some("rust".to_string()),
+                hash: String::new(),
+                lines: 10,
+                title: None,
+                description: None,
+                is_test: false,
+            }),
+            match_info: MatchInfo {
+                score: -2.5,
+                name_score: -2.0,
+                file_score: -1.0,
+                content_score: -0.5,
+                matched_column: "name".to_string(),
+                snippet: Some("a «cache»\nline".to_string()),
+            },
+        }];
+        let out = format_explained_search_results(&results, 1, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "src/cache.rs file (rust, 10 lines)\n\
+             \x20 match name score -2.50 (name -2.00, file -1.00, content -0.50)\n\
+             \x20 > a «cache» line\n"
+        );
+
+        let json = format_explained_search_results(&results, 1, OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value[0]["type"], "file");
+        assert_eq!(value[0]["path"], "src/cache.rs");
+        assert_eq!(value[0]["match"]["matched_column"], "name");
+    }
+
+    #[test]
+    fn test_format_texts_text() {
+        let texts = vec![TextEntry {
+            file: "src/a.rs".to_string(),
+            kind: "comment".to_string(),
+            line: [3, 4],
+            text: "TODO(bob): one\n  two".to_string(),
+            parent: None,
+            project: String::new(),
+        }];
+        let out = format_texts(&texts, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "src/a.rs[3-4] text comment\n  │ TODO(bob): one\n  │   two\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_references_with_definition() {
+        let reference = |line: u32| ReferenceEntry {
+            file: "src/main.rs".to_string(),
+            name: "parse".to_string(),
+            kind: "call".to_string(),
+            line: [line, line],
+            caller: Some("main".to_string()),
+            project: String::new(),
+        };
+        let refs = vec![
+            ReferenceWithSnippet {
+                reference: reference(4),
+                context: None,
+                def_file: Some("src/parser.rs".to_string()),
+                def_line: Some(12),
+                def_project: None,
+            },
+            ReferenceWithSnippet {
+                reference: reference(9),
+                context: None,
+                def_file: Some("lib.rs".to_string()),
+                def_line: Some(3),
+                def_project: Some("libs/core".to_string()),
+            },
+            ReferenceWithSnippet {
+                reference: reference(15),
+                context: None,
+                def_file: None,
+                def_line: None,
+                def_project: None,
+            },
+        ];
+        let out = format_references(&refs, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "src/main.rs[4] ref call main -> parse @ src/parser.rs[12]\n\
+             src/main.rs[9] ref call main -> parse @ libs/core:lib.rs[3]\n\
+             src/main.rs[15] ref call main -> parse\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&format_references(&refs, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["def_file"], "src/parser.rs");
+        assert_eq!(json[0]["def_line"], 12);
+        assert!(json[0].get("def_project").is_none());
+        assert!(json[2].get("def_file").is_none());
+    }
+
+    #[test]
+    fn test_format_source_snippet_text() {
+        let snippet = SourceSnippet {
+            file: "src/a.rs".to_string(),
+            project: String::new(),
+            line: [3, 4],
+            text: "fn a() {\n}".to_string(),
+            truncated: true,
+        };
+        let out = format_source_snippet(&snippet, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "src/a.rs[3-4]\nfn a() {\n}\n... (capped at 500 lines)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_definition_text() {
+        let entry = crate::index::format::SymbolEntry {
+            project: String::new(),
+            file: "app.py".to_string(),
+            name: "run".to_string(),
+            kind: "function".to_string(),
+            line: [3, 4],
+            parent: None,
+            sig: None,
+            tokens: None,
+            alias: None,
+            visibility: None,
+        };
+        let other = crate::index::format::SymbolEntry {
+            file: "cli.py".to_string(),
+            line: [9, 9],
+            ..entry.clone()
+        };
+        let definition = Definition {
+            symbol: SymbolOutput::from_entry(&entry, None),
+            kind: entry.kind.clone(),
+            score: 120,
+            source: SourceSnippet {
+                file: "app.py".to_string(),
+                project: String::new(),
+                line: [3, 4],
+                text: "def run():\n    pass".to_string(),
+                truncated: false,
+            },
+            docs: vec![TextEntry {
+                project: String::new(),
+                file: "app.py".to_string(),
+                kind: "docstring".to_string(),
+                line: [4, 4],
+                text: "Start the app.".to_string(),
+                parent: Some("run".to_string()),
+            }],
+            alternatives: vec![ScoredSymbol {
+                symbol: SymbolOutput::from_entry(&other, None),
+                score: 100,
+            }],
+        };
+        let out = format_definition(Some(&definition), OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "app.py[3-4] function run (score 120)\n  │ Start the app.\n\n\
+             def run():\n    pass\n\n\
+             alternatives:\ncli.py[9] symbol run (score 100)\n"
+        );
+
+        let json = format_definition(Some(&definition), OutputFormat::Json).unwrap();
+        assert!(json.contains("\"alternatives\""));
+        assert_eq!(format_definition(None, OutputFormat::Json).unwrap(), "null");
+        assert_eq!(
+            format_definition(None, OutputFormat::Text).unwrap(),
+            "no definition found\n"
+        );
+    }
+
+    #[test]
+    fn test_format_outline_text() {
+        let node =
+            |name: &str, kind: &str, line: [u32; 2], children: Vec<OutlineNode>| OutlineNode {
+                symbol: crate::index::format::SymbolEntry {
+                    project: String::new(),
+                    file: "m.py".to_string(),
+                    name: name.to_string(),
+                    kind: kind.to_string(),
+                    line,
+                    parent: None,
+                    sig: None,
+                    tokens: None,
+                    alias: None,
+                    visibility: None,
+                },
+                children,
+            };
+        let outline = vec![
+            node(
+                "Outer",
+                "class",
+                [1, 7],
+                vec![node("Outer.meth", "method", [5, 7], vec![])],
+            ),
+            node("top", "function", [8, 9], vec![]),
+        ];
+        let out = format_outline(&outline, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            "m.py[1-7] class Outer\n  m.py[5-7] method Outer.meth\nm.py[8-9] function top\n"
+        );
+
+        // JSON nests children and omits empty lists
+        let json: serde_json::Value =
+            serde_json::from_str(&format_outline(&outline, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["children"][0]["name"], "Outer.meth");
+        assert!(json[1].get("children").is_none());
+    }
+
+    #[test]
+    fn test_format_explore_tree_text() {
+        let node = |path: &str, langs: &[(&str, usize)], files: &[&str]| ExploreNode {
+            path: path.to_string(),
+            langs: langs.iter().map(|(l, c)| (l.to_string(), *c)).collect(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        };
+        let mut root = node(".", &[("markdown", 1)], &["README.md"]);
+        let mut src = node("src", &[("rust", 3)], &["lib.rs", "main.rs"]);
+        src.more_files = 1;
+        let mut server = node("src/server", &[("rust", 2), ("other", 1)], &["db.rs"]);
+        server.more_files = 2;
+        server.truncated = true;
+        src.children.push(server);
+        root.children.push(src);
+
+        let out = format_explore_tree(&root, OutputFormat::Text).unwrap();
+        assert_eq!(
+            out,
+            ". (1 markdown)\n\
+             ├── src/ (3 rust)\n\
+             │   ├── server/ (2 rust, 1 other)\n\
+             │   │   ├── db.rs\n\
+             │   │   ├── +2 files\n\
+             │   │   └── …\n\
+             │   ├── lib.rs\n\
+             │   ├── main.rs\n\
+             │   └── +1 files\n\
+             └── README.md\n\
+             \n2 directories, 7 files\n"
+        );
+    }
+
     #[test]
     fn test_output_format_default() {
         assert_eq!(OutputFormat::default(), OutputFormat::Json);
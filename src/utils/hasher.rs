@@ -4,15 +4,22 @@ use anyhow::Result;
 
 /// Compute the BLAKE3 hash of a file's contents, truncated to 64 bits,
 /// returned as a 16-character hex string.
+///
+/// The file is streamed through the hasher, so its contents are never
+/// buffered in full (cheap to call on large files just to detect changes).
 pub fn hash_file(path: &Path) -> Result<String> {
-    let contents = std::fs::read(path)?;
-    Ok(hash_bytes(&contents))
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(std::fs::File::open(path)?)?;
+    Ok(to_hex(hasher.finalize()))
 }
 
 /// Compute the BLAKE3 hash of byte content, truncated to 64 bits,
 /// returned as a 16-character hex string.
 pub fn hash_bytes(content: &[u8]) -> String {
-    let hash = blake3::hash(content);
+    to_hex(blake3::hash(content))
+}
+
+fn to_hex(hash: blake3::Hash) -> String {
     let bytes = hash.as_bytes();
     // Truncate to first 8 bytes (64 bits), hex-encode to 16 chars
     bytes[..8]
@@ -23,3 +30,19 @@ pub fn hash_bytes(content: &[u8]) -> String {
             s
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_file_matches_hash_bytes() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let content = "fn main() {}\n".repeat(10_000);
+        std::fs::write(tmp.path(), &content).unwrap();
+
+        let hash = hash_file(tmp.path()).unwrap();
+        assert_eq!(hash, hash_bytes(content.as_bytes()));
+        assert_eq!(hash.len(), 16);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod binary;
 pub mod format;
+pub mod fuzzy;
 pub mod hasher;
 pub mod manifest;
+pub mod test_files;
@@ -0,0 +1,70 @@
+/// Directories whose contents are tests, wherever they sit in the path.
+const TEST_DIRS: &[&str] = &["tests", "test", "__tests__", "spec"];
+
+/// Guess whether a project-relative path is a test file from its name alone.
+///
+/// Matches files under a `tests/`, `test/`, `__tests__/` or `spec/` directory
+/// and the usual per-language names: `*_test.go`, `test_*.py` / `*_test.py`,
+/// `*.test.ts` / `*.spec.ts` (and the other JS/TS extensions), `*_spec.rb`,
+/// and `FooTest.java` / `FooTests.cs` / `FooTest.kt`. Rust unit tests live in
+/// the file they test, so only `tests/` marks Rust code.
+pub fn is_test_path(path: &str) -> bool {
+    let mut components = path.split('/').filter(|c| !c.is_empty());
+    let Some(name) = components.next_back() else {
+        return false;
+    };
+    if components.any(|dir| TEST_DIRS.contains(&dir)) {
+        return true;
+    }
+
+    let (stem, ext) = name.rsplit_once('.').unwrap_or((name, ""));
+    match ext {
+        "go" => stem.ends_with("_test"),
+        "py" => stem.starts_with("test_") || stem.ends_with("_test"),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+            stem.ends_with(".test") || stem.ends_with(".spec")
+        }
+        "rb" => stem.ends_with("_spec") || stem.ends_with("_test"),
+        "java" | "kt" | "cs" => {
+            stem.len() > 4 && (stem.ends_with("Test") || stem.ends_with("Tests"))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_test_path() {
+        for path in [
+            "tests/integration.rs",
+            "crates/core/tests/fixtures/a.json",
+            "src/__tests__/App.jsx",
+            "spec/models/user_spec.rb",
+            "pkg/server/handler_test.go",
+            "test_parser.py",
+            "app/models_test.py",
+            "src/api.spec.ts",
+            "src/api.test.tsx",
+            "src/main/java/com/acme/UserServiceTest.java",
+            "Acme.Tests/ParserTests.cs",
+        ] {
+            assert!(is_test_path(path), "{path}");
+        }
+        for path in [
+            "src/lib.rs",
+            "src/testing.rs",
+            "latest/notes.md",
+            "pkg/server/handler.go",
+            "contest.py",
+            "src/spec.ts",
+            "src/Test.java",
+            "attestation/Attest.java",
+            "",
+        ] {
+            assert!(!is_test_path(path), "{path}");
+        }
+    }
+}